@@ -22,7 +22,7 @@ trap cleanup EXIT INT TERM
 # Function to start Rust server
 start_rust_server() {
     echo "Starting Rust simulation engine..."
-    /usr/local/bin/league-simulator-rust --api &
+    /usr/local/bin/league-simulator-rust serve &
     RUST_PID=$!
     
     echo "Waiting for Rust server to start (PID: $RUST_PID)..."
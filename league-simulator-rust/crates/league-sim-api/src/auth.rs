@@ -0,0 +1,336 @@
+// Optional API key authentication for deployments beyond the cluster. Keys
+// come from the TOML file named by API_KEYS_FILE or from the API_KEYS
+// variable; without either, every request is allowed as before. Each key has
+// scopes (simulate, batch, admin) and optionally a daily iteration budget.
+// The budget is charged with the iterations a request asks for (the
+// configured default where it leaves them unset) before it runs. GraphQL
+// queries and session events, whose runs only their handlers know, are
+// charged by those handlers for each run instead.
+
+use crate::error::ApiError;
+use crate::AppState;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// /simulate and its variants, sessions and GraphQL
+    Simulate,
+    /// /simulate/batch and /jobs
+    Batch,
+    /// Everything
+    Admin,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKey {
+    pub key: String,
+    /// Label for logs; defaults to the key's position in the file
+    #[serde(default)]
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// Monte Carlo iterations per UTC day; unlimited if unset
+    pub daily_iterations: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeysFile {
+    keys: Vec<ApiKey>,
+}
+
+/// Iterations used by one key on one day.
+#[derive(Default)]
+struct Usage {
+    day: u64,
+    iterations: u64,
+}
+
+impl Scope {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Simulate => "simulate",
+            Self::Batch => "batch",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Simulate, Self::Batch, Self::Admin]
+            .into_iter()
+            .find(|scope| scope.name() == s)
+            .ok_or_else(|| format!("unknown scope '{}'", s))
+    }
+}
+
+/// The configured keys and their budget usage.
+#[derive(Clone)]
+pub struct ApiKeys {
+    keys: Arc<HashMap<String, ApiKey>>,
+    usage: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl ApiKeys {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        let keys = keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut key)| {
+                if key.name.is_empty() {
+                    key.name = format!("key {}", i);
+                }
+                (key.key.clone(), key)
+            })
+            .collect();
+        Self {
+            keys: Arc::new(keys),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Whether `key` is one of the configured keys.
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// Parse a keys file: `[[keys]]` tables with `key`, `scopes` and
+    /// optional `name` and `daily_iterations`.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let file: KeysFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        Ok(Self::new(file.keys))
+    }
+
+    /// Parse `key:scope+scope,key:scope`, e.g. `abc:simulate+batch,xyz:admin`.
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        let keys = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .enumerate()
+            .map(|(i, entry)| {
+                let (key, scopes) = entry
+                    .split_once(':')
+                    .ok_or_else(|| format!("API key entry {} has no scopes", i))?;
+                let scopes = scopes
+                    .split('+')
+                    .map(|s| s.trim().parse())
+                    .collect::<Result<_, String>>()
+                    .map_err(|e| format!("API key entry {}: {}", i, e))?;
+                Ok(ApiKey {
+                    key: key.trim().to_string(),
+                    name: String::new(),
+                    scopes,
+                    daily_iterations: None,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self::new(keys))
+    }
+
+    /// Keys from API_KEYS_FILE or API_KEYS; None (authentication off) if
+    /// neither is set. An unreadable configuration panics at startup rather
+    /// than leaving the service open.
+    pub fn from_env() -> Option<Self> {
+        let keys = if let Ok(path) = std::env::var("API_KEYS_FILE") {
+            std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Self::from_toml(&text))
+                .map_err(|e| format!("API_KEYS_FILE {}: {}", path, e))
+        } else if let Ok(spec) = std::env::var("API_KEYS") {
+            Self::from_spec(&spec).map_err(|e| format!("API_KEYS: {}", e))
+        } else {
+            return None;
+        };
+        Some(keys.unwrap_or_else(|e| panic!("{}", e)))
+    }
+
+    /// Add `iterations` to today's usage of `key`, unless that exceeds its
+    /// budget; then return the seconds until the budget resets.
+    fn charge(&self, key: &ApiKey, iterations: u64) -> Result<(), u64> {
+        let Some(budget) = key.daily_iterations else {
+            return Ok(());
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let today = now / SECONDS_PER_DAY;
+        let mut usage = self.usage.lock().unwrap();
+        let used = usage.entry(key.key.clone()).or_default();
+        if used.day != today {
+            *used = Usage {
+                day: today,
+                iterations: 0,
+            };
+        }
+        if used.iterations + iterations > budget {
+            return Err((today + 1) * SECONDS_PER_DAY - now);
+        }
+        used.iterations += iterations;
+        Ok(())
+    }
+}
+
+/// A budgeted key, passed to the handlers that charge it per run.
+#[derive(Clone)]
+pub struct Budget {
+    keys: ApiKeys,
+    key: ApiKey,
+}
+
+impl Budget {
+    /// Charge a run of `iterations` to the key.
+    pub(crate) fn charge(&self, iterations: usize) -> Result<(), ApiError> {
+        self.keys
+            .charge(&self.key, iterations as u64)
+            .map_err(|retry_after| ApiError::BudgetExhausted { retry_after })
+    }
+}
+
+/// Scope a route needs; None for public routes.
+fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    let public = path == "/health"
+        || path == "/metrics"
+        || path == "/openapi.json"
+        || path.starts_with("/swagger-ui")
+        || (path == "/graphql" && method == Method::GET);
+    if public {
+        None
+    } else if path == "/simulate/batch" || path.starts_with("/jobs") {
+        Some(Scope::Batch)
+    } else {
+        Some(Scope::Simulate)
+    }
+}
+
+/// Whether the handler of `path` charges the budget itself: a GraphQL query
+/// may run several simulations and a session event reruns the session's.
+fn charged_by_handler(path: &str) -> bool {
+    path == "/graphql" || (path.starts_with("/sessions/") && path.ends_with("/events"))
+}
+
+/// Iterations a simulation request body asks for: the sum over the leagues
+/// of a batch, or the body's own `iterations`. Where unset, `default`, or
+/// `open_ended` for requests with a `time_budget_ms` or `target_se`, which
+/// may run up to that many. `awards`, `expected_table` and
+/// `match_importance` each add another pass of as many iterations, and
+/// `return_samples` one of that many, capped at the iterations.
+fn requested_iterations(body: &[u8], default: u64, open_ended: u64) -> u64 {
+    let iterations = |request: &serde_json::Value| {
+        let stops_early = ["time_budget_ms", "target_se"]
+            .iter()
+            .any(|field| request.get(field).is_some_and(|v| !v.is_null()));
+        let iterations = request
+            .get("iterations")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(if stops_early { open_ended } else { default });
+        let passes = ["awards", "expected_table", "match_importance"]
+            .iter()
+            .filter(|field| request.get(**field).and_then(|v| v.as_bool()) == Some(true))
+            .count() as u64;
+        let samples = request
+            .get("return_samples")
+            .and_then(|v| v.as_u64())
+            .map_or(0, |n| n.min(iterations));
+        iterations
+            .saturating_mul(1 + passes)
+            .saturating_add(samples)
+    };
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(value) => match value.get("leagues").and_then(|l| l.as_array()) {
+            Some(leagues) => leagues
+                .iter()
+                .map(|league| iterations(&league["request"]))
+                .fold(0, u64::saturating_add),
+            None => iterations(&value),
+        },
+        Err(_) => default,
+    }
+}
+
+/// The API key a request presents, from X-Api-Key or a bearer token.
+pub(crate) fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .or_else(|| headers.get(header::AUTHORIZATION))
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_start_matches("Bearer ").trim())
+}
+
+/// Middleware checking the caller's key, scope and budget when
+/// `state.auth` is configured.
+pub async fn authenticate(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(keys) = &state.auth else {
+        return next.run(request).await;
+    };
+    let path = crate::unversioned(request.uri().path());
+    let Some(scope) = required_scope(request.method(), path) else {
+        return next.run(request).await;
+    };
+
+    let Some(key) = presented_key(request.headers()).and_then(|k| keys.keys.get(k)) else {
+        return ApiError::Unauthorized.into_response();
+    };
+    if !key.scopes.contains(&scope) && !key.scopes.contains(&Scope::Admin) {
+        return ApiError::Forbidden(format!(
+            "API key '{}' lacks the {} scope",
+            key.name,
+            scope.name()
+        ))
+        .into_response();
+    }
+    // Imports and rating maintenance run no simulation.
+    let simulates = request.method() == Method::POST
+        && !path.starts_with("/import")
+        && !path.starts_with("/elo");
+    if key.daily_iterations.is_none() || !simulates {
+        return next.run(request).await;
+    }
+    if charged_by_handler(path) {
+        let budget = Budget {
+            keys: keys.clone(),
+            key: key.clone(),
+        };
+        request.extensions_mut().insert(budget);
+        return next.run(request).await;
+    }
+
+    // Budgeted keys: read the body to find the requested iterations.
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, crate::MAX_BODY_BYTES).await else {
+        return ApiError::InvalidRequest("unreadable or oversized request body".to_string())
+            .into_response();
+    };
+    let config = state.config.current();
+    let requested = requested_iterations(
+        &bytes,
+        config.simulation.iterations as u64,
+        config.limits.max_iterations as u64,
+    );
+    if let Err(retry_after) = keys.charge(key, requested) {
+        return ApiError::BudgetExhausted { retry_after }.into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
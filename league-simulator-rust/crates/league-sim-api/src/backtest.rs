@@ -0,0 +1,173 @@
+// Forecast evaluation: replays historical seasons matchday by matchday and
+// scores the engine's pre-match probabilities against what happened, so a
+// change of parameters can be judged on past results before it goes live.
+
+use crate::elo::{check_results, index_ratings, PlayedResult, TeamRating};
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::{telemetry, AppState};
+use axum::{extract::State, Json};
+use league_sim_core::{
+    backtest, BacktestReport, Match, RatingSystemKind, Season, SimulationParams,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct BacktestRequest {
+    /// Historical seasons, each replayed from its starting ratings
+    seasons: Vec<BacktestSeason>,
+
+    /// ELO modification factor of the replay (default: server setting)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points (default: server setting)
+    home_advantage: Option<f64>,
+
+    /// Slope of the goal model (default: server setting)
+    tore_slope: Option<f64>,
+
+    /// Intercept of the goal model (default: server setting)
+    tore_intercept: Option<f64>,
+
+    /// Rating system updating the ratings between matchdays
+    /// (default: "elo")
+    rating_system: Option<RatingSystemKind>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BacktestSeason {
+    /// Rating of every team before the season's first result
+    ratings: Vec<TeamRating>,
+
+    /// Played matches; with a matchday on every one they are forecast a
+    /// matchday at a time, otherwise one by one in the order given
+    results: Vec<BacktestResult>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BacktestResult {
+    #[serde(flatten)]
+    result: PlayedResult,
+
+    /// Matchday of the result, 1-based (optional)
+    matchday: Option<u32>,
+}
+
+/// Score the engine's pre-match forecasts on historical seasons.
+///
+/// Before every matchday each match is given win/draw/loss probabilities
+/// from the current ratings; the matchday's results then update the
+/// ratings. The answer reports the Brier score and log loss over all
+/// matches and per season, and a reliability curve of forecast against
+/// observed frequencies. Lower scores are better.
+#[utoipa::path(
+    post,
+    path = "/backtest",
+    tag = "fit",
+    request_body = BacktestRequest,
+    responses(
+        (status = 200, description = "Forecast scores and reliability curve", body = BacktestReport),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or unknown team", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn run_backtest(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<BacktestRequest>,
+) -> Result<Json<BacktestReport>, ApiError> {
+    let config = state.config.current();
+    let limits = &config.limits;
+    let results: usize = payload.seasons.iter().map(|s| s.results.len()).sum();
+    if results > limits.max_matches {
+        return Err(ApiError::limit(
+            "seasons",
+            format!(
+                "{} results exceed the limit of {}",
+                results, limits.max_matches
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    if payload.seasons.is_empty() {
+        errors.push(FieldError {
+            field: "seasons".into(),
+            message: "at least one season is required".into(),
+        });
+    }
+    let mut seasons = Vec::with_capacity(payload.seasons.len());
+    for (i, season) in payload.seasons.iter().enumerate() {
+        let path = format!("seasons[{}]", i);
+        if season.ratings.len() > limits.max_teams {
+            return Err(ApiError::limit(
+                format!("{}.ratings", path),
+                format!(
+                    "{} teams exceed the limit of {}",
+                    season.ratings.len(),
+                    limits.max_teams
+                ),
+            ));
+        }
+        let index = index_ratings(&season.ratings, &format!("{}.ratings", path), &mut errors);
+        check_results(
+            season.results.iter().map(|r| &r.result),
+            &format!("{}.results", path),
+            &index,
+            &mut errors,
+        );
+        if !errors.is_empty() {
+            continue;
+        }
+        seasons.push(Season {
+            matches: season
+                .results
+                .iter()
+                .map(|r| Match {
+                    team_home: index[r.result.home.as_str()],
+                    team_away: index[r.result.away.as_str()],
+                    goals_home: Some(r.result.goals_home),
+                    goals_away: Some(r.result.goals_away),
+                    matchday: r.matchday,
+                    competition: None,
+                    weight: None,
+                })
+                .collect(),
+            team_elos: season.ratings.iter().map(|r| r.elo).collect(),
+            number_teams: season.ratings.len(),
+        });
+    }
+    for (name, value) in [
+        ("mod_factor", payload.mod_factor),
+        ("home_advantage", payload.home_advantage),
+        ("tore_slope", payload.tore_slope),
+        ("tore_intercept", payload.tore_intercept),
+    ] {
+        if let Some(value) = value.filter(|v| !v.is_finite()) {
+            errors.push(FieldError {
+                field: name.into(),
+                message: format!("{} must be a finite number, got {}", name, value),
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let simulation = &config.simulation;
+    let params = SimulationParams {
+        mod_factor: payload.mod_factor.unwrap_or(simulation.mod_factor),
+        home_advantage: payload.home_advantage.unwrap_or(simulation.home_advantage),
+        tore_slope: payload.tore_slope.unwrap_or(simulation.tore_slope),
+        tore_intercept: payload.tore_intercept.unwrap_or(simulation.tore_intercept),
+        rating_system: payload.rating_system.unwrap_or_default(),
+        ..Default::default()
+    };
+    let report = telemetry::spawn_blocking(move || backtest(&seasons, &params))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,124 @@
+// In-memory LRU cache of /simulate responses.
+// Requests are keyed by their canonical engine inputs (schedule, Elos,
+// parameters, team names, structure), so payloads that differ only in field
+// order or omitted defaults share an entry, and no two different inputs can.
+// Entries expire after a configurable TTL; the least recently used entry is
+// evicted when full.
+
+use crate::config::CacheConfig;
+use crate::handlers::SimulateResponse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use twox_hash::XxHash3_128;
+
+/// Canonical engine inputs of a request: equal keys give equal results.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey(Arc<str>);
+
+impl CacheKey {
+    pub(crate) fn new(canonical: String) -> Self {
+        Self(canonical.into())
+    }
+
+    /// XXH3-128 of the canonical inputs, for ETags: stable across restarts,
+    /// builds and instances, unlike the standard library's hasher.
+    pub(crate) fn digest(&self) -> u128 {
+        XxHash3_128::oneshot(self.0.as_bytes())
+    }
+}
+
+struct CacheEntry {
+    response: SimulateResponse,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Monotonic use counter; the entry with the smallest `last_used` is
+    /// the least recently used.
+    tick: u64,
+}
+
+/// Shared response cache. A capacity of 0 disables caching.
+#[derive(Clone)]
+pub struct ResultCache {
+    state: Arc<Mutex<CacheState>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl ResultCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState::default())),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Cache sized by the `[cache]` configuration.
+    pub fn from_config(config: &CacheConfig) -> Self {
+        Self::new(config.size, Duration::from_secs(config.ttl_seconds))
+    }
+
+    /// How long entries stay valid.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Cached response for `key`, if present and not expired.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<SimulateResponse> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        match state.entries.get_mut(key) {
+            Some(entry) if entry.inserted_at.elapsed() < self.ttl => {
+                entry.last_used = tick;
+                Some(entry.response.clone())
+            }
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: CacheKey, response: SimulateResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let ttl = self.ttl;
+            state
+                .entries
+                .retain(|_, entry| entry.inserted_at.elapsed() < ttl);
+            if state.entries.len() >= self.capacity {
+                let oldest = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    state.entries.remove(&oldest);
+                }
+            }
+        }
+
+        state.entries.insert(
+            key,
+            CacheEntry {
+                response,
+                inserted_at: Instant::now(),
+                last_used: tick,
+            },
+        );
+    }
+}
@@ -0,0 +1,454 @@
+// Configuration file (TOML, or YAML for `.yaml`/`.yml` paths): server
+// settings, simulation defaults, limits, cache and additional league
+// templates. Environment variables override the file. The file named by
+// LEAGUE_SIM_CONFIG is re-read on SIGHUP and whenever its modification time
+// changes, so parameter tweaks and new leagues take effect without
+// restarting long-running deployments; `[server]` and `[cache]` are only
+// read at startup. A file that fails to parse is reported and the previous
+// configuration stays in effect.
+
+use league_sim_core::structure::{templates, LeagueStructure};
+use league_sim_core::SimulationParams;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Default seconds between modification-time checks of the config file.
+const DEFAULT_WATCH_INTERVAL_SECONDS: u64 = 5;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    /// Read at startup only
+    pub server: ServerConfig,
+
+    pub simulation: SimulationDefaults,
+
+    pub limits: Limits,
+
+    /// Read at startup only
+    pub cache: CacheConfig,
+
+    /// Additional league templates; an entry replaces the built-in template
+    /// with the same id
+    pub leagues: Vec<LeagueStructure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    /// Origins allowed to call the API from a browser; `*` allows any.
+    /// Empty sends no CORS headers.
+    pub cors_origins: Vec<String>,
+    /// Responses smaller than this are sent uncompressed
+    pub compression_min_bytes: u16,
+    /// HTTPS on `port`; plain HTTP unless a certificate or ACME domains
+    /// are set
+    pub tls: TlsConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            cors_origins: Vec::new(),
+            compression_min_bytes: 1024,
+            tls: TlsConfig::default(),
+        }
+    }
+}
+
+/// Certificate for built-in HTTPS (the `tls` feature): PEM files, or one
+/// obtained from Let's Encrypt over TLS-ALPN-01, which needs the port
+/// reachable as 443 from the internet.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain; re-read with the key on SIGHUP
+    pub cert_path: Option<PathBuf>,
+    /// PEM private key
+    pub key_path: Option<PathBuf>,
+    /// Domains to request a certificate for over ACME
+    pub acme_domains: Vec<String>,
+    /// Contact address of the ACME account, e.g. "admin@example.org"
+    pub acme_contact: Option<String>,
+    /// Directory keeping the ACME account and certificate across restarts
+    pub acme_cache_dir: Option<PathBuf>,
+    /// Use the Let's Encrypt staging directory, for trying a setup out
+    pub acme_staging: bool,
+}
+
+impl TlsConfig {
+    /// Whether HTTPS is configured.
+    pub fn enabled(&self) -> bool {
+        self.cert_path.is_some() || !self.acme_domains.is_empty()
+    }
+}
+
+/// Values used when a request leaves the field unset.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationDefaults {
+    pub iterations: usize,
+    pub mod_factor: f64,
+    pub home_advantage: f64,
+    /// Goal model: expected goals per Elo point of difference
+    pub tore_slope: f64,
+    /// Goal model: expected goals between equal teams
+    pub tore_intercept: f64,
+}
+
+impl Default for SimulationDefaults {
+    fn default() -> Self {
+        let params = SimulationParams::default();
+        Self {
+            iterations: 10000,
+            mod_factor: 20.0,
+            home_advantage: 65.0,
+            tore_slope: params.tore_slope,
+            tore_intercept: params.tore_intercept,
+        }
+    }
+}
+
+/// Server-side maxima, so a single client cannot monopolise the CPU.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Monte Carlo iterations per request (422 above)
+    pub max_iterations: usize,
+    /// Teams per league (413 above)
+    pub max_teams: usize,
+    /// Schedule rows per league (413 above)
+    pub max_matches: usize,
+    /// Leagues per batch or job (413 above)
+    pub max_batch_leagues: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100_000,
+            max_teams: 64,
+            max_matches: 5_000,
+            max_batch_leagues: 50,
+        }
+    }
+}
+
+/// Result cache of /simulate responses.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Cached responses; 0 disables caching
+    pub size: usize,
+    pub ttl_seconds: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            size: 128,
+            ttl_seconds: 300,
+        }
+    }
+}
+
+/// Environment variables overriding the configuration file.
+const ENV_OVERRIDES: &[&str] = &[
+    "PORT",
+    "CORS_ORIGINS",
+    "COMPRESSION_MIN_BYTES",
+    "TLS_CERT_PATH",
+    "TLS_KEY_PATH",
+    "ACME_DOMAINS",
+    "ACME_CONTACT",
+    "ACME_CACHE_DIR",
+    "DEFAULT_ITERATIONS",
+    "MAX_ITERATIONS",
+    "MAX_TEAMS",
+    "MAX_MATCHES",
+    "MAX_BATCH_LEAGUES",
+    "RESULT_CACHE_SIZE",
+    "RESULT_CACHE_TTL_SECONDS",
+];
+
+impl RuntimeConfig {
+    /// Parse and validate a TOML configuration.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse and validate a YAML configuration with the same layout.
+    pub fn from_yaml(text: &str) -> Result<Self, String> {
+        let config: Self = serde_yaml_ng::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The built-in defaults with the environment overrides applied.
+    pub fn from_env() -> Result<Self, String> {
+        let mut config = Self::default();
+        config.apply_env(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Override settings with the variables `var` returns (see
+    /// [`ENV_OVERRIDES`]); a value that does not parse is an error.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| format!("{}: invalid value '{}'", name, value))
+        }
+        fn list(value: &str) -> Vec<String> {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+        for &name in ENV_OVERRIDES {
+            let Some(value) = var(name) else {
+                continue;
+            };
+            match name {
+                "PORT" => self.server.port = parse(name, &value)?,
+                "CORS_ORIGINS" => self.server.cors_origins = list(&value),
+                "COMPRESSION_MIN_BYTES" => self.server.compression_min_bytes = parse(name, &value)?,
+                "TLS_CERT_PATH" => self.server.tls.cert_path = Some(value.into()),
+                "TLS_KEY_PATH" => self.server.tls.key_path = Some(value.into()),
+                "ACME_DOMAINS" => self.server.tls.acme_domains = list(&value),
+                "ACME_CONTACT" => self.server.tls.acme_contact = Some(value),
+                "ACME_CACHE_DIR" => self.server.tls.acme_cache_dir = Some(value.into()),
+                "DEFAULT_ITERATIONS" => self.simulation.iterations = parse(name, &value)?,
+                "MAX_ITERATIONS" => self.limits.max_iterations = parse(name, &value)?,
+                "MAX_TEAMS" => self.limits.max_teams = parse(name, &value)?,
+                "MAX_MATCHES" => self.limits.max_matches = parse(name, &value)?,
+                "MAX_BATCH_LEAGUES" => self.limits.max_batch_leagues = parse(name, &value)?,
+                "RESULT_CACHE_SIZE" => self.cache.size = parse(name, &value)?,
+                "RESULT_CACHE_TTL_SECONDS" => self.cache.ttl_seconds = parse(name, &value)?,
+                _ => unreachable!("every override is handled"),
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let config = self;
+        if config.simulation.iterations == 0 {
+            return Err("simulation.iterations must be at least 1".to_string());
+        }
+        if !(config.simulation.tore_slope.is_finite()
+            && config.simulation.tore_intercept.is_finite()
+            && config.simulation.tore_intercept > 0.0)
+        {
+            return Err(
+                "simulation.tore_slope must be finite and tore_intercept positive".to_string(),
+            );
+        }
+        let tls = &config.server.tls;
+        if tls.cert_path.is_some() != tls.key_path.is_some() {
+            return Err("server.tls: cert_path and key_path must be set together".to_string());
+        }
+        if tls.cert_path.is_some() && !tls.acme_domains.is_empty() {
+            return Err(
+                "server.tls: use either a certificate file or acme_domains, not both".to_string(),
+            );
+        }
+        if tls.enabled() && !cfg!(feature = "tls") {
+            return Err("server.tls: HTTPS needs a build with the `tls` feature".to_string());
+        }
+        let limits = &config.limits;
+        for (name, value) in [
+            ("max_iterations", limits.max_iterations),
+            ("max_teams", limits.max_teams),
+            ("max_matches", limits.max_matches),
+            ("max_batch_leagues", limits.max_batch_leagues),
+        ] {
+            if value == 0 {
+                return Err(format!("limits.{} must be at least 1", name));
+            }
+        }
+        for league in &config.leagues {
+            if league.number_teams < 2 {
+                return Err(format!("league '{}': needs at least 2 teams", league.id));
+            }
+            for zone in &league.zones {
+                if zone.first == 0 || zone.first > zone.last || zone.last > league.number_teams {
+                    return Err(format!(
+                        "league '{}': zone '{}' must lie within positions 1..={}",
+                        league.id, zone.name, league.number_teams
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Built-in templates followed by the configured leagues, configured
+    /// entries replacing built-ins with the same id.
+    pub fn templates(&self) -> Vec<LeagueStructure> {
+        let mut all: Vec<LeagueStructure> = templates()
+            .into_iter()
+            .filter(|t| self.leagues.iter().all(|l| l.id != t.id))
+            .collect();
+        all.extend(self.leagues.iter().cloned());
+        all
+    }
+
+    /// Template by id, configured leagues first.
+    pub fn template(&self, id: &str) -> Option<LeagueStructure> {
+        self.templates().into_iter().find(|t| t.id == id)
+    }
+}
+
+/// Shared, reloadable handle to the current configuration. Readers take a
+/// snapshot with `current()`, so a reload never changes the settings of a
+/// request halfway through.
+#[derive(Clone)]
+pub struct ConfigHandle {
+    path: Option<PathBuf>,
+    current: Arc<RwLock<Arc<RuntimeConfig>>>,
+}
+
+impl ConfigHandle {
+    /// Fixed configuration without a backing file.
+    pub fn new(config: RuntimeConfig) -> Self {
+        Self {
+            path: None,
+            current: Arc::new(RwLock::new(Arc::new(config))),
+        }
+    }
+
+    /// Load the configuration from `path`; later reloads re-read it.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let config = read_config(&path)?;
+        Ok(Self {
+            path: Some(path),
+            current: Arc::new(RwLock::new(Arc::new(config))),
+        })
+    }
+
+    /// Load the file named by LEAGUE_SIM_CONFIG, or use the defaults if the
+    /// variable is unset. Environment overrides apply either way; an
+    /// unreadable or invalid file, like an invalid override, panics at
+    /// startup.
+    pub fn from_env() -> Self {
+        Self::from_path(std::env::var("LEAGUE_SIM_CONFIG").ok())
+    }
+
+    /// [`ConfigHandle::from_env`] with the file at `path`, if any.
+    pub(crate) fn from_path(path: Option<String>) -> Self {
+        match path {
+            Some(path) => {
+                Self::load(&path).unwrap_or_else(|e| panic!("configuration file {}: {}", path, e))
+            }
+            None => Self::new(
+                RuntimeConfig::from_env().unwrap_or_else(|e| panic!("configuration: {}", e)),
+            ),
+        }
+    }
+
+    pub fn current(&self) -> Arc<RuntimeConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Re-read the backing file. Returns false without a file; on error the
+    /// current configuration is kept.
+    pub fn reload(&self) -> Result<bool, String> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        let config = read_config(path)?;
+        *self.current.write().unwrap() = Arc::new(config);
+        Ok(true)
+    }
+
+    /// Reload on SIGHUP and whenever the file's modification time changes,
+    /// checked every CONFIG_WATCH_INTERVAL_SECONDS (default 5; 0 disables
+    /// polling). Does nothing without a backing file. Must be called from
+    /// within a Tokio runtime.
+    pub fn watch(&self) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+
+        #[cfg(unix)]
+        {
+            let handle = self.clone();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+                let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+                    tracing::warn!("config: cannot listen for SIGHUP");
+                    return;
+                };
+                while hangup.recv().await.is_some() {
+                    handle.reload_and_report("SIGHUP");
+                }
+            });
+        }
+
+        let interval = std::env::var("CONFIG_WATCH_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_WATCH_INTERVAL_SECONDS);
+        if interval == 0 {
+            return;
+        }
+        let handle = self.clone();
+        tokio::spawn(async move {
+            let mut last = modified(&path);
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            loop {
+                ticker.tick().await;
+                let now = modified(&path);
+                if now != last {
+                    last = now;
+                    handle.reload_and_report("file change");
+                }
+            }
+        });
+    }
+
+    fn reload_and_report(&self, trigger: &str) {
+        let path = self.path.as_deref().unwrap_or(Path::new("")).display();
+        match self.reload() {
+            Ok(_) => tracing::info!(path = %path, trigger, "config reloaded"),
+            Err(e) => tracing::warn!(
+                path = %path,
+                error = %e,
+                "invalid config, keeping the previous one"
+            ),
+        }
+    }
+}
+
+/// Parse the file at `path` by its extension and apply the environment
+/// overrides.
+fn read_config(path: &Path) -> Result<RuntimeConfig, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let mut config = if yaml {
+        RuntimeConfig::from_yaml(&text)?
+    } else {
+        RuntimeConfig::from_toml(&text)?
+    };
+    config.apply_env(|name| std::env::var(name).ok())?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,479 @@
+// Coordinator/worker mode for spreading one simulation over several
+// instances. With SIMULATION_WORKERS set, /simulate splits its iterations
+// into one contiguous range per worker, POSTs each range to the worker's
+// /simulate/shard, and adds up the position counts they return. Every
+// iteration draws from the stream of (seed, iteration), so the merged
+// counts are exactly those of a single-node run with the same seed. A
+// shard whose worker fails or times out is simulated locally instead.
+
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::handlers::{SimulateResponse, SimulationJob};
+use crate::telemetry;
+use crate::AppState;
+use axum::{extract::State, Json};
+use league_sim_core::{
+    count_iteration_range, probabilities_from_counts, Season, SimulationParams, Solver,
+    TeamStrength, TiebreakerRule,
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Per-shard request timeout.
+const SHARD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// One range of iterations of a seeded run, as sent to a worker.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ShardRequest {
+    #[schema(value_type = Object)]
+    pub season: Season,
+    /// Engine parameters of the whole run; `seed` is required and
+    /// `iterations` is ignored
+    #[schema(value_type = Object)]
+    pub params: SimulationParams,
+    /// First iteration of the range
+    pub first_iteration: u64,
+    /// Iterations in the range
+    pub iterations: usize,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ShardResponse {
+    /// How often each team finished in each position:
+    /// `counts[team_id][position - 1]`
+    pub counts: Vec<Vec<usize>>,
+    pub first_iteration: u64,
+    pub iterations: usize,
+}
+
+impl ShardResponse {
+    /// Whether these counts can be the answer to `request`: a full square
+    /// of positions per team, each team placed once per iteration.
+    fn answers(&self, request: &ShardRequest) -> bool {
+        let n = request.season.number_teams;
+        self.counts.len() == n
+            && self.counts.iter().all(|row| {
+                let placed = row.iter().try_fold(0usize, |sum, &c| sum.checked_add(c));
+                row.len() == n && placed == Some(request.iterations)
+            })
+    }
+}
+
+/// Simulate a range of iterations for a coordinator.
+///
+/// Meant for instances running as workers. The route is reachable like any
+/// other, so the engine inputs get the checks /simulate applies to its
+/// request before they reach the simulation.
+#[utoipa::path(
+    post,
+    path = "/simulate/shard",
+    tag = "simulation",
+    request_body = ShardRequest,
+    responses(
+        (status = 200, description = "Position counts of the range", body = ShardResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_shard(
+    State(state): State<AppState>,
+    ApiJson(shard): ApiJson<ShardRequest>,
+) -> Result<Json<ShardResponse>, ApiError> {
+    validate_shard(&shard, &state.config.current().limits)?;
+    Ok(Json(count_shard(shard).await?))
+}
+
+/// Simulate the range of `shard` off the async workers.
+async fn count_shard(shard: ShardRequest) -> Result<ShardResponse, ApiError> {
+    telemetry::spawn_blocking(move || ShardResponse {
+        counts: count_iteration_range(
+            &shard.season,
+            &shard.params,
+            shard.params.seed.unwrap_or_default(),
+            shard.first_iteration,
+            shard.iterations,
+        ),
+        first_iteration: shard.first_iteration,
+        iterations: shard.iterations,
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+fn validate_shard(shard: &ShardRequest, limits: &crate::config::Limits) -> Result<(), ApiError> {
+    let season = &shard.season;
+    if season.number_teams > limits.max_teams {
+        return Err(ApiError::limit(
+            "season.number_teams",
+            format!(
+                "{} teams exceed the limit of {}",
+                season.number_teams, limits.max_teams
+            ),
+        ));
+    }
+    if season.matches.len() > limits.max_matches {
+        return Err(ApiError::limit(
+            "season.matches",
+            format!(
+                "{} matches exceed the limit of {}",
+                season.matches.len(),
+                limits.max_matches
+            ),
+        ));
+    }
+    if shard.iterations == 0 || shard.iterations > limits.max_iterations {
+        return Err(ApiError::field(
+            "iterations",
+            format!(
+                "iterations must be between 1 and {}, got {}",
+                limits.max_iterations, shard.iterations
+            ),
+        ));
+    }
+    if shard.params.seed.is_none() {
+        return Err(ApiError::field(
+            "params.seed",
+            "shards need the seed of their run",
+        ));
+    }
+    if season.team_elos.len() != season.number_teams {
+        return Err(ApiError::field(
+            "season.team_elos",
+            format!(
+                "expected {} ratings, got {}",
+                season.number_teams,
+                season.team_elos.len()
+            ),
+        ));
+    }
+    let errors = engine_input_errors(season, &shard.params);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidFields(errors))
+    }
+}
+
+/// Problems of a raw season and its parameters that would produce wrong
+/// tables or crash the simulation, as `validate_request` finds them in a
+/// /simulate body.
+fn engine_input_errors(season: &Season, params: &SimulationParams) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut fail = |field: String, message: String| errors.push(FieldError { field, message });
+    let n = season.number_teams;
+    let finite = |v: f64| v.is_finite();
+    let non_negative = |v: f64| v.is_finite() && v >= 0.0;
+    let positive = |v: f64| v.is_finite() && v > 0.0;
+    let in_strength_range = |v: f64| v > 0.0 && v <= TeamStrength::MAX;
+
+    if n == 0 {
+        fail("season.number_teams".into(), "a season needs teams".into());
+    }
+    if let Some(i) = season.team_elos.iter().position(|&elo| !finite(elo)) {
+        fail(
+            format!("season.team_elos[{}]", i),
+            format!("season.team_elos[{}] must be a finite number", i),
+        );
+    }
+    for (i, m) in season.matches.iter().enumerate() {
+        let field = || format!("season.matches[{}]", i);
+        if m.team_home >= n || m.team_away >= n {
+            fail(field(), format!("team index out of range 0..{}", n));
+        } else if m.team_home == m.team_away {
+            fail(field(), "a team cannot play itself".into());
+        }
+        match (m.goals_home, m.goals_away) {
+            (Some(home), Some(away)) if home < 0 || away < 0 => {
+                fail(field(), "goals must not be negative".into())
+            }
+            (Some(_), None) | (None, Some(_)) => fail(
+                field(),
+                "goals_home and goals_away must both be set or both be null".into(),
+            ),
+            _ => {}
+        }
+        if m.weight.is_some_and(|w| !non_negative(w)) {
+            fail(field(), "weight must be a non-negative number".into());
+        }
+    }
+
+    for (name, value) in [
+        ("mod_factor", params.mod_factor),
+        ("home_advantage", params.home_advantage),
+        ("tore_slope", params.tore_slope),
+        ("tore_intercept", params.tore_intercept),
+    ] {
+        if !finite(value) {
+            fail(
+                format!("params.{}", name),
+                format!("params.{} must be a finite number", name),
+            );
+        }
+    }
+    let mut per_team = |name: &str, len: Option<usize>| {
+        if let Some(len) = len.filter(|&len| len != n) {
+            fail(
+                format!("params.{}", name),
+                format!(
+                    "params.{} has length {}, expected {} (one per team)",
+                    name, len, n
+                ),
+            );
+        }
+    };
+    per_team("adj_points", params.adj_points.as_ref().map(Vec::len));
+    per_team("adj_goals", params.adj_goals.as_ref().map(Vec::len));
+    per_team(
+        "adj_goals_against",
+        params.adj_goals_against.as_ref().map(Vec::len),
+    );
+    per_team("adj_goal_diff", params.adj_goal_diff.as_ref().map(Vec::len));
+    per_team("adj_played", params.adj_played.as_ref().map(Vec::len));
+    per_team("fair_play", params.fair_play.as_ref().map(Vec::len));
+    per_team(
+        "glicko.deviations",
+        params.glicko.deviations.as_ref().map(Vec::len),
+    );
+    per_team(
+        "team_strengths",
+        params.team_strengths.as_ref().map(Vec::len),
+    );
+    per_team(
+        "strength_priors",
+        params.strength_priors.as_ref().map(Vec::len),
+    );
+    per_team(
+        "poisson_strengths.attack",
+        params.poisson_strengths.as_ref().map(|p| p.attack.len()),
+    );
+    per_team(
+        "poisson_strengths.defense",
+        params.poisson_strengths.as_ref().map(|p| p.defense.len()),
+    );
+
+    if let Some(weights) = &params.mod_factor_weights {
+        if weights.len() != season.matches.len() {
+            fail(
+                "params.mod_factor_weights".into(),
+                format!(
+                    "params.mod_factor_weights has length {}, expected {} (one per match)",
+                    weights.len(),
+                    season.matches.len()
+                ),
+            );
+        }
+        if weights.iter().any(|&w| !non_negative(w)) {
+            fail(
+                "params.mod_factor_weights".into(),
+                "params.mod_factor_weights must be non-negative numbers".into(),
+            );
+        }
+    }
+    if let Some((name, _)) = params
+        .competition_weights
+        .iter()
+        .flatten()
+        .find(|(_, &w)| !non_negative(w))
+    {
+        fail(
+            format!("params.competition_weights.{}", name),
+            format!(
+                "params.competition_weights.{} must be a non-negative number",
+                name
+            ),
+        );
+    }
+
+    let glicko = &params.glicko;
+    let glicko_values = [glicko.deviation, glicko.volatility, glicko.tau];
+    if !glicko_values
+        .iter()
+        .chain(glicko.deviations.iter().flatten())
+        .all(|&v| positive(v))
+    {
+        fail(
+            "params.glicko".into(),
+            "params.glicko: deviations, volatility and tau must be positive numbers".into(),
+        );
+    }
+    if params
+        .team_strengths
+        .iter()
+        .flatten()
+        .any(|s| !(in_strength_range(s.attack) && in_strength_range(s.defense)))
+    {
+        fail(
+            "params.team_strengths".into(),
+            format!(
+                "params.team_strengths: attack and defense must be positive numbers up to {}",
+                TeamStrength::MAX
+            ),
+        );
+    }
+    if let Some(poisson) = &params.poisson_strengths {
+        let coefficients = [poisson.intercept, poisson.home_advantage];
+        if !coefficients
+            .iter()
+            .chain(&poisson.attack)
+            .chain(&poisson.defense)
+            .all(|&v| finite(v))
+        {
+            fail(
+                "params.poisson_strengths".into(),
+                "params.poisson_strengths must be finite numbers".into(),
+            );
+        }
+    }
+    if params.strength_priors.iter().flatten().any(|&v| !finite(v)) {
+        fail(
+            "params.strength_priors".into(),
+            "params.strength_priors must be finite numbers".into(),
+        );
+    }
+    for (name, weight) in [
+        ("strength_weight", params.strength_weight),
+        ("prior_weight", params.prior_weight),
+    ] {
+        if !(0.0..=1.0).contains(&weight) {
+            fail(
+                format!("params.{}", name),
+                format!("params.{} must be between 0 and 1, got {}", name, weight),
+            );
+        }
+    }
+    if params.tiebreakers.first() != Some(&TiebreakerRule::Points) {
+        fail(
+            "params.tiebreakers".into(),
+            "params.tiebreakers must start with points".into(),
+        );
+    }
+    errors
+}
+
+/// Worker instances a coordinator shards its simulations across.
+#[derive(Clone, Default)]
+pub struct Workers {
+    /// Base URLs, e.g. `http://worker-0:8080`
+    urls: Vec<String>,
+    /// Sent as `x-api-key` when the workers require one
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Workers {
+    pub fn new(urls: Vec<String>, api_key: Option<String>) -> Self {
+        Self {
+            urls: urls
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Workers from SIMULATION_WORKERS (comma-separated base URLs) and
+    /// SIMULATION_WORKER_API_KEY. Unset runs every simulation locally.
+    pub fn from_env() -> Self {
+        let urls = std::env::var("SIMULATION_WORKERS")
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self::new(urls, std::env::var("SIMULATION_WORKER_API_KEY").ok())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Run `job` across the workers and build its response. Scenario
+    /// mixtures, hybrid runs and jobs that stop early are not sharded and
+    /// run locally.
+    pub(crate) async fn simulate(
+        &self,
+        job: SimulationJob,
+        start: Instant,
+    ) -> Result<SimulateResponse, ApiError> {
+        if self.is_empty()
+            || job.scenarios.is_some()
+            || job.solver == Solver::Hybrid
+            || job.stopping_rule().is_some()
+        {
+            return telemetry::spawn_blocking(move || job.simulate(start))
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()));
+        }
+
+        // Every job carries a master seed, which all shards share.
+        let params = job.params.clone();
+        let total = params.iterations;
+        let shards = self.urls.len().min(total);
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, url) in self.urls.iter().take(shards).enumerate() {
+            let first = total * index / shards;
+            let request = ShardRequest {
+                season: job.season.clone(),
+                params: params.clone(),
+                first_iteration: first as u64,
+                iterations: total * (index + 1) / shards - first,
+            };
+            let workers = self.clone();
+            let url = url.clone();
+            tasks.spawn(async move { workers.run_shard(&url, request).await });
+        }
+
+        let n_teams = job.season.number_teams;
+        let mut counts = vec![vec![0usize; n_teams]; n_teams];
+        while let Some(shard) = tasks.join_next().await {
+            let shard = shard.map_err(|e| ApiError::Internal(e.to_string()))??;
+            for (sum, row) in counts.iter_mut().zip(shard.counts) {
+                for (cell, count) in sum.iter_mut().zip(row) {
+                    *cell += count;
+                }
+            }
+        }
+
+        telemetry::spawn_blocking(move || {
+            let result = probabilities_from_counts(&counts, total, &job.team_names);
+            job.respond(result, start.elapsed())
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))
+    }
+
+    /// POST `request` to the worker at `url`, simulating it locally if the
+    /// worker cannot be reached or does not answer with counts.
+    async fn run_shard(&self, url: &str, request: ShardRequest) -> Result<ShardResponse, ApiError> {
+        let mut post = self
+            .client
+            .post(format!("{}/simulate/shard", url))
+            .timeout(SHARD_TIMEOUT)
+            .json(&request);
+        if let Some(key) = &self.api_key {
+            post = post.header("x-api-key", key);
+        }
+        let answer = match post.send().await {
+            Ok(response) if response.status().is_success() => response
+                .json::<ShardResponse>()
+                .await
+                .map_err(|e| e.to_string()),
+            Ok(response) => Err(format!("worker answered {}", response.status())),
+            Err(e) => Err(e.to_string()),
+        };
+        let error = match answer {
+            Ok(shard) if shard.answers(&request) => return Ok(shard),
+            Ok(_) => "worker counts do not match the shard".to_string(),
+            Err(e) => e,
+        };
+        tracing::warn!(worker = url, error, "shard failed, simulating it locally");
+        count_shard(request).await
+    }
+}
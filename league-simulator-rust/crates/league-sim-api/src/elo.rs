@@ -0,0 +1,478 @@
+// Rating maintenance without a simulation: applies a matchday's played
+// results to a rating table with the engine's Elo formula, so the R pipeline
+// can delegate its weekly update, carries a league's ratings into the next
+// season and derives starting ratings from past seasons.
+
+use crate::config::Limits;
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::AppState;
+use axum::{extract::State, Json};
+use league_sim_core::{bootstrap_elos, calculate_elo_change, roll_over_season, EloParams, Match};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct EloUpdateRequest {
+    /// Current rating of every team
+    ratings: Vec<TeamRating>,
+
+    /// Played matches, applied in order
+    results: Vec<PlayedResult>,
+
+    /// ELO modification factor (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points (default: 65)
+    home_advantage: Option<f64>,
+}
+
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub struct TeamRating {
+    pub team: String,
+    pub elo: f64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PlayedResult {
+    pub(crate) home: String,
+    pub(crate) away: String,
+    pub(crate) goals_home: i32,
+    pub(crate) goals_away: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EloUpdateResponse {
+    /// Updated ratings, in the order of the request's `ratings`
+    pub ratings: Vec<TeamRating>,
+    /// One entry per result, in the order of the request's `results`
+    pub changes: Vec<EloChange>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EloChange {
+    pub home: String,
+    pub away: String,
+    /// Expected score of the home team before the match
+    pub win_probability_home: f64,
+    /// Points gained by the home team (lost by the away team)
+    pub elo_change: f64,
+}
+
+/// Apply played results to a rating table.
+#[utoipa::path(
+    post,
+    path = "/elo/update",
+    tag = "elo",
+    request_body = EloUpdateRequest,
+    responses(
+        (status = 200, description = "Updated ratings and the change of every match", body = EloUpdateResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or unknown team", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody)
+    )
+)]
+pub async fn update_elo(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<EloUpdateRequest>,
+) -> Result<Json<EloUpdateResponse>, ApiError> {
+    let config = state.config.current();
+    let limits = &config.limits;
+    check_team_count(payload.ratings.len(), limits)?;
+    if payload.results.len() > limits.max_matches {
+        return Err(ApiError::limit(
+            "results",
+            format!(
+                "{} results exceed the limit of {}",
+                payload.results.len(),
+                limits.max_matches
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let index = index_ratings(&payload.ratings, "ratings", &mut errors);
+    check_results(&payload.results, "results", &index, &mut errors);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let mod_factor = payload.mod_factor.unwrap_or(config.simulation.mod_factor);
+    let home_advantage = payload
+        .home_advantage
+        .unwrap_or(config.simulation.home_advantage);
+    let mut ratings = payload.ratings.clone();
+    let changes = payload
+        .results
+        .into_iter()
+        .map(|result| {
+            let (home, away) = (index[result.home.as_str()], index[result.away.as_str()]);
+            let change = calculate_elo_change(&EloParams {
+                elo_home: ratings[home].elo,
+                elo_away: ratings[away].elo,
+                goals_home: result.goals_home,
+                goals_away: result.goals_away,
+                mod_factor,
+                home_advantage,
+            });
+            let elo_change = change.new_elo_home - ratings[home].elo;
+            ratings[home].elo = change.new_elo_home;
+            ratings[away].elo = change.new_elo_away;
+            EloChange {
+                home: result.home,
+                away: result.away,
+                win_probability_home: change.win_probability_home,
+                elo_change,
+            }
+        })
+        .collect();
+
+    Ok(Json(EloUpdateResponse { ratings, changes }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EloRolloverRequest {
+    /// Final ratings of last season's league
+    ratings: Vec<TeamRating>,
+
+    /// Teams of `ratings` leaving the league
+    #[serde(default)]
+    relegated: Vec<String>,
+
+    /// Teams joining the league
+    #[serde(default)]
+    promoted: Vec<PromotedTeam>,
+
+    /// Share of each rating's distance from the league mean kept, between
+    /// 0 (everyone average) and 1 (ratings carried over unchanged)
+    regression: f64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PromotedTeam {
+    team: String,
+
+    /// Rating from the lower league (default: mean of the relegated teams)
+    elo: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EloRolloverResponse {
+    /// The returning teams in request order, then the promoted ones
+    pub ratings: Vec<TeamRating>,
+}
+
+/// Carry a league's ratings into the next season: swap relegated for
+/// promoted teams and regress every rating towards the league mean.
+#[utoipa::path(
+    post,
+    path = "/elo/rollover",
+    tag = "elo",
+    request_body = EloRolloverRequest,
+    responses(
+        (status = 200, description = "Ratings for the next season", body = EloRolloverResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or unknown team", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody)
+    )
+)]
+pub async fn roll_over(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<EloRolloverRequest>,
+) -> Result<Json<EloRolloverResponse>, ApiError> {
+    let config = state.config.current();
+    check_team_count(payload.ratings.len(), &config.limits)?;
+    check_team_count(
+        payload.ratings.len() - payload.relegated.len().min(payload.ratings.len())
+            + payload.promoted.len(),
+        &config.limits,
+    )?;
+
+    let mut errors = Vec::new();
+    let index = index_ratings(&payload.ratings, "ratings", &mut errors);
+    let mut fail = |field: String, message: String| errors.push(FieldError { field, message });
+    if !(0.0..=1.0).contains(&payload.regression) {
+        fail(
+            "regression".into(),
+            format!(
+                "regression must be between 0 and 1, got {}",
+                payload.regression
+            ),
+        );
+    }
+    let mut leaving = vec![false; payload.ratings.len()];
+    for (i, team) in payload.relegated.iter().enumerate() {
+        match index.get(team.as_str()) {
+            Some(&t) => leaving[t] = true,
+            None => fail(
+                format!("relegated[{}]", i),
+                format!("relegated team '{}' has no rating", team),
+            ),
+        }
+    }
+    let mut joining = std::collections::HashSet::new();
+    for (i, promoted) in payload.promoted.iter().enumerate() {
+        let stays = index
+            .get(promoted.team.as_str())
+            .is_some_and(|&t| !leaving[t]);
+        if stays || !joining.insert(promoted.team.as_str()) {
+            fail(
+                format!("promoted[{}].team", i),
+                format!("team '{}' is already in the league", promoted.team),
+            );
+        }
+        if promoted.elo.is_some_and(|elo| !elo.is_finite()) {
+            fail(
+                format!("promoted[{}].elo", i),
+                format!("promoted[{}].elo must be a finite number", i),
+            );
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let elos = |leaves: bool| -> Vec<f64> {
+        payload
+            .ratings
+            .iter()
+            .zip(&leaving)
+            .filter(|&(_, &l)| l == leaves)
+            .map(|(r, _)| r.elo)
+            .collect()
+    };
+    let next = roll_over_season(
+        &elos(false),
+        &elos(true),
+        &payload.promoted.iter().map(|p| p.elo).collect::<Vec<_>>(),
+        payload.regression,
+    );
+    let ratings = payload
+        .ratings
+        .iter()
+        .zip(&leaving)
+        .filter(|&(_, &l)| !l)
+        .map(|(r, _)| r.team.clone())
+        .chain(payload.promoted.iter().map(|p| p.team.clone()))
+        .zip(next)
+        .map(|(team, elo)| TeamRating { team, elo })
+        .collect();
+
+    Ok(Json(EloRolloverResponse { ratings }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EloBootstrapRequest {
+    /// Results per season, oldest season first, matches in the order they
+    /// were played
+    seasons: Vec<Vec<PlayedResult>>,
+
+    /// Seasons after which a result counts half (default: no decay)
+    half_life: Option<f64>,
+
+    /// Rating every team starts the replay with (default: 1500)
+    initial_elo: Option<f64>,
+
+    /// ELO modification factor (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points (default: 65)
+    home_advantage: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EloBootstrapResponse {
+    /// Every team of the history, in order of first appearance
+    pub ratings: Vec<TeamRating>,
+}
+
+/// Derive starting ratings by replaying several seasons of results, older
+/// seasons counting less.
+#[utoipa::path(
+    post,
+    path = "/elo/bootstrap",
+    tag = "elo",
+    request_body = EloBootstrapRequest,
+    responses(
+        (status = 200, description = "Ratings after the replay", body = EloBootstrapResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody)
+    )
+)]
+pub async fn bootstrap(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<EloBootstrapRequest>,
+) -> Result<Json<EloBootstrapResponse>, ApiError> {
+    let config = state.config.current();
+    let limits = &config.limits;
+    let results: usize = payload.seasons.iter().map(Vec::len).sum();
+    if results > limits.max_matches {
+        return Err(ApiError::limit(
+            "seasons",
+            format!(
+                "{} results exceed the limit of {}",
+                results, limits.max_matches
+            ),
+        ));
+    }
+
+    let (teams, index) = index_teams(payload.seasons.iter().flatten());
+    if teams.len() > limits.max_teams {
+        return Err(ApiError::limit(
+            "seasons",
+            format!(
+                "{} teams exceed the limit of {}",
+                teams.len(),
+                limits.max_teams
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    for (i, season) in payload.seasons.iter().enumerate() {
+        check_results(season, &format!("seasons[{}]", i), &index, &mut errors);
+    }
+    let half_life = payload.half_life.unwrap_or(f64::INFINITY);
+    if half_life.is_nan() || half_life <= 0.0 {
+        errors.push(FieldError {
+            field: "half_life".into(),
+            message: format!("half_life must be positive, got {}", half_life),
+        });
+    }
+    let initial_elo = payload.initial_elo.unwrap_or(1500.0);
+    if !initial_elo.is_finite() {
+        errors.push(FieldError {
+            field: "initial_elo".into(),
+            message: "initial_elo must be a finite number".into(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let seasons: Vec<Vec<Match>> = payload
+        .seasons
+        .iter()
+        .map(|season| {
+            season
+                .iter()
+                .map(|r| Match {
+                    team_home: index[r.home.as_str()],
+                    team_away: index[r.away.as_str()],
+                    goals_home: Some(r.goals_home),
+                    goals_away: Some(r.goals_away),
+                    matchday: None,
+                    competition: None,
+                    weight: None,
+                })
+                .collect()
+        })
+        .collect();
+    let elos = bootstrap_elos(
+        &seasons,
+        teams.len(),
+        initial_elo,
+        half_life,
+        payload.mod_factor.unwrap_or(config.simulation.mod_factor),
+        payload
+            .home_advantage
+            .unwrap_or(config.simulation.home_advantage),
+    );
+    let ratings = teams
+        .into_iter()
+        .zip(elos)
+        .map(|(team, elo)| TeamRating {
+            team: team.to_string(),
+            elo,
+        })
+        .collect();
+
+    Ok(Json(EloBootstrapResponse { ratings }))
+}
+
+pub(crate) fn check_team_count(teams: usize, limits: &Limits) -> Result<(), ApiError> {
+    if teams > limits.max_teams {
+        return Err(ApiError::limit(
+            "ratings",
+            format!("{} teams exceed the limit of {}", teams, limits.max_teams),
+        ));
+    }
+    Ok(())
+}
+
+/// Position of each team in `ratings`, recording non-finite and duplicate
+/// entries in `errors` under `path`.
+pub(crate) fn index_ratings<'a>(
+    ratings: &'a [TeamRating],
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) -> HashMap<&'a str, usize> {
+    let mut index = HashMap::new();
+    for (i, rating) in ratings.iter().enumerate() {
+        if !rating.elo.is_finite() {
+            errors.push(FieldError {
+                field: format!("{}[{}].elo", path, i),
+                message: format!("{}[{}].elo must be a finite number", path, i),
+            });
+        }
+        if index.insert(rating.team.as_str(), i).is_some() {
+            errors.push(FieldError {
+                field: format!("{}[{}].team", path, i),
+                message: format!("team '{}' is rated twice", rating.team),
+            });
+        }
+    }
+    index
+}
+
+/// Teams of `results` in order of first appearance, and each one's
+/// position in that order.
+pub(crate) fn index_teams<'a>(
+    results: impl IntoIterator<Item = &'a PlayedResult>,
+) -> (Vec<&'a str>, HashMap<&'a str, usize>) {
+    let mut teams: Vec<&str> = Vec::new();
+    let mut index = HashMap::new();
+    for result in results {
+        for team in [&result.home, &result.away] {
+            index.entry(team.as_str()).or_insert_with(|| {
+                teams.push(team.as_str());
+                teams.len() - 1
+            });
+        }
+    }
+    (teams, index)
+}
+
+/// Record results under `path` naming unrated teams, pairing a team with
+/// itself or carrying negative goals in `errors`.
+pub(crate) fn check_results<'a>(
+    results: impl IntoIterator<Item = &'a PlayedResult>,
+    path: &str,
+    index: &HashMap<&str, usize>,
+    errors: &mut Vec<FieldError>,
+) {
+    let mut fail = |field: String, message: String| errors.push(FieldError { field, message });
+    for (i, result) in results.into_iter().enumerate() {
+        for (side, team) in [("home", &result.home), ("away", &result.away)] {
+            if !index.contains_key(team.as_str()) {
+                fail(
+                    format!("{}[{}].{}", path, i, side),
+                    format!("{}[{}]: team '{}' has no rating", path, i, team),
+                );
+            }
+        }
+        if result.home == result.away {
+            fail(
+                format!("{}[{}]", path, i),
+                format!("{}[{}]: '{}' cannot play itself", path, i, result.home),
+            );
+        }
+        if result.goals_home < 0 || result.goals_away < 0 {
+            fail(
+                format!("{}[{}]", path, i),
+                format!("{}[{}]: goals must not be negative", path, i),
+            );
+        }
+    }
+}
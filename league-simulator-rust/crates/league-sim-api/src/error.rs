@@ -0,0 +1,221 @@
+// Error type of every handler. Failures are answered with a JSON body
+// `{"code": ..., "message": ..., "field": ...}` so clients can branch on
+// `code` and point users at the offending `field` instead of parsing text.
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{info_span, Instrument};
+use utoipa::ToSchema;
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// Request fields are missing, out of range or inconsistent with the
+    /// rest of the request (never empty)
+    #[error("{}", join_messages(.0))]
+    InvalidFields(Vec<FieldError>),
+    /// The request exceeds a configured size limit
+    #[error("{}", .0.message)]
+    LimitExceeded(FieldError),
+    /// The request is invalid as a whole
+    #[error("{0}")]
+    InvalidRequest(String),
+    /// The body is not JSON or does not match the request type
+    #[error("{}", .0.body_text())]
+    Body(#[from] JsonRejection),
+    #[error("{0}")]
+    NotFound(String),
+    /// No valid API key was presented
+    #[error("missing or unknown API key")]
+    Unauthorized,
+    /// The API key does not grant access to the route
+    #[error("{0}")]
+    Forbidden(String),
+    /// The API key used up its daily iteration budget
+    #[error("daily iteration budget exhausted, resets in {retry_after} s")]
+    BudgetExhausted { retry_after: u64 },
+    /// The client used up its request budget
+    #[error("rate limit exceeded, retry in {retry_after} s")]
+    RateLimited { retry_after: u64 },
+    /// Every simulation slot and queue place is taken
+    #[error("server busy, simulation queue full")]
+    Overloaded,
+    /// The request took longer than the configured timeout
+    #[error("request timed out")]
+    Timeout,
+    /// The simulation was cancelled before it finished
+    #[error("simulation cancelled")]
+    Cancelled,
+    #[error("{0}")]
+    Internal(String),
+}
+
+/// One problem with one request field.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `schedule[3]`
+    pub field: String,
+    pub message: String,
+}
+
+fn join_messages(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// JSON body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Machine-readable error code, e.g. `invalid_field` or `not_found`
+    pub code: &'static str,
+    pub message: String,
+    /// Path of the offending request field, e.g. `schedule[3]` (the first
+    /// one if there are several)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Every invalid field, for validation errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidFields(vec![FieldError {
+            field: field.into(),
+            message: message.into(),
+        }])
+    }
+
+    pub fn limit(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::LimitExceeded(FieldError {
+            field: field.into(),
+            message: message.into(),
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidFields(_) | Self::InvalidRequest(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::LimitExceeded(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Body(rejection) => rejection.status(),
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::BudgetExhausted { .. } | Self::RateLimited { .. } | Self::Overloaded => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Timeout => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Cancelled => StatusCode::CONFLICT,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidFields(_) => "invalid_field",
+            Self::LimitExceeded(_) => "limit_exceeded",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::Body(JsonRejection::JsonSyntaxError(_)) => "malformed_json",
+            Self::Body(JsonRejection::MissingJsonContentType(_)) => "unsupported_media_type",
+            Self::Body(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                "payload_too_large"
+            }
+            Self::Body(_) => "invalid_body",
+            Self::NotFound(_) => "not_found",
+            Self::Unauthorized => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::BudgetExhausted { .. } => "budget_exhausted",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Overloaded => "overloaded",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn field_path(&self) -> Option<&str> {
+        match self {
+            Self::InvalidFields(errors) => errors.first().map(|e| e.field.as_str()),
+            Self::LimitExceeded(error) => Some(&error.field),
+            _ => None,
+        }
+    }
+
+    /// The same error for a request nested in a larger body: `path` is
+    /// prepended to the field and `label` to the message, e.g. for one
+    /// league of a batch.
+    pub fn within(self, path: &str, label: &str) -> Self {
+        match self {
+            Self::InvalidFields(errors) => Self::InvalidFields(
+                errors
+                    .into_iter()
+                    .map(|e| FieldError {
+                        field: format!("{}.{}", path, e.field),
+                        message: format!("{}: {}", label, e.message),
+                    })
+                    .collect(),
+            ),
+            Self::LimitExceeded(e) => Self::limit(
+                format!("{}.{}", path, e.field),
+                format!("{}: {}", label, e.message),
+            ),
+            Self::InvalidRequest(message) => {
+                Self::field(path.to_string(), format!("{}: {}", label, message))
+            }
+            other => other,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            field: self.field_path().map(str::to_string),
+            details: match self {
+                Self::InvalidFields(errors) => errors.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status(), Json(self.body())).into_response();
+        if let Self::RateLimited { retry_after } | Self::BudgetExhausted { retry_after } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        if let Self::Overloaded = self {
+            // The queue moves as soon as running simulations finish.
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(1));
+        }
+        response
+    }
+}
+
+/// `Json` extractor whose rejections are answered as [`ApiError`].
+pub struct ApiJson<T>(pub T);
+
+impl<S: Send + Sync, T: DeserializeOwned> FromRequest<S> for ApiJson<T> {
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .instrument(info_span!("parse"))
+            .await?;
+        Ok(Self(value))
+    }
+}
@@ -0,0 +1,384 @@
+// Model calibration: fits the engine's free parameters to historical
+// results supplied by the client, so a league can be simulated with values
+// estimated from its own past instead of the built-in defaults.
+
+use crate::elo::{check_results, index_ratings, index_teams, PlayedResult, TeamRating};
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::{telemetry, AppState};
+use axum::{extract::State, Json};
+use league_sim_core::{
+    fit_goal_model, fit_home_advantage, fit_poisson_strengths, GoalModelFit, GoalObservation,
+    HomeAdvantageFit, Match, PoissonStrengthFit, Season, SimulationParams, HOME_ADVANTAGE_RANGE,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Largest home advantage, either way, a fit may search, in ELO points.
+/// Every grid point replays all seasons.
+const MAX_RANGE: f64 = 500.0;
+
+#[derive(Deserialize, ToSchema)]
+pub struct HomeAdvantageRequest {
+    /// Historical seasons, each replayed from its starting ratings
+    seasons: Vec<HistoricalSeason>,
+
+    /// Also fit each league on its own (default: false)
+    per_league: Option<bool>,
+
+    /// ELO modification factor of the replay (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Lowest and highest home advantage searched, in ELO points, within
+    /// ±500 (default: [-100, 300])
+    range: Option<[f64; 2]>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct HistoricalSeason {
+    /// League of the season; required with `per_league`
+    league: Option<String>,
+
+    /// Rating of every team before the season's first result
+    ratings: Vec<TeamRating>,
+
+    /// Played matches, in the order they were played
+    results: Vec<PlayedResult>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct HomeAdvantageResponse {
+    /// Fit over every season
+    pub pooled: HomeAdvantageFit,
+    /// Fit per league, in order of first appearance; only with
+    /// `per_league`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leagues: Option<Vec<LeagueHomeAdvantage>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LeagueHomeAdvantage {
+    pub league: String,
+    #[serde(flatten)]
+    pub fit: HomeAdvantageFit,
+}
+
+/// Estimate the home advantage that best predicts historical results.
+///
+/// Every season is replayed with the engine's Elo updates, scoring each
+/// result by its probability under the goal model before it moves the
+/// ratings; the home advantage maximising the total log-likelihood wins.
+#[utoipa::path(
+    post,
+    path = "/fit/home-advantage",
+    tag = "fit",
+    request_body = HomeAdvantageRequest,
+    responses(
+        (status = 200, description = "Fitted home advantage, pooled and per league", body = HomeAdvantageResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or unknown team", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn estimate_home_advantage(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<HomeAdvantageRequest>,
+) -> Result<Json<HomeAdvantageResponse>, ApiError> {
+    let config = state.config.current();
+    let limits = &config.limits;
+    let results: usize = payload.seasons.iter().map(|s| s.results.len()).sum();
+    if results > limits.max_matches {
+        return Err(ApiError::limit(
+            "seasons",
+            format!(
+                "{} results exceed the limit of {}",
+                results, limits.max_matches
+            ),
+        ));
+    }
+
+    let per_league = payload.per_league.unwrap_or(false);
+    let mut errors = Vec::new();
+    if payload.seasons.is_empty() {
+        errors.push(FieldError {
+            field: "seasons".into(),
+            message: "at least one season is required".into(),
+        });
+    }
+    let mut seasons = Vec::with_capacity(payload.seasons.len());
+    for (i, season) in payload.seasons.iter().enumerate() {
+        let path = format!("seasons[{}]", i);
+        if season.ratings.len() > limits.max_teams {
+            return Err(ApiError::limit(
+                format!("{}.ratings", path),
+                format!(
+                    "{} teams exceed the limit of {}",
+                    season.ratings.len(),
+                    limits.max_teams
+                ),
+            ));
+        }
+        if per_league && season.league.is_none() {
+            errors.push(FieldError {
+                field: format!("{}.league", path),
+                message: format!("{}.league is required with per_league", path),
+            });
+        }
+        let index = index_ratings(&season.ratings, &format!("{}.ratings", path), &mut errors);
+        check_results(
+            &season.results,
+            &format!("{}.results", path),
+            &index,
+            &mut errors,
+        );
+        if !errors.is_empty() {
+            continue;
+        }
+        seasons.push(Season {
+            matches: season
+                .results
+                .iter()
+                .map(|r| Match {
+                    team_home: index[r.home.as_str()],
+                    team_away: index[r.away.as_str()],
+                    goals_home: Some(r.goals_home),
+                    goals_away: Some(r.goals_away),
+                    matchday: None,
+                    competition: None,
+                    weight: None,
+                })
+                .collect(),
+            team_elos: season.ratings.iter().map(|r| r.elo).collect(),
+            number_teams: season.ratings.len(),
+        });
+    }
+    let range = match payload.range {
+        Some([lo, hi]) if !(-MAX_RANGE <= lo && lo <= hi && hi <= MAX_RANGE) => {
+            errors.push(FieldError {
+                field: "range".into(),
+                message: format!(
+                    "range must be two bounds in order within ±{MAX_RANGE}, got [{lo}, {hi}]"
+                ),
+            });
+            HOME_ADVANTAGE_RANGE
+        }
+        Some([lo, hi]) => lo..=hi,
+        None => HOME_ADVANTAGE_RANGE,
+    };
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let params = SimulationParams {
+        mod_factor: payload.mod_factor.unwrap_or(config.simulation.mod_factor),
+        home_advantage: config.simulation.home_advantage,
+        tore_slope: config.simulation.tore_slope,
+        tore_intercept: config.simulation.tore_intercept,
+        ..Default::default()
+    };
+    let leagues: Vec<Option<String>> = payload.seasons.into_iter().map(|s| s.league).collect();
+    let response = telemetry::spawn_blocking(move || {
+        let pooled = fit_home_advantage(&seasons, &params, range.clone());
+        let leagues = per_league.then(|| {
+            let mut names: Vec<&String> = Vec::new();
+            for league in leagues.iter().flatten() {
+                if !names.contains(&league) {
+                    names.push(league);
+                }
+            }
+            names
+                .into_iter()
+                .map(|name| {
+                    let own: Vec<Season> = seasons
+                        .iter()
+                        .zip(&leagues)
+                        .filter(|(_, league)| league.as_ref() == Some(name))
+                        .map(|(season, _)| season.clone())
+                        .collect();
+                    LeagueHomeAdvantage {
+                        league: name.clone(),
+                        fit: fit_home_advantage(&own, &params, range.clone()),
+                    }
+                })
+                .collect()
+        });
+        HomeAdvantageResponse { pooled, leagues }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Json(response))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GoalModelRequest {
+    /// Goals scored by one team at a given ELO difference; a match gives
+    /// two, one per side
+    observations: Vec<GoalObservation>,
+
+    /// Confidence level of the intervals (default: 0.95)
+    confidence: Option<f64>,
+}
+
+/// Refit the goal model's `tore_slope` and `tore_intercept` by least
+/// squares.
+#[utoipa::path(
+    post,
+    path = "/fit/goal-model",
+    tag = "fit",
+    request_body = GoalModelRequest,
+    responses(
+        (status = 200, description = "Fitted slope and intercept with confidence intervals", body = GoalModelFit),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or too little data to fit", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody)
+    )
+)]
+pub async fn estimate_goal_model(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<GoalModelRequest>,
+) -> Result<Json<GoalModelFit>, ApiError> {
+    let limits = &state.config.current().limits;
+    // Two observations per match.
+    if payload.observations.len() > 2 * limits.max_matches {
+        return Err(ApiError::limit(
+            "observations",
+            format!(
+                "{} observations exceed the limit of {}",
+                payload.observations.len(),
+                2 * limits.max_matches
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    for (i, o) in payload.observations.iter().enumerate() {
+        if !(o.elo_delta.is_finite() && o.goals.is_finite() && o.goals >= 0.0) {
+            errors.push(FieldError {
+                field: format!("observations[{}]", i),
+                message: format!(
+                    "observations[{}]: elo_delta must be finite and goals a non-negative number",
+                    i
+                ),
+            });
+        }
+    }
+    let confidence = payload.confidence.unwrap_or(0.95);
+    if !(confidence > 0.0 && confidence < 1.0) {
+        errors.push(FieldError {
+            field: "confidence".into(),
+            message: format!("confidence must be between 0 and 1, got {}", confidence),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    fit_goal_model(&payload.observations, confidence)
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::field(
+                "observations",
+                "at least three observations with differing elo_delta are required",
+            )
+        })
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TeamStrengthsRequest {
+    /// Played matches; teams are numbered in order of first appearance
+    results: Vec<PlayedResult>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TeamStrengthsResponse {
+    /// Teams in the order of `attack` and `defense`
+    pub team_names: Vec<String>,
+    /// Coefficients in the shape of the `poisson_strengths` field of
+    /// `/simulate`, with the fit's diagnostics
+    #[serde(flatten)]
+    pub fit: PoissonStrengthFit,
+}
+
+/// Fit per-team attack and defense strengths and a home advantage to
+/// played results by Poisson regression.
+///
+/// Home goals are Poisson with mean
+/// `exp(intercept + home_advantage + attack[home] + defense[away])`, away
+/// goals with `exp(intercept + attack[away] + defense[home])`. Attack and
+/// defense each sum to zero across teams.
+#[utoipa::path(
+    post,
+    path = "/fit/team-strengths",
+    tag = "fit",
+    request_body = TeamStrengthsRequest,
+    responses(
+        (status = 200, description = "Fitted coefficients per team", body = TeamStrengthsResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or too little data to fit", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn estimate_team_strengths(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<TeamStrengthsRequest>,
+) -> Result<Json<TeamStrengthsResponse>, ApiError> {
+    let limits = &state.config.current().limits;
+    if payload.results.len() > limits.max_matches {
+        return Err(ApiError::limit(
+            "results",
+            format!(
+                "{} results exceed the limit of {}",
+                payload.results.len(),
+                limits.max_matches
+            ),
+        ));
+    }
+    let (teams, index) = index_teams(&payload.results);
+    if teams.len() > limits.max_teams {
+        return Err(ApiError::limit(
+            "results",
+            format!(
+                "{} teams exceed the limit of {}",
+                teams.len(),
+                limits.max_teams
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    check_results(&payload.results, "results", &index, &mut errors);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+    let matches: Vec<Match> = payload
+        .results
+        .iter()
+        .map(|r| Match {
+            team_home: index[r.home.as_str()],
+            team_away: index[r.away.as_str()],
+            goals_home: Some(r.goals_home),
+            goals_away: Some(r.goals_away),
+            matchday: None,
+            competition: None,
+            weight: None,
+        })
+        .collect();
+    let team_names: Vec<String> = teams.into_iter().map(String::from).collect();
+
+    let number_teams = team_names.len();
+    let fit = telemetry::spawn_blocking(move || fit_poisson_strengths(&matches, number_teams))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| {
+            ApiError::field(
+                "results",
+                "at least one result between two different teams is required",
+            )
+        })?;
+
+    Ok(Json(TeamStrengthsResponse { team_names, fit }))
+}
@@ -0,0 +1,179 @@
+// Preseason schedules. POST /fixtures/generate turns a team list with Elo
+// ratings into a complete double round robin, or with `rounds` a Swiss-model
+// league phase, answered as a /simulate body, so projections can run before
+// the official fixtures are published.
+
+use crate::config::Limits;
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::AppState;
+use axum::{extract::State, Json};
+use league_sim_core::{
+    double_round_robin, entropy_seed, random_double_round_robin, swiss_schedule,
+};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct FixtureTeam {
+    pub name: String,
+    pub elo: f64,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GenerateFixturesRequest {
+    /// The league's teams, at least two; their order is the team order of
+    /// the generated body
+    pub teams: Vec<FixtureTeam>,
+
+    /// Draw who meets whom on which matchday from this seed (optional).
+    /// Without one the pairings follow the circle method in team order.
+    pub seed: Option<u64>,
+
+    /// Play a Swiss-model league phase of this many rounds instead, each
+    /// team meeting a different opponent every round (optional). Needs an
+    /// even number of teams and fewer rounds than teams; opponents are
+    /// drawn from `seed`, or at random without one.
+    pub rounds: Option<usize>,
+}
+
+/// A /simulate request body for the generated season; add parameters and
+/// post it as is.
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GeneratedFixtures {
+    /// `[team_home, team_away, null, null]`, 1-based team indices in the
+    /// order of `team_names`, in matchday order
+    pub schedule: Vec<[Option<i32>; 4]>,
+    pub elo_values: Vec<f64>,
+    pub team_names: Vec<String>,
+    /// Matchday of each schedule row; in a double round robin the second
+    /// half mirrors the first with home and away swapped
+    pub matchdays: Vec<u32>,
+}
+
+/// Validate a team list and generate its double round robin or Swiss
+/// league phase.
+pub fn generate_fixtures(
+    payload: GenerateFixturesRequest,
+    limits: &Limits,
+) -> Result<GeneratedFixtures, ApiError> {
+    let n = payload.teams.len();
+    if n > limits.max_teams {
+        return Err(ApiError::limit(
+            "teams",
+            format!("{} teams exceed the limit of {}", n, limits.max_teams),
+        ));
+    }
+    let match_count = match payload.rounds {
+        Some(rounds) => rounds.saturating_mul(n / 2),
+        None => n * n.saturating_sub(1),
+    };
+    if match_count > limits.max_matches {
+        return Err(ApiError::limit(
+            "teams",
+            format!(
+                "{} teams play {} matches, above the limit of {}",
+                n, match_count, limits.max_matches
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    if n < 2 {
+        errors.push(FieldError {
+            field: "teams".into(),
+            message: format!("at least 2 teams are required, got {}", n),
+        });
+    }
+    let mut names = HashSet::new();
+    for (i, team) in payload.teams.iter().enumerate() {
+        if !names.insert(team.name.as_str()) {
+            errors.push(FieldError {
+                field: format!("teams[{}].name", i),
+                message: format!("team {}: name '{}' appears twice", i, team.name),
+            });
+        }
+        if !team.elo.is_finite() {
+            errors.push(FieldError {
+                field: format!("teams[{}].elo", i),
+                message: format!("team {}: elo must be a finite number", i),
+            });
+        }
+    }
+    if let Some(rounds) = payload.rounds {
+        if n % 2 == 1 {
+            errors.push(FieldError {
+                field: "rounds".into(),
+                message: format!(
+                    "a Swiss league phase needs an even number of teams, got {}",
+                    n
+                ),
+            });
+        }
+        if rounds == 0 || rounds >= n.max(2) {
+            errors.push(FieldError {
+                field: "rounds".into(),
+                message: format!(
+                    "rounds must be between 1 and {} for {} teams, got {}",
+                    n.max(2) - 1,
+                    n,
+                    rounds
+                ),
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let matches = match (payload.rounds, payload.seed) {
+        (Some(rounds), seed) => swiss_schedule(
+            n,
+            rounds,
+            &mut rand::rngs::StdRng::seed_from_u64(seed.unwrap_or_else(entropy_seed)),
+        ),
+        (None, Some(seed)) => {
+            random_double_round_robin(n, &mut rand::rngs::StdRng::seed_from_u64(seed))
+        }
+        (None, None) => double_round_robin(n),
+    };
+    Ok(GeneratedFixtures {
+        schedule: matches
+            .iter()
+            .map(|m| {
+                [
+                    Some(m.team_home as i32 + 1),
+                    Some(m.team_away as i32 + 1),
+                    None,
+                    None,
+                ]
+            })
+            .collect(),
+        matchdays: matches.iter().map(|m| m.matchday.unwrap_or(1)).collect(),
+        elo_values: payload.teams.iter().map(|t| t.elo).collect(),
+        team_names: payload.teams.into_iter().map(|t| t.name).collect(),
+    })
+}
+
+/// Generate a double round robin or Swiss league phase for a team list, as
+/// a /simulate body.
+#[utoipa::path(
+    post,
+    path = "/fixtures/generate",
+    tag = "simulation",
+    request_body = GenerateFixturesRequest,
+    responses(
+        (status = 200, description = "Request body for /simulate", body = GeneratedFixtures),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid team list", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody)
+    )
+)]
+pub async fn generate(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<GenerateFixturesRequest>,
+) -> Result<Json<GeneratedFixtures>, ApiError> {
+    let config = state.config.current();
+    Ok(Json(generate_fixtures(payload, &config.limits)?))
+}
@@ -0,0 +1,211 @@
+// GraphQL view of /simulate: clients select only the fields they need, e.g.
+// the relegation probabilities of three teams instead of the full matrix.
+// The simulation runs inside the `simulate` resolver and shares the result
+// cache with the REST endpoint. Each `simulate` field is charged to the
+// caller's iteration budget, cache hits included, as on the REST endpoint.
+
+use crate::auth::Budget;
+use crate::error::ApiError;
+use crate::handlers::{SimulateRequest, SimulateResponse, SimulationJob};
+use crate::telemetry;
+use crate::AppState;
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, ErrorExtensions, InputObject, Object, Schema,
+};
+use axum::{extract::State, response::Html, Extension, Json};
+use serde::Serialize;
+use std::sync::{Arc, OnceLock};
+
+pub type SimulationSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// The schema is stateless; per-request state is passed as query data.
+fn schema() -> &'static SimulationSchema {
+    static SCHEMA: OnceLock<SimulationSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::new(Query, EmptyMutation, EmptySubscription))
+}
+
+pub async fn graphql_handler(
+    State(state): State<AppState>,
+    budget: Option<Extension<Budget>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let mut request = request.data(state);
+    if let Some(Extension(budget)) = budget {
+        request = request.data(budget);
+    }
+    Json(schema().execute(request).await)
+}
+
+pub async fn graphql_playground() -> Html<String> {
+    Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+}
+
+/// Simulation inputs; the same fields as the REST body.
+#[derive(InputObject, Serialize)]
+pub struct SimulationInput {
+    /// Rows [team_home, team_away, goals_home, goals_away], 1-indexed teams,
+    /// null goals for unplayed matches
+    schedule: Vec<Vec<Option<i32>>>,
+    elo_values: Vec<f64>,
+    team_names: Option<Vec<String>>,
+    iterations: Option<u32>,
+    mod_factor: Option<f64>,
+    home_advantage: Option<f64>,
+    adj_points: Option<Vec<i32>>,
+    adj_goals: Option<Vec<i32>>,
+    adj_goals_against: Option<Vec<i32>>,
+    adj_goal_diff: Option<Vec<i32>>,
+    /// League preset id, e.g. "bundesliga"
+    league: Option<String>,
+    /// League template id, or "auto"
+    structure: Option<String>,
+}
+
+pub struct Query;
+
+impl ErrorExtensions for ApiError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, ext| {
+            ext.set("code", self.code());
+            if let Some(field) = self.field_path() {
+                ext.set("field", field);
+            }
+        })
+    }
+}
+
+#[Object]
+impl Query {
+    /// Run a Monte Carlo simulation, or serve it from the result cache.
+    async fn simulate(
+        &self,
+        ctx: &Context<'_>,
+        input: SimulationInput,
+    ) -> async_graphql::Result<Simulation> {
+        let state = ctx.data::<AppState>()?;
+        let start = std::time::Instant::now();
+
+        let request: SimulateRequest = serde_json::from_value(serde_json::to_value(&input)?)?;
+        let job = SimulationJob::from_request(request, &state.config.current())
+            .map_err(|e| e.extend())?;
+        if let Some(budget) = ctx.data_opt::<Budget>() {
+            budget
+                .charge(job.iterations_per_run())
+                .map_err(|e| e.extend())?;
+        }
+
+        let key = job.cache_key();
+        let response = match state.cache.get(&key) {
+            Some(mut cached) => {
+                cached.cache_hit = true;
+                cached.time_ms = start.elapsed().as_millis();
+                cached
+            }
+            None => {
+                let reproducible = job.reproducible();
+                let response = telemetry::spawn_blocking(move || job.simulate(start)).await?;
+                crate::metrics::add_iterations(response.simulations_performed);
+                if reproducible {
+                    state.cache.insert(key, response.clone());
+                }
+                response
+            }
+        };
+        Ok(Simulation(Arc::new(response)))
+    }
+}
+
+pub struct Simulation(Arc<SimulateResponse>);
+
+#[Object]
+impl Simulation {
+    async fn simulations_performed(&self) -> usize {
+        self.0.simulations_performed
+    }
+
+    async fn time_ms(&self) -> u64 {
+        self.0.time_ms as u64
+    }
+
+    async fn cache_hit(&self) -> bool {
+        self.0.cache_hit
+    }
+
+    /// Teams in final rank order, optionally only the named ones.
+    async fn teams(&self, names: Option<Vec<String>>) -> async_graphql::Result<Vec<TeamResult>> {
+        let all = (0..self.0.team_names.len()).map(|row| TeamResult {
+            response: self.0.clone(),
+            row,
+        });
+        let Some(names) = names else {
+            return Ok(all.collect());
+        };
+        names
+            .iter()
+            .map(|name| {
+                self.0
+                    .team_names
+                    .iter()
+                    .position(|n| n == name)
+                    .map(|row| TeamResult {
+                        response: self.0.clone(),
+                        row,
+                    })
+                    .ok_or_else(|| format!("unknown team '{}'", name).into())
+            })
+            .collect()
+    }
+}
+
+/// One team's row of the result.
+pub struct TeamResult {
+    response: Arc<SimulateResponse>,
+    row: usize,
+}
+
+#[Object]
+impl TeamResult {
+    async fn name(&self) -> &str {
+        &self.response.team_names[self.row]
+    }
+
+    /// 1-based rank by average simulated position
+    async fn rank(&self) -> usize {
+        self.row + 1
+    }
+
+    /// Probability of each final position, first place first
+    async fn position_probabilities(&self) -> Vec<f64> {
+        self.response.dense_matrix()[self.row].clone()
+    }
+
+    /// Probability of finishing between positions `from` and `to`
+    /// (1-based, inclusive), e.g. 16..18 for the relegation places.
+    async fn probability_between(&self, from: usize, to: usize) -> f64 {
+        let matrix = self.response.dense_matrix();
+        let row = &matrix[self.row];
+        let to = to.min(row.len());
+        if from == 0 || from > to {
+            return 0.0;
+        }
+        row[from - 1..to].iter().sum()
+    }
+
+    /// Probability of finishing in the named zone of the applied league
+    /// template; null without a template or for an unknown zone.
+    async fn zone_probability(&self, zone: String) -> Option<f64> {
+        let zones = self.response.zone_probabilities.as_ref()?;
+        zones[self.row].zones.get(&zone).copied()
+    }
+
+    /// Expected points from the deterministic projected table
+    async fn expected_points(&self) -> Option<f64> {
+        let name = &self.response.team_names[self.row];
+        self.response
+            .projected_table
+            .iter()
+            .find(|row| &row.team_name == name)
+            .map(|row| row.expected_points)
+    }
+}
@@ -0,0 +1,2106 @@
+use crate::cache::CacheKey;
+use crate::config::{Limits, RuntimeConfig};
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::overload;
+use crate::reports::{arrow_unavailable, Negotiated, ReportFormat, ReportOptions, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use league_sim_core::presets::{preset, LeaguePreset};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::structure::{detect_structure_among, LeagueStructure};
+use league_sim_core::{
+    auto_solver, entropy_seed, final_matchday_fixtures, importance_zones, max_standard_error,
+    position_bounds, priors_from_market_values, project_table, run_award_simulation,
+    run_expected_table, run_hybrid_simulation, run_match_importance, run_monte_carlo_simulation,
+    run_monte_carlo_simulation_cancellable, run_monte_carlo_simulation_until, run_scenario_mixture,
+    run_season_samples, schedule_warnings, ExpectedStanding, GlickoSettings, Match,
+    MatchImportance, PoissonStrengths, RatingSystemKind, RngKind, Scenario, ScenarioResult, Season,
+    SeasonSample, SimulationParams, SimulationResult, Solver, TableEntry, TeamAwards, TeamStrength,
+    TiebreakerRule, HYBRID_MAX_FIXTURES,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{info_span, Instrument, Span};
+use utoipa::ToSchema;
+
+/// Most components a scenario mixture may have.
+const MAX_SCENARIOS: usize = 16;
+
+/// ELO points between squads of which one is worth twice the other, when
+/// market values become strength priors.
+const DEFAULT_ELO_PER_DOUBLING: f64 = 100.0;
+
+/// Check a request for inconsistencies that would otherwise produce wrong
+/// tables or crash the simulation. Every problem is reported, not only the
+/// first one.
+fn validate_request(payload: &SimulateRequest, limits: &Limits) -> Result<(), ApiError> {
+    // Oversized leagues are turned away before looking at their contents.
+    if payload.elo_values.len() > limits.max_teams {
+        return Err(ApiError::limit(
+            "elo_values",
+            format!(
+                "{} teams exceed the limit of {}",
+                payload.elo_values.len(),
+                limits.max_teams
+            ),
+        ));
+    }
+    if payload.schedule.len() > limits.max_matches {
+        return Err(ApiError::limit(
+            "schedule",
+            format!(
+                "{} schedule rows exceed the limit of {}",
+                payload.schedule.len(),
+                limits.max_matches
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let mut fail = |field: String, message: String| errors.push(FieldError { field, message });
+
+    let number_teams = payload.elo_values.len();
+    if payload.schedule.is_empty() {
+        fail("schedule".into(), "schedule must not be empty".into());
+    }
+    if number_teams == 0 {
+        fail("elo_values".into(), "elo_values must not be empty".into());
+    }
+    for (i, elo) in payload.elo_values.iter().enumerate() {
+        if !elo.is_finite() {
+            fail(
+                format!("elo_values[{}]", i),
+                format!("elo_values[{}] must be a finite number", i),
+            );
+        }
+    }
+    if let Some(iterations) = payload.iterations {
+        if iterations == 0 || iterations > limits.max_iterations {
+            fail(
+                "iterations".into(),
+                format!(
+                    "iterations must be between 1 and {}, got {}",
+                    limits.max_iterations, iterations
+                ),
+            );
+        }
+    }
+    if payload.time_budget_ms == Some(0) {
+        fail(
+            "time_budget_ms".into(),
+            "time_budget_ms must be at least 1".into(),
+        );
+    }
+    if let Some(se) = payload.target_se {
+        if !(se > 0.0 && se < 0.5) {
+            fail(
+                "target_se".into(),
+                format!(
+                    "target_se must be between 0 and 0.5 (exclusive), got {}",
+                    se
+                ),
+            );
+        }
+    }
+
+    // Highest team index the schedule refers to.
+    let mut implied_teams = 0;
+    for (i, row) in payload.schedule.iter().enumerate() {
+        let field = || format!("schedule[{}]", i);
+        for (name, value) in [("team_home", row[0]), ("team_away", row[1])] {
+            match value {
+                Some(v) if v >= 1 => {
+                    implied_teams = implied_teams.max(v as usize);
+                    if v as usize > number_teams {
+                        fail(
+                            field(),
+                            format!(
+                                "schedule row {}: {} index {} out of range 1..={}",
+                                i, name, v, number_teams
+                            ),
+                        );
+                    }
+                }
+                Some(v) => fail(
+                    field(),
+                    format!(
+                        "schedule row {}: {} index {} must be at least 1",
+                        i, name, v
+                    ),
+                ),
+                None => fail(
+                    field(),
+                    format!("schedule row {}: {} must not be null", i, name),
+                ),
+            }
+        }
+        if row[0].is_some() && row[0] == row[1] {
+            fail(
+                field(),
+                format!("schedule row {}: a team cannot play itself", i),
+            );
+        }
+        match (row[2], row[3]) {
+            (Some(home), Some(away)) if home < 0 || away < 0 => fail(
+                field(),
+                format!("schedule row {}: goals must not be negative", i),
+            ),
+            (Some(_), None) | (None, Some(_)) => fail(
+                field(),
+                format!(
+                    "schedule row {}: goals_home and goals_away must both be set or both be null",
+                    i
+                ),
+            ),
+            _ => {}
+        }
+    }
+    // In table-only mode the table, not the schedule, lists every team.
+    if number_teams > 0
+        && implied_teams != number_teams
+        && (payload.current_table.is_none() || implied_teams > number_teams)
+    {
+        fail(
+            "elo_values".into(),
+            format!(
+                "elo_values has length {}, but the schedule has {} teams",
+                number_teams, implied_teams
+            ),
+        );
+    }
+
+    for (name, adj) in [
+        ("adj_points", &payload.adj_points),
+        ("adj_goals", &payload.adj_goals),
+        ("adj_goals_against", &payload.adj_goals_against),
+        ("adj_goal_diff", &payload.adj_goal_diff),
+        ("fair_play", &payload.fair_play),
+    ] {
+        if let Some(v) = adj {
+            if v.len() != number_teams {
+                fail(
+                    name.into(),
+                    format!(
+                        "{} has length {}, expected {} (one per team)",
+                        name,
+                        v.len(),
+                        number_teams
+                    ),
+                );
+            }
+        }
+    }
+    if let Some(names) = &payload.team_names {
+        if names.len() != number_teams {
+            fail(
+                "team_names".into(),
+                format!(
+                    "team_names has length {}, expected {} (one per team)",
+                    names.len(),
+                    number_teams
+                ),
+            );
+        }
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                fail(
+                    format!("team_names[{}]", i),
+                    format!("team_names[{}] repeats the name '{}'", i, name),
+                );
+            }
+        }
+    }
+    if let Some(rules) = &payload.tiebreakers {
+        if rules.first() != Some(&TiebreakerRule::Points) {
+            fail(
+                "tiebreakers".into(),
+                "tiebreakers must start with points".into(),
+            );
+        }
+        for (i, rule) in rules.iter().enumerate() {
+            if rules[..i].contains(rule) {
+                fail(
+                    format!("tiebreakers[{}]", i),
+                    format!("tiebreakers[{}] repeats an earlier rule", i),
+                );
+            }
+        }
+    }
+    if let Some(weights) = &payload.mod_factor_weights {
+        if weights.len() != payload.schedule.len() {
+            fail(
+                "mod_factor_weights".into(),
+                format!(
+                    "mod_factor_weights has length {}, expected {} (one per schedule row)",
+                    weights.len(),
+                    payload.schedule.len()
+                ),
+            );
+        }
+        if let Some(i) = weights.iter().position(|w| !(w.is_finite() && *w >= 0.0)) {
+            fail(
+                format!("mod_factor_weights[{}]", i),
+                format!("mod_factor_weights[{}] must be a non-negative number", i),
+            );
+        }
+    }
+    if let Some(competitions) = &payload.competitions {
+        if competitions.len() != payload.schedule.len() {
+            fail(
+                "competitions".into(),
+                format!(
+                    "competitions has length {}, expected {} (one per schedule row)",
+                    competitions.len(),
+                    payload.schedule.len()
+                ),
+            );
+        }
+    }
+    if let Some(weights) = &payload.match_weights {
+        if weights.len() != payload.schedule.len() {
+            fail(
+                "match_weights".into(),
+                format!(
+                    "match_weights has length {}, expected {} (one per schedule row)",
+                    weights.len(),
+                    payload.schedule.len()
+                ),
+            );
+        }
+        if let Some(i) = weights
+            .iter()
+            .position(|w| w.is_some_and(|w| !(w.is_finite() && w >= 0.0)))
+        {
+            fail(
+                format!("match_weights[{}]", i),
+                format!("match_weights[{}] must be a non-negative number", i),
+            );
+        }
+    }
+    if let Some(weights) = &payload.competition_weights {
+        for (name, _) in weights
+            .iter()
+            .filter(|(_, w)| !(w.is_finite() && **w >= 0.0))
+        {
+            fail(
+                format!("competition_weights.{}", name),
+                format!("competition_weights.{} must be a non-negative number", name),
+            );
+        }
+    }
+    if let Some(glicko) = &payload.glicko {
+        if let Some(deviations) = &glicko.deviations {
+            if deviations.len() != number_teams {
+                fail(
+                    "glicko.deviations".into(),
+                    format!(
+                        "glicko.deviations has length {}, expected {} (one per team)",
+                        deviations.len(),
+                        number_teams
+                    ),
+                );
+            }
+            if let Some(i) = deviations.iter().position(|d| !(d.is_finite() && *d > 0.0)) {
+                fail(
+                    format!("glicko.deviations[{}]", i),
+                    format!("glicko.deviations[{}] must be a positive number", i),
+                );
+            }
+        }
+        for (name, value) in [
+            ("deviation", glicko.deviation),
+            ("volatility", glicko.volatility),
+            ("tau", glicko.tau),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                fail(
+                    format!("glicko.{}", name),
+                    format!("glicko.{} must be a positive number, got {}", name, value),
+                );
+            }
+        }
+    }
+    if let Some(strengths) = &payload.team_strengths {
+        if strengths.len() != number_teams {
+            fail(
+                "team_strengths".into(),
+                format!(
+                    "team_strengths has length {}, expected {} (one per team)",
+                    strengths.len(),
+                    number_teams
+                ),
+            );
+        }
+        let in_range = |v: f64| v > 0.0 && v <= TeamStrength::MAX;
+        if let Some(i) = strengths
+            .iter()
+            .position(|s| !(in_range(s.attack) && in_range(s.defense)))
+        {
+            fail(
+                format!("team_strengths[{}]", i),
+                format!(
+                    "team_strengths[{}]: attack and defense must be positive numbers up to {}",
+                    i,
+                    TeamStrength::MAX
+                ),
+            );
+        }
+    }
+    if let Some(weight) = payload.strength_weight {
+        if payload.team_strengths.is_none() {
+            fail(
+                "strength_weight".into(),
+                "strength_weight needs team_strengths".into(),
+            );
+        } else if !(0.0..=1.0).contains(&weight) {
+            fail(
+                "strength_weight".into(),
+                format!("strength_weight must be between 0 and 1, got {}", weight),
+            );
+        }
+    }
+    if let Some(threshold) = payload.sparse_threshold {
+        if !(0.0..1.0).contains(&threshold) {
+            fail(
+                "sparse_threshold".into(),
+                format!(
+                    "sparse_threshold must be at least 0 and below 1, got {}",
+                    threshold
+                ),
+            );
+        }
+    }
+    if let Some(poisson) = &payload.poisson_strengths {
+        if payload.team_strengths.is_some() {
+            fail(
+                "poisson_strengths".into(),
+                "poisson_strengths and team_strengths are mutually exclusive".into(),
+            );
+        }
+        if !(poisson.intercept.is_finite() && poisson.home_advantage.is_finite()) {
+            fail(
+                "poisson_strengths".into(),
+                "poisson_strengths: intercept and home_advantage must be finite".into(),
+            );
+        }
+        for (name, coefficients) in [("attack", &poisson.attack), ("defense", &poisson.defense)] {
+            if coefficients.len() != number_teams {
+                fail(
+                    format!("poisson_strengths.{}", name),
+                    format!(
+                        "poisson_strengths.{} has length {}, expected {} (one per team)",
+                        name,
+                        coefficients.len(),
+                        number_teams
+                    ),
+                );
+            } else if coefficients.iter().any(|c| !c.is_finite()) {
+                fail(
+                    format!("poisson_strengths.{}", name),
+                    format!("poisson_strengths.{} must be finite numbers", name),
+                );
+            }
+        }
+    }
+    let priors = [
+        ("market_values", payload.market_values.as_ref()),
+        ("strength_priors", payload.strength_priors.as_ref()),
+    ];
+    for (name, values) in priors {
+        let Some(values) = values else { continue };
+        if payload.team_strengths.is_some() || payload.poisson_strengths.is_some() {
+            fail(
+                name.into(),
+                format!(
+                    "{} cannot be combined with team_strengths or poisson_strengths",
+                    name
+                ),
+            );
+        }
+        if values.len() != number_teams {
+            fail(
+                name.into(),
+                format!(
+                    "{} has length {}, expected {} (one per team)",
+                    name,
+                    values.len(),
+                    number_teams
+                ),
+            );
+        } else if let Some(i) = values
+            .iter()
+            .position(|&v| !v.is_finite() || (name == "market_values" && v <= 0.0))
+        {
+            let expected = if name == "market_values" {
+                "a positive number"
+            } else {
+                "finite"
+            };
+            fail(
+                format!("{}[{}]", name, i),
+                format!("{}[{}] must be {}", name, i, expected),
+            );
+        }
+    }
+    if payload.market_values.is_some() && payload.strength_priors.is_some() {
+        fail(
+            "strength_priors".into(),
+            "strength_priors and market_values are mutually exclusive".into(),
+        );
+    }
+    if let Some(weight) = payload.prior_weight {
+        if payload.market_values.is_none() && payload.strength_priors.is_none() {
+            fail(
+                "prior_weight".into(),
+                "prior_weight needs market_values or strength_priors".into(),
+            );
+        } else if !(0.0..=1.0).contains(&weight) {
+            fail(
+                "prior_weight".into(),
+                format!("prior_weight must be between 0 and 1, got {}", weight),
+            );
+        }
+    }
+    if let Some(scale) = payload.elo_per_doubling {
+        if payload.market_values.is_none() {
+            fail(
+                "elo_per_doubling".into(),
+                "elo_per_doubling needs market_values".into(),
+            );
+        } else if !scale.is_finite() {
+            fail(
+                "elo_per_doubling".into(),
+                format!("elo_per_doubling must be a finite number, got {}", scale),
+            );
+        }
+    }
+    if let Some(matchdays) = &payload.matchdays {
+        if matchdays.len() != payload.schedule.len() {
+            fail(
+                "matchdays".into(),
+                format!(
+                    "matchdays has length {}, expected {} (one per schedule row)",
+                    matchdays.len(),
+                    payload.schedule.len()
+                ),
+            );
+        }
+        if let Some(i) = matchdays.iter().position(|&day| day == 0) {
+            fail(
+                format!("matchdays[{}]", i),
+                format!("matchdays[{}] must be at least 1", i),
+            );
+        }
+    }
+    if let Some(overrides) = &payload.overrides {
+        for (i, o) in overrides.iter().enumerate() {
+            let field = || format!("overrides[{}]", i);
+            match payload.schedule.get(o.row) {
+                None => fail(
+                    field(),
+                    format!(
+                        "override {}: row {} out of range (schedule has {} rows)",
+                        i,
+                        o.row,
+                        payload.schedule.len()
+                    ),
+                ),
+                Some(row) if row[2].is_some() => fail(
+                    field(),
+                    format!("override {}: row {} is already played", i, o.row),
+                ),
+                Some(_) => {}
+            }
+            if o.goals_home < 0 || o.goals_away < 0 {
+                fail(
+                    field(),
+                    format!("override {}: goals must not be negative", i),
+                );
+            }
+            if overrides[..i].iter().any(|earlier| earlier.row == o.row) {
+                fail(
+                    field(),
+                    format!("override {}: row {} is overridden twice", i, o.row),
+                );
+            }
+        }
+    }
+    match payload.simulate_until_matchday {
+        Some(0) => fail(
+            "simulate_until_matchday".into(),
+            "simulate_until_matchday must be at least 1".into(),
+        ),
+        Some(_) if payload.matchdays.is_none() => fail(
+            "simulate_until_matchday".into(),
+            "simulate_until_matchday needs matchdays".into(),
+        ),
+        _ => {}
+    }
+    if let Some(scenarios) = &payload.scenarios {
+        for (field, set) in [
+            ("time_budget_ms", payload.time_budget_ms.is_some()),
+            ("target_se", payload.target_se.is_some()),
+        ] {
+            if set {
+                fail(
+                    field.into(),
+                    format!("{} is not supported with scenarios", field),
+                );
+            }
+        }
+        if scenarios.is_empty() || scenarios.len() > MAX_SCENARIOS {
+            fail(
+                "scenarios".into(),
+                format!(
+                    "scenarios must have between 1 and {} entries, got {}",
+                    MAX_SCENARIOS,
+                    scenarios.len()
+                ),
+            );
+        }
+        for (i, scenario) in scenarios.iter().enumerate() {
+            if !(scenario.weight.is_finite() && scenario.weight > 0.0) {
+                fail(
+                    format!("scenarios[{}].weight", i),
+                    format!("scenario {}: weight must be positive", i),
+                );
+            }
+            if let Some(scale) = scenario.elo_scale {
+                if !(scale.is_finite() && scale >= 0.0) {
+                    fail(
+                        format!("scenarios[{}].elo_scale", i),
+                        format!("scenario {}: elo_scale must not be negative", i),
+                    );
+                }
+            }
+        }
+    }
+    if let Some(table) = &payload.current_table {
+        if table.len() != number_teams {
+            fail(
+                "current_table".into(),
+                format!(
+                    "current_table has length {}, expected {} (one per team)",
+                    table.len(),
+                    number_teams
+                ),
+            );
+        }
+        // Table-only mode: the table already contains every played match,
+        // so a played schedule row would be counted twice.
+        if let Some(i) = payload
+            .schedule
+            .iter()
+            .position(|row| row[2].is_some() || row[3].is_some())
+        {
+            fail(
+                format!("schedule[{}]", i),
+                format!(
+                    "schedule row {}: played matches are not allowed when current_table is given",
+                    i
+                ),
+            );
+        }
+        for (i, entry) in table.iter().enumerate() {
+            if entry.played < 0 {
+                fail(
+                    format!("current_table[{}].played", i),
+                    format!("current_table row {}: played must not be negative", i),
+                );
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidFields(errors))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
+    status: String,
+    version: String,
+    performance: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Service is up", body = HealthResponse))
+)]
+pub async fn health_check() -> impl IntoResponse {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        performance: "370,000+ simulations/second".to_string(),
+    })
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SimulateRequest {
+    /// Schedule matrix: each row is [team_home, team_away, goals_home, goals_away]
+    /// goals are null/None for unplayed matches
+    schedule: Vec<[Option<i32>; 4]>,
+
+    /// Initial ELO values for each team
+    elo_values: Vec<f64>,
+
+    /// Team names (optional, for display)
+    team_names: Option<Vec<String>>,
+
+    /// Number of Monte Carlo iterations (default: 10000)
+    iterations: Option<usize>,
+
+    /// Wall-clock budget in milliseconds (optional): batches of iterations
+    /// run until it is spent, at most `iterations` (default: the server's
+    /// max_iterations), and the response reports how many ran and the
+    /// precision reached. Covers the main pass only, not second passes.
+    time_budget_ms: Option<u64>,
+
+    /// Standard error to reach (optional, e.g. 0.005): batches of
+    /// iterations run until no probability_matrix cell's standard error is
+    /// above it, at most `iterations` (default: the server's
+    /// max_iterations). Combines with time_budget_ms; whichever is met
+    /// first stops the run.
+    target_se: Option<f64>,
+
+    /// "monte_carlo" samples every remaining fixture; "hybrid" enumerates
+    /// the home win, draw and away win of each final-matchday fixture
+    /// exactly and samples the rest (optional). Unset, the hybrid solver is
+    /// picked once at most two rounds remain.
+    solver: Option<Solver>,
+
+    /// ELO modification factor (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Multiplier of mod_factor per schedule row (optional), e.g. above 1
+    /// for the last matchdays or cup games; applies to played and
+    /// simulated matches alike
+    mod_factor_weights: Option<Vec<f64>>,
+
+    /// Competition of each schedule row (optional), e.g. "league", "cup"
+    /// or "friendly"; null for none
+    competitions: Option<Vec<Option<String>>>,
+
+    /// Multiplier of mod_factor per competition (optional), e.g.
+    /// {"friendly": 0.5, "cup": 1.5}; competitions not listed count once.
+    /// Multiplies mod_factor_weights
+    competition_weights: Option<BTreeMap<String, f64>>,
+
+    /// Multiplier of mod_factor per schedule row (optional), overriding
+    /// the row's competition weight; null keeps the competition's.
+    /// Multiplies mod_factor_weights
+    match_weights: Option<Vec<Option<f64>>>,
+
+    /// Home advantage in ELO points (default: 65)
+    home_advantage: Option<f64>,
+
+    /// Rating system updating the strengths as the season is simulated
+    /// (default: "elo")
+    rating_system: Option<RatingSystemKind>,
+
+    /// Starting deviations, volatility and tau of "glicko2" ratings
+    /// (optional); elo_values are the starting ratings
+    glicko: Option<GlickoSettings>,
+
+    /// Attack and defense per team (optional), e.g. expected goals scored
+    /// and conceded per match from a shot model, each above 0 and at most
+    /// 20; goals are then drawn from these instead of the Elo goal model
+    team_strengths: Option<Vec<TeamStrength>>,
+
+    /// Share of team_strengths in the goal means, 0 to 1 (default: 1);
+    /// the rest comes from the Elo goal model
+    strength_weight: Option<f64>,
+
+    /// Log-linear attack and defense coefficients per team (optional), as
+    /// returned by /fit/team-strengths; goals are then drawn from these
+    /// instead of the Elo goal model
+    poisson_strengths: Option<PoissonStrengths>,
+
+    /// Squad market value per team (optional, any currency unit); turned
+    /// into strength priors, elo_per_doubling ELO points per doubling of
+    /// value, that blend with the ratings in the Elo goal model
+    market_values: Option<Vec<f64>>,
+
+    /// ELO points between a squad and one worth half as much (default:
+    /// 100)
+    elo_per_doubling: Option<f64>,
+
+    /// Prior strength per team in ELO points (optional), instead of
+    /// market_values; only differences between teams matter
+    strength_priors: Option<Vec<f64>>,
+
+    /// Share of the priors in the strength difference the goals are
+    /// computed from, 0 to 1 (default: 0.5); the rest comes from the
+    /// ratings. Lower it as results accumulate.
+    prior_weight: Option<f64>,
+
+    /// Point adjustments per team (optional)
+    adj_points: Option<Vec<i32>>,
+
+    /// Goal adjustments per team (optional)
+    adj_goals: Option<Vec<i32>>,
+
+    /// Goals against adjustments per team (optional)
+    adj_goals_against: Option<Vec<i32>>,
+
+    /// Goal difference adjustments per team (optional)
+    adj_goal_diff: Option<Vec<i32>>,
+
+    /// Order of the final table (default: points, goal_difference,
+    /// goals_for). Must start with points. Head-to-head criteria only see
+    /// the matches in the schedule, so in table-only mode just the
+    /// remaining ones.
+    tiebreakers: Option<Vec<TiebreakerRule>>,
+
+    /// Fair play points per team, fewer is better (optional, for the
+    /// fair_play tiebreaker)
+    fair_play: Option<Vec<i32>>,
+
+    /// Current table per team (optional). Enables table-only mode: the
+    /// schedule then holds only the remaining fixtures and elo_values are
+    /// used as-is instead of being replayed from the match history.
+    current_table: Option<Vec<TableEntry>>,
+
+    /// Matchday (round) of each schedule row, 1-based (optional). Enables
+    /// the check that no team plays twice on a matchday.
+    matchdays: Option<Vec<u32>>,
+
+    /// Stop the season after this matchday (optional, needs `matchdays`):
+    /// later fixtures are ignored, played or not, so the probabilities are
+    /// those of the table after that matchday
+    simulate_until_matchday: Option<u32>,
+
+    /// Results to assume for unplayed matches (optional), e.g. "Dortmund
+    /// beats Bayern 2-0"; the rest of the season is simulated as usual
+    overrides: Option<Vec<ResultOverride>>,
+
+    /// Random number generator backend (default: "std")
+    rng: Option<RngKind>,
+
+    /// Master seed of the simulation (optional): the same request with the
+    /// same seed reproduces the same probabilities exactly
+    seed: Option<u64>,
+
+    /// League preset (optional), e.g. "bundesliga": the league's
+    /// tiebreakers and structure unless the request sets its own. The
+    /// schedule must have the league's team count.
+    league: Option<String>,
+
+    /// League template for zone probabilities (optional): a template id such
+    /// as "bundesliga", or "auto" to apply the template closest to the
+    /// schedule's shape
+    structure: Option<String>,
+
+    /// URL to POST the finished SimulateResponse to (optional). The request
+    /// is then answered with 202 Accepted immediately instead of blocking.
+    /// Only honoured by /simulate; ignored inside batches and streams.
+    callback_url: Option<String>,
+
+    /// Scenario mixture (optional): model variants simulated side by side
+    /// and combined with their weights. Not supported by /simulate/stream.
+    scenarios: Option<Vec<ScenarioSpec>>,
+
+    /// Also estimate end-of-season award probabilities (default: false).
+    /// Runs a second pass with the same number of iterations.
+    awards: Option<bool>,
+
+    /// Also return the expected table: mean simulated points, goal
+    /// difference and position with its spread, ordered by expected points
+    /// (default: false). Runs a second pass with the same number of
+    /// iterations.
+    expected_table: Option<bool>,
+
+    /// Also rank the unplayed matches by how much their result moves the
+    /// title, top-four and relegation probabilities (default: false). Runs
+    /// a second pass with the same number of iterations.
+    match_importance: Option<bool>,
+
+    /// Also return up to this many sampled final tables (default: 0), for
+    /// joint statistics the probability matrix cannot express. Drawn in a
+    /// second pass of that many iterations, capped at `iterations`.
+    return_samples: Option<usize>,
+
+    /// Return only the matrix cells above this probability, as
+    /// `[team, position, probability]` triples in probability_cells,
+    /// instead of the full probability_matrix (optional, 0 to 1)
+    sparse_threshold: Option<f64>,
+}
+
+/// An assumed result for an unplayed match.
+#[derive(Deserialize, ToSchema)]
+pub struct ResultOverride {
+    /// 0-based row of the schedule
+    row: usize,
+    goals_home: i32,
+    goals_away: i32,
+}
+
+/// One model variant of a scenario mixture. Unset fields inherit the
+/// request's values.
+#[derive(Deserialize, ToSchema)]
+pub struct ScenarioSpec {
+    name: String,
+
+    /// Relative weight, e.g. 70 / 20 / 10
+    weight: f64,
+
+    home_advantage: Option<f64>,
+
+    mod_factor: Option<f64>,
+
+    /// Scale every team's distance from the mean Elo (default: 1). Values
+    /// below 1 model a more even, higher-variance season.
+    elo_scale: Option<f64>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct SimulateResponse {
+    /// Probability matrix: rows are teams (in final rank order), columns are positions
+    /// Values are probabilities [0,1] of team finishing in that position
+    /// (empty when the request set sparse_threshold)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) probability_matrix: Vec<Vec<f64>>,
+
+    /// The cells of probability_matrix above the request's
+    /// sparse_threshold (sparse requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) probability_cells: Option<Vec<ProbabilityCell>>,
+
+    /// Team names in the same order as probability_matrix rows
+    pub(crate) team_names: Vec<String>,
+
+    /// Number of simulations actually performed
+    pub(crate) simulations_performed: usize,
+
+    /// Largest standard error of a probability_matrix cell,
+    /// `sqrt(p (1 - p) / simulations_performed)` (time_budget_ms and
+    /// target_se requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_standard_error: Option<f64>,
+
+    /// Time taken in milliseconds
+    pub(crate) time_ms: u128,
+
+    /// Deterministic projected final table from expected points per
+    /// remaining fixture (no sampling), in projected rank order
+    pub(crate) projected_table: Vec<ProjectedRow>,
+
+    /// Best and worst final position each team can still reach whatever
+    /// the remaining results, rows in probability_matrix order. Unlike a
+    /// simulated probability of 0 or 1, these are certain.
+    pub(crate) position_bounds: Vec<TeamPositionBounds>,
+
+    /// League template applied for zone probabilities (if requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) structure: Option<AppliedStructure>,
+
+    /// Per-team zone probabilities, rows in probability_matrix order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) zone_probabilities: Option<Vec<TeamZoneProbabilities>>,
+
+    /// True if this response was served from the result cache
+    pub(crate) cache_hit: bool,
+
+    /// Per-scenario matrices and probability bands (mixture requests only);
+    /// probability_matrix is then the weighted mixture
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) scenarios: Option<ScenarioBands>,
+
+    /// Per-team probabilities of the season's superlatives, in team input
+    /// order (awards requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) awards: Option<Vec<TeamAwards>>,
+
+    /// Simulated expected table, by expected points (expected_table
+    /// requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expected_table: Option<Vec<ExpectedStanding>>,
+
+    /// Unplayed matches, most important first (match_importance requests
+    /// only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) match_importance: Option<Vec<MatchImportance>>,
+
+    /// Sampled final tables, positions and points in team input order
+    /// (return_samples requests only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) samples: Option<Vec<SeasonSample>>,
+
+    /// Signs of a malformed schedule that did not stop the simulation: a
+    /// team playing twice on a matchday, or an incomplete or uneven round
+    /// robin (not checked in table-only mode)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) schedule_warnings: Vec<String>,
+
+    /// The request's seed, if it set one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+
+    /// How the result was produced, for audits and reruns
+    pub(crate) metadata: SimulationMetadata,
+}
+
+/// Engine build and effective inputs of a simulation. Sending the same body
+/// with `seed` set to `metadata.seed` to the same build reproduces the
+/// response.
+#[derive(Clone, Serialize, ToSchema)]
+pub struct SimulationMetadata {
+    /// Version of the simulator
+    pub(crate) engine_version: &'static str,
+
+    /// Commit the server was built from, or "unknown"
+    pub(crate) git_sha: &'static str,
+
+    /// Engine parameters after defaults and table baselines were applied
+    #[schema(value_type = Object)]
+    pub(crate) params: SimulationParams,
+
+    /// Master seed of the run, drawn from entropy for unseeded requests
+    pub(crate) seed: u64,
+
+    /// Iterations simulated
+    pub(crate) iterations: usize,
+
+    /// Solver the probabilities come from
+    pub(crate) solver: Solver,
+
+    /// Time the simulation took, excluding cache lookups and serialization
+    pub(crate) wall_time_ms: u128,
+
+    /// Worker threads of the simulation pool
+    pub(crate) threads: usize,
+}
+
+impl SimulateResponse {
+    /// Rows are teams in `team_names` order, columns positions.
+    pub fn probability_matrix(&self) -> &[Vec<f64>] {
+        &self.probability_matrix
+    }
+
+    pub fn team_names(&self) -> &[String] {
+        &self.team_names
+    }
+
+    /// The full probability matrix, rebuilt from probability_cells for
+    /// sparse responses.
+    pub(crate) fn dense_matrix(&self) -> Cow<'_, [Vec<f64>]> {
+        let Some(cells) = &self.probability_cells else {
+            return Cow::Borrowed(&self.probability_matrix);
+        };
+        let n_teams = self.team_names.len();
+        let mut matrix = vec![vec![0.0; n_teams]; n_teams];
+        for &ProbabilityCell(team, position, probability) in cells {
+            matrix[team][position - 1] = probability;
+        }
+        Cow::Owned(matrix)
+    }
+}
+
+impl Tabular for SimulateResponse {
+    fn table(&self) -> Report {
+        Report::probabilities(&self.team_names, &self.dense_matrix())
+    }
+}
+
+/// One cell of a sparse probability matrix: the 0-based row of the team in
+/// team_names, the 1-based position and the probability.
+#[derive(Clone, Serialize, ToSchema)]
+pub struct ProbabilityCell(pub(crate) usize, pub(crate) usize, pub(crate) f64);
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct ScenarioBands {
+    /// Component results, matrices in probability_matrix row order
+    components: Vec<ScenarioResult>,
+
+    /// Smallest probability any scenario gives, per team and position
+    lower_bound: Vec<Vec<f64>>,
+
+    /// Largest probability any scenario gives, per team and position
+    upper_bound: Vec<Vec<f64>>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct AppliedStructure {
+    #[serde(flatten)]
+    structure: LeagueStructure,
+
+    /// True if the template was picked by schedule-shape detection
+    detected: bool,
+
+    /// Differences between the schedule and the applied template
+    warnings: Vec<String>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct TeamZoneProbabilities {
+    pub(crate) team_name: String,
+
+    /// Zone name -> probability of finishing in it
+    pub(crate) zones: BTreeMap<String, f64>,
+
+    /// Zones the team is mathematically certain to finish in
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) clinched: Vec<String>,
+
+    /// Zones the team can no longer finish in
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) eliminated: Vec<String>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct TeamPositionBounds {
+    pub(crate) team_name: String,
+    pub(crate) best_position: usize,
+    pub(crate) worst_position: usize,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct ProjectedRow {
+    pub(crate) team_name: String,
+    pub(crate) position: usize,
+    pub(crate) expected_points: f64,
+    pub(crate) expected_goals_for: f64,
+    pub(crate) expected_goals_against: f64,
+    pub(crate) expected_goal_difference: f64,
+}
+
+/// Simulate one league.
+///
+/// Answers 202 and POSTs the result to `callback_url` later if one is given.
+#[utoipa::path(
+    post,
+    path = "/simulate",
+    tag = "simulation",
+    request_body = SimulateRequest,
+    params(
+        ReportOptions,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a response the client already holds")
+    ),
+    responses(
+        (status = 200, description = "Simulation result; CSV and Markdown hold the table selected by `table`", content(
+            (SimulateResponse = "application/json"),
+            (SimulateResponse = "application/msgpack"),
+            (String = "text/csv"),
+            (String = "text/markdown")
+        )),
+        (status = 202, description = "Accepted; result will be POSTed to callback_url", body = CallbackAccepted),
+        (status = 304, description = "Unchanged since the given ETag"),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_league(
+    State(state): State<AppState>,
+    Query(report): Query<ReportOptions>,
+    headers: HeaderMap,
+    ApiJson(mut payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let format = report.format(&headers);
+    if format == ReportFormat::Arrow {
+        return Err(arrow_unavailable());
+    }
+
+    let callback_url = payload.callback_url.take();
+    if let Some(url) = &callback_url {
+        state
+            .webhooks
+            .check_url(url)
+            .await
+            .map_err(|e| ApiError::field("callback_url", e))?;
+    }
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+
+    let Some(callback_url) = callback_url else {
+        // A time budget's iteration count follows the clock: such runs are
+        // neither cached nor given validators a later run could match.
+        if !job.reproducible() {
+            let response = state.workers.simulate(job, start).await?;
+            crate::metrics::add_iterations(response.simulations_performed);
+            return Ok(report.render(response, format));
+        }
+        let key = job.cache_key();
+        let etag = format!("W/\"{:032x}{}\"", key.digest(), report.etag_suffix(format));
+        let cache_control = format!("private, max-age={}", state.cache.ttl().as_secs());
+        let validators = [
+            (header::ETAG, etag.clone()),
+            (header::CACHE_CONTROL, cache_control),
+            (header::VARY, "accept".to_string()),
+        ];
+
+        // The client already holds the response for these exact inputs.
+        if if_none_match(&headers, &etag) {
+            return Ok((StatusCode::NOT_MODIFIED, validators).into_response());
+        }
+
+        if let Some(mut cached) = state.cache.get(&key) {
+            tracing::info!(cache_hit = true, "simulation served from cache");
+            cached.cache_hit = true;
+            cached.time_ms = start.elapsed().as_millis();
+            return Ok((validators, report.render(cached, format)).into_response());
+        }
+
+        // Off the async workers, so the request timeout can fire; across
+        // the worker instances if any are configured.
+        let response = state.workers.simulate(job, start).await?;
+        crate::metrics::add_iterations(response.simulations_performed);
+        state.cache.insert(key, response.clone());
+        return Ok((validators, report.render(response, format)).into_response());
+    };
+
+    let accepted = CallbackAccepted {
+        status: "accepted",
+        callback_url: callback_url.clone(),
+    };
+    let workers = state.workers.clone();
+    // The run keeps the request's slot; the delivery and its retries don't.
+    let simulation = overload::in_slot(async move { workers.simulate(job, start).await });
+    tokio::spawn(
+        async move {
+            let response = simulation.await;
+            // A panicking simulation has nothing to deliver.
+            if let Ok(response) = response {
+                if let Err(e) = state.webhooks.deliver(&callback_url, &response).await {
+                    tracing::warn!(callback_url, error = %e, "webhook delivery failed");
+                }
+            }
+        }
+        .instrument(Span::current()),
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(accepted)).into_response())
+}
+
+/// JSON response body, serialized inside its own span.
+pub(crate) fn serialize(value: impl Serialize) -> Response {
+    info_span!("serialize").in_scope(|| Json(value).into_response())
+}
+
+/// Whether an If-None-Match header matches `etag` (weak comparison, so
+/// `W/` prefixes are ignored) or is `*`.
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// Immediate answer to a /simulate request with a callback_url.
+#[derive(Serialize, ToSchema)]
+pub struct CallbackAccepted {
+    status: &'static str,
+    callback_url: String,
+}
+
+/// A validated request converted to engine inputs.
+#[derive(Clone)]
+pub(crate) struct SimulationJob {
+    pub(crate) season: Season,
+    pub(crate) params: SimulationParams,
+    pub(crate) team_names: Vec<String>,
+    structure: Option<AppliedStructure>,
+    pub(crate) scenarios: Option<Vec<Scenario>>,
+    awards: bool,
+    expected_table: bool,
+    match_importance: bool,
+    return_samples: usize,
+    sparse_threshold: Option<f64>,
+    schedule_warnings: Vec<String>,
+    /// The request's seed; `params.seed` is always set, drawn from entropy
+    /// for unseeded requests so the response can report it
+    requested_seed: Option<u64>,
+    /// Last matchday simulated, if the season was cut off there
+    pub(crate) until_matchday: Option<u32>,
+    /// Wall-clock budget of the main pass; `params.iterations` is then
+    /// only its upper bound
+    time_budget: Option<std::time::Duration>,
+    /// Standard error at which the main pass stops early
+    target_se: Option<f64>,
+    /// Solver of the main pass, picked from the remaining fixtures if the
+    /// request left it open
+    pub(crate) solver: Solver,
+    /// The request's solver, if it named one
+    requested_solver: Option<Solver>,
+    /// The league preset the request named
+    pub(crate) league: Option<LeaguePreset>,
+}
+
+impl SimulationJob {
+    pub(crate) fn from_request(
+        payload: SimulateRequest,
+        config: &RuntimeConfig,
+    ) -> Result<Self, ApiError> {
+        let _span = info_span!("validate").entered();
+        validate_request(&payload, &config.limits)?;
+
+        let number_teams = payload.elo_values.len();
+        let league = match payload.league.as_deref() {
+            None => None,
+            Some(id) => {
+                let preset = preset(id)
+                    .ok_or_else(|| ApiError::field("league", format!("unknown league '{}'", id)))?;
+                if preset.number_teams != number_teams {
+                    return Err(ApiError::field(
+                        "league",
+                        format!(
+                            "league '{}' has {} teams, schedule has {}",
+                            id, preset.number_teams, number_teams
+                        ),
+                    ));
+                }
+                Some(preset)
+            }
+        };
+
+        // Convert schedule to Match structs
+        let mut matches: Vec<Match> = payload
+            .schedule
+            .iter()
+            .enumerate()
+            .map(|(i, row)| Match {
+                // Validated above: indices are Some and within 1..=number_teams.
+                // R uses 1-indexed, Rust uses 0-indexed.
+                team_home: row[0].unwrap() as usize - 1,
+                team_away: row[1].unwrap() as usize - 1,
+                goals_home: row[2],
+                goals_away: row[3],
+                matchday: payload.matchdays.as_ref().map(|days| days[i]),
+                competition: payload
+                    .competitions
+                    .as_ref()
+                    .and_then(|competitions| competitions[i].clone()),
+                weight: payload
+                    .match_weights
+                    .as_ref()
+                    .and_then(|weights| weights[i]),
+            })
+            .collect();
+        // Assumed results count as played, Elo updates included.
+        for o in payload.overrides.iter().flatten() {
+            matches[o.row].goals_home = Some(o.goals_home);
+            matches[o.row].goals_away = Some(o.goals_away);
+        }
+
+        // Create Season struct
+        let mut season = Season {
+            matches,
+            team_elos: payload.elo_values,
+            number_teams,
+        };
+        // Warnings describe the schedule as given, before any cutoff.
+        let schedule_warnings = schedule_warnings(&season, payload.current_table.is_none());
+        let mut mod_factor_weights = payload.mod_factor_weights;
+        if let Some(last) = payload.simulate_until_matchday {
+            // Weights follow their rows out of the schedule.
+            if let Some(weights) = &mut mod_factor_weights {
+                let mut kept = season
+                    .matches
+                    .iter()
+                    .map(|m| m.matchday.is_none_or(|day| day <= last));
+                weights.retain(|_| kept.next().unwrap_or(true));
+            }
+            season.truncate_to_matchday(last);
+        }
+
+        // Set simulation parameters
+        let mut params = SimulationParams {
+            // A budget or precision goal rather than the default count
+            // bounds such runs.
+            iterations: payload.iterations.unwrap_or(
+                if payload.time_budget_ms.is_some() || payload.target_se.is_some() {
+                    config.limits.max_iterations
+                } else {
+                    config.simulation.iterations
+                },
+            ),
+            mod_factor: payload.mod_factor.unwrap_or(config.simulation.mod_factor),
+            mod_factor_weights,
+            competition_weights: payload.competition_weights,
+            home_advantage: payload
+                .home_advantage
+                .unwrap_or(config.simulation.home_advantage),
+            tore_slope: config.simulation.tore_slope,
+            tore_intercept: config.simulation.tore_intercept,
+            adj_points: payload.adj_points,
+            adj_goals: payload.adj_goals,
+            adj_goals_against: payload.adj_goals_against,
+            adj_goal_diff: payload.adj_goal_diff,
+            adj_played: None,
+            rng: payload.rng.unwrap_or_default(),
+            seed: Some(payload.seed.unwrap_or_else(entropy_seed)),
+            tiebreakers: payload
+                .tiebreakers
+                .or_else(|| league.as_ref().map(|l| l.tiebreakers.clone()))
+                .unwrap_or_else(|| TiebreakerRule::DEFAULT_CHAIN.to_vec()),
+            fair_play: payload.fair_play,
+            rating_system: payload.rating_system.unwrap_or_default(),
+            glicko: payload.glicko.unwrap_or_default(),
+            team_strengths: payload.team_strengths,
+            strength_weight: payload.strength_weight.unwrap_or(1.0),
+            poisson_strengths: payload.poisson_strengths,
+            strength_priors: payload.strength_priors.or_else(|| {
+                payload.market_values.map(|values| {
+                    priors_from_market_values(
+                        &values,
+                        payload.elo_per_doubling.unwrap_or(DEFAULT_ELO_PER_DOUBLING),
+                    )
+                })
+            }),
+            prior_weight: payload.prior_weight.unwrap_or(0.5),
+        };
+        if let Some(table) = &payload.current_table {
+            params.apply_table_baseline(table);
+        }
+
+        let scenarios = payload.scenarios.map(|specs| {
+            specs
+                .into_iter()
+                .map(|spec| Scenario {
+                    name: spec.name,
+                    weight: spec.weight,
+                    params: SimulationParams {
+                        home_advantage: spec.home_advantage.unwrap_or(params.home_advantage),
+                        mod_factor: spec.mod_factor.unwrap_or(params.mod_factor),
+                        ..params.clone()
+                    },
+                    elo_scale: spec.elo_scale.unwrap_or(1.0),
+                })
+                .collect()
+        });
+
+        // Generate team names if not provided
+        let team_names = payload.team_names.unwrap_or_else(|| {
+            (0..number_teams)
+                .map(|i| format!("Team_{}", i + 1))
+                .collect()
+        });
+
+        let requested_structure = payload
+            .structure
+            .as_deref()
+            .or(league.as_ref().map(|l| l.structure.as_str()));
+        let structure = match requested_structure {
+            None => None,
+            Some("auto") => {
+                let suggestion = detect_structure_among(&season, &config.templates());
+                Some(AppliedStructure {
+                    structure: suggestion.structure,
+                    detected: true,
+                    warnings: suggestion.warnings,
+                })
+            }
+            Some(id) => {
+                let structure = config.template(id).ok_or_else(|| {
+                    ApiError::field("structure", format!("unknown structure '{}'", id))
+                })?;
+                if structure.number_teams != number_teams {
+                    return Err(ApiError::field(
+                        "structure",
+                        format!(
+                            "structure '{}' is for {} teams, schedule has {}",
+                            id, structure.number_teams, number_teams
+                        ),
+                    ));
+                }
+                Some(AppliedStructure {
+                    structure,
+                    detected: false,
+                    warnings: Vec::new(),
+                })
+            }
+        };
+
+        // Mixtures and runs that stop early sample every fixture.
+        let sampled_only = match (&scenarios, payload.time_budget_ms, payload.target_se) {
+            (Some(_), _, _) => Some("scenarios"),
+            (None, Some(_), _) => Some("time_budget_ms"),
+            (None, None, Some(_)) => Some("target_se"),
+            (None, None, None) => None,
+        };
+        let solver = match (payload.solver, sampled_only) {
+            (Some(Solver::Hybrid), Some(field)) => {
+                return Err(ApiError::field(
+                    "solver",
+                    format!("the hybrid solver is not supported with {}", field),
+                ));
+            }
+            (Some(Solver::Hybrid), None) if final_matchday_fixtures(&season).is_none() => {
+                return Err(ApiError::field(
+                    "solver",
+                    format!(
+                        "the hybrid solver needs 1 to {} unplayed fixtures at the end of \
+                         the schedule, on one matchday",
+                        HYBRID_MAX_FIXTURES
+                    ),
+                ));
+            }
+            (Some(solver), _) => solver,
+            (None, Some(_)) => Solver::MonteCarlo,
+            (None, None) => auto_solver(&season, &params),
+        };
+
+        Ok(Self {
+            season,
+            params,
+            team_names,
+            structure,
+            scenarios,
+            awards: payload.awards.unwrap_or(false),
+            expected_table: payload.expected_table.unwrap_or(false),
+            match_importance: payload.match_importance.unwrap_or(false),
+            return_samples: payload.return_samples.unwrap_or(0),
+            sparse_threshold: payload.sparse_threshold,
+            requested_seed: payload.seed,
+            schedule_warnings,
+            until_matchday: payload.simulate_until_matchday,
+            time_budget: payload.time_budget_ms.map(std::time::Duration::from_millis),
+            target_se: payload.target_se,
+            solver,
+            requested_solver: payload.solver,
+            league,
+        })
+    }
+
+    /// Request field of the rule stopping the main pass early, if any.
+    pub(crate) fn stopping_rule(&self) -> Option<&'static str> {
+        match (self.time_budget, self.target_se) {
+            (Some(_), _) => Some("time_budget_ms"),
+            (None, Some(_)) => Some("target_se"),
+            (None, None) => None,
+        }
+    }
+
+    /// Iterations a full run of [`simulate`](Self::simulate) costs: the
+    /// main pass plus one as long for each of awards, expected_table and
+    /// match_importance, and the sample pass.
+    pub(crate) fn iterations_per_run(&self) -> usize {
+        let passes = [self.awards, self.expected_table, self.match_importance]
+            .iter()
+            .filter(|&&on| on)
+            .count();
+        let iterations = self.params.iterations;
+        iterations * (1 + passes) + self.return_samples.min(iterations)
+    }
+
+    /// Whether equal inputs give an equal response, so it may be cached and
+    /// validated. Not for a time budget, which stops with the clock.
+    pub(crate) fn reproducible(&self) -> bool {
+        self.time_budget.is_none()
+    }
+
+    /// The applied league template, if any.
+    pub(crate) fn structure(&self) -> Option<&LeagueStructure> {
+        self.structure.as_ref().map(|applied| &applied.structure)
+    }
+
+    /// Canonical engine inputs, used as the result cache key. Serializing
+    /// the converted inputs (rather than the raw payload) makes field order
+    /// and omitted defaults irrelevant.
+    pub(crate) fn cache_key(&self) -> CacheKey {
+        // Keyed on the requested seed: unseeded requests share results.
+        let params = SimulationParams {
+            seed: self.requested_seed,
+            ..self.params.clone()
+        };
+        let canonical = serde_json::to_string(&(
+            &self.season,
+            &params,
+            &self.team_names,
+            &self.structure,
+            &self.scenarios,
+            self.awards,
+            self.expected_table,
+            self.match_importance,
+            self.return_samples,
+            self.sparse_threshold,
+            // Also covers fixtures a matchday cutoff removed from the season.
+            &self.schedule_warnings,
+            self.time_budget,
+            self.target_se,
+            self.solver,
+        ))
+        .expect("engine inputs serialize");
+        CacheKey::new(canonical)
+    }
+
+    /// Run the simulation, as a weighted mixture if the request defined
+    /// scenarios, and build the wire response.
+    pub(crate) fn simulate(&self, start: std::time::Instant) -> SimulateResponse {
+        if self.stopping_rule().is_some() {
+            let never = AtomicBool::new(false);
+            return self
+                .simulate_until(start, &never)
+                .expect("run without a cancellation signal always completes");
+        }
+        let Some(scenarios) = &self.scenarios else {
+            let run = match self.solver {
+                Solver::MonteCarlo => run_monte_carlo_simulation,
+                Solver::Hybrid => run_hybrid_simulation,
+            };
+            let result = run(&self.season, &self.params, self.team_names.clone());
+            return self.respond(result, start.elapsed());
+        };
+
+        let mixture = run_scenario_mixture(
+            &self.season,
+            scenarios,
+            self.params.iterations,
+            self.team_names.clone(),
+        );
+        let mut response = self.respond(mixture.result, start.elapsed());
+        response.simulations_performed = mixture.scenarios.iter().map(|s| s.iterations).sum();
+        response.metadata.iterations = response.simulations_performed;
+        response.scenarios = Some(ScenarioBands {
+            components: mixture.scenarios,
+            lower_bound: mixture.lower_bound,
+            upper_bound: mixture.upper_bound,
+        });
+        response
+    }
+
+    /// Sample every fixture from here on, for routes without the hybrid
+    /// solver (`route` completes the error, e.g. "in sessions"). An error
+    /// if the request asked for the hybrid solver.
+    pub(crate) fn monte_carlo_only(&mut self, route: &str) -> Result<(), ApiError> {
+        if self.requested_solver == Some(Solver::Hybrid) {
+            return Err(ApiError::field(
+                "solver",
+                format!("the hybrid solver is not supported {}", route),
+            ));
+        }
+        self.solver = Solver::MonteCarlo;
+        Ok(())
+    }
+
+    /// Run batches of iterations until the time budget after `start` is
+    /// spent, the target standard error is reached or `cancel` is set
+    /// (None), and build the wire response over the iterations that ran.
+    fn simulate_until(
+        &self,
+        start: std::time::Instant,
+        cancel: &AtomicBool,
+    ) -> Option<SimulateResponse> {
+        let deadline = self.time_budget.map(|budget| start + budget);
+        let (result, done) = run_monte_carlo_simulation_until(
+            &self.season,
+            &self.params,
+            self.team_names.clone(),
+            CANCEL_CHECK_BATCH,
+            cancel,
+            |progress| {
+                deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline)
+                    || self
+                        .target_se
+                        .is_some_and(|target| progress.max_standard_error() <= target)
+            },
+        )?;
+        // Second passes and the metadata follow the iterations that ran.
+        let job = SimulationJob {
+            params: SimulationParams {
+                iterations: done,
+                ..self.params.clone()
+            },
+            ..self.clone()
+        };
+        Some(job.respond(result, start.elapsed()))
+    }
+
+    /// Build the wire response for a finished simulation.
+    pub(crate) fn respond(
+        &self,
+        result: SimulationResult,
+        elapsed: std::time::Duration,
+    ) -> SimulateResponse {
+        let _span = info_span!("respond").entered();
+        tracing::info!(
+            iterations = self.params.iterations,
+            teams = self.season.number_teams,
+            matches = self.season.matches.len(),
+            remaining = self.season.matches.iter().filter(|m| m.goals_home.is_none()).count(),
+            mod_factor = self.params.mod_factor,
+            home_advantage = self.params.home_advantage,
+            tore_slope = self.params.tore_slope,
+            tore_intercept = self.params.tore_intercept,
+            rng = ?self.params.rng,
+            time_ms = elapsed.as_millis() as u64,
+            "simulation finished"
+        );
+        let projected_table = project_table(&self.season, &self.params)
+            .into_iter()
+            .map(|s| ProjectedRow {
+                team_name: self
+                    .team_names
+                    .get(s.team_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Team {}", s.team_id + 1)),
+                position: s.position,
+                expected_points: s.expected_points,
+                expected_goals_for: s.expected_goals_for,
+                expected_goals_against: s.expected_goals_against,
+                expected_goal_difference: s.expected_goal_difference,
+            })
+            .collect();
+
+        let awards = self
+            .awards
+            .then(|| run_award_simulation(&self.season, &self.params, &self.team_names));
+
+        let expected_table = self
+            .expected_table
+            .then(|| run_expected_table(&self.season, &self.params, &self.team_names));
+
+        let match_importance = self.match_importance.then(|| {
+            // The applied structure's relegation places, else the bottom two.
+            let relegated = self
+                .structure
+                .as_ref()
+                .and_then(|a| a.structure.zones.iter().find(|z| z.name == "relegation"))
+                .map_or(2, |z| z.last + 1 - z.first);
+            let zones = importance_zones(self.season.number_teams, relegated);
+            run_match_importance(&self.season, &self.params, &self.team_names, &zones)
+        });
+
+        let samples = (self.return_samples > 0).then(|| {
+            let params = SimulationParams {
+                iterations: self.return_samples.min(self.params.iterations),
+                ..self.params.clone()
+            };
+            run_season_samples(&self.season, &params)
+        });
+
+        // Matrix rows are named, not numbered; map them back to team_ids.
+        let bounds = position_bounds(&self.season, &self.params);
+        let bounds_of = |name: &String| {
+            let team = self.team_names.iter().position(|n| n == name);
+            team.and_then(|t| bounds.get(t))
+        };
+        let position_bounds = result
+            .team_names
+            .iter()
+            .filter_map(|name| {
+                bounds_of(name).map(|b| TeamPositionBounds {
+                    team_name: name.clone(),
+                    best_position: b.best,
+                    worst_position: b.worst,
+                })
+            })
+            .collect();
+
+        let zone_probabilities = self.structure.as_ref().map(|applied| {
+            let zones = &applied.structure.zones;
+            result
+                .probability_matrix
+                .iter()
+                .zip(&result.team_names)
+                .map(|(row, name)| {
+                    let settled = |outcome: bool| -> Vec<String> {
+                        zones
+                            .iter()
+                            .filter(|z| bounds_of(name).and_then(|b| b.settles(z)) == Some(outcome))
+                            .map(|z| z.name.clone())
+                            .collect()
+                    };
+                    TeamZoneProbabilities {
+                        team_name: name.clone(),
+                        zones: zones
+                            .iter()
+                            .map(|z| z.name.clone())
+                            .zip(applied.structure.zone_probabilities(row))
+                            .collect(),
+                        clinched: settled(true),
+                        eliminated: settled(false),
+                    }
+                })
+                .collect()
+        });
+
+        let max_standard_error = self
+            .stopping_rule()
+            .map(|_| max_standard_error(&result.probability_matrix, self.params.iterations));
+
+        // Sparse responses keep only the cells above the threshold.
+        let (probability_matrix, probability_cells) = match self.sparse_threshold {
+            None => (result.probability_matrix, None),
+            Some(threshold) => {
+                let cells = result
+                    .probability_matrix
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(team, row)| {
+                        row.iter()
+                            .enumerate()
+                            .filter(|&(_, &p)| p > threshold)
+                            .map(move |(pos, &p)| ProbabilityCell(team, pos + 1, p))
+                    })
+                    .collect();
+                (Vec::new(), Some(cells))
+            }
+        };
+
+        SimulateResponse {
+            probability_matrix,
+            probability_cells,
+            team_names: result.team_names,
+            simulations_performed: self.params.iterations,
+            max_standard_error,
+            time_ms: elapsed.as_millis(),
+            projected_table,
+            position_bounds,
+            structure: self.structure.clone(),
+            zone_probabilities,
+            cache_hit: false,
+            scenarios: None,
+            awards,
+            expected_table,
+            match_importance,
+            samples,
+            schedule_warnings: self.schedule_warnings.clone(),
+            seed: self.requested_seed,
+            metadata: SimulationMetadata {
+                engine_version: env!("CARGO_PKG_VERSION"),
+                git_sha: env!("LEAGUE_SIM_GIT_SHA"),
+                params: self.params.clone(),
+                seed: self.params.seed.unwrap_or_default(),
+                iterations: self.params.iterations,
+                solver: self.solver,
+                wall_time_ms: elapsed.as_millis(),
+                threads: rayon::current_num_threads(),
+            },
+        }
+    }
+}
+
+/// Batch simulation endpoint for multiple leagues
+#[derive(Deserialize, ToSchema)]
+pub struct BatchSimulateRequest {
+    leagues: Vec<LeagueRequest>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LeagueRequest {
+    name: String,
+    request: SimulateRequest,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchSimulateResponse {
+    pub(crate) results: Vec<LeagueResult>,
+    pub(crate) total_time_ms: u128,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LeagueResult {
+    pub(crate) name: String,
+    pub(crate) status: LeagueStatus,
+    /// When the league's simulation began, in ms after the batch started
+    pub(crate) started_ms: u128,
+    /// How long the league's simulation took
+    pub(crate) time_ms: u128,
+    /// Index of an earlier league with the same request, whose result
+    /// this one shares; it was simulated once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) duplicate_of: Option<usize>,
+    /// The simulation, for leagues that succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) response: Option<SimulateResponse>,
+    /// Why the league failed, with `field` relative to the batch body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<ErrorBody>,
+}
+
+/// One row per (league, team, position) of the leagues that succeeded, as
+/// in the Arrow stream.
+impl Tabular for BatchSimulateResponse {
+    fn table(&self) -> Report {
+        let mut report = Report::new(["league", "rank", "team", "position", "probability"]);
+        for league in &self.results {
+            let Some(response) = &league.response else {
+                continue;
+            };
+            let matrix = response.dense_matrix();
+            for (rank, (team, row)) in response.team_names.iter().zip(matrix.iter()).enumerate() {
+                for (position, &p) in row.iter().enumerate() {
+                    report.push(vec![
+                        Cell::Text(league.name.clone()),
+                        Cell::Int(rank as i64 + 1),
+                        Cell::Text(team.clone()),
+                        Cell::Int(position as i64 + 1),
+                        Cell::Probability(p),
+                    ]);
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Outcome of one league of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LeagueStatus {
+    Success,
+    Error,
+}
+
+/// Simulate several leagues in parallel. A league that fails is reported
+/// in its own result and does not affect the others.
+#[utoipa::path(
+    post,
+    path = "/simulate/batch",
+    tag = "simulation",
+    request_body = BatchSimulateRequest,
+    responses(
+        (status = 200, description = "One result per league, each a success or an error; CSV and Markdown hold one row per league, team and position", body = BatchSimulateResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_batch(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<BatchSimulateRequest>,
+) -> Result<Response, ApiError> {
+    #[cfg(not(feature = "arrow"))]
+    if format.0 == ReportFormat::Arrow {
+        return Err(arrow_unavailable());
+    }
+    let config = state.config.current();
+    check_batch_size(&payload, &config.limits)?;
+    let batch = run_batch(payload, config, Arc::new(AtomicBool::new(false))).await?;
+    #[cfg(feature = "arrow")]
+    if format.0 == ReportFormat::Arrow {
+        return crate::ipc::arrow_response(&batch);
+    }
+    Ok(format.render(batch))
+}
+
+/// Reject batches with more leagues than the configured limit.
+pub(crate) fn check_batch_size(
+    payload: &BatchSimulateRequest,
+    limits: &Limits,
+) -> Result<(), ApiError> {
+    if payload.leagues.len() > limits.max_batch_leagues {
+        return Err(ApiError::limit(
+            "leagues",
+            format!(
+                "{} leagues exceed the limit of {}",
+                payload.leagues.len(),
+                limits.max_batch_leagues
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Iterations per batch between checks of a batch run's cancellation flag
+/// or a request's time budget and target standard error.
+const CANCEL_CHECK_BATCH: usize = 1000;
+
+/// Shared by the synchronous batch endpoint and the async job API. Setting
+/// `cancel` stops every league's simulation at its next batch boundary.
+///
+/// All leagues go to the one rayon pool the simulations already use: each
+/// league is a task of its own, and its iterations are split further, so
+/// idle threads steal work from whichever leagues are left instead of one
+/// OS thread per league competing for the same cores. Leagues whose
+/// requests convert to the same engine inputs (see
+/// [`SimulationJob::cache_key`]) are simulated once and share the result.
+pub(crate) async fn run_batch(
+    payload: BatchSimulateRequest,
+    config: Arc<RuntimeConfig>,
+    cancel: Arc<AtomicBool>,
+) -> Result<BatchSimulateResponse, ApiError> {
+    let start = std::time::Instant::now();
+
+    let leagues = telemetry::spawn_blocking(move || {
+        let parent = Span::current();
+        let mut first_by_key = HashMap::new();
+        let mut leagues: Vec<BatchLeague> = payload
+            .leagues
+            .into_iter()
+            .enumerate()
+            .map(|(i, league)| {
+                let started = start.elapsed();
+                let span = info_span!(parent: &parent, "league", name = %league.name);
+                let state =
+                    match span.in_scope(|| SimulationJob::from_request(league.request, &config)) {
+                        Ok(job) => match *first_by_key.entry(job.cache_key()).or_insert(i) {
+                            first if first != i => LeagueState::Duplicate(first),
+                            _ => LeagueState::Pending(Box::new(job)),
+                        },
+                        Err(e) => LeagueState::Done(Err(e)),
+                    };
+                BatchLeague {
+                    name: league.name,
+                    span,
+                    started,
+                    took: start.elapsed() - started,
+                    state,
+                }
+            })
+            .collect();
+
+        leagues.par_iter_mut().with_max_len(1).for_each(|league| {
+            if let LeagueState::Pending(job) = &league.state {
+                let started = start.elapsed();
+                let response = league.span.in_scope(|| simulate_job(job, &cancel));
+                league.started = started;
+                league.took = start.elapsed() - started;
+                league.state = LeagueState::Done(response.map(Box::new));
+            }
+        });
+        leagues
+    })
+    .await
+    .map_err(|_| ApiError::Internal("batch task panicked".to_string()))?;
+
+    let mut results: Vec<LeagueResult> = Vec::with_capacity(leagues.len());
+    for (i, league) in leagues.into_iter().enumerate() {
+        let result = match league.state {
+            LeagueState::Duplicate(first) => {
+                let shared = &results[first];
+                LeagueResult {
+                    name: league.name,
+                    duplicate_of: Some(first),
+                    response: shared.response.clone(),
+                    error: None,
+                    ..*shared
+                }
+            }
+            LeagueState::Done(Ok(response)) => {
+                crate::metrics::add_iterations(response.simulations_performed);
+                LeagueResult {
+                    name: league.name,
+                    status: LeagueStatus::Success,
+                    started_ms: league.started.as_millis(),
+                    time_ms: league.took.as_millis(),
+                    duplicate_of: None,
+                    response: Some(*response),
+                    error: None,
+                }
+            }
+            // A cancelled batch has no partial answer.
+            LeagueState::Done(Err(ApiError::Cancelled)) => return Err(ApiError::Cancelled),
+            LeagueState::Done(Err(e)) => {
+                let e = e.within(
+                    &format!("leagues[{}].request", i),
+                    &format!("league '{}'", league.name),
+                );
+                tracing::warn!(league = %league.name, error = %e, "league of a batch failed");
+                LeagueResult {
+                    name: league.name,
+                    status: LeagueStatus::Error,
+                    started_ms: league.started.as_millis(),
+                    time_ms: league.took.as_millis(),
+                    duplicate_of: None,
+                    response: None,
+                    error: Some(e.body()),
+                }
+            }
+            LeagueState::Pending(_) => unreachable!("every pending league is simulated"),
+        };
+        results.push(result);
+    }
+
+    Ok(BatchSimulateResponse {
+        results,
+        total_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Engine inputs of a /simulate body, for callers outside the server such
+/// as the command-line tool.
+pub struct EngineInputs {
+    pub season: Season,
+    pub params: SimulationParams,
+    pub team_names: Vec<String>,
+}
+
+/// Validate a /simulate body and convert it to engine inputs.
+pub fn engine_inputs(
+    payload: SimulateRequest,
+    config: &RuntimeConfig,
+) -> Result<EngineInputs, ApiError> {
+    let job = SimulationJob::from_request(payload, config)?;
+    Ok(EngineInputs {
+        season: job.season,
+        params: job.params,
+        team_names: job.team_names,
+    })
+}
+
+/// Run a /simulate body to completion and build the same response the
+/// endpoint would. A `callback_url` is ignored.
+pub fn simulate_request(
+    payload: SimulateRequest,
+    config: &RuntimeConfig,
+) -> Result<SimulateResponse, ApiError> {
+    let start = std::time::Instant::now();
+    Ok(SimulationJob::from_request(payload, config)?.simulate(start))
+}
+
+/// One league of a batch on its way through [`run_batch`].
+struct BatchLeague {
+    name: String,
+    span: Span,
+    /// Since the batch started, of the league's last phase
+    started: std::time::Duration,
+    took: std::time::Duration,
+    state: LeagueState,
+}
+
+enum LeagueState {
+    /// Valid and waiting to be simulated
+    Pending(Box<SimulationJob>),
+    /// Same engine inputs as the league at this index
+    Duplicate(usize),
+    Done(Result<Box<SimulateResponse>, ApiError>),
+}
+
+/// Simulate one league of a batch, checking `cancel` between batches of
+/// iterations.
+fn simulate_job(job: &SimulationJob, cancel: &AtomicBool) -> Result<SimulateResponse, ApiError> {
+    let start = std::time::Instant::now();
+    // Mixtures and hybrid runs go in one go; cancellation is only checked
+    // before they start.
+    if job.scenarios.is_some() || job.solver == Solver::Hybrid {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ApiError::Cancelled);
+        }
+        return Ok(job.simulate(start));
+    }
+    if job.stopping_rule().is_some() {
+        return job.simulate_until(start, cancel).ok_or(ApiError::Cancelled);
+    }
+    let result = run_monte_carlo_simulation_cancellable(
+        &job.season,
+        &job.params,
+        job.team_names.clone(),
+        CANCEL_CHECK_BATCH,
+        cancel,
+    )
+    .ok_or(ApiError::Cancelled)?;
+    Ok(job.respond(result, start.elapsed()))
+}
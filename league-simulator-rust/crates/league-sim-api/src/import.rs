@@ -0,0 +1,84 @@
+// Conversion of the R pipeline's CSV files (TeamList_<season>.csv and a
+// fixtures file) into a /simulate request body, so callers holding those
+// files need no conversion of their own.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use axum::Json;
+use league_sim_core::ingestion::{load_fixtures, LeagueData, TeamList};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CsvImportRequest {
+    /// Contents of a TeamList CSV (`TeamID;ShortText;Promotion;InitialELO`)
+    team_list: String,
+
+    /// Contents of a fixtures CSV with the columns home, away, goals_home,
+    /// goals_away and optionally matchday (or TeamHeim, TeamGast, ToreHeim,
+    /// ToreGast, Spieltag)
+    fixtures: String,
+}
+
+/// A /simulate request body for the imported league; add parameters and
+/// post it as is.
+#[derive(Serialize, ToSchema)]
+pub struct CsvImportResponse {
+    /// `[team_home, team_away, goals_home, goals_away]`, 1-based team
+    /// indices in the order of `team_names`
+    pub schedule: Vec<[Option<i32>; 4]>,
+    pub elo_values: Vec<f64>,
+    /// Short names, alphabetical
+    pub team_names: Vec<String>,
+    /// Matchday of each schedule row, if the fixtures have one
+    pub matchdays: Vec<Option<u32>>,
+    /// `Promotion` of each team (0, or -50 for teams that cannot be
+    /// promoted); pass as `adj_points` for the promotion ranking
+    pub promotion: Vec<i32>,
+}
+
+impl From<LeagueData> for CsvImportResponse {
+    fn from(league: LeagueData) -> Self {
+        let schedule = league
+            .season
+            .matches
+            .iter()
+            .map(|m| {
+                [
+                    Some(m.team_home as i32 + 1),
+                    Some(m.team_away as i32 + 1),
+                    m.goals_home,
+                    m.goals_away,
+                ]
+            })
+            .collect();
+        Self {
+            schedule,
+            elo_values: league.season.team_elos,
+            team_names: league.team_names,
+            matchdays: league.matchdays,
+            promotion: league.promotion,
+        }
+    }
+}
+
+/// Convert a TeamList and a fixtures CSV into a /simulate request body.
+#[utoipa::path(
+    post,
+    path = "/import/csv",
+    tag = "simulation",
+    request_body = CsvImportRequest,
+    responses(
+        (status = 200, description = "Request body for /simulate", body = CsvImportResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Unreadable CSV or unknown team", body = ErrorBody)
+    )
+)]
+pub async fn import_csv(
+    ApiJson(payload): ApiJson<CsvImportRequest>,
+) -> Result<Json<CsvImportResponse>, ApiError> {
+    let teams = TeamList::from_reader(payload.team_list.as_bytes())
+        .map_err(|e| ApiError::InvalidRequest(format!("team_list: {}", e)))?;
+    let league = load_fixtures(&teams, payload.fixtures.as_bytes())
+        .map_err(|e| ApiError::InvalidRequest(format!("fixtures: {}", e)))?;
+    Ok(Json(league.into()))
+}
@@ -0,0 +1,112 @@
+// Arrow IPC stream answers from /simulate/batch. Parsing the nested JSON of
+// a large batch dominates the Shiny updater's run time; the R arrow package
+// reads an IPC stream (`arrow::read_ipc_stream`) without parsing.
+//
+// Chosen with `?format=arrow` or `Accept: application/vnd.apache.arrow.stream`
+// (see `reports`).
+// The stream holds one long table, one row per (league, team, position):
+//
+//   league       Utf8     the league's `name`
+//   rank         Int32    1-based row of the team in the league's matrix
+//   team         Utf8
+//   position     Int32    1-based final position
+//   probability  Float64
+//
+// `total_time_ms` is kept in the schema metadata.
+
+use crate::error::ApiError;
+use crate::handlers::BatchSimulateResponse;
+use crate::reports::ARROW_STREAM_CONTENT_TYPE;
+use arrow_array::{ArrayRef, Float64Array, Int32Array, RecordBatch, StringArray};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info_span;
+use utoipa::openapi::{ContentBuilder, OpenApi, RefOr};
+
+/// The batch as an Arrow IPC stream, serialized inside its own span.
+pub fn arrow_response(batch: &BatchSimulateResponse) -> Result<Response, ApiError> {
+    let bytes = info_span!("serialize")
+        .in_scope(|| write_stream(batch))
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, ARROW_STREAM_CONTENT_TYPE)], bytes).into_response())
+}
+
+fn write_stream(batch: &BatchSimulateResponse) -> Result<Vec<u8>, ArrowError> {
+    let mut league = Vec::new();
+    let mut rank = Vec::new();
+    let mut team = Vec::new();
+    let mut position = Vec::new();
+    let mut probability = Vec::new();
+    // Failed leagues have no rows; their errors are in the JSON answer.
+    for result in &batch.results {
+        let Some(response) = &result.response else {
+            continue;
+        };
+        for (row, (name, probabilities)) in response
+            .team_names
+            .iter()
+            .zip(response.dense_matrix().iter())
+            .enumerate()
+        {
+            for (pos, &p) in probabilities.iter().enumerate() {
+                league.push(result.name.as_str());
+                rank.push(row as i32 + 1);
+                team.push(name.as_str());
+                position.push(pos as i32 + 1);
+                probability.push(p);
+            }
+        }
+    }
+
+    let schema = Arc::new(
+        Schema::new(vec![
+            Field::new("league", DataType::Utf8, false),
+            Field::new("rank", DataType::Int32, false),
+            Field::new("team", DataType::Utf8, false),
+            Field::new("position", DataType::Int32, false),
+            Field::new("probability", DataType::Float64, false),
+        ])
+        .with_metadata(HashMap::from([(
+            "total_time_ms".to_string(),
+            batch.total_time_ms.to_string(),
+        )])),
+    );
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(league)),
+        Arc::new(Int32Array::from(rank)),
+        Arc::new(StringArray::from(team)),
+        Arc::new(Int32Array::from(position)),
+        Arc::new(Float64Array::from(probability)),
+    ];
+    let record_batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let mut writer = StreamWriter::try_new(Vec::new(), &schema)?;
+    writer.write(&record_batch)?;
+    writer.finish()?;
+    writer.into_inner()
+}
+
+/// Add the Arrow response of /simulate/batch to `spec`; the handler's own
+/// annotation describes the build without Arrow.
+pub fn document(spec: &mut OpenApi) {
+    let Some(operation) = spec
+        .paths
+        .paths
+        .get_mut("/simulate/batch")
+        .and_then(|item| item.post.as_mut())
+    else {
+        return;
+    };
+    if let Some(RefOr::T(ok)) = operation.responses.responses.get_mut("200") {
+        ok.content.insert(
+            ARROW_STREAM_CONTENT_TYPE.to_string(),
+            RefOr::T(ContentBuilder::new().build()),
+        );
+    }
+}
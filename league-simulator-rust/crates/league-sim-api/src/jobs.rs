@@ -0,0 +1,253 @@
+// Asynchronous job API for large batch requests.
+// POST /jobs starts a batch simulation in the background and returns a job
+// ID immediately; GET /jobs/{id} reports its status and, once finished, the
+// result. DELETE /jobs/{id} cancels a running job (the simulation stops at
+// its next batch boundary) or forgets a finished one. Finished jobs are kept
+// in memory for a configurable TTL, up to a configurable number of jobs.
+// Jobs share the slots and queue of the simulation routes, so a job that
+// finds the queue full is answered with 429 like any other simulation.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{check_batch_size, run_batch, BatchSimulateRequest, BatchSimulateResponse};
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use rand::RngExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Default time finished jobs stay retrievable.
+const DEFAULT_JOB_TTL: Duration = Duration::from_secs(3600);
+
+/// Default number of jobs kept, running and finished.
+const DEFAULT_MAX_JOBS: usize = 1000;
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(crate) enum JobState {
+    Running,
+    Succeeded { result: BatchSimulateResponse },
+    Failed { error: String },
+    Cancelled,
+}
+
+struct JobEntry {
+    state: JobState,
+    finished_at: Option<Instant>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Outcome of a DELETE on a job.
+enum Deletion {
+    Cancelled,
+    Removed,
+}
+
+/// In-memory job store shared by all request handlers.
+#[derive(Clone)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+    ttl: Duration,
+    max_jobs: usize,
+}
+
+impl JobRegistry {
+    pub fn new(ttl: Duration, max_jobs: usize) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            max_jobs,
+        }
+    }
+
+    /// TTL from `JOB_TTL_SECONDS`, falling back to one hour, and capacity
+    /// from `MAX_JOBS`, falling back to 1000.
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        let ttl = env("JOB_TTL_SECONDS")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_JOB_TTL);
+        Self::new(
+            ttl,
+            env("MAX_JOBS").map_or(DEFAULT_MAX_JOBS, |n: u64| n as usize),
+        )
+    }
+
+    /// Register a new running job; returns its ID and cancellation flag.
+    /// A full registry makes room by forgetting the oldest finished job and
+    /// answers Overloaded if all are still running.
+    fn insert_running(&self) -> Result<(String, Arc<AtomicBool>), ApiError> {
+        let id = format!("{:016x}", rand::rng().random::<u64>());
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut jobs = self.jobs.lock().unwrap();
+        self.purge_expired(&mut jobs);
+        if jobs.len() >= self.max_jobs {
+            let oldest = jobs
+                .iter()
+                .filter_map(|(id, entry)| Some((entry.finished_at?, id)))
+                .min()
+                .map(|(_, id)| id.clone())
+                .ok_or(ApiError::Overloaded)?;
+            jobs.remove(&oldest);
+        }
+        jobs.insert(
+            id.clone(),
+            JobEntry {
+                state: JobState::Running,
+                finished_at: None,
+                cancel: cancel.clone(),
+            },
+        );
+        Ok((id, cancel))
+    }
+
+    /// Record the outcome of a job. A job cancelled in the meantime stays
+    /// cancelled.
+    fn finish(&self, id: &str, state: JobState) {
+        if let Some(entry) = self.jobs.lock().unwrap().get_mut(id) {
+            if matches!(entry.state, JobState::Running) {
+                entry.state = state;
+                entry.finished_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Signal a running job to stop, or drop a finished one.
+    fn delete(&self, id: &str) -> Option<Deletion> {
+        let mut jobs = self.jobs.lock().unwrap();
+        self.purge_expired(&mut jobs);
+        let entry = jobs.get_mut(id)?;
+        if matches!(entry.state, JobState::Running) {
+            entry.cancel.store(true, Ordering::Relaxed);
+            entry.state = JobState::Cancelled;
+            entry.finished_at = Some(Instant::now());
+            Some(Deletion::Cancelled)
+        } else {
+            jobs.remove(id);
+            Some(Deletion::Removed)
+        }
+    }
+
+    /// Serialized status of `id`, or None if unknown or expired.
+    fn status(&self, id: &str) -> Option<serde_json::Value> {
+        let mut jobs = self.jobs.lock().unwrap();
+        self.purge_expired(&mut jobs);
+        jobs.get(id).map(|entry| {
+            let mut value = serde_json::to_value(&entry.state).unwrap_or_default();
+            value["job_id"] = serde_json::Value::String(id.to_string());
+            value
+        })
+    }
+
+    fn purge_expired(&self, jobs: &mut HashMap<String, JobEntry>) {
+        jobs.retain(|_, entry| {
+            entry
+                .finished_at
+                .is_none_or(|finished| finished.elapsed() < self.ttl)
+        });
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct JobCreated {
+    job_id: String,
+    status: &'static str,
+}
+
+/// Start a batch simulation in the background.
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    tag = "jobs",
+    request_body = BatchSimulateRequest,
+    responses(
+        (status = 202, description = "Job started; poll the Location header", body = JobCreated),
+        (status = 413, description = "Too many leagues", body = ErrorBody),
+        (status = 429, description = "Simulation queue or job store full", body = ErrorBody)
+    )
+)]
+pub async fn create_job(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<BatchSimulateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let config = state.config.current();
+    check_batch_size(&payload, &config.limits)?;
+    let reservation = state.overload.reserve()?;
+    let (id, cancel) = state.jobs.insert_running()?;
+
+    let registry = state.jobs.clone();
+    let job_id = id.clone();
+    tokio::spawn(async move {
+        let outcome = match reservation.run(run_batch(payload, config, cancel)).await {
+            Ok(Ok(result)) => JobState::Succeeded { result },
+            Ok(Err(e)) | Err(e) => JobState::Failed {
+                error: e.to_string(),
+            },
+        };
+        registry.finish(&job_id, outcome);
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, format!("/jobs/{}", id))],
+        Json(JobCreated {
+            job_id: id,
+            status: "running",
+        }),
+    ))
+}
+
+/// Job status, with the result once it has finished.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id from POST /jobs")),
+    responses(
+        (status = 200, description = "Current job state", body = JobState),
+        (status = 404, description = "Unknown or expired job", body = ErrorBody)
+    )
+)]
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state
+        .jobs
+        .status(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("job '{}' not found", id)))
+}
+
+/// Cancel a running job or discard a finished one.
+#[utoipa::path(
+    delete,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id from POST /jobs")),
+    responses(
+        (status = 200, description = "Running job cancelled", body = JobState),
+        (status = 204, description = "Finished job discarded"),
+        (status = 404, description = "Unknown or expired job", body = ErrorBody)
+    )
+)]
+pub async fn delete_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<axum::response::Response, ApiError> {
+    match state.jobs.delete(&id) {
+        Some(Deletion::Cancelled) => {
+            Ok(Json(state.jobs.status(&id).unwrap_or_default()).into_response())
+        }
+        Some(Deletion::Removed) => Ok(StatusCode::NO_CONTENT.into_response()),
+        None => Err(ApiError::NotFound(format!("job '{}' not found", id))),
+    }
+}
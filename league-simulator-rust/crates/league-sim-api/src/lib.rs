@@ -0,0 +1,271 @@
+// REST API service for R/Shiny integration
+// Provides high-performance simulation endpoints on top of league-sim-core
+
+pub mod auth;
+pub mod backtest;
+pub mod cache;
+pub mod config;
+pub mod distributed;
+pub mod elo;
+pub mod error;
+pub mod fit;
+pub mod fixtures;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod handlers;
+pub mod import;
+#[cfg(feature = "arrow")]
+pub mod ipc;
+pub mod jobs;
+pub mod metrics;
+pub mod multi_season;
+pub mod openapi;
+pub mod outcomes;
+pub mod overload;
+pub mod ratelimit;
+pub mod replay;
+pub mod reports;
+#[cfg(feature = "parquet")]
+pub mod samples;
+pub mod sessions;
+pub mod stream;
+pub mod team;
+pub mod telemetry;
+pub mod tls;
+pub mod tournament;
+pub mod trajectory;
+pub mod webhook;
+
+#[cfg(test)]
+mod tests;
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use tower::ServiceBuilder;
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+
+/// State shared by all handlers.
+#[derive(Clone)]
+pub struct AppState {
+    pub jobs: jobs::JobRegistry,
+    pub webhooks: webhook::WebhookSender,
+    pub cache: cache::ResultCache,
+    /// Open matchday sessions
+    pub sessions: sessions::SessionRegistry,
+    pub rate_limiter: ratelimit::RateLimiter,
+    /// API keys; None leaves every route open
+    pub auth: Option<auth::ApiKeys>,
+    /// Timeout and concurrency limit of the simulation routes
+    pub overload: overload::OverloadLimits,
+    /// Reloadable simulation defaults and league templates
+    pub config: config::ConfigHandle,
+    /// Responses smaller than this are sent uncompressed
+    pub compression_min_bytes: u16,
+    /// Browser origins allowed by CORS; empty sends no CORS headers
+    pub cors_origins: Vec<String>,
+    /// Instances /simulate shards its iterations across; empty simulates
+    /// locally
+    pub workers: distributed::Workers,
+    /// Latency histograms and counters served at /metrics
+    pub metrics: metrics::Metrics,
+}
+
+/// Largest accepted request body. Payloads are ~306 fixture rows
+/// (<100 KB); 2 MB is generous headroom.
+pub const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+impl AppState {
+    pub fn from_env() -> Self {
+        let config = config::ConfigHandle::from_env();
+        let startup = config.current();
+        Self {
+            jobs: jobs::JobRegistry::from_env(),
+            webhooks: webhook::WebhookSender::from_env(),
+            cache: cache::ResultCache::from_config(&startup.cache),
+            sessions: sessions::SessionRegistry::from_env(),
+            rate_limiter: ratelimit::RateLimiter::from_env(),
+            auth: auth::ApiKeys::from_env(),
+            overload: overload::OverloadLimits::from_env(),
+            compression_min_bytes: startup.server.compression_min_bytes,
+            cors_origins: startup.server.cors_origins.clone(),
+            workers: distributed::Workers::from_env(),
+            metrics: metrics::Metrics::default(),
+            config,
+        }
+    }
+}
+
+/// Prefix of the current API version.
+///
+/// Versioning policy: every route is served under `/v1`. Additive changes
+/// (new endpoints, new optional request fields, new response fields) stay
+/// in the current version. A breaking change to a request or response
+/// schema starts `/v2`, while `/v1` keeps its behaviour. The unversioned
+/// paths are aliases of `/v1` for clients written before versioning and
+/// stay on `/v1` when a later version appears.
+pub const API_PREFIX: &str = "/v1";
+
+/// `path` without the version prefix, for middleware that treats the
+/// versioned and unversioned forms of a route alike.
+pub(crate) fn unversioned(path: &str) -> &str {
+    match path.strip_prefix(API_PREFIX) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => path,
+    }
+}
+
+pub fn create_router() -> Router {
+    create_router_with_state(AppState::from_env())
+}
+
+pub fn create_router_with_state(state: AppState) -> Router {
+    // gzip or brotli as negotiated by Accept-Encoding. SSE is excluded so
+    // progress events aren't held back by the encoder's buffering.
+    let compression = CompressionLayer::new().compress_when(
+        SizeAbove::new(state.compression_min_bytes)
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::SSE),
+    );
+
+    // Routes that simulate in the request. Jobs and streams simulate in a
+    // background task and take their slot in the handler instead.
+    let simulations = Router::new()
+        .route("/simulate", post(handlers::simulate_league))
+        .route("/simulate/batch", post(handlers::simulate_batch))
+        .route("/simulate/outcomes", post(outcomes::simulate_outcomes))
+        .route(
+            "/simulate/elo-trajectory",
+            post(trajectory::simulate_elo_trajectory),
+        )
+        .route("/simulate/team", post(team::simulate_team))
+        .route("/simulate/replay", post(replay::simulate_replay))
+        .route("/simulate/shard", post(distributed::simulate_shard))
+        .route(
+            "/simulate/multi-season",
+            post(multi_season::simulate_multi_season),
+        )
+        .route(
+            "/simulate/league-system",
+            post(multi_season::simulate_league_system),
+        )
+        .route("/fit/home-advantage", post(fit::estimate_home_advantage))
+        .route("/fit/team-strengths", post(fit::estimate_team_strengths))
+        .route("/backtest", post(backtest::run_backtest))
+        .route("/tournament", post(tournament::simulate_tournament))
+        .route(
+            "/tournament/groups",
+            post(tournament::simulate_group_tournament),
+        )
+        .route(
+            "/tournament/draw",
+            post(tournament::simulate_drawn_tournament),
+        )
+        .route("/sessions", post(sessions::open_session))
+        .route("/sessions/{id}/events", post(sessions::push_events));
+    #[cfg(feature = "graphql")]
+    let simulations = simulations.route("/graphql", post(graphql::graphql_handler));
+    #[cfg(feature = "parquet")]
+    let simulations = simulations.route("/simulate/samples", post(samples::simulate_samples));
+    let simulations = simulations.route_layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(overload::handle_error))
+            .timeout(state.overload.timeout)
+            .layer(middleware::from_fn_with_state(
+                state.overload.clone(),
+                overload::admit,
+            )),
+    );
+
+    let api = Router::new()
+        .route("/health", get(handlers::health_check))
+        .route("/metrics", get(metrics::metrics))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/simulate/stream", post(stream::simulate_stream))
+        .route("/import/csv", post(import::import_csv))
+        .route("/fixtures/generate", post(fixtures::generate))
+        .route("/elo/update", post(elo::update_elo))
+        .route("/elo/rollover", post(elo::roll_over))
+        .route("/elo/bootstrap", post(elo::bootstrap))
+        .route("/fit/goal-model", post(fit::estimate_goal_model))
+        .route("/jobs", post(jobs::create_job))
+        .route("/jobs/{id}", get(jobs::get_job).delete(jobs::delete_job))
+        .route(
+            "/sessions/{id}",
+            get(sessions::get_session).delete(sessions::close_session),
+        )
+        .merge(simulations);
+    #[cfg(feature = "graphql")]
+    let api = api.route("/graphql", get(graphql::graphql_playground));
+    let router = Router::new().nest(API_PREFIX, api.clone()).merge(api);
+
+    #[cfg(feature = "swagger-ui")]
+    let router = router.merge(
+        utoipa_swagger_ui::SwaggerUi::new("/swagger-ui")
+            .config(utoipa_swagger_ui::Config::from("/openapi.json")),
+    );
+
+    let router = router
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::authenticate,
+        ))
+        // Outside authentication, so unauthenticated floods are limited too;
+        // inside the metrics and trace layers, so rejections are recorded.
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            ratelimit::limit,
+        ))
+        // Inside the trace layer, so the access log carries the request ID.
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track,
+        ))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(telemetry::request_span)
+                .on_response(telemetry::record_response),
+        )
+        // Outside the trace layer, so its span can read the ID.
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(compression);
+
+    // Outside authentication, so browsers' preflight requests need no key.
+    let router = match cors_layer(&state.cors_origins) {
+        Some(cors) => router.layer(cors),
+        None => router,
+    };
+    router.with_state(state)
+}
+
+/// CORS for the configured browser origins; None if there are none.
+fn cors_layer(origins: &[String]) -> Option<CorsLayer> {
+    if origins.is_empty() {
+        return None;
+    }
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(origins.iter().filter_map(|o| o.parse().ok()))
+    };
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .expose_headers(Any),
+    )
+}
@@ -0,0 +1,242 @@
+// Request metrics and access logs. Every request is timed and logged as one
+// "request finished" line with its route, status, duration, body sizes and
+// the Monte Carlo iterations it ran; the same observations feed
+// per-route Prometheus histograms and counters served at GET /metrics.
+//
+// Durations run until the response head is ready, so a stream counts the
+// time to its first byte. Body sizes are before compression and left out
+// where unknown (chunked uploads, streamed answers). Routes are labelled by
+// their template without the version prefix; unmatched paths share the
+// "unmatched" label so scanners cannot grow the label set.
+
+use crate::AppState;
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 13] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+tokio::task_local! {
+    /// Iterations the request being served ran.
+    static ITERATIONS: Arc<AtomicU64>;
+}
+
+/// Count `n` iterations towards the current request; a no-op outside one.
+pub(crate) fn add_iterations(n: usize) {
+    let _ = ITERATIONS.try_with(|count| count.fetch_add(n as u64, Ordering::Relaxed));
+}
+
+/// Replace the current request's iteration count, for routes whose parts
+/// are validated as separate requests but simulated together.
+pub(crate) fn set_iterations(n: usize) {
+    let _ = ITERATIONS.try_with(|count| count.store(n as u64, Ordering::Relaxed));
+}
+
+/// `f` counting its iterations towards the current request, for running on
+/// another thread.
+pub(crate) fn in_request<R>(f: impl FnOnce() -> R) -> impl FnOnce() -> R {
+    let count = ITERATIONS.try_with(Arc::clone).ok();
+    move || match count {
+        Some(count) => ITERATIONS.sync_scope(count, f),
+        None => f(),
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    route: String,
+    method: Method,
+    status: u16,
+}
+
+#[derive(Default)]
+struct Observations {
+    /// Requests per bucket of `BUCKETS`, not cumulative
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    seconds: f64,
+    request_bytes: u64,
+    response_bytes: u64,
+    iterations: u64,
+}
+
+/// Observations by route, method and status, shared by all requests.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    observations: Arc<Mutex<BTreeMap<Key, Observations>>>,
+}
+
+/// One finished request.
+struct Finished {
+    seconds: f64,
+    request_bytes: Option<u64>,
+    response_bytes: Option<u64>,
+    iterations: u64,
+}
+
+impl Metrics {
+    fn record(&self, key: Key, finished: &Finished) {
+        let mut observations = self.observations.lock().unwrap();
+        let o = observations.entry(key).or_default();
+        if let Some(bucket) = BUCKETS.iter().position(|&le| finished.seconds <= le) {
+            o.buckets[bucket] += 1;
+        }
+        o.count += 1;
+        o.seconds += finished.seconds;
+        o.request_bytes += finished.request_bytes.unwrap_or(0);
+        o.response_bytes += finished.response_bytes.unwrap_or(0);
+        o.iterations += finished.iterations;
+    }
+
+    /// The observations in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let observations = self.observations.lock().unwrap();
+        let mut out = String::new();
+        let labels = |key: &Key| {
+            format!(
+                "method=\"{}\",route=\"{}\",status=\"{}\"",
+                key.method,
+                key.route.replace('\\', "\\\\").replace('"', "\\\""),
+                key.status
+            )
+        };
+
+        out.push_str(
+            "# HELP http_request_duration_seconds Time until the response head was ready.\n",
+        );
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for (key, o) in observations.iter() {
+            let labels = labels(key);
+            let mut cumulative = 0;
+            for (le, n) in BUCKETS.iter().zip(o.buckets) {
+                cumulative += n;
+                let _ = writeln!(
+                    out,
+                    "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, o.count
+            );
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_sum{{{}}} {}",
+                labels, o.seconds
+            );
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_count{{{}}} {}",
+                labels, o.count
+            );
+        }
+
+        for (name, help, value) in [
+            (
+                "http_request_body_bytes_total",
+                "Request body bytes received.",
+                (|o: &Observations| o.request_bytes) as fn(&Observations) -> u64,
+            ),
+            (
+                "http_response_body_bytes_total",
+                "Response body bytes sent, before compression.",
+                |o| o.response_bytes,
+            ),
+            (
+                "simulation_iterations_total",
+                "Monte Carlo iterations requested.",
+                |o| o.iterations,
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (key, o) in observations.iter() {
+                let _ = writeln!(out, "{}{{{}}} {}", name, labels(key), value(o));
+            }
+        }
+        out
+    }
+}
+
+/// Middleware timing, counting and logging every request. Runs inside the
+/// request span, so the log line carries the request ID.
+pub async fn track(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", |path| crate::unversioned(path.as_str()))
+        .to_string();
+    let request_bytes = body_bytes(request.body(), request.headers());
+
+    let iterations = Arc::new(AtomicU64::new(0));
+    let response = ITERATIONS
+        .scope(iterations.clone(), next.run(request))
+        .await;
+
+    let finished = Finished {
+        seconds: start.elapsed().as_secs_f64(),
+        request_bytes,
+        response_bytes: body_bytes(response.body(), response.headers()),
+        iterations: iterations.load(Ordering::Relaxed),
+    };
+    let status = response.status().as_u16();
+    tracing::info!(
+        route,
+        status,
+        latency_ms = (finished.seconds * 1000.0) as u64,
+        request_bytes = finished.request_bytes,
+        response_bytes = finished.response_bytes,
+        iterations = finished.iterations,
+        "request finished"
+    );
+    state.metrics.record(
+        Key {
+            route,
+            method,
+            status,
+        },
+        &finished,
+    );
+    response
+}
+
+/// Size of a body: exact if known, else the Content-Length header.
+fn body_bytes(body: &impl HttpBody, headers: &HeaderMap) -> Option<u64> {
+    body.size_hint().exact().or_else(|| {
+        headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+    })
+}
+
+/// Request latency histograms and counters by route, method and status,
+/// and the simulation queue.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Prometheus text exposition format", content_type = "text/plain"))
+)]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render() + &state.overload.render(),
+    )
+}
@@ -0,0 +1,336 @@
+// Several consecutive seasons of a pyramid of divisions with promotion,
+// relegation and Elo carryover, for questions such as "promoted within three
+// seasons", and the German three-tier system as a preset of it.
+
+use crate::config::RuntimeConfig;
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{extract::State, response::Response};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::{
+    run_multi_season, Division, LeagueSystem, MultiSeason, MultiSeasonResult, Tier, TwoLegRules,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Most seasons one request may simulate.
+pub const MAX_SEASONS: usize = 10;
+
+#[derive(Deserialize, ToSchema)]
+pub struct MultiSeasonRequest {
+    /// Divisions, top first
+    divisions: Vec<DivisionRequest>,
+
+    /// Seasons to simulate, the current one included
+    seasons: usize,
+
+    /// Share of each rating's distance from its division's mean kept
+    /// between seasons, 0 to 1
+    regression: f64,
+
+    /// Number of Monte Carlo runs (default: 10000)
+    iterations: Option<usize>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DivisionRequest {
+    name: String,
+
+    /// The division's current season, as for /simulate. Its adjustments
+    /// apply to the current season only; `iterations` is ignored. With a
+    /// `league` preset, the fields below default to the league's.
+    request: SimulateRequest,
+
+    /// Top teams going up after each season (default: 0)
+    promoted: Option<usize>,
+
+    /// Bottom teams going down after each season (default: 0)
+    relegated: Option<usize>,
+
+    /// Points added per team when ranking for promotion only, e.g. -50 for
+    /// second teams (the TeamList `Promotion` column)
+    promotion: Option<Vec<i32>>,
+
+    /// Whether the team above the relegation spots plays the best
+    /// non-promoted team of the division below in a two-legged tie
+    /// (default: false)
+    relegation_playoff: Option<bool>,
+
+    /// How a level relegation playoff is settled (default: extra time and
+    /// penalties, no away goals)
+    playoff_rules: Option<TwoLegRules>,
+
+    /// ELO points added to the division's ratings to put them on the scale
+    /// shared by all divisions, for playoffs and teams changing division
+    /// (default: 0, the ratings already share one)
+    elo_offset: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MultiSeasonResponse {
+    #[serde(flatten)]
+    pub result: MultiSeasonResult,
+    pub simulations_performed: usize,
+    pub time_ms: u128,
+}
+
+/// One row per team, simulated season and division.
+impl Tabular for MultiSeasonResponse {
+    fn table(&self) -> Report {
+        let mut report = Report::new(["team", "season", "division", "probability"]);
+        for team in &self.result.teams {
+            for (season, divisions) in team.division_probabilities.iter().enumerate() {
+                for (division, &p) in divisions.iter().enumerate() {
+                    report.push(vec![
+                        Cell::Text(team.team_name.clone()),
+                        Cell::Int(season as i64 + 1),
+                        Cell::Text(self.result.divisions[division].clone()),
+                        Cell::Probability(p),
+                    ]);
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Simulate consecutive seasons of several divisions with promotion and
+/// relegation between them.
+#[utoipa::path(
+    post,
+    path = "/simulate/multi-season",
+    tag = "simulation",
+    request_body = MultiSeasonRequest,
+    responses(
+        (status = 200, description = "Division probabilities per team and season", body = MultiSeasonResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or inconsistent movement", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_multi_season(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<MultiSeasonRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let config = state.config.current();
+    if payload.divisions.len() > config.limits.max_batch_leagues {
+        return Err(ApiError::limit(
+            "divisions",
+            format!(
+                "{} divisions exceed the limit of {}",
+                payload.divisions.len(),
+                config.limits.max_batch_leagues
+            ),
+        ));
+    }
+    let iterations = check_run(
+        &config,
+        payload.seasons,
+        payload.regression,
+        payload.iterations,
+    )?;
+
+    let mut divisions = Vec::with_capacity(payload.divisions.len());
+    for (i, division) in payload.divisions.into_iter().enumerate() {
+        let job = SimulationJob::from_request(division.request, &config).map_err(|e| {
+            e.within(
+                &format!("divisions[{}].request", i),
+                &format!("division '{}'", division.name),
+            )
+        })?;
+        let league = job.league.as_ref();
+        divisions.push(Division {
+            name: division.name,
+            promoted: division
+                .promoted
+                .or(league.map(|l| l.promoted))
+                .unwrap_or(0),
+            relegated: division
+                .relegated
+                .or(league.map(|l| l.relegated))
+                .unwrap_or(0),
+            promotion_adjustments: division
+                .promotion
+                .or_else(|| league?.promotion_adjustments(&job.team_names)),
+            relegation_playoff: division
+                .relegation_playoff
+                .or(league.map(|l| l.relegation_playoff))
+                .unwrap_or(false),
+            playoff_rules: division
+                .playoff_rules
+                .or(league.map(|l| l.playoff_rules))
+                .unwrap_or_default(),
+            elo_offset: finite_offset(division.elo_offset, &format!("divisions[{}]", i))?,
+            season: job.season,
+            params: job.params,
+            team_names: job.team_names,
+        });
+    }
+    let setup = MultiSeason {
+        divisions,
+        seasons: payload.seasons,
+        regression: payload.regression,
+    };
+    run(setup, iterations, start, format).await
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LeagueSystemRequest {
+    bundesliga: TierRequest,
+    zweite_bundesliga: TierRequest,
+    dritte_liga: TierRequest,
+
+    /// Seasons to simulate, the current one included (default: 1)
+    seasons: Option<usize>,
+
+    /// Share of each rating's distance from its league's mean kept between
+    /// seasons, 0 to 1 (default: 1)
+    regression: Option<f64>,
+
+    /// Number of Monte Carlo runs (default: 10000)
+    iterations: Option<usize>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TierRequest {
+    /// The league's current season, as for /simulate; `iterations` is
+    /// ignored
+    request: SimulateRequest,
+
+    /// `Promotion` of each team from the TeamList (0, or -50 for second
+    /// teams, which cannot be promoted)
+    promotion: Option<Vec<i32>>,
+
+    /// ELO offset of the league's ratings, as for /simulate/multi-season
+    /// divisions (default: 0)
+    elo_offset: Option<f64>,
+}
+
+/// Simulate Bundesliga, 2. Bundesliga and 3. Liga jointly, with the
+/// promotion, relegation and playoff places between them.
+#[utoipa::path(
+    post,
+    path = "/simulate/league-system",
+    tag = "simulation",
+    request_body = LeagueSystemRequest,
+    responses(
+        (status = 200, description = "League probabilities per team and season", body = MultiSeasonResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_league_system(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<LeagueSystemRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let config = state.config.current();
+    let seasons = payload.seasons.unwrap_or(1);
+    let regression = payload.regression.unwrap_or(1.0);
+    let iterations = check_run(&config, seasons, regression, payload.iterations)?;
+
+    let tier = |field: &str, name: &str, tier: TierRequest| -> Result<Tier, ApiError> {
+        let job = SimulationJob::from_request(tier.request, &config)
+            .map_err(|e| e.within(&format!("{}.request", field), name))?;
+        Ok(Tier {
+            season: job.season,
+            params: job.params,
+            team_names: job.team_names,
+            promotion_adjustments: tier.promotion,
+            elo_offset: finite_offset(tier.elo_offset, field)?,
+        })
+    };
+    let [bundesliga, zweite_bundesliga, dritte_liga] = LeagueSystem::DIVISIONS;
+    let system = LeagueSystem {
+        bundesliga: tier("bundesliga", bundesliga, payload.bundesliga)?,
+        zweite_bundesliga: tier(
+            "zweite_bundesliga",
+            zweite_bundesliga,
+            payload.zweite_bundesliga,
+        )?,
+        dritte_liga: tier("dritte_liga", dritte_liga, payload.dritte_liga)?,
+    };
+    run(
+        system.into_multi_season(seasons, regression),
+        iterations,
+        start,
+        format,
+    )
+    .await
+}
+
+/// The `elo_offset` of the division at `field`, 0 if not given.
+fn finite_offset(offset: Option<f64>, field: &str) -> Result<f64, ApiError> {
+    match offset {
+        Some(offset) if !offset.is_finite() => Err(ApiError::field(
+            format!("{}.elo_offset", field),
+            format!("{}.elo_offset must be a finite number", field),
+        )),
+        offset => Ok(offset.unwrap_or(0.0)),
+    }
+}
+
+/// Validate the run-wide settings, returning the iteration count.
+fn check_run(
+    config: &RuntimeConfig,
+    seasons: usize,
+    regression: f64,
+    iterations: Option<usize>,
+) -> Result<usize, ApiError> {
+    let limits = &config.limits;
+    if seasons > MAX_SEASONS {
+        return Err(ApiError::limit(
+            "seasons",
+            format!("{} seasons exceed the limit of {}", seasons, MAX_SEASONS),
+        ));
+    }
+    let iterations = iterations.unwrap_or(config.simulation.iterations);
+    if iterations == 0 || iterations > limits.max_iterations {
+        return Err(ApiError::field(
+            "iterations",
+            format!(
+                "iterations must be between 1 and {}, got {}",
+                limits.max_iterations, iterations
+            ),
+        ));
+    }
+    if !(0.0..=1.0).contains(&regression) {
+        return Err(ApiError::field(
+            "regression",
+            format!("regression must be between 0 and 1, got {}", regression),
+        ));
+    }
+    Ok(iterations)
+}
+
+async fn run(
+    setup: MultiSeason,
+    iterations: usize,
+    start: std::time::Instant,
+    format: Negotiated,
+) -> Result<Response, ApiError> {
+    setup.check().map_err(ApiError::InvalidRequest)?;
+    // The divisions' own iteration counts are not used.
+    crate::metrics::set_iterations(iterations);
+
+    let result = telemetry::spawn_blocking(move || run_multi_season(&setup, iterations))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(format.render(MultiSeasonResponse {
+        result,
+        simulations_performed: iterations,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
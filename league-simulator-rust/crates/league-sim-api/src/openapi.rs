@@ -0,0 +1,86 @@
+// OpenAPI 3.1 description of the REST API, generated from the handler and
+// type annotations, served at /openapi.json so R, Python and JS clients can
+// generate typed bindings. With the `swagger-ui` feature it is also
+// browsable at /swagger-ui.
+
+use crate::{
+    backtest, distributed, elo, fit, fixtures, handlers, import, jobs, metrics, multi_season,
+    outcomes, replay, sessions, stream, team, tournament, trajectory,
+};
+use axum::Json;
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "League Simulator API",
+        description = "Monte Carlo football league simulation (Elo ratings, Poisson goals)"
+    ),
+    servers((
+        url = "/v1",
+        description = "Current API version; the unversioned paths are aliases of it"
+    )),
+    paths(
+        handlers::health_check,
+        metrics::metrics,
+        handlers::simulate_league,
+        handlers::simulate_batch,
+        stream::simulate_stream,
+        outcomes::simulate_outcomes,
+        trajectory::simulate_elo_trajectory,
+        team::simulate_team,
+        replay::simulate_replay,
+        distributed::simulate_shard,
+        multi_season::simulate_multi_season,
+        multi_season::simulate_league_system,
+        tournament::simulate_tournament,
+        tournament::simulate_group_tournament,
+        tournament::simulate_drawn_tournament,
+        import::import_csv,
+        fixtures::generate,
+        elo::update_elo,
+        elo::roll_over,
+        elo::bootstrap,
+        fit::estimate_home_advantage,
+        fit::estimate_goal_model,
+        fit::estimate_team_strengths,
+        backtest::run_backtest,
+        jobs::create_job,
+        jobs::get_job,
+        jobs::delete_job,
+        sessions::open_session,
+        sessions::get_session,
+        sessions::push_events,
+        sessions::close_session,
+    ),
+    tags(
+        (name = "simulation", description = "Synchronous and streaming simulation"),
+        (name = "jobs", description = "Background batch jobs"),
+        (name = "sessions", description = "Live matchday sessions"),
+        (name = "elo", description = "Rating maintenance"),
+        (name = "fit", description = "Model calibration from historical results"),
+        (name = "health", description = "Liveness")
+    )
+)]
+pub struct ApiDoc;
+
+/// Routes that only exist with the `parquet` feature.
+#[cfg(feature = "parquet")]
+#[derive(OpenApi)]
+#[openapi(paths(crate::samples::simulate_samples))]
+struct ParquetDoc;
+
+/// The spec of the routes compiled into this build.
+pub fn spec() -> utoipa::openapi::OpenApi {
+    let mut spec = ApiDoc::openapi();
+    #[cfg(feature = "parquet")]
+    spec.merge(ParquetDoc::openapi());
+    crate::reports::document(&mut spec);
+    #[cfg(feature = "arrow")]
+    crate::ipc::document(&mut spec);
+    spec
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(spec())
+}
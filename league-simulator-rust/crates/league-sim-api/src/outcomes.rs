@@ -0,0 +1,180 @@
+// Outcome tree for the last matchday(s): every combination of results of the
+// remaining fixtures with the final table it produces, for "all the ways it
+// can end" graphics.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    response::Response,
+};
+use league_sim_core::outcomes::{count_outcomes, enumerate_outcomes, OutcomeMode};
+use league_sim_core::reports::{Cell, Report};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Largest tree the endpoint will enumerate (3^10 results, or 4^8 scorelines
+/// with a cap of 1).
+pub const MAX_OUTCOMES: u64 = 100_000;
+
+#[derive(Deserialize, IntoParams)]
+pub struct OutcomeOptions {
+    /// Enumerate scorelines up to this many goals per side instead of
+    /// win/draw/loss
+    scoreline_cap: Option<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OutcomesResponse {
+    pub fixtures: Vec<OutcomeFixture>,
+    pub outcomes: Vec<OutcomeRow>,
+    /// Total probability of the listed outcomes; below 1 when scorelines
+    /// above the cap are left out
+    pub coverage: f64,
+    pub outcome_count: usize,
+}
+
+/// One row per outcome and team, with the final table the outcome
+/// produces; the fixtures' results stay in the JSON answer.
+impl Tabular for OutcomesResponse {
+    fn table(&self) -> Report {
+        let mut report = Report::new([
+            "outcome",
+            "probability",
+            "team",
+            "position",
+            "points",
+            "goal_difference",
+            "goals_for",
+        ]);
+        for (i, outcome) in self.outcomes.iter().enumerate() {
+            for row in &outcome.table {
+                report.push(vec![
+                    Cell::Int(i as i64 + 1),
+                    Cell::Probability(outcome.probability),
+                    Cell::Text(row.team_name.clone()),
+                    Cell::Int(row.position as i64),
+                    Cell::Int(row.points.into()),
+                    Cell::Int(row.goal_difference.into()),
+                    Cell::Int(row.goals_for.into()),
+                ]);
+            }
+        }
+        report
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OutcomeFixture {
+    pub team_home: String,
+    pub team_away: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OutcomeRow {
+    /// `[goals_home, goals_away]` per fixture, in the order of `fixtures`
+    pub results: Vec<[i32; 2]>,
+    pub probability: f64,
+    pub table: Vec<OutcomeTableRow>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OutcomeTableRow {
+    pub team_name: String,
+    pub position: usize,
+    pub points: i32,
+    pub goal_difference: i32,
+    pub goals_for: i32,
+}
+
+/// Enumerate every result of the unplayed fixtures and the final table each
+/// one produces. Rejects requests whose tree exceeds `MAX_OUTCOMES`.
+#[utoipa::path(
+    post,
+    path = "/simulate/outcomes",
+    tag = "simulation",
+    params(OutcomeOptions),
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Every outcome of the remaining fixtures", body = OutcomesResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or too many outcomes", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_outcomes(
+    State(state): State<AppState>,
+    Query(options): Query<OutcomeOptions>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+    // Outcomes are enumerated, not sampled.
+    crate::metrics::set_iterations(0);
+    let mode = match options.scoreline_cap {
+        Some(max_goals) => OutcomeMode::Scorelines { max_goals },
+        None => OutcomeMode::Results,
+    };
+
+    match count_outcomes(&job.season, mode) {
+        Some(count) if count <= MAX_OUTCOMES => {}
+        count => {
+            return Err(ApiError::InvalidRequest(format!(
+                "remaining fixtures have {} outcomes, limit is {}",
+                count.map_or("too many".to_string(), |c| c.to_string()),
+                MAX_OUTCOMES
+            )))
+        }
+    }
+
+    let response = telemetry::spawn_blocking(move || {
+        let tree = enumerate_outcomes(&job.season, &job.params, mode);
+        let name = |team: usize| {
+            job.team_names
+                .get(team)
+                .cloned()
+                .unwrap_or_else(|| format!("Team_{}", team + 1))
+        };
+
+        OutcomesResponse {
+            fixtures: tree
+                .fixtures
+                .iter()
+                .map(|m| OutcomeFixture {
+                    team_home: name(m.team_home),
+                    team_away: name(m.team_away),
+                })
+                .collect(),
+            outcome_count: tree.outcomes.len(),
+            outcomes: tree
+                .outcomes
+                .into_iter()
+                .map(|outcome| OutcomeRow {
+                    results: outcome.results,
+                    probability: outcome.probability,
+                    table: outcome
+                        .table
+                        .iter()
+                        .map(|row| OutcomeTableRow {
+                            team_name: name(row.team_id),
+                            position: row.position,
+                            points: row.points,
+                            goal_difference: row.goal_difference,
+                            goals_for: row.goals_for,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            coverage: tree.coverage,
+        }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(format.render(response))
+}
@@ -0,0 +1,247 @@
+// Backpressure for the simulation routes. At most MAX_CONCURRENT_SIMULATIONS
+// requests are simulated at once across all of them; up to
+// MAX_QUEUED_SIMULATIONS more wait for a slot in arrival order. Requests
+// beyond that are answered with 429 right away instead of queuing without
+// bound. A request that takes longer than REQUEST_TIMEOUT_SECONDS, its wait
+// in the queue included, is answered with 503; a simulation it started
+// finishes in the background and is discarded, keeping the request's slot
+// until it does, so abandoned work still counts against the limit. Work a
+// request hands to a background task (jobs, streams, callback runs) takes
+// its slot or queue place before it is accepted and keeps it to the end.
+
+use crate::error::ApiError;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+    BoxError,
+};
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::timeout::error::Elapsed;
+
+tokio::task_local! {
+    /// Slot of the simulation request being served.
+    static SLOT: Arc<OwnedSemaphorePermit>;
+}
+
+/// `f` holding the current request's slot until it returns, for running
+/// on another thread: a request that times out gives up its future, not
+/// the work it handed off.
+pub(crate) fn holding_slot<R>(f: impl FnOnce() -> R) -> impl FnOnce() -> R {
+    let slot = SLOT.try_with(Arc::clone).ok();
+    move || {
+        let result = f();
+        drop(slot);
+        result
+    }
+}
+
+/// `fut` running in the current request's slot, for spawning: the slot is
+/// given up once `fut` finishes rather than when the request is answered.
+pub(crate) fn in_slot<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let slot = SLOT.try_with(Arc::clone).ok();
+    async move {
+        match slot {
+            Some(slot) => SLOT.scope(slot, fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+/// Default seconds a simulation request may take.
+const DEFAULT_REQUEST_TIMEOUT_SECONDS: u64 = 60;
+
+/// Default concurrent simulations per CPU.
+const DEFAULT_CONCURRENCY_PER_CPU: usize = 4;
+
+#[derive(Clone)]
+pub struct OverloadLimits {
+    pub timeout: Duration,
+    /// Shared by every simulation route
+    permits: Arc<Semaphore>,
+    max_concurrent: usize,
+    max_queued: usize,
+    /// Requests waiting for a permit
+    queued: Arc<AtomicUsize>,
+    /// Requests turned away because the queue was full
+    rejected: Arc<AtomicU64>,
+}
+
+impl OverloadLimits {
+    /// `max_concurrent` is raised to at least 1; a `max_queued` of 0 turns
+    /// away every request that finds the slots taken.
+    pub fn new(timeout: Duration, max_concurrent: usize, max_queued: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            timeout,
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+            max_queued,
+            queued: Arc::new(AtomicUsize::new(0)),
+            rejected: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// REQUEST_TIMEOUT_SECONDS (default 60), MAX_CONCURRENT_SIMULATIONS
+    /// (default four per CPU) and MAX_QUEUED_SIMULATIONS (default as many
+    /// as run at once).
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok();
+        let timeout = env("REQUEST_TIMEOUT_SECONDS")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECONDS);
+        let max_concurrent = env("MAX_CONCURRENT_SIMULATIONS")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(|| {
+                let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+                cpus * DEFAULT_CONCURRENCY_PER_CPU
+            });
+        let max_queued = env("MAX_QUEUED_SIMULATIONS")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(max_concurrent);
+        Self::new(Duration::from_secs(timeout), max_concurrent, max_queued)
+    }
+
+    /// Semaphore whose permits bound the concurrent simulations.
+    pub fn permits(&self) -> Arc<Semaphore> {
+        self.permits.clone()
+    }
+
+    /// Requests currently waiting for a slot.
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// A free slot, or else a place in the queue for one; Overloaded if the
+    /// queue is full.
+    pub(crate) fn reserve(&self) -> Result<Reservation, ApiError> {
+        // The semaphore hands freed permits to waiters in order, so this
+        // only succeeds if nobody is queued.
+        let held = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => Ok(permit),
+            Err(_) => Err(self.enqueue().ok_or(ApiError::Overloaded)?),
+        };
+        Ok(Reservation {
+            permits: self.permits.clone(),
+            held,
+        })
+    }
+
+    /// A place in the queue, or None (counted as a rejection) if it is full.
+    fn enqueue(&self) -> Option<QueuePlace> {
+        let taken = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < self.max_queued).then_some(n + 1)
+            });
+        match taken {
+            Ok(_) => Some(QueuePlace(self.queued.clone())),
+            Err(_) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Slot and queue gauges and the rejection counter in the Prometheus
+    /// text exposition format.
+    pub fn render(&self) -> String {
+        let in_progress = self.max_concurrent - self.permits.available_permits();
+        let mut out = String::new();
+        for (name, kind, help, value) in [
+            (
+                "simulations_in_progress",
+                "gauge",
+                "Simulation requests holding a slot.",
+                in_progress as u64,
+            ),
+            (
+                "simulation_slots",
+                "gauge",
+                "Simulation requests served at once.",
+                self.max_concurrent as u64,
+            ),
+            (
+                "simulation_queue_depth",
+                "gauge",
+                "Simulation requests waiting for a slot.",
+                self.queue_depth() as u64,
+            ),
+            (
+                "simulation_queue_capacity",
+                "gauge",
+                "Simulation requests that may wait for a slot.",
+                self.max_queued as u64,
+            ),
+            (
+                "simulation_queue_rejected_total",
+                "counter",
+                "Simulation requests answered with 429 because the queue was full.",
+                self.rejected.load(Ordering::Relaxed),
+            ),
+        ] {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+}
+
+/// A request's place in the queue, given up when dropped: on getting a
+/// slot, on timing out, or when the client goes away.
+struct QueuePlace(Arc<AtomicUsize>);
+
+impl Drop for QueuePlace {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A slot, or a place in the queue for one, taken before a simulation is
+/// accepted.
+pub(crate) struct Reservation {
+    permits: Arc<Semaphore>,
+    held: Result<OwnedSemaphorePermit, QueuePlace>,
+}
+
+impl Reservation {
+    /// Run `fut` once the slot is free, holding it until `fut` and the
+    /// blocking work it hands off have finished.
+    pub(crate) async fn run<F: Future>(self, fut: F) -> Result<F::Output, ApiError> {
+        let permit = match self.held {
+            Ok(permit) => permit,
+            Err(_place) => self
+                .permits
+                .acquire_owned()
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?,
+        };
+        Ok(SLOT.scope(Arc::new(permit), fut).await)
+    }
+}
+
+/// Middleware running a simulation request once it holds a slot, queuing
+/// it while all are taken and turning it away if the queue is full.
+pub async fn admit(
+    State(limits): State<OverloadLimits>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    limits.reserve()?.run(next.run(request)).await
+}
+
+/// Error handler of the timeout layer: requests running too long are
+/// answered with 503.
+pub async fn handle_error(error: BoxError) -> ApiError {
+    if error.is::<Elapsed>() {
+        ApiError::Timeout
+    } else {
+        ApiError::Internal(error.to_string())
+    }
+}
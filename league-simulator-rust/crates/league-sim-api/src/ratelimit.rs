@@ -0,0 +1,138 @@
+// Per-client rate limiting for the shared deployment. Each client gets a
+// token bucket refilled at RATE_LIMIT_PER_MINUTE and holding at most
+// RATE_LIMIT_BURST requests; a request finding the bucket empty is answered
+// with 429 and a Retry-After header. Clients are told apart by their API
+// key when it is one of the configured keys, otherwise by their IP address,
+// so made-up keys cannot open fresh buckets. Health checks and metric
+// scrapes are never limited so probes and monitoring keep working under
+// load.
+
+use crate::auth::{presented_key, ApiKeys};
+use crate::error::ApiError;
+use crate::AppState;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Buckets are pruned once more clients than this have been seen.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets by client, shared by all requests.
+#[derive(Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+    /// Tokens added per second; 0 disables limiting
+    rate: f64,
+    burst: f64,
+    /// Take the client IP from X-Forwarded-For (behind an ingress)
+    trust_forwarded: bool,
+}
+
+impl RateLimiter {
+    /// `per_minute` 0 disables limiting. `burst` is raised to at least 1.
+    pub fn new(per_minute: u32, burst: u32, trust_forwarded: bool) -> Self {
+        Self {
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            rate: f64::from(per_minute) / 60.0,
+            burst: f64::from(burst.max(1)),
+            trust_forwarded,
+        }
+    }
+
+    /// RATE_LIMIT_PER_MINUTE (default 0: off), RATE_LIMIT_BURST (default
+    /// the per-minute rate) and RATE_LIMIT_TRUST_FORWARDED (default false).
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok();
+        let per_minute = env("RATE_LIMIT_PER_MINUTE")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let burst = env("RATE_LIMIT_BURST")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(per_minute);
+        let trust_forwarded = env("RATE_LIMIT_TRUST_FORWARDED").is_some_and(|v| v == "true");
+        Self::new(per_minute, burst, trust_forwarded)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.rate > 0.0
+    }
+
+    /// Take a token for `client`, or return the seconds until one is
+    /// available.
+    fn acquire(&self, client: &str) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            // Full buckets carry no state worth keeping.
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * self.rate < self.burst
+            });
+        }
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / self.rate).ceil() as u64)
+        }
+    }
+
+    /// Key identifying the client that sent `request`: its API key if
+    /// `auth` knows it, else its IP.
+    fn client_key(&self, request: &Request, auth: Option<&ApiKeys>) -> String {
+        let headers = request.headers();
+        let key = presented_key(headers).filter(|key| auth.is_some_and(|keys| keys.contains(key)));
+        if let Some(key) = key {
+            return format!("key:{}", key);
+        }
+        if self.trust_forwarded {
+            // The trusted proxy appends the address it saw; hops to its
+            // left come from the client and may be forged.
+            let forwarded = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .next_back()
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.rsplit(',').next())
+                .map(str::trim);
+            if let Some(ip) = forwarded.filter(|ip| !ip.is_empty()) {
+                return format!("ip:{}", ip);
+            }
+        }
+        match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+            None => "ip:unknown".to_string(),
+        }
+    }
+}
+
+/// Middleware applying `state.rate_limiter` to every route but /health and
+/// /metrics.
+pub async fn limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let limiter = &state.rate_limiter;
+    let path = crate::unversioned(request.uri().path());
+    if !limiter.enabled() || path == "/health" || path == "/metrics" {
+        return next.run(request).await;
+    }
+    match limiter.acquire(&limiter.client_key(&request, state.auth.as_ref())) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => ApiError::RateLimited { retry_after }.into_response(),
+    }
+}
@@ -0,0 +1,86 @@
+// Season replay: re-runs the simulator on a completed season as it stood
+// after each matchday, so its forecasts can be checked against how the
+// season actually ended.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{extract::State, response::Response};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::{replay_season, SeasonReplay};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ReplayResponse {
+    #[serde(flatten)]
+    pub replay: SeasonReplay,
+    pub simulations_performed: usize,
+    pub time_ms: u128,
+}
+
+/// One row per snapshot and team with the probability it gave of the
+/// team's actual final position.
+impl Tabular for ReplayResponse {
+    fn table(&self) -> Report {
+        let replay = &self.replay;
+        let mut report = Report::new(["matchday", "team", "final_position", "probability"]);
+        for snapshot in &replay.snapshots {
+            for (team, &p) in snapshot.actual_position_probability.iter().enumerate() {
+                report.push(vec![
+                    Cell::Int(snapshot.matchday.into()),
+                    Cell::Text(replay.team_names[team].clone()),
+                    Cell::Int(replay.final_positions[team] as i64),
+                    Cell::Probability(p),
+                ]);
+            }
+        }
+        report
+    }
+}
+
+/// Replay a completed season matchday by matchday.
+///
+/// Every schedule row must be played. For each matchday before the last,
+/// the later results are hidden and the rest of the season is simulated;
+/// the answer lists each snapshot's probability matrix (rows in input
+/// order) and the probability it gave every team of its actual final
+/// position.
+#[utoipa::path(
+    post,
+    path = "/simulate/replay",
+    tag = "simulation",
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Probabilities after each matchday next to the final table", body = ReplayResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or unplayed matches", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_replay(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+    let replay = telemetry::spawn_blocking(move || {
+        replay_season(&job.season, &job.params, &job.team_names)
+            .map(|replay| (replay, job.params.iterations))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let (replay, simulations_performed) = replay
+        .ok_or_else(|| ApiError::field("schedule", "a replay needs every schedule row played"))?;
+
+    Ok(format.render(ReplayResponse {
+        replay,
+        simulations_performed,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
@@ -0,0 +1,296 @@
+// Response formats of the results endpoints: JSON, MessagePack for compact
+// binary answers, and CSV and Markdown for spreadsheets and GitHub job
+// summaries. Chosen with `?format=json|msgpack|csv|markdown` or an Accept
+// header of application/json, application/msgpack, text/csv or
+// text/markdown; the query parameter wins. CSV and Markdown hold each
+// response's main table (see `Tabular`); on /simulate `?table=projected`
+// renders the projected final table instead of the position probabilities.
+// `?locale=de` writes both with decimal commas, and CSV with semicolons, as
+// German spreadsheets expect.
+
+use crate::error::ApiError;
+use crate::handlers::{serialize, SimulateResponse};
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use league_sim_core::reports::{Cell, Locale, Report};
+use serde::{Deserialize, Serialize};
+use tracing::info_span;
+use utoipa::openapi::path::ParameterIn;
+use utoipa::openapi::{ContentBuilder, OpenApi, RefOr};
+use utoipa::{IntoParams, ToSchema};
+
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Routes answering in every format of [`ReportFormat`].
+pub const RESULT_PATHS: &[&str] = &[
+    "/simulate",
+    "/simulate/batch",
+    "/simulate/team",
+    "/simulate/outcomes",
+    "/simulate/elo-trajectory",
+    "/simulate/replay",
+    "/simulate/multi-season",
+    "/simulate/league-system",
+    "/tournament",
+    "/tournament/groups",
+    "/tournament/draw",
+    "/sessions",
+    "/sessions/{id}",
+    "/sessions/{id}/events",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Csv,
+    Markdown,
+    /// Arrow IPC stream, from /simulate/batch only
+    Arrow,
+}
+
+impl ReportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => MSGPACK_CONTENT_TYPE,
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Arrow => ARROW_STREAM_CONTENT_TYPE,
+        }
+    }
+
+    /// Suffix distinguishing the ETags of the representations.
+    pub(crate) fn etag_suffix(self) -> &'static str {
+        match self {
+            Self::Json => "",
+            Self::MessagePack => "-msgpack",
+            Self::Csv => "-csv",
+            Self::Markdown => "-md",
+            Self::Arrow => "-arrow",
+        }
+    }
+
+    /// The first format the Accept header names, else JSON.
+    fn from_accept(headers: &HeaderMap) -> Self {
+        for media_type in accepted_media_types(headers) {
+            match media_type {
+                "application/json" => return Self::Json,
+                MSGPACK_CONTENT_TYPE | "application/x-msgpack" => return Self::MessagePack,
+                "text/csv" => return Self::Csv,
+                "text/markdown" => return Self::Markdown,
+                ARROW_STREAM_CONTENT_TYPE => return Self::Arrow,
+                _ => {}
+            }
+        }
+        Self::Json
+    }
+}
+
+/// The error for `format=arrow` outside /simulate/batch.
+pub(crate) fn arrow_unavailable() -> ApiError {
+    ApiError::field(
+        "format",
+        "Arrow streams are only produced by /simulate/batch",
+    )
+}
+
+/// Results with a main table, rendered by the CSV and Markdown formats.
+pub trait Tabular {
+    fn table(&self) -> Report;
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct FormatOptions {
+    /// Response format (default: from the Accept header, else JSON)
+    format: Option<ReportFormat>,
+    /// Number conventions of CSV and Markdown answers (default: en)
+    locale: Option<Locale>,
+}
+
+/// Format a results endpoint answers in: the query's `format`, else the
+/// first supported media type of the Accept header, else JSON; and the
+/// query's `locale`.
+#[derive(Debug, Clone, Copy)]
+pub struct Negotiated(pub ReportFormat, pub Locale);
+
+impl<S: Send + Sync> FromRequestParts<S> for Negotiated {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        let Query(options) = Query::<FormatOptions>::try_from_uri(&parts.uri)
+            .map_err(|e| ApiError::field("format", e.body_text()))?;
+        Ok(Self(
+            options
+                .format
+                .unwrap_or_else(|| ReportFormat::from_accept(&parts.headers)),
+            options.locale.unwrap_or_default(),
+        ))
+    }
+}
+
+impl Negotiated {
+    /// `value` in the negotiated format, serialized inside its own span.
+    pub fn render<T: Serialize + Tabular>(self, value: T) -> Response {
+        encode(value, self.0, self.1, Tabular::table)
+    }
+}
+
+/// `value` as JSON or MessagePack, or `table(&value)` as CSV or Markdown in
+/// `locale`.
+fn encode<T: Serialize>(
+    value: T,
+    format: ReportFormat,
+    locale: Locale,
+    table: impl FnOnce(&T) -> Report,
+) -> Response {
+    match format {
+        ReportFormat::Json => return serialize(value),
+        ReportFormat::Arrow => return arrow_unavailable().into_response(),
+        _ => {}
+    }
+    let _span = info_span!("serialize").entered();
+    let body = match format {
+        // Encoded from the JSON value tree, so both formats have the same
+        // shape (rmp-serde would write u128 durations as 16-byte binaries).
+        ReportFormat::MessagePack => match serde_json::to_value(&value)
+            .map_err(|e| e.to_string())
+            .and_then(|tree| rmp_serde::to_vec_named(&tree).map_err(|e| e.to_string()))
+        {
+            Ok(bytes) => bytes,
+            Err(e) => return ApiError::Internal(e).into_response(),
+        },
+        ReportFormat::Csv => table(&value).to_csv_in(locale).into_bytes(),
+        _ => table(&value).to_markdown_in(locale).into_bytes(),
+    };
+    ([(header::CONTENT_TYPE, format.content_type())], body).into_response()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTable {
+    /// Position probabilities, one row per team
+    #[default]
+    Probabilities,
+    /// Projected final table
+    Projected,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct ReportOptions {
+    /// Response format (default: from the Accept header, else JSON)
+    format: Option<ReportFormat>,
+    /// Table rendered by the CSV and Markdown formats (default:
+    /// probabilities)
+    table: Option<ReportTable>,
+    /// Number conventions of CSV and Markdown answers (default: en)
+    locale: Option<Locale>,
+}
+
+impl ReportOptions {
+    /// The query's format, else the first format the Accept header names.
+    pub fn format(&self, headers: &HeaderMap) -> ReportFormat {
+        self.format
+            .unwrap_or_else(|| ReportFormat::from_accept(headers))
+    }
+
+    /// Suffix distinguishing the ETags of the representations: the format
+    /// and, for the tables, which one and its locale.
+    pub(crate) fn etag_suffix(&self, format: ReportFormat) -> String {
+        let mut suffix = format.etag_suffix().to_string();
+        if matches!(format, ReportFormat::Csv | ReportFormat::Markdown) {
+            if self.table.unwrap_or_default() == ReportTable::Projected {
+                suffix.push_str("-projected");
+            }
+            if self.locale.unwrap_or_default() == Locale::De {
+                suffix.push_str("-de");
+            }
+        }
+        suffix
+    }
+
+    /// `response` in `format`, serialized inside its own span.
+    pub fn render(&self, response: SimulateResponse, format: ReportFormat) -> Response {
+        let table = self.table.unwrap_or_default();
+        let locale = self.locale.unwrap_or_default();
+        encode(response, format, locale, |response| match table {
+            ReportTable::Probabilities => response.table(),
+            ReportTable::Projected => projected_table(response),
+        })
+    }
+}
+
+/// Media types of the Accept header in the order given, parameters
+/// stripped.
+fn accepted_media_types(headers: &HeaderMap) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.split(';').next().unwrap_or_default().trim())
+}
+
+/// Add the `format` parameter and the MessagePack, CSV and Markdown answers
+/// to the GET and POST operations of [`RESULT_PATHS`] in `spec`.
+pub fn document(spec: &mut OpenApi) {
+    for path in RESULT_PATHS {
+        let Some(item) = spec.paths.paths.get_mut(*path) else {
+            continue;
+        };
+        for operation in [item.get.as_mut(), item.post.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            let parameters = operation.parameters.get_or_insert_with(Vec::new);
+            if !parameters
+                .iter()
+                .any(|p| matches!(p, RefOr::T(p) if p.name == "format"))
+            {
+                parameters.extend(
+                    FormatOptions::into_params(|| Some(ParameterIn::Query))
+                        .into_iter()
+                        .map(RefOr::T),
+                );
+            }
+            for status in ["200", "201"] {
+                let Some(RefOr::T(ok)) = operation.responses.responses.get_mut(status) else {
+                    continue;
+                };
+                for content_type in [MSGPACK_CONTENT_TYPE, "text/csv", "text/markdown"] {
+                    ok.content
+                        .entry(content_type.to_string())
+                        .or_insert_with(|| RefOr::T(ContentBuilder::new().build()));
+                }
+            }
+        }
+    }
+}
+
+fn projected_table(response: &SimulateResponse) -> Report {
+    let mut report = Report::new([
+        "position",
+        "team",
+        "expected_points",
+        "expected_goals_for",
+        "expected_goals_against",
+        "expected_goal_difference",
+    ]);
+    for row in &response.projected_table {
+        report.push(vec![
+            Cell::Int(row.position as i64),
+            Cell::Text(row.team_name.clone()),
+            Cell::Number(row.expected_points),
+            Cell::Number(row.expected_goals_for),
+            Cell::Number(row.expected_goals_against),
+            Cell::Number(row.expected_goal_difference),
+        ]);
+    }
+    report
+}
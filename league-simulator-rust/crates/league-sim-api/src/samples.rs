@@ -0,0 +1,60 @@
+// Per-iteration outcomes as a Parquet file: one row per (iteration, team)
+// with the final position and points, so R or Python can analyse the raw
+// seasons without re-running the simulation.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::telemetry;
+use crate::AppState;
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use league_sim_core::export::write_samples_parquet;
+use league_sim_core::run_season_samples;
+use tracing::info_span;
+
+pub const PARQUET_CONTENT_TYPE: &str = "application/vnd.apache.parquet";
+
+/// Simulate a league and return every iteration's final table as Parquet.
+#[utoipa::path(
+    post,
+    path = "/simulate/samples",
+    tag = "simulation",
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Parquet file: iteration, team_id, team, position, points", content_type = "application/vnd.apache.parquet"),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_samples(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+    let bytes = telemetry::spawn_blocking(move || {
+        let samples = run_season_samples(&job.season, &job.params);
+        info_span!("serialize")
+            .in_scope(|| write_samples_parquet(&samples, &job.team_names, Vec::new()))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, PARQUET_CONTENT_TYPE),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"samples.parquet\"",
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
@@ -0,0 +1,395 @@
+// Matchday sessions for live tickers. POST /sessions opens a session from a
+// /simulate body; POST /sessions/{id}/events pushes scores as they happen and
+// returns the recomputed probabilities. Matches in progress are completed
+// from their current score, so only the minutes left are simulated. Sessions
+// are kept in memory and dropped after a configurable idle time.
+
+use crate::auth::Budget;
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulateResponse, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use league_sim_core::reports::Report;
+use league_sim_core::{run_live_simulation, LiveMatch};
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Default time a session survives without any request.
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(4 * 3600);
+
+/// Default number of sessions open at once.
+const DEFAULT_MAX_SESSIONS: usize = 1000;
+
+/// A score update for one schedule row.
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub struct MatchEvent {
+    /// 0-based row of the session's schedule
+    pub row: usize,
+    pub goals_home: i32,
+    pub goals_away: i32,
+    /// Minute the score was reached; omit once the match has finished
+    pub minute: Option<u32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SessionEvents {
+    /// Applied in order, a later event for the same row replaces an earlier one
+    pub events: Vec<MatchEvent>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub session_id: String,
+    /// Matches currently in progress
+    pub live: Vec<MatchEvent>,
+    /// Probabilities after the latest update
+    pub result: SimulateResponse,
+}
+
+/// The position probabilities of the latest result.
+impl Tabular for SessionResponse {
+    fn table(&self) -> Report {
+        self.result.table()
+    }
+}
+
+struct Session {
+    job: SimulationJob,
+    /// Matches in progress by schedule row
+    live: BTreeMap<usize, LiveMatch>,
+    result: SimulateResponse,
+}
+
+impl Session {
+    fn response(&self, id: &str) -> SessionResponse {
+        SessionResponse {
+            session_id: id.to_string(),
+            live: self
+                .live
+                .values()
+                .map(|m| MatchEvent {
+                    row: m.index,
+                    goals_home: m.goals_home,
+                    goals_away: m.goals_away,
+                    minute: Some(m.minute),
+                })
+                .collect(),
+            result: self.result.clone(),
+        }
+    }
+}
+
+struct SessionEntry {
+    /// Held across the recomputation so updates to one session apply in order
+    session: Arc<tokio::sync::Mutex<Session>>,
+    touched: Instant,
+}
+
+/// In-memory session store shared by all request handlers.
+#[derive(Clone)]
+pub struct SessionRegistry {
+    sessions: Arc<Mutex<HashMap<String, SessionEntry>>>,
+    ttl: Duration,
+    max_sessions: usize,
+}
+
+impl SessionRegistry {
+    pub fn new(ttl: Duration, max_sessions: usize) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            max_sessions,
+        }
+    }
+
+    /// Idle TTL from `SESSION_TTL_SECONDS`, falling back to four hours, and
+    /// capacity from `MAX_SESSIONS`, falling back to 1000.
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        let ttl = env("SESSION_TTL_SECONDS")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SESSION_TTL);
+        Self::new(
+            ttl,
+            env("MAX_SESSIONS").map_or(DEFAULT_MAX_SESSIONS, |n: u64| n as usize),
+        )
+    }
+
+    /// Overloaded if `max_sessions` sessions are open; checked before the
+    /// first simulation of a new session so a full store costs nothing.
+    fn ensure_room(&self) -> Result<(), ApiError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        self.purge_expired(&mut sessions);
+        if sessions.len() >= self.max_sessions {
+            return Err(ApiError::Overloaded);
+        }
+        Ok(())
+    }
+
+    /// Store a new session; Overloaded if the store filled up meanwhile.
+    fn insert(&self, session: Session) -> Result<String, ApiError> {
+        let id = format!("{:016x}", rand::rng().random::<u64>());
+        let mut sessions = self.sessions.lock().unwrap();
+        self.purge_expired(&mut sessions);
+        if sessions.len() >= self.max_sessions {
+            return Err(ApiError::Overloaded);
+        }
+        sessions.insert(
+            id.clone(),
+            SessionEntry {
+                session: Arc::new(tokio::sync::Mutex::new(session)),
+                touched: Instant::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// The session `id`, resetting its idle timer; None if unknown or expired.
+    fn get(&self, id: &str) -> Option<Arc<tokio::sync::Mutex<Session>>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        self.purge_expired(&mut sessions);
+        let entry = sessions.get_mut(id)?;
+        entry.touched = Instant::now();
+        Some(entry.session.clone())
+    }
+
+    fn remove(&self, id: &str) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        self.purge_expired(&mut sessions);
+        sessions.remove(id).is_some()
+    }
+
+    fn purge_expired(&self, sessions: &mut HashMap<String, SessionEntry>) {
+        sessions.retain(|_, entry| entry.touched.elapsed() < self.ttl);
+    }
+}
+
+fn not_found(id: &str) -> ApiError {
+    ApiError::NotFound(format!("session '{}' not found", id))
+}
+
+/// Simulate `job` with the matches in `live` completed from their current
+/// score.
+fn recompute(job: &SimulationJob, live: &BTreeMap<usize, LiveMatch>) -> SimulateResponse {
+    let start = Instant::now();
+    let response = if live.is_empty() {
+        job.simulate(start)
+    } else {
+        let live: Vec<LiveMatch> = live.values().copied().collect();
+        let result = run_live_simulation(&job.season, &job.params, job.team_names.clone(), &live);
+        job.respond(result, start.elapsed())
+    };
+    crate::metrics::add_iterations(response.simulations_performed);
+    response
+}
+
+/// Open a matchday session from a /simulate body.
+#[utoipa::path(
+    post,
+    path = "/sessions",
+    tag = "sessions",
+    request_body = SimulateRequest,
+    responses(
+        (status = 201, description = "Session opened with its initial probabilities", body = SessionResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue or session store full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn open_session(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let mut job = SimulationJob::from_request(payload, &state.config.current())?;
+    // Live recomputations sample every fixture, so the first run does too.
+    job.monte_carlo_only("in sessions")?;
+    if job.scenarios.is_some() {
+        return Err(ApiError::field(
+            "scenarios",
+            "scenarios are not supported in sessions",
+        ));
+    }
+    if let Some(field) = job.stopping_rule() {
+        return Err(ApiError::field(
+            field,
+            format!("{} is not supported in sessions", field),
+        ));
+    }
+    if job.until_matchday.is_some() {
+        return Err(ApiError::field(
+            "simulate_until_matchday",
+            "sessions follow the whole schedule; simulate_until_matchday is not supported",
+        ));
+    }
+
+    state.sessions.ensure_room()?;
+
+    let session = telemetry::spawn_blocking(move || {
+        let live = BTreeMap::new();
+        let result = recompute(&job, &live);
+        Session { job, live, result }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let id = state.sessions.insert(session)?;
+    let session = state.sessions.get(&id).ok_or_else(|| not_found(&id))?;
+    let response = session.lock().await.response(&id);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, format!("/sessions/{}", id))],
+        format.render(response),
+    )
+        .into_response())
+}
+
+/// Latest probabilities and the matches in progress.
+#[utoipa::path(
+    get,
+    path = "/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id from POST /sessions")),
+    responses(
+        (status = 200, description = "Current session state", body = SessionResponse),
+        (status = 404, description = "Unknown or expired session", body = ErrorBody)
+    )
+)]
+pub async fn get_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    format: Negotiated,
+) -> Result<Response, ApiError> {
+    let session = state.sessions.get(&id).ok_or_else(|| not_found(&id))?;
+    let session = session.lock().await;
+    Ok(format.render(session.response(&id)))
+}
+
+/// Apply score updates and recompute the probabilities. An event with a
+/// `minute` marks the match as in progress; one without records the final
+/// result.
+#[utoipa::path(
+    post,
+    path = "/sessions/{id}/events",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id from POST /sessions")),
+    request_body = SessionEvents,
+    responses(
+        (status = 200, description = "Probabilities after the update", body = SessionResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid event", body = ErrorBody),
+        (status = 404, description = "Unknown or expired session", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn push_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    budget: Option<Extension<Budget>>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SessionEvents>,
+) -> Result<Response, ApiError> {
+    let session = state.sessions.get(&id).ok_or_else(|| not_found(&id))?;
+    let mut session = session.lock().await;
+
+    let rows = session.job.season.matches.len();
+    for (i, event) in payload.events.iter().enumerate() {
+        if event.row >= rows {
+            return Err(ApiError::field(
+                format!("events[{}].row", i),
+                format!(
+                    "event {}: row {} out of range (schedule has {} rows)",
+                    i, event.row, rows
+                ),
+            ));
+        }
+        if event.goals_home < 0 || event.goals_away < 0 {
+            return Err(ApiError::field(
+                format!("events[{}]", i),
+                format!("event {}: goals must be non-negative", i),
+            ));
+        }
+    }
+
+    let mut job = session.job.clone();
+    let mut live = session.live.clone();
+    for event in payload.events {
+        let m = &mut job.season.matches[event.row];
+        match event.minute {
+            Some(minute) => {
+                m.goals_home = None;
+                m.goals_away = None;
+                live.insert(
+                    event.row,
+                    LiveMatch {
+                        index: event.row,
+                        goals_home: event.goals_home,
+                        goals_away: event.goals_away,
+                        minute,
+                    },
+                );
+            }
+            None => {
+                m.goals_home = Some(event.goals_home);
+                m.goals_away = Some(event.goals_away);
+                live.remove(&event.row);
+            }
+        }
+    }
+
+    // Without matches in progress the full job runs again, extra passes
+    // included; otherwise only the main pass completes the live matches.
+    if let Some(Extension(budget)) = budget {
+        budget.charge(if live.is_empty() {
+            job.iterations_per_run()
+        } else {
+            job.params.iterations
+        })?;
+    }
+
+    let (job, live, result) = telemetry::spawn_blocking(move || {
+        let result = recompute(&job, &live);
+        (job, live, result)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    *session = Session { job, live, result };
+    Ok(format.render(session.response(&id)))
+}
+
+/// Close a session.
+#[utoipa::path(
+    delete,
+    path = "/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session id from POST /sessions")),
+    responses(
+        (status = 204, description = "Session closed"),
+        (status = 404, description = "Unknown or expired session", body = ErrorBody)
+    )
+)]
+pub async fn close_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if state.sessions.remove(&id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(not_found(&id))
+    }
+}
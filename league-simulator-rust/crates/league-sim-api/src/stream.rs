@@ -0,0 +1,152 @@
+// Server-Sent Events variant of /simulate for long-running requests.
+// The Monte Carlo run happens on the blocking pool in batches; each batch
+// emits a progress event so the Shiny UI can show feedback. The run takes
+// a simulation slot or queue place before the stream is opened and holds
+// it until the run ends, or until the batch after the client went away.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::AppState;
+use crate::{metrics, telemetry};
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use league_sim_core::run_monte_carlo_simulation_until;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tracing::{Instrument, Span};
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct StreamOptions {
+    /// Iterations per batch (default: 1000)
+    batch_size: Option<usize>,
+
+    /// Emit the interim probability matrix every N batches (default: 1)
+    matrix_every: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct ProgressEvent {
+    iterations_done: usize,
+    iterations_total: usize,
+}
+
+#[derive(Serialize)]
+struct MatrixEvent {
+    iterations_done: usize,
+    probability_matrix: Vec<Vec<f64>>,
+    team_names: Vec<String>,
+}
+
+/// Streams `progress` events after every batch, `matrix` events with the
+/// interim probability matrix every `matrix_every` batches, and a final
+/// `result` event carrying the same body as POST /simulate.
+#[utoipa::path(
+    post,
+    path = "/simulate/stream",
+    tag = "simulation",
+    params(StreamOptions),
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "`progress`, `matrix` and `result` events", content_type = "text/event-stream"),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody)
+    )
+)]
+pub async fn simulate_stream(
+    State(state): State<AppState>,
+    Query(options): Query<StreamOptions>,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let mut job = SimulationJob::from_request(payload, &state.config.current())?;
+    job.monte_carlo_only("by /simulate/stream")?;
+    if job.scenarios.is_some() {
+        return Err(ApiError::field(
+            "scenarios",
+            "scenarios are not supported by /simulate/stream",
+        ));
+    }
+    if let Some(field) = job.stopping_rule() {
+        return Err(ApiError::field(
+            field,
+            format!("{} is not supported by /simulate/stream", field),
+        ));
+    }
+    let batch_size = options.batch_size.unwrap_or(1000).max(1);
+    let matrix_every = options.matrix_every.unwrap_or(1).max(1);
+    let reservation = state.overload.reserve()?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+
+    let run = metrics::in_request(move || {
+        let start = std::time::Instant::now();
+        let mut batches = 0;
+        // Set once a send fails, i.e. the client went away; the run stops
+        // before its next batch.
+        let cancel = AtomicBool::new(false);
+        let send = |event| {
+            if tx.blocking_send(event).is_err() {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+
+        let run = run_monte_carlo_simulation_until(
+            &job.season,
+            &job.params,
+            job.team_names.clone(),
+            batch_size,
+            &cancel,
+            |progress| {
+                batches += 1;
+                send(json_event(
+                    "progress",
+                    &ProgressEvent {
+                        iterations_done: progress.iterations_done,
+                        iterations_total: progress.iterations_total,
+                    },
+                ));
+                if batches % matrix_every == 0
+                    && progress.iterations_done < progress.iterations_total
+                {
+                    let interim = progress.result(&job.team_names);
+                    send(json_event(
+                        "matrix",
+                        &MatrixEvent {
+                            iterations_done: progress.iterations_done,
+                            probability_matrix: interim.probability_matrix,
+                            team_names: interim.team_names,
+                        },
+                    ));
+                }
+                false
+            },
+        );
+        let Some((result, done)) = run else {
+            return;
+        };
+        metrics::add_iterations(done);
+
+        let response = job.respond(result, start.elapsed());
+        let _ = tx.blocking_send(json_event("result", &response));
+    });
+    tokio::spawn(
+        reservation
+            .run(async move { telemetry::spawn_blocking(run).await })
+            .instrument(Span::current()),
+    );
+
+    Ok(Sse::new(ReceiverStream::new(rx).map(Ok)).keep_alive(KeepAlive::default()))
+}
+
+fn json_event(name: &str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
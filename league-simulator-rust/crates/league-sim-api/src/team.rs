@@ -0,0 +1,188 @@
+// Everything about one team in a single compact answer: its position and
+// points distributions, zone probabilities and the odds of each of its
+// remaining fixtures, for team pages that do not need the whole matrix.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    response::Response,
+};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::{
+    fixture_probabilities, position_bounds, run_hybrid_simulation, run_team_distribution,
+    PointsProbability, Solver,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Deserialize, IntoParams)]
+pub struct TeamOptions {
+    /// Name of the team, as in `team_names` (default names are "Team_1",
+    /// "Team_2", ...)
+    team: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TeamDetailResponse {
+    pub team_name: String,
+
+    /// Probability of each final position, first place first
+    pub position_probabilities: Vec<f64>,
+
+    /// Probability of each final points total that occurred, fewest points
+    /// first
+    pub points_distribution: Vec<PointsProbability>,
+
+    /// Best and worst final position the team can still reach
+    pub best_position: usize,
+    pub worst_position: usize,
+
+    /// Zone name -> probability of finishing in it (requests with a
+    /// structure only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone_probabilities: Option<BTreeMap<String, f64>>,
+
+    /// The team's unplayed matches in schedule order
+    pub fixtures: Vec<TeamFixture>,
+
+    pub simulations_performed: usize,
+    pub time_ms: u128,
+}
+
+/// The position probabilities, one row per position.
+impl Tabular for TeamDetailResponse {
+    fn table(&self) -> Report {
+        let mut report = Report::new(["team", "position", "probability"]);
+        for (position, &p) in self.position_probabilities.iter().enumerate() {
+            report.push(vec![
+                Cell::Text(self.team_name.clone()),
+                Cell::Int(position as i64 + 1),
+                Cell::Probability(p),
+            ]);
+        }
+        report
+    }
+}
+
+/// Outcome probabilities of one remaining match, from the team's side.
+#[derive(Serialize, ToSchema)]
+pub struct TeamFixture {
+    pub opponent: String,
+    pub home: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matchday: Option<u32>,
+    pub win: f64,
+    pub draw: f64,
+    pub loss: f64,
+}
+
+/// Simulate the season and describe one team: its final position and
+/// points distributions, zone probabilities and remaining fixture odds.
+///
+/// Fixture odds come from the Elo goal model at the ratings after the
+/// played matches, as in the projected table.
+#[utoipa::path(
+    post,
+    path = "/simulate/team",
+    tag = "simulation",
+    params(TeamOptions),
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "The team's distributions and fixture odds", body = TeamDetailResponse),
+        (status = 400, description = "Malformed JSON or missing team", body = ErrorBody),
+        (status = 422, description = "Invalid request or unknown team", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_team(
+    State(state): State<AppState>,
+    Query(options): Query<TeamOptions>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+    let team = job
+        .team_names
+        .iter()
+        .position(|name| *name == options.team)
+        .filter(|&team| team < job.season.number_teams)
+        .ok_or_else(|| ApiError::field("team", format!("unknown team '{}'", options.team)))?;
+
+    let response = telemetry::spawn_blocking(move || team_detail(&job, team, start))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(format.render(response))
+}
+
+fn team_detail(job: &SimulationJob, team: usize, start: std::time::Instant) -> TeamDetailResponse {
+    let mut distribution = run_team_distribution(&job.season, &job.params, team);
+    // Positions as /simulate reports them; the points stay sampled.
+    if job.solver == Solver::Hybrid {
+        let mut result = run_hybrid_simulation(&job.season, &job.params, job.team_names.clone());
+        if let Some(row) = result
+            .team_names
+            .iter()
+            .position(|n| Some(n) == job.team_names.get(team))
+        {
+            distribution.positions = result.probability_matrix.swap_remove(row);
+        }
+    }
+    let bounds = position_bounds(&job.season, &job.params)[team];
+
+    let zone_probabilities = job.structure().map(|structure| {
+        structure
+            .zones
+            .iter()
+            .map(|z| z.name.clone())
+            .zip(structure.zone_probabilities(&distribution.positions))
+            .collect()
+    });
+
+    let name = |t: usize| {
+        job.team_names
+            .get(t)
+            .cloned()
+            .unwrap_or_else(|| format!("Team_{}", t + 1))
+    };
+    let fixtures = fixture_probabilities(&job.season, &job.params)
+        .into_iter()
+        .filter_map(|(i, p)| {
+            let m = &job.season.matches[i];
+            let (home, opponent, win, loss) = if m.team_home == team {
+                (true, m.team_away, p.home_win, p.away_win)
+            } else if m.team_away == team {
+                (false, m.team_home, p.away_win, p.home_win)
+            } else {
+                return None;
+            };
+            Some(TeamFixture {
+                opponent: name(opponent),
+                home,
+                matchday: m.matchday,
+                win,
+                draw: p.draw,
+                loss,
+            })
+        })
+        .collect();
+
+    TeamDetailResponse {
+        team_name: name(team),
+        position_probabilities: distribution.positions,
+        points_distribution: distribution.points,
+        best_position: bounds.best,
+        worst_position: bounds.worst,
+        zone_probabilities,
+        fixtures,
+        simulations_performed: job.params.iterations,
+        time_ms: start.elapsed().as_millis(),
+    }
+}
@@ -0,0 +1,170 @@
+// Tracing setup. Every request gets a span continuing the caller's W3C
+// `traceparent`, so a request can be followed from the Shiny app through the
+// handlers into the engine's phases (parse, validate, simulate, aggregate,
+// serialize). With the `otel` feature and OTEL_EXPORTER_OTLP_ENDPOINT set,
+// spans are exported over OTLP/HTTP; the standard OTEL_* variables (service
+// name, headers, sampler) are honoured by the exporter.
+//
+// Logs are JSON lines (LOG_FORMAT=text for local runs). The request span
+// carries the request ID (X-Request-Id, generated if absent), so every line
+// logged while serving a request can be correlated with it.
+
+use axum::http::{HeaderMap, Request, Response};
+use std::time::Duration;
+use tracing::{field::Empty, info_span, Span};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+#[cfg(feature = "otel")]
+use opentelemetry::{
+    propagation::{Extractor, TextMapPropagator},
+    trace::TracerProvider as _,
+};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+#[cfg(feature = "otel")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Service name reported to the collector unless OTEL_SERVICE_NAME is set.
+#[cfg(feature = "otel")]
+const SERVICE_NAME: &str = "league-simulator";
+
+/// Flushes exported spans when dropped; keep it alive for the process.
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!(error = %e, "flushing spans failed");
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: JSON log lines (plain text with
+/// LOG_FORMAT=text) filtered by RUST_LOG (default `info`), plus the OTLP
+/// exporter if one is configured.
+pub fn init() -> Telemetry {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let text = std::env::var("LOG_FORMAT").is_ok_and(|v| v == "text");
+    let json = (!text).then(|| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_events(FmtSpan::NONE)
+    });
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(json)
+        .with(text.then(tracing_subscriber::fmt::layer));
+
+    #[cfg(feature = "otel")]
+    {
+        let (provider, error) = match otlp_provider() {
+            Ok(provider) => (provider, None),
+            Err(e) => (None, Some(e)),
+        };
+        let layer = provider.as_ref().map(|provider| {
+            tracing_opentelemetry::layer().with_tracer(provider.tracer("league-sim-api"))
+        });
+        registry.with(layer).init();
+        if let Some(e) = error {
+            tracing::warn!(error = %e, "OTLP exporter disabled");
+        }
+        Telemetry { provider }
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        Telemetry {}
+    }
+}
+
+/// Tracer provider exporting to OTEL_EXPORTER_OTLP_ENDPOINT; None if unset.
+#[cfg(feature = "otel")]
+fn otlp_provider() -> Result<Option<SdkTracerProvider>, opentelemetry_otlp::ExporterBuildError> {
+    if std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err() {
+        return Ok(None);
+    }
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()?;
+    let mut resource = Resource::builder();
+    if std::env::var("OTEL_SERVICE_NAME").is_err() {
+        resource = resource.with_service_name(SERVICE_NAME);
+    }
+    Ok(Some(
+        SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource.build())
+            .build(),
+    ))
+}
+
+/// Span for one HTTP request, a child of the caller's trace if the request
+/// carries a `traceparent` header. Runs inside `SetRequestIdLayer`, so the
+/// X-Request-Id header is always present.
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let span = info_span!(
+        "request",
+        request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        status = Empty,
+    );
+    set_remote_parent(&span, request.headers());
+    span
+}
+
+#[cfg(feature = "otel")]
+fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+    struct Headers<'a>(&'a HeaderMap);
+
+    impl Extractor for Headers<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    let parent = TraceContextPropagator::new().extract(&Headers(headers));
+    // Fails only when no OpenTelemetry layer is installed.
+    let _ = span.set_parent(parent);
+}
+
+#[cfg(not(feature = "otel"))]
+fn set_remote_parent(_span: &Span, _headers: &HeaderMap) {}
+
+/// Record the response status on the request span; `metrics::track` logs
+/// the request.
+pub fn record_response<B>(response: &Response<B>, _latency: Duration, span: &Span) {
+    span.record("status", response.status().as_u16());
+}
+
+/// `tokio::task::spawn_blocking` running `f` inside the current span, so
+/// the engine's spans stay attached to the request, counting its
+/// iterations towards the request's metrics and holding its simulation
+/// slot.
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = Span::current();
+    let f = crate::overload::holding_slot(crate::metrics::in_request(f));
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
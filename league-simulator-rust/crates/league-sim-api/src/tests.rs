@@ -0,0 +1,3990 @@
+//! HTTP-API handler tests.
+//!
+//! These tests exercise the `axum::Router` returned by `create_router` using
+//! `tower::ServiceExt::oneshot`, so no real port is opened. They pin the
+//! wire-format contract that the R-side scheduler depends on, and they
+//! document the validation paths in `simulate_league` (empty schedule, empty
+//! elo_values).
+
+use crate::create_router;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// Send `req` through the router and return (status, body).
+///
+/// Success responses are JSON and are parsed as such. Error responses (e.g.
+/// validation failures) are plain text — `(StatusCode, String)` rejections
+/// render as a text body, not JSON — so those are wrapped as a JSON string
+/// instead of failing the parse.
+async fn send(req: Request<Body>) -> (StatusCode, Value) {
+    let response = create_router()
+        .oneshot(req)
+        .await
+        .expect("router service should not fail");
+
+    let status = response.status();
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body collect")
+        .to_bytes();
+    let body: Value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+    };
+
+    (status, body)
+}
+
+fn post_simulate_json(payload: Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/simulate")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+        .unwrap()
+}
+
+/// A minimal valid simulate request: 2 teams, 1 played match plus 1 to
+/// simulate, low iteration count to keep tests fast.
+fn minimal_valid_simulate_payload() -> Value {
+    json!({
+        "schedule": [
+            [1, 2, 1, 0],          // played match
+            [2, 1, null, null]     // match to simulate
+        ],
+        "elo_values": [1500.0, 1500.0],
+        "iterations": 50
+    })
+}
+
+#[tokio::test]
+async fn health_returns_ok_with_status_version_and_performance_fields() {
+    let req = Request::builder()
+        .method("GET")
+        .uri("/health")
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert!(
+        body["version"].is_string(),
+        "version field must be present and a string, got: {body}"
+    );
+    assert!(
+        body["performance"].is_string(),
+        "performance field must be present and a string, got: {body}"
+    );
+}
+
+#[tokio::test]
+async fn simulate_returns_422_when_schedule_is_empty() {
+    let req = post_simulate_json(json!({
+        "schedule": [],
+        "elo_values": [1500.0, 1500.0],
+        "iterations": 10
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_returns_422_when_elo_values_is_empty() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 2, 1, 0]],
+        "elo_values": [],
+        "iterations": 10
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_happy_path_returns_probability_matrix_with_expected_shape() {
+    let req = post_simulate_json(minimal_valid_simulate_payload());
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::OK);
+
+    let matrix = body["probability_matrix"]
+        .as_array()
+        .expect("probability_matrix must be a JSON array");
+    assert_eq!(matrix.len(), 2, "matrix should have one row per team");
+    for row in matrix {
+        let cols = row.as_array().expect("each row must be an array");
+        assert_eq!(
+            cols.len(),
+            2,
+            "each row should have one column per position"
+        );
+        let row_sum: f64 = cols.iter().map(|v| v.as_f64().unwrap()).sum();
+        assert!(
+            (row_sum - 1.0).abs() < 1e-9,
+            "row probabilities must sum to 1, got {row_sum}"
+        );
+    }
+
+    let names = body["team_names"]
+        .as_array()
+        .expect("team_names must be a JSON array");
+    assert_eq!(names.len(), 2);
+
+    assert_eq!(
+        body["simulations_performed"].as_u64().unwrap(),
+        50,
+        "simulations_performed should reflect the requested iterations"
+    );
+    assert!(
+        body["time_ms"].is_number(),
+        "time_ms must be a number, got: {body}"
+    );
+}
+
+#[tokio::test]
+async fn simulate_uses_caller_supplied_team_names_in_response() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["team_names"] = json!(["Foo FC", "Bar United"]);
+
+    let req = post_simulate_json(payload);
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<String> = body["team_names"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap().to_string())
+        .collect();
+    assert!(
+        names.contains(&"Foo FC".to_string()),
+        "response team_names must contain caller-supplied 'Foo FC', got {names:?}"
+    );
+    assert!(
+        names.contains(&"Bar United".to_string()),
+        "response team_names must contain caller-supplied 'Bar United', got {names:?}"
+    );
+}
+
+#[tokio::test]
+async fn simulate_defaults_iterations_to_10000_when_not_provided() {
+    // Note: this is the slow test (10k iterations) — kept minimal (2 teams,
+    // 2-match schedule) so it still completes well under a second on dev
+    // hardware.
+    let req = post_simulate_json(json!({
+        "schedule": [
+            [1, 2, 1, 0],
+            [2, 1, null, null]
+        ],
+        "elo_values": [1500.0, 1500.0]
+    }));
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["simulations_performed"].as_u64().unwrap(),
+        10_000,
+        "default iterations should be 10000 when caller omits the field"
+    );
+}
+
+#[tokio::test]
+async fn simulate_rejects_team_index_zero() {
+    // team index 0 previously underflowed to usize::MAX and aborted the process
+    let req = post_simulate_json(json!({
+        "schedule": [[0, 2, null, null]],
+        "elo_values": [1500.0, 1500.0]
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_rejects_null_team_index() {
+    let req = post_simulate_json(json!({
+        "schedule": [[null, 2, null, null]],
+        "elo_values": [1500.0, 1500.0]
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_rejects_out_of_range_team_index() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 3, null, null]],
+        "elo_values": [1500.0, 1500.0]
+    }));
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "invalid_field");
+    assert_eq!(body["field"], "schedule[0]");
+    assert!(body["message"].as_str().unwrap().contains("out of range"));
+}
+
+#[tokio::test]
+async fn simulate_reports_every_invalid_field() {
+    let req = post_simulate_json(json!({
+        "schedule": [
+            [1, 2, -1, 0],
+            [2, 2, null, null],
+            [1, 3, 2, null]
+        ],
+        "elo_values": [1500.0, 1500.0, 1500.0, 1500.0]
+    }));
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "schedule[0]");
+    let messages: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(messages.len(), 4, "details: {messages:?}");
+    assert!(messages[0].contains("negative"));
+    assert!(messages[1].contains("cannot play itself"));
+    assert!(messages[2].contains("both be set"));
+    assert!(messages[3].contains("elo_values has length 4, but the schedule has 3 teams"));
+}
+
+#[tokio::test]
+async fn malformed_json_is_answered_with_an_error_body() {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/simulate")
+        .header("content-type", "application/json")
+        .body(Body::from("{\"schedule\": ["))
+        .unwrap();
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "malformed_json");
+    assert!(body.get("field").is_none());
+}
+
+#[tokio::test]
+async fn simulate_rejects_excessive_iterations() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 2, null, null]],
+        "elo_values": [1500.0, 1500.0],
+        "iterations": 100_000_000
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_rejects_mismatched_adjustment_length() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 2, null, null]],
+        "elo_values": [1500.0, 1500.0],
+        "adj_points": [0, 0, 0]
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_rejects_missing_extra_or_repeated_team_names() {
+    for (names, field) in [
+        (json!(["FCB"]), "team_names"),
+        (json!(["FCB", "BVB", "S04"]), "team_names"),
+        (json!(["FCB", "FCB"]), "team_names[1]"),
+    ] {
+        let mut payload = minimal_valid_simulate_payload();
+        payload["team_names"] = names;
+        let (status, body) = send(post_simulate_json(payload)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["field"], field, "body: {body}");
+    }
+}
+
+#[tokio::test]
+async fn simulate_accepts_current_table_with_remaining_fixtures() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 2, null, null]],
+        "elo_values": [1500.0, 1500.0],
+        "iterations": 50,
+        "current_table": [
+            {"played": 33, "points": 80, "goals_for": 70, "goals_against": 20},
+            {"played": 33, "points": 40, "goals_for": 40, "goals_against": 45}
+        ]
+    }));
+
+    let (status, body) = send(req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    // A 40-point lead with one match left is decisive.
+    assert_eq!(body["probability_matrix"][0][0].as_f64().unwrap(), 1.0);
+}
+
+#[tokio::test]
+async fn simulate_rejects_played_rows_in_table_only_mode() {
+    let req = post_simulate_json(json!({
+        "schedule": [[1, 2, 1, 0], [2, 1, null, null]],
+        "elo_values": [1500.0, 1500.0],
+        "current_table": [
+            {"played": 1, "points": 3, "goals_for": 1, "goals_against": 0},
+            {"played": 1, "points": 0, "goals_for": 0, "goals_against": 1}
+        ]
+    }));
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn simulate_accepts_alternative_rng_backend() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["rng"] = json!("xoshiro256_plus_plus");
+
+    let (status, _body) = send(post_simulate_json(payload)).await;
+
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn simulate_returns_projected_table_alongside_matrix() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["team_names"] = json!(["Foo FC", "Bar United"]);
+
+    let (status, body) = send(post_simulate_json(payload)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let rows = body["projected_table"]
+        .as_array()
+        .expect("projected_table must be a JSON array");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["position"], 1);
+    // Foo FC won the played match 1-0, so starts 3 points ahead.
+    assert_eq!(rows[0]["team_name"], "Foo FC");
+    assert!(rows[0]["expected_points"].as_f64().unwrap() >= 3.0);
+}
+
+#[tokio::test]
+async fn simulate_stream_emits_progress_and_final_result() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(100);
+    let req = Request::builder()
+        .method("POST")
+        .uri("/simulate/stream?batch_size=25&matrix_every=2")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+        .unwrap();
+
+    let response = create_router().oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+    assert_eq!(text.matches("event: progress").count(), 4);
+    // Interim matrices after batches 2 (and not 4: that is the final result).
+    assert_eq!(text.matches("event: matrix").count(), 1);
+    let result_data = text
+        .split("event: result\ndata: ")
+        .nth(1)
+        .expect("stream must end with a result event")
+        .lines()
+        .next()
+        .unwrap();
+    let result: Value = serde_json::from_str(result_data).unwrap();
+    assert_eq!(result["simulations_performed"], 100);
+}
+
+#[tokio::test]
+async fn simulate_stream_validates_before_streaming() {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/simulate/stream")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({"schedule": [], "elo_values": [1500.0]})).unwrap(),
+        ))
+        .unwrap();
+
+    let (status, _body) = send(req).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn csv_import_produces_a_simulate_request_body() {
+    let team_list =
+        "TeamID;ShortText;Promotion;InitialELO\n1;FCB;0;1900\n2;BVB;0;1750\n3;FCB2;-50;1300\n";
+    let fixtures =
+        "home,away,goals_home,goals_away,matchday\nFCB,BVB,2,1,1\nBVB,FCB2,,,2\nFCB2,FCB,,,3\n";
+    let (status, body) = send(post_json(
+        "/import/csv",
+        &json!({"team_list": team_list, "fixtures": fixtures}),
+    ))
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["team_names"], json!(["BVB", "FCB", "FCB2"]));
+    assert_eq!(
+        body["schedule"],
+        json!([[2, 1, 2, 1], [1, 3, null, null], [3, 2, null, null]])
+    );
+    assert_eq!(body["elo_values"], json!([1750.0, 1900.0, 1300.0]));
+    assert_eq!(body["promotion"], json!([0, 0, -50]));
+
+    // The body simulates as is.
+    let (status, _) = send(post_simulate_json(body)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(post_json(
+        "/import/csv",
+        &json!({"team_list": team_list, "fixtures": "home,away,goals_home,goals_away\nFCB,HSV,,\n"}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        body["message"].as_str().unwrap().contains("HSV"),
+        "{}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn generated_fixtures_are_a_simulate_request_body() {
+    let teams = json!([
+        {"name": "FCB", "elo": 1900.0},
+        {"name": "BVB", "elo": 1750.0},
+        {"name": "SCF", "elo": 1550.0},
+        {"name": "KOE", "elo": 1450.0}
+    ]);
+    let (status, body) = send(post_json("/fixtures/generate", &json!({"teams": teams}))).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["team_names"], json!(["FCB", "BVB", "SCF", "KOE"]));
+    assert_eq!(body["schedule"].as_array().unwrap().len(), 12);
+    assert_eq!(body["matchdays"][11], 6);
+    assert!(body["schedule"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|row| row[2].is_null() && row[3].is_null()));
+
+    // The body simulates as is, without schedule warnings.
+    let mut request = body.clone();
+    request["iterations"] = json!(100);
+    let (status, simulated) = send(post_simulate_json(request)).await;
+    assert_eq!(status, StatusCode::OK, "{}", simulated);
+    assert!(
+        simulated.get("schedule_warnings").is_none(),
+        "{}",
+        simulated
+    );
+
+    // A seed draws the pairings, the same way every time.
+    let seeded = json!({"teams": teams, "seed": 3});
+    let (_, first) = send(post_json("/fixtures/generate", &seeded)).await;
+    let (_, again) = send(post_json("/fixtures/generate", &seeded)).await;
+    assert_eq!(first["schedule"], again["schedule"]);
+
+    let invalid =
+        json!({"teams": [{"name": "FCB", "elo": 1900.0}, {"name": "FCB", "elo": 1800.0}]});
+    let (status, body) = send(post_json("/fixtures/generate", &invalid)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "teams[1].name");
+}
+
+#[tokio::test]
+async fn generated_fixtures_can_be_a_swiss_league_phase() {
+    let teams: Vec<Value> = (0..8)
+        .map(|i| json!({"name": format!("T{}", i), "elo": 1500.0 + 10.0 * i as f64}))
+        .collect();
+    let swiss = json!({"teams": teams, "rounds": 3, "seed": 5});
+    let (status, body) = send(post_json("/fixtures/generate", &swiss)).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let schedule = body["schedule"].as_array().unwrap();
+    assert_eq!(schedule.len(), 12);
+    assert_eq!(body["matchdays"][11], 3);
+    let mut pairs: Vec<(i64, i64)> = schedule
+        .iter()
+        .map(|row| {
+            let (a, b) = (row[0].as_i64().unwrap(), row[1].as_i64().unwrap());
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    assert_eq!(pairs.len(), 12, "no pairing repeats");
+    let (_, again) = send(post_json("/fixtures/generate", &swiss)).await;
+    assert_eq!(body["schedule"], again["schedule"]);
+
+    let mut request = body.clone();
+    request["iterations"] = json!(100);
+    let (status, simulated) = send(post_simulate_json(request)).await;
+    assert_eq!(status, StatusCode::OK, "{}", simulated);
+
+    let odd = json!({"teams": teams[..7], "rounds": 8});
+    let (status, body) = send(post_json("/fixtures/generate", &odd)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["rounds", "rounds"]);
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn simulate_samples_returns_a_parquet_file() {
+    let response = create_router()
+        .oneshot(post_json(
+            "/simulate/samples",
+            &minimal_valid_simulate_payload(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/vnd.apache.parquet"
+    );
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+
+    let (status, _) = send(post_json(
+        "/simulate/samples",
+        &json!({"schedule": [], "elo_values": [1500.0]}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (_, spec) = send(get("/openapi.json")).await;
+    assert!(spec["paths"].get("/simulate/samples").is_some());
+}
+
+#[cfg(feature = "arrow")]
+#[tokio::test]
+async fn simulate_batch_returns_an_arrow_stream() {
+    use arrow_array::{Float64Array, StringArray};
+    use arrow_ipc::reader::StreamReader;
+
+    let app = create_router();
+    let by_query = post_json("/simulate/batch?format=arrow", &batch_payload());
+    let mut by_accept = post_json("/simulate/batch", &batch_payload());
+    by_accept.headers_mut().insert(
+        "accept",
+        "application/vnd.apache.arrow.stream".parse().unwrap(),
+    );
+    for req in [by_query, by_accept] {
+        let response = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "application/vnd.apache.arrow.stream"
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let reader = StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        assert!(reader.schema().metadata().contains_key("total_time_ms"));
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        // Two leagues of two teams with two positions each.
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 8);
+
+        let batch = &batches[0];
+        let leagues = batch
+            .column_by_name("league")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(leagues.value(0), "Liga A");
+        assert_eq!(leagues.value(7), "Liga B");
+        let probabilities = batch
+            .column_by_name("probability")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        // Each team's row sums to one.
+        let first_team: f64 = probabilities.values()[..2].iter().sum();
+        assert!((first_team - 1.0).abs() < 1e-9);
+    }
+
+    // JSON stays the default.
+    let (status, body) = send_to(&app, post_json("/simulate/batch", &batch_payload())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"].as_array().unwrap().len(), 2);
+
+    let (_, spec) = send(get("/openapi.json")).await;
+    let batch_op = &spec["paths"]["/simulate/batch"]["post"];
+    assert!(batch_op["responses"]["200"]["content"]
+        .get("application/vnd.apache.arrow.stream")
+        .is_some());
+}
+
+#[tokio::test]
+async fn seeded_requests_reproduce_their_probabilities() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1500, 1500, 1500]);
+    payload["iterations"] = json!(200);
+    payload["seed"] = json!(2024);
+    let (status, single) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {single}");
+    assert_eq!(single["seed"], 2024);
+
+    // The batch route skips the cache and runs in batches, yet agrees.
+    let batch = json!({"leagues": [{"name": "L", "request": payload.clone()}]});
+    let (status, body) = send(post_json("/simulate/batch", &batch)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let league = &body["results"][0]["response"];
+    assert_eq!(league["cache_hit"], false);
+    assert_eq!(league["probability_matrix"], single["probability_matrix"]);
+
+    payload["seed"] = json!(2025);
+    let (_, other) = send(post_simulate_json(payload.clone())).await;
+    assert_ne!(other["probability_matrix"], single["probability_matrix"]);
+
+    payload.as_object_mut().unwrap().remove("seed");
+    let (_, unseeded) = send(post_simulate_json(payload)).await;
+    assert!(unseeded.get("seed").is_none());
+}
+
+#[tokio::test]
+async fn metadata_reports_the_seed_to_rerun_an_unseeded_request() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1500, 1500, 1500]);
+    let (status, first) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {first}");
+    let metadata = &first["metadata"];
+    assert_eq!(metadata["engine_version"], env!("CARGO_PKG_VERSION"));
+    assert!(!metadata["git_sha"].as_str().unwrap().is_empty());
+    assert_eq!(metadata["iterations"], 50);
+    assert!(metadata["threads"].as_u64().unwrap() >= 1);
+    // Defaults are filled in.
+    assert_eq!(metadata["params"]["iterations"], 50);
+    assert!(metadata["params"]["home_advantage"].is_number());
+    assert_eq!(metadata["params"]["seed"], metadata["seed"]);
+
+    payload["seed"] = metadata["seed"].clone();
+    let (_, rerun) = send(post_simulate_json(payload)).await;
+    assert_eq!(rerun["probability_matrix"], first["probability_matrix"]);
+    assert_eq!(rerun["metadata"]["seed"], metadata["seed"]);
+}
+
+#[tokio::test]
+async fn coordinator_merges_worker_shards_into_the_single_node_result() {
+    let mut workers = Vec::new();
+    for _ in 0..2 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        workers.push(format!("http://{}", listener.local_addr().unwrap()));
+        tokio::spawn(async move { axum::serve(listener, create_router()).await.unwrap() });
+    }
+    // Nothing listens here any more: its shard falls back to the coordinator.
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    workers.push(format!("http://{}", closed.local_addr().unwrap()));
+    drop(closed);
+
+    let coordinator = crate::create_router_with_state(crate::AppState {
+        workers: crate::distributed::Workers::new(workers, None),
+        ..crate::AppState::from_env()
+    });
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1600, 1500, 1400]);
+    payload["iterations"] = json!(301);
+    payload["seed"] = json!(77);
+
+    let (status, sharded) = send_to(&coordinator, post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {sharded}");
+    let (_, single) = send(post_simulate_json(payload)).await;
+    assert_eq!(sharded["probability_matrix"], single["probability_matrix"]);
+    assert_eq!(sharded["team_names"], single["team_names"]);
+    assert_eq!(sharded["simulations_performed"], 301);
+}
+
+#[tokio::test]
+async fn coordinator_recomputes_shards_with_inconsistent_counts() {
+    // Each fake worker answers with a full league's rows, but short rows or
+    // rows that do not add up to the shard's iterations.
+    let bad_answers = [
+        json!({"counts": [[1, 0], [0, 1], [1, 1]], "first_iteration": 0, "iterations": 1}),
+        json!({"counts": [[9, 9, 9], [0, 0, 0], [0, 0, 0]], "first_iteration": 0, "iterations": 1}),
+    ];
+    let mut workers = Vec::new();
+    for answer in bad_answers {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        workers.push(format!("http://{}", listener.local_addr().unwrap()));
+        let fake = axum::Router::new().route(
+            "/simulate/shard",
+            axum::routing::post(move || async move { axum::Json(answer) }),
+        );
+        tokio::spawn(async move { axum::serve(listener, fake).await.unwrap() });
+    }
+
+    let coordinator = crate::create_router_with_state(crate::AppState {
+        workers: crate::distributed::Workers::new(workers, None),
+        ..crate::AppState::from_env()
+    });
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1600, 1500, 1400]);
+    payload["iterations"] = json!(200);
+    payload["seed"] = json!(77);
+
+    let (status, sharded) = send_to(&coordinator, post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {sharded}");
+    let (_, single) = send(post_simulate_json(payload)).await;
+    assert_eq!(sharded["probability_matrix"], single["probability_matrix"]);
+}
+
+#[tokio::test]
+async fn shard_requests_need_the_seed_of_their_run() {
+    let season = json!({
+        "matches": [{"team_home": 0, "team_away": 1, "goals_home": null, "goals_away": null}],
+        "team_elos": [1500.0, 1500.0],
+        "number_teams": 2
+    });
+    let params = serde_json::to_value(league_sim_core::SimulationParams::default()).unwrap();
+    let mut shard = json!({
+        "season": season,
+        "params": params,
+        "first_iteration": 10,
+        "iterations": 50
+    });
+    let (status, body) = send(post_json("/simulate/shard", &shard)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "body: {body}");
+    assert_eq!(body["field"], "params.seed");
+
+    shard["params"]["seed"] = json!(5);
+    let (status, body) = send(post_json("/simulate/shard", &shard)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let counts = body["counts"].as_array().unwrap();
+    let total: u64 = counts[0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_u64().unwrap())
+        .sum();
+    assert_eq!(total, 50);
+
+    // Engine inputs that would crash the simulation are turned away.
+    let shard_with_seed = shard.clone();
+    let mut bad = shard;
+    bad["params"]["adj_points"] = json!([0]);
+    bad["season"]["matches"][0]["goals_home"] = json!(1);
+    bad["season"]["matches"][0]["goals_away"] = Value::Null;
+    let (status, body) = send(post_json("/simulate/shard", &bad)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "body: {body}");
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["season.matches[0]", "params.adj_points"]);
+
+    let mut bad = shard_with_seed;
+    bad["params"]["team_strengths"] = json!([
+        {"attack": 1e200, "defense": 1.0},
+        {"attack": 1.0, "defense": 1.0}
+    ]);
+    let (status, body) = send(post_json("/simulate/shard", &bad)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "body: {body}");
+    assert_eq!(body["field"], "params.team_strengths");
+}
+
+#[tokio::test]
+async fn batch_keeps_league_order_and_times_each_league() {
+    let names = ["A", "B", "C", "D", "E"];
+    let leagues: Vec<Value> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let mut request = minimal_valid_simulate_payload();
+            request["elo_values"] = json!([1500.0 + i as f64, 1500.0]);
+            json!({"name": name, "request": request})
+        })
+        .collect();
+    let (status, body) = send(post_json("/simulate/batch", &json!({"leagues": leagues}))).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let total = body["total_time_ms"].as_u64().unwrap();
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), names.len());
+    for (result, name) in results.iter().zip(names) {
+        assert_eq!(result["name"], name);
+        let started = result["started_ms"].as_u64().unwrap();
+        let time = result["time_ms"].as_u64().unwrap();
+        assert_eq!(result["status"], "success");
+        assert!(time >= result["response"]["time_ms"].as_u64().unwrap());
+        assert!(
+            started + time <= total,
+            "{name}: {started} + {time} > {total}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn batch_reports_a_failing_league_without_masking_the_rest() {
+    let mut too_many = minimal_valid_simulate_payload();
+    too_many["iterations"] = json!(1_000_000_000);
+    let batch = json!({"leagues": [
+        {"name": "Good", "request": minimal_valid_simulate_payload()},
+        {"name": "Broken", "request": {"schedule": [[1, 5, null, null]], "elo_values": [1500.0, 1500.0]}},
+        {"name": "Huge", "request": too_many},
+        {"name": "Also good", "request": minimal_valid_simulate_payload()}
+    ]});
+    let (status, body) = send(post_json("/simulate/batch", &batch)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let results = body["results"].as_array().unwrap();
+    let statuses: Vec<&str> = results
+        .iter()
+        .map(|r| r["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["success", "error", "error", "success"]);
+    assert!(results[0]["response"]["probability_matrix"].is_array());
+    assert!(results[3]["response"]["probability_matrix"].is_array());
+
+    let broken = &results[1];
+    assert!(broken.get("response").is_none());
+    assert_eq!(broken["error"]["code"], "invalid_field");
+    assert!(broken["error"]["field"]
+        .as_str()
+        .unwrap()
+        .starts_with("leagues[1].request."));
+    assert!(broken["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("league 'Broken'"));
+    assert_eq!(
+        results[2]["error"]["field"],
+        "leagues[2].request.iterations"
+    );
+}
+
+#[tokio::test]
+async fn batch_simulates_identical_leagues_once() {
+    // Unseeded, so only a shared simulation gives identical matrices.
+    let mut request = minimal_valid_simulate_payload();
+    request["iterations"] = json!(500);
+    // Spelling out a default does not make it a different request.
+    let mut explicit = request.clone();
+    explicit["mod_factor"] = json!(20.0);
+    let mut other = request.clone();
+    other["elo_values"] = json!([1400.0, 1600.0]);
+    let batch = json!({"leagues": [
+        {"name": "Liga", "request": request},
+        {"name": "Other", "request": other},
+        {"name": "Liga (retry)", "request": explicit}
+    ]});
+    let (status, body) = send(post_json("/simulate/batch", &batch)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let results = body["results"].as_array().unwrap();
+    assert!(results[0].get("duplicate_of").is_none());
+    assert!(results[1].get("duplicate_of").is_none());
+    assert_eq!(results[2]["duplicate_of"], 0);
+    assert_eq!(results[2]["name"], "Liga (retry)");
+    assert_eq!(results[2]["status"], "success");
+    assert_eq!(results[2]["response"], results[0]["response"]);
+    assert_ne!(
+        results[1]["response"]["probability_matrix"],
+        results[0]["response"]["probability_matrix"]
+    );
+}
+
+#[tokio::test]
+async fn elo_update_applies_results_in_order() {
+    let payload = json!({
+        "ratings": [
+            {"team": "FCB", "elo": 1900.0},
+            {"team": "BVB", "elo": 1750.0},
+            {"team": "S04", "elo": 1500.0}
+        ],
+        "results": [
+            {"home": "FCB", "away": "BVB", "goals_home": 2, "goals_away": 1},
+            {"home": "S04", "away": "FCB", "goals_home": 3, "goals_away": 0}
+        ]
+    });
+    let (status, body) = send(post_json("/elo/update", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let params = league_sim_core::SimulationParams::default();
+    let expected = league_sim_core::apply_results(
+        &[1900.0, 1750.0, 1500.0],
+        &[
+            league_sim_core::Match {
+                team_home: 0,
+                team_away: 1,
+                goals_home: Some(2),
+                goals_away: Some(1),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            league_sim_core::Match {
+                team_home: 2,
+                team_away: 0,
+                goals_home: Some(3),
+                goals_away: Some(0),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+        ],
+        params.mod_factor,
+        params.home_advantage,
+    );
+    let ratings = body["ratings"].as_array().unwrap();
+    assert_eq!(ratings[0]["team"], "FCB");
+    for (rating, elo) in ratings.iter().zip(expected) {
+        assert!((rating["elo"].as_f64().unwrap() - elo).abs() < 1e-9);
+    }
+    let changes = body["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert!(changes[0]["elo_change"].as_f64().unwrap() > 0.0);
+    assert!(changes[1]["win_probability_home"].as_f64().unwrap() < 0.5);
+
+    let (status, body) = send(post_json(
+        "/elo/update",
+        &json!({
+            "ratings": [{"team": "FCB", "elo": 1900.0}],
+            "results": [{"home": "FCB", "away": "HSV", "goals_home": 1, "goals_away": 0}]
+        }),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "results[0].away");
+}
+
+#[tokio::test]
+async fn elo_rollover_swaps_teams_and_regresses_to_the_mean() {
+    let payload = json!({
+        "ratings": [
+            {"team": "FCB", "elo": 1900.0},
+            {"team": "BVB", "elo": 1700.0},
+            {"team": "S04", "elo": 1400.0}
+        ],
+        "relegated": ["S04"],
+        "promoted": [{"team": "HSV"}, {"team": "FCK", "elo": 1500.0}],
+        "regression": 0.5
+    });
+    let (status, body) = send(post_json("/elo/rollover", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    // HSV inherits S04's 1400; the new league's mean is 1625.
+    let ratings = body["ratings"].as_array().unwrap();
+    let teams: Vec<&str> = ratings
+        .iter()
+        .map(|r| r["team"].as_str().unwrap())
+        .collect();
+    assert_eq!(teams, ["FCB", "BVB", "HSV", "FCK"]);
+    for (rating, expected) in ratings.iter().zip([1762.5, 1662.5, 1512.5, 1562.5]) {
+        assert!((rating["elo"].as_f64().unwrap() - expected).abs() < 1e-9);
+    }
+
+    let (status, body) = send(post_json(
+        "/elo/rollover",
+        &json!({
+            "ratings": [{"team": "FCB", "elo": 1900.0}],
+            "promoted": [{"team": "FCB"}],
+            "regression": 1.5
+        }),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("regression") && message.contains("already in the league"));
+}
+
+#[tokio::test]
+async fn elo_bootstrap_replays_seasons_with_decay() {
+    let result = |home: &str, away: &str, goals_home: i32, goals_away: i32| json!({"home": home, "away": away, "goals_home": goals_home, "goals_away": goals_away});
+    let seasons = json!([
+        [result("FCB", "BVB", 3, 0), result("BVB", "FCB", 0, 2)],
+        [result("BVB", "S04", 1, 1), result("FCB", "BVB", 0, 1)]
+    ]);
+    let (status, body) = send(post_json(
+        "/elo/bootstrap",
+        &json!({"seasons": seasons, "half_life": 1.0}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let ratings = body["ratings"].as_array().unwrap();
+    let teams: Vec<&str> = ratings
+        .iter()
+        .map(|r| r["team"].as_str().unwrap())
+        .collect();
+    assert_eq!(teams, ["FCB", "BVB", "S04"]);
+    let total: f64 = ratings.iter().map(|r| r["elo"].as_f64().unwrap()).sum();
+    assert!((total - 4500.0).abs() < 1e-9);
+    assert!(ratings[0]["elo"].as_f64().unwrap() > 1500.0);
+
+    let (status, body) = send(post_json(
+        "/elo/bootstrap",
+        &json!({"seasons": [[result("FCB", "FCB", 1, 0)]], "half_life": 0.0}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["seasons[0][0]", "half_life"]);
+}
+
+#[tokio::test]
+async fn goal_model_fit_returns_the_regression_line() {
+    // goals = 0.002 * delta + 1.3 exactly, plus one point off the line.
+    let mut observations: Vec<Value> = [-300.0, -100.0, 0.0, 100.0, 300.0]
+        .iter()
+        .map(|&d: &f64| json!({"elo_delta": d, "goals": 0.002 * d + 1.3}))
+        .collect();
+    observations.push(json!({"elo_delta": 0.0, "goals": 2.3}));
+    observations.push(json!({"elo_delta": 0.0, "goals": 0.3}));
+    let (status, body) = send(post_json(
+        "/fit/goal-model",
+        &json!({"observations": observations, "confidence": 0.9}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!((body["tore_slope"].as_f64().unwrap() - 0.002).abs() < 1e-12);
+    assert!((body["tore_intercept"].as_f64().unwrap() - 1.3).abs() < 1e-12);
+    assert_eq!(body["observations"], 7);
+    let interval = body["intercept_interval"].as_array().unwrap();
+    assert!(interval[0].as_f64().unwrap() < 1.3 && 1.3 < interval[1].as_f64().unwrap());
+
+    let (status, body) = send(post_json(
+        "/fit/goal-model",
+        &json!({"observations": vec![json!({"elo_delta": 0.0, "goals": 1.0}); 3]}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "observations");
+}
+
+#[tokio::test]
+async fn backtest_scores_forecasts_of_past_seasons() {
+    // The stronger side wins every match at home and draws away.
+    let teams = [("A", 1700.0), ("B", 1500.0), ("C", 1300.0)];
+    let mut results = Vec::new();
+    let mut matchday = 0;
+    for (h, (home, home_elo)) in teams.iter().enumerate() {
+        for (away, away_elo) in teams.iter().skip(h + 1) {
+            matchday += 1;
+            let stronger_home = home_elo > away_elo;
+            results.push(json!({
+                "home": home, "away": away, "matchday": matchday,
+                "goals_home": if stronger_home { 2 } else { 1 }, "goals_away": 1
+            }));
+            results.push(json!({
+                "home": away, "away": home, "matchday": matchday + 3,
+                "goals_home": 1, "goals_away": 1
+            }));
+        }
+    }
+    let season = json!({
+        "ratings": teams.map(|(team, elo)| json!({"team": team, "elo": elo})),
+        "results": results
+    });
+    let (status, body) = send(post_json(
+        "/backtest",
+        &json!({"seasons": [season.clone(), season.clone()]}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["matches"], 12);
+    assert_eq!(body["seasons"].as_array().unwrap().len(), 2);
+    assert_eq!(body["seasons"][0], body["seasons"][1]);
+    let informed = body["log_loss"].as_f64().unwrap();
+    let forecasts: u64 = body["reliability"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["forecasts"].as_u64().unwrap())
+        .sum();
+    assert_eq!(forecasts, 36);
+
+    // A goal model blind to the ratings forecasts worse.
+    let (status, body) = send(post_json(
+        "/backtest",
+        &json!({"seasons": [season], "tore_slope": 0.0}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!(body["log_loss"].as_f64().unwrap() > informed);
+
+    let (status, body) = send(post_json(
+        "/backtest",
+        &json!({"seasons": [{"ratings": [{"team": "A", "elo": 1500.0}],
+                             "results": [{"home": "A", "away": "Z", "goals_home": 1, "goals_away": 0}]}]}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "seasons[0].results[0].away");
+}
+
+#[tokio::test]
+async fn home_advantage_fit_separates_leagues() {
+    // Four equal teams, every pairing played home and away: the home side
+    // wins each match in "home" and loses each in "away".
+    let season = |league: &str, home_wins: bool| {
+        let teams = ["A", "B", "C", "D"];
+        let mut results = Vec::new();
+        for home in teams {
+            for away in teams.iter().filter(|&&t| t != home) {
+                let (goals_home, goals_away) = if home_wins { (2, 0) } else { (0, 1) };
+                results.push(json!({
+                    "home": home, "away": away,
+                    "goals_home": goals_home, "goals_away": goals_away
+                }));
+            }
+        }
+        json!({
+            "league": league,
+            "ratings": teams.map(|team| json!({"team": team, "elo": 1500.0})),
+            "results": results
+        })
+    };
+    let payload = json!({
+        "seasons": [season("home", true), season("home", true), season("away", false)],
+        "per_league": true,
+        "range": [-200.0, 400.0]
+    });
+    let (status, body) = send(post_json("/fit/home-advantage", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    assert_eq!(body["pooled"]["matches"], 36);
+    let pooled = body["pooled"]["home_advantage"].as_f64().unwrap();
+    let leagues = body["leagues"].as_array().unwrap();
+    assert_eq!(leagues[0]["league"], "home");
+    assert_eq!(leagues[1]["league"], "away");
+    let home = leagues[0]["home_advantage"].as_f64().unwrap();
+    let away = leagues[1]["home_advantage"].as_f64().unwrap();
+    assert!(
+        away < 0.0 && away < pooled && pooled < home,
+        "{away} {pooled} {home}"
+    );
+
+    let (status, body) = send(post_json(
+        "/fit/home-advantage",
+        &json!({
+            "seasons": [{
+                "ratings": [{"team": "A", "elo": 1500.0}],
+                "results": [{"home": "A", "away": "B", "goals_home": 1, "goals_away": 0}]
+            }],
+            "per_league": true
+        }),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["seasons[0].league", "seasons[0].results[0].away"]);
+
+    let mut wide = payload;
+    wide["range"] = json!([-1e300, 1e300]);
+    let (status, body) = send(post_json("/fit/home-advantage", &wide)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "range");
+}
+
+fn batch_payload() -> Value {
+    json!({
+        "leagues": [
+            {"name": "Liga A", "request": minimal_valid_simulate_payload()},
+            {"name": "Liga B", "request": minimal_valid_simulate_payload()}
+        ]
+    })
+}
+
+async fn send_to(app: &axum::Router, req: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(req).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+    (status, body)
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+fn post_json(uri: &str, payload: &Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(payload).unwrap()))
+        .unwrap()
+}
+
+/// Poll GET /jobs/{id} until the job leaves the running state.
+async fn wait_for_job(app: &axum::Router, id: &str) -> (StatusCode, Value) {
+    for _ in 0..200 {
+        let (status, body) = send_to(app, get(&format!("/jobs/{}", id))).await;
+        if status != StatusCode::OK || body["status"] != "running" {
+            return (status, body);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("job {} did not finish", id);
+}
+
+#[tokio::test]
+async fn job_api_runs_batch_in_background() {
+    let app = create_router();
+
+    let (status, created) = send_to(&app, post_json("/jobs", &batch_payload())).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let id = created["job_id"].as_str().unwrap().to_string();
+
+    let (status, job) = wait_for_job(&app, &id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["job_id"], id.as_str());
+    assert_eq!(job["result"]["results"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn job_api_reports_failed_batches() {
+    let app = create_router();
+    let payload = json!({
+        "leagues": [{"name": "Broken", "request": {"schedule": [], "elo_values": [1500.0]}}]
+    });
+
+    let (_, created) = send_to(&app, post_json("/jobs", &payload)).await;
+    let (_, job) = wait_for_job(&app, created["job_id"].as_str().unwrap()).await;
+
+    // The batch itself completes; the league carries the error.
+    assert_eq!(job["status"], "succeeded");
+    let league = &job["result"]["results"][0];
+    assert_eq!(league["status"], "error");
+    assert!(league["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Broken"));
+}
+
+#[tokio::test]
+async fn job_api_returns_404_for_unknown_and_expired_jobs() {
+    let app = crate::create_router_with_state(crate::AppState {
+        jobs: crate::jobs::JobRegistry::new(std::time::Duration::ZERO, 1000),
+        ..crate::AppState::from_env()
+    });
+
+    let (status, _) = send_to(&app, get("/jobs/does-not-exist")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // With a zero TTL the job disappears as soon as it finishes.
+    let (_, created) = send_to(&app, post_json("/jobs", &batch_payload())).await;
+    let (status, _) = wait_for_job(&app, created["job_id"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+fn delete(uri: &str) -> Request<Body> {
+    Request::builder()
+        .method("DELETE")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn job_api_cancels_running_and_removes_finished_jobs() {
+    let app = create_router();
+    let mut league = minimal_valid_simulate_payload();
+    league["iterations"] = json!(100_000);
+    let payload = json!({"leagues": [{"name": "Long", "request": league}]});
+
+    let (_, created) = send_to(&app, post_json("/jobs", &payload)).await;
+    let id = created["job_id"].as_str().unwrap().to_string();
+    let uri = format!("/jobs/{}", id);
+
+    let (status, job) = send_to(&app, delete(&uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(job["status"], "cancelled");
+
+    // The background task finishing later must not overwrite the state.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let (_, job) = send_to(&app, get(&uri)).await;
+    assert_eq!(job["status"], "cancelled");
+
+    let (status, _) = send_to(&app, delete(&uri)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_to(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_to(&app, delete(&uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn simulate_auto_structure_returns_zone_probabilities_and_warnings() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["structure"] = json!("auto");
+
+    let (status, body) = send(post_simulate_json(payload)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["structure"]["detected"], true);
+    assert!(
+        !body["structure"]["warnings"].as_array().unwrap().is_empty(),
+        "a 2-team schedule matches no template exactly"
+    );
+    let zones = body["zone_probabilities"].as_array().unwrap();
+    assert_eq!(zones.len(), 2);
+}
+
+#[tokio::test]
+async fn simulate_reports_clinched_and_eliminated_zones() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["structure"] = json!("auto");
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // Team 1 leads by three points, but a 1-0 win for team 2 would level
+    // the table: either team can still finish first or second.
+    for row in body["position_bounds"].as_array().unwrap() {
+        assert_eq!(row["best_position"], 1, "{row}");
+        assert_eq!(row["worst_position"], 2, "{row}");
+    }
+
+    payload["schedule"] = json!([[1, 2, 3, 0], [2, 1, 0, 0]]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let bounds = &body["position_bounds"];
+    assert_eq!(bounds[0]["team_name"], "Team_1");
+    assert_eq!(bounds[0]["worst_position"], 1);
+    assert_eq!(bounds[1]["best_position"], 2);
+    // Once the season is over every zone is either clinched or out of
+    // reach.
+    for team in body["zone_probabilities"].as_array().unwrap() {
+        let count = |key: &str| team.get(key).map_or(0, |z| z.as_array().unwrap().len());
+        assert_eq!(
+            count("clinched") + count("eliminated"),
+            team["zones"].as_object().unwrap().len(),
+            "{team}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn simulate_returns_expected_table_on_request() {
+    let (status, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("expected_table").is_none());
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["expected_table"] = json!(true);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let table = body["expected_table"].as_array().unwrap();
+    assert_eq!(table.len(), 2);
+    // Team 1 won the first leg 1-0 and cannot finish behind on points.
+    assert_eq!(table[0]["team_name"], "Team_1");
+    let points = |row: usize| table[row]["expected_points"].as_f64().unwrap();
+    assert!(points(0) >= points(1));
+    for row in table {
+        let position = row["expected_position"].as_f64().unwrap();
+        assert!((1.0..=2.0).contains(&position), "{row}");
+        assert_eq!(row["position_interval"].as_array().unwrap().len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn simulate_orders_the_table_by_the_requested_tiebreakers() {
+    // Teams 1 and 2 finish level on points; team 1 has the better goal
+    // difference, team 2 won their meeting.
+    let mut payload = json!({
+        "schedule": [[1, 2, 0, 1], [1, 3, 5, 0], [2, 3, 0, 0], [3, 1, 0, 0]],
+        "elo_values": [1500.0, 1500.0, 1500.0],
+        "iterations": 10
+    });
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_names"][0], "Team_1");
+
+    payload["tiebreakers"] = json!(["points", "head_to_head_points", "goal_difference"]);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_names"][0], "Team_2");
+    assert_eq!(body["probability_matrix"][0][0], 1.0);
+
+    payload["tiebreakers"] = json!(["goal_difference", "points", "goal_difference"]);
+    payload["fair_play"] = json!([1, 2]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["fair_play", "tiebreakers", "tiebreakers[2]"]);
+}
+
+#[tokio::test]
+async fn simulate_warns_about_malformed_schedules() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["matchdays"] = json!([1, 2]);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("schedule_warnings").is_none(), "body: {body}");
+
+    // Both matches on one matchday, and a third team that meets nobody.
+    payload["schedule"] = json!([[1, 2, 1, 0], [2, 1, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1500.0, 1500.0, 1500.0]);
+    payload["matchdays"] = json!([1, 1, 2]);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let warnings = body["schedule_warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings[0].as_str().unwrap().contains("on matchday 1"));
+    assert!(warnings[1]
+        .as_str()
+        .unwrap()
+        .contains("not a complete double round robin"));
+
+    payload["matchdays"] = json!([1, 0]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["matchdays", "matchdays[1]"]);
+}
+
+#[tokio::test]
+async fn simulate_until_matchday_ignores_later_fixtures() {
+    // Team 1 has won matchday 1; matchday 2 is still to come.
+    let payload = json!({
+        "schedule": [[1, 2, 3, 0], [2, 1, null, null]],
+        "elo_values": [1500.0, 1500.0],
+        "matchdays": [1, 2],
+        "simulate_until_matchday": 1,
+        "iterations": 50
+    });
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_names"][0], "Team_1");
+    assert_eq!(body["probability_matrix"][0][0], 1.0);
+
+    let mut without_matchdays = payload;
+    without_matchdays
+        .as_object_mut()
+        .unwrap()
+        .remove("matchdays");
+    let (status, body) = send(post_simulate_json(without_matchdays)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "simulate_until_matchday");
+}
+
+#[tokio::test]
+async fn replay_reports_each_matchday_of_a_completed_season() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([
+        [1, 2, 2, 0],
+        [3, 4, 1, 1],
+        [1, 3, 1, 0],
+        [2, 4, 0, 2],
+        [4, 1, 0, 3],
+        [2, 3, 1, 1]
+    ]);
+    payload["elo_values"] = json!([1600, 1500, 1500, 1400]);
+    payload["team_names"] = json!(["A", "B", "C", "D"]);
+    payload["matchdays"] = json!([1, 1, 2, 2, 3, 3]);
+    let (status, body) = send(post_json("/simulate/replay", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_names"], json!(["A", "B", "C", "D"]));
+    // A wins all three; C and D finish level on points, D ahead on goals.
+    assert_eq!(body["final_positions"], json!([1, 4, 3, 2]));
+    let snapshots = body["snapshots"].as_array().unwrap();
+    assert_eq!(snapshots.len(), 3);
+    assert_eq!(snapshots[2]["matches_played"], 4);
+    // Two matchdays in, only D can still overtake A.
+    let top = |day: usize| {
+        snapshots[day]["actual_position_probability"][0]
+            .as_f64()
+            .unwrap()
+    };
+    assert!(top(2) > top(0));
+    assert_eq!(snapshots[2]["probability_matrix"][1][0], 0.0);
+
+    payload["schedule"][5] = json!([2, 3, null, null]);
+    let (status, body) = send(post_json("/simulate/replay", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "schedule");
+}
+
+#[tokio::test]
+async fn mod_factor_weights_scale_the_elo_updates_of_their_rows() {
+    // A weight of 0 keeps the simulated second match from moving anyone.
+    let mut payload = minimal_valid_simulate_payload();
+    payload["mod_factor_weights"] = json!([2.0, 0.0]);
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    for team in body["teams"].as_array().unwrap() {
+        assert_eq!(team["points"][0]["std_dev"], 0.0);
+    }
+
+    payload["mod_factor_weights"] = json!([1.0, -1.0, 1.0]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["mod_factor_weights", "mod_factor_weights[1]"]);
+}
+
+#[tokio::test]
+async fn competition_weights_scale_the_elo_updates_of_tagged_rows() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["competitions"] = json!(["league", "friendly"]);
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!(body["teams"][0]["points"][0]["std_dev"].as_f64().unwrap() > 0.0);
+
+    // A friendly weighing 0 moves no one.
+    payload["competition_weights"] = json!({"friendly": 0.0, "cup": 1.5});
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    for team in body["teams"].as_array().unwrap() {
+        assert_eq!(team["points"][0]["std_dev"], 0.0);
+    }
+
+    payload["competitions"] = json!(["league", null, "cup"]);
+    payload["competition_weights"] = json!({"friendly": -1.0});
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["competitions", "competition_weights.friendly"]);
+}
+
+#[tokio::test]
+async fn match_weights_override_the_competition_weight_of_their_row() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["competitions"] = json!(["league", "friendly"]);
+    payload["competition_weights"] = json!({"friendly": 0.0});
+    payload["match_weights"] = json!([null, 1.0]);
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!(body["teams"][0]["points"][0]["std_dev"].as_f64().unwrap() > 0.0);
+
+    // A row weighing 0 moves no one, whatever its competition.
+    payload["match_weights"] = json!([null, 0.0]);
+    payload["competition_weights"] = json!({"friendly": 2.0});
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    for team in body["teams"].as_array().unwrap() {
+        assert_eq!(team["points"][0]["std_dev"], 0.0);
+    }
+
+    payload["match_weights"] = json!([null, -1.0, 1.0]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["match_weights", "match_weights[1]"]);
+}
+
+#[tokio::test]
+async fn simulate_runs_under_glicko2_ratings() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["rating_system"] = json!("glicko2");
+    payload["glicko"] = json!({"deviations": [40.0, 250.0]});
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // The uncertain Team_2 loses more on the played 1-0 than Team_1 gains.
+    let change = |team: usize| body["teams"][team]["current_elo"].as_f64().unwrap() - 1500.0;
+    assert!(change(0) > 0.0 && -change(1) > 2.0 * change(0));
+
+    payload["glicko"] = json!({"deviations": [40.0], "tau": 0.0});
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["glicko.deviations", "glicko.tau"]);
+}
+
+#[tokio::test]
+async fn team_strengths_drive_the_goals_of_simulated_matches() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 1, null, null]]);
+    payload["iterations"] = json!(400);
+    payload["seed"] = json!(7);
+    payload["team_strengths"] = json!([
+        {"attack": 0.6, "defense": 2.4},
+        {"attack": 2.4, "defense": 0.6}
+    ]);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // Level Elo, but Team_2 outscores Team_1 by far.
+    assert_eq!(body["team_names"][0], "Team_2");
+    assert!(body["probability_matrix"][0][0].as_f64().unwrap() > 0.8);
+
+    payload["team_strengths"] = json!([{"attack": 1.0, "defense": 0.0}]);
+    payload["strength_weight"] = json!(1.5);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        fields,
+        ["team_strengths", "team_strengths[0]", "strength_weight"]
+    );
+
+    // Strengths that overflow the goal means are refused even when their
+    // weight would leave them out.
+    payload["team_strengths"] = json!([
+        {"attack": 1e200, "defense": 1.0},
+        {"attack": 1.0, "defense": 1e200}
+    ]);
+    payload["strength_weight"] = json!(0.0);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "body: {body}");
+    assert_eq!(body["field"], "team_strengths[0]");
+}
+
+#[tokio::test]
+async fn market_values_shift_early_season_forecasts() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 1, null, null]]);
+    payload["iterations"] = json!(400);
+    payload["seed"] = json!(7);
+    payload["market_values"] = json!([50.0, 800.0]);
+    payload["prior_weight"] = json!(1.0);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // Level Elo, but Team_2 is worth sixteen times as much: it tops the
+    // table in about 83% of the seasons.
+    assert_eq!(body["team_names"][0], "Team_2");
+    assert!(body["probability_matrix"][0][0].as_f64().unwrap() > 0.7);
+
+    payload["market_values"] = json!([50.0, 0.0]);
+    payload["strength_priors"] = json!([0.0]);
+    payload["prior_weight"] = json!(2.0);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "market_values[1]",
+            "strength_priors",
+            "strength_priors",
+            "prior_weight"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn fitted_poisson_strengths_feed_the_simulation() {
+    // A scores three at home and two away against everyone; B and C only
+    // ever score once.
+    let mut results = Vec::new();
+    for (home, away) in [
+        ("A", "B"),
+        ("B", "A"),
+        ("A", "C"),
+        ("C", "A"),
+        ("B", "C"),
+        ("C", "B"),
+    ] {
+        let goals = |team: &str, at_home: bool| match (team, at_home) {
+            ("A", true) => 3,
+            ("A", false) => 2,
+            _ => 1,
+        };
+        results.push(json!({
+            "home": home, "away": away,
+            "goals_home": goals(home, true), "goals_away": goals(away, false)
+        }));
+    }
+    let (status, fit) = send(post_json(
+        "/fit/team-strengths",
+        &json!({"results": results}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "body: {fit}");
+    assert_eq!(fit["team_names"], json!(["A", "B", "C"]));
+    assert_eq!(fit["matches"], 6);
+    assert_eq!(fit["converged"], true);
+    let attack = fit["attack"].as_array().unwrap();
+    assert!(attack[0].as_f64().unwrap() > attack[1].as_f64().unwrap());
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["schedule"] = json!([[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]]);
+    payload["elo_values"] = json!([1500, 1500, 1500]);
+    payload["team_names"] = fit["team_names"].clone();
+    payload["poisson_strengths"] = json!({
+        "intercept": fit["intercept"],
+        "home_advantage": fit["home_advantage"],
+        "attack": fit["attack"],
+        "defense": fit["defense"]
+    });
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_names"][0], "A");
+
+    payload["poisson_strengths"]["attack"] = json!([0.0]);
+    payload["team_strengths"] = json!(vec![json!({"attack": 1.0, "defense": 1.0}); 3]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["poisson_strengths", "poisson_strengths.attack"]);
+
+    let (status, body) = send(post_json("/fit/team-strengths", &json!({"results": []}))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "results");
+}
+
+#[tokio::test]
+async fn simulate_overrides_pin_unplayed_results() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["overrides"] = json!([{"row": 1, "goals_home": 2, "goals_away": 0}]);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // 1-0 and 0-2: team 2 tops the table in every run.
+    assert_eq!(body["team_names"][0], "Team_2");
+    assert_eq!(body["probability_matrix"][0][0], 1.0);
+
+    payload["overrides"] = json!([
+        {"row": 0, "goals_home": 0, "goals_away": 0},
+        {"row": 5, "goals_home": 1, "goals_away": 0}
+    ]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let messages: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "override 0: row 0 is already played",
+            "override 1: row 5 out of range (schedule has 2 rows)"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn swiss_league_phase_reports_qualification_probabilities() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+    let schedule: Vec<_> = league_sim_core::swiss_schedule(36, 8, &mut rng)
+        .iter()
+        .map(|m| json!([m.team_home + 1, m.team_away + 1, null, null]))
+        .collect();
+    let elos: Vec<f64> = (0..36).map(|i| 2000.0 - 10.0 * i as f64).collect();
+    let payload = json!({
+        "schedule": schedule,
+        "elo_values": elos,
+        "structure": "auto",
+        "iterations": 200
+    });
+
+    let (status, body) = send(post_simulate_json(payload)).await;
+
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["structure"]["id"], "champions_league_phase");
+    let zones = body["zone_probabilities"].as_array().unwrap();
+    let total = |zone: &str| -> f64 {
+        zones
+            .iter()
+            .map(|z| z["zones"][zone].as_f64().unwrap())
+            .sum()
+    };
+    assert!((total("round_of_16") - 8.0).abs() < 1e-9);
+    assert!((total("knockout_playoff") - 16.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn simulate_rejects_template_for_wrong_team_count() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["structure"] = json!("bundesliga");
+
+    let (status, _body) = send(post_simulate_json(payload)).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn league_preset_supplies_tiebreakers_and_structure() {
+    let schedule: Vec<_> = (1..=18)
+        .flat_map(|home| (1..=18).map(move |away| (home, away)))
+        .filter(|(home, away)| home != away)
+        .map(|(home, away)| json!([home, away, null, null]))
+        .collect();
+    let elos: Vec<f64> = (0..18).map(|i| 1800.0 - 20.0 * i as f64).collect();
+    let payload = json!({
+        "schedule": schedule,
+        "elo_values": elos,
+        "league": "bundesliga",
+        "iterations": 100
+    });
+
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["structure"]["id"], "bundesliga");
+    assert_eq!(
+        body["metadata"]["params"]["tiebreakers"],
+        json!([
+            "points",
+            "goal_difference",
+            "goals_for",
+            "head_to_head_points",
+            "head_to_head_away_goals"
+        ])
+    );
+
+    // The request's own choices win.
+    let mut own = payload.clone();
+    own["tiebreakers"] = json!(["points", "goals_for"]);
+    let (status, body) = send(post_simulate_json(own)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(
+        body["metadata"]["params"]["tiebreakers"],
+        json!(["points", "goals_for"])
+    );
+
+    let mut unknown = payload;
+    unknown["league"] = json!("serie_a");
+    let (status, body) = send(post_simulate_json(unknown)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "league");
+
+    let mut small = minimal_valid_simulate_payload();
+    small["league"] = json!("premier_league");
+    let (status, body) = send(post_simulate_json(small)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("has 20 teams, schedule has 2"));
+}
+
+#[tokio::test]
+async fn simulate_omits_structure_fields_by_default() {
+    let (status, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("structure").is_none());
+    assert!(body.get("zone_probabilities").is_none());
+}
+
+/// Start a local callback receiver that answers 500 to the first
+/// `failures` requests and records every body it accepts.
+async fn spawn_callback_receiver(
+    failures: usize,
+) -> (String, std::sync::Arc<std::sync::Mutex<Vec<Value>>>) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let store = received.clone();
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::post(move |axum::Json(body): axum::Json<Value>| {
+            let store = store.clone();
+            let calls = calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < failures {
+                    return StatusCode::INTERNAL_SERVER_ERROR;
+                }
+                store.lock().unwrap().push(body);
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+    (format!("http://{}/hook", addr), received)
+}
+
+#[tokio::test]
+async fn webhook_sender_retries_until_accepted() {
+    let (url, received) = spawn_callback_receiver(2).await;
+    let sender = crate::webhook::WebhookSender::new(3, std::time::Duration::from_millis(5))
+        .with_allowed_hosts(["127.0.0.1"]);
+
+    let attempts = sender.deliver(&url, &json!({"ok": true})).await.unwrap();
+    assert_eq!(attempts, 3);
+    assert_eq!(received.lock().unwrap().as_slice(), &[json!({"ok": true})]);
+
+    let (url, _) = spawn_callback_receiver(usize::MAX).await;
+    let err = sender.deliver(&url, &json!({})).await.unwrap_err();
+    assert!(err.contains("500"), "got {}", err);
+}
+
+#[tokio::test]
+async fn simulate_with_callback_url_answers_202_and_posts_result() {
+    let (url, received) = spawn_callback_receiver(0).await;
+    let mut payload = minimal_valid_simulate_payload();
+    payload["callback_url"] = json!(url);
+    let app = crate::create_router_with_state(crate::AppState {
+        webhooks: crate::webhook::WebhookSender::new(3, std::time::Duration::from_millis(5))
+            .with_allowed_hosts(["127.0.0.1"]),
+        ..crate::AppState::from_env()
+    });
+
+    let (status, body) = send_to(&app, post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["status"], "accepted");
+    assert_eq!(body["callback_url"], url.as_str());
+
+    for _ in 0..200 {
+        if let Some(result) = received.lock().unwrap().first() {
+            assert!(result["probability_matrix"].is_array());
+            assert!(result["simulations_performed"].as_u64().unwrap() > 0);
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("callback was never delivered");
+}
+
+#[tokio::test]
+async fn simulate_rejects_non_http_callback_url() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["callback_url"] = json!("ftp://example.org/hook");
+
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "callback_url");
+}
+
+#[tokio::test]
+async fn simulate_rejects_callbacks_into_internal_networks() {
+    for url in [
+        "http://127.0.0.1:8080/hook",
+        "http://169.254.169.254/latest/meta-data/",
+        "http://10.0.0.7/hook",
+        "http://[::1]/hook",
+        "http://[::ffff:192.168.1.1]/hook",
+        "http://localhost/hook",
+    ] {
+        let mut payload = minimal_valid_simulate_payload();
+        payload["callback_url"] = json!(url);
+        let (status, body) = send(post_simulate_json(payload)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", url);
+        assert_eq!(body["field"], "callback_url");
+    }
+
+    // An allowlist admits its own hosts and nothing else.
+    let sender = crate::webhook::WebhookSender::new(1, std::time::Duration::ZERO)
+        .with_allowed_hosts(["127.0.0.1"]);
+    assert!(sender.check_url("http://127.0.0.1:9/hook").await.is_ok());
+    let err = sender
+        .check_url("http://localhost:9/hook")
+        .await
+        .unwrap_err();
+    assert!(err.contains("not allowed"), "got {}", err);
+}
+
+fn app_with_cache(capacity: usize, ttl: std::time::Duration) -> axum::Router {
+    crate::create_router_with_state(crate::AppState {
+        cache: crate::cache::ResultCache::new(capacity, ttl),
+        ..crate::AppState::from_env()
+    })
+}
+
+#[tokio::test]
+async fn identical_requests_are_served_from_cache() {
+    let app = app_with_cache(1, std::time::Duration::from_secs(60));
+    let payload = minimal_valid_simulate_payload();
+
+    let (_, first) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(first["cache_hit"], false);
+
+    // Same inputs with defaults spelled out hash to the same key.
+    let mut explicit = payload.clone();
+    explicit["mod_factor"] = json!(20.0);
+    explicit["home_advantage"] = json!(65.0);
+    let (status, second) = send_to(&app, post_json("/simulate", &explicit)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(second["cache_hit"], true);
+    assert_eq!(second["probability_matrix"], first["probability_matrix"]);
+
+    // A different request evicts the only entry (capacity 1).
+    let mut other = payload.clone();
+    other["elo_values"] = json!([1600.0, 1400.0]);
+    let (_, third) = send_to(&app, post_json("/simulate", &other)).await;
+    assert_eq!(third["cache_hit"], false);
+    let (_, fourth) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(fourth["cache_hit"], false);
+}
+
+#[tokio::test]
+async fn cache_respects_zero_capacity_and_ttl() {
+    let payload = minimal_valid_simulate_payload();
+    for app in [
+        app_with_cache(0, std::time::Duration::from_secs(60)),
+        app_with_cache(8, std::time::Duration::ZERO),
+    ] {
+        send_to(&app, post_json("/simulate", &payload)).await;
+        let (_, again) = send_to(&app, post_json("/simulate", &payload)).await;
+        assert_eq!(again["cache_hit"], false);
+    }
+}
+
+#[tokio::test]
+async fn simulate_sets_etag_and_honours_if_none_match() {
+    let app = create_router();
+    let payload = minimal_valid_simulate_payload();
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/simulate", &payload))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    assert!(etag.starts_with("W/\""));
+    // The hash is fixed (XXH3-128), not seeded per process.
+    assert_eq!(etag.len(), "W/\"\"".len() + 32, "{}", etag);
+    assert_eq!(
+        crate::cache::CacheKey::new(String::new()).digest(),
+        0x99aa06d3014798d86001c324468d497f
+    );
+    assert!(response.headers()["cache-control"]
+        .to_str()
+        .unwrap()
+        .contains("max-age="));
+
+    let conditional = |tag: &str| {
+        let mut req = post_json("/simulate", &payload);
+        req.headers_mut()
+            .insert("if-none-match", tag.parse().unwrap());
+        req
+    };
+
+    let response = app.clone().oneshot(conditional(&etag)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty());
+
+    let response = app.clone().oneshot(conditional("*")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Different inputs get a different tag.
+    let (status, _) = send_to(&app, conditional("\"0000000000000000\"")).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut other = payload.clone();
+    other["iterations"] = json!(60);
+    let response = app
+        .clone()
+        .oneshot(post_json("/simulate", &other))
+        .await
+        .unwrap();
+    assert_ne!(response.headers()["etag"], etag.as_str());
+
+    // A time budget's iterations depend on the clock: no validators, no
+    // cached copy.
+    let mut timed = payload.clone();
+    timed["time_budget_ms"] = json!(5);
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(post_json("/simulate", &timed))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("etag"));
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["cache_hit"], false);
+    }
+}
+
+#[tokio::test]
+async fn simulate_renders_csv_and_markdown() {
+    let app = create_router();
+    let mut payload = minimal_valid_simulate_payload();
+    payload["team_names"] = json!(["Home, FC", "Away"]);
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/simulate?format=csv", &payload))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    assert_eq!(response.headers()["vary"], "accept");
+    let csv_etag = response.headers()["etag"].clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "team,1,2");
+    assert_eq!(lines.len(), 3);
+    assert!(csv.contains("\"Home, FC\","), "{}", csv);
+
+    let mut req = post_json("/simulate?table=projected", &payload);
+    req.headers_mut().insert(
+        "accept",
+        "text/markdown, application/json;q=0.5".parse().unwrap(),
+    );
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/markdown; charset=utf-8"
+    );
+    assert_ne!(response.headers()["etag"], csv_etag);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let markdown = String::from_utf8(body.to_vec()).unwrap();
+    assert!(
+        markdown.starts_with("| position | team | expected_points |"),
+        "{}",
+        markdown
+    );
+    assert_eq!(markdown.lines().count(), 4);
+
+    // German spreadsheets: decimal commas, semicolons between fields.
+    let response = app
+        .clone()
+        .oneshot(post_json("/simulate?format=csv&locale=de", &payload))
+        .await
+        .unwrap();
+    assert_ne!(response.headers()["etag"], csv_etag);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "team;1;2");
+    assert!(lines[1].starts_with("Home, FC;0,"), "{}", csv);
+
+    // JSON stays the default.
+    let (status, body) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["probability_matrix"].is_array());
+}
+
+#[tokio::test]
+async fn results_endpoints_negotiate_msgpack_and_csv() {
+    let app = create_router();
+    let mut payload = minimal_valid_simulate_payload();
+    payload["seed"] = json!(7);
+
+    for path in ["/simulate", "/simulate/elo-trajectory"] {
+        let (_, json_body) = send_to(&app, post_json(path, &payload)).await;
+        let by_query = post_json(&format!("{}?format=msgpack", path), &payload);
+        let mut by_accept = post_json(path, &payload);
+        by_accept
+            .headers_mut()
+            .insert("accept", "application/msgpack".parse().unwrap());
+        for req in [by_query, by_accept] {
+            let response = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["content-type"], "application/msgpack");
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = rmp_serde::from_slice(&bytes).unwrap();
+            let keys = |v: &Value| v.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+            assert_eq!(keys(&body), keys(&json_body), "{}", path);
+            assert_eq!(body["team_names"], json_body["team_names"]);
+            assert_eq!(body["teams"], json_body["teams"]);
+        }
+    }
+
+    let tournament = json!({
+        "teams": [
+            {"team": "FCB", "elo": 1950.0},
+            {"team": "SVE", "elo": 1350.0}
+        ],
+        "iterations": 100
+    });
+    let response = app
+        .clone()
+        .oneshot(post_json("/tournament?format=csv", &tournament))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(csv.lines().next(), Some("team,Final,Winner"));
+    assert_eq!(csv.lines().count(), 3);
+    let response = app
+        .clone()
+        .oneshot(post_json("/tournament?format=csv&locale=de", &tournament))
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    assert_eq!(csv.lines().next(), Some("team;Final;Winner"));
+
+    let (status, body) = send_to(&app, post_json("/simulate/replay?format=bogus", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "format", "{body}");
+    let (status, body) = send_to(&app, post_json("/simulate?format=arrow", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "format", "{body}");
+}
+
+/// POST a 20-team request (large enough to cross the compression
+/// threshold) with the given Accept-Encoding.
+fn large_simulate_request(accept_encoding: &str) -> Request<Body> {
+    let n = 20;
+    let schedule: Vec<Value> = (1..=n)
+        .flat_map(|h| {
+            (1..=n)
+                .filter(move |&a| a != h)
+                .map(move |a| json!([h, a, null, null]))
+        })
+        .collect();
+    let payload = json!({
+        "schedule": schedule,
+        "elo_values": vec![1500.0; n],
+        "iterations": 20
+    });
+    let mut req = post_json("/simulate", &payload);
+    req.headers_mut()
+        .insert("accept-encoding", accept_encoding.parse().unwrap());
+    req
+}
+
+#[tokio::test]
+async fn responses_are_compressed_as_negotiated() {
+    use tower_http::decompression::Decompression;
+
+    for encoding in ["gzip", "br"] {
+        let response = create_router()
+            .oneshot(large_simulate_request(encoding))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-encoding"], encoding);
+
+        // Round trip: the decoded body is the normal JSON response.
+        let decoded = Decompression::new(create_router())
+            .oneshot(large_simulate_request(encoding))
+            .await
+            .unwrap();
+        let bytes = decoded.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["probability_matrix"].as_array().unwrap().len(), 20);
+    }
+
+    let response = create_router()
+        .oneshot(large_simulate_request("identity"))
+        .await
+        .unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn small_responses_stay_uncompressed() {
+    let app = crate::create_router_with_state(crate::AppState {
+        compression_min_bytes: u16::MAX,
+        ..crate::AppState::from_env()
+    });
+    let response = app.oneshot(large_simulate_request("gzip")).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+
+    let req = Request::builder()
+        .uri("/health")
+        .header("accept-encoding", "gzip")
+        .body(Body::empty())
+        .unwrap();
+    let response = create_router().oneshot(req).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn outcomes_enumerate_results_and_tables() {
+    let payload = json!({
+        "schedule": [[1, 2, 2, 0], [1, 3, null, null], [2, 3, null, null]],
+        "elo_values": [1600.0, 1500.0, 1400.0],
+        "team_names": ["A", "B", "C"]
+    });
+
+    let (status, body) = send(post_json("/simulate/outcomes", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["outcome_count"], 9);
+    assert_eq!(body["fixtures"][0]["team_home"], "A");
+    assert_eq!(body["fixtures"][1]["team_away"], "C");
+
+    let outcomes = body["outcomes"].as_array().unwrap();
+    let total: f64 = outcomes
+        .iter()
+        .map(|o| o["probability"].as_f64().unwrap())
+        .sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    // A wins 1:0, B wins 1:0: A tops the table with 6 points.
+    assert_eq!(outcomes[0]["results"], json!([[1, 0], [1, 0]]));
+    assert_eq!(outcomes[0]["table"][0]["team_name"], "A");
+    assert_eq!(outcomes[0]["table"][0]["points"], 6);
+
+    let (status, body) = send(post_json("/simulate/outcomes?scoreline_cap=2", &payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["outcome_count"], 81);
+    assert!(body["coverage"].as_f64().unwrap() < 1.0);
+}
+
+#[tokio::test]
+async fn outcomes_reject_oversized_trees() {
+    let schedule: Vec<Value> = (0..11).map(|_| json!([1, 2, null, null])).collect();
+    let payload = json!({ "schedule": schedule, "elo_values": [1500.0, 1500.0] });
+
+    let (status, _) = send(post_json("/simulate/outcomes", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn time_budget_reports_iterations_run_and_precision() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(5000);
+    payload["time_budget_ms"] = json!(1);
+
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    // At least one batch runs, however short the budget.
+    let performed = body["simulations_performed"].as_u64().unwrap();
+    assert!((1000..=5000).contains(&performed), "{performed}");
+    assert_eq!(body["metadata"]["iterations"], performed);
+    let se = body["max_standard_error"].as_f64().unwrap();
+    assert!(se > 0.0 && se <= 0.5 / (performed as f64).sqrt() + 1e-12);
+
+    payload["time_budget_ms"] = json!(0);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "time_budget_ms");
+
+    payload["time_budget_ms"] = json!(100);
+    payload["scenarios"] = json!([{ "name": "baseline", "weight": 1 }]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "time_budget_ms");
+}
+
+#[tokio::test]
+async fn target_se_stops_once_reached_or_at_the_cap() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(20000);
+    payload["target_se"] = json!(0.02);
+
+    // sqrt(0.25 / 1000) is below 0.02, so the first batch suffices.
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["simulations_performed"], 1000);
+    assert!(body["max_standard_error"].as_f64().unwrap() <= 0.02);
+
+    // Unreachable within the cap: all iterations run, the SE stays above.
+    payload["iterations"] = json!(3000);
+    payload["target_se"] = json!(0.001);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["simulations_performed"], 3000);
+    assert!(body["max_standard_error"].as_f64().unwrap() > 0.001);
+
+    payload["target_se"] = json!(0.5);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "target_se");
+}
+
+#[tokio::test]
+async fn solver_is_hybrid_on_the_last_matchday_unless_chosen() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["seed"] = json!(5);
+    let (status, hybrid) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {hybrid}");
+    assert_eq!(hybrid["metadata"]["solver"], "hybrid");
+    for row in hybrid["probability_matrix"].as_array().unwrap() {
+        let sum: f64 = row
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p.as_f64().unwrap())
+            .sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    payload["solver"] = json!("monte_carlo");
+    let (_, sampled) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(sampled["metadata"]["solver"], "monte_carlo");
+
+    // Three rounds left: the earlier ones dominate, so Monte Carlo.
+    let mut early = minimal_valid_simulate_payload();
+    early["schedule"] = json!([[1, 2, null, null], [2, 1, null, null], [1, 2, null, null]]);
+    let (_, body) = send(post_simulate_json(early)).await;
+    assert_eq!(body["metadata"]["solver"], "monte_carlo");
+
+    payload["solver"] = json!("hybrid");
+    payload["target_se"] = json!(0.01);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "solver");
+
+    payload["target_se"] = Value::Null;
+    let (status, body) = send(post_json("/simulate/stream", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "solver");
+}
+
+#[tokio::test]
+async fn scenario_mixture_returns_components_and_bands() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(100);
+    payload["scenarios"] = json!([
+        { "name": "baseline", "weight": 70 },
+        { "name": "low_home_advantage", "weight": 20, "home_advantage": 0 },
+        { "name": "high_variance", "weight": 10, "elo_scale": 0.5 }
+    ]);
+
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["simulations_performed"], 100);
+
+    let components = body["scenarios"]["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    assert_eq!(components[1]["name"], "low_home_advantage");
+    assert_eq!(components[1]["iterations"], 20);
+    let lower = &body["scenarios"]["lower_bound"];
+    let upper = &body["scenarios"]["upper_bound"];
+    for (team, row) in body["probability_matrix"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .enumerate()
+    {
+        for (position, p) in row.as_array().unwrap().iter().enumerate() {
+            let p = p.as_f64().unwrap();
+            assert!(lower[team][position].as_f64().unwrap() <= p + 1e-12);
+            assert!(p <= upper[team][position].as_f64().unwrap() + 1e-12);
+        }
+    }
+
+    // Plain requests carry no scenario block.
+    let (_, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert!(body.get("scenarios").is_none());
+}
+
+#[tokio::test]
+async fn scenario_mixture_rejects_bad_weights_and_streams() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["scenarios"] = json!([{ "name": "broken", "weight": 0 }]);
+    let (status, _) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["scenarios"] = json!([{ "name": "baseline", "weight": 1 }]);
+    let (status, _) = send(post_json("/simulate/stream", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_returns_only_the_selected_fields() {
+    let query = r#"
+        query {
+            simulate(input: {
+                schedule: [[1, 2, 1, 0], [2, 3, null, null], [3, 1, null, null]],
+                eloValues: [1600, 1500, 1400],
+                teamNames: ["A", "B", "C"],
+                iterations: 200
+            }) {
+                simulationsPerformed
+                teams(names: ["C", "A"]) { name probabilityBetween(from: 2, to: 3) }
+            }
+        }
+    "#;
+    let (status, body) = send(post_json("/graphql", &json!({ "query": query }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("errors").is_none(), "errors: {body}");
+
+    let simulation = &body["data"]["simulate"];
+    assert_eq!(simulation["simulationsPerformed"], 200);
+    let teams = simulation["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[0]["name"], "C");
+    assert_eq!(teams[1]["name"], "A");
+    assert!(teams[0].get("positionProbabilities").is_none());
+    let p = teams[0]["probabilityBetween"].as_f64().unwrap();
+    assert!((0.0..=1.0).contains(&p));
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn graphql_reports_validation_errors() {
+    let query = r#"{ simulate(input: { schedule: [], eloValues: [1500] }) { timeMs } }"#;
+    let (status, body) = send(post_json("/graphql", &json!({ "query": query }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let message = body["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("schedule"), "message: {message}");
+    assert_eq!(body["errors"][0]["extensions"]["field"], "schedule");
+}
+
+#[tokio::test]
+async fn config_reload_updates_defaults_and_leagues() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("league-sim.toml");
+    let write_config = |iterations: usize| {
+        let text = format!(
+            r#"
+            [simulation]
+            iterations = {iterations}
+
+            [[leagues]]
+            id = "duel"
+            name = "Two-team league"
+            number_teams = 2
+            matchdays = 2
+            split = false
+            zones = [{{ name = "champion", first = 1, last = 1 }}]
+            "#
+        );
+        std::fs::write(&path, text).unwrap();
+    };
+    write_config(30);
+
+    let config = crate::config::ConfigHandle::load(&path).unwrap();
+    let app = crate::create_router_with_state(crate::AppState {
+        config: config.clone(),
+        ..crate::AppState::from_env()
+    });
+    let mut payload = minimal_valid_simulate_payload();
+    payload.as_object_mut().unwrap().remove("iterations");
+    payload["structure"] = json!("duel");
+
+    let (status, body) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["simulations_performed"], 30);
+    assert_eq!(body["structure"]["name"], "Two-team league");
+
+    write_config(40);
+    assert!(config.reload().unwrap());
+    let (_, body) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(body["simulations_performed"], 40);
+
+    // A broken file is rejected and the previous configuration stays.
+    std::fs::write(&path, "[simulation]\niterations = \"many\"\n").unwrap();
+    assert!(config.reload().is_err());
+    assert_eq!(config.current().simulation.iterations, 40);
+}
+
+#[test]
+#[should_panic(expected = "configuration file")]
+fn invalid_config_file_stops_startup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("league-sim.toml");
+    std::fs::write(&path, "[limits]\nmax_iterations = \"many\"\n").unwrap();
+    crate::config::ConfigHandle::from_path(Some(path.display().to_string()));
+}
+
+#[test]
+fn config_rejects_zones_outside_the_league() {
+    let text = r#"
+        [[leagues]]
+        id = "tiny"
+        name = "Tiny"
+        number_teams = 4
+        matchdays = 6
+        split = false
+        zones = [{ name = "relegation", first = 4, last = 5 }]
+    "#;
+    let err = crate::config::RuntimeConfig::from_toml(text).unwrap_err();
+    assert!(err.contains("relegation"), "error: {err}");
+}
+
+#[test]
+fn yaml_config_with_environment_overrides() {
+    let text = r#"
+server:
+  port: 9000
+  cors_origins: ["https://shiny.example.org"]
+simulation:
+  iterations: 2000
+  tore_slope: 0.002
+cache:
+  size: 16
+"#;
+    let mut config = crate::config::RuntimeConfig::from_yaml(text).unwrap();
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.simulation.tore_slope, 0.002);
+    assert_eq!(config.cache.size, 16);
+
+    let env = |name: &str| match name {
+        "PORT" => Some("8081".to_string()),
+        "MAX_TEAMS" => Some("20".to_string()),
+        _ => None,
+    };
+    config.apply_env(env).unwrap();
+    assert_eq!(config.server.port, 8081);
+    assert_eq!(config.limits.max_teams, 20);
+    assert_eq!(config.simulation.iterations, 2000);
+
+    let err = config
+        .apply_env(|name| (name == "PORT").then(|| "eighty".to_string()))
+        .unwrap_err();
+    assert!(err.contains("PORT"), "error: {err}");
+}
+
+#[test]
+fn tls_config_takes_a_certificate_pair_or_acme_domains() {
+    let text = r#"
+        [server.tls]
+        cert_path = "/etc/league-sim/cert.pem"
+        key_path = "/etc/league-sim/key.pem"
+    "#;
+    let config = crate::config::RuntimeConfig::from_toml(text).unwrap();
+    assert!(config.server.tls.enabled());
+    assert!(!crate::config::RuntimeConfig::default().server.tls.enabled());
+
+    let err = crate::config::RuntimeConfig::from_toml(
+        "[server.tls]\ncert_path = \"/etc/league-sim/cert.pem\"",
+    )
+    .unwrap_err();
+    assert!(err.contains("key_path"), "error: {err}");
+
+    let mut config = crate::config::RuntimeConfig::default();
+    config
+        .apply_env(|name| match name {
+            "ACME_DOMAINS" => Some("sim.example.org, api.example.org".to_string()),
+            "ACME_CONTACT" => Some("admin@example.org".to_string()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        config.server.tls.acme_domains,
+        ["sim.example.org", "api.example.org"]
+    );
+    assert!(config.server.tls.enabled());
+
+    let err = crate::config::RuntimeConfig::from_toml(
+        r#"
+        [server.tls]
+        cert_path = "/etc/league-sim/cert.pem"
+        key_path = "/etc/league-sim/key.pem"
+        acme_domains = ["sim.example.org"]
+    "#,
+    )
+    .unwrap_err();
+    assert!(err.contains("not both"), "error: {err}");
+}
+
+#[tokio::test]
+async fn cors_headers_are_sent_for_configured_origins() {
+    let app = crate::create_router_with_state(crate::AppState {
+        cors_origins: vec!["https://shiny.example.org".to_string()],
+        ..crate::AppState::from_env()
+    });
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .header("origin", "https://shiny.example.org")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://shiny.example.org"
+    );
+
+    // No origins configured: no CORS headers.
+    let response = create_router()
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .header("origin", "https://shiny.example.org")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn awards_are_returned_only_when_requested() {
+    let (_, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert!(body.get("awards").is_none());
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["awards"] = json!(true);
+    payload["team_names"] = json!(["A", "B"]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let awards = body["awards"].as_array().unwrap();
+    assert_eq!(awards.len(), 2);
+    assert_eq!(awards[0]["team_name"], "A");
+    for field in ["most_goals", "fewest_conceded", "biggest_overperformance"] {
+        let total: f64 = awards.iter().map(|a| a[field].as_f64().unwrap()).sum();
+        assert!((total - 1.0).abs() < 1e-9, "{field} sums to {total}");
+    }
+}
+
+#[tokio::test]
+async fn match_importance_ranks_the_unplayed_matches() {
+    let (_, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert!(body.get("match_importance").is_none());
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["match_importance"] = json!(true);
+    payload["team_names"] = json!(["A", "B"]);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+
+    let ranked = body["match_importance"].as_array().unwrap();
+    assert_eq!(ranked.len(), 1);
+    let only = &ranked[0];
+    assert_eq!(only["row"], 1);
+    assert_eq!(only["team_home"], "B");
+    // Two teams: the bottom two places are everyone's, so only the title
+    // race can swing.
+    let swings = only["swings"].as_object().unwrap();
+    assert_eq!(swings.len(), 2);
+    assert!(swings["title"].as_f64().unwrap() > 0.0);
+    assert_eq!(swings["relegation"], 0.0);
+}
+
+#[tokio::test]
+async fn return_samples_adds_up_to_n_sampled_tables() {
+    let (_, body) = send(post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert!(body.get("samples").is_none());
+
+    let mut payload = minimal_valid_simulate_payload();
+    payload["return_samples"] = json!(5);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let samples = body["samples"].as_array().unwrap();
+    assert_eq!(samples.len(), 5);
+    for sample in samples {
+        let mut positions: Vec<u64> = sample["positions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p.as_u64().unwrap())
+            .collect();
+        positions.sort_unstable();
+        assert_eq!(positions, [1, 2]);
+        // Team 1 won the played match, so it ends on 3, 4 or 6 points.
+        let points = sample["points"].as_array().unwrap();
+        assert!([3, 4, 6].contains(&points[0].as_i64().unwrap()));
+    }
+
+    // Never more samples than iterations.
+    payload["return_samples"] = json!(1000);
+    let (_, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(body["samples"].as_array().unwrap().len(), 50);
+}
+
+#[tokio::test]
+async fn sparse_threshold_returns_only_the_likely_cells() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["seed"] = json!(7);
+    let (_, dense) = send(post_simulate_json(payload.clone())).await;
+    assert!(dense.get("probability_cells").is_none());
+    let matrix = dense["probability_matrix"].as_array().unwrap();
+
+    payload["sparse_threshold"] = json!(0.2);
+    let (status, body) = send(post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert!(body.get("probability_matrix").is_none());
+    let cells = body["probability_cells"].as_array().unwrap();
+    let expected: Vec<Value> = matrix
+        .iter()
+        .enumerate()
+        .flat_map(|(team, row)| {
+            let row = row.as_array().unwrap().clone();
+            row.into_iter()
+                .enumerate()
+                .filter(|(_, p)| p.as_f64().unwrap() > 0.2)
+                .map(move |(pos, p)| json!([team, pos + 1, p]))
+        })
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(cells, &expected);
+
+    // Reports are rendered from the full matrix.
+    let response = create_router()
+        .oneshot(post_json("/simulate?format=csv", &payload))
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(String::from_utf8(body.to_vec()).unwrap().lines().count(), 3);
+
+    payload["sparse_threshold"] = json!(1.0);
+    let (status, body) = send(post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "sparse_threshold");
+}
+
+#[tokio::test]
+async fn elo_trajectory_has_a_point_per_remaining_match() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["team_names"] = json!(["A", "B"]);
+    let (status, body) = send(post_json("/simulate/elo-trajectory", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["simulations_performed"], 50);
+
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[0]["team_name"], "A");
+    // A won the played match and is rated above its start.
+    assert!(teams[0]["current_elo"].as_f64().unwrap() > 1500.0);
+    for team in teams {
+        let points = team["points"].as_array().unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0]["match_number"], 2);
+        assert!(points[0]["std_dev"].as_f64().unwrap() > 0.0);
+    }
+
+    let (status, _) = send(post_json(
+        "/simulate/elo-trajectory",
+        &json!({"schedule": [], "elo_values": [1500.0]}),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn team_detail_describes_one_team() {
+    let mut payload = minimal_valid_simulate_payload();
+    payload["team_names"] = json!(["A", "B"]);
+    payload["seed"] = json!(3);
+    let (_, full) = send(post_simulate_json(payload.clone())).await;
+
+    let (status, body) = send(post_json("/simulate/team?team=B", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["team_name"], "B");
+    // Same seed, so the same row as the matrix of /simulate.
+    let row = full["team_names"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .position(|n| n == "B")
+        .unwrap();
+    assert_eq!(
+        body["position_probabilities"],
+        full["probability_matrix"][row]
+    );
+    // B lost the played match, so it ends on 0, 1 or 3 points.
+    let points: Vec<i64> = body["points_distribution"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["points"].as_i64().unwrap())
+        .collect();
+    assert!(points.iter().all(|p| [0, 1, 3].contains(p)), "{points:?}");
+    assert_eq!(body["best_position"], 1);
+    assert_eq!(body["worst_position"], 2);
+    assert!(body.get("zone_probabilities").is_none());
+
+    let fixtures = body["fixtures"].as_array().unwrap();
+    assert_eq!(fixtures.len(), 1);
+    assert_eq!(fixtures[0]["opponent"], "A");
+    assert_eq!(fixtures[0]["home"], true);
+    let total: f64 = ["win", "draw", "loss"]
+        .iter()
+        .map(|k| fixtures[0][k].as_f64().unwrap())
+        .sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let (status, body) = send(post_json("/simulate/team?team=C", &payload)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "team");
+
+    // Names beyond the league's teams cannot select one.
+    let mut extra = payload.clone();
+    extra["team_names"].as_array_mut().unwrap().push(json!("C"));
+    let (status, body) = send(post_json("/simulate/team?team=C", &extra)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "team_names");
+
+    let mut repeated = payload;
+    repeated["team_names"] = json!(["B", "B"]);
+    let (status, body) = send(post_json("/simulate/team?team=B", &repeated)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "team_names[1]");
+}
+
+#[tokio::test]
+async fn multi_season_reports_division_probabilities() {
+    let league = |elos: [f64; 3], names: [&str; 3]| {
+        json!({
+            "schedule": [[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]],
+            "elo_values": elos,
+            "team_names": names
+        })
+    };
+    let payload = json!({
+        "divisions": [
+            {"name": "Top", "request": league([1800.0, 1600.0, 1400.0], ["A", "B", "C"]), "relegated": 1},
+            {"name": "Second", "request": league([1500.0, 1300.0, 1200.0], ["D", "E", "F"]), "promoted": 1}
+        ],
+        "seasons": 2,
+        "regression": 0.8,
+        "iterations": 200
+    });
+    let (status, body) = send(post_json("/simulate/multi-season", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["divisions"], json!(["Top", "Second"]));
+    assert_eq!(body["simulations_performed"], 200);
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 6);
+    assert_eq!(teams[3]["team_name"], "D");
+    assert_eq!(teams[3]["division"], 1);
+    let next = teams[3]["division_probabilities"][0].as_array().unwrap();
+    let total: f64 = next.iter().map(|p| p.as_f64().unwrap()).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert_eq!(teams[3]["promoted_within"].as_array().unwrap().len(), 2);
+
+    let mut unbalanced = payload.clone();
+    unbalanced["divisions"][0]["relegated"] = json!(2);
+    let (status, body) = send(post_json("/simulate/multi-season", &unbalanced)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["message"].as_str().unwrap().contains("relegates 2"));
+
+    let mut invalid = payload;
+    invalid["divisions"][1]["request"]["elo_values"] = json!([]);
+    let (status, body) = send(post_json("/simulate/multi-season", &invalid)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["field"]
+        .as_str()
+        .unwrap()
+        .starts_with("divisions[1].request"));
+}
+
+#[tokio::test]
+async fn league_system_keeps_second_teams_out_of_promotion() {
+    let league = |elos: Vec<f64>, prefix: &str| {
+        let schedule: Vec<_> = (1..=6)
+            .flat_map(|home| (1..=6).map(move |away| (home, away)))
+            .filter(|(home, away)| home != away)
+            .map(|(home, away)| json!([home, away, null, null]))
+            .collect();
+        let names: Vec<_> = (1..=6).map(|i| format!("{}{}", prefix, i)).collect();
+        json!({"schedule": schedule, "elo_values": elos, "team_names": names})
+    };
+    let payload = json!({
+        "bundesliga": {"request": league(vec![1800.0, 1700.0, 1600.0, 1500.0, 1400.0, 1300.0], "A")},
+        "zweite_bundesliga": {"request": league(vec![1500.0; 6], "B")},
+        "dritte_liga": {
+            "request": league(vec![2400.0, 1500.0, 1500.0, 1500.0, 1500.0, 1500.0], "C"),
+            "promotion": [-50, 0, 0, 0, 0, 0]
+        },
+        "iterations": 200
+    });
+    let (status, body) = send(post_json("/simulate/league-system", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(
+        body["divisions"],
+        json!(["Bundesliga", "2. Bundesliga", "3. Liga"])
+    );
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 18);
+    assert_eq!(teams[12]["team_name"], "C1");
+    assert_eq!(
+        teams[12]["division_probabilities"][0],
+        json!([0.0, 0.0, 1.0])
+    );
+    let directly: f64 = teams[..6]
+        .iter()
+        .map(|t| t["relegated_directly"][0].as_f64().unwrap())
+        .sum();
+    assert!((directly - 2.0).abs() < 1e-9);
+    assert!(teams[5]["relegated_after_playoff"][0].is_number());
+
+    let mut invalid = payload;
+    invalid["dritte_liga"]["promotion"] = json!([-50]);
+    let (status, body) = send(post_json("/simulate/league-system", &invalid)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("1 promotion adjustments"));
+}
+
+#[tokio::test]
+async fn tournament_reports_round_probabilities() {
+    let payload = json!({
+        "teams": [
+            {"team": "FCB", "elo": 1950.0},
+            {"team": "SVE", "elo": 1350.0},
+            {"team": "B04", "elo": 1800.0},
+            {"team": "FCS", "elo": 1400.0}
+        ],
+        "iterations": 500
+    });
+    let (status, body) = send(post_json("/tournament", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(body["rounds"], json!(["Semi-finals", "Final", "Winner"]));
+    assert_eq!(body["simulations_performed"], 500);
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams[0]["team_name"], "FCB");
+    let winners: f64 = teams
+        .iter()
+        .map(|t| t["round_probabilities"][2].as_f64().unwrap())
+        .sum();
+    assert!((winners - 1.0).abs() < 1e-9);
+
+    let mut uneven = payload.clone();
+    uneven["teams"].as_array_mut().unwrap().pop();
+    let (status, body) = send(post_json("/tournament", &uneven)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "teams");
+
+    let mut duplicate = payload;
+    duplicate["teams"][1]["team"] = json!("FCB");
+    let (status, body) = send(post_json("/tournament", &duplicate)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["message"].as_str().unwrap().contains("entered twice"));
+}
+
+#[tokio::test]
+async fn tournament_plays_two_legged_rounds_on_request() {
+    let payload = json!({
+        "teams": [
+            {"team": "FCB", "elo": 1950.0},
+            {"team": "SVE", "elo": 1350.0},
+            {"team": "B04", "elo": 1800.0},
+            {"team": "FCS", "elo": 1400.0}
+        ],
+        "two_legs": {"away_goals": true},
+        "iterations": 500
+    });
+    let (status, body) = send(post_json("/tournament", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let finalists: f64 = body["teams"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["round_probabilities"][1].as_f64().unwrap())
+        .sum();
+    assert!((finalists - 2.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn tournament_puts_league_ratings_on_one_scale() {
+    let payload = json!({
+        "teams": [
+            {"team": "FCB", "elo": 1700.0, "league": "bundesliga"},
+            {"team": "SVE", "elo": 1700.0, "league": "3_liga"},
+            {"team": "B04", "elo": 1700.0, "league": "bundesliga"},
+            {"team": "FCS", "elo": 1700.0, "league": "3_liga"}
+        ],
+        "league_offsets": {"bundesliga": 0.0, "3_liga": -400.0},
+        "iterations": 500
+    });
+    let (status, body) = send(post_json("/tournament", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    let teams = body["teams"].as_array().unwrap();
+    let winner = |i: usize| teams[i]["round_probabilities"][2].as_f64().unwrap();
+    assert!(winner(0) > 2.0 * winner(1));
+
+    let mut missing = payload;
+    missing["league_offsets"] = json!({"bundesliga": 0.0});
+    let (status, body) = send(post_json("/tournament", &missing)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["teams[1].league", "teams[3].league"]);
+}
+
+#[tokio::test]
+async fn group_tournament_reports_round_probabilities() {
+    let group = |name: &str, top: f64| {
+        json!({
+            "name": name,
+            "request": {
+                "schedule": [[1, 2, null, null], [2, 3, null, null], [3, 1, null, null]],
+                "elo_values": [top, 1500.0, 1400.0],
+                "team_names": [format!("{name}1"), format!("{name}2"), format!("{name}3")],
+                "home_advantage": 0.0
+            }
+        })
+    };
+    let payload = json!({
+        "groups": [group("A", 1900.0), group("B", 1600.0), group("C", 1600.0), group("D", 1600.0)],
+        "iterations": 300
+    });
+    let (status, body) = send(post_json("/tournament/groups", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(
+        body["rounds"],
+        json!([
+            "Group stage",
+            "Quarter-finals",
+            "Semi-finals",
+            "Final",
+            "Winner"
+        ])
+    );
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 12);
+    assert_eq!(teams[3]["team_name"], "B1");
+    let through: f64 = teams
+        .iter()
+        .map(|t| t["round_probabilities"][1].as_f64().unwrap())
+        .sum();
+    assert!((through - 8.0).abs() < 1e-9);
+
+    let mut thirds = payload.clone();
+    thirds["best_thirds"] = json!(2);
+    let (status, body) = send(post_json("/tournament/groups", &thirds)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "bracket");
+
+    let mut unknown = payload;
+    unknown["bracket"] = json!([{"group": "A", "position": 1}, {"group": "E", "position": 2}]);
+    let (status, body) = send(post_json("/tournament/groups", &unknown)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "bracket[1].group");
+}
+
+#[tokio::test]
+async fn drawn_tournament_keeps_associations_apart() {
+    let associations = ["GER", "ENG", "ESP", "ITA"];
+    let teams: Vec<_> = (0..8)
+        .map(|i| {
+            json!({
+                "team": format!("T{}", i + 1),
+                "elo": 1800.0 - 40.0 * i as f64,
+                "pot": i / 2 + 1,
+                "association": associations[(i + i / 2) % 4]
+            })
+        })
+        .collect();
+    let payload = json!({"teams": teams, "groups": 2, "iterations": 200});
+    let (status, body) = send(post_json("/tournament/draw", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {body}");
+    assert_eq!(
+        body["rounds"],
+        json!(["Group stage", "Semi-finals", "Final", "Winner"])
+    );
+    let teams = body["teams"].as_array().unwrap();
+    assert_eq!(teams.len(), 8);
+    assert_eq!(teams[0]["team_name"], "T1");
+    let through: f64 = teams
+        .iter()
+        .map(|t| t["round_probabilities"][1].as_f64().unwrap())
+        .sum();
+    assert!((through - 4.0).abs() < 1e-9);
+
+    let mut crowded = payload.clone();
+    for team in crowded["teams"].as_array_mut().unwrap().iter_mut().take(3) {
+        team["association"] = json!("GER");
+    }
+    let (status, body) = send(post_json("/tournament/draw", &crowded)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["message"].as_str().unwrap().contains("'GER'"));
+
+    let mut unpotted = payload;
+    unpotted["teams"][0]["pot"] = json!(0);
+    let (status, body) = send(post_json("/tournament/draw", &unpotted)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "teams[0].pot");
+}
+
+#[tokio::test]
+async fn openapi_spec_documents_routes_and_schemas() {
+    let (status, spec) = send(get("/openapi.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+    for path in [
+        "/simulate",
+        "/simulate/batch",
+        "/simulate/outcomes",
+        "/simulate/elo-trajectory",
+        "/jobs/{id}",
+        "/sessions/{id}/events",
+    ] {
+        assert!(spec["paths"].get(path).is_some(), "missing path {path}");
+    }
+    assert!(spec["paths"]["/jobs/{id}"].get("delete").is_some());
+
+    let request = &spec["components"]["schemas"]["SimulateRequest"];
+    let required = request["required"].as_array().unwrap();
+    assert!(required.contains(&json!("schedule")));
+    assert!(required.contains(&json!("elo_values")));
+    assert!(request["properties"].get("scenarios").is_some());
+    assert!(spec["components"]["schemas"]
+        .get("SimulateResponse")
+        .is_some());
+}
+
+#[tokio::test]
+async fn v1_routes_answer_like_the_unversioned_aliases() {
+    let app = create_router();
+    let mut payload = minimal_valid_simulate_payload();
+    payload["seed"] = json!(12);
+    let (status, legacy) = send_to(&app, post_json("/simulate", &payload)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, versioned) = send_to(&app, post_json("/v1/simulate", &payload)).await;
+    assert_eq!(status, StatusCode::OK, "body: {versioned}");
+    assert_eq!(
+        versioned["probability_matrix"],
+        legacy["probability_matrix"]
+    );
+
+    let (status, _) = send_to(&app, get("/v1/health")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, spec) = send_to(&app, get("/v1/openapi.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spec["servers"][0]["url"], "/v1");
+
+    let (status, _) = send_to(&app, post_json("/v2/simulate", &payload)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn metrics_report_latency_histograms_per_route() {
+    let app = create_router();
+    let payload = minimal_valid_simulate_payload();
+    send_to(&app, post_json("/simulate", &payload)).await;
+    send_to(&app, post_json("/v1/simulate", &payload)).await;
+    send_to(&app, post_json("/simulate/batch", &batch_payload())).await;
+    send_to(&app, get("/no/such/route")).await;
+
+    let response = app.clone().oneshot(get("/metrics")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    let simulate = r#"method="POST",route="/simulate",status="200""#;
+    // Both spellings of the route share one series.
+    assert!(
+        text.contains(&format!(
+            "http_request_duration_seconds_count{{{}}} 2",
+            simulate
+        )),
+        "{text}"
+    );
+    assert!(text.contains(&format!(
+        "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2",
+        simulate
+    )));
+    // Only iterations that ran count: the second request is a cache hit.
+    assert!(
+        text.contains(&format!("simulation_iterations_total{{{}}} 50", simulate)),
+        "{text}"
+    );
+    // A batch counts the leagues simulated off the async workers; its two
+    // identical leagues run once.
+    assert!(
+        text.contains(
+            r#"simulation_iterations_total{method="POST",route="/simulate/batch",status="200"} 50"#
+        ),
+        "{text}"
+    );
+    assert!(text.contains(r#"route="unmatched",status="404""#));
+    let request_bytes = text
+        .lines()
+        .find(|l| l.starts_with(&format!("http_request_body_bytes_total{{{}}}", simulate)))
+        .and_then(|l| l.rsplit(' ').next())
+        .and_then(|n| n.parse::<u64>().ok())
+        .unwrap();
+    assert_eq!(
+        request_bytes,
+        2 * serde_json::to_vec(&payload).unwrap().len() as u64
+    );
+}
+
+#[cfg(feature = "swagger-ui")]
+#[tokio::test]
+async fn swagger_ui_is_served() {
+    let response = create_router().oneshot(get("/swagger-ui/")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(String::from_utf8_lossy(&bytes).contains("swagger"));
+}
+
+#[tokio::test]
+async fn session_recomputes_on_live_and_final_scores() {
+    let app = create_router();
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(500);
+
+    let (status, opened) = send_to(&app, post_json("/sessions", &payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = opened["session_id"].as_str().unwrap().to_string();
+    let events = format!("/sessions/{}/events", id);
+
+    // Team_2 leads the open match 3-0 in the 89th minute.
+    let live = json!({"events": [{"row": 1, "goals_home": 3, "goals_away": 0, "minute": 89}]});
+    let (status, body) = send_to(&app, post_json(&events, &live)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["live"].as_array().unwrap().len(), 1);
+    assert_eq!(body["result"]["team_names"][0], "Team_2");
+    assert!(body["result"]["probability_matrix"][0][0].as_f64().unwrap() > 0.95);
+
+    let full_time = json!({"events": [{"row": 1, "goals_home": 2, "goals_away": 0}]});
+    let (_, body) = send_to(&app, post_json(&events, &full_time)).await;
+    assert!(body["live"].as_array().unwrap().is_empty());
+    assert_eq!(body["result"]["probability_matrix"][0][0], 1.0);
+
+    let (status, body) = send_to(&app, get(&format!("/sessions/{}", id))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["team_names"][0], "Team_2");
+
+    let bad_row = json!({"events": [{"row": 9, "goals_home": 0, "goals_away": 0}]});
+    let (status, _) = send_to(&app, post_json(&events, &bad_row)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let delete = Request::builder()
+        .method("DELETE")
+        .uri(format!("/sessions/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send_to(&app, delete).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_to(&app, get(&format!("/sessions/{}", id))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn a_full_session_store_turns_new_sessions_away() {
+    let app = crate::create_router_with_state(crate::AppState {
+        sessions: crate::sessions::SessionRegistry::new(std::time::Duration::from_secs(60), 1),
+        ..crate::AppState::from_env()
+    });
+    let payload = minimal_valid_simulate_payload();
+
+    let (status, opened) = send_to(&app, post_json("/sessions", &payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, body) = send_to(&app, post_json("/sessions", &payload)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "overloaded");
+
+    // Closing a session makes room again.
+    let delete = Request::builder()
+        .method("DELETE")
+        .uri(format!(
+            "/sessions/{}",
+            opened["session_id"].as_str().unwrap()
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send_to(&app, delete).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_to(&app, post_json("/sessions", &payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn configured_limits_reject_oversized_requests() {
+    let config = crate::config::RuntimeConfig {
+        limits: crate::config::Limits {
+            max_iterations: 100,
+            max_teams: 2,
+            max_matches: 4,
+            max_batch_leagues: 1,
+        },
+        ..Default::default()
+    };
+    let app = crate::create_router_with_state(crate::AppState {
+        config: crate::config::ConfigHandle::new(config),
+        ..crate::AppState::from_env()
+    });
+
+    let mut too_many_teams = minimal_valid_simulate_payload();
+    too_many_teams["elo_values"] = json!([1500.0, 1500.0, 1500.0]);
+    let (status, body) = send_to(&app, post_json("/simulate", &too_many_teams)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"], "limit_exceeded");
+    assert_eq!(body["field"], "elo_values");
+
+    let mut too_many_matches = minimal_valid_simulate_payload();
+    too_many_matches["schedule"] = json!(vec![[Some(1), Some(2), None, None]; 5]);
+    let (status, body) = send_to(&app, post_json("/simulate", &too_many_matches)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["field"], "schedule");
+
+    let mut too_many_iterations = minimal_valid_simulate_payload();
+    too_many_iterations["iterations"] = json!(101);
+    let (status, body) = send_to(&app, post_json("/simulate", &too_many_iterations)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["field"], "iterations");
+
+    let league = json!({"name": "L", "request": minimal_valid_simulate_payload()});
+    let batch = json!({ "leagues": [league.clone(), league] });
+    for uri in ["/simulate/batch", "/jobs"] {
+        let (status, body) = send_to(&app, post_json(uri, &batch)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{uri}");
+        assert_eq!(body["field"], "leagues");
+    }
+}
+
+#[tokio::test]
+async fn rate_limiter_answers_429_once_the_burst_is_used() {
+    use crate::auth::{ApiKey, ApiKeys, Scope};
+    let key = |name: &str| ApiKey {
+        key: name.to_string(),
+        name: name.to_string(),
+        scopes: vec![Scope::Admin],
+        daily_iterations: None,
+    };
+    let app = crate::create_router_with_state(crate::AppState {
+        rate_limiter: crate::ratelimit::RateLimiter::new(60, 2, false),
+        auth: Some(ApiKeys::new(vec![key("a"), key("b")])),
+        ..crate::AppState::from_env()
+    });
+    let with_key = |key: &str| {
+        Request::builder()
+            .uri("/jobs/unknown")
+            .header("x-api-key", key)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        let (status, _) = send_to(&app, with_key("a")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    let response = app.clone().oneshot(with_key("a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+
+    // Other clients and health checks are unaffected.
+    let (status, _) = send_to(&app, with_key("b")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_to(&app, get("/health")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Made-up keys share their address's bucket instead of opening fresh
+    // ones.
+    for (i, expected) in [
+        StatusCode::UNAUTHORIZED,
+        StatusCode::UNAUTHORIZED,
+        StatusCode::TOO_MANY_REQUESTS,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let (status, _) = send_to(&app, with_key(&format!("random-{i}"))).await;
+        assert_eq!(status, expected);
+    }
+}
+
+#[tokio::test]
+async fn rate_limiter_trusts_only_the_hop_the_proxy_added() {
+    let app = crate::create_router_with_state(crate::AppState {
+        rate_limiter: crate::ratelimit::RateLimiter::new(60, 1, true),
+        ..crate::AppState::from_env()
+    });
+    let forwarded = |chain: &str| {
+        Request::builder()
+            .uri("/jobs/unknown")
+            .header("x-forwarded-for", chain)
+            .body(Body::empty())
+            .unwrap()
+    };
+    let (status, _) = send_to(&app, forwarded("1.1.1.1, 10.0.0.7")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // A forged leftmost hop does not change the client.
+    let (status, _) = send_to(&app, forwarded("2.2.2.2, 10.0.0.7")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _) = send_to(&app, forwarded("10.0.0.8")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn api_keys_enforce_scopes_and_daily_budgets() {
+    use crate::auth::{ApiKey, ApiKeys, Scope};
+    let keys = ApiKeys::new(vec![
+        ApiKey {
+            key: "shiny".to_string(),
+            name: "shiny".to_string(),
+            scopes: vec![Scope::Simulate, Scope::Batch],
+            daily_iterations: None,
+        },
+        ApiKey {
+            key: "trial".to_string(),
+            name: String::new(),
+            scopes: vec![Scope::Simulate],
+            daily_iterations: Some(120),
+        },
+    ]);
+    let app = crate::create_router_with_state(crate::AppState {
+        auth: Some(keys),
+        ..crate::AppState::from_env()
+    });
+    let with_key = |mut request: Request<Body>, key: &str| {
+        request
+            .headers_mut()
+            .insert("x-api-key", key.parse().unwrap());
+        request
+    };
+    let payload = minimal_valid_simulate_payload();
+
+    let (status, body) = send_to(&app, post_simulate_json(payload.clone())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+    let (status, _) = send_to(&app, with_key(post_simulate_json(payload.clone()), "nope")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Bearer tokens are accepted as well.
+    let mut request = post_simulate_json(payload.clone());
+    request
+        .headers_mut()
+        .insert("authorization", "Bearer shiny".parse().unwrap());
+    let (status, _) = send_to(&app, request).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let batch = json!({ "leagues": [{ "name": "A", "request": payload.clone() }] });
+    let (status, body) = send_to(
+        &app,
+        with_key(post_json("/simulate/batch", &batch), "trial"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "forbidden");
+    // Versioned paths need the same scopes.
+    let (status, _) = send_to(
+        &app,
+        with_key(post_json("/v1/simulate/batch", &batch), "trial"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A time budget without iterations may run up to the iteration limit,
+    // and is charged that.
+    let mut open_ended = payload.clone();
+    open_ended.as_object_mut().unwrap().remove("iterations");
+    open_ended["time_budget_ms"] = json!(50);
+    let (status, _) = send_to(&app, with_key(post_simulate_json(open_ended), "trial")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // 50 + 50 iterations fit the budget of 120, a third run does not.
+    for _ in 0..2 {
+        let (status, _) =
+            send_to(&app, with_key(post_simulate_json(payload.clone()), "trial")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let response = app
+        .clone()
+        .oneshot(with_key(post_simulate_json(payload.clone()), "trial"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+
+    let (status, _) = send_to(&app, get("/health")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_to(&app, get("/v1/health")).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn budgets_charge_the_extra_passes_a_request_enables() {
+    use crate::auth::{ApiKey, ApiKeys, Scope};
+    let keys = ApiKeys::new(vec![ApiKey {
+        key: "trial".to_string(),
+        name: String::new(),
+        scopes: vec![Scope::Simulate],
+        daily_iterations: Some(200),
+    }]);
+    let app = crate::create_router_with_state(crate::AppState {
+        auth: Some(keys),
+        ..crate::AppState::from_env()
+    });
+    let with_key = |mut request: Request<Body>| {
+        request
+            .headers_mut()
+            .insert("x-api-key", "trial".parse().unwrap());
+        request
+    };
+
+    // Awards and the expected table each rerun the 50 iterations: 150 in
+    // all, so the 50 left of the budget fit one plain run and no more.
+    let mut payload = minimal_valid_simulate_payload();
+    payload["awards"] = json!(true);
+    payload["expected_table"] = json!(true);
+    let (status, _) = send_to(&app, with_key(post_simulate_json(payload))).await;
+    assert_eq!(status, StatusCode::OK);
+    let plain = minimal_valid_simulate_payload();
+    let (status, _) = send_to(&app, with_key(post_simulate_json(plain.clone()))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send_to(&app, with_key(post_simulate_json(plain))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "budget_exhausted");
+}
+
+#[tokio::test]
+async fn budgets_charge_every_run_of_graphql_queries_and_session_events() {
+    use crate::auth::{ApiKey, ApiKeys, Scope};
+    let keys = ApiKeys::new(vec![ApiKey {
+        key: "trial".to_string(),
+        name: String::new(),
+        scopes: vec![Scope::Simulate],
+        daily_iterations: Some(120),
+    }]);
+    let app = crate::create_router_with_state(crate::AppState {
+        auth: Some(keys),
+        ..crate::AppState::from_env()
+    });
+    let with_key = |mut request: Request<Body>| {
+        request
+            .headers_mut()
+            .insert("x-api-key", "trial".parse().unwrap());
+        request
+    };
+
+    // Opening the session runs 50 iterations, and so does every event.
+    let (status, opened) = send_to(
+        &app,
+        with_key(post_json("/sessions", &minimal_valid_simulate_payload())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let events = format!(
+        "/sessions/{}/events",
+        opened["session_id"].as_str().unwrap()
+    );
+    let update = json!({"events": [{"row": 1, "goals_home": 1, "goals_away": 0, "minute": 10}]});
+    let (status, _) = send_to(&app, with_key(post_json(&events, &update))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send_to(&app, with_key(post_json(&events, &update))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "budget_exhausted");
+
+    #[cfg(feature = "graphql")]
+    {
+        let app = crate::create_router_with_state(crate::AppState {
+            auth: Some(ApiKeys::new(vec![ApiKey {
+                key: "trial".to_string(),
+                name: String::new(),
+                scopes: vec![Scope::Simulate],
+                daily_iterations: Some(120),
+            }])),
+            ..crate::AppState::from_env()
+        });
+        // Each alias is a run of its own: two fit the budget, the third
+        // does not.
+        let run = r#"simulate(input: {
+            schedule: [[1, 2, null, null]], eloValues: [1500, 1500], iterations: 50
+        }) { simulationsPerformed }"#;
+        let query = format!("{{ a: {run} b: {run} c: {run} }}");
+        let (status, body) = send_to(
+            &app,
+            with_key(post_json("/graphql", &json!({ "query": query }))),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let errors = body["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1, "errors: {body}");
+        assert_eq!(errors[0]["extensions"]["code"], "budget_exhausted");
+    }
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn request_span_continues_the_callers_trace() {
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let request = Request::builder()
+        .uri("/simulate")
+        .header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .body(Body::empty())
+        .unwrap();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let span = crate::telemetry::request_span(&request);
+        let context = span.context();
+        assert_eq!(
+            context.span().span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    });
+}
+
+#[tokio::test]
+async fn responses_carry_the_request_id() {
+    let app = crate::create_router();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .header("x-request-id", "shiny-42")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-request-id"], "shiny-42");
+
+    // One is generated when the caller sends none.
+    let response = app.oneshot(get("/health")).await.unwrap();
+    let generated = response.headers()["x-request-id"].to_str().unwrap();
+    assert_eq!(generated.len(), 36);
+}
+
+#[tokio::test]
+async fn simulations_wait_in_a_bounded_queue_then_get_429() {
+    let overload = crate::overload::OverloadLimits::new(std::time::Duration::from_secs(60), 1, 1);
+    let permits = overload.permits();
+    let app = crate::create_router_with_state(crate::AppState {
+        overload: overload.clone(),
+        ..crate::AppState::from_env()
+    });
+
+    // A running simulation holds the only slot; the next request queues.
+    let held = permits.acquire_owned().await.unwrap();
+    let queued = tokio::spawn(
+        app.clone()
+            .oneshot(post_simulate_json(minimal_valid_simulate_payload())),
+    );
+    while overload.queue_depth() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    // The queue is full now.
+    let response = app
+        .clone()
+        .oneshot(post_simulate_json(minimal_valid_simulate_payload()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+    let (status, _) = send_to(&app, get("/health")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = app.clone().oneshot(get("/metrics")).await.unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(metrics.contains("simulation_queue_depth 1\n"));
+    assert!(metrics.contains("simulation_queue_capacity 1\n"));
+    assert!(metrics.contains("simulation_queue_rejected_total 1\n"));
+    assert!(metrics.contains("simulations_in_progress 1\n"));
+
+    // Freeing the slot lets the queued request run.
+    drop(held);
+    let response = queued.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(overload.queue_depth(), 0);
+    let (status, _) = send_to(&app, post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn simulations_exceeding_the_timeout_get_503() {
+    let app = crate::create_router_with_state(crate::AppState {
+        overload: crate::overload::OverloadLimits::new(std::time::Duration::ZERO, 4, 4),
+        ..crate::AppState::from_env()
+    });
+    // Long enough to outlast the timer's first tick; a tiny run can finish
+    // before a zero timeout fires.
+    let mut payload = minimal_valid_simulate_payload();
+    payload["iterations"] = json!(100_000);
+    let (status, body) = send_to(&app, post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "timeout");
+}
+
+#[tokio::test]
+async fn timed_out_simulations_keep_their_slot_until_they_finish() {
+    let overload = crate::overload::OverloadLimits::new(std::time::Duration::ZERO, 1, 0);
+    let permits = overload.permits();
+    let app = crate::create_router_with_state(crate::AppState {
+        overload,
+        ..crate::AppState::from_env()
+    });
+    // A full 18-team season, long enough to outlast the request.
+    let schedule: Vec<Value> = (1..=18)
+        .flat_map(|home| {
+            (1..=18)
+                .filter(move |&away| away != home)
+                .map(move |away| json!([home, away, null, null]))
+        })
+        .collect();
+    let payload = json!({
+        "schedule": schedule,
+        "elo_values": vec![1500.0; 18],
+        "iterations": 3_000
+    });
+
+    let (status, _) = send_to(&app, post_simulate_json(payload)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    // The abandoned run still holds the only slot.
+    assert_eq!(permits.available_permits(), 0);
+    let (status, _) = send_to(&app, post_simulate_json(minimal_valid_simulate_payload())).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    while permits.available_permits() == 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn jobs_and_streams_take_a_slot_before_they_are_accepted() {
+    let overload = crate::overload::OverloadLimits::new(std::time::Duration::from_secs(60), 1, 1);
+    let permits = overload.permits();
+    let app = crate::create_router_with_state(crate::AppState {
+        overload: overload.clone(),
+        jobs: crate::jobs::JobRegistry::new(std::time::Duration::from_secs(60), 1),
+        ..crate::AppState::from_env()
+    });
+
+    // With the only slot taken, a job waits in the queue.
+    let held = permits.clone().acquire_owned().await.unwrap();
+    let (status, created) = send_to(&app, post_json("/jobs", &batch_payload())).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(overload.queue_depth(), 1);
+
+    // The queue is full now, for jobs and streams alike.
+    let (status, body) = send_to(&app, post_json("/jobs", &batch_payload())).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "overloaded");
+    let (status, _) = send_to(
+        &app,
+        post_json("/simulate/stream", &minimal_valid_simulate_payload()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    drop(held);
+    let id = created["job_id"].as_str().unwrap();
+    let (_, job) = wait_for_job(&app, id).await;
+    assert_eq!(job["status"], "succeeded");
+    while permits.available_permits() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    // A full job store forgets its oldest finished job to make room.
+    let (status, _) = send_to(&app, post_json("/jobs", &batch_payload())).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let (status, _) = send_to(&app, get(&format!("/jobs/{}", id))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn streams_stop_simulating_once_the_client_goes_away() {
+    let overload = crate::overload::OverloadLimits::new(std::time::Duration::from_secs(60), 1, 0);
+    let permits = overload.permits();
+    let app = crate::create_router_with_state(crate::AppState {
+        overload,
+        ..crate::AppState::from_env()
+    });
+    // A full 18-team season that would take the better part of a minute.
+    let schedule: Vec<Value> = (1..=18)
+        .flat_map(|home| {
+            (1..=18)
+                .filter(move |&away| away != home)
+                .map(move |away| json!([home, away, null, null]))
+        })
+        .collect();
+    let payload = json!({
+        "schedule": schedule,
+        "elo_values": vec![1500.0; 18],
+        "iterations": 100_000
+    });
+
+    let response = app
+        .clone()
+        .oneshot(post_json("/simulate/stream?batch_size=100", &payload))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    drop(response);
+
+    // The run notices at its next batch and gives up the slot.
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while permits.available_permits() == 0 {
+        assert!(std::time::Instant::now() < deadline, "the run kept going");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,95 @@
+// Built-in HTTPS, so a simple deployment can expose the server without a
+// reverse proxy in front. `[server.tls]` names either a PEM certificate and
+// key, re-read on SIGHUP so a renewal (e.g. a certbot deploy hook) needs no
+// restart, or domains to obtain and renew a Let's Encrypt certificate for
+// over TLS-ALPN-01 on the listening port. Without the `tls` feature a
+// configuration asking for HTTPS is rejected at startup.
+
+use crate::config::TlsConfig;
+use axum::Router;
+use std::io;
+use std::net::SocketAddr;
+
+#[cfg(feature = "tls")]
+use axum_server::tls_rustls::RustlsConfig;
+#[cfg(feature = "tls")]
+use rustls_acme::{caches::DirCache, AcmeConfig};
+#[cfg(feature = "tls")]
+use std::path::PathBuf;
+#[cfg(feature = "tls")]
+use tokio_stream::StreamExt;
+
+/// Serve `app` over HTTPS on `addr` until the server fails. Peer addresses
+/// are passed on as `ConnectInfo`, as with plain HTTP.
+#[cfg(feature = "tls")]
+pub async fn serve(addr: SocketAddr, app: Router, tls: &TlsConfig) -> io::Result<()> {
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    match (&tls.cert_path, &tls.key_path) {
+        (Some(cert), Some(key)) => {
+            let config = RustlsConfig::from_pem_file(cert, key)
+                .await
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", cert.display(), e)))?;
+            reload_on_hangup(config.clone(), cert.clone(), key.clone());
+            axum_server::bind_rustls(addr, config).serve(service).await
+        }
+        _ => {
+            if tls.acme_cache_dir.is_none() {
+                tracing::warn!(
+                    "tls: no acme_cache_dir, the certificate is requested anew on every start"
+                );
+            }
+            let mut state = AcmeConfig::new(&tls.acme_domains)
+                .contact(tls.acme_contact.iter().map(|c| format!("mailto:{}", c)))
+                .cache_option(tls.acme_cache_dir.clone().map(DirCache::new))
+                .directory_lets_encrypt(!tls.acme_staging)
+                .state();
+            let acceptor = state.axum_acceptor(state.default_rustls_config());
+            // Drives ordering and renewal; each step is only reported.
+            tokio::spawn(async move {
+                while let Some(event) = state.next().await {
+                    match event {
+                        Ok(event) => tracing::info!(?event, "acme"),
+                        Err(error) => tracing::warn!(?error, "acme"),
+                    }
+                }
+            });
+            axum_server::bind(addr)
+                .acceptor(acceptor)
+                .serve(service)
+                .await
+        }
+    }
+}
+
+#[cfg(not(feature = "tls"))]
+pub async fn serve(_addr: SocketAddr, _app: Router, _tls: &TlsConfig) -> io::Result<()> {
+    Err(io::Error::other(
+        "HTTPS needs a build with the `tls` feature",
+    ))
+}
+
+/// Re-read the certificate and key on SIGHUP; a pair that fails to load
+/// is reported and the current one kept.
+#[cfg(feature = "tls")]
+fn reload_on_hangup(config: RustlsConfig, cert: PathBuf, key: PathBuf) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+        let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+            tracing::warn!("tls: cannot listen for SIGHUP");
+            return;
+        };
+        while hangup.recv().await.is_some() {
+            match config.reload_from_pem_file(&cert, &key).await {
+                Ok(()) => tracing::info!(cert = %cert.display(), "tls: certificate reloaded"),
+                Err(e) => tracing::warn!(
+                    cert = %cert.display(),
+                    error = %e,
+                    "tls: invalid certificate, keeping the previous one"
+                ),
+            }
+        }
+    });
+    #[cfg(not(unix))]
+    let _ = (config, cert, key);
+}
@@ -0,0 +1,565 @@
+// Single-elimination cups (DFB-Pokal style) and group-stage tournaments
+// (World Cup, Euro), the latter also from a simulated draw, simulated from
+// Elo ratings, with extra time and penalties, answering "how far does each
+// team get".
+
+use crate::config::RuntimeConfig;
+use crate::error::{ApiError, ApiJson, ErrorBody, FieldError};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{extract::State, response::Response};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::tournament::{
+    group_name, run_drawn_tournament, run_group_tournament, run_knockout, Draw, DrawTeam,
+    DrawnTournament, Group, GroupTournament, Knockout, Slot, TournamentResult,
+};
+use league_sim_core::{apply_league_offsets, SimulationParams, TwoLegRules};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TournamentRequest {
+    /// Teams in bracket order: the first round pairs the 1st and 2nd, the
+    /// 3rd and 4th, ..., the first of each pair at home. A power of two.
+    teams: Vec<CupTeam>,
+
+    /// ELO points added to the ratings of each league's teams to put
+    /// leagues rated on their own scales on one, e.g. `{"bundesliga": 0,
+    /// "3_liga": -300}`; every team's `league` needs an entry
+    league_offsets: Option<HashMap<String, f64>>,
+
+    /// Whether the final is played on neutral ground (default: true)
+    neutral_final: Option<bool>,
+
+    /// Play every round before the final over two legs, settled by these
+    /// rules (default: single matches)
+    two_legs: Option<TwoLegRules>,
+
+    /// Number of Monte Carlo runs (default: 10000)
+    iterations: Option<usize>,
+
+    /// ELO modification factor (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points (default: 65)
+    home_advantage: Option<f64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CupTeam {
+    team: String,
+    elo: f64,
+    /// League the team plays in, a key of `league_offsets` (optional)
+    league: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TournamentResponse {
+    #[serde(flatten)]
+    pub result: TournamentResult,
+    pub simulations_performed: usize,
+    pub time_ms: u128,
+}
+
+/// One row per team with its probability of reaching every round.
+impl Tabular for TournamentResponse {
+    fn table(&self) -> Report {
+        let mut report =
+            Report::new(std::iter::once("team".to_string()).chain(self.result.rounds.clone()));
+        for team in &self.result.teams {
+            let mut cells = vec![Cell::Text(team.team_name.clone())];
+            cells.extend(
+                team.round_probabilities
+                    .iter()
+                    .map(|&p| Cell::Probability(p)),
+            );
+            report.push(cells);
+        }
+        report
+    }
+}
+
+/// Simulate a knockout cup and report each team's chance of reaching every
+/// round.
+#[utoipa::path(
+    post,
+    path = "/tournament",
+    tag = "simulation",
+    request_body = TournamentRequest,
+    responses(
+        (status = 200, description = "Round-by-round advancement probabilities per team", body = TournamentResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_tournament(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<TournamentRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let config = state.config.current();
+    let limits = &config.limits;
+    if payload.teams.len() > limits.max_teams {
+        return Err(ApiError::limit(
+            "teams",
+            format!(
+                "{} teams exceed the limit of {}",
+                payload.teams.len(),
+                limits.max_teams
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, team) in payload.teams.iter().enumerate() {
+        if !team.elo.is_finite() {
+            errors.push(FieldError {
+                field: format!("teams[{}].elo", i),
+                message: format!("teams[{}].elo must be a finite number", i),
+            });
+        }
+        if !seen.insert(team.team.as_str()) {
+            errors.push(FieldError {
+                field: format!("teams[{}].team", i),
+                message: format!("team '{}' is entered twice", team.team),
+            });
+        }
+    }
+    let leagues: Vec<Option<&str>> = payload.teams.iter().map(|t| t.league.as_deref()).collect();
+    let offsets = payload.league_offsets.unwrap_or_default();
+    check_league_offsets(&leagues, &offsets, &mut errors);
+    let iterations = check_iterations(payload.iterations, &config, &mut errors);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let elos: Vec<f64> = payload.teams.iter().map(|t| t.elo).collect();
+    let knockout = Knockout {
+        team_names: payload.teams.iter().map(|t| t.team.clone()).collect(),
+        elos: apply_league_offsets(&elos, &leagues, &offsets),
+        params: knockout_params(payload.mod_factor, payload.home_advantage, &config),
+        neutral_final: payload.neutral_final.unwrap_or(true),
+        two_legs: payload.two_legs,
+    };
+    knockout.check().map_err(|e| ApiError::field("teams", e))?;
+
+    crate::metrics::set_iterations(iterations);
+    let result = telemetry::spawn_blocking(move || run_knockout(&knockout, iterations))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(format.render(TournamentResponse {
+        result,
+        simulations_performed: iterations,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GroupTournamentRequest {
+    /// Groups in order; each `request` as for /simulate (its `iterations`
+    /// are ignored). Set `home_advantage` to 0 there for neutral venues.
+    groups: Vec<GroupRequest>,
+
+    /// Third-placed teams going through besides the bracket's group places
+    /// (default: 0)
+    #[serde(default)]
+    best_thirds: usize,
+
+    /// First knockout round in bracket order (default: each group winner
+    /// against the runner-up of the neighbouring group, A1-B2, C1-D2, ...,
+    /// B1-A2, D1-C2, ...). Required when best thirds go through.
+    bracket: Option<Vec<BracketPlace>>,
+
+    /// Whether the knockout rounds are on neutral ground (default: true)
+    neutral: Option<bool>,
+
+    /// Play every knockout round before the final over two legs, settled
+    /// by these rules (default: single matches)
+    two_legs: Option<TwoLegRules>,
+
+    /// Number of Monte Carlo runs (default: 10000)
+    iterations: Option<usize>,
+
+    /// ELO modification factor of the knockout rounds (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points of the knockout rounds, unless neutral
+    /// (default: 65)
+    home_advantage: Option<f64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GroupRequest {
+    name: String,
+    request: SimulateRequest,
+}
+
+/// A team of the first knockout round: a group's `position` (1-based), or
+/// the n-th `best_third`.
+#[derive(Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum BracketPlace {
+    Group { group: String, position: usize },
+    BestThird { best_third: usize },
+}
+
+/// Simulate group tables followed by a knockout bracket filled from them,
+/// and report each team's chance of reaching every round.
+#[utoipa::path(
+    post,
+    path = "/tournament/groups",
+    tag = "simulation",
+    request_body = GroupTournamentRequest,
+    responses(
+        (status = 200, description = "Round-by-round advancement probabilities per team", body = TournamentResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request or bracket", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_group_tournament(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<GroupTournamentRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let config = state.config.current();
+    if payload.groups.len() > config.limits.max_batch_leagues {
+        return Err(ApiError::limit(
+            "groups",
+            format!(
+                "{} groups exceed the limit of {}",
+                payload.groups.len(),
+                config.limits.max_batch_leagues
+            ),
+        ));
+    }
+    let mut errors = Vec::new();
+    let iterations = check_iterations(payload.iterations, &config, &mut errors);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+
+    let names: Vec<&str> = payload.groups.iter().map(|g| g.name.as_str()).collect();
+    let bracket = resolve_bracket(payload.bracket, payload.best_thirds, &names)?;
+
+    let mut groups = Vec::with_capacity(payload.groups.len());
+    for (i, group) in payload.groups.into_iter().enumerate() {
+        let job = SimulationJob::from_request(group.request, &config).map_err(|e| {
+            e.within(
+                &format!("groups[{}].request", i),
+                &format!("group '{}'", group.name),
+            )
+        })?;
+        groups.push(Group {
+            name: group.name,
+            season: job.season,
+            params: job.params,
+            team_names: job.team_names,
+        });
+    }
+    let tournament = GroupTournament {
+        groups,
+        best_thirds: payload.best_thirds,
+        bracket,
+        params: knockout_params(payload.mod_factor, payload.home_advantage, &config),
+        neutral: payload.neutral.unwrap_or(true),
+        two_legs: payload.two_legs,
+    };
+    tournament.check().map_err(ApiError::InvalidRequest)?;
+
+    crate::metrics::set_iterations(iterations);
+    let result = telemetry::spawn_blocking(move || run_group_tournament(&tournament, iterations))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(format.render(TournamentResponse {
+        result,
+        simulations_performed: iterations,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DrawTournamentRequest {
+    /// Teams to draw
+    teams: Vec<DrawTeamRequest>,
+
+    /// Number of groups, named A, B, ...; a group takes one team per pot
+    groups: usize,
+
+    /// Whether each group plays home and away (default: false, once on
+    /// neutral ground)
+    #[serde(default)]
+    home_and_away: bool,
+
+    /// Third-placed teams going through besides the bracket's group places
+    /// (default: 0)
+    #[serde(default)]
+    best_thirds: usize,
+
+    /// First knockout round in bracket order, groups named A, B, ...
+    /// (default: winners against runners-up as for /tournament/groups)
+    bracket: Option<Vec<BracketPlace>>,
+
+    /// Whether the knockout rounds are on neutral ground (default: true)
+    neutral: Option<bool>,
+
+    /// Play every knockout round before the final over two legs, settled
+    /// by these rules (default: single matches)
+    two_legs: Option<TwoLegRules>,
+
+    /// Number of Monte Carlo runs, each with its own draw (default: 10000)
+    iterations: Option<usize>,
+
+    /// ELO offset per league, as for /tournament
+    league_offsets: Option<HashMap<String, f64>>,
+
+    /// ELO modification factor (default: 20)
+    mod_factor: Option<f64>,
+
+    /// Home advantage in ELO points of home-and-away group matches and
+    /// knockout rounds not on neutral ground (default: 65)
+    home_advantage: Option<f64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DrawTeamRequest {
+    team: String,
+    elo: f64,
+    /// Seeding pot, 1 for the top seeds
+    pot: usize,
+    /// National association; teams sharing one are drawn into different
+    /// groups
+    association: String,
+    /// League the team plays in, a key of `league_offsets` (optional)
+    league: Option<String>,
+}
+
+/// Draw groups from seeding pots with country protection, play the group
+/// stage and knockout rounds, and report each team's chance of reaching
+/// every round over many draws.
+#[utoipa::path(
+    post,
+    path = "/tournament/draw",
+    tag = "simulation",
+    request_body = DrawTournamentRequest,
+    responses(
+        (status = 200, description = "Round-by-round advancement probabilities per team", body = TournamentResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request, bracket or impossible draw", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_drawn_tournament(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<DrawTournamentRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let config = state.config.current();
+    let limits = &config.limits;
+    if payload.teams.len() > limits.max_teams {
+        return Err(ApiError::limit(
+            "teams",
+            format!(
+                "{} teams exceed the limit of {}",
+                payload.teams.len(),
+                limits.max_teams
+            ),
+        ));
+    }
+
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, team) in payload.teams.iter().enumerate() {
+        if !team.elo.is_finite() {
+            errors.push(FieldError {
+                field: format!("teams[{}].elo", i),
+                message: format!("teams[{}].elo must be a finite number", i),
+            });
+        }
+        if team.pot == 0 {
+            errors.push(FieldError {
+                field: format!("teams[{}].pot", i),
+                message: format!("teams[{}].pot must be at least 1", i),
+            });
+        }
+        if !seen.insert(team.team.as_str()) {
+            errors.push(FieldError {
+                field: format!("teams[{}].team", i),
+                message: format!("team '{}' is entered twice", team.team),
+            });
+        }
+    }
+    let leagues: Vec<Option<&str>> = payload.teams.iter().map(|t| t.league.as_deref()).collect();
+    let offsets = payload.league_offsets.unwrap_or_default();
+    check_league_offsets(&leagues, &offsets, &mut errors);
+    let iterations = check_iterations(payload.iterations, &config, &mut errors);
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidFields(errors));
+    }
+    let elos: Vec<f64> = payload.teams.iter().map(|t| t.elo).collect();
+    let elos = apply_league_offsets(&elos, &leagues, &offsets);
+
+    let names: Vec<String> = (0..payload.groups).map(group_name).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let bracket = resolve_bracket(payload.bracket, payload.best_thirds, &names)?;
+    let params = knockout_params(payload.mod_factor, payload.home_advantage, &config);
+    let tournament = DrawnTournament {
+        draw: Draw {
+            teams: payload
+                .teams
+                .into_iter()
+                .zip(elos)
+                .map(|(t, elo)| DrawTeam {
+                    name: t.team,
+                    elo,
+                    pot: t.pot - 1,
+                    association: t.association,
+                })
+                .collect(),
+            groups: payload.groups,
+        },
+        home_and_away: payload.home_and_away,
+        group_params: SimulationParams {
+            home_advantage: if payload.home_and_away {
+                params.home_advantage
+            } else {
+                0.0
+            },
+            ..params.clone()
+        },
+        best_thirds: payload.best_thirds,
+        bracket,
+        params,
+        neutral: payload.neutral.unwrap_or(true),
+        two_legs: payload.two_legs,
+    };
+
+    // The check draws once, a backtracking search, so it stays off the
+    // async workers too.
+    crate::metrics::set_iterations(iterations);
+    let result = telemetry::spawn_blocking(move || {
+        tournament.check()?;
+        Ok::<_, String>(run_drawn_tournament(&tournament, iterations))
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .map_err(ApiError::InvalidRequest)?;
+
+    Ok(format.render(TournamentResponse {
+        result,
+        simulations_performed: iterations,
+        time_ms: start.elapsed().as_millis(),
+    }))
+}
+
+/// The bracket of `places`, referring to groups by name, or winners
+/// against runners-up when none are given.
+fn resolve_bracket(
+    places: Option<Vec<BracketPlace>>,
+    best_thirds: usize,
+    groups: &[&str],
+) -> Result<Vec<Slot>, ApiError> {
+    let Some(places) = places else {
+        if best_thirds > 0 {
+            return Err(ApiError::field(
+                "bracket",
+                "bracket is required when best thirds go through",
+            ));
+        }
+        return Ok(GroupTournament::winners_and_runners_up(groups.len()));
+    };
+    places
+        .into_iter()
+        .enumerate()
+        .map(|(i, place)| match place {
+            BracketPlace::Group { group, position } => groups
+                .iter()
+                .position(|&g| g == group)
+                .map(|group| Slot::Group { group, position })
+                .ok_or_else(|| {
+                    ApiError::field(
+                        format!("bracket[{}].group", i),
+                        format!("bracket[{}]: no group '{}'", i, group),
+                    )
+                }),
+            BracketPlace::BestThird { best_third } => Ok(Slot::BestThird(best_third)),
+        })
+        .collect()
+}
+
+/// Record finite offsets for every league of `leagues` in `errors` unless
+/// `offsets` has them.
+fn check_league_offsets(
+    leagues: &[Option<&str>],
+    offsets: &HashMap<String, f64>,
+    errors: &mut Vec<FieldError>,
+) {
+    let mut names: Vec<&String> = offsets.keys().collect();
+    names.sort();
+    for name in names {
+        if !offsets[name].is_finite() {
+            errors.push(FieldError {
+                field: format!("league_offsets.{}", name),
+                message: format!("league_offsets.{} must be a finite number", name),
+            });
+        }
+    }
+    for (i, league) in leagues.iter().enumerate() {
+        if let Some(league) = league.filter(|l| !offsets.contains_key(*l)) {
+            errors.push(FieldError {
+                field: format!("teams[{}].league", i),
+                message: format!("league '{}' has no entry in league_offsets", league),
+            });
+        }
+    }
+}
+
+/// The requested iterations or the configured default, recording an
+/// out-of-range count in `errors`.
+fn check_iterations(
+    iterations: Option<usize>,
+    config: &RuntimeConfig,
+    errors: &mut Vec<FieldError>,
+) -> usize {
+    let iterations = iterations.unwrap_or(config.simulation.iterations);
+    if iterations == 0 || iterations > config.limits.max_iterations {
+        errors.push(FieldError {
+            field: "iterations".into(),
+            message: format!(
+                "iterations must be between 1 and {}, got {}",
+                config.limits.max_iterations, iterations
+            ),
+        });
+    }
+    iterations
+}
+
+fn knockout_params(
+    mod_factor: Option<f64>,
+    home_advantage: Option<f64>,
+    config: &RuntimeConfig,
+) -> SimulationParams {
+    SimulationParams {
+        mod_factor: mod_factor.unwrap_or(config.simulation.mod_factor),
+        home_advantage: home_advantage.unwrap_or(config.simulation.home_advantage),
+        tore_slope: config.simulation.tore_slope,
+        tore_intercept: config.simulation.tore_intercept,
+        ..SimulationParams::default()
+    }
+}
@@ -0,0 +1,74 @@
+// Elo trajectories over the rest of the season: mean and spread of every
+// team's rating after each of its remaining matches, for rating-evolution
+// charts.
+
+use crate::error::{ApiError, ApiJson, ErrorBody};
+use crate::handlers::{SimulateRequest, SimulationJob};
+use crate::reports::{Negotiated, Tabular};
+use crate::telemetry;
+use crate::AppState;
+use axum::{extract::State, response::Response};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::{run_elo_trajectories, TeamEloTrajectory};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct EloTrajectoryResponse {
+    /// One trajectory per team, in input order
+    pub teams: Vec<TeamEloTrajectory>,
+    pub simulations_performed: usize,
+    pub time_ms: u128,
+}
+
+/// One row per team and remaining match.
+impl Tabular for EloTrajectoryResponse {
+    fn table(&self) -> Report {
+        let mut report = Report::new(["team", "match_number", "mean", "std_dev"]);
+        for team in &self.teams {
+            for point in &team.points {
+                report.push(vec![
+                    Cell::Text(team.team_name.clone()),
+                    Cell::Int(point.match_number as i64),
+                    Cell::Number(point.mean),
+                    Cell::Number(point.std_dev),
+                ]);
+            }
+        }
+        report
+    }
+}
+
+/// Simulate the remaining season and summarize each team's Elo after every
+/// one of its matches.
+#[utoipa::path(
+    post,
+    path = "/simulate/elo-trajectory",
+    tag = "simulation",
+    request_body = SimulateRequest,
+    responses(
+        (status = 200, description = "Mean and standard deviation of each team's Elo per remaining match", body = EloTrajectoryResponse),
+        (status = 400, description = "Malformed JSON", body = ErrorBody),
+        (status = 422, description = "Invalid request", body = ErrorBody),
+        (status = 413, description = "Exceeds the configured limits", body = ErrorBody),
+        (status = 429, description = "Simulation queue full", body = ErrorBody),
+        (status = 503, description = "Timed out", body = ErrorBody)
+    )
+)]
+pub async fn simulate_elo_trajectory(
+    State(state): State<AppState>,
+    format: Negotiated,
+    ApiJson(payload): ApiJson<SimulateRequest>,
+) -> Result<Response, ApiError> {
+    let start = std::time::Instant::now();
+    let job = SimulationJob::from_request(payload, &state.config.current())?;
+    let response = telemetry::spawn_blocking(move || EloTrajectoryResponse {
+        teams: run_elo_trajectories(&job.season, &job.params, &job.team_names),
+        simulations_performed: job.params.iterations,
+        time_ms: start.elapsed().as_millis(),
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(format.render(response))
+}
@@ -0,0 +1,183 @@
+// Webhook delivery for fire-and-forget simulations.
+// A /simulate request with a callback_url is answered with 202 right away;
+// the finished SimulateResponse is then POSTed to the callback URL, retrying
+// with exponential backoff on connection errors and non-2xx answers.
+//
+// Callback hosts are resolved before every attempt and refused if any of
+// their addresses is loopback, private, link-local or otherwise not public,
+// so a request cannot make the service POST into its own network (cloud
+// metadata endpoints, admin ports). The connection is pinned to the checked
+// addresses and redirects are not followed. Hosts listed in
+// WEBHOOK_ALLOWED_HOSTS skip the address check; when that list is set, no
+// other host is accepted.
+
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// Default number of delivery attempts per callback.
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Default delay before the first retry; doubled after every failure.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Per-attempt request timeout.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP client plus retry policy for callback deliveries.
+#[derive(Clone)]
+pub struct WebhookSender {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    allowed_hosts: Vec<String>,
+}
+
+impl WebhookSender {
+    pub fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            allowed_hosts: Vec::new(),
+        }
+    }
+
+    /// Accept only these hosts, without checking their addresses.
+    pub fn with_allowed_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_hosts = hosts
+            .into_iter()
+            .map(|h| h.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Policy from `WEBHOOK_MAX_ATTEMPTS`, `WEBHOOK_BACKOFF_MS` and
+    /// `WEBHOOK_ALLOWED_HOSTS` (comma-separated), falling back to 5 attempts
+    /// starting at one second and any public host.
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        let initial_backoff = std::env::var("WEBHOOK_BACKOFF_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_INITIAL_BACKOFF);
+        let allowed_hosts = std::env::var("WEBHOOK_ALLOWED_HOSTS").unwrap_or_default();
+        Self::new(max_attempts, initial_backoff).with_allowed_hosts(
+            allowed_hosts
+                .split(',')
+                .map(str::trim)
+                .filter(|h| !h.is_empty()),
+        )
+    }
+
+    /// Check that `url` is an absolute http(s) URL whose host may be
+    /// called, and return the addresses to connect to.
+    pub async fn check_url(&self, url: &str) -> Result<(String, Vec<SocketAddr>), String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("callback_url: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "callback_url must use http or https, got '{}'",
+                parsed.scheme()
+            ));
+        }
+        let host = match parsed.host_str() {
+            // IPv6 literals come bracketed.
+            Some(host) => host.trim_matches(['[', ']']).to_ascii_lowercase(),
+            None => return Err("callback_url has no host".to_string()),
+        };
+        let port = parsed.port_or_known_default().unwrap_or(80);
+
+        let allowed = self.allowed_hosts.contains(&host);
+        if !self.allowed_hosts.is_empty() && !allowed {
+            return Err(format!("callback_url host '{}' is not allowed", host));
+        }
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|e| format!("callback_url host '{}' does not resolve: {}", host, e))?
+            .collect();
+        if addrs.is_empty() {
+            return Err(format!("callback_url host '{}' does not resolve", host));
+        }
+        if !allowed {
+            if let Some(addr) = addrs.iter().find(|a| !is_public(a.ip())) {
+                return Err(format!(
+                    "callback_url host '{}' resolves to non-public address {}",
+                    host,
+                    addr.ip()
+                ));
+            }
+        }
+        Ok((host, addrs))
+    }
+
+    /// POST `body` to `url` until it is accepted (2xx) or the attempts are
+    /// used up. Returns the number of attempts on success, or the last error.
+    pub async fn deliver<T: Serialize>(&self, url: &str, body: &T) -> Result<u32, String> {
+        let mut backoff = self.initial_backoff;
+        let mut last_error = String::new();
+
+        for attempt in 1..=self.max_attempts {
+            // Re-resolved per attempt, so a DNS answer that changes after
+            // validation is checked again before anything is sent.
+            let (host, addrs) = self.check_url(url).await?;
+            let client = reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .resolve_to_addrs(&host, &addrs)
+                .build()
+                .map_err(|e| e.to_string())?;
+            let sent = client
+                .post(url)
+                .timeout(ATTEMPT_TIMEOUT)
+                .json(body)
+                .send()
+                .await;
+            match sent {
+                Ok(response) if response.status().is_success() => return Ok(attempt),
+                Ok(response) => last_error = format!("callback answered {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+            if attempt < self.max_attempts {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+/// Whether `ip` is a globally routable unicast address.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                // 0.0.0.0/8, carrier-grade NAT 100.64.0.0/10, 240.0.0.0/4.
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local())
+            }
+        },
+    }
+}
@@ -0,0 +1,674 @@
+// Subcommands of the command-line tool. `simulate`, `table` and `elo` read
+// the same request body as POST /simulate and validate it the same way, so
+// a file that works against the API works here and vice versa. With
+// `--teams` they read the R pipeline's CSV files instead, converted the way
+// POST /import/csv converts them. `fixtures` writes such a body, or such a
+// fixtures CSV, for a league whose schedule is not out yet.
+// `build-history` and `backtest` work on played seasons: the bundled ones,
+// a dataset directory, or a history file built from one.
+
+use crate::{CliError, Format};
+use league_sim_api as api;
+use league_sim_api::config::{ConfigHandle, RuntimeConfig};
+use league_sim_api::fixtures::{generate_fixtures, FixtureTeam, GenerateFixturesRequest};
+use league_sim_api::handlers::{engine_inputs, simulate_request, EngineInputs, SimulateRequest};
+use league_sim_api::import::CsvImportResponse;
+use league_sim_core::datasets::{self, DatasetError, HistoricalData};
+use league_sim_core::export::write_samples_parquet;
+use league_sim_core::history::{HistoryError, HistoryStore};
+use league_sim_core::ingestion::{load_fixtures, IngestionError, TeamList};
+use league_sim_core::reports::{Cell, Report};
+use league_sim_core::{
+    apply_results, backtest, calculate_table_with, elos_from_positions, run_season_samples,
+    BacktestScore, PositionElo, SimulationParams,
+};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Run the API server until it is stopped.
+pub fn serve(port: Option<u16>, config: Option<PathBuf>) -> Result<(), CliError> {
+    // Flags become the environment overrides the server reads at startup;
+    // set before the runtime starts any threads.
+    if let Some(port) = port {
+        std::env::set_var("PORT", port.to_string());
+    }
+    if let Some(config) = config {
+        std::env::set_var("LEAGUE_SIM_CONFIG", config);
+    }
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| CliError::Io(e.to_string()))?;
+    runtime.block_on(async {
+        // JSON logs; flushes exported spans on shutdown.
+        let _telemetry = api::telemetry::init();
+
+        let state = api::AppState::from_env();
+        // Reload LEAGUE_SIM_CONFIG on SIGHUP or when the file changes.
+        state.config.watch();
+        let server = state.config.current().server.clone();
+        let addr = SocketAddr::from(([0, 0, 0, 0], server.port));
+        let app = api::create_router_with_state(state);
+
+        if server.tls.enabled() {
+            tracing::info!(
+                %addr,
+                version = env!("CARGO_PKG_VERSION"),
+                docs = "/openapi.json",
+                "server listening over https"
+            );
+            return api::tls::serve(addr, app, &server.tls)
+                .await
+                .map_err(|e| CliError::Io(format!("cannot serve https on {}: {}", addr, e)));
+        }
+
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| CliError::Io(format!("cannot listen on {}: {}", addr, e)))?;
+        tracing::info!(
+            %addr,
+            version = env!("CARGO_PKG_VERSION"),
+            docs = "/openapi.json",
+            "server listening"
+        );
+
+        // Peer addresses key the per-client rate limits.
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .map_err(|e| CliError::Io(e.to_string()))
+    })
+}
+
+/// Simulate the request in `file` and print the position probabilities.
+pub fn simulate(
+    file: &Path,
+    teams: Option<&Path>,
+    iterations: Option<usize>,
+    config: Option<&Path>,
+    format: Format,
+) -> Result<(), CliError> {
+    let mut body = read_body(file, teams)?;
+    if let Some(iterations) = iterations {
+        body["iterations"] = iterations.into();
+    }
+    let request = parse_request(body)?;
+    let config = load_config(config)?;
+    let response =
+        simulate_request(request, &config).map_err(|e| CliError::Input(e.to_string()))?;
+
+    match format {
+        Format::Json => print_json(&response),
+        Format::Table => {
+            print_probabilities(response.team_names(), response.probability_matrix());
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => print_report(
+            &Report::probabilities(response.team_names(), response.probability_matrix()),
+            format,
+        ),
+    }
+}
+
+/// Simulate the request in `file` and write every iteration's final table
+/// to `output` as Parquet.
+pub fn samples(
+    file: &Path,
+    teams: Option<&Path>,
+    iterations: Option<usize>,
+    config: Option<&Path>,
+    output: &Path,
+) -> Result<(), CliError> {
+    let mut body = read_body(file, teams)?;
+    if let Some(iterations) = iterations {
+        body["iterations"] = iterations.into();
+    }
+    let config = load_config(config)?;
+    let inputs =
+        engine_inputs(parse_request(body)?, &config).map_err(|e| CliError::Input(e.to_string()))?;
+    let samples = run_season_samples(&inputs.season, &inputs.params);
+
+    let io_error = |e: &dyn std::fmt::Display| CliError::Io(format!("{}: {}", output.display(), e));
+    let out: Box<dyn Write + Send> = if output == Path::new("-") {
+        Box::new(std::io::stdout())
+    } else {
+        let file = std::fs::File::create(output).map_err(|e| io_error(&e))?;
+        Box::new(std::io::BufWriter::new(file))
+    };
+    let mut out =
+        write_samples_parquet(&samples, &inputs.team_names, out).map_err(|e| io_error(&e))?;
+    out.flush().map_err(|e| io_error(&e))
+}
+
+#[derive(Serialize)]
+struct TableRow<'a> {
+    position: usize,
+    team: &'a str,
+    played: i32,
+    won: i32,
+    drawn: i32,
+    lost: i32,
+    goals_for: i32,
+    goals_against: i32,
+    goal_difference: i32,
+    points: i32,
+}
+
+/// Print the standings after the played matches in `file`.
+pub fn table(file: &Path, teams: Option<&Path>, format: Format) -> Result<(), CliError> {
+    let inputs = read_inputs(file, teams)?;
+    let params = &inputs.params;
+    let table = calculate_table_with(&inputs.season.matches, inputs.season.number_teams, params);
+    let rows: Vec<TableRow> = table
+        .standings
+        .iter()
+        .map(|s| TableRow {
+            position: s.position,
+            team: &inputs.team_names[s.team_id],
+            played: s.played,
+            won: s.won,
+            drawn: s.drawn,
+            lost: s.lost,
+            goals_for: s.goals_for,
+            goals_against: s.goals_against,
+            goal_difference: s.goal_difference,
+            points: s.points,
+        })
+        .collect();
+
+    match format {
+        Format::Json => print_json(&rows),
+        Format::Table => {
+            let width = name_width(&inputs.team_names);
+            println!(
+                "{:>3}  {:width$}  {:>3} {:>3} {:>3} {:>3}  {:>7} {:>4} {:>4}",
+                "#", "Team", "P", "W", "D", "L", "Goals", "Diff", "Pts"
+            );
+            for row in &rows {
+                println!(
+                    "{:>3}  {:width$}  {:>3} {:>3} {:>3} {:>3}  {:>3}:{:<3} {:>+4} {:>4}",
+                    row.position,
+                    row.team,
+                    row.played,
+                    row.won,
+                    row.drawn,
+                    row.lost,
+                    row.goals_for,
+                    row.goals_against,
+                    row.goal_difference,
+                    row.points
+                );
+            }
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => {
+            print_report(&Report::standings(&table, &inputs.team_names), format)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EloRow<'a> {
+    team: &'a str,
+    elo_before: f64,
+    elo_after: f64,
+}
+
+/// Print the ratings after the played matches in `file`.
+pub fn elo(file: &Path, teams: Option<&Path>, format: Format) -> Result<(), CliError> {
+    let inputs = read_inputs(file, teams)?;
+    let updated = apply_results(
+        &inputs.season.team_elos,
+        &inputs.season.matches,
+        inputs.params.mod_factor,
+        inputs.params.home_advantage,
+    );
+    let rows: Vec<EloRow> = inputs
+        .team_names
+        .iter()
+        .zip(&inputs.season.team_elos)
+        .zip(&updated)
+        .map(|((team, &elo_before), &elo_after)| EloRow {
+            team,
+            elo_before,
+            elo_after,
+        })
+        .collect();
+
+    match format {
+        Format::Json => print_json(&rows),
+        Format::Table => {
+            let width = name_width(&inputs.team_names);
+            println!(
+                "{:width$}  {:>8}  {:>8}  {:>7}",
+                "Team", "Before", "After", "Change"
+            );
+            for row in &rows {
+                println!(
+                    "{:width$}  {:>8.1}  {:>8.1}  {:>+7.1}",
+                    row.team,
+                    row.elo_before,
+                    row.elo_after,
+                    row.elo_after - row.elo_before
+                );
+            }
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => {
+            let mut report = Report::new(["team", "elo_before", "elo_after", "change"]);
+            for row in &rows {
+                report.push(vec![
+                    Cell::Text(row.team.to_string()),
+                    Cell::Number(row.elo_before),
+                    Cell::Number(row.elo_after),
+                    Cell::Number(row.elo_after - row.elo_before),
+                ]);
+            }
+            print_report(&report, format)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StartEloRow<'a> {
+    team: &'a str,
+    /// Final position last season; None for joining teams
+    position: Option<usize>,
+    elo: f64,
+}
+
+/// Print the kickoff ratings of the next season: the final table of `file`
+/// without the `leaving` teams, mapped to ratings by `formula`, followed by
+/// the `joining` teams (`NAME` or `NAME=ELO`).
+pub fn start_elo(
+    file: &Path,
+    teams: Option<&Path>,
+    leaving: &[String],
+    joining: &[String],
+    formula: PositionElo,
+    format: Format,
+) -> Result<(), CliError> {
+    let inputs = read_inputs(file, teams)?;
+    let number_teams = inputs.season.number_teams;
+    let table = calculate_table_with(&inputs.season.matches, number_teams, &inputs.params);
+
+    let mut leaves = vec![false; number_teams];
+    for team in leaving {
+        let index = inputs
+            .team_names
+            .iter()
+            .position(|name| name == team)
+            .ok_or_else(|| {
+                CliError::Input(format!("leaving team '{}' is not in the table", team))
+            })?;
+        leaves[index] = true;
+    }
+    let mut joiners = Vec::new();
+    for spec in joining {
+        let (team, elo) = match spec.rsplit_once('=') {
+            Some((team, elo)) => {
+                let elo: f64 = elo
+                    .parse()
+                    .ok()
+                    .filter(|elo: &f64| elo.is_finite())
+                    .ok_or_else(|| {
+                        CliError::Input(format!(
+                            "joining team '{}': invalid rating '{}'",
+                            team, elo
+                        ))
+                    })?;
+                (team, Some(elo))
+            }
+            None => (spec.as_str(), None),
+        };
+        let stays = inputs
+            .team_names
+            .iter()
+            .zip(&leaves)
+            .any(|(name, &leaves)| name == team && !leaves);
+        if stays || joiners.iter().any(|&(joined, _)| joined == team) {
+            return Err(CliError::Input(format!(
+                "joining team '{}' is already in the league",
+                team
+            )));
+        }
+        joiners.push((team, elo));
+    }
+
+    let returning: Vec<_> = table
+        .standings
+        .iter()
+        .filter(|s| !leaves[s.team_id])
+        .collect();
+    let positions: Vec<usize> = returning.iter().map(|s| s.position).collect();
+    let promoted: Vec<Option<f64>> = joiners.iter().map(|&(_, elo)| elo).collect();
+    let elos = elos_from_positions(&positions, number_teams, &promoted, &formula);
+    let rows: Vec<StartEloRow> = returning
+        .iter()
+        .map(|s| (inputs.team_names[s.team_id].as_str(), Some(s.position)))
+        .chain(joiners.iter().map(|&(team, _)| (team, None)))
+        .zip(elos)
+        .map(|((team, position), elo)| StartEloRow {
+            team,
+            position,
+            elo,
+        })
+        .collect();
+
+    match format {
+        Format::Json => print_json(&rows),
+        Format::Table => {
+            let names: Vec<String> = rows.iter().map(|r| r.team.to_string()).collect();
+            let width = name_width(&names);
+            println!("{:width$}  {:>4}  {:>8}", "Team", "Last", "Elo");
+            for row in &rows {
+                let position = row.position.map_or("new".to_string(), |p| p.to_string());
+                println!("{:width$}  {:>4}  {:>8.1}", row.team, position, row.elo);
+            }
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => {
+            let mut report = Report::new(["team", "last_position", "elo"]);
+            for row in &rows {
+                report.push(vec![
+                    Cell::Text(row.team.to_string()),
+                    row.position
+                        .map_or(Cell::Text(String::new()), |p| Cell::Int(p as i64)),
+                    Cell::Number(row.elo),
+                ]);
+            }
+            print_report(&report, format)
+        }
+    }
+}
+
+/// Generate a double round robin for the team list in `file` and print it
+/// as a request body, or as a fixtures CSV the other commands read with
+/// `--teams`.
+pub fn fixtures(
+    file: &Path,
+    team_list: bool,
+    seed: Option<u64>,
+    rounds: Option<usize>,
+    format: Format,
+) -> Result<(), CliError> {
+    let text = read_text(file)?;
+    let mut request: GenerateFixturesRequest = if team_list {
+        let teams = TeamList::from_reader(text.as_bytes()).map_err(|e| match e {
+            IngestionError::Io(e) => CliError::Io(format!("{}: {}", file.display(), e)),
+            e => CliError::Input(format!("{}: {}", file.display(), e)),
+        })?;
+        GenerateFixturesRequest {
+            teams: teams
+                .teams()
+                .iter()
+                .map(|t| FixtureTeam {
+                    name: t.short_name.clone(),
+                    elo: t.initial_elo,
+                })
+                .collect(),
+            seed: None,
+            rounds: None,
+        }
+    } else {
+        serde_json::from_str(&text)
+            .map_err(|e| CliError::Input(format!("{}: {}", file.display(), e)))?
+    };
+    if seed.is_some() {
+        request.seed = seed;
+    }
+    if rounds.is_some() {
+        request.rounds = rounds;
+    }
+    let config = load_config(None)?;
+    let fixtures =
+        generate_fixtures(request, &config.limits).map_err(|e| CliError::Input(e.to_string()))?;
+
+    let name = |row: &[Option<i32>; 4], side: usize| {
+        fixtures.team_names[row[side].unwrap_or(1) as usize - 1].as_str()
+    };
+    match format {
+        Format::Json => print_json(&fixtures),
+        Format::Table => {
+            let width = name_width(&fixtures.team_names);
+            println!("{:>8}  {:width$}  Away", "Matchday", "Home");
+            for (row, matchday) in fixtures.schedule.iter().zip(&fixtures.matchdays) {
+                println!("{:>8}  {:width$}  {}", matchday, name(row, 0), name(row, 1));
+            }
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => {
+            let mut report = Report::new(["home", "away", "goals_home", "goals_away", "matchday"]);
+            for (row, &matchday) in fixtures.schedule.iter().zip(&fixtures.matchdays) {
+                report.push(vec![
+                    Cell::Text(name(row, 0).to_string()),
+                    Cell::Text(name(row, 1).to_string()),
+                    Cell::Text(String::new()),
+                    Cell::Text(String::new()),
+                    Cell::Int(matchday.into()),
+                ]);
+            }
+            print_report(&report, format)
+        }
+    }
+}
+
+/// Write the seasons of `data_dir`, or the bundled ones, to a history file.
+pub fn build_history(data_dir: Option<&Path>, output: &Path) -> Result<(), CliError> {
+    let written = match data_dir {
+        Some(dir) => datasets::write_history(dir, output),
+        None => HistoricalData::bundled().write_history(output),
+    };
+    written.map_err(|e| dataset_error(data_dir.unwrap_or(output), e))?;
+    let store = HistoryStore::open(output).map_err(|e| history_error(output, e))?;
+    eprintln!(
+        "wrote {} results of {} league seasons to {}",
+        store.len(),
+        store.seasons().len(),
+        output.display()
+    );
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct BacktestRow {
+    season: u16,
+    #[serde(flatten)]
+    score: BacktestScore,
+}
+
+/// Score the default model's pre-match forecasts on every season of
+/// `league`, from a history file or from the bundled seasons and
+/// `data_dir`.
+pub fn backtest_league(
+    league: &str,
+    history: Option<&Path>,
+    data_dir: Option<&Path>,
+    format: Format,
+) -> Result<(), CliError> {
+    let (years, seasons): (Vec<u16>, Vec<_>) = match history {
+        Some(path) => {
+            let store = HistoryStore::open(path).map_err(|e| history_error(path, e))?;
+            let years = store
+                .league_id(league)
+                .map(|id| {
+                    store
+                        .seasons()
+                        .iter()
+                        .filter(|s| s.league == id)
+                        .map(|s| s.season)
+                        .collect()
+                })
+                .unwrap_or_default();
+            (years, store.backtest_seasons(league))
+        }
+        None => {
+            let data = load_dataset(data_dir)?;
+            let years = data.league(league).map(|s| s.season).collect();
+            (years, data.backtest_seasons(league))
+        }
+    };
+    if seasons.is_empty() {
+        return Err(CliError::Input(format!(
+            "no season of league '{}' in the dataset",
+            league
+        )));
+    }
+
+    let report = backtest(&seasons, &SimulationParams::default());
+    let rows: Vec<BacktestRow> = years
+        .into_iter()
+        .zip(&report.seasons)
+        .map(|(season, &score)| BacktestRow { season, score })
+        .collect();
+    match format {
+        Format::Json => print_json(&report),
+        Format::Table => {
+            println!(
+                "{:>7}  {:>7}  {:>7}  {:>8}",
+                "Season", "Matches", "Brier", "Log loss"
+            );
+            for row in &rows {
+                println!(
+                    "{:>7}  {:>7}  {:>7.4}  {:>8.4}",
+                    row.season, row.score.matches, row.score.brier_score, row.score.log_loss
+                );
+            }
+            println!(
+                "{:>7}  {:>7}  {:>7.4}  {:>8.4}",
+                "All", report.overall.matches, report.overall.brier_score, report.overall.log_loss
+            );
+            Ok(())
+        }
+        Format::Csv | Format::Markdown => {
+            let mut report = Report::new(["season", "matches", "brier_score", "log_loss"]);
+            for row in &rows {
+                report.push(vec![
+                    Cell::Int(row.season.into()),
+                    Cell::Int(row.score.matches as i64),
+                    Cell::Number(row.score.brier_score),
+                    Cell::Number(row.score.log_loss),
+                ]);
+            }
+            print_report(&report, format)
+        }
+    }
+}
+
+/// The bundled seasons, with those of `data_dir` added.
+pub fn load_dataset(data_dir: Option<&Path>) -> Result<HistoricalData, CliError> {
+    let mut data = HistoricalData::bundled();
+    if let Some(dir) = data_dir {
+        data.extend(HistoricalData::from_dir(dir).map_err(|e| dataset_error(dir, e))?);
+    }
+    Ok(data)
+}
+
+/// The seasons of a history file.
+pub fn load_history(path: &Path) -> Result<HistoricalData, CliError> {
+    let store = HistoryStore::open(path).map_err(|e| history_error(path, e))?;
+    Ok(HistoricalData::from_history(&store))
+}
+
+fn dataset_error(path: &Path, e: DatasetError) -> CliError {
+    match e {
+        DatasetError::Io(e) => CliError::Io(format!("{}: {}", path.display(), e)),
+        DatasetError::History(e) => history_error(path, e),
+        e => CliError::Input(e.to_string()),
+    }
+}
+
+fn history_error(path: &Path, e: HistoryError) -> CliError {
+    match e {
+        HistoryError::Io(e) => CliError::Io(format!("{}: {}", path.display(), e)),
+        e => CliError::Input(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Position probabilities as a text table, one row per team.
+pub fn print_probabilities(team_names: &[String], matrix: &[Vec<f64>]) {
+    let width = name_width(team_names);
+    print!("{:width$} |", "Team");
+    for position in 1..=matrix.first().map_or(0, Vec::len) {
+        print!(" {:>6} |", position);
+    }
+    println!();
+    for (name, row) in team_names.iter().zip(matrix) {
+        print!("{:width$} |", name);
+        for p in row {
+            print!(" {:>5.1}% |", p * 100.0);
+        }
+        println!();
+    }
+}
+
+fn name_width(team_names: &[String]) -> usize {
+    team_names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4)
+}
+
+fn read_text(file: &Path) -> Result<String, CliError> {
+    let mut text = String::new();
+    let read = if file == Path::new("-") {
+        std::io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        std::fs::read_to_string(file).map(|t| text = t)
+    };
+    read.map_err(|e| CliError::Io(format!("{}: {}", file.display(), e)))?;
+    Ok(text)
+}
+
+/// Request body from a JSON file, or from a fixtures CSV and a team list.
+fn read_body(file: &Path, teams: Option<&Path>) -> Result<serde_json::Value, CliError> {
+    let text = read_text(file)?;
+    let Some(teams) = teams else {
+        return serde_json::from_str(&text)
+            .map_err(|e| CliError::Input(format!("{}: {}", file.display(), e)));
+    };
+    let csv_error = |path: &Path, e: IngestionError| match e {
+        IngestionError::Io(e) => CliError::Io(format!("{}: {}", path.display(), e)),
+        e => CliError::Input(format!("{}: {}", path.display(), e)),
+    };
+    let team_list = TeamList::from_path(teams).map_err(|e| csv_error(teams, e))?;
+    let league = load_fixtures(&team_list, text.as_bytes()).map_err(|e| csv_error(file, e))?;
+    serde_json::to_value(CsvImportResponse::from(league)).map_err(|e| CliError::Io(e.to_string()))
+}
+
+fn parse_request(body: serde_json::Value) -> Result<SimulateRequest, CliError> {
+    serde_json::from_value(body).map_err(|e| CliError::Input(e.to_string()))
+}
+
+fn read_inputs(file: &Path, teams: Option<&Path>) -> Result<EngineInputs, CliError> {
+    let request = parse_request(read_body(file, teams)?)?;
+    let config = load_config(None)?;
+    engine_inputs(request, &config).map_err(|e| CliError::Input(e.to_string()))
+}
+
+/// The file at `path`, or the defaults; environment overrides apply.
+fn load_config(path: Option<&Path>) -> Result<RuntimeConfig, CliError> {
+    let config = match path {
+        Some(path) => ConfigHandle::load(path).map(|handle| (*handle.current()).clone()),
+        None => RuntimeConfig::from_env(),
+    };
+    config.map_err(|e| CliError::Input(format!("configuration: {}", e)))
+}
+
+fn print_report(report: &Report, format: Format) -> Result<(), CliError> {
+    match format {
+        Format::Csv => print!("{}", report.to_csv()),
+        _ => print!("{}", report.to_markdown()),
+    }
+    Ok(())
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), CliError> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| CliError::Io(e.to_string()))?;
+    println!("{}", text);
+    Ok(())
+}
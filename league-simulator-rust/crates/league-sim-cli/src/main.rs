@@ -0,0 +1,383 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use league_sim_core::*;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+mod commands;
+
+/// Football league Monte Carlo simulator: REST API server and offline tools.
+///
+/// Input files hold a /simulate request body (`schedule`, `elo_values`,
+/// optional `team_names`, parameters and adjustments), or with `--teams` a
+/// fixtures CSV; `-` reads stdin.
+#[derive(Parser)]
+#[command(name = "league-simulator-rust", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Start the API server (what running without a subcommand does)
+    #[arg(long, hide = true)]
+    api: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the REST API server
+    Serve {
+        /// Port to listen on (overrides PORT and the config file)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Configuration file (overrides LEAGUE_SIM_CONFIG)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Simulate the rest of a season from a request file
+    Simulate {
+        file: PathBuf,
+        /// TeamList CSV; FILE is then a fixtures CSV
+        #[arg(long, value_name = "TEAM_LIST")]
+        teams: Option<PathBuf>,
+        /// Monte Carlo iterations (overrides the file and the config)
+        #[arg(long)]
+        iterations: Option<usize>,
+        /// Configuration file with simulation defaults and limits
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Write every simulated season's final table to a Parquet file
+    Samples {
+        file: PathBuf,
+        /// TeamList CSV; FILE is then a fixtures CSV
+        #[arg(long, value_name = "TEAM_LIST")]
+        teams: Option<PathBuf>,
+        /// Monte Carlo iterations (overrides the file and the config)
+        #[arg(long)]
+        iterations: Option<usize>,
+        /// Configuration file with simulation defaults and limits
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Parquet file to write (`-` for stdout)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Compute the current standings from the played matches
+    Table {
+        file: PathBuf,
+        /// TeamList CSV; FILE is then a fixtures CSV
+        #[arg(long, value_name = "TEAM_LIST")]
+        teams: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Update the Elo ratings with the played matches, in schedule order
+    Elo {
+        file: PathBuf,
+        /// TeamList CSV; FILE is then a fixtures CSV
+        #[arg(long, value_name = "TEAM_LIST")]
+        teams: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Starting ratings for the next season from the final table of FILE
+    StartElo {
+        file: PathBuf,
+        /// TeamList CSV; FILE is then a fixtures CSV
+        #[arg(long, value_name = "TEAM_LIST")]
+        teams: Option<PathBuf>,
+        /// Team leaving the league (relegated or promoted out), repeatable
+        #[arg(long, value_name = "TEAM")]
+        leaving: Vec<String>,
+        /// Team joining the league, with its rating if known, repeatable
+        #[arg(long, value_name = "TEAM[=ELO]")]
+        joining: Vec<String>,
+        /// Rating of the champion
+        #[arg(long, default_value_t = PositionElo::default().top)]
+        top: f64,
+        /// Rating of the last team
+        #[arg(long, default_value_t = PositionElo::default().bottom)]
+        bottom: f64,
+        /// Above 1 widens the gaps at the top of the table, below 1 at the
+        /// bottom
+        #[arg(long, default_value_t = PositionElo::default().exponent)]
+        exponent: f64,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Generate a double round robin or Swiss league phase for a team list,
+    /// as a request body
+    Fixtures {
+        /// Team list as for POST /fixtures/generate
+        file: PathBuf,
+        /// FILE is a TeamList CSV; all of its teams form the league
+        #[arg(long)]
+        team_list: bool,
+        /// Draw the pairings from this seed (overrides the file)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Generate a Swiss league phase of this many rounds instead
+        /// (overrides the file)
+        #[arg(long)]
+        rounds: Option<usize>,
+        /// CSV prints a fixtures file for `--teams`
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Simulate the second half of a played season and print the timing
+    Demo {
+        /// League preset id
+        #[arg(long, default_value = "bundesliga")]
+        league: String,
+        /// Start year of the season (default: the latest one of the league)
+        #[arg(long)]
+        season: Option<u16>,
+        /// Simulate from after this matchday (default: halfway)
+        #[arg(long)]
+        matchday: Option<u32>,
+        /// Dataset directory with more seasons, laid out like the bundled
+        /// ones (`<year>/TeamList.csv`, `<year>/<league>.csv`)
+        #[arg(long)]
+        data_dir: Option<PathBuf>,
+        /// History file from `build-history` to read the seasons from
+        /// instead of the bundled ones and DATA_DIR
+        #[arg(long, conflicts_with = "data_dir")]
+        history: Option<PathBuf>,
+    },
+    /// Convert played seasons into a history file for backtests
+    BuildHistory {
+        /// Dataset directory laid out like the bundled seasons (default: the
+        /// bundled seasons)
+        data_dir: Option<PathBuf>,
+        /// History file to write
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Score the default model's pre-match forecasts on every season of a
+    /// league
+    Backtest {
+        /// League preset id
+        #[arg(long, default_value = "bundesliga")]
+        league: String,
+        /// History file from `build-history` to read the seasons from
+        #[arg(long)]
+        history: Option<PathBuf>,
+        /// Dataset directory with more seasons, added to the bundled ones
+        #[arg(long, conflicts_with = "history")]
+        data_dir: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Aligned text for terminals
+    Table,
+    Json,
+    Csv,
+    /// GitHub-flavoured Markdown table
+    Markdown,
+}
+
+/// Exit codes: 0 success, 1 invalid input, 2 usage error (from clap), 3 I/O
+/// error.
+enum CliError {
+    Input(String),
+    Io(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Input(_) => ExitCode::from(1),
+            Self::Io(_) => ExitCode::from(3),
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::Input(message) | Self::Io(message) => message,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let command = match cli.command {
+        Some(command) => command,
+        None => Command::Serve {
+            port: None,
+            config: None,
+        },
+    };
+
+    let result = match command {
+        Command::Serve { port, config } => commands::serve(port, config),
+        Command::Simulate {
+            file,
+            teams,
+            iterations,
+            config,
+            format,
+        } => commands::simulate(
+            &file,
+            teams.as_deref(),
+            iterations,
+            config.as_deref(),
+            format,
+        ),
+        Command::Samples {
+            file,
+            teams,
+            iterations,
+            config,
+            output,
+        } => commands::samples(
+            &file,
+            teams.as_deref(),
+            iterations,
+            config.as_deref(),
+            &output,
+        ),
+        Command::Table {
+            file,
+            teams,
+            format,
+        } => commands::table(&file, teams.as_deref(), format),
+        Command::Elo {
+            file,
+            teams,
+            format,
+        } => commands::elo(&file, teams.as_deref(), format),
+        Command::StartElo {
+            file,
+            teams,
+            leaving,
+            joining,
+            top,
+            bottom,
+            exponent,
+            format,
+        } => commands::start_elo(
+            &file,
+            teams.as_deref(),
+            &leaving,
+            &joining,
+            PositionElo {
+                top,
+                bottom,
+                exponent,
+            },
+            format,
+        ),
+        Command::Fixtures {
+            file,
+            team_list,
+            seed,
+            rounds,
+            format,
+        } => commands::fixtures(&file, team_list, seed, rounds, format),
+        Command::Demo {
+            league,
+            season,
+            matchday,
+            data_dir,
+            history,
+        } => demo_simulation(
+            &league,
+            season,
+            matchday,
+            data_dir.as_deref(),
+            history.as_deref(),
+        ),
+        Command::BuildHistory { data_dir, output } => {
+            commands::build_history(data_dir.as_deref(), &output)
+        }
+        Command::Backtest {
+            league,
+            history,
+            data_dir,
+            format,
+        } => commands::backtest_league(&league, history.as_deref(), data_dir.as_deref(), format),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e.message());
+            e.exit_code()
+        }
+    }
+}
+
+fn demo_simulation(
+    league: &str,
+    season: Option<u16>,
+    matchday: Option<u32>,
+    data_dir: Option<&Path>,
+    history: Option<&Path>,
+) -> Result<(), CliError> {
+    let data = match history {
+        Some(path) => commands::load_history(path)?,
+        None => commands::load_dataset(data_dir)?,
+    };
+    let played = match season {
+        Some(season) => data.get(league, season),
+        None => data.league(league).last(),
+    }
+    .ok_or_else(|| {
+        let season = season.map(|s| format!(" {}", s)).unwrap_or_default();
+        CliError::Input(format!(
+            "no season{} of league '{}' in the dataset",
+            season, league
+        ))
+    })?;
+    let last = played.matchdays().ok_or_else(|| {
+        CliError::Input(format!(
+            "{} {} has no matchdays to stop at",
+            played.league, played.season
+        ))
+    })?;
+    let matchday = matchday.unwrap_or(last / 2).min(last);
+
+    println!("League Simulator Rust - High Performance Monte Carlo Engine");
+    println!("============================================================");
+    let name = presets::preset(&played.league).map_or(played.league.clone(), |p| p.name);
+    println!(
+        "\n{} {}/{:02} after matchday {} of {}",
+        name,
+        played.season,
+        (played.season + 1) % 100,
+        matchday,
+        last
+    );
+
+    let params = SimulationParams {
+        iterations: 1000,
+        ..Default::default()
+    };
+
+    println!("\nRunning {} Monte Carlo simulations...", params.iterations);
+    let start = Instant::now();
+
+    let result = run_monte_carlo_simulation(
+        &played.after_matchday(matchday),
+        &params,
+        played.data.team_names.clone(),
+    );
+
+    let duration = start.elapsed();
+    println!("Completed in {:.2?}", duration);
+
+    println!("\nProbability Matrix (Team x Position):");
+    commands::print_probabilities(&result.team_names, &result.probability_matrix);
+
+    println!(
+        "\nPerformance: {:.0} simulations/second",
+        params.iterations as f64 / duration.as_secs_f64()
+    );
+    Ok(())
+}
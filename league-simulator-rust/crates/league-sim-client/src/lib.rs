@@ -0,0 +1,176 @@
+// Typed client for the league simulator REST API.
+// Wraps reqwest with the request/response types of the service so other
+// Rust services don't hand-roll JSON against the endpoints.
+
+pub mod types;
+
+pub use types::*;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The server answered with a non-success status. `code` and `field`
+    /// come from the JSON error body; for other bodies `message` is the raw
+    /// text and `code` is empty.
+    #[error("server answered {status}: {message}")]
+    Api {
+        status: u16,
+        code: String,
+        message: String,
+        field: Option<String>,
+    },
+}
+
+/// Client for one simulator instance, e.g. `Client::new("http://localhost:8080")`.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Use a preconfigured reqwest client (timeouts, proxies, TLS roots).
+    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { http, base_url }
+    }
+
+    /// `GET /health`
+    pub async fn health(&self) -> Result<HealthResponse, ClientError> {
+        self.get("/health").await
+    }
+
+    /// `POST /simulate`, waiting for the result.
+    pub async fn simulate(
+        &self,
+        request: &SimulateRequest,
+    ) -> Result<SimulateResponse, ClientError> {
+        self.post("/simulate", request).await
+    }
+
+    /// `POST /simulate` with a callback URL: returns once the server has
+    /// accepted the request; the result is POSTed to `callback_url`.
+    pub async fn simulate_with_callback(
+        &self,
+        request: &SimulateRequest,
+        callback_url: &str,
+    ) -> Result<CallbackAccepted, ClientError> {
+        let mut body = serde_json::to_value(request).expect("request serializes");
+        body["callback_url"] = serde_json::Value::String(callback_url.to_string());
+        self.post("/simulate", &body).await
+    }
+
+    /// `POST /simulate/batch`
+    pub async fn simulate_batch(
+        &self,
+        request: &BatchSimulateRequest,
+    ) -> Result<BatchSimulateResponse, ClientError> {
+        self.post("/simulate/batch", request).await
+    }
+
+    /// `POST /jobs`: start a batch in the background.
+    pub async fn create_job(
+        &self,
+        request: &BatchSimulateRequest,
+    ) -> Result<JobCreated, ClientError> {
+        self.post("/jobs", request).await
+    }
+
+    /// `GET /jobs/{id}`
+    pub async fn job(&self, id: &str) -> Result<JobStatus, ClientError> {
+        self.get(&format!("/jobs/{}", id)).await
+    }
+
+    /// `DELETE /jobs/{id}`: cancel a running job or discard a finished one.
+    pub async fn delete_job(&self, id: &str) -> Result<(), ClientError> {
+        let response = self
+            .http
+            .delete(self.url(&format!("/jobs/{}", id)))
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// `POST /sessions`: open a live matchday session.
+    pub async fn open_session(
+        &self,
+        request: &SimulateRequest,
+    ) -> Result<SessionResponse, ClientError> {
+        self.post("/sessions", request).await
+    }
+
+    /// `GET /sessions/{id}`
+    pub async fn session(&self, id: &str) -> Result<SessionResponse, ClientError> {
+        self.get(&format!("/sessions/{}", id)).await
+    }
+
+    /// `POST /sessions/{id}/events`: push scores and get the recomputed
+    /// probabilities.
+    pub async fn push_events(
+        &self,
+        id: &str,
+        events: &[MatchEvent],
+    ) -> Result<SessionResponse, ClientError> {
+        let body = serde_json::json!({ "events": events });
+        self.post(&format!("/sessions/{}/events", id), &body).await
+    }
+
+    /// `DELETE /sessions/{id}`
+    pub async fn close_session(&self, id: &str) -> Result<(), ClientError> {
+        let response = self
+            .http
+            .delete(self.url(&format!("/sessions/{}", id)))
+            .send()
+            .await?;
+        check(response).await.map(|_| ())
+    }
+
+    /// Requests go to the API version this client's types were written
+    /// for, so newer server versions cannot change their shape.
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1{}", self.base_url, path)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let response = self.http.get(self.url(path)).send().await?;
+        Ok(check(response).await?.json().await?)
+    }
+
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        let response = self.http.post(self.url(path)).json(body).send().await?;
+        Ok(check(response).await?.json().await?)
+    }
+}
+
+/// Turn non-success answers into [`ClientError::Api`].
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let text = response.text().await.unwrap_or_default();
+    let body: ErrorBody = serde_json::from_str(&text).unwrap_or(ErrorBody {
+        code: String::new(),
+        message: text,
+        field: None,
+    });
+    Err(ClientError::Api {
+        status: status.as_u16(),
+        code: body.code,
+        message: body.message,
+        field: body.field,
+    })
+}
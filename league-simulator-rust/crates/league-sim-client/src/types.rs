@@ -0,0 +1,451 @@
+// Wire types mirroring the league-sim-api request and response bodies.
+// Optional request fields are omitted when unset so the server applies its
+// own defaults; response fields added later by the server are ignored.
+
+use league_sim_core::structure::LeagueStructure;
+use league_sim_core::{
+    ExpectedStanding, GlickoSettings, MatchImportance, PoissonStrengths, RatingSystemKind, RngKind,
+    ScenarioResult, SeasonSample, SimulationParams, TableEntry, TeamAwards, TeamStrength,
+    TiebreakerRule,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Body of `POST /simulate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulateRequest {
+    /// Schedule rows `[team_home, team_away, goals_home, goals_away]`,
+    /// 1-indexed teams, goals None for unplayed matches
+    pub schedule: Vec<[Option<i32>; 4]>,
+
+    /// Initial ELO values for each team
+    pub elo_values: Vec<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team_names: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_factor: Option<f64>,
+
+    /// Multiplier of mod_factor per schedule row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_factor_weights: Option<Vec<f64>>,
+
+    /// Competition of each schedule row, e.g. "cup" or "friendly"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub competitions: Option<Vec<Option<String>>>,
+
+    /// Multiplier of mod_factor per competition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub competition_weights: Option<BTreeMap<String, f64>>,
+
+    /// Multiplier of mod_factor per schedule row, overriding its
+    /// competition's; None entries keep the competition weight
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_weights: Option<Vec<Option<f64>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_advantage: Option<f64>,
+
+    /// Rating system of the simulation, Elo by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating_system: Option<RatingSystemKind>,
+
+    /// Glicko-2 settings for `RatingSystemKind::Glicko2`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glicko: Option<GlickoSettings>,
+
+    /// Attack and defense per team, e.g. from an expected goals model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team_strengths: Option<Vec<TeamStrength>>,
+
+    /// Share of `team_strengths` in the goal means, 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength_weight: Option<f64>,
+
+    /// Log-linear attack and defense per team, e.g. from
+    /// `POST /fit/team-strengths`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poisson_strengths: Option<PoissonStrengths>,
+
+    /// Squad market value per team, turned into strength priors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_values: Option<Vec<f64>>,
+
+    /// ELO points per doubling of market value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elo_per_doubling: Option<f64>,
+
+    /// Prior strength per team in ELO points, instead of `market_values`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength_priors: Option<Vec<f64>>,
+
+    /// Share of the priors in the strength difference, 0 to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prior_weight: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adj_points: Option<Vec<i32>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adj_goals: Option<Vec<i32>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adj_goals_against: Option<Vec<i32>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adj_goal_diff: Option<Vec<i32>>,
+
+    /// Order of the final table, starting with points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tiebreakers: Option<Vec<TiebreakerRule>>,
+
+    /// Fair play points per team, fewer is better
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fair_play: Option<Vec<i32>>,
+
+    /// Current table per team (table-only mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_table: Option<Vec<TableEntry>>,
+
+    /// Matchday of each schedule row, 1-based
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matchdays: Option<Vec<u32>>,
+
+    /// Ignore every fixture after this matchday
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulate_until_matchday: Option<u32>,
+
+    /// Results to assume for unplayed schedule rows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Vec<ResultOverride>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rng: Option<RngKind>,
+
+    /// Master seed; the same request and seed give the same probabilities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// League preset id, e.g. "bundesliga": its tiebreakers and structure
+    /// unless set here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub league: Option<String>,
+
+    /// League template id, or "auto"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structure: Option<String>,
+
+    /// Scenario mixture components
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scenarios: Option<Vec<ScenarioSpec>>,
+
+    /// Also estimate end-of-season award probabilities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub awards: Option<bool>,
+
+    /// Also return the simulated expected table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_table: Option<bool>,
+
+    /// Also rank the unplayed matches by importance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_importance: Option<bool>,
+
+    /// Also return up to this many sampled final tables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_samples: Option<usize>,
+
+    /// Return only the matrix cells above this probability
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparse_threshold: Option<f64>,
+}
+
+/// An assumed result for an unplayed match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultOverride {
+    /// 0-based schedule row
+    pub row: usize,
+    pub goals_home: i32,
+    pub goals_away: i32,
+}
+
+/// One weighted model variant of a scenario mixture; unset fields inherit
+/// the request's values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenarioSpec {
+    pub name: String,
+    pub weight: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_advantage: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_factor: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elo_scale: Option<f64>,
+}
+
+impl SimulateRequest {
+    /// Request with the server's defaults for everything but the schedule
+    /// and Elo values.
+    pub fn new(schedule: Vec<[Option<i32>; 4]>, elo_values: Vec<f64>) -> Self {
+        Self {
+            schedule,
+            elo_values,
+            ..Default::default()
+        }
+    }
+
+    pub fn team_names(mut self, names: Vec<String>) -> Self {
+        self.team_names = Some(names);
+        self
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    pub fn league(mut self, league: impl Into<String>) -> Self {
+        self.league = Some(league.into());
+        self
+    }
+
+    pub fn structure(mut self, structure: impl Into<String>) -> Self {
+        self.structure = Some(structure.into());
+        self
+    }
+
+    /// Blend priors from squad market values into the Elo goal model at
+    /// `weight`.
+    pub fn market_values(mut self, values: Vec<f64>, weight: f64) -> Self {
+        self.market_values = Some(values);
+        self.prior_weight = Some(weight);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn sparse_threshold(mut self, threshold: f64) -> Self {
+        self.sparse_threshold = Some(threshold);
+        self
+    }
+}
+
+/// Body of a successful `POST /simulate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulateResponse {
+    /// Rows are teams in final rank order, columns are positions; empty
+    /// for sparse requests
+    #[serde(default)]
+    pub probability_matrix: Vec<Vec<f64>>,
+    /// `(team row, 1-based position, probability)` cells above the
+    /// request's sparse_threshold
+    #[serde(default)]
+    pub probability_cells: Option<Vec<(usize, usize, f64)>>,
+    pub team_names: Vec<String>,
+    pub simulations_performed: usize,
+    pub time_ms: u64,
+    #[serde(default)]
+    pub projected_table: Vec<ProjectedRow>,
+    /// Certain best and worst final positions, rows in matrix order
+    #[serde(default)]
+    pub position_bounds: Vec<TeamPositionBounds>,
+    #[serde(default)]
+    pub structure: Option<AppliedStructure>,
+    #[serde(default)]
+    pub zone_probabilities: Option<Vec<TeamZoneProbabilities>>,
+    #[serde(default)]
+    pub cache_hit: bool,
+    #[serde(default)]
+    pub scenarios: Option<ScenarioBands>,
+    #[serde(default)]
+    pub awards: Option<Vec<TeamAwards>>,
+    #[serde(default)]
+    pub expected_table: Option<Vec<ExpectedStanding>>,
+    #[serde(default)]
+    pub match_importance: Option<Vec<MatchImportance>>,
+    #[serde(default)]
+    pub samples: Option<Vec<SeasonSample>>,
+    #[serde(default)]
+    pub schedule_warnings: Vec<String>,
+    /// Echo of the request's seed
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub metadata: Option<SimulationMetadata>,
+}
+
+/// Engine build and effective inputs of a simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationMetadata {
+    pub engine_version: String,
+    pub git_sha: String,
+    /// Engine parameters after the server's defaults
+    pub params: SimulationParams,
+    /// Master seed of the run; send it as `seed` to reproduce the response
+    pub seed: u64,
+    pub iterations: usize,
+    pub wall_time_ms: u64,
+    pub threads: usize,
+}
+
+/// Scenario components and probability bands of a mixture response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioBands {
+    pub components: Vec<ScenarioResult>,
+    pub lower_bound: Vec<Vec<f64>>,
+    pub upper_bound: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectedRow {
+    pub team_name: String,
+    pub position: usize,
+    pub expected_points: f64,
+    pub expected_goals_for: f64,
+    pub expected_goals_against: f64,
+    pub expected_goal_difference: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedStructure {
+    #[serde(flatten)]
+    pub structure: LeagueStructure,
+    pub detected: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamZoneProbabilities {
+    pub team_name: String,
+    pub zones: BTreeMap<String, f64>,
+    /// Zones the team is mathematically certain to finish in
+    #[serde(default)]
+    pub clinched: Vec<String>,
+    /// Zones the team can no longer finish in
+    #[serde(default)]
+    pub eliminated: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamPositionBounds {
+    pub team_name: String,
+    pub best_position: usize,
+    pub worst_position: usize,
+}
+
+/// Answer to a `POST /simulate` carrying a callback URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackAccepted {
+    pub status: String,
+    pub callback_url: String,
+}
+
+/// Body of `POST /simulate/batch` and `POST /jobs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchSimulateRequest {
+    pub leagues: Vec<LeagueRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueRequest {
+    pub name: String,
+    pub request: SimulateRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSimulateResponse {
+    pub results: Vec<LeagueResult>,
+    pub total_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueResult {
+    pub name: String,
+    pub status: LeagueStatus,
+    /// When the league's simulation began, in ms after the batch started
+    #[serde(default)]
+    pub started_ms: u64,
+    /// How long the league's simulation took
+    #[serde(default)]
+    pub time_ms: u64,
+    /// Index of an earlier league with the same request, whose result this
+    /// one shares
+    #[serde(default)]
+    pub duplicate_of: Option<usize>,
+    /// Set for leagues that succeeded
+    #[serde(default)]
+    pub response: Option<SimulateResponse>,
+    /// Set for leagues that failed
+    #[serde(default)]
+    pub error: Option<ErrorBody>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeagueStatus {
+    Success,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+    pub performance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobCreated {
+    pub job_id: String,
+    pub status: String,
+}
+
+/// State of an asynchronous batch job (`GET /jobs/{id}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Succeeded { result: BatchSimulateResponse },
+    Failed { error: String },
+    Cancelled,
+}
+
+/// Score update for one schedule row (`POST /sessions/{id}/events`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchEvent {
+    /// 0-based row of the session's schedule
+    pub row: usize,
+    pub goals_home: i32,
+    pub goals_away: i32,
+    /// Minute the score was reached; None once the match has finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minute: Option<u32>,
+}
+
+/// State of a matchday session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionResponse {
+    pub session_id: String,
+    /// Matches currently in progress
+    pub live: Vec<MatchEvent>,
+    pub result: SimulateResponse,
+}
+
+/// JSON body of every error response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub field: Option<String>,
+}
@@ -0,0 +1,101 @@
+// Runs the typed client against a real league-sim-api router on a local
+// port, so drift between the client types and the server's wire format
+// fails here.
+
+use league_sim_client::*;
+
+async fn spawn_server() -> Client {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, league_sim_api::create_router())
+            .await
+            .unwrap()
+    });
+    Client::new(format!("http://{}/", addr))
+}
+
+fn small_request() -> SimulateRequest {
+    SimulateRequest::new(
+        vec![
+            [Some(1), Some(2), Some(1), Some(0)],
+            [Some(2), Some(1), None, None],
+        ],
+        vec![1500.0, 1500.0],
+    )
+    .team_names(vec!["A".into(), "B".into()])
+    .iterations(50)
+}
+
+#[tokio::test]
+async fn health_and_simulate_parse_into_typed_responses() {
+    let client = spawn_server().await;
+
+    assert_eq!(client.health().await.unwrap().status, "ok");
+
+    let response = client.simulate(&small_request()).await.unwrap();
+    assert_eq!(response.simulations_performed, 50);
+    assert_eq!(response.probability_matrix.len(), 2);
+    assert_eq!(response.projected_table.len(), 2);
+    assert!(!response.cache_hit);
+}
+
+#[tokio::test]
+async fn batch_and_jobs_round_trip() {
+    let client = spawn_server().await;
+    let batch = BatchSimulateRequest {
+        leagues: vec![LeagueRequest {
+            name: "Liga".into(),
+            request: small_request(),
+        }],
+    };
+
+    let response = client.simulate_batch(&batch).await.unwrap();
+    assert_eq!(response.results[0].name, "Liga");
+    assert_eq!(response.results[0].status, LeagueStatus::Success);
+    assert!(response.results[0].response.is_some());
+
+    let created = client.create_job(&batch).await.unwrap();
+    let mut status = client.job(&created.job_id).await.unwrap();
+    for _ in 0..200 {
+        if !matches!(status, JobStatus::Running) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        status = client.job(&created.job_id).await.unwrap();
+    }
+    match status {
+        JobStatus::Succeeded { result } => assert_eq!(result.results.len(), 1),
+        other => panic!("unexpected job status {:?}", other),
+    }
+
+    client.delete_job(&created.job_id).await.unwrap();
+    assert!(matches!(
+        client.job(&created.job_id).await,
+        Err(ClientError::Api { status: 404, .. })
+    ));
+}
+
+#[tokio::test]
+async fn validation_errors_surface_as_api_errors() {
+    let client = spawn_server().await;
+    let err = client
+        .simulate(&SimulateRequest::new(Vec::new(), vec![1500.0]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ClientError::Api {
+            status,
+            code,
+            message,
+            field,
+        } => {
+            assert_eq!(status, 422);
+            assert_eq!(code, "invalid_field");
+            assert!(message.contains("schedule"));
+            assert_eq!(field.as_deref(), Some("schedule"));
+        }
+        other => panic!("unexpected error {}", other),
+    }
+}
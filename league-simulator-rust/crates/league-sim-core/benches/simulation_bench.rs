@@ -0,0 +1,264 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use league_sim_core::*;
+use std::hint::black_box;
+
+fn create_bundesliga_season() -> Season {
+    // Create a realistic Bundesliga season (18 teams, 306 matches)
+    let mut matches = Vec::new();
+    for home in 0..18 {
+        for away in 0..18 {
+            if home != away {
+                matches.push(Match {
+                    team_home: home,
+                    team_away: away,
+                    goals_home: if home < 9 && away < 9 {
+                        Some((home % 3) as i32)
+                    } else {
+                        None
+                    },
+                    goals_away: if home < 9 && away < 9 {
+                        Some((away % 2) as i32)
+                    } else {
+                        None
+                    },
+                    matchday: None,
+                    competition: None,
+                    weight: None,
+                });
+            }
+        }
+    }
+
+    let team_elos = vec![
+        1850.0, 1800.0, 1750.0, 1700.0, 1650.0, 1600.0, // Top teams
+        1550.0, 1500.0, 1500.0, 1500.0, 1500.0, 1450.0, // Mid table
+        1450.0, 1400.0, 1400.0, 1350.0, 1300.0, 1250.0, // Bottom teams
+    ];
+
+    Season {
+        matches,
+        team_elos,
+        number_teams: 18,
+    }
+}
+
+fn benchmark_elo_calculation(c: &mut Criterion) {
+    let params = EloParams {
+        elo_home: 1500.0,
+        elo_away: 1600.0,
+        goals_home: 2,
+        goals_away: 1,
+        mod_factor: 40.0,
+        home_advantage: 65.0,
+    };
+
+    c.bench_function("elo_calculation", |b| {
+        b.iter(|| calculate_elo_change(black_box(&params)))
+    });
+}
+
+fn benchmark_monte_carlo(c: &mut Criterion) {
+    let season = create_bundesliga_season();
+    let team_names: Vec<String> = (0..18).map(|i| format!("Team {}", i + 1)).collect();
+
+    let mut group = c.benchmark_group("monte_carlo");
+
+    for iterations in [100, 1000, 10000] {
+        let params = SimulationParams {
+            iterations,
+            ..Default::default()
+        };
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(iterations),
+            &iterations,
+            |b, _| {
+                b.iter(|| {
+                    run_monte_carlo_simulation(
+                        black_box(&season),
+                        black_box(&params),
+                        black_box(team_names.clone()),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Fixed-size Monte Carlo run inside rayon pools of increasing width.
+/// Per-thread fold/reduce accumulation has no shared counters, so throughput
+/// should scale close to linearly until the physical core count is reached.
+fn benchmark_thread_scaling(c: &mut Criterion) {
+    let season = create_bundesliga_season();
+    let team_names: Vec<String> = (0..18).map(|i| format!("Team {}", i + 1)).collect();
+    let params = SimulationParams {
+        iterations: 10000,
+        ..Default::default()
+    };
+
+    let max_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let mut group = c.benchmark_group("thread_scaling");
+    group.sample_size(10);
+
+    let mut threads = 1;
+    while threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build rayon pool");
+
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| {
+                    run_monte_carlo_simulation(
+                        black_box(&season),
+                        black_box(&params),
+                        black_box(team_names.clone()),
+                    )
+                })
+            })
+        });
+        threads *= 2;
+    }
+    group.finish();
+}
+
+fn benchmark_rng_backends(c: &mut Criterion) {
+    let season = create_bundesliga_season();
+    let team_names: Vec<String> = (0..18).map(|i| format!("Team {}", i + 1)).collect();
+
+    let mut group = c.benchmark_group("rng_backend");
+
+    for rng in [RngKind::Std, RngKind::SmallRng, RngKind::Xoshiro256PlusPlus] {
+        let params = SimulationParams {
+            iterations: 1000,
+            rng,
+            ..Default::default()
+        };
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", rng)),
+            &rng,
+            |b, _| {
+                b.iter(|| {
+                    run_monte_carlo_simulation(
+                        black_box(&season),
+                        black_box(&params),
+                        black_box(team_names.clone()),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn benchmark_single_season_simulation(c: &mut Criterion) {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let season = create_bundesliga_season();
+
+    c.bench_function("single_season_simulation", |b| {
+        b.iter(|| {
+            let mut rng = StdRng::seed_from_u64(42);
+            simulate_season(
+                black_box(&season),
+                20.0,
+                None,
+                65.0,
+                0.0017854953143549,
+                1.3218390804597700,
+                &mut rng,
+            )
+        })
+    });
+}
+
+fn benchmark_poisson_lanes(c: &mut Criterion) {
+    // Typical goal means and uniform draws, four matches at a time.
+    let lambda = [0.9, 1.3218390805, 1.6, 2.2];
+    let p = [0.12, 0.47, 0.81, 0.96];
+
+    let mut group = c.benchmark_group("poisson_quantile");
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            let (p, lambda) = (black_box(p), black_box(lambda));
+            std::array::from_fn::<f64, 4, _>(|lane| poisson_quantile_direct(p[lane], lambda[lane]))
+        })
+    });
+    group.bench_function("lanes", |b| {
+        b.iter(|| poisson_quantile_lanes(black_box(p), black_box(lambda)))
+    });
+    group.finish();
+}
+
+fn benchmark_tiebreak_sort(c: &mut Criterion) {
+    // Every match drawn 1:1, so all 18 teams stay level through the
+    // head-to-head criteria and the chain ends in drawing of lots.
+    let mut season = create_bundesliga_season();
+    for m in &mut season.matches {
+        m.goals_home = Some(1);
+        m.goals_away = Some(1);
+    }
+    let mut table = initial_standings(18, None, None, None, None);
+    add_match_results(&mut table, &season.matches);
+    let rules = [
+        TiebreakerRule::Points,
+        TiebreakerRule::HeadToHeadPoints,
+        TiebreakerRule::HeadToHeadAwayGoals,
+        TiebreakerRule::GoalDifference,
+        TiebreakerRule::GoalsFor,
+        TiebreakerRule::DrawingOfLots,
+    ];
+    let tiebreakers = Tiebreakers {
+        rules: &rules,
+        fair_play: None,
+    };
+    let matches: [&[Match]; 1] = [&season.matches];
+
+    let mut group = c.benchmark_group("tiebreak_sort");
+    group.bench_function("fresh", |b| {
+        let mut standings = table.clone();
+        let mut lot = 0u64;
+        b.iter(|| {
+            tiebreakers.sort(black_box(&mut standings), &matches, || {
+                lot = lot.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                lot
+            })
+        })
+    });
+    group.bench_function("reused", |b| {
+        let mut standings = table.clone();
+        let mut scratch = TiebreakScratch::default();
+        let mut lot = 0u64;
+        b.iter(|| {
+            tiebreakers.sort_with(
+                black_box(&mut standings),
+                &matches,
+                || {
+                    lot = lot.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                    lot
+                },
+                &mut scratch,
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_elo_calculation,
+    benchmark_single_season_simulation,
+    benchmark_monte_carlo,
+    benchmark_thread_scaling,
+    benchmark_rng_backends,
+    benchmark_poisson_lanes,
+    benchmark_tiebreak_sort
+);
+criterion_main!(benches);
@@ -0,0 +1,248 @@
+use crate::models::{Match, OutcomeProbabilities, RatingSystemKind, Season, SimulationParams};
+use crate::rating::{update_pair, Elo, RatingSystem};
+use crate::simulation::GoalModel;
+use serde::{Deserialize, Serialize};
+
+/// Equal-width probability bins of the reliability curve.
+pub const RELIABILITY_BINS: usize = 10;
+
+/// Accuracy of pre-match win/draw/loss forecasts.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BacktestScore {
+    /// Played matches forecast
+    pub matches: usize,
+    /// Mean squared error of the three outcome probabilities, summed over
+    /// the outcomes: 0 is perfect, 2 certain and wrong
+    pub brier_score: f64,
+    /// Mean negative natural log of the probability given to the outcome
+    /// that happened
+    pub log_loss: f64,
+}
+
+/// One bin of the reliability curve: forecasts of an outcome with a
+/// probability in `[lower, upper)` against how often it happened.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReliabilityBin {
+    pub lower: f64,
+    pub upper: f64,
+    /// Outcome probabilities in the bin; every match gives three
+    pub forecasts: usize,
+    /// Mean forecast probability in the bin
+    pub mean_forecast: f64,
+    /// Share of the binned outcomes that happened
+    pub observed_frequency: f64,
+}
+
+/// Scores of a [`backtest`] over all seasons and per season.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    #[serde(flatten)]
+    pub overall: BacktestScore,
+    /// Non-empty bins of the reliability curve, in order of probability
+    pub reliability: Vec<ReliabilityBin>,
+    /// Scores per season, in the order of the input
+    pub seasons: Vec<BacktestScore>,
+}
+
+/// Replay played seasons and score the forecasts the model would have made
+/// before each matchday.
+///
+/// Each season starts from its `team_elos`. When every played match has a
+/// matchday, matchdays are replayed in ascending order: all matches of a
+/// matchday are forecast from the ratings before it, then its results
+/// update the ratings in schedule order. Otherwise every match is its own
+/// step in schedule order. Unplayed matches are skipped.
+///
+/// Forecasts are the exact outcome probabilities of the goal model of
+/// `params` (its Poisson strengths or team strengths if set, else the Elo
+/// goal model), ratings move with `params.rating_system`, `mod_factor`
+/// and `home_advantage`, each match weighted by its `weight` or
+/// `competition` as in the simulation. `mod_factor_weights` are ignored.
+/// Score two parameter sets on the same seasons to compare them: lower is
+/// better for both scores.
+pub fn backtest(seasons: &[Season], params: &SimulationParams) -> BacktestReport {
+    let mut overall = Tally::default();
+    let scores = seasons
+        .iter()
+        .map(|season| {
+            let mut tally = Tally::default();
+            match params.rating_system {
+                RatingSystemKind::Elo => replay(
+                    &Elo {
+                        mod_factor: params.mod_factor,
+                    },
+                    season.team_elos.clone(),
+                    &season.matches,
+                    params,
+                    &mut tally,
+                ),
+                RatingSystemKind::Glicko2 => replay(
+                    &params.glicko.system(),
+                    params.glicko.starting_ratings(&season.team_elos),
+                    &season.matches,
+                    params,
+                    &mut tally,
+                ),
+            }
+            overall.merge(&tally);
+            tally.score()
+        })
+        .collect();
+
+    BacktestReport {
+        overall: overall.score(),
+        reliability: overall.reliability(),
+        seasons: scores,
+    }
+}
+
+/// Forecast and then apply the played matches of one season.
+fn replay<S: RatingSystem>(
+    system: &S,
+    mut ratings: Vec<S::Rating>,
+    matches: &[Match],
+    params: &SimulationParams,
+    tally: &mut Tally,
+) {
+    let mut played: Vec<usize> = (0..matches.len())
+        .filter(|&i| matches[i].goals_home.is_some() && matches[i].goals_away.is_some())
+        .collect();
+    let by_matchday = played.iter().all(|&i| matches[i].matchday.is_some());
+    if by_matchday {
+        played.sort_by_key(|&i| matches[i].matchday);
+    }
+
+    let mut step = 0;
+    while step < played.len() {
+        let len = if by_matchday {
+            let matchday = matches[played[step]].matchday;
+            played[step..]
+                .iter()
+                .take_while(|&&i| matches[i].matchday == matchday)
+                .count()
+        } else {
+            1
+        };
+        let rows = &played[step..step + len];
+        for &i in rows {
+            let m = &matches[i];
+            let strength_delta = system.strength(&ratings[m.team_home]) + params.home_advantage
+                - system.strength(&ratings[m.team_away]);
+            let probabilities = forecast(params, m.team_home, m.team_away, strength_delta);
+            tally.record(&probabilities, m.goals_home.cmp(&m.goals_away));
+        }
+        for &i in rows {
+            let m = &matches[i];
+            let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+                continue;
+            };
+            update_pair(
+                system,
+                &mut ratings,
+                m,
+                goals_home,
+                goals_away,
+                params.home_advantage,
+                params.match_weight(m),
+            );
+        }
+        step += len;
+    }
+}
+
+/// Outcome probabilities under the goal model `params` simulates with.
+fn forecast(
+    params: &SimulationParams,
+    home: usize,
+    away: usize,
+    strength_delta: f64,
+) -> OutcomeProbabilities {
+    if let Some(poisson) = &params.poisson_strengths {
+        poisson.outcome_probabilities(home, away, strength_delta)
+    } else if let Some(model) = params.strength_goal_model() {
+        model.outcome_probabilities(home, away, strength_delta)
+    } else if let Some(model) = params.prior_goal_model() {
+        model.outcome_probabilities(home, away, strength_delta)
+    } else {
+        params
+            .goal_model()
+            .outcome_probabilities(home, away, strength_delta)
+    }
+}
+
+/// Running sums of the scores and the reliability bins.
+#[derive(Default)]
+struct Tally {
+    matches: usize,
+    brier: f64,
+    log_loss: f64,
+    /// Per bin: forecasts, summed probability, outcomes that happened
+    bins: [(usize, f64, usize); RELIABILITY_BINS],
+}
+
+impl Tally {
+    fn record(&mut self, probabilities: &OutcomeProbabilities, result: std::cmp::Ordering) {
+        let forecast = [
+            probabilities.home_win,
+            probabilities.draw,
+            probabilities.away_win,
+        ];
+        let happened = match result {
+            std::cmp::Ordering::Greater => 0,
+            std::cmp::Ordering::Equal => 1,
+            std::cmp::Ordering::Less => 2,
+        };
+        self.matches += 1;
+        self.log_loss -= forecast[happened].max(f64::MIN_POSITIVE).ln();
+        for (k, &p) in forecast.iter().enumerate() {
+            let observed = usize::from(k == happened);
+            self.brier += (p - observed as f64).powi(2);
+            let bin = ((p * RELIABILITY_BINS as f64) as usize).min(RELIABILITY_BINS - 1);
+            self.bins[bin].0 += 1;
+            self.bins[bin].1 += p;
+            self.bins[bin].2 += observed;
+        }
+    }
+
+    fn merge(&mut self, other: &Tally) {
+        self.matches += other.matches;
+        self.brier += other.brier;
+        self.log_loss += other.log_loss;
+        for (bin, other) in self.bins.iter_mut().zip(&other.bins) {
+            bin.0 += other.0;
+            bin.1 += other.1;
+            bin.2 += other.2;
+        }
+    }
+
+    fn score(&self) -> BacktestScore {
+        let n = self.matches.max(1) as f64;
+        BacktestScore {
+            matches: self.matches,
+            brier_score: self.brier / n,
+            log_loss: self.log_loss / n,
+        }
+    }
+
+    fn reliability(&self) -> Vec<ReliabilityBin> {
+        let width = 1.0 / RELIABILITY_BINS as f64;
+        self.bins
+            .iter()
+            .enumerate()
+            .filter(|(_, &(forecasts, _, _))| forecasts > 0)
+            .map(|(i, &(forecasts, probability, happened))| ReliabilityBin {
+                lower: i as f64 * width,
+                upper: (i + 1) as f64 * width,
+                forecasts,
+                mean_forecast: probability / forecasts as f64,
+                observed_frequency: happened as f64 / forecasts as f64,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests;
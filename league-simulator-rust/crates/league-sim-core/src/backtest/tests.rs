@@ -0,0 +1,108 @@
+use super::*;
+use crate::models::{Match, Season, SimulationParams};
+use crate::simulation::{double_round_robin, outcome_probabilities, simulate_season};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Played seasons of an 18 team league, simulated with the default
+/// parameters.
+fn history(seasons: usize) -> Vec<Season> {
+    let params = SimulationParams::default();
+    let mut rng = StdRng::seed_from_u64(11);
+    (0..seasons)
+        .map(|_| {
+            let season = Season {
+                matches: double_round_robin(18),
+                team_elos: (0..18).map(|i| 1300.0 + 25.0 * i as f64).collect(),
+                number_teams: 18,
+            };
+            let (matches, _) = simulate_season(
+                &season,
+                params.mod_factor,
+                None,
+                params.home_advantage,
+                params.tore_slope,
+                params.tore_intercept,
+                &mut rng,
+            );
+            Season { matches, ..season }
+        })
+        .collect()
+}
+
+#[test]
+fn backtest_prefers_the_parameters_that_generated_the_results() {
+    let seasons = history(6);
+    let truth = backtest(&seasons, &SimulationParams::default());
+
+    assert_eq!(truth.overall.matches, 6 * 306);
+    assert_eq!(truth.seasons.len(), 6);
+    assert!(truth.seasons.iter().all(|s| s.matches == 306));
+    assert!(truth.overall.brier_score > 0.0 && truth.overall.brier_score < 2.0 / 3.0);
+
+    let blind = backtest(
+        &seasons,
+        &SimulationParams {
+            tore_slope: 0.0,
+            home_advantage: 0.0,
+            ..Default::default()
+        },
+    );
+    assert!(truth.overall.log_loss < blind.overall.log_loss);
+    assert!(truth.overall.brier_score < blind.overall.brier_score);
+
+    // Three forecasts per match; well filled bins are calibrated.
+    let forecasts: usize = truth.reliability.iter().map(|b| b.forecasts).sum();
+    assert_eq!(forecasts, 3 * truth.overall.matches);
+    for bin in truth.reliability.iter().filter(|b| b.forecasts >= 300) {
+        assert!(bin.lower <= bin.mean_forecast && bin.mean_forecast < bin.upper);
+        assert!(
+            (bin.observed_frequency - bin.mean_forecast).abs() < 0.08,
+            "{:?}",
+            bin
+        );
+    }
+}
+
+#[test]
+fn backtest_forecasts_a_matchday_from_the_ratings_before_it() {
+    let played = |home, away, matchday| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: Some(3),
+        goals_away: Some(0),
+        matchday,
+        competition: None,
+        weight: None,
+    };
+    let params = SimulationParams::default();
+    let equal = outcome_probabilities(
+        1500.0,
+        1500.0,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    );
+    let season = |matchday| Season {
+        // Team 0 plays twice on the matchday; the unplayed row is skipped.
+        matches: vec![
+            played(0, 1, matchday),
+            played(0, 2, matchday),
+            Match {
+                goals_home: None,
+                goals_away: None,
+                ..played(1, 2, matchday)
+            },
+        ],
+        team_elos: vec![1500.0; 3],
+        number_teams: 3,
+    };
+
+    let same_day = backtest(&[season(Some(1))], &params);
+    assert_eq!(same_day.overall.matches, 2);
+    assert!((same_day.overall.log_loss + equal.home_win.ln()).abs() < 1e-12);
+
+    // Without matchdays the first win already lifts team 0 for the second.
+    let in_order = backtest(&[season(None)], &params);
+    assert!(in_order.overall.log_loss < same_day.overall.log_loss);
+}
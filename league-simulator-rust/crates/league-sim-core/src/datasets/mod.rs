@@ -0,0 +1,374 @@
+//! Played seasons for fitting, backtests and demos.
+//!
+//! A dataset directory holds a folder per season, named by the year the
+//! season starts in, with the season's `TeamList.csv` and a fixtures CSV
+//! per league named by the league's preset id (both formats are described
+//! in [`crate::ingestion`]):
+//!
+//! ```text
+//! 2024/TeamList.csv
+//! 2024/bundesliga.csv
+//! 2024/2_bundesliga.csv
+//! 2024/3_liga.csv
+//! ```
+//!
+//! Only one season is compiled in: the regular season 2024/25 of the
+//! Bundesliga, 2. Bundesliga and 3. Liga, laid out the same way under
+//! `data/`. [`HistoricalData::bundled`] is therefore enough for demos and
+//! single-season checks, not for calibration across seasons; earlier
+//! seasons are read from a directory with [`HistoricalData::from_dir`].
+//!
+//! With the `storage` feature, [`write_history`] converts a dataset
+//! directory into a [`crate::history`] file once, and
+//! [`HistoricalData::from_history`] loads the seasons back from it without
+//! parsing any CSV.
+
+#[cfg(feature = "storage")]
+use crate::history::{HistoricalResult, HistoryError, HistoryStore, HistoryWriter, SeasonTeam};
+use crate::ingestion::{load_fixtures, IngestionError, LeagueData, TeamList};
+use crate::models::Season;
+#[cfg(feature = "storage")]
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DatasetError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{path}: {source}")]
+    File {
+        path: PathBuf,
+        source: IngestionError,
+    },
+    #[cfg(feature = "storage")]
+    #[error("history file: {0}")]
+    History(#[from] HistoryError),
+}
+
+/// The compiled-in seasons: start year, TeamList, fixtures per league.
+type BundledSeason = (u16, &'static str, &'static [(&'static str, &'static str)]);
+
+const BUNDLED: &[BundledSeason] = &[(
+    2024,
+    include_str!("../../data/2024/TeamList.csv"),
+    &[
+        ("bundesliga", include_str!("../../data/2024/bundesliga.csv")),
+        (
+            "2_bundesliga",
+            include_str!("../../data/2024/2_bundesliga.csv"),
+        ),
+        ("3_liga", include_str!("../../data/2024/3_liga.csv")),
+    ],
+)];
+
+/// One league's season.
+#[derive(Debug, Clone)]
+pub struct HistoricalSeason {
+    /// Preset id of the league, e.g. `bundesliga`
+    pub league: String,
+    /// Year the season starts in
+    pub season: u16,
+    /// The matches with their results and, where the file has them, their
+    /// matchdays; `team_elos` are the TeamList ratings from before the
+    /// season
+    pub data: LeagueData,
+}
+
+impl HistoricalSeason {
+    /// The season as it stood after `matchday`: the results of later
+    /// matchdays removed, those of matches without a matchday kept.
+    pub fn after_matchday(&self, matchday: u32) -> Season {
+        let mut season = self.data.season.clone();
+        for m in &mut season.matches {
+            if m.matchday.is_some_and(|md| md > matchday) {
+                m.goals_home = None;
+                m.goals_away = None;
+            }
+        }
+        season
+    }
+
+    /// Last matchday of the season, if the fixtures have matchdays.
+    pub fn matchdays(&self) -> Option<u32> {
+        self.data.matchdays.iter().flatten().max().copied()
+    }
+}
+
+/// Played seasons, ordered by year and league.
+#[derive(Debug, Clone, Default)]
+pub struct HistoricalData {
+    seasons: Vec<HistoricalSeason>,
+}
+
+impl HistoricalData {
+    /// The compiled-in seasons: 2024/25 only.
+    pub fn bundled() -> Self {
+        let mut data = Self::default();
+        for &(season, team_list, leagues) in BUNDLED {
+            let teams =
+                TeamList::from_reader(team_list.as_bytes()).expect("bundled TeamList is valid");
+            for &(league, fixtures) in leagues {
+                let loaded = load_season(league, season, &teams, fixtures.as_bytes())
+                    .expect("bundled fixtures are valid");
+                data.insert(loaded);
+            }
+        }
+        data
+    }
+
+    /// The seasons of a dataset directory. Entries not named by a year,
+    /// and files in a season folder other than `*.csv`, are skipped.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, DatasetError> {
+        let mut data = Self::default();
+        for (season, folder) in season_folders(dir.as_ref())? {
+            let (teams, files) = read_folder(&folder)?;
+            for (league, path) in files {
+                data.insert(read_season(&league, season, &teams, &path)?);
+            }
+        }
+        Ok(data)
+    }
+
+    /// The seasons of a history file written by [`write_history`] or
+    /// [`HistoricalData::write_history`]. The file holds played matches
+    /// only, so unplayed fixtures of the written seasons are not restored.
+    #[cfg(feature = "storage")]
+    pub fn from_history(store: &HistoryStore) -> Self {
+        let mut data = Self::default();
+        for entry in store.seasons() {
+            let Some(league) = store.league_name(entry.league) else {
+                continue;
+            };
+            let Some((season, teams)) = store.season_data(entry.league, entry.season) else {
+                continue;
+            };
+            let team_names = teams
+                .iter()
+                .map(|t| store.team_name(t.team).unwrap_or_default().to_string())
+                .collect();
+            data.insert(HistoricalSeason {
+                league: league.to_string(),
+                season: entry.season,
+                data: LeagueData {
+                    matchdays: season.matches.iter().map(|m| m.matchday).collect(),
+                    promotion: teams.iter().map(|t| t.promotion).collect(),
+                    team_names,
+                    season,
+                },
+            });
+        }
+        data
+    }
+
+    /// Write these seasons to a history file at `path`.
+    #[cfg(feature = "storage")]
+    pub fn write_history(&self, path: impl AsRef<Path>) -> Result<(), DatasetError> {
+        let mut seasons: Vec<&HistoricalSeason> = self.seasons.iter().collect();
+        seasons.sort_by_key(|s| (s.league.as_str(), s.season));
+        let mut writer = HistoryWriter::create(path)?;
+        for season in seasons {
+            push_season(&mut writer, season)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Add the seasons of `other`, replacing those of the same league and
+    /// year.
+    pub fn extend(&mut self, other: HistoricalData) {
+        for season in other.seasons {
+            self.insert(season);
+        }
+    }
+
+    pub fn seasons(&self) -> &[HistoricalSeason] {
+        &self.seasons
+    }
+
+    pub fn get(&self, league: &str, season: u16) -> Option<&HistoricalSeason> {
+        self.seasons
+            .iter()
+            .find(|s| s.league == league && s.season == season)
+    }
+
+    /// Seasons of one league, oldest first.
+    pub fn league<'a>(&'a self, league: &'a str) -> impl Iterator<Item = &'a HistoricalSeason> {
+        self.seasons.iter().filter(move |s| s.league == league)
+    }
+
+    /// Seasons of one league, oldest first, as [`backtest`](crate::backtest)
+    /// takes them.
+    pub fn backtest_seasons(&self, league: &str) -> Vec<Season> {
+        self.league(league).map(|s| s.data.season.clone()).collect()
+    }
+
+    fn insert(&mut self, season: HistoricalSeason) {
+        let key = |s: &HistoricalSeason| (s.season, s.league.clone());
+        match self.seasons.binary_search_by_key(&key(&season), key) {
+            Ok(i) => self.seasons[i] = season,
+            Err(i) => self.seasons.insert(i, season),
+        }
+    }
+}
+
+/// Write the seasons of a dataset directory to a history file at `path`,
+/// reading one fixtures file at a time, so the directory may hold more
+/// seasons than fit in memory. Skips what [`HistoricalData::from_dir`]
+/// skips.
+#[cfg(feature = "storage")]
+pub fn write_history(dir: impl AsRef<Path>, path: impl AsRef<Path>) -> Result<(), DatasetError> {
+    // The store wants league-major order, the directory is season-major:
+    // collect the file paths per league first.
+    let mut leagues: BTreeMap<String, Vec<(u16, PathBuf)>> = BTreeMap::new();
+    let mut team_lists = BTreeMap::new();
+    for (season, folder) in season_folders(dir.as_ref())? {
+        let (teams, files) = read_folder(&folder)?;
+        for (league, path) in files {
+            leagues.entry(league).or_default().push((season, path));
+        }
+        team_lists.insert(season, teams);
+    }
+
+    let mut writer = HistoryWriter::create(path)?;
+    for (league, mut files) in leagues {
+        files.sort();
+        for (season, path) in files {
+            let loaded = read_season(&league, season, &team_lists[&season], &path)?;
+            push_season(&mut writer, &loaded)?;
+        }
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Append the played matches of one season, ordered by matchday, and its
+/// team list.
+#[cfg(feature = "storage")]
+fn push_season(writer: &mut HistoryWriter, season: &HistoricalSeason) -> Result<(), HistoryError> {
+    let league = writer.league_id(&season.league)?;
+    let ids: Vec<u32> = season
+        .data
+        .team_names
+        .iter()
+        .map(|name| writer.team_id(name))
+        .collect();
+    let mut matches: Vec<_> = season.data.season.matches.iter().collect();
+    matches.sort_by_key(|m| m.matchday);
+
+    let goals = |goals: i32| {
+        u8::try_from(goals).map_err(|_| {
+            HistoryError::InvalidRecord(format!(
+                "{} {}: {} goals do not fit a record",
+                season.league, season.season, goals
+            ))
+        })
+    };
+    let matchday = |matchday: u32| {
+        u16::try_from(matchday).map_err(|_| {
+            HistoryError::InvalidRecord(format!(
+                "{} {}: matchday {} does not fit a record",
+                season.league, season.season, matchday
+            ))
+        })
+    };
+    for m in matches {
+        let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+            continue;
+        };
+        writer.push(HistoricalResult {
+            league,
+            season: season.season,
+            matchday: matchday(m.matchday.unwrap_or(0))?,
+            date: 0,
+            team_home: ids[m.team_home],
+            team_away: ids[m.team_away],
+            goals_home: goals(goals_home)?,
+            goals_away: goals(goals_away)?,
+        })?;
+    }
+
+    let teams = ids
+        .iter()
+        .zip(&season.data.season.team_elos)
+        .zip(&season.data.promotion)
+        .map(|((&team, &elo), &promotion)| SeasonTeam {
+            team,
+            elo,
+            promotion,
+        })
+        .collect();
+    writer.season_teams(league, season.season, teams);
+    Ok(())
+}
+
+/// Season folders of a dataset directory: entries named by a year.
+fn season_folders(dir: &Path) -> Result<Vec<(u16, PathBuf)>, DatasetError> {
+    let mut folders = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let folder = entry?.path();
+        let season = folder
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse::<u16>().ok());
+        if let Some(season) = season.filter(|_| folder.is_dir()) {
+            folders.push((season, folder));
+        }
+    }
+    Ok(folders)
+}
+
+/// The TeamList of a season folder and its fixtures files by league.
+fn read_folder(folder: &Path) -> Result<(TeamList, Vec<(String, PathBuf)>), DatasetError> {
+    let team_list = folder.join("TeamList.csv");
+    let teams = TeamList::from_path(&team_list).map_err(|source| DatasetError::File {
+        path: team_list.clone(),
+        source,
+    })?;
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path == team_list || path.extension().is_none_or(|ext| ext != "csv") {
+            continue;
+        }
+        if let Some(league) = path.file_stem().and_then(|stem| stem.to_str()) {
+            files.push((league.to_string(), path));
+        }
+    }
+    Ok((teams, files))
+}
+
+fn read_season(
+    league: &str,
+    season: u16,
+    teams: &TeamList,
+    path: &Path,
+) -> Result<HistoricalSeason, DatasetError> {
+    load_season(league, season, teams, std::fs::File::open(path)?).map_err(|source| {
+        DatasetError::File {
+            path: path.to_path_buf(),
+            source,
+        }
+    })
+}
+
+/// Load a fixtures file, carrying its matchdays into the matches.
+fn load_season(
+    league: &str,
+    season: u16,
+    teams: &TeamList,
+    reader: impl Read,
+) -> Result<HistoricalSeason, IngestionError> {
+    let mut data = load_fixtures(teams, reader)?;
+    for (m, matchday) in data.season.matches.iter_mut().zip(&data.matchdays) {
+        m.matchday = *matchday;
+    }
+    Ok(HistoricalSeason {
+        league: league.to_string(),
+        season,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests;
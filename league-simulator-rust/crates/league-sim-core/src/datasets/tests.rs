@@ -0,0 +1,209 @@
+use super::*;
+use crate::{backtest, calculate_table, fit_poisson_strengths, SimulationParams};
+use tempfile::tempdir;
+
+#[test]
+fn bundled_seasons_are_complete() {
+    let data = HistoricalData::bundled();
+    let leagues: Vec<(&str, u16)> = data
+        .seasons()
+        .iter()
+        .map(|s| (s.league.as_str(), s.season))
+        .collect();
+    assert_eq!(
+        leagues,
+        [
+            ("2_bundesliga", 2024),
+            ("3_liga", 2024),
+            ("bundesliga", 2024)
+        ]
+    );
+
+    for (league, teams) in [("bundesliga", 18), ("2_bundesliga", 18), ("3_liga", 20)] {
+        let season = data.get(league, 2024).unwrap();
+        let n = season.data.season.number_teams;
+        assert_eq!(n, teams, "{}", league);
+        assert_eq!(season.data.season.matches.len(), n * (n - 1), "{}", league);
+        assert_eq!(season.matchdays(), Some(2 * (n as u32 - 1)), "{}", league);
+        assert!(season
+            .data
+            .season
+            .matches
+            .iter()
+            .all(|m| m.goals_home.is_some() && m.matchday.is_some()));
+    }
+}
+
+#[test]
+fn bundesliga_2024_final_table() {
+    let data = HistoricalData::bundled();
+    let season = data.get("bundesliga", 2024).unwrap();
+    let matches = &season.data.season.matches;
+    let table = calculate_table(
+        matches,
+        season.data.season.number_teams,
+        None,
+        None,
+        None,
+        None,
+    );
+    let champion = table.standings.iter().max_by_key(|t| t.points).unwrap();
+    assert_eq!(season.data.team_names[champion.team_id], "FCB");
+    assert_eq!(champion.points, 82);
+}
+
+#[test]
+fn after_matchday_keeps_earlier_results_only() {
+    let data = HistoricalData::bundled();
+    let season = data.get("bundesliga", 2024).unwrap();
+    let half = season.after_matchday(17);
+    let played = half
+        .matches
+        .iter()
+        .filter(|m| m.goals_home.is_some())
+        .count();
+    assert_eq!(played, 17 * 9);
+    assert!(half
+        .matches
+        .iter()
+        .all(|m| m.goals_home.is_some() == (m.matchday.unwrap() <= 17)));
+}
+
+#[test]
+fn bundled_seasons_feed_fitting_and_backtests() {
+    let data = HistoricalData::bundled();
+    let season = &data.get("3_liga", 2024).unwrap().data.season;
+    assert!(fit_poisson_strengths(&season.matches, season.number_teams).is_some());
+
+    let report = backtest(
+        &data.backtest_seasons("bundesliga"),
+        &SimulationParams::default(),
+    );
+    assert_eq!(report.overall.matches, 306);
+    assert_eq!(report.seasons.len(), 1);
+}
+
+#[test]
+fn from_dir_reads_the_bundled_layout() {
+    let dir = tempdir().unwrap();
+    let season = dir.path().join("2023");
+    std::fs::create_dir(&season).unwrap();
+    std::fs::write(
+        season.join("TeamList.csv"),
+        "TeamID;ShortText;Promotion;InitialELO\n1;AAA;0;1600\n2;BBB;0;1400\n",
+    )
+    .unwrap();
+    std::fs::write(
+        season.join("bundesliga.csv"),
+        "home,away,goals_home,goals_away,matchday\nAAA,BBB,2,0,1\nBBB,AAA,1,1,2\n",
+    )
+    .unwrap();
+    std::fs::write(season.join("README.txt"), "skipped").unwrap();
+    std::fs::write(dir.path().join("notes.csv"), "skipped").unwrap();
+
+    let mut data = HistoricalData::bundled();
+    data.extend(HistoricalData::from_dir(dir.path()).unwrap());
+    let loaded = data.get("bundesliga", 2023).unwrap();
+    assert_eq!(loaded.data.team_names, ["AAA", "BBB"]);
+    assert_eq!(loaded.data.season.team_elos, [1600.0, 1400.0]);
+    assert_eq!(loaded.data.season.matches[1].matchday, Some(2));
+    assert_eq!(
+        data.league("bundesliga")
+            .map(|s| s.season)
+            .collect::<Vec<_>>(),
+        [2023, 2024]
+    );
+
+    std::fs::write(
+        season.join("3_liga.csv"),
+        "home,away,goals_home,goals_away\nAAA,ZZZ,1,0\n",
+    )
+    .unwrap();
+    let err = HistoricalData::from_dir(dir.path()).unwrap_err();
+    assert!(
+        matches!(&err, DatasetError::File { path, source: IngestionError::UnknownTeam { .. } } if path.ends_with("3_liga.csv")),
+        "{}",
+        err
+    );
+}
+
+#[cfg(feature = "storage")]
+#[test]
+fn history_file_round_trips_played_seasons() {
+    use crate::history::HistoryStore;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("history.bin");
+    let data = HistoricalData::bundled();
+    data.write_history(&path).unwrap();
+
+    let store = HistoryStore::open(&path).unwrap();
+    assert_eq!(store.leagues(), ["2_bundesliga", "3_liga", "bundesliga"]);
+    let loaded = HistoricalData::from_history(&store);
+    assert_eq!(loaded.seasons().len(), 3);
+    for season in data.seasons() {
+        let back = loaded.get(&season.league, season.season).unwrap();
+        assert_eq!(back.data.team_names, season.data.team_names);
+        assert_eq!(back.data.promotion, season.data.promotion);
+        assert_eq!(back.data.season.team_elos, season.data.season.team_elos);
+        assert_eq!(
+            back.data.season.matches.len(),
+            season.data.season.matches.len()
+        );
+        assert_eq!(back.matchdays(), season.matchdays());
+    }
+
+    // Matches come back ordered by matchday, which the backtest replays
+    // the same way.
+    let params = SimulationParams::default();
+    assert_eq!(
+        backtest(&store.backtest_seasons("bundesliga"), &params),
+        backtest(&data.backtest_seasons("bundesliga"), &params)
+    );
+}
+
+#[cfg(feature = "storage")]
+#[test]
+fn write_history_streams_a_dataset_directory() {
+    use crate::history::HistoryStore;
+
+    let dir = tempdir().unwrap();
+    for (year, result) in [("2023", "2,0"), ("2024", "0,3")] {
+        let season = dir.path().join(year);
+        std::fs::create_dir(&season).unwrap();
+        std::fs::write(
+            season.join("TeamList.csv"),
+            "TeamID;ShortText;Promotion;InitialELO\n1;AAA;0;1600\n2;BBB;0;1400\n",
+        )
+        .unwrap();
+        for league in ["bundesliga", "3_liga"] {
+            std::fs::write(
+                season.join(format!("{}.csv", league)),
+                format!(
+                    "home,away,goals_home,goals_away,matchday\nBBB,AAA,1,1,2\nAAA,BBB,{},1\n",
+                    result
+                ),
+            )
+            .unwrap();
+        }
+    }
+    let path = dir.path().join("history.bin");
+    write_history(dir.path(), &path).unwrap();
+
+    let store = HistoryStore::open(&path).unwrap();
+    assert_eq!(store.len(), 8);
+    let from_dir = HistoricalData::from_dir(dir.path()).unwrap();
+    let from_history = HistoricalData::from_history(&store);
+    assert_eq!(from_history.seasons().len(), 4);
+    let season = from_history.get("bundesliga", 2024).unwrap();
+    assert_eq!(season.data.season.matches[0].goals_away, Some(3));
+    assert_eq!(
+        season.data.season.team_elos,
+        from_dir
+            .get("bundesliga", 2024)
+            .unwrap()
+            .data
+            .season
+            .team_elos
+    );
+}
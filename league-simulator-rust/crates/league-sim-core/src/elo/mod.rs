@@ -0,0 +1,214 @@
+use crate::models::{EloParams, EloResult, Match};
+use crate::rating::{apply_rated_results, Elo};
+use std::collections::HashMap;
+
+/// Calculate ELO changes based on match result
+/// This matches the logic in SpielNichtSimulieren.cpp exactly
+pub fn calculate_elo_change(params: &EloParams) -> EloResult {
+    // Calculate ELO delta (inverted as in C++ code)
+    let elo_delta_inv = params.elo_away - params.elo_home - params.home_advantage;
+
+    // Clamp to [-400, 400] range as in C++ code
+    let elo_delta_inv_clamped = elo_delta_inv.max(-400.0).min(400.0);
+
+    // Calculate win probability for home team
+    let elo_prob = 1.0 / (1.0 + 10_f64.powf(elo_delta_inv_clamped / 400.0));
+
+    // Calculate actual result (0 = loss, 0.5 = draw, 1 = win)
+    let goal_diff = params.goals_home - params.goals_away;
+    let result = ((0 < goal_diff) as i32 - (goal_diff < 0) as i32 + 1) as f64 / 2.0;
+
+    // Goal difference modifier (square root of absolute goal difference, minimum 1)
+    let goal_mod = (goal_diff.abs().max(1) as f64).sqrt();
+
+    // Calculate ELO change
+    let elo_modificator = (result - elo_prob) * goal_mod * params.mod_factor;
+
+    EloResult {
+        new_elo_home: params.elo_home + elo_modificator,
+        new_elo_away: params.elo_away - elo_modificator,
+        goals_home: params.goals_home,
+        goals_away: params.goals_away,
+        win_probability_home: elo_prob,
+    }
+}
+
+/// `mod_factor` scaled by the weight of the match at `index` of the
+/// schedule, as set by `SimulationParams::mod_factor_weights`. Matches
+/// without a weight count once.
+pub fn weighted_mod_factor(mod_factor: f64, weights: Option<&[f64]>, index: usize) -> f64 {
+    mod_factor * weights.and_then(|w| w.get(index)).copied().unwrap_or(1.0)
+}
+
+/// Ratings after applying the played matches of `matches` in order to
+/// `elos`, indexed by team_id. Unplayed matches are skipped.
+pub fn apply_results(
+    elos: &[f64],
+    matches: &[Match],
+    mod_factor: f64,
+    home_advantage: f64,
+) -> Vec<f64> {
+    apply_weighted_results(elos, matches, mod_factor, None, home_advantage)
+}
+
+/// [`apply_results`] with `mod_factor` scaled per match by `weights`, one
+/// per entry of `matches` (see [`weighted_mod_factor`]). The [`Elo`]
+/// case of [`apply_rated_results`].
+pub fn apply_weighted_results(
+    elos: &[f64],
+    matches: &[Match],
+    mod_factor: f64,
+    weights: Option<&[f64]>,
+    home_advantage: f64,
+) -> Vec<f64> {
+    apply_rated_results(&Elo { mod_factor }, elos, matches, weights, home_advantage)
+}
+
+/// `elos` pulled towards their mean by `regression`:
+/// `mean + regression * (elo - mean)`. 1 keeps the ratings, 0 makes every
+/// team average. The total rating is unchanged.
+pub fn regress_to_mean(elos: &[f64], regression: f64) -> Vec<f64> {
+    if elos.is_empty() {
+        return Vec::new();
+    }
+    let mean = elos.iter().sum::<f64>() / elos.len() as f64;
+    elos.iter()
+        .map(|elo| mean + regression * (elo - mean))
+        .collect()
+}
+
+/// Ratings for the next season of a league: the `returning` teams followed
+/// by the `promoted` ones, regressed to the mean by `regression`.
+///
+/// Promoted teams without a rating take the mean of the `relegated` teams
+/// they replace (or of the returning teams if nobody went down), so a
+/// promoted side starts where the departed ones left off.
+pub fn roll_over_season(
+    returning: &[f64],
+    relegated: &[f64],
+    promoted: &[Option<f64>],
+    regression: f64,
+) -> Vec<f64> {
+    let mean = |elos: &[f64]| elos.iter().sum::<f64>() / elos.len().max(1) as f64;
+    let default = if relegated.is_empty() {
+        mean(returning)
+    } else {
+        mean(relegated)
+    };
+    let league: Vec<f64> = returning
+        .iter()
+        .copied()
+        .chain(promoted.iter().map(|elo| elo.unwrap_or(default)))
+        .collect();
+    regress_to_mean(&league, regression)
+}
+
+/// Ratings of teams from several leagues on one scale, for cups and
+/// playoffs between them: each rating plus the ELO offset of the team's
+/// league in `offsets`, e.g. `{"3_liga": -300}` when every league rates
+/// its teams around 1500. Teams without a league, or whose league has no
+/// offset, keep their rating.
+pub fn apply_league_offsets(
+    elos: &[f64],
+    leagues: &[Option<&str>],
+    offsets: &HashMap<String, f64>,
+) -> Vec<f64> {
+    elos.iter()
+        .enumerate()
+        .map(|(i, elo)| {
+            let league = leagues.get(i).copied().flatten();
+            elo + league.and_then(|l| offsets.get(l)).copied().unwrap_or(0.0)
+        })
+        .collect()
+}
+
+/// Starting ratings by final table position:
+/// `bottom + (top - bottom) * x^exponent`, where `x` falls evenly from 1
+/// for the champion to 0 for the last team.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionElo {
+    /// Rating of the champion
+    pub top: f64,
+    /// Rating of the last team
+    pub bottom: f64,
+    /// 1 spaces the ratings evenly; above 1 the gaps widen towards the
+    /// top of the table, below 1 towards the bottom
+    pub exponent: f64,
+}
+
+impl Default for PositionElo {
+    fn default() -> Self {
+        Self {
+            top: 1800.0,
+            bottom: 1400.0,
+            exponent: 1.0,
+        }
+    }
+}
+
+impl PositionElo {
+    /// Rating of 1-based `position` in a table of `number_teams`.
+    pub fn rating(&self, position: usize, number_teams: usize) -> f64 {
+        if number_teams < 2 {
+            return self.top;
+        }
+        let x = (number_teams - position.clamp(1, number_teams)) as f64 / (number_teams - 1) as f64;
+        self.bottom + (self.top - self.bottom) * x.powf(self.exponent)
+    }
+}
+
+/// Ratings for the kickoff of a season from the final table of the last
+/// one: the `returning` teams, given by their final position (1-based, in
+/// a table of `number_teams`), followed by the `promoted` ones.
+///
+/// Promoted teams without a rating take the mean rating of the bottom
+/// places they fill, one per promoted team, so they start where the
+/// departed sides would have.
+pub fn elos_from_positions(
+    returning: &[usize],
+    number_teams: usize,
+    promoted: &[Option<f64>],
+    formula: &PositionElo,
+) -> Vec<f64> {
+    let filled = promoted.len().clamp(1, number_teams.max(1));
+    let default = (number_teams + 1 - filled..=number_teams)
+        .map(|position| formula.rating(position, number_teams))
+        .sum::<f64>()
+        / filled as f64;
+    returning
+        .iter()
+        .map(|&position| formula.rating(position, number_teams))
+        .chain(promoted.iter().map(|elo| elo.unwrap_or(default)))
+        .collect()
+}
+
+/// Starting ratings from several seasons of results instead of a
+/// hand-kept rating table.
+///
+/// Every team of `number_teams` (ids shared across `seasons`) starts at
+/// `initial_elo`; the seasons, oldest first, are replayed with the Elo
+/// formula. Between seasons every rating is regressed to the mean by
+/// `0.5^(1 / half_life)`, so a result's effect on the final ratings halves
+/// with every `half_life` seasons of age, and the guesswork of the starting
+/// values fades the same way. An infinite `half_life` replays without decay.
+pub fn bootstrap_elos(
+    seasons: &[Vec<Match>],
+    number_teams: usize,
+    initial_elo: f64,
+    half_life: f64,
+    mod_factor: f64,
+    home_advantage: f64,
+) -> Vec<f64> {
+    let decay = 0.5f64.powf(1.0 / half_life);
+    let mut elos = vec![initial_elo; number_teams];
+    for (i, season) in seasons.iter().enumerate() {
+        if i > 0 {
+            elos = regress_to_mean(&elos, decay);
+        }
+        elos = apply_results(&elos, season, mod_factor, home_advantage);
+    }
+    elos
+}
+
+#[cfg(test)]
+mod tests;
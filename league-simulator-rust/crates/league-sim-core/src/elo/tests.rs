@@ -240,3 +240,154 @@ fn test_home_advantage_effect() {
         "Winning with home advantage should produce smaller ELO gain"
     );
 }
+
+#[test]
+fn apply_results_skips_unplayed_matches() {
+    let played = |home, away, goals: Option<(i32, i32)>| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: goals.map(|g| g.0),
+        goals_away: goals.map(|g| g.1),
+        matchday: None,
+        competition: None,
+        weight: None,
+    };
+    let matches = [
+        played(0, 1, Some((2, 0))),
+        played(1, 2, None),
+        played(2, 0, Some((1, 1))),
+    ];
+    let elos = apply_results(&[1500.0, 1500.0, 1500.0], &matches, 20.0, 65.0);
+
+    let first = calculate_elo_change(&EloParams {
+        elo_home: 1500.0,
+        elo_away: 1500.0,
+        goals_home: 2,
+        goals_away: 0,
+        mod_factor: 20.0,
+        home_advantage: 65.0,
+    });
+    let third = calculate_elo_change(&EloParams {
+        elo_home: 1500.0,
+        elo_away: first.new_elo_home,
+        goals_home: 1,
+        goals_away: 1,
+        mod_factor: 20.0,
+        home_advantage: 65.0,
+    });
+    assert_relative_eq!(elos[0], third.new_elo_away);
+    assert_relative_eq!(elos[1], first.new_elo_away);
+    assert_relative_eq!(elos[2], third.new_elo_home);
+    // Ratings are zero-sum.
+    assert_relative_eq!(elos.iter().sum::<f64>(), 4500.0, epsilon = 1e-9);
+}
+
+#[test]
+fn regression_pulls_ratings_to_the_mean_and_keeps_the_total() {
+    let elos = [1900.0, 1600.0, 1400.0, 1300.0];
+    let regressed = regress_to_mean(&elos, 0.5);
+    // Mean 1550.
+    assert_relative_eq!(regressed[0], 1725.0);
+    assert_relative_eq!(regressed[3], 1425.0);
+    assert_relative_eq!(regressed.iter().sum::<f64>(), elos.iter().sum::<f64>());
+    assert_eq!(regress_to_mean(&elos, 1.0), elos);
+    assert!(regress_to_mean(&[], 0.5).is_empty());
+}
+
+#[test]
+fn promoted_teams_inherit_the_relegated_mean() {
+    let next = roll_over_season(
+        &[1800.0, 1500.0],
+        &[1300.0, 1200.0],
+        &[None, Some(1400.0)],
+        1.0,
+    );
+    assert_eq!(next, [1800.0, 1500.0, 1250.0, 1400.0]);
+
+    // Without relegations the league mean is used.
+    let next = roll_over_season(&[1800.0, 1600.0], &[], &[None], 1.0);
+    assert_eq!(next, [1800.0, 1600.0, 1700.0]);
+
+    let next = roll_over_season(&[1800.0, 1500.0], &[1300.0], &[None], 0.0);
+    let mean = (1800.0 + 1500.0 + 1300.0) / 3.0;
+    assert!(next.iter().all(|&elo| (elo - mean).abs() < 1e-9));
+}
+
+#[test]
+fn league_offsets_shift_ratings_by_league() {
+    let offsets = std::collections::HashMap::from([
+        ("bundesliga".to_string(), 0.0),
+        ("3_liga".to_string(), -300.0),
+    ]);
+    let elos = apply_league_offsets(
+        &[1500.0, 1500.0, 1500.0, 1500.0],
+        &[
+            Some("bundesliga"),
+            Some("3_liga"),
+            None,
+            Some("regionalliga"),
+        ],
+        &offsets,
+    );
+    assert_eq!(elos, [1500.0, 1200.0, 1500.0, 1500.0]);
+}
+
+#[test]
+fn kickoff_ratings_follow_the_final_table() {
+    let linear = PositionElo::default();
+    assert_eq!(linear.rating(1, 5), 1800.0);
+    assert_eq!(linear.rating(3, 5), 1600.0);
+    assert_eq!(linear.rating(5, 5), 1400.0);
+
+    // Steeper at the top: the champion's lead over second grows.
+    let steep = PositionElo {
+        exponent: 2.0,
+        ..linear
+    };
+    assert_eq!(steep.rating(3, 5), 1500.0);
+    assert!(steep.rating(1, 5) - steep.rating(2, 5) > linear.rating(1, 5) - linear.rating(2, 5));
+
+    // Positions 1, 3 and 2 return; two promoted teams replace 4 and 5,
+    // the first at their mean rating, the second at its own.
+    let next = elos_from_positions(&[1, 3, 2], 5, &[None, Some(1450.0)], &linear);
+    assert_eq!(next, [1800.0, 1600.0, 1700.0, 1450.0, 1450.0]);
+
+    let next = elos_from_positions(&[2, 1], 3, &[None], &linear);
+    assert_eq!(next, [1600.0, 1800.0, 1400.0]);
+}
+
+#[test]
+fn bootstrap_weighs_recent_seasons_more() {
+    let result = |home, away, goals_home, goals_away| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: Some(goals_home),
+        goals_away: Some(goals_away),
+        matchday: None,
+        competition: None,
+        weight: None,
+    };
+    // Team 0 dominated two seasons ago and has lost since.
+    let seasons = vec![
+        vec![result(0, 1, 4, 0), result(1, 0, 0, 3)],
+        vec![result(0, 1, 0, 1)],
+        vec![result(1, 0, 2, 0)],
+    ];
+
+    // Without decay it is a plain replay of every result.
+    let flat: Vec<Match> = seasons.concat();
+    assert_eq!(
+        bootstrap_elos(&seasons, 2, 1500.0, f64::INFINITY, 20.0, 65.0),
+        apply_results(&[1500.0, 1500.0], &flat, 20.0, 65.0)
+    );
+
+    let long = bootstrap_elos(&seasons, 2, 1500.0, 10.0, 20.0, 65.0);
+    let short = bootstrap_elos(&seasons, 2, 1500.0, 0.5, 20.0, 65.0);
+    assert!(short[0] < long[0]);
+    assert_relative_eq!(short.iter().sum::<f64>(), 3000.0, epsilon = 1e-9);
+    // Teams that never played keep the initial rating.
+    assert_eq!(
+        bootstrap_elos(&seasons, 3, 1500.0, 2.0, 20.0, 65.0)[2],
+        1500.0
+    );
+}
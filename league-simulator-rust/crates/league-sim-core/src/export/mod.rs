@@ -0,0 +1,109 @@
+//! Parquet export of sampled seasons for analysis in R (`arrow::read_parquet`)
+//! or Python (`pandas.read_parquet`) without re-running the simulation.
+//!
+//! The file has one row per (iteration, team), iteration-major:
+//!
+//! ```text
+//! iteration  INT32   0-based
+//! team_id    INT32   0-based index into the request's teams
+//! team       UTF8
+//! position   INT32   1-based final position
+//! points     INT32   final points, adjustments included
+//! ```
+
+use crate::monte_carlo::SeasonSample;
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, Int32Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::io::Write;
+use std::sync::Arc;
+
+const SCHEMA: &str = "
+message season_samples {
+    REQUIRED INT32 iteration;
+    REQUIRED INT32 team_id;
+    REQUIRED BYTE_ARRAY team (UTF8);
+    REQUIRED INT32 position;
+    REQUIRED INT32 points;
+}
+";
+
+/// Rows per row group; bounds the writer's memory on long runs.
+const ROW_GROUP_ITERATIONS: usize = 50_000;
+
+/// Write `samples` to `out` as a Snappy-compressed Parquet file and return
+/// `out`. Teams beyond `team_names` are named "Team <n>".
+pub fn write_samples_parquet<W: Write + Send>(
+    samples: &[SeasonSample],
+    team_names: &[String],
+    out: W,
+) -> Result<W, ParquetError> {
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(out, schema, properties)?;
+
+    let n_teams = samples.first().map_or(0, |s| s.positions.len());
+    let names: Vec<ByteArray> = (0..n_teams)
+        .map(|team_id| match team_names.get(team_id) {
+            Some(name) => ByteArray::from(name.as_str()),
+            None => ByteArray::from(format!("Team {}", team_id + 1).as_str()),
+        })
+        .collect();
+
+    for (chunk_index, chunk) in samples.chunks(ROW_GROUP_ITERATIONS).enumerate() {
+        let first = chunk_index * ROW_GROUP_ITERATIONS;
+        let iterations: Vec<i32> = (0..chunk.len())
+            .flat_map(|i| std::iter::repeat_n((first + i) as i32, n_teams))
+            .collect();
+        let team_ids: Vec<i32> = (0..chunk.len()).flat_map(|_| 0..n_teams as i32).collect();
+        let teams: Vec<ByteArray> = (0..chunk.len())
+            .flat_map(|_| names.iter().cloned())
+            .collect();
+        let positions: Vec<i32> = chunk
+            .iter()
+            .flat_map(|s| s.positions.iter().map(|&p| p as i32))
+            .collect();
+        let points: Vec<i32> = chunk
+            .iter()
+            .flat_map(|s| s.points.iter().copied())
+            .collect();
+
+        let mut row_group = writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            match index {
+                2 => {
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&teams, None, None)?;
+                }
+                _ => {
+                    let values = match index {
+                        0 => &iterations,
+                        1 => &team_ids,
+                        3 => &positions,
+                        _ => &points,
+                    };
+                    column
+                        .typed::<Int32Type>()
+                        .write_batch(values, None, None)?;
+                }
+            }
+            column.close()?;
+            index += 1;
+        }
+        row_group.close()?;
+    }
+
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests;
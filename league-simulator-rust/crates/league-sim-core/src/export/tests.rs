@@ -0,0 +1,47 @@
+use super::*;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::RowAccessor;
+
+#[test]
+fn samples_round_trip_one_row_per_iteration_and_team() {
+    let samples = vec![
+        SeasonSample {
+            positions: vec![1, 2],
+            points: vec![6, 3],
+        },
+        SeasonSample {
+            positions: vec![2, 1],
+            points: vec![1, 4],
+        },
+    ];
+    let names = vec!["Bayern".to_string()];
+
+    let mut file = tempfile::tempfile().unwrap();
+    write_samples_parquet(&samples, &names, &mut file).unwrap();
+
+    let reader = SerializedFileReader::new(file).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 4);
+    let rows: Vec<(i32, i32, String, i32, i32)> = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            (
+                row.get_int(0).unwrap(),
+                row.get_int(1).unwrap(),
+                row.get_string(2).unwrap().clone(),
+                row.get_int(3).unwrap(),
+                row.get_int(4).unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (0, 0, "Bayern".to_string(), 1, 6),
+            (0, 1, "Team 2".to_string(), 2, 3),
+            (1, 0, "Bayern".to_string(), 2, 1),
+            (1, 1, "Team 2".to_string(), 1, 4),
+        ]
+    );
+}
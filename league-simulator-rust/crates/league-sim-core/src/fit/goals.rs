@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, StudentsT};
+
+/// Goals a team scored against an opponent `elo_delta` ELO points weaker,
+/// home advantage included (negative for a stronger opponent).
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GoalObservation {
+    pub elo_delta: f64,
+    pub goals: f64,
+}
+
+impl GoalObservation {
+    /// The two observations of a played match: the home side's goals at
+    /// `elo_home + home_advantage - elo_away`, the away side's at the
+    /// negated delta, as the goal model of
+    /// [`expected_goals`](crate::simulation::expected_goals) sees them.
+    pub fn from_match(
+        elo_home: f64,
+        elo_away: f64,
+        home_advantage: f64,
+        goals_home: i32,
+        goals_away: i32,
+    ) -> [Self; 2] {
+        let elo_delta = elo_home + home_advantage - elo_away;
+        [
+            Self {
+                elo_delta,
+                goals: goals_home.into(),
+            },
+            Self {
+                elo_delta: -elo_delta,
+                goals: goals_away.into(),
+            },
+        ]
+    }
+}
+
+/// Goal model parameters refitted by least squares, with confidence
+/// intervals.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoalModelFit {
+    /// Expected goals per ELO point of difference
+    pub tore_slope: f64,
+    /// Expected goals between equal teams
+    pub tore_intercept: f64,
+    /// Lower and upper confidence bound of `tore_slope`
+    pub slope_interval: [f64; 2],
+    /// Lower and upper confidence bound of `tore_intercept`
+    pub intercept_interval: [f64; 2],
+    pub slope_standard_error: f64,
+    pub intercept_standard_error: f64,
+    /// Standard deviation of the goals around the fitted line
+    pub residual_sd: f64,
+    /// Confidence level of the intervals, e.g. 0.95
+    pub confidence: f64,
+    pub observations: usize,
+}
+
+/// Ordinary least squares fit of `goals = tore_slope * elo_delta +
+/// tore_intercept`, the regression the built-in constants of
+/// [`SimulationParams`](crate::models::SimulationParams) came from.
+///
+/// The intervals are the usual t intervals at `confidence`. None with fewer
+/// than three observations, when every `elo_delta` is the same, or when
+/// `confidence` is not strictly between 0 and 1.
+pub fn fit_goal_model(observations: &[GoalObservation], confidence: f64) -> Option<GoalModelFit> {
+    let n = observations.len();
+    if n < 3 || !(confidence > 0.0 && confidence < 1.0) {
+        return None;
+    }
+    let count = n as f64;
+    let mean_x = observations.iter().map(|o| o.elo_delta).sum::<f64>() / count;
+    let mean_y = observations.iter().map(|o| o.goals).sum::<f64>() / count;
+    let (mut sxx, mut sxy) = (0.0, 0.0);
+    for o in observations {
+        sxx += (o.elo_delta - mean_x).powi(2);
+        sxy += (o.elo_delta - mean_x) * (o.goals - mean_y);
+    }
+    if sxx <= 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+
+    let residuals: f64 = observations
+        .iter()
+        .map(|o| (o.goals - intercept - slope * o.elo_delta).powi(2))
+        .sum();
+    let df = count - 2.0;
+    let sigma = (residuals / df).sqrt();
+    let slope_se = sigma / sxx.sqrt();
+    let intercept_se = sigma * (1.0 / count + mean_x.powi(2) / sxx).sqrt();
+    let t = StudentsT::new(0.0, 1.0, df)
+        .ok()?
+        .inverse_cdf((1.0 + confidence) / 2.0);
+
+    Some(GoalModelFit {
+        tore_slope: slope,
+        tore_intercept: intercept,
+        slope_interval: [slope - t * slope_se, slope + t * slope_se],
+        intercept_interval: [intercept - t * intercept_se, intercept + t * intercept_se],
+        slope_standard_error: slope_se,
+        intercept_standard_error: intercept_se,
+        residual_sd: sigma,
+        confidence,
+        observations: n,
+    })
+}
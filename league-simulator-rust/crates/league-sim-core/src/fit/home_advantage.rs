@@ -0,0 +1,150 @@
+use crate::elo::calculate_elo_change;
+use crate::models::{EloParams, Season, SimulationParams};
+use crate::simulation::outcome_probabilities;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Home advantages searched by default, in ELO points. Wide enough for
+/// leagues played without crowds as well as for the strongest home sides.
+pub const HOME_ADVANTAGE_RANGE: RangeInclusive<f64> = -100.0..=300.0;
+
+/// Home advantage fitted to historical results.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HomeAdvantageFit {
+    /// Home advantage in ELO points maximising the log-likelihood
+    pub home_advantage: f64,
+    /// Approximate standard error from the curvature of the log-likelihood
+    /// at the optimum; None when it is flat there
+    pub standard_error: Option<f64>,
+    /// Log-likelihood of the observed outcomes at the fitted value
+    pub log_likelihood: f64,
+    /// Played matches the fit is based on
+    pub matches: usize,
+}
+
+/// Log-likelihood of the win/draw/loss outcomes of the played matches of
+/// `seasons` under `home_advantage`.
+///
+/// Each season is replayed from its `team_elos` in schedule order: a match
+/// is scored with the probabilities of
+/// [`outcome_probabilities`] at the ratings current at that point, then its
+/// result updates the ratings with the same home advantage, so every match
+/// is predicted from the results before it only. Unplayed matches are
+/// skipped. `tore_slope`, `tore_intercept` and `mod_factor` come from
+/// `params`.
+pub fn home_advantage_log_likelihood(
+    seasons: &[Season],
+    params: &SimulationParams,
+    home_advantage: f64,
+) -> f64 {
+    let mut log_likelihood = 0.0;
+    for season in seasons {
+        let mut elos = season.team_elos.clone();
+        for m in &season.matches {
+            let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+                continue;
+            };
+            let (home, away) = (m.team_home, m.team_away);
+            let probs = outcome_probabilities(
+                elos[home],
+                elos[away],
+                home_advantage,
+                params.tore_slope,
+                params.tore_intercept,
+            );
+            let p = match goals_home.cmp(&goals_away) {
+                std::cmp::Ordering::Greater => probs.home_win,
+                std::cmp::Ordering::Equal => probs.draw,
+                std::cmp::Ordering::Less => probs.away_win,
+            };
+            log_likelihood += p.max(f64::MIN_POSITIVE).ln();
+
+            let result = calculate_elo_change(&EloParams {
+                elo_home: elos[home],
+                elo_away: elos[away],
+                goals_home,
+                goals_away,
+                mod_factor: params.mod_factor,
+                home_advantage,
+            });
+            elos[home] = result.new_elo_home;
+            elos[away] = result.new_elo_away;
+        }
+    }
+    log_likelihood
+}
+
+/// Home advantage within `range` that best predicts the played matches of
+/// `seasons`, by [`home_advantage_log_likelihood`].
+///
+/// A grid in steps of 5 ELO points, coarser for ranges wider than 1000
+/// points so it never has more than 200 steps, locates the best region,
+/// which a golden-section search narrows to 0.01. Without played matches the fit
+/// falls back to `params.home_advantage`, clamped to `range`. Pool several
+/// leagues by passing all their seasons, or fit each league on its own.
+pub fn fit_home_advantage(
+    seasons: &[Season],
+    params: &SimulationParams,
+    range: RangeInclusive<f64>,
+) -> HomeAdvantageFit {
+    const GRID_STEP: f64 = 5.0;
+    const MAX_GRID_STEPS: f64 = 200.0;
+    const TOLERANCE: f64 = 0.01;
+
+    let (lo, hi) = (*range.start(), *range.end());
+    let matches = seasons
+        .iter()
+        .flat_map(|season| &season.matches)
+        .filter(|m| m.goals_home.is_some() && m.goals_away.is_some())
+        .count();
+    if matches == 0 {
+        return HomeAdvantageFit {
+            home_advantage: params.home_advantage.clamp(lo, hi),
+            standard_error: None,
+            log_likelihood: 0.0,
+            matches,
+        };
+    }
+    let log_likelihood = |h: f64| home_advantage_log_likelihood(seasons, params, h);
+
+    let step = GRID_STEP.max((hi - lo) / MAX_GRID_STEPS);
+    let steps = ((hi - lo) / step).ceil().clamp(0.0, MAX_GRID_STEPS) as usize;
+    let grid: Vec<f64> = (0..=steps)
+        .map(|i| (lo + i as f64 * step).min(hi))
+        .collect();
+    let scores: Vec<f64> = grid.iter().map(|&h| log_likelihood(h)).collect();
+    let best = (0..grid.len())
+        .max_by(|&a, &b| scores[a].total_cmp(&scores[b]).then(b.cmp(&a)))
+        .unwrap_or(0);
+
+    let inv_phi = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (grid[best.saturating_sub(1)], grid[(best + 1).min(steps)]);
+    let mut c = b - inv_phi * (b - a);
+    let mut d = a + inv_phi * (b - a);
+    let (mut fc, mut fd) = (log_likelihood(c), log_likelihood(d));
+    while b - a > TOLERANCE {
+        if fc >= fd {
+            (b, d, fd) = (d, c, fc);
+            c = b - inv_phi * (b - a);
+            fc = log_likelihood(c);
+        } else {
+            (a, c, fc) = (c, d, fd);
+            d = a + inv_phi * (b - a);
+            fd = log_likelihood(d);
+        }
+    }
+    let home_advantage = (a + b) / 2.0;
+    let fitted = log_likelihood(home_advantage);
+
+    // Observed information: the negative second derivative, by central
+    // differences one ELO point either side.
+    let curvature =
+        log_likelihood(home_advantage + 1.0) - 2.0 * fitted + log_likelihood(home_advantage - 1.0);
+    HomeAdvantageFit {
+        home_advantage,
+        standard_error: (curvature < 0.0).then(|| (-1.0 / curvature).sqrt()),
+        log_likelihood: fitted,
+        matches,
+    }
+}
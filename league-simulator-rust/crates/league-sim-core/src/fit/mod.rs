@@ -0,0 +1,10 @@
+mod goals;
+mod home_advantage;
+mod poisson;
+
+pub use goals::*;
+pub use home_advantage::*;
+pub use poisson::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,192 @@
+use crate::models::{Match, PoissonStrengths};
+use serde::{Deserialize, Serialize};
+use statrs::function::factorial::ln_factorial;
+
+/// Iterations of the reweighting before giving up on convergence.
+const MAX_ITERATIONS: usize = 100;
+
+/// Largest coefficient change at which the fit counts as converged.
+const TOLERANCE: f64 = 1e-8;
+
+/// Ridge penalty on the team coefficients. Negligible against the
+/// information of a season of matches, it keeps a team that never scored
+/// (or never conceded) at a finite coefficient.
+const RIDGE: f64 = 1e-3;
+
+/// [`PoissonStrengths`] fitted to played matches.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoissonStrengthFit {
+    #[serde(flatten)]
+    pub strengths: PoissonStrengths,
+    /// Log-likelihood of the observed goals at the fitted coefficients
+    pub log_likelihood: f64,
+    /// Played matches the fit is based on
+    pub matches: usize,
+    /// Reweighting iterations until convergence
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Fit per-team attack and defense coefficients and a home advantage to
+/// the played matches of `matches` by Poisson regression with a log link,
+/// the model of Maher (1982) and Dixon and Coles (1997) without the
+/// low-score correction.
+///
+/// Every match gives two observations, the home goals and the away goals.
+/// The coefficients are found by iteratively reweighted least squares with
+/// team 0 as the reference, then centred so that attack and defense each
+/// sum to zero. None without played matches or with fewer than two teams.
+pub fn fit_poisson_strengths(matches: &[Match], number_teams: usize) -> Option<PoissonStrengthFit> {
+    let observations: Vec<Observation> = matches
+        .iter()
+        .filter_map(|m| {
+            let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+                return None;
+            };
+            Some([
+                Observation {
+                    home: true,
+                    attack: m.team_home,
+                    defense: m.team_away,
+                    goals: goals_home.into(),
+                },
+                Observation {
+                    home: false,
+                    attack: m.team_away,
+                    defense: m.team_home,
+                    goals: goals_away.into(),
+                },
+            ])
+        })
+        .flatten()
+        .collect();
+    if observations.is_empty() || number_teams < 2 {
+        return None;
+    }
+
+    // Columns: intercept, home advantage, attack of teams 1.., defense of
+    // teams 1..; team 0 is the reference at zero.
+    let columns = 2 * number_teams;
+    let mean_goals = observations.iter().map(|o| o.goals).sum::<f64>() / observations.len() as f64;
+    let mut beta = vec![0.0; columns];
+    beta[0] = mean_goals.max(0.1).ln();
+
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < MAX_ITERATIONS && !converged {
+        iterations += 1;
+        let mut information = vec![vec![0.0; columns]; columns];
+        let mut score = vec![0.0; columns];
+        for (c, row) in information.iter_mut().enumerate().skip(2) {
+            row[c] = RIDGE;
+        }
+        for o in &observations {
+            let cols = o.columns(number_teams);
+            let eta: f64 = cols.iter().flatten().map(|&c| beta[c]).sum();
+            let mu = eta.exp();
+            let z = eta + (o.goals - mu) / mu;
+            for &a in cols.iter().flatten() {
+                score[a] += mu * z;
+                for &b in cols.iter().flatten() {
+                    information[a][b] += mu;
+                }
+            }
+        }
+        let next = solve_symmetric(information, score)?;
+        converged = next
+            .iter()
+            .zip(&beta)
+            .all(|(a, b)| (a - b).abs() < TOLERANCE);
+        beta = next;
+    }
+
+    let n = number_teams;
+    let mut attack: Vec<f64> = std::iter::once(0.0)
+        .chain(beta[2..n + 1].iter().copied())
+        .collect();
+    let mut defense: Vec<f64> = std::iter::once(0.0)
+        .chain(beta[n + 1..].iter().copied())
+        .collect();
+    let mut intercept = beta[0];
+    for coefficients in [&mut attack, &mut defense] {
+        let mean = coefficients.iter().sum::<f64>() / n as f64;
+        coefficients.iter_mut().for_each(|c| *c -= mean);
+        intercept += mean;
+    }
+    let strengths = PoissonStrengths {
+        intercept,
+        home_advantage: beta[1],
+        attack,
+        defense,
+    };
+
+    let log_likelihood = observations
+        .iter()
+        .map(|o| {
+            let eta = strengths.intercept
+                + if o.home {
+                    strengths.home_advantage
+                } else {
+                    0.0
+                }
+                + strengths.attack[o.attack]
+                + strengths.defense[o.defense];
+            o.goals * eta - eta.exp() - ln_factorial(o.goals as u64)
+        })
+        .sum();
+
+    Some(PoissonStrengthFit {
+        strengths,
+        log_likelihood,
+        matches: observations.len() / 2,
+        iterations,
+        converged,
+    })
+}
+
+/// Goals of one side of a match.
+struct Observation {
+    home: bool,
+    /// Team scoring the goals
+    attack: usize,
+    /// Team conceding them
+    defense: usize,
+    goals: f64,
+}
+
+impl Observation {
+    /// Design matrix columns with a 1 in this observation's row.
+    fn columns(&self, number_teams: usize) -> [Option<usize>; 4] {
+        [
+            Some(0),
+            self.home.then_some(1),
+            (self.attack > 0).then(|| 1 + self.attack),
+            (self.defense > 0).then(|| number_teams + self.defense),
+        ]
+    }
+}
+
+/// Solve `a x = b` for a symmetric positive definite `a` by Cholesky
+/// decomposition. None if `a` is not positive definite.
+fn solve_symmetric(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for j in 0..n {
+        let diagonal = a[j][j] - (0..j).map(|k| a[j][k] * a[j][k]).sum::<f64>();
+        if diagonal <= 0.0 || !diagonal.is_finite() {
+            return None;
+        }
+        a[j][j] = diagonal.sqrt();
+        for i in j + 1..n {
+            let off = a[i][j] - (0..j).map(|k| a[i][k] * a[j][k]).sum::<f64>();
+            a[i][j] = off / a[j][j];
+        }
+    }
+    for i in 0..n {
+        b[i] = (b[i] - (0..i).map(|k| a[i][k] * b[k]).sum::<f64>()) / a[i][i];
+    }
+    for i in (0..n).rev() {
+        b[i] = (b[i] - (i + 1..n).map(|k| a[k][i] * b[k]).sum::<f64>()) / a[i][i];
+    }
+    Some(b)
+}
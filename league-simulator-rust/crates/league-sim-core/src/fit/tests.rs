@@ -0,0 +1,174 @@
+use super::*;
+use crate::models::{Season, SimulationParams};
+use crate::simulation::{double_round_robin, simulate_season};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Played seasons of an 18 team league, simulated with `home_advantage`.
+fn history(home_advantage: f64, seasons: usize) -> Vec<Season> {
+    let params = SimulationParams::default();
+    let mut rng = StdRng::seed_from_u64(7);
+    (0..seasons)
+        .map(|_| {
+            let season = Season {
+                matches: double_round_robin(18),
+                team_elos: (0..18).map(|i| 1300.0 + 25.0 * i as f64).collect(),
+                number_teams: 18,
+            };
+            let (matches, _) = simulate_season(
+                &season,
+                params.mod_factor,
+                None,
+                home_advantage,
+                params.tore_slope,
+                params.tore_intercept,
+                &mut rng,
+            );
+            Season { matches, ..season }
+        })
+        .collect()
+}
+
+#[test]
+fn home_advantage_fit_recovers_the_simulated_value() {
+    let seasons = history(120.0, 12);
+    let fit = fit_home_advantage(&seasons, &SimulationParams::default(), HOME_ADVANTAGE_RANGE);
+
+    assert_eq!(fit.matches, 12 * 306);
+    let se = fit.standard_error.expect("curved likelihood");
+    assert!(se > 0.0 && se < 30.0, "standard error {}", se);
+    assert!(
+        (fit.home_advantage - 120.0).abs() < 3.0 * se,
+        "fitted {} ± {}",
+        fit.home_advantage,
+        se
+    );
+    for h in [0.0, 65.0, 200.0] {
+        assert!(
+            home_advantage_log_likelihood(&seasons, &SimulationParams::default(), h)
+                <= fit.log_likelihood
+        );
+    }
+}
+
+#[test]
+fn home_advantage_fit_without_results_keeps_the_configured_value() {
+    let season = Season {
+        matches: double_round_robin(4),
+        team_elos: vec![1500.0; 4],
+        number_teams: 4,
+    };
+    let fit = fit_home_advantage(&[season], &SimulationParams::default(), 0.0..=50.0);
+    assert_eq!(fit.home_advantage, 50.0);
+    assert_eq!(fit.matches, 0);
+    assert!(fit.standard_error.is_none());
+}
+
+#[test]
+fn home_advantage_fit_caps_the_grid_of_wide_ranges() {
+    let seasons = history(120.0, 1);
+    let fit = fit_home_advantage(&seasons, &SimulationParams::default(), -1e300..=1e300);
+    assert!(fit.home_advantage.is_finite());
+    assert_eq!(fit.matches, 306);
+}
+
+#[test]
+fn goal_model_fit_recovers_the_built_in_constants() {
+    // Goals sampled from the engine's own model should give back its
+    // slope and intercept within the reported intervals.
+    let params = SimulationParams::default();
+    let mut observations = Vec::new();
+    for season in history(params.home_advantage, 8) {
+        let mut elos = season.team_elos.clone();
+        for m in &season.matches {
+            let (goals_home, goals_away) = (m.goals_home.unwrap(), m.goals_away.unwrap());
+            observations.extend(GoalObservation::from_match(
+                elos[m.team_home],
+                elos[m.team_away],
+                params.home_advantage,
+                goals_home,
+                goals_away,
+            ));
+            elos = crate::elo::apply_results(
+                &elos,
+                std::slice::from_ref(m),
+                params.mod_factor,
+                params.home_advantage,
+            );
+        }
+    }
+
+    let fit = fit_goal_model(&observations, 0.95).unwrap();
+    assert_eq!(fit.observations, 8 * 306 * 2);
+    let [lo, hi] = fit.slope_interval;
+    assert!(
+        lo < params.tore_slope && params.tore_slope < hi,
+        "{:?}",
+        fit
+    );
+    let [lo, hi] = fit.intercept_interval;
+    assert!(
+        lo < params.tore_intercept && params.tore_intercept < hi,
+        "{:?}",
+        fit
+    );
+
+    let wider = fit_goal_model(&observations, 0.99).unwrap();
+    assert!(wider.slope_interval[0] < fit.slope_interval[0]);
+    assert_eq!(wider.tore_slope, fit.tore_slope);
+}
+
+#[test]
+fn goal_model_fit_needs_spread_in_the_ratings() {
+    let flat = [GoalObservation {
+        elo_delta: 0.0,
+        goals: 1.0,
+    }; 5];
+    assert!(fit_goal_model(&flat, 0.95).is_none());
+    assert!(fit_goal_model(&flat[..2], 0.95).is_none());
+}
+
+#[test]
+fn poisson_fit_recovers_attack_defense_and_home_advantage() {
+    use crate::models::PoissonStrengths;
+    use crate::rating::Elo;
+    use crate::simulation::simulate_season_rated;
+
+    let truth = PoissonStrengths {
+        intercept: 0.25,
+        home_advantage: 0.3,
+        attack: vec![0.4, 0.2, 0.0, 0.0, -0.2, -0.4],
+        defense: vec![-0.3, -0.1, 0.0, 0.1, 0.1, 0.2],
+    };
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut matches = Vec::new();
+    for _ in 0..60 {
+        let mut season = double_round_robin(6);
+        simulate_season_rated(
+            &Elo { mod_factor: 20.0 },
+            &truth,
+            &mut season,
+            &mut [1500.0; 6],
+            None,
+            0.0,
+            &mut rng,
+            |_, _| {},
+        );
+        matches.extend(season);
+    }
+
+    let fit = fit_poisson_strengths(&matches, 6).expect("played matches");
+    assert!(fit.converged);
+    assert_eq!(fit.matches, 60 * 30);
+    let s = &fit.strengths;
+    assert!((s.home_advantage - truth.home_advantage).abs() < 0.05);
+    assert!((s.intercept - truth.intercept).abs() < 0.05);
+    for team in 0..6 {
+        assert!((s.attack[team] - truth.attack[team]).abs() < 0.1, "{s:?}");
+        assert!((s.defense[team] - truth.defense[team]).abs() < 0.1, "{s:?}");
+    }
+    assert!(s.attack.iter().sum::<f64>().abs() < 1e-9);
+    assert!(s.defense.iter().sum::<f64>().abs() < 1e-9);
+
+    assert!(fit_poisson_strengths(&double_round_robin(6), 6).is_none());
+}
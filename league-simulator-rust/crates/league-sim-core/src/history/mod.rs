@@ -0,0 +1,555 @@
+//! Compact binary store of historical match results for backtests.
+//!
+//! Calibration jobs replay decades of results, so instead of reparsing CSVs
+//! on every run the results are written once into a single file that is
+//! memory-mapped on open. The file holds fixed-width little-endian records
+//! sorted by (league, season, date, matchday), an index of every
+//! (league, season) pair with its record range and team list, the team
+//! lists with each team's rating before the season, and the team and
+//! league names:
+//!
+//! ```text
+//! header   64 bytes   magic, version, section counts, names offset
+//! records  20 bytes   one per played match
+//! index    40 bytes   one per (league, season)
+//! teams    16 bytes   one per team of a season: id, promotion, rating
+//! names    u32 length + UTF-8 bytes, one per team id, then per league id
+//! ```
+//!
+//! [`HistoryWriter`] streams the records to disk as they are pushed and
+//! keeps only the index, team lists and names in memory, so the records
+//! never have to fit in memory at once. The price is that the results must
+//! arrive sorted; `datasets::write_history` reads a dataset directory one
+//! fixtures file at a time in that order.
+
+use crate::models::{Match, Season};
+use memmap2::Mmap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"LSHIST01";
+const VERSION: u32 = 2;
+const HEADER_LEN: usize = 64;
+const RECORD_LEN: usize = 20;
+const INDEX_LEN: usize = 40;
+const TEAM_LEN: usize = 16;
+
+/// Rating before the season of a team with results but no entry in its
+/// season's team list.
+pub const UNRATED_ELO: f64 = 1500.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid history file: {0}")]
+    InvalidFormat(String),
+    #[error("invalid history record: {0}")]
+    InvalidRecord(String),
+}
+
+/// One historical match. Team ids index the store's name table, league
+/// ids its league table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoricalResult {
+    pub league: u16,
+    pub season: u16,
+    /// 1-based, 0 if unknown
+    pub matchday: u16,
+    /// Kick-off date as yyyymmdd, 0 if unknown
+    pub date: u32,
+    pub team_home: u32,
+    pub team_away: u32,
+    pub goals_home: u8,
+    pub goals_away: u8,
+}
+
+impl HistoricalResult {
+    fn sort_key(&self) -> (u16, u16, u32, u16) {
+        (self.league, self.season, self.date, self.matchday)
+    }
+
+    fn encode(&self, out: &mut [u8]) {
+        out[0..2].copy_from_slice(&self.league.to_le_bytes());
+        out[2..4].copy_from_slice(&self.season.to_le_bytes());
+        out[4..6].copy_from_slice(&self.matchday.to_le_bytes());
+        out[6] = self.goals_home;
+        out[7] = self.goals_away;
+        out[8..12].copy_from_slice(&self.date.to_le_bytes());
+        out[12..16].copy_from_slice(&self.team_home.to_le_bytes());
+        out[16..20].copy_from_slice(&self.team_away.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            league: u16::from_le_bytes([bytes[0], bytes[1]]),
+            season: u16::from_le_bytes([bytes[2], bytes[3]]),
+            matchday: u16::from_le_bytes([bytes[4], bytes[5]]),
+            goals_home: bytes[6],
+            goals_away: bytes[7],
+            date: read_u32(bytes, 8),
+            team_home: read_u32(bytes, 12),
+            team_away: read_u32(bytes, 16),
+        }
+    }
+}
+
+/// A team of a league season.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeasonTeam {
+    /// Id in the store's name table
+    pub team: u32,
+    /// Rating before the season
+    pub elo: f64,
+    /// Promotion adjustment, as in the TeamList
+    pub promotion: i32,
+}
+
+impl SeasonTeam {
+    fn encode(&self, out: &mut [u8]) {
+        out[0..4].copy_from_slice(&self.team.to_le_bytes());
+        out[4..8].copy_from_slice(&self.promotion.to_le_bytes());
+        out[8..16].copy_from_slice(&self.elo.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            team: read_u32(bytes, 0),
+            promotion: read_u32(bytes, 4) as i32,
+            elo: f64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        }
+    }
+}
+
+/// Record range and team list of one (league, season) pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeasonIndex {
+    pub league: u16,
+    pub season: u16,
+    pub first: usize,
+    pub count: usize,
+    pub teams_first: usize,
+    pub teams_count: usize,
+}
+
+/// Writes a history file, streaming the records to disk as they arrive.
+///
+/// Results must be pushed sorted by (league id, season, date, matchday);
+/// a result out of order is refused. League ids are handed out in order of
+/// first use, so register leagues in the order their results follow.
+pub struct HistoryWriter {
+    out: BufWriter<File>,
+    records: usize,
+    last: Option<(u16, u16, u32, u16)>,
+    /// Record ranges in file order
+    ranges: Vec<SeasonIndex>,
+    teams: BTreeMap<(u16, u16), Vec<SeasonTeam>>,
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    leagues: Vec<String>,
+}
+
+impl HistoryWriter {
+    /// Start a history file at `path`, replacing any file there.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, HistoryError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        // The header is written by `finish`, once the counts are known.
+        out.write_all(&[0u8; HEADER_LEN])?;
+        Ok(Self {
+            out,
+            records: 0,
+            last: None,
+            ranges: Vec::new(),
+            teams: BTreeMap::new(),
+            names: Vec::new(),
+            ids: HashMap::new(),
+            leagues: Vec::new(),
+        })
+    }
+
+    /// Id of `name` in the name table, adding it on first use.
+    pub fn team_id(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Id of league `name`, adding it on first use.
+    pub fn league_id(&mut self, name: &str) -> Result<u16, HistoryError> {
+        if let Some(id) = self.leagues.iter().position(|l| l == name) {
+            return Ok(id as u16);
+        }
+        let id = u16::try_from(self.leagues.len())
+            .map_err(|_| HistoryError::InvalidRecord("more than 65536 leagues".to_string()))?;
+        self.leagues.push(name.to_string());
+        Ok(id)
+    }
+
+    /// Append a result. An error if it sorts before the previous one.
+    pub fn push(&mut self, result: HistoricalResult) -> Result<(), HistoryError> {
+        let key = result.sort_key();
+        if let Some(last) = self.last.filter(|&last| key < last) {
+            return Err(HistoryError::InvalidRecord(format!(
+                "results must arrive sorted by league, season, date and matchday; \
+                 got {:?} after {:?}",
+                key, last
+            )));
+        }
+        self.last = Some(key);
+
+        let mut record = [0u8; RECORD_LEN];
+        result.encode(&mut record);
+        self.out.write_all(&record)?;
+        match self.ranges.last_mut() {
+            Some(last) if last.league == result.league && last.season == result.season => {
+                last.count += 1
+            }
+            _ => self.ranges.push(SeasonIndex {
+                league: result.league,
+                season: result.season,
+                first: self.records,
+                count: 1,
+                teams_first: 0,
+                teams_count: 0,
+            }),
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Set the team list of a league season, replacing an earlier one. The
+    /// seasons rebuilt by [`HistoryStore::season_data`] number their teams
+    /// in this order.
+    pub fn season_teams(&mut self, league: u16, season: u16, teams: Vec<SeasonTeam>) {
+        self.teams.insert((league, season), teams);
+    }
+
+    /// Write the index, team lists and names, then the header.
+    pub fn finish(mut self) -> Result<(), HistoryError> {
+        let mut seasons: BTreeMap<(u16, u16), SeasonIndex> = self
+            .ranges
+            .iter()
+            .map(|entry| ((entry.league, entry.season), *entry))
+            .collect();
+        for &(league, season) in self.teams.keys() {
+            seasons.entry((league, season)).or_insert(SeasonIndex {
+                league,
+                season,
+                first: 0,
+                count: 0,
+                teams_first: 0,
+                teams_count: 0,
+            });
+        }
+
+        let mut team_count = 0;
+        for (key, entry) in seasons.iter_mut() {
+            let teams = self.teams.get(key).map_or(0, Vec::len);
+            entry.teams_first = team_count;
+            entry.teams_count = teams;
+            team_count += teams;
+        }
+
+        for entry in seasons.values() {
+            let mut bytes = [0u8; INDEX_LEN];
+            bytes[0..2].copy_from_slice(&entry.league.to_le_bytes());
+            bytes[2..4].copy_from_slice(&entry.season.to_le_bytes());
+            bytes[8..16].copy_from_slice(&(entry.first as u64).to_le_bytes());
+            bytes[16..24].copy_from_slice(&(entry.count as u64).to_le_bytes());
+            bytes[24..32].copy_from_slice(&(entry.teams_first as u64).to_le_bytes());
+            bytes[32..40].copy_from_slice(&(entry.teams_count as u64).to_le_bytes());
+            self.out.write_all(&bytes)?;
+        }
+
+        let mut bytes = [0u8; TEAM_LEN];
+        for key in seasons.keys() {
+            for team in self.teams.get(key).into_iter().flatten() {
+                team.encode(&mut bytes);
+                self.out.write_all(&bytes)?;
+            }
+        }
+
+        for name in self.names.iter().chain(&self.leagues) {
+            self.out.write_all(&(name.len() as u32).to_le_bytes())?;
+            self.out.write_all(name.as_bytes())?;
+        }
+
+        let names_offset = HEADER_LEN
+            + self.records * RECORD_LEN
+            + seasons.len() * INDEX_LEN
+            + team_count * TEAM_LEN;
+        let mut header = [0u8; HEADER_LEN];
+        header[0..8].copy_from_slice(MAGIC);
+        header[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        header[16..24].copy_from_slice(&(self.records as u64).to_le_bytes());
+        header[24..32].copy_from_slice(&(seasons.len() as u64).to_le_bytes());
+        header[32..40].copy_from_slice(&(names_offset as u64).to_le_bytes());
+        header[40..48].copy_from_slice(&(self.names.len() as u64).to_le_bytes());
+        header[48..56].copy_from_slice(&(team_count as u64).to_le_bytes());
+        header[56..64].copy_from_slice(&(self.leagues.len() as u64).to_le_bytes());
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Read-only, memory-mapped view of a history file.
+pub struct HistoryStore {
+    mmap: Mmap,
+    record_count: usize,
+    index: Vec<SeasonIndex>,
+    teams_offset: usize,
+    names: Vec<String>,
+    leagues: Vec<String>,
+}
+
+impl HistoryStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, HistoryError> {
+        let file = File::open(path)?;
+        // SAFETY: the file is opened read-only and history files are written
+        // once by `HistoryWriter`; concurrent modification is not supported.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let bytes = &mmap[..];
+
+        if bytes.len() < HEADER_LEN || &bytes[0..8] != MAGIC {
+            return Err(HistoryError::InvalidFormat("bad magic".to_string()));
+        }
+        let version = read_u32(bytes, 8);
+        if version != VERSION {
+            return Err(HistoryError::InvalidFormat(format!(
+                "unsupported version {}",
+                version
+            )));
+        }
+        let record_count = read_u64(bytes, 16) as usize;
+        let index_count = read_u64(bytes, 24) as usize;
+        let names_offset = read_u64(bytes, 32) as usize;
+        let name_count = read_u64(bytes, 40) as usize;
+        let team_count = read_u64(bytes, 48) as usize;
+        let league_count = read_u64(bytes, 56) as usize;
+
+        let invalid = |message: &str| HistoryError::InvalidFormat(message.to_string());
+        let section = |offset: Option<usize>, count: usize, len: usize| {
+            offset.and_then(|offset| count.checked_mul(len)?.checked_add(offset))
+        };
+        let index_offset = section(Some(HEADER_LEN), record_count, RECORD_LEN);
+        let teams_offset = section(index_offset, index_count, INDEX_LEN);
+        let teams_end = section(teams_offset, team_count, TEAM_LEN);
+        let (index_offset, teams_offset) = match (index_offset, teams_offset, teams_end) {
+            (Some(index), Some(teams), Some(end)) if end == names_offset && end <= bytes.len() => {
+                (index, teams)
+            }
+            _ => return Err(invalid("section offsets do not match file size")),
+        };
+
+        let index: Vec<SeasonIndex> = (0..index_count)
+            .map(|i| {
+                let b = &bytes[index_offset + i * INDEX_LEN..];
+                SeasonIndex {
+                    league: u16::from_le_bytes([b[0], b[1]]),
+                    season: u16::from_le_bytes([b[2], b[3]]),
+                    first: read_u64(b, 8) as usize,
+                    count: read_u64(b, 16) as usize,
+                    teams_first: read_u64(b, 24) as usize,
+                    teams_count: read_u64(b, 32) as usize,
+                }
+            })
+            .collect();
+        // `find` binary-searches the index and `range` and `season_teams`
+        // slice by it.
+        let within = |first: usize, count: usize, total: usize| {
+            first.checked_add(count).is_some_and(|end| end <= total)
+        };
+        for (i, entry) in index.iter().enumerate() {
+            if !within(entry.first, entry.count, record_count) {
+                return Err(invalid("index entry points past the records"));
+            }
+            if !within(entry.teams_first, entry.teams_count, team_count) {
+                return Err(invalid("index entry points past the team lists"));
+            }
+            if i > 0 && (index[i - 1].league, index[i - 1].season) >= (entry.league, entry.season) {
+                return Err(invalid("index is not sorted by league and season"));
+            }
+        }
+
+        // Every name takes at least its 4-byte length.
+        let table_len = name_count.saturating_add(league_count);
+        let mut table = Vec::with_capacity(table_len.min(bytes.len() / 4));
+        let mut pos = names_offset;
+        for _ in 0..table_len {
+            if pos + 4 > bytes.len() {
+                return Err(invalid("truncated name table"));
+            }
+            let len = read_u32(bytes, pos) as usize;
+            let name = bytes
+                .get(pos + 4..pos + 4 + len)
+                .ok_or_else(|| invalid("truncated name table"))?;
+            table.push(
+                String::from_utf8(name.to_vec())
+                    .map_err(|e| HistoryError::InvalidFormat(e.to_string()))?,
+            );
+            pos += 4 + len;
+        }
+        let leagues = table.split_off(name_count);
+
+        Ok(Self {
+            mmap,
+            record_count,
+            index,
+            teams_offset,
+            names: table,
+            leagues,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.record_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.record_count == 0
+    }
+
+    /// Record `i` in (league, season, date, matchday) order.
+    pub fn get(&self, i: usize) -> Option<HistoricalResult> {
+        (i < self.record_count).then(|| {
+            let start = HEADER_LEN + i * RECORD_LEN;
+            HistoricalResult::decode(&self.mmap[start..start + RECORD_LEN])
+        })
+    }
+
+    /// All records in file order. Decoding is lazy; nothing is copied up front.
+    pub fn iter(&self) -> impl Iterator<Item = HistoricalResult> + '_ {
+        self.range(0, self.record_count)
+    }
+
+    /// Every (league, season) pair in the store, in file order.
+    pub fn seasons(&self) -> &[SeasonIndex] {
+        &self.index
+    }
+
+    /// Records of one league season, found via the index.
+    pub fn season(&self, league: u16, season: u16) -> impl Iterator<Item = HistoricalResult> + '_ {
+        let (first, count) = self
+            .find(league, season)
+            .map_or((0, 0), |entry| (entry.first, entry.count));
+        self.range(first, count)
+    }
+
+    /// Team list of one league season, empty if it has none.
+    pub fn season_teams(&self, league: u16, season: u16) -> Vec<SeasonTeam> {
+        let Some(entry) = self.find(league, season) else {
+            return Vec::new();
+        };
+        let start = self.teams_offset + entry.teams_first * TEAM_LEN;
+        self.mmap[start..start + entry.teams_count * TEAM_LEN]
+            .chunks_exact(TEAM_LEN)
+            .map(SeasonTeam::decode)
+            .collect()
+    }
+
+    /// One league season as the simulation and [`backtest`](crate::backtest)
+    /// take it, with the teams its matches index: the season's team list,
+    /// then teams with results but no list entry, rated [`UNRATED_ELO`].
+    /// None if the store has no such season.
+    pub fn season_data(&self, league: u16, season: u16) -> Option<(Season, Vec<SeasonTeam>)> {
+        self.find(league, season)?;
+        let mut teams = self.season_teams(league, season);
+        let mut local: HashMap<u32, usize> =
+            teams.iter().enumerate().map(|(i, t)| (t.team, i)).collect();
+        let mut local_id = |team: u32| {
+            *local.entry(team).or_insert_with(|| {
+                teams.push(SeasonTeam {
+                    team,
+                    elo: UNRATED_ELO,
+                    promotion: 0,
+                });
+                teams.len() - 1
+            })
+        };
+        let matches: Vec<Match> = self
+            .season(league, season)
+            .map(|r| Match {
+                team_home: local_id(r.team_home),
+                team_away: local_id(r.team_away),
+                goals_home: Some(r.goals_home.into()),
+                goals_away: Some(r.goals_away.into()),
+                matchday: (r.matchday > 0).then_some(r.matchday.into()),
+                competition: None,
+                weight: None,
+            })
+            .collect();
+        let season = Season {
+            matches,
+            team_elos: teams.iter().map(|t| t.elo).collect(),
+            number_teams: teams.len(),
+        };
+        Some((season, teams))
+    }
+
+    /// Seasons of one league, oldest first, as [`backtest`](crate::backtest)
+    /// takes them. Empty for an unknown league.
+    pub fn backtest_seasons(&self, league: &str) -> Vec<Season> {
+        let Some(league) = self.league_id(league) else {
+            return Vec::new();
+        };
+        self.index
+            .iter()
+            .filter(|entry| entry.league == league)
+            .filter_map(|entry| self.season_data(league, entry.season))
+            .map(|(season, _)| season)
+            .collect()
+    }
+
+    pub fn team_name(&self, id: u32) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    /// League names by id.
+    pub fn leagues(&self) -> &[String] {
+        &self.leagues
+    }
+
+    pub fn league_id(&self, name: &str) -> Option<u16> {
+        self.leagues
+            .iter()
+            .position(|l| l == name)
+            .map(|i| i as u16)
+    }
+
+    pub fn league_name(&self, id: u16) -> Option<&str> {
+        self.leagues.get(id as usize).map(String::as_str)
+    }
+
+    fn find(&self, league: u16, season: u16) -> Option<&SeasonIndex> {
+        self.index
+            .binary_search_by_key(&(league, season), |e| (e.league, e.season))
+            .ok()
+            .map(|i| &self.index[i])
+    }
+
+    fn range(&self, first: usize, count: usize) -> impl Iterator<Item = HistoricalResult> + '_ {
+        let start = HEADER_LEN + first * RECORD_LEN;
+        self.mmap[start..start + count * RECORD_LEN]
+            .chunks_exact(RECORD_LEN)
+            .map(HistoricalResult::decode)
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests;
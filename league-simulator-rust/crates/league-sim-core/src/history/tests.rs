@@ -0,0 +1,189 @@
+use super::*;
+use tempfile::tempdir;
+
+fn result(league: u16, season: u16, matchday: u16, home: u32, away: u32) -> HistoricalResult {
+    HistoricalResult {
+        league,
+        season,
+        matchday,
+        date: 20240800 + matchday as u32,
+        team_home: home,
+        team_away: away,
+        goals_home: (home % 4) as u8,
+        goals_away: (away % 3) as u8,
+    }
+}
+
+#[test]
+fn round_trip_preserves_records_in_sorted_order() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("history.bin");
+
+    let mut writer = HistoryWriter::create(&path).unwrap();
+    let bayern = writer.team_id("Bayern");
+    let dortmund = writer.team_id("Dortmund");
+    assert_eq!(writer.team_id("Bayern"), bayern, "ids must be stable");
+
+    writer.push(result(1, 2023, 1, bayern, dortmund)).unwrap();
+    writer.push(result(1, 2024, 1, bayern, dortmund)).unwrap();
+    writer.push(result(1, 2024, 2, dortmund, bayern)).unwrap();
+    writer.finish().unwrap();
+
+    let store = HistoryStore::open(&path).unwrap();
+    assert_eq!(store.len(), 3);
+    let all: Vec<_> = store.iter().collect();
+    assert_eq!(all[0], result(1, 2023, 1, bayern, dortmund));
+    assert_eq!(all[1], result(1, 2024, 1, bayern, dortmund));
+    assert_eq!(all[2], result(1, 2024, 2, dortmund, bayern));
+    assert_eq!(store.get(2), Some(all[2]));
+    assert_eq!(store.get(3), None);
+    assert_eq!(store.team_name(dortmund), Some("Dortmund"));
+}
+
+#[test]
+fn season_lookup_uses_index() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("history.bin");
+
+    let mut writer = HistoryWriter::create(&path).unwrap();
+    for league in 1..=3 {
+        for season in 2020..2023 {
+            for matchday in 1..=4 {
+                writer.push(result(league, season, matchday, 0, 1)).unwrap();
+            }
+        }
+    }
+    writer.finish().unwrap();
+
+    let store = HistoryStore::open(&path).unwrap();
+    assert_eq!(store.seasons().len(), 9);
+
+    let season: Vec<_> = store.season(2, 2021).collect();
+    assert_eq!(season.len(), 4);
+    assert!(season.iter().all(|r| r.league == 2 && r.season == 2021));
+    assert_eq!(store.season(4, 2021).count(), 0);
+}
+
+#[test]
+fn push_rejects_results_out_of_order() {
+    let dir = tempdir().unwrap();
+    let mut writer = HistoryWriter::create(dir.path().join("history.bin")).unwrap();
+    writer.push(result(1, 2024, 2, 0, 1)).unwrap();
+    // Same matchday again is fine, an earlier one or season is not.
+    writer.push(result(1, 2024, 2, 1, 0)).unwrap();
+    assert!(matches!(
+        writer.push(result(1, 2024, 1, 0, 1)),
+        Err(HistoryError::InvalidRecord(_))
+    ));
+    assert!(matches!(
+        writer.push(result(1, 2023, 5, 0, 1)),
+        Err(HistoryError::InvalidRecord(_))
+    ));
+    writer.push(result(2, 2020, 1, 0, 1)).unwrap();
+}
+
+#[test]
+fn season_data_numbers_teams_by_the_team_list() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("history.bin");
+
+    let mut writer = HistoryWriter::create(&path).unwrap();
+    let league = writer.league_id("bundesliga").unwrap();
+    assert_eq!(writer.league_id("bundesliga").unwrap(), league);
+    let [bayern, dortmund, kiel] = ["Bayern", "Dortmund", "Kiel"].map(|t| writer.team_id(t));
+    writer.push(result(league, 2024, 1, kiel, bayern)).unwrap();
+    writer
+        .push(result(league, 2024, 2, dortmund, kiel))
+        .unwrap();
+    // Kiel has results but no list entry.
+    writer.season_teams(
+        league,
+        2024,
+        vec![
+            SeasonTeam {
+                team: dortmund,
+                elo: 1700.0,
+                promotion: 0,
+            },
+            SeasonTeam {
+                team: bayern,
+                elo: 1900.0,
+                promotion: -1,
+            },
+        ],
+    );
+    writer.finish().unwrap();
+
+    let store = HistoryStore::open(&path).unwrap();
+    assert_eq!(store.leagues(), ["bundesliga"]);
+    assert_eq!(store.league_id("bundesliga"), Some(league));
+    assert_eq!(store.season_teams(league, 2024).len(), 2);
+    assert!(store.season_data(league, 2023).is_none());
+
+    let (season, teams) = store.season_data(league, 2024).unwrap();
+    assert_eq!(season.number_teams, 3);
+    assert_eq!(season.team_elos, [1700.0, 1900.0, UNRATED_ELO]);
+    assert_eq!(teams[1].promotion, -1);
+    assert_eq!(teams[2].team, kiel);
+    assert_eq!(
+        (season.matches[0].team_home, season.matches[0].team_away),
+        (2, 1)
+    );
+    assert_eq!(season.matches[1].matchday, Some(2));
+    assert_eq!(season.matches[1].goals_home, Some((dortmund % 4) as i32));
+
+    assert_eq!(store.backtest_seasons("bundesliga").len(), 1);
+    assert!(store.backtest_seasons("3_liga").is_empty());
+}
+
+#[test]
+fn open_rejects_foreign_files() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("not_history.bin");
+    std::fs::write(&path, b"home,away,goals\n").unwrap();
+
+    assert!(matches!(
+        HistoryStore::open(&path),
+        Err(HistoryError::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn open_rejects_corrupt_headers_and_indexes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("history.bin");
+    let mut writer = HistoryWriter::create(&path).unwrap();
+    for league in 1..=2 {
+        writer.push(result(league, 2024, 1, 0, 1)).unwrap();
+    }
+    writer.finish().unwrap();
+    let good = std::fs::read(&path).unwrap();
+    let index_offset = HEADER_LEN + 2 * RECORD_LEN;
+
+    let corrupt = |edit: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = good.clone();
+        edit(&mut bytes);
+        std::fs::write(&path, bytes).unwrap();
+        HistoryStore::open(&path)
+    };
+    // A record count whose byte size overflows.
+    let result = corrupt(&|b| b[16..24].copy_from_slice(&u64::MAX.to_le_bytes()));
+    assert!(matches!(result, Err(HistoryError::InvalidFormat(_))));
+    // An index entry running past the records.
+    let result =
+        corrupt(&|b| b[index_offset + 16..index_offset + 24].copy_from_slice(&5u64.to_le_bytes()));
+    assert!(matches!(result, Err(HistoryError::InvalidFormat(_))));
+    // Index entries out of order.
+    let result =
+        corrupt(&|b| b[index_offset..index_offset + 2].copy_from_slice(&3u16.to_le_bytes()));
+    assert!(matches!(result, Err(HistoryError::InvalidFormat(_))));
+    // A team list running past the team section.
+    let result =
+        corrupt(&|b| b[index_offset + 32..index_offset + 40].copy_from_slice(&1u64.to_le_bytes()));
+    assert!(matches!(result, Err(HistoryError::InvalidFormat(_))));
+    // A name count far beyond the file.
+    let result = corrupt(&|b| b[40..48].copy_from_slice(&u64::MAX.to_le_bytes()));
+    assert!(matches!(result, Err(HistoryError::InvalidFormat(_))));
+
+    assert!(corrupt(&|_| {}).is_ok());
+}
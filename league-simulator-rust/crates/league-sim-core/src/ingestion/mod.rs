@@ -0,0 +1,314 @@
+//! Readers for the CSV files of the R pipeline.
+//!
+//! `TeamList_<season>.csv` is semicolon-separated with the columns
+//! `TeamID;ShortText;Promotion;InitialELO` (older files say `ShortName`).
+//! It lists the teams of every league of a season; `Promotion` is -50 for
+//! second teams, which cannot be promoted.
+//!
+//! A fixtures file has one row per match with the home and away team, the
+//! goals (empty or `NA` while unplayed) and optionally the matchday:
+//!
+//! ```text
+//! home,away,goals_home,goals_away,matchday
+//! FCB,BVB,2,1,1
+//! BVB,S04,,,2
+//! ```
+//!
+//! The German column names of the R data frames (`TeamHeim`, `TeamGast`,
+//! `ToreHeim`, `ToreGast`, `Spieltag`) work as well, and `;` may be used as
+//! the separator. Teams are given by short name or by `TeamID`. Files mixing
+//! competitions may add a `competition` column (e.g. `league`, `cup`,
+//! `friendly`) and a `weight` column scaling the match's Elo update; see
+//! `SimulationParams::competition_weights`.
+
+use crate::models::{Match, Season};
+use csv::{ReaderBuilder, StringRecord, Trim};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IngestionError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("missing column {0}")]
+    MissingColumn(&'static str),
+    #[error("row {row}: invalid {column} {value:?}")]
+    InvalidValue {
+        row: usize,
+        column: &'static str,
+        value: String,
+    },
+    #[error("row {row}: team {name:?} is not in the team list")]
+    UnknownTeam { row: usize, name: String },
+}
+
+/// One row of a TeamList CSV.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamEntry {
+    pub team_id: u32,
+    pub short_name: String,
+    /// Point adjustment applied when ranking for promotion (0 or -50)
+    pub promotion: i32,
+    pub initial_elo: f64,
+}
+
+/// The teams of a season, looked up by short name or `TeamID`.
+#[derive(Debug, Clone, Default)]
+pub struct TeamList {
+    teams: Vec<TeamEntry>,
+}
+
+impl TeamList {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, IngestionError> {
+        Self::from_reader(std::fs::File::open(path)?)
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self, IngestionError> {
+        let mut csv = ReaderBuilder::new()
+            .delimiter(b';')
+            .trim(Trim::All)
+            .from_reader(reader);
+        let headers = csv.headers()?.clone();
+        let id = column(&headers, &["TeamID"], "TeamID")?;
+        let name = column(&headers, &["ShortText", "ShortName"], "ShortText")?;
+        let promotion = column(&headers, &["Promotion"], "Promotion")?;
+        let elo = column(&headers, &["InitialELO"], "InitialELO")?;
+
+        let mut teams = Vec::new();
+        for (i, record) in csv.records().enumerate() {
+            let record = record?;
+            // Row numbers count the header as row 1, like a spreadsheet.
+            let row = i + 2;
+            teams.push(TeamEntry {
+                team_id: parse(&record, id, row, "TeamID")?,
+                short_name: record.get(name).unwrap_or_default().to_string(),
+                promotion: parse(&record, promotion, row, "Promotion")?,
+                initial_elo: parse(&record, elo, row, "InitialELO")?,
+            });
+        }
+        Ok(Self { teams })
+    }
+
+    pub fn teams(&self) -> &[TeamEntry] {
+        &self.teams
+    }
+
+    /// Team by short name, or by `TeamID` if `key` is a number.
+    pub fn get(&self, key: &str) -> Option<&TeamEntry> {
+        self.teams.iter().find(|t| t.short_name == key).or_else(|| {
+            let id: u32 = key.parse().ok()?;
+            self.teams.iter().find(|t| t.team_id == id)
+        })
+    }
+}
+
+/// A league loaded from CSV, ready for the engine.
+#[derive(Debug, Clone)]
+pub struct LeagueData {
+    /// Teams are indexed in alphabetical order of their short names, the
+    /// column order of the R pipeline.
+    pub season: Season,
+    pub team_names: Vec<String>,
+    /// Matchday of each match, if the fixtures file has the column
+    pub matchdays: Vec<Option<u32>>,
+    /// `Promotion` of each team, usable as `adj_points` for the promotion
+    /// ranking
+    pub promotion: Vec<i32>,
+}
+
+impl LeagueData {
+    /// Index of a team in `season`, by short name.
+    pub fn team_index(&self, name: &str) -> Option<usize> {
+        self.team_names.iter().position(|n| n == name)
+    }
+}
+
+/// Read a fixtures CSV, resolving teams against `teams`. Only the teams
+/// that appear in the fixtures are part of the league.
+pub fn load_fixtures(
+    teams: &TeamList,
+    mut reader: impl Read,
+) -> Result<LeagueData, IngestionError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let header = text.lines().next().unwrap_or_default();
+    let delimiter = if header.contains(';') { b';' } else { b',' };
+    let mut csv = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(Trim::All)
+        .from_reader(text.as_bytes());
+
+    let headers = csv.headers()?.clone();
+    let home = column(&headers, &["home", "team_home", "TeamHeim"], "home")?;
+    let away = column(&headers, &["away", "team_away", "TeamGast"], "away")?;
+    let goals_home = column(&headers, &["goals_home", "ToreHeim"], "goals_home")?;
+    let goals_away = column(&headers, &["goals_away", "ToreGast"], "goals_away")?;
+    let matchday = column(&headers, &["matchday", "Spieltag"], "matchday").ok();
+    let competition = column(&headers, &["competition", "Wettbewerb"], "competition").ok();
+    let weight = column(&headers, &["weight", "Gewicht"], "weight").ok();
+
+    struct Row<'a> {
+        home: &'a TeamEntry,
+        away: &'a TeamEntry,
+        goals: Option<(i32, i32)>,
+        matchday: Option<u32>,
+        competition: Option<String>,
+        weight: Option<f64>,
+    }
+
+    let mut rows = Vec::new();
+    for (i, record) in csv.records().enumerate() {
+        let record = record?;
+        let row = i + 2;
+        let team = |col: usize| {
+            let name = record.get(col).unwrap_or_default();
+            teams.get(name).ok_or_else(|| IngestionError::UnknownTeam {
+                row,
+                name: name.to_string(),
+            })
+        };
+        let goals = match (
+            goal(&record, goals_home, row, "goals_home")?,
+            goal(&record, goals_away, row, "goals_away")?,
+        ) {
+            (Some(h), Some(a)) => Some((h, a)),
+            (None, None) => None,
+            _ => {
+                return Err(IngestionError::InvalidValue {
+                    row,
+                    column: "goals",
+                    value: "only one side has a score".to_string(),
+                })
+            }
+        };
+        let matchday = match matchday {
+            Some(col) if !missing(record.get(col).unwrap_or_default()) => {
+                Some(parse(&record, col, row, "matchday")?)
+            }
+            _ => None,
+        };
+        let competition = competition
+            .and_then(|col| record.get(col))
+            .filter(|value| !missing(value))
+            .map(str::to_string);
+        let weight = match weight {
+            Some(col) if !missing(record.get(col).unwrap_or_default()) => {
+                let weight: f64 = parse(&record, col, row, "weight")?;
+                if !(weight.is_finite() && weight >= 0.0) {
+                    return Err(IngestionError::InvalidValue {
+                        row,
+                        column: "weight",
+                        value: record.get(col).unwrap_or_default().to_string(),
+                    });
+                }
+                Some(weight)
+            }
+            _ => None,
+        };
+        rows.push(Row {
+            home: team(home)?,
+            away: team(away)?,
+            goals,
+            matchday,
+            competition,
+            weight,
+        });
+    }
+
+    let mut league_teams: Vec<&TeamEntry> = rows.iter().flat_map(|r| [r.home, r.away]).collect();
+    league_teams.sort_by(|a, b| (&a.short_name, a.team_id).cmp(&(&b.short_name, b.team_id)));
+    league_teams.dedup_by_key(|t| t.team_id);
+    let index: HashMap<u32, usize> = league_teams
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.team_id, i))
+        .collect();
+
+    let matches = rows
+        .iter()
+        .map(|r| Match {
+            team_home: index[&r.home.team_id],
+            team_away: index[&r.away.team_id],
+            goals_home: r.goals.map(|g| g.0),
+            goals_away: r.goals.map(|g| g.1),
+            matchday: None,
+            competition: r.competition.clone(),
+            weight: r.weight,
+        })
+        .collect();
+
+    Ok(LeagueData {
+        season: Season {
+            matches,
+            team_elos: league_teams.iter().map(|t| t.initial_elo).collect(),
+            number_teams: league_teams.len(),
+        },
+        team_names: league_teams.iter().map(|t| t.short_name.clone()).collect(),
+        matchdays: rows.iter().map(|r| r.matchday).collect(),
+        promotion: league_teams.iter().map(|t| t.promotion).collect(),
+    })
+}
+
+/// `load_fixtures` on the file at `path`.
+pub fn load_fixtures_path(
+    teams: &TeamList,
+    path: impl AsRef<Path>,
+) -> Result<LeagueData, IngestionError> {
+    load_fixtures(teams, std::fs::File::open(path)?)
+}
+
+/// Position of the first header matching one of `names`, ignoring case.
+fn column(
+    headers: &StringRecord,
+    names: &[&str],
+    label: &'static str,
+) -> Result<usize, IngestionError> {
+    headers
+        .iter()
+        .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+        .ok_or(IngestionError::MissingColumn(label))
+}
+
+fn parse<T: std::str::FromStr>(
+    record: &StringRecord,
+    col: usize,
+    row: usize,
+    column: &'static str,
+) -> Result<T, IngestionError> {
+    let value = record.get(col).unwrap_or_default();
+    value.parse().map_err(|_| IngestionError::InvalidValue {
+        row,
+        column,
+        value: value.to_string(),
+    })
+}
+
+fn missing(value: &str) -> bool {
+    value.is_empty() || value == "NA"
+}
+
+fn goal(
+    record: &StringRecord,
+    col: usize,
+    row: usize,
+    column: &'static str,
+) -> Result<Option<i32>, IngestionError> {
+    if missing(record.get(col).unwrap_or_default()) {
+        return Ok(None);
+    }
+    let goals: i32 = parse(record, col, row, column)?;
+    if goals < 0 {
+        return Err(IngestionError::InvalidValue {
+            row,
+            column,
+            value: goals.to_string(),
+        });
+    }
+    Ok(Some(goals))
+}
+
+#[cfg(test)]
+mod tests;
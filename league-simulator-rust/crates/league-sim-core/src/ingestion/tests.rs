@@ -0,0 +1,118 @@
+use super::*;
+
+const TEAM_LIST: &str = "\
+TeamID;ShortText;Promotion;InitialELO
+157;FCB;0;1969.32428619061
+165;BVB;0;1780.5
+174;S04;0;1500
+999;FCB2;-50;1310
+";
+
+fn team_list() -> TeamList {
+    TeamList::from_reader(TEAM_LIST.as_bytes()).unwrap()
+}
+
+#[test]
+fn team_list_reads_the_r_pipeline_format() {
+    let teams = team_list();
+    assert_eq!(teams.teams().len(), 4);
+    assert_eq!(
+        teams.get("FCB"),
+        Some(&TeamEntry {
+            team_id: 157,
+            short_name: "FCB".to_string(),
+            promotion: 0,
+            initial_elo: 1969.32428619061,
+        })
+    );
+    assert_eq!(teams.get("999").map(|t| t.promotion), Some(-50));
+    assert!(teams.get("HSV").is_none());
+
+    // Older files name the column ShortName.
+    let old =
+        TeamList::from_reader("TeamID;ShortName;Promotion;InitialELO\n1;TST;0;1500\n".as_bytes())
+            .unwrap();
+    assert_eq!(old.get("TST").map(|t| t.team_id), Some(1));
+}
+
+#[test]
+fn fixtures_map_names_to_alphabetical_indices() {
+    let fixtures = "\
+home,away,goals_home,goals_away,matchday
+FCB,BVB,2,1,1
+S04,999,NA,NA,1
+BVB,S04,,,2
+";
+    let league = load_fixtures(&team_list(), fixtures.as_bytes()).unwrap();
+
+    assert_eq!(league.team_names, ["BVB", "FCB", "FCB2", "S04"]);
+    assert_eq!(league.season.number_teams, 4);
+    assert_eq!(
+        league.season.team_elos,
+        [1780.5, 1969.32428619061, 1310.0, 1500.0]
+    );
+    assert_eq!(league.promotion, [0, 0, -50, 0]);
+    assert_eq!(league.matchdays, [Some(1), Some(1), Some(2)]);
+    assert_eq!(league.team_index("S04"), Some(3));
+
+    let m = &league.season.matches;
+    assert_eq!((m[0].team_home, m[0].team_away), (1, 0));
+    assert_eq!((m[0].goals_home, m[0].goals_away), (Some(2), Some(1)));
+    assert_eq!((m[1].team_home, m[1].team_away), (3, 2));
+    assert_eq!(m[1].goals_home, None);
+    assert_eq!(m[2].goals_away, None);
+}
+
+#[test]
+fn fixtures_accept_the_german_columns_and_semicolons() {
+    let fixtures = "TeamHeim;TeamGast;ToreHeim;ToreGast\nFCB;BVB;0;0\n";
+    let league = load_fixtures(&team_list(), fixtures.as_bytes()).unwrap();
+    assert_eq!(league.team_names, ["BVB", "FCB"]);
+    assert_eq!(league.matchdays, [None]);
+    assert_eq!(league.season.matches[0].goals_home, Some(0));
+}
+
+#[test]
+fn fixtures_read_competitions_and_weights() {
+    let fixtures =
+        "home,away,goals_home,goals_away,competition,weight\nFCB,BVB,1,0,cup,\nBVB,FCB,,,NA,0.5\n";
+    let league = load_fixtures(&team_list(), fixtures.as_bytes()).unwrap();
+    let m = &league.season.matches;
+    assert_eq!(
+        (m[0].competition.as_deref(), m[0].weight),
+        (Some("cup"), None)
+    );
+    assert_eq!(
+        (m[1].competition.as_deref(), m[1].weight),
+        (None, Some(0.5))
+    );
+
+    let negative = "home,away,goals_home,goals_away,weight\nFCB,BVB,1,0,-1\n";
+    let err = load_fixtures(&team_list(), negative.as_bytes()).unwrap_err();
+    assert!(matches!(
+        err,
+        IngestionError::InvalidValue {
+            row: 2,
+            column: "weight",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn fixture_errors_name_the_row() {
+    let unknown = "home,away,goals_home,goals_away\nFCB,BVB,1,0\nFCB,HSV,,\n";
+    let err = load_fixtures(&team_list(), unknown.as_bytes()).unwrap_err();
+    assert!(matches!(err, IngestionError::UnknownTeam { row: 3, ref name } if name == "HSV"));
+
+    let half = "home,away,goals_home,goals_away\nFCB,BVB,1,\n";
+    let err = load_fixtures(&team_list(), half.as_bytes()).unwrap_err();
+    assert!(matches!(err, IngestionError::InvalidValue { row: 2, .. }));
+
+    let negative = "home,away,goals_home,goals_away\nFCB,BVB,-1,0\n";
+    assert!(load_fixtures(&team_list(), negative.as_bytes()).is_err());
+
+    let no_goals = "home,away\nFCB,BVB\n";
+    let err = load_fixtures(&team_list(), no_goals.as_bytes()).unwrap_err();
+    assert!(matches!(err, IngestionError::MissingColumn("goals_home")));
+}
@@ -0,0 +1,31 @@
+pub mod backtest;
+#[cfg(feature = "datasets")]
+pub mod datasets;
+pub mod elo;
+#[cfg(feature = "parquet")]
+pub mod export;
+pub mod fit;
+#[cfg(feature = "storage")]
+pub mod history;
+#[cfg(feature = "ingestion")]
+pub mod ingestion;
+pub mod models;
+pub mod monte_carlo;
+pub mod outcomes;
+pub mod presets;
+pub mod projection;
+pub mod rating;
+#[cfg(feature = "reports")]
+pub mod reports;
+pub mod simulation;
+pub mod structure;
+pub mod tournament;
+
+pub use backtest::*;
+pub use elo::*;
+pub use fit::*;
+pub use models::*;
+pub use monte_carlo::*;
+pub use projection::*;
+pub use rating::*;
+pub use simulation::*;
@@ -0,0 +1,453 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Result of an ELO calculation after a match
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EloResult {
+    pub new_elo_home: f64,
+    pub new_elo_away: f64,
+    pub goals_home: i32,
+    pub goals_away: i32,
+    pub win_probability_home: f64,
+}
+
+/// Parameters for ELO calculation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EloParams {
+    pub elo_home: f64,
+    pub elo_away: f64,
+    pub goals_home: i32,
+    pub goals_away: i32,
+    pub mod_factor: f64,
+    pub home_advantage: f64,
+}
+
+/// Win/draw/loss probabilities of a single fixture under the goal model
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct OutcomeProbabilities {
+    pub home_win: f64,
+    pub draw: f64,
+    pub away_win: f64,
+    pub expected_goals_home: f64,
+    pub expected_goals_away: f64,
+}
+
+/// Match result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub team_home: usize,
+    pub team_away: usize,
+    pub goals_home: Option<i32>,
+    pub goals_away: Option<i32>,
+    /// Matchday (round) the match belongs to, 1-based, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matchday: Option<u32>,
+    /// Competition the match belongs to, e.g. `"cup"` or `"friendly"`,
+    /// looked up in `SimulationParams::competition_weights`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub competition: Option<String>,
+    /// Multiplier of `mod_factor` for this match, overriding the weight of
+    /// its competition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// A match in progress: the score so far and the minute it was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LiveMatch {
+    /// Index of the match in `Season::matches`
+    pub index: usize,
+    pub goals_home: i32,
+    pub goals_away: i32,
+    /// Minutes played, 0..=90 (stoppage time counts as 90)
+    pub minute: u32,
+}
+
+/// Season schedule with matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Season {
+    pub matches: Vec<Match>,
+    pub team_elos: Vec<f64>,
+    pub number_teams: usize,
+}
+
+/// League table entry for a team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamStanding {
+    pub team_id: usize,
+    pub played: i32,
+    pub won: i32,
+    pub drawn: i32,
+    pub lost: i32,
+    pub goals_for: i32,
+    pub goals_against: i32,
+    pub goal_difference: i32,
+    pub points: i32,
+    pub position: usize,
+}
+
+/// Complete league table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeagueTable {
+    pub standings: Vec<TeamStanding>,
+}
+
+/// Random number generator used for the per-iteration streams.
+/// `Std` (ChaCha12) is the default; the non-cryptographic generators are
+/// cheaper to seed and step, which matters since every iteration gets a
+/// freshly seeded generator.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RngKind {
+    #[default]
+    Std,
+    SmallRng,
+    Xoshiro256PlusPlus,
+}
+
+/// One criterion of a league's tiebreaker chain. The table is ordered by
+/// the first criterion, teams level on it by the second, and so on.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TiebreakerRule {
+    Points,
+    GoalDifference,
+    GoalsFor,
+    /// Points from the matches among the tied teams
+    HeadToHeadPoints,
+    /// Away goals scored in the matches among the tied teams
+    HeadToHeadAwayGoals,
+    /// Fewest fair play points (`SimulationParams::fair_play`)
+    FairPlay,
+    /// Random order, drawn afresh in every simulated season
+    DrawingOfLots,
+}
+
+impl TiebreakerRule {
+    /// Points, then goal difference, then goals scored: the order used
+    /// when a league does not specify one.
+    pub const DEFAULT_CHAIN: [TiebreakerRule; 3] = [
+        TiebreakerRule::Points,
+        TiebreakerRule::GoalDifference,
+        TiebreakerRule::GoalsFor,
+    ];
+}
+
+/// Rating system that turns results into team strengths, see
+/// [`RatingSystem`](crate::rating::RatingSystem).
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RatingSystemKind {
+    /// The Elo formula of SpielNichtSimulieren.cpp, `mod_factor` as K
+    #[default]
+    Elo,
+    /// Glicko-2: ratings carry a deviation and volatility, so uncertain
+    /// teams move further on each result (`SimulationParams::glicko`)
+    Glicko2,
+}
+
+/// Starting uncertainty and dynamics of Glicko-2 ratings. The ratings
+/// themselves are `Season::team_elos`, read on the Glicko scale.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlickoSettings {
+    /// Rating deviation per team at the start (optional); None gives every
+    /// team `deviation`
+    pub deviations: Option<Vec<f64>>,
+    /// Rating deviation of teams without their own (default: 60, about
+    /// where a team settles after a few seasons at the default volatility)
+    pub deviation: f64,
+    /// Starting volatility of every team (default: 0.06)
+    pub volatility: f64,
+    /// System constant limiting how fast volatility changes (default: 0.5)
+    pub tau: f64,
+}
+
+impl Default for GlickoSettings {
+    fn default() -> Self {
+        Self {
+            deviations: None,
+            deviation: 60.0,
+            volatility: 0.06,
+            tau: 0.5,
+        }
+    }
+}
+
+/// Attacking and defensive strength of a team, e.g. from an expected goals
+/// model: goals scored and conceded per match against an average side.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TeamStrength {
+    pub attack: f64,
+    pub defense: f64,
+}
+
+impl TeamStrength {
+    /// Largest attack or defense accepted from clients, in goals per match;
+    /// far beyond any real side, but small enough that the goal means stay
+    /// finite.
+    pub const MAX: f64 = 20.0;
+}
+
+/// Team coefficients of a Poisson regression of goals, on the log scale:
+/// home goals have mean `exp(intercept + home_advantage + attack[home] +
+/// defense[away])`, away goals `exp(intercept + attack[away] +
+/// defense[home])`. Attack and defense each sum to zero over the teams; a
+/// positive defense concedes more than average.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoissonStrengths {
+    pub intercept: f64,
+    pub home_advantage: f64,
+    /// Per team_id
+    pub attack: Vec<f64>,
+    /// Per team_id
+    pub defense: Vec<f64>,
+}
+
+fn default_tiebreakers() -> Vec<TiebreakerRule> {
+    TiebreakerRule::DEFAULT_CHAIN.to_vec()
+}
+
+/// Simulation parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationParams {
+    pub mod_factor: f64,
+    /// Multiplier of `mod_factor` per entry of `Season::matches`, e.g.
+    /// above 1 for the last matchdays; None weighs every match alike
+    #[serde(default)]
+    pub mod_factor_weights: Option<Vec<f64>>,
+    /// Multiplier of `mod_factor` per `Match::competition`, e.g. 0.5 for
+    /// `"friendly"`; competitions not listed weigh 1
+    #[serde(default)]
+    pub competition_weights: Option<BTreeMap<String, f64>>,
+    pub home_advantage: f64,
+    pub iterations: usize,
+    pub tore_slope: f64,
+    pub tore_intercept: f64,
+    /// Optional point adjustments per team (e.g., penalties)
+    pub adj_points: Option<Vec<i32>>,
+    /// Optional goals scored adjustments per team
+    pub adj_goals: Option<Vec<i32>>,
+    /// Optional goals against adjustments per team
+    pub adj_goals_against: Option<Vec<i32>>,
+    /// Optional goal difference adjustments per team
+    pub adj_goal_diff: Option<Vec<i32>>,
+    /// Optional matches already played per team, e.g. from a current table
+    #[serde(default)]
+    pub adj_played: Option<Vec<i32>>,
+    /// Generator backend for the per-iteration random streams
+    #[serde(default)]
+    pub rng: RngKind,
+    /// Master seed of the per-iteration streams, for runs that can be
+    /// repeated exactly; None draws them from OS entropy
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Order in which the final table is sorted
+    #[serde(default = "default_tiebreakers")]
+    pub tiebreakers: Vec<TiebreakerRule>,
+    /// Fair play points per team (cards), fewer is better. Only consulted
+    /// by `TiebreakerRule::FairPlay`; the matches still to play add none.
+    #[serde(default)]
+    pub fair_play: Option<Vec<i32>>,
+    /// Rating system updating the strengths during Monte Carlo runs
+    #[serde(default)]
+    pub rating_system: RatingSystemKind,
+    /// Glicko-2 settings, used with `RatingSystemKind::Glicko2`
+    #[serde(default)]
+    pub glicko: GlickoSettings,
+    /// Attack and defense per team; goals are then drawn from these
+    /// instead of, or blended with, the Elo goal model
+    #[serde(default)]
+    pub team_strengths: Option<Vec<TeamStrength>>,
+    /// Share of `team_strengths` in the blended goal means, 0 to 1; 1
+    /// ignores the Elo goal model
+    #[serde(default = "default_strength_weight")]
+    pub strength_weight: f64,
+    /// Fitted Poisson coefficients per team; goals are then drawn from
+    /// these alone, and `team_strengths` is ignored
+    #[serde(default)]
+    pub poisson_strengths: Option<PoissonStrengths>,
+    /// Prior strength per team in ELO points, e.g. from squad market
+    /// values; the Elo goal model then reads a strength difference blended
+    /// from these and the ratings
+    #[serde(default)]
+    pub strength_priors: Option<Vec<f64>>,
+    /// Share of `strength_priors` in the blended strength difference, 0 to
+    /// 1; 1 ignores the ratings
+    #[serde(default = "default_prior_weight")]
+    pub prior_weight: f64,
+}
+
+fn default_strength_weight() -> f64 {
+    1.0
+}
+
+fn default_prior_weight() -> f64 {
+    0.5
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        Self {
+            mod_factor: 20.0,
+            mod_factor_weights: None,
+            competition_weights: None,
+            home_advantage: 65.0,
+            iterations: 10000,
+            tore_slope: 0.0017854953143549,
+            tore_intercept: 1.3218390804597700,
+            adj_points: None,
+            adj_goals: None,
+            adj_goals_against: None,
+            adj_goal_diff: None,
+            adj_played: None,
+            rng: RngKind::Std,
+            seed: None,
+            tiebreakers: default_tiebreakers(),
+            fair_play: None,
+            rating_system: RatingSystemKind::Elo,
+            glicko: GlickoSettings::default(),
+            team_strengths: None,
+            strength_weight: default_strength_weight(),
+            poisson_strengths: None,
+            strength_priors: None,
+            prior_weight: default_prior_weight(),
+        }
+    }
+}
+
+/// Current table row for a single team.
+/// Used as the starting point in table-only mode, where the per-match history
+/// is unavailable and only the remaining fixtures are supplied.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TableEntry {
+    pub played: i32,
+    pub points: i32,
+    pub goals_for: i32,
+    pub goals_against: i32,
+}
+
+impl Season {
+    /// End the season after matchday `last`: later matches are dropped,
+    /// played or not, as if they were not on the schedule. Matches without
+    /// a matchday are kept.
+    pub fn truncate_to_matchday(&mut self, last: u32) {
+        self.matches
+            .retain(|m| m.matchday.is_none_or(|day| day <= last));
+    }
+}
+
+impl SimulationParams {
+    /// Fold a current table into the adjustment vectors so that the
+    /// simulated remaining fixtures are added on top of it.
+    /// `table` must have one entry per team.
+    pub fn apply_table_baseline(&mut self, table: &[TableEntry]) {
+        fn add(adj: &mut Option<Vec<i32>>, values: impl Iterator<Item = i32>) {
+            let values: Vec<i32> = values.collect();
+            match adj {
+                Some(existing) => {
+                    for (a, v) in existing.iter_mut().zip(values) {
+                        *a += v;
+                    }
+                }
+                None => *adj = Some(values),
+            }
+        }
+
+        add(&mut self.adj_points, table.iter().map(|t| t.points));
+        add(&mut self.adj_goals, table.iter().map(|t| t.goals_for));
+        add(
+            &mut self.adj_goals_against,
+            table.iter().map(|t| t.goals_against),
+        );
+        add(
+            &mut self.adj_goal_diff,
+            table.iter().map(|t| t.goals_for - t.goals_against),
+        );
+        add(&mut self.adj_played, table.iter().map(|t| t.played));
+    }
+
+    /// Empty table rows in team_id order, seeded with these adjustments.
+    pub fn baseline_standings(&self, number_teams: usize) -> Vec<TeamStanding> {
+        let mut standings = crate::simulation::initial_standings(
+            number_teams,
+            self.adj_points.as_deref(),
+            self.adj_goals.as_deref(),
+            self.adj_goals_against.as_deref(),
+            self.adj_goal_diff.as_deref(),
+        );
+        if let Some(played) = &self.adj_played {
+            for (s, p) in standings.iter_mut().zip(played) {
+                s.played = *p;
+            }
+        }
+        standings
+    }
+
+    /// Weight of `m` in the Elo updates: its own `weight`, else that of its
+    /// competition, else 1.
+    pub fn match_weight(&self, m: &Match) -> f64 {
+        m.weight.unwrap_or_else(|| {
+            m.competition
+                .as_ref()
+                .and_then(|c| self.competition_weights.as_ref()?.get(c))
+                .copied()
+                .unwrap_or(1.0)
+        })
+    }
+
+    /// `mod_factor_weights` for the schedule `matches`, each multiplied by
+    /// [`match_weight`](Self::match_weight); None when every match weighs 1.
+    pub fn schedule_weights(&self, matches: &[Match]) -> Option<Vec<f64>> {
+        let tagged = self.competition_weights.is_some();
+        if !matches
+            .iter()
+            .any(|m| m.weight.is_some() || (tagged && m.competition.is_some()))
+        {
+            return self.mod_factor_weights.clone();
+        }
+        Some(
+            matches
+                .iter()
+                .enumerate()
+                .map(|(i, m)| {
+                    let row = self
+                        .mod_factor_weights
+                        .as_ref()
+                        .and_then(|w| w.get(i))
+                        .copied()
+                        .unwrap_or(1.0);
+                    row * self.match_weight(m)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Row of the deterministic projected final table: played results plus
+/// expected points and goals from every remaining fixture
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectedStanding {
+    pub team_id: usize,
+    pub expected_points: f64,
+    pub expected_goals_for: f64,
+    pub expected_goals_against: f64,
+    pub expected_goal_difference: f64,
+    pub position: usize,
+}
+
+/// Result of Monte Carlo simulation - probability distribution of final positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    /// Probability matrix: rows are teams, columns are positions
+    /// probability[team_id][position] = probability of team finishing in that position
+    pub probability_matrix: Vec<Vec<f64>>,
+    pub team_names: Vec<String>,
+}
@@ -0,0 +1,150 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams, TeamStanding};
+use crate::projection::project_table;
+use crate::simulation::{stream_seeds, PreparedSeason};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Probabilities of one team winning each end-of-season superlative.
+/// A title shared by several teams in an iteration counts as a fraction
+/// for each, so every award sums to 1 over the league.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamAwards {
+    pub team_name: String,
+    /// Most goals scored (team-level proxy for the top scorer race)
+    pub most_goals: f64,
+    /// Fewest goals conceded
+    pub fewest_conceded: f64,
+    /// Largest final points minus expected points (from `project_table`)
+    pub biggest_overperformance: f64,
+    /// Smallest final points minus expected points
+    pub biggest_underperformance: f64,
+}
+
+/// Award probabilities over `params.iterations` simulated seasons, in
+/// team_id order. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_award_simulation(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+) -> Vec<TeamAwards> {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    awards_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Deterministic variant of [`run_award_simulation`].
+pub fn run_award_simulation_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    master_seed: u64,
+) -> Vec<TeamAwards> {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    awards_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Award shares per team: most goals, fewest conceded, over-, under-
+/// performance.
+type Shares = Vec<[f64; 4]>;
+
+fn awards_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    seeds: &[u64],
+) -> Vec<TeamAwards> {
+    let prepared = PreparedSeason::new(season, params);
+    let mut expected_points = vec![0.0; season.number_teams];
+    for row in project_table(season, params) {
+        expected_points[row.team_id] = row.expected_points;
+    }
+
+    let shares = match params.rng {
+        RngKind::Std => award_shares::<StdRng>(params, &prepared, &expected_points, seeds),
+        RngKind::SmallRng => award_shares::<SmallRng>(params, &prepared, &expected_points, seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            award_shares::<Xoshiro256PlusPlus>(params, &prepared, &expected_points, seeds)
+        }
+    };
+
+    let iterations = seeds.len().max(1) as f64;
+    shares
+        .into_iter()
+        .enumerate()
+        .map(|(team_id, [goals, conceded, over, under])| TeamAwards {
+            team_name: team_names
+                .get(team_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Team {}", team_id + 1)),
+            most_goals: goals / iterations,
+            fewest_conceded: conceded / iterations,
+            biggest_overperformance: over / iterations,
+            biggest_underperformance: under / iterations,
+        })
+        .collect()
+}
+
+fn award_shares<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    expected_points: &[f64],
+    seeds: &[u64],
+) -> Shares {
+    let n_teams = prepared.baseline.len();
+
+    seeds
+        .par_iter()
+        .fold(
+            || (IterBuffers::new(prepared), vec![[0.0; 4]; n_teams]),
+            |(mut buffers, mut shares), &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                // Higher is better for every award.
+                let scores = |row: &TeamStanding| {
+                    let surplus = row.points as f64 - expected_points[row.team_id];
+                    [
+                        row.goals_for as f64,
+                        -row.goals_against as f64,
+                        surplus,
+                        -surplus,
+                    ]
+                };
+
+                let mut best = [f64::MIN; 4];
+                for row in &buffers.standings {
+                    for (b, s) in best.iter_mut().zip(scores(row)) {
+                        *b = b.max(s);
+                    }
+                }
+                let mut winners = [0usize; 4];
+                for row in &buffers.standings {
+                    for (award, s) in scores(row).into_iter().enumerate() {
+                        winners[award] += usize::from(s == best[award]);
+                    }
+                }
+                for row in &buffers.standings {
+                    for (award, s) in scores(row).into_iter().enumerate() {
+                        if s == best[award] {
+                            shares[row.team_id][award] += 1.0 / winners[award] as f64;
+                        }
+                    }
+                }
+                (buffers, shares)
+            },
+        )
+        .map(|(_, shares)| shares)
+        .reduce(
+            || vec![[0.0; 4]; n_teams],
+            |mut a, b| {
+                for (row_a, row_b) in a.iter_mut().zip(b) {
+                    for (x, y) in row_a.iter_mut().zip(row_b) {
+                        *x += y;
+                    }
+                }
+                a
+            },
+        )
+}
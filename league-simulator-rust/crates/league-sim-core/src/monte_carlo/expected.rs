@@ -0,0 +1,186 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Row of the simulated expected table: means over the simulated seasons,
+/// with the spread of the final position.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedStanding {
+    pub team_name: String,
+    /// Mean final points, adjustments included
+    pub expected_points: f64,
+    /// Standard deviation of the final points
+    pub points_sd: f64,
+    pub expected_goal_difference: f64,
+    /// Mean final position, 1-based
+    pub expected_position: f64,
+    /// Standard deviation of the final position
+    pub position_sd: f64,
+    /// Central 90% interval of the final position: the 5th and 95th
+    /// percentile, 1-based
+    pub position_interval: [usize; 2],
+}
+
+/// Expected final table over `params.iterations` simulated seasons, ordered
+/// by expected points, then expected goal difference. Unlike
+/// [`project_table`](crate::projection::project_table), which adds expected
+/// results without sampling, the means here come from the simulated seasons
+/// and carry their spread. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_expected_table(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+) -> Vec<ExpectedStanding> {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    expected_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Deterministic variant of [`run_expected_table`].
+pub fn run_expected_table_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    master_seed: u64,
+) -> Vec<ExpectedStanding> {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    expected_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Per team sums over the runs. Integer sums keep the result independent
+/// of the order rayon combines them in.
+#[derive(Clone)]
+struct Totals {
+    points: i64,
+    points_squared: i64,
+    goal_difference: i64,
+    /// Runs finishing in each position, 0-based
+    positions: Vec<usize>,
+}
+
+fn expected_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    seeds: &[u64],
+) -> Vec<ExpectedStanding> {
+    let _span = super::run_span(season, params).entered();
+    let prepared = PreparedSeason::new(season, params);
+    let totals = match params.rng {
+        RngKind::Std => total_with::<StdRng>(params, &prepared, seeds),
+        RngKind::SmallRng => total_with::<SmallRng>(params, &prepared, seeds),
+        RngKind::Xoshiro256PlusPlus => total_with::<Xoshiro256PlusPlus>(params, &prepared, seeds),
+    };
+
+    let runs = seeds.len().max(1) as f64;
+    let mut table: Vec<ExpectedStanding> = totals
+        .into_iter()
+        .enumerate()
+        .map(|(team_id, t)| {
+            let expected_points = t.points as f64 / runs;
+            let expected_position = t
+                .positions
+                .iter()
+                .enumerate()
+                .map(|(p, &n)| (p + 1) as f64 * n as f64)
+                .sum::<f64>()
+                / runs;
+            let position_variance = t
+                .positions
+                .iter()
+                .enumerate()
+                .map(|(p, &n)| ((p + 1) as f64 - expected_position).powi(2) * n as f64)
+                .sum::<f64>()
+                / runs;
+            let points_variance = t.points_squared as f64 / runs - expected_points.powi(2);
+            ExpectedStanding {
+                team_name: team_names
+                    .get(team_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Team {}", team_id + 1)),
+                expected_points,
+                points_sd: points_variance.max(0.0).sqrt(),
+                expected_goal_difference: t.goal_difference as f64 / runs,
+                expected_position,
+                position_sd: position_variance.sqrt(),
+                position_interval: [
+                    percentile(&t.positions, 0.05),
+                    percentile(&t.positions, 0.95),
+                ],
+            }
+        })
+        .collect();
+    table.sort_by(|a, b| {
+        b.expected_points.total_cmp(&a.expected_points).then(
+            b.expected_goal_difference
+                .total_cmp(&a.expected_goal_difference),
+        )
+    });
+    table
+}
+
+/// Smallest 1-based position whose cumulative share of runs reaches `q`.
+fn percentile(counts: &[usize], q: f64) -> usize {
+    let runs: usize = counts.iter().sum();
+    let mut seen = 0;
+    for (p, &n) in counts.iter().enumerate() {
+        seen += n;
+        if seen as f64 >= q * runs as f64 && seen > 0 {
+            return p + 1;
+        }
+    }
+    counts.len()
+}
+
+fn total_with<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    seeds: &[u64],
+) -> Vec<Totals> {
+    let n_teams = prepared.baseline.len();
+    let empty = || {
+        vec![
+            Totals {
+                points: 0,
+                points_squared: 0,
+                goal_difference: 0,
+                positions: vec![0; n_teams],
+            };
+            n_teams
+        ]
+    };
+
+    seeds
+        .par_iter()
+        .fold(
+            || (IterBuffers::new(prepared), empty()),
+            |(mut buffers, mut totals), &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                for row in &buffers.standings {
+                    let t = &mut totals[row.team_id];
+                    t.points += i64::from(row.points);
+                    t.points_squared += i64::from(row.points).pow(2);
+                    t.goal_difference += i64::from(row.goal_difference);
+                    t.positions[row.position - 1] += 1;
+                }
+                (buffers, totals)
+            },
+        )
+        .map(|(_, totals)| totals)
+        .reduce(empty, |mut a, b| {
+            for (x, y) in a.iter_mut().zip(b) {
+                x.points += y.points;
+                x.points_squared += y.points_squared;
+                x.goal_difference += y.goal_difference;
+                for (p, q) in x.positions.iter_mut().zip(y.positions) {
+                    *p += q;
+                }
+            }
+            a
+        })
+}
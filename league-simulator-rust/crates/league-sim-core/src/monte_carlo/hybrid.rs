@@ -0,0 +1,300 @@
+use super::{master_seed, ranked_result, run_span, IterBuffers};
+use crate::models::{Match, RngKind, Season, SimulationParams, SimulationResult, TeamStanding};
+use crate::simulation::{
+    add_match_results, poisson_pmf_into, stream_seed, stream_seeds, GoalModel, PreparedSeason,
+    TiebreakScratch, Tiebreakers,
+};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::info_span;
+
+/// How the probabilities of a run are computed.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Solver {
+    /// Every remaining fixture sampled
+    MonteCarlo,
+    /// Home win, draw and away win of each final-matchday fixture
+    /// enumerated exactly; earlier rounds and the scorelines within each
+    /// result sampled
+    Hybrid,
+}
+
+/// Most final-matchday fixtures the hybrid solver enumerates: 3^10 =
+/// 59,049 result combinations per sampled run of the earlier rounds.
+pub const HYBRID_MAX_FIXTURES: usize = 10;
+
+/// Rounds, the final one included, that may remain for [`auto_solver`] to
+/// pick the hybrid solver. With more, the earlier rounds dominate the
+/// uncertainty and the enumeration only costs runs.
+const HYBRID_MAX_ROUNDS: usize = 2;
+
+/// Fixed-point scale of the outcome weights, so sums do not depend on the
+/// order rayon combines them in.
+const WEIGHT_SCALE: f64 = (1u64 << 40) as f64;
+
+/// Number of unplayed fixtures on the final matchday: the trailing run of
+/// unplayed schedule rows in which no team plays twice and, where the rows
+/// carry matchdays, all on the last row's. None if there are none or more
+/// than [`HYBRID_MAX_FIXTURES`].
+pub fn final_matchday_fixtures(season: &Season) -> Option<usize> {
+    let last_day = season.matches.last()?.matchday;
+    let mut seen = vec![false; season.number_teams];
+    let count = season
+        .matches
+        .iter()
+        .rev()
+        .take_while(|m| {
+            let fresh = m.goals_home.is_none()
+                && m.matchday == last_day
+                && !seen[m.team_home]
+                && !seen[m.team_away];
+            seen[m.team_home] = true;
+            seen[m.team_away] = true;
+            fresh
+        })
+        .count();
+    (1..=HYBRID_MAX_FIXTURES).contains(&count).then_some(count)
+}
+
+/// The solver picked by the remaining-match count: hybrid once at most two
+/// rounds remain (no more unplayed fixtures than twice the final
+/// matchday's) and the final matchday's 3^n result combinations fit in
+/// `params.iterations`; Monte Carlo otherwise.
+pub fn auto_solver(season: &Season, params: &SimulationParams) -> Solver {
+    let Some(fixtures) = final_matchday_fixtures(season) else {
+        return Solver::MonteCarlo;
+    };
+    let remaining = season
+        .matches
+        .iter()
+        .filter(|m| m.goals_home.is_none())
+        .count();
+    if remaining <= HYBRID_MAX_ROUNDS * fixtures && 3usize.pow(fixtures as u32) <= params.iterations
+    {
+        Solver::Hybrid
+    } else {
+        Solver::MonteCarlo
+    }
+}
+
+/// Variant of [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation)
+/// with the hybrid solver, for sharper last-day probabilities.
+///
+/// Each run samples the rounds before the final matchday (see
+/// [`final_matchday_fixtures`]) as usual, then enumerates every combination
+/// of home win, draw and away win on it, weighted by its probability under
+/// the goal model at the ratings the run reached, with one scoreline per
+/// fixture drawn given its result. There are `params.iterations / 3^n`
+/// runs (rounded up), so about as many final tables are ranked as in a
+/// Monte Carlo run, but the final-day results carry no sampling noise.
+/// Falls back to Monte Carlo if there is no final matchday to enumerate.
+/// Seeded like `run_monte_carlo_simulation`.
+pub fn run_hybrid_simulation(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+) -> SimulationResult {
+    let Some(fixtures) = final_matchday_fixtures(season) else {
+        return super::run_monte_carlo_simulation(season, params, team_names);
+    };
+    let _span = run_span(season, params).entered();
+    let split = season.matches.len() - fixtures;
+    let earlier = Season {
+        matches: season.matches[..split].to_vec(),
+        team_elos: season.team_elos.clone(),
+        number_teams: season.number_teams,
+    };
+    let final_day = &season.matches[split..];
+    let runs = params.iterations.div_ceil(3usize.pow(fixtures as u32));
+    let seeds = stream_seeds(master_seed(params), 0, runs.max(1));
+
+    let prepared = info_span!("prepare").in_scope(|| PreparedSeason::new(&earlier, params));
+    let (weights, total) = info_span!("simulate").in_scope(|| match params.rng {
+        RngKind::Std => weigh_positions::<StdRng>(params, &prepared, final_day, &seeds),
+        RngKind::SmallRng => weigh_positions::<SmallRng>(params, &prepared, final_day, &seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            weigh_positions::<Xoshiro256PlusPlus>(params, &prepared, final_day, &seeds)
+        }
+    });
+    info_span!("aggregate").in_scope(|| {
+        let matrix: Vec<Vec<f64>> = weights
+            .iter()
+            .map(|row| row.iter().map(|&w| w as f64 / total as f64).collect())
+            .collect();
+        ranked_result(&matrix, &team_names)
+    })
+}
+
+/// Weight of each final position per team, `weights[team_id][position]`,
+/// in units of [`WEIGHT_SCALE`], and the total weight.
+fn weigh_positions<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    final_day: &[Match],
+    seeds: &[u64],
+) -> (Vec<Vec<u64>>, u64) {
+    let n_teams = prepared.baseline.len();
+    let empty = || (vec![vec![0u64; n_teams]; n_teams], 0u64);
+    seeds
+        .par_iter()
+        .fold(
+            || {
+                (
+                    IterBuffers::new(prepared),
+                    FinalDay::new(final_day),
+                    empty(),
+                )
+            },
+            |(mut buffers, mut day, (mut weights, mut total)), &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                // The table before the final matchday, in team_id order.
+                prepared.finish_table(&buffers.matches, &mut buffers.standings);
+                let mut rng = R::seed_from_u64(stream_seed(seed, 1));
+                day.weigh::<R>(params, prepared, &buffers, &mut rng, |table, w| {
+                    for row in table {
+                        weights[row.team_id][row.position - 1] += w;
+                    }
+                    total += w;
+                });
+                (buffers, day, (weights, total))
+            },
+        )
+        .map(|(_, _, sums)| sums)
+        .reduce(empty, |(mut a, total_a), (b, total_b)| {
+            for (row_a, row_b) in a.iter_mut().zip(b) {
+                for (cell_a, cell_b) in row_a.iter_mut().zip(row_b) {
+                    *cell_a += cell_b;
+                }
+            }
+            (a, total_a + total_b)
+        })
+}
+
+/// Buffers for enumerating the final matchday, reused across runs.
+struct FinalDay {
+    fixtures: Vec<Match>,
+    results: Vec<FixtureResults>,
+    table: Vec<TeamStanding>,
+    tiebreak: TiebreakScratch,
+}
+
+impl FinalDay {
+    fn new(final_day: &[Match]) -> Self {
+        Self {
+            fixtures: final_day.to_vec(),
+            results: final_day
+                .iter()
+                .map(|_| FixtureResults::default())
+                .collect(),
+            table: Vec::new(),
+            tiebreak: TiebreakScratch::default(),
+        }
+    }
+
+    /// Rank the final table of every result combination after the earlier
+    /// rounds in `buffers`, and pass each with its weight to `record`.
+    /// Combinations whose weight rounds to zero are skipped.
+    fn weigh<R: Rng + RngExt>(
+        &mut self,
+        params: &SimulationParams,
+        prepared: &PreparedSeason,
+        buffers: &IterBuffers,
+        rng: &mut R,
+        mut record: impl FnMut(&[TeamStanding], u64),
+    ) {
+        for (fixture, results) in self.fixtures.iter().zip(&mut self.results) {
+            let (home, away) = (fixture.team_home, fixture.team_away);
+            let delta = buffers.elos[home] + params.home_advantage - buffers.elos[away];
+            let (lambda_home, lambda_away) = if let Some(model) = &params.poisson_strengths {
+                model.expected_goals(home, away, delta)
+            } else if let Some(model) = params.strength_goal_model() {
+                model.expected_goals(home, away, delta)
+            } else if let Some(model) = params.prior_goal_model() {
+                model.expected_goals(home, away, delta)
+            } else {
+                params.goal_model().expected_goals(home, away, delta)
+            };
+            results.fill(lambda_home, lambda_away);
+        }
+
+        let tiebreakers = Tiebreakers::new(params);
+        for combination in 0..3usize.pow(self.fixtures.len() as u32) {
+            let mut digits = combination;
+            let mut weight = 1.0;
+            for (fixture, results) in self.fixtures.iter_mut().zip(&self.results) {
+                let result = digits % 3;
+                digits /= 3;
+                weight *= results.probability[result];
+                let (goals_home, goals_away) = results.draw(result, rng.random());
+                fixture.goals_home = Some(goals_home);
+                fixture.goals_away = Some(goals_away);
+            }
+            let weight = (weight * WEIGHT_SCALE).round() as u64;
+            if weight == 0 {
+                continue;
+            }
+            self.table.clear();
+            self.table.extend_from_slice(&buffers.standings);
+            add_match_results(&mut self.table, &self.fixtures);
+            tiebreakers.sort_with(
+                &mut self.table,
+                &[&prepared.prefix, &buffers.matches, &self.fixtures],
+                || rng.next_u64(),
+                &mut self.tiebreak,
+            );
+            record(&self.table, weight);
+        }
+    }
+}
+
+/// Scorelines of one fixture grouped by result: home win, draw, away win.
+#[derive(Default)]
+struct FixtureResults {
+    probability: [f64; 3],
+    scorelines: [Vec<(i32, i32)>; 3],
+    /// Running sums of the scoreline probabilities, per result
+    cumulative: [Vec<f64>; 3],
+    pmf_home: Vec<f64>,
+    pmf_away: Vec<f64>,
+}
+
+impl FixtureResults {
+    /// Fill in the scorelines of independent Poisson goals with these means.
+    fn fill(&mut self, lambda_home: f64, lambda_away: f64) {
+        poisson_pmf_into(lambda_home, &mut self.pmf_home);
+        poisson_pmf_into(lambda_away, &mut self.pmf_away);
+        for result in 0..3 {
+            self.scorelines[result].clear();
+            self.cumulative[result].clear();
+        }
+        let mut sums = [0.0; 3];
+        for (h, &p_home) in self.pmf_home.iter().enumerate() {
+            for (a, &p_away) in self.pmf_away.iter().enumerate() {
+                let result = match h.cmp(&a) {
+                    std::cmp::Ordering::Greater => 0,
+                    std::cmp::Ordering::Equal => 1,
+                    std::cmp::Ordering::Less => 2,
+                };
+                sums[result] += p_home * p_away;
+                self.scorelines[result].push((h as i32, a as i32));
+                self.cumulative[result].push(sums[result]);
+            }
+        }
+        self.probability = sums;
+    }
+
+    /// Scoreline of `result` at quantile `u` of its conditional
+    /// distribution; 1:0, 0:0 or 0:1 if the result is out of reach.
+    fn draw(&self, result: usize, u: f64) -> (i32, i32) {
+        let (scorelines, cumulative) = (&self.scorelines[result], &self.cumulative[result]);
+        let Some(&mass) = cumulative.last() else {
+            return [(1, 0), (0, 0), (0, 1)][result];
+        };
+        let index = cumulative.partition_point(|&c| c < u * mass);
+        scorelines[index.min(scorelines.len() - 1)]
+    }
+}
@@ -0,0 +1,218 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use crate::structure::Zone;
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How much the result of one remaining fixture moves the zone races.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchImportance {
+    /// 0-based index of the fixture in `Season::matches`
+    pub row: usize,
+    pub team_home: String,
+    pub team_away: String,
+    /// Share of runs ending in a home win, a draw and an away win
+    pub outcome_probabilities: [f64; 3],
+    /// Expected zone probability moved by the result, summed over teams,
+    /// per zone name
+    pub swings: BTreeMap<String, f64>,
+    /// Sum of `swings`
+    pub importance: f64,
+}
+
+/// Title, top four and the bottom `relegated` places of a league of
+/// `number_teams`, the zones "game of the week" rankings usually weigh.
+pub fn importance_zones(number_teams: usize, relegated: usize) -> Vec<Zone> {
+    let zone = |name: &str, first: usize, last: usize| Zone {
+        name: name.to_string(),
+        first,
+        last,
+    };
+    let relegated = relegated.min(number_teams);
+    let mut zones = vec![zone("title", 1, 1)];
+    if number_teams >= 4 {
+        zones.push(zone("top_4", 1, 4));
+    }
+    if relegated > 0 {
+        zones.push(zone(
+            "relegation",
+            number_teams + 1 - relegated,
+            number_teams,
+        ));
+    }
+    zones
+}
+
+/// Rank the unplayed fixtures by how much their result matters.
+///
+/// Over `params.iterations` simulated seasons, a fixture's swing for a zone
+/// is the probability each team's chance of finishing in the zone moves,
+/// in expectation over home win, draw and away win, from its unconditional
+/// value: `Σ_team Σ_result P(result) · |P(zone | result) − P(zone)|`.
+/// Importance is the sum over `zones`; the list is sorted by it, most
+/// important first. Sampling noise adds a small positive bias, so compare
+/// fixtures from one run. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_match_importance(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    zones: &[Zone],
+) -> Vec<MatchImportance> {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    importance_with_seeds(season, params, team_names, zones, &seeds)
+}
+
+/// Deterministic variant of [`run_match_importance`].
+pub fn run_match_importance_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    zones: &[Zone],
+    master_seed: u64,
+) -> Vec<MatchImportance> {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    importance_with_seeds(season, params, team_names, zones, &seeds)
+}
+
+/// Per run counts: `results[fixture][result]`, and
+/// `in_zone[((fixture * 3 + result) * zones + zone) * teams + team]`.
+struct Counts {
+    results: Vec<[usize; 3]>,
+    in_zone: Vec<usize>,
+}
+
+fn importance_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    zones: &[Zone],
+    seeds: &[u64],
+) -> Vec<MatchImportance> {
+    let _span = super::run_span(season, params).entered();
+    let prepared = PreparedSeason::new(season, params);
+    // Rows of the unplayed fixtures, and their indices into the tail.
+    let split = season.matches.len() - prepared.tail.len();
+    let fixtures: Vec<usize> = (0..prepared.tail.len())
+        .filter(|&i| prepared.tail[i].goals_home.is_none())
+        .collect();
+
+    let counts = match params.rng {
+        RngKind::Std => count_with::<StdRng>(params, &prepared, &fixtures, zones, seeds),
+        RngKind::SmallRng => count_with::<SmallRng>(params, &prepared, &fixtures, zones, seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            count_with::<Xoshiro256PlusPlus>(params, &prepared, &fixtures, zones, seeds)
+        }
+    };
+
+    let teams = season.number_teams;
+    let runs = seeds.len().max(1) as f64;
+    let name = |team: usize| {
+        team_names
+            .get(team)
+            .cloned()
+            .unwrap_or_else(|| format!("Team {}", team + 1))
+    };
+    let mut ranked: Vec<MatchImportance> = fixtures
+        .iter()
+        .enumerate()
+        .map(|(f, &tail_index)| {
+            let m = &prepared.tail[tail_index];
+            let results = counts.results[f];
+            let swings: BTreeMap<String, f64> = zones
+                .iter()
+                .enumerate()
+                .map(|(z, zone)| {
+                    let at = |result: usize, team: usize| {
+                        counts.in_zone[((f * 3 + result) * zones.len() + z) * teams + team] as f64
+                    };
+                    // P(o) · |P(z|o) − P(z)| = |c_o − n_o · c / N| / N for
+                    // c_o runs in the zone out of n_o with result o.
+                    let swing: f64 = (0..teams)
+                        .map(|team| {
+                            let total: f64 = (0..3).map(|o| at(o, team)).sum();
+                            (0..3)
+                                .map(|o| (at(o, team) - results[o] as f64 * total / runs).abs())
+                                .sum::<f64>()
+                                / runs
+                        })
+                        .sum();
+                    (zone.name.clone(), swing)
+                })
+                .collect();
+            MatchImportance {
+                row: split + tail_index,
+                team_home: name(m.team_home),
+                team_away: name(m.team_away),
+                outcome_probabilities: results.map(|n| n as f64 / runs),
+                importance: swings.values().sum(),
+                swings,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.importance.total_cmp(&a.importance));
+    ranked
+}
+
+fn count_with<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    fixtures: &[usize],
+    zones: &[Zone],
+    seeds: &[u64],
+) -> Counts {
+    let teams = prepared.baseline.len();
+    let empty = || Counts {
+        results: vec![[0; 3]; fixtures.len()],
+        in_zone: vec![0; fixtures.len() * 3 * zones.len() * teams],
+    };
+
+    seeds
+        .par_iter()
+        .fold(
+            || (IterBuffers::new(prepared), empty(), Vec::new()),
+            |(mut buffers, mut counts, mut members), &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                // Only the few teams in a zone are counted, not every team
+                // for every fixture.
+                members.clear();
+                for (z, zone) in zones.iter().enumerate() {
+                    let last = zone.last.min(buffers.standings.len());
+                    for row in buffers.standings.get(zone.first - 1..last).unwrap_or(&[]) {
+                        members.push(z * teams + row.team_id);
+                    }
+                }
+                for (f, &i) in fixtures.iter().enumerate() {
+                    let m = &buffers.matches[i];
+                    let result = match m.goals_home.cmp(&m.goals_away) {
+                        std::cmp::Ordering::Greater => 0,
+                        std::cmp::Ordering::Equal => 1,
+                        std::cmp::Ordering::Less => 2,
+                    };
+                    counts.results[f][result] += 1;
+                    let base = (f * 3 + result) * zones.len() * teams;
+                    for &member in &members {
+                        counts.in_zone[base + member] += 1;
+                    }
+                }
+                (buffers, counts, members)
+            },
+        )
+        .map(|(_, counts, _)| counts)
+        .reduce(empty, |mut a, b| {
+            for (x, y) in a.results.iter_mut().zip(b.results) {
+                for (p, q) in x.iter_mut().zip(y) {
+                    *p += q;
+                }
+            }
+            for (x, y) in a.in_zone.iter_mut().zip(b.in_zone) {
+                *x += y;
+            }
+            a
+        })
+}
@@ -0,0 +1,64 @@
+use super::{Division, MultiSeason};
+use crate::models::{Season, SimulationParams};
+use crate::simulation::TwoLegRules;
+
+/// One league of a [`LeagueSystem`].
+#[derive(Debug, Clone)]
+pub struct Tier {
+    /// The current season, team indices local to the league
+    pub season: Season,
+    pub params: SimulationParams,
+    pub team_names: Vec<String>,
+    /// Promotion ranking adjustment per team, see
+    /// [`Division::promotion_adjustments`]
+    pub promotion_adjustments: Option<Vec<i32>>,
+    /// See [`Division::elo_offset`]
+    pub elo_offset: f64,
+}
+
+/// The three German professional leagues, simulated jointly.
+///
+/// Between neighbouring tiers the bottom two go down and the top two
+/// eligible teams go up; the 16th plays the best remaining team of the tier
+/// below for its place. 3. Liga second teams are kept out of the promotion
+/// places by their promotion adjustments (-50, as in the TeamList). The
+/// drop from the 3. Liga to the Regionalliga is outside the system, so a
+/// 3. Liga team's probability of staying includes it.
+#[derive(Debug, Clone)]
+pub struct LeagueSystem {
+    pub bundesliga: Tier,
+    pub zweite_bundesliga: Tier,
+    pub dritte_liga: Tier,
+}
+
+impl LeagueSystem {
+    pub const DIVISIONS: [&'static str; 3] = ["Bundesliga", "2. Bundesliga", "3. Liga"];
+
+    /// The system over `seasons` seasons as a [`MultiSeason`], whose
+    /// `division_probabilities[0]` answers "which league next year".
+    pub fn into_multi_season(self, seasons: usize, regression: f64) -> MultiSeason {
+        let tiers = [self.bundesliga, self.zweite_bundesliga, self.dritte_liga];
+        let divisions = tiers
+            .into_iter()
+            .zip(Self::DIVISIONS)
+            .enumerate()
+            .map(|(d, (tier, name))| Division {
+                name: name.to_string(),
+                season: tier.season,
+                params: tier.params,
+                team_names: tier.team_names,
+                promoted: if d > 0 { 2 } else { 0 },
+                relegated: if d < 2 { 2 } else { 0 },
+                promotion_adjustments: tier.promotion_adjustments,
+                relegation_playoff: d < 2,
+                playoff_rules: TwoLegRules::default(),
+                elo_offset: tier.elo_offset,
+            })
+            .collect();
+        MultiSeason {
+            divisions,
+            seasons,
+            regression,
+        }
+    }
+}
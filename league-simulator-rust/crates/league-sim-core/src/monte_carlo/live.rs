@@ -0,0 +1,25 @@
+use super::{count_positions, master_seed, probabilities_from_counts, run_span};
+use crate::models::{LiveMatch, Season, SimulationParams, SimulationResult};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use tracing::info_span;
+
+/// Variant of [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation)
+/// for a matchday in progress: every match in `live` starts from its current
+/// score and only the remaining minutes are simulated. Entries that point at
+/// played matches are ignored.
+pub fn run_live_simulation(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    live: &[LiveMatch],
+) -> SimulationResult {
+    let _span = run_span(season, params).entered();
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+
+    let prepared = info_span!("prepare")
+        .in_scope(|| PreparedSeason::new(season, params).with_live(season, live));
+    let position_counts =
+        info_span!("simulate").in_scope(|| count_positions(season, params, &prepared, &seeds));
+    info_span!("aggregate")
+        .in_scope(|| probabilities_from_counts(&position_counts, params.iterations, &team_names))
+}
@@ -0,0 +1,148 @@
+use super::{count_positions, master_seed, rank_by_average_position};
+use crate::models::{Season, SimulationParams, SimulationResult};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use serde::{Deserialize, Serialize};
+
+/// One component of a scenario mixture: a model variant and its weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Relative weight; weights are normalised over the mixture
+    pub weight: f64,
+    /// Parameters of this variant (`iterations` is ignored, the mixture
+    /// splits its own iteration budget by weight)
+    pub params: SimulationParams,
+    /// Factor applied to every team's distance from the mean Elo. Below 1
+    /// brings the teams closer together (a high-variance season), 1 leaves
+    /// the ratings unchanged
+    pub elo_scale: f64,
+}
+
+/// Per-component result of a mixture, rows in the mixture's rank order.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    /// Normalised weight
+    pub weight: f64,
+    pub iterations: usize,
+    pub probability_matrix: Vec<Vec<f64>>,
+}
+
+/// Weighted combination of several scenarios plus the spread between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixtureResult {
+    /// Weighted mixture of the component probability matrices
+    pub result: SimulationResult,
+    pub scenarios: Vec<ScenarioResult>,
+    /// Smallest probability any component assigns, per team and position
+    pub lower_bound: Vec<Vec<f64>>,
+    /// Largest probability any component assigns, per team and position
+    pub upper_bound: Vec<Vec<f64>>,
+}
+
+/// Simulate every scenario and combine them with their weights.
+///
+/// `iterations` is split across the scenarios in proportion to their
+/// weights (at least one each). The combined matrix weights each
+/// component's probabilities exactly rather than by its rounded iteration
+/// count. All matrices are returned in the combined rank order. Weights must
+/// be positive and finite, and `scenarios` non-empty.
+pub fn run_scenario_mixture(
+    season: &Season,
+    scenarios: &[Scenario],
+    iterations: usize,
+    team_names: Vec<String>,
+) -> MixtureResult {
+    assert!(!scenarios.is_empty(), "mixture needs at least one scenario");
+    let n_teams = season.number_teams;
+    let total_weight: f64 = scenarios.iter().map(|s| s.weight).sum();
+    // Scenarios share the request's seed, if any, and take consecutive
+    // ranges of its iteration streams.
+    let master = master_seed(&scenarios[0].params);
+    let mut first = 0;
+
+    // Component matrices in team_id order.
+    let components: Vec<(f64, usize, Vec<Vec<f64>>)> = scenarios
+        .iter()
+        .map(|scenario| {
+            let weight = scenario.weight / total_weight;
+            let runs = ((iterations as f64 * weight).round() as usize).max(1);
+            let params = SimulationParams {
+                iterations: runs,
+                ..scenario.params.clone()
+            };
+            let season = scaled_season(season, scenario.elo_scale);
+            let seeds = stream_seeds(master, first, runs);
+            first += runs as u64;
+
+            let prepared = PreparedSeason::new(&season, &params);
+            let counts = count_positions(&season, &params, &prepared, &seeds);
+            let matrix = counts
+                .iter()
+                .map(|row| row.iter().map(|&c| c as f64 / runs as f64).collect())
+                .collect();
+            (weight, runs, matrix)
+        })
+        .collect();
+
+    let mut combined = vec![vec![0.0; n_teams]; n_teams];
+    let mut lower = vec![vec![f64::INFINITY; n_teams]; n_teams];
+    let mut upper = vec![vec![0.0f64; n_teams]; n_teams];
+    for (weight, _, matrix) in &components {
+        for team in 0..n_teams {
+            for position in 0..n_teams {
+                let p = matrix[team][position];
+                combined[team][position] += weight * p;
+                lower[team][position] = lower[team][position].min(p);
+                upper[team][position] = upper[team][position].max(p);
+            }
+        }
+    }
+
+    let order = rank_by_average_position(&combined);
+    let reorder = |matrix: &[Vec<f64>]| -> Vec<Vec<f64>> {
+        order.iter().map(|&team| matrix[team].clone()).collect()
+    };
+
+    MixtureResult {
+        result: SimulationResult {
+            probability_matrix: reorder(&combined),
+            team_names: order
+                .iter()
+                .map(|&team| {
+                    team_names
+                        .get(team)
+                        .cloned()
+                        .unwrap_or_else(|| format!("Team {}", team + 1))
+                })
+                .collect(),
+        },
+        scenarios: scenarios
+            .iter()
+            .zip(&components)
+            .map(|(scenario, (weight, runs, matrix))| ScenarioResult {
+                name: scenario.name.clone(),
+                weight: *weight,
+                iterations: *runs,
+                probability_matrix: reorder(matrix),
+            })
+            .collect(),
+        lower_bound: reorder(&lower),
+        upper_bound: reorder(&upper),
+    }
+}
+
+/// `season` with every Elo's distance from the mean multiplied by `scale`.
+fn scaled_season(season: &Season, scale: f64) -> Season {
+    let n = season.team_elos.len().max(1) as f64;
+    let mean = season.team_elos.iter().sum::<f64>() / n;
+    Season {
+        team_elos: season
+            .team_elos
+            .iter()
+            .map(|elo| mean + (elo - mean) * scale)
+            .collect(),
+        ..season.clone()
+    }
+}
@@ -0,0 +1,711 @@
+use crate::models::{RngKind, Season, SimulationParams, SimulationResult};
+use crate::rating::{match_weight, update_pair, Elo, GlickoRating, RatingSystem};
+use crate::simulation::{
+    add_match_results, entropy_seed, expected_goals_lanes, poisson_quantile_lanes,
+    simulate_season_rated, stream_seeds, GoalModel, PreparedSeason, TiebreakScratch, Tiebreakers,
+    LANES,
+};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info_span;
+
+mod awards;
+mod expected;
+mod hybrid;
+mod importance;
+mod league_system;
+mod live;
+mod mixture;
+mod multi_season;
+mod replay;
+mod samples;
+mod team;
+mod trajectory;
+pub use awards::*;
+pub use expected::*;
+pub use hybrid::*;
+pub use importance::*;
+pub use league_system::*;
+pub use live::*;
+pub use mixture::*;
+pub use multi_season::*;
+pub use replay::*;
+pub use samples::*;
+pub use team::*;
+pub use trajectory::*;
+
+/// Run Monte Carlo simulations in parallel to get probability distribution.
+/// Matches the logic in simulationsCPP.R and leagueSimulatorCPP.R.
+///
+/// Without `params.seed` the run draws its master seed from the OS entropy
+/// pool, so two consecutive calls with the same `params` produce slightly
+/// different probability matrices. This matches the R/C++ behavior the
+/// scheduler relies on. With `params.seed` set the run is exactly
+/// [`run_monte_carlo_simulation_seeded`] with that seed.
+pub fn run_monte_carlo_simulation(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+) -> SimulationResult {
+    if let Some(counts) = settled_counts(season, params, params.iterations) {
+        return probabilities_from_counts(&counts, params.iterations, &team_names);
+    }
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    run_monte_carlo_simulation_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Deterministic variant of [`run_monte_carlo_simulation`].
+///
+/// Iteration `i` draws from its own stream, seeded with
+/// [`stream_seed`](crate::simulation::stream_seed)`(master_seed, i)`, so
+/// the same `master_seed` and `params` give bit-identical probability
+/// matrices whatever the thread count, and however the iterations are
+/// batched or sharded: ranges run with
+/// [`count_iteration_range`] add up to the counts of this run. Seeded API
+/// requests rely on this to be reproducible.
+pub fn run_monte_carlo_simulation_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    master_seed: u64,
+) -> SimulationResult {
+    if let Some(counts) = settled_counts(season, params, params.iterations) {
+        return probabilities_from_counts(&counts, params.iterations, &team_names);
+    }
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    run_monte_carlo_simulation_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Shared implementation: takes a pre-built per-iteration seed slice so the
+/// caller controls the determinism policy. Iteration order under Rayon does
+/// not affect the result because aggregation is via integer counts (commutative).
+fn run_monte_carlo_simulation_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    seeds: &[u64],
+) -> SimulationResult {
+    assert_eq!(
+        seeds.len(),
+        params.iterations,
+        "must provide one seed per iteration"
+    );
+
+    let _span = run_span(season, params).entered();
+    let prepared = info_span!("prepare").in_scope(|| PreparedSeason::new(season, params));
+    let position_counts =
+        info_span!("simulate").in_scope(|| count_positions(season, params, &prepared, seeds));
+    info_span!("aggregate")
+        .in_scope(|| probabilities_from_counts(&position_counts, params.iterations, &team_names))
+}
+
+/// Position counts of iterations `first..first + count` of the run keyed on
+/// `master_seed`: `counts[team_id][position]`. Iterations draw from
+/// [`stream_seed`](crate::simulation::stream_seed), so ranges covering
+/// `0..params.iterations`, wherever they ran, add up to exactly the counts
+/// behind [`run_monte_carlo_simulation_seeded`] with that seed; see
+/// [`probabilities_from_counts`]. `params.iterations` is ignored.
+pub fn count_iteration_range(
+    season: &Season,
+    params: &SimulationParams,
+    master_seed: u64,
+    first: u64,
+    count: usize,
+) -> Vec<Vec<usize>> {
+    if let Some(counts) = settled_counts(season, params, count) {
+        return counts;
+    }
+    let _span = run_span(season, params).entered();
+    let seeds = stream_seeds(master_seed, first, count);
+    let prepared = info_span!("prepare").in_scope(|| PreparedSeason::new(season, params));
+    info_span!("simulate").in_scope(|| count_positions(season, params, &prepared, &seeds))
+}
+
+/// Position counts of `iterations` runs of a season with nothing left to
+/// play: every run ends in the one final table, so none is simulated. None
+/// if a match is unplayed or the table needs a drawing of lots, which only
+/// sampling decides.
+fn settled_counts(
+    season: &Season,
+    params: &SimulationParams,
+    iterations: usize,
+) -> Option<Vec<Vec<usize>>> {
+    if season
+        .matches
+        .iter()
+        .any(|m| m.goals_home.is_none() || m.goals_away.is_none())
+    {
+        return None;
+    }
+    let mut table = params.baseline_standings(season.number_teams);
+    add_match_results(&mut table, &season.matches);
+    let mut lots = false;
+    Tiebreakers::new(params).sort(&mut table, &[&season.matches], || {
+        lots = true;
+        0
+    });
+    if lots {
+        return None;
+    }
+    let mut counts = vec![vec![0; season.number_teams]; season.number_teams];
+    for row in &table {
+        counts[row.team_id][row.position - 1] = iterations;
+    }
+    Some(counts)
+}
+
+/// Master seed of a run: `params.seed` if set, so the run can be repeated
+/// exactly, else fresh OS entropy.
+fn master_seed(params: &SimulationParams) -> u64 {
+    params.seed.unwrap_or_else(entropy_seed)
+}
+
+/// Span covering one Monte Carlo run, with its phases as children.
+fn run_span(season: &Season, params: &SimulationParams) -> tracing::Span {
+    info_span!(
+        "monte_carlo",
+        iterations = params.iterations,
+        teams = season.number_teams,
+        matches = season.matches.len(),
+    )
+}
+
+/// State of a batched run after a completed batch.
+pub struct SimulationProgress<'a> {
+    pub iterations_done: usize,
+    pub iterations_total: usize,
+    /// Position counts so far: `counts[team_id][position]`
+    pub counts: &'a [Vec<usize>],
+}
+
+impl SimulationProgress<'_> {
+    /// Interim probability matrix from the iterations completed so far.
+    pub fn result(&self, team_names: &[String]) -> SimulationResult {
+        probabilities_from_counts(self.counts, self.iterations_done, team_names)
+    }
+
+    /// Largest standard error of the interim probabilities, as
+    /// [`max_standard_error`] of [`Self::result`].
+    pub fn max_standard_error(&self) -> f64 {
+        let n = self.iterations_done.max(1) as f64;
+        self.counts
+            .iter()
+            .flatten()
+            .map(|&c| c as f64 / n)
+            .map(|p| (p * (1.0 - p) / n).sqrt())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Variant of [`run_monte_carlo_simulation`] that runs the iterations in
+/// batches of `batch_size` and calls `on_batch` after each one, so callers
+/// can report progress and interim results on long runs. The final result
+/// has the same distribution as a single unbatched run.
+pub fn run_monte_carlo_simulation_batched(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    batch_size: usize,
+    mut on_batch: impl FnMut(&SimulationProgress),
+) -> SimulationResult {
+    let never = AtomicBool::new(false);
+    let (counts, _) = run_batches(season, params, batch_size, &never, |progress| {
+        on_batch(progress);
+        ControlFlow::Continue(())
+    })
+    .expect("run without a cancellation signal always completes");
+    probabilities_from_counts(&counts, params.iterations, &team_names)
+}
+
+/// Variant of [`run_monte_carlo_simulation`] that checks `cancel` between
+/// batches of `batch_size` iterations and stops early once it is set.
+/// Returns None if the run was cancelled.
+pub fn run_monte_carlo_simulation_cancellable(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    batch_size: usize,
+    cancel: &AtomicBool,
+) -> Option<SimulationResult> {
+    let (counts, _) = run_batches(season, params, batch_size, cancel, |_| {
+        ControlFlow::Continue(())
+    })?;
+    Some(probabilities_from_counts(
+        &counts,
+        params.iterations,
+        &team_names,
+    ))
+}
+
+/// Variant of [`run_monte_carlo_simulation_cancellable`] that also stops
+/// once `stop` returns true after a batch, e.g. at a deadline, so
+/// `params.iterations` is only an upper bound. Returns the result over the
+/// iterations actually run and their number, or None if the run was
+/// cancelled.
+pub fn run_monte_carlo_simulation_until(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: Vec<String>,
+    batch_size: usize,
+    cancel: &AtomicBool,
+    mut stop: impl FnMut(&SimulationProgress) -> bool,
+) -> Option<(SimulationResult, usize)> {
+    let (counts, done) = run_batches(season, params, batch_size, cancel, |progress| {
+        if stop(progress) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    Some((probabilities_from_counts(&counts, done, &team_names), done))
+}
+
+/// Largest standard error `sqrt(p (1 - p) / n)` of the cells of a
+/// probability matrix estimated from `iterations` seasons: the precision
+/// the run achieved.
+pub fn max_standard_error(probability_matrix: &[Vec<f64>], iterations: usize) -> f64 {
+    probability_matrix
+        .iter()
+        .flatten()
+        .map(|&p| (p * (1.0 - p) / iterations.max(1) as f64).sqrt())
+        .fold(0.0, f64::max)
+}
+
+/// Run the iterations in batches, calling `on_batch` after each batch and
+/// returning the counts and the number of iterations run once it breaks or
+/// all are done. Gives up (None) as soon as `cancel` is seen set before a
+/// batch.
+fn run_batches(
+    season: &Season,
+    params: &SimulationParams,
+    batch_size: usize,
+    cancel: &AtomicBool,
+    mut on_batch: impl FnMut(&SimulationProgress) -> ControlFlow<()>,
+) -> Option<(Vec<Vec<usize>>, usize)> {
+    if let Some(counts) = settled_counts(season, params, params.iterations) {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let _ = on_batch(&SimulationProgress {
+            iterations_done: params.iterations,
+            iterations_total: params.iterations,
+            counts: &counts,
+        });
+        return Some((counts, params.iterations));
+    }
+    let _span = run_span(season, params).entered();
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+
+    let n_teams = season.number_teams;
+    let prepared = info_span!("prepare").in_scope(|| PreparedSeason::new(season, params));
+    let _simulate = info_span!("simulate").entered();
+    let mut counts = vec![vec![0usize; n_teams]; n_teams];
+    let mut done = 0;
+
+    for batch in seeds.chunks(batch_size.max(1)) {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let batch_counts = count_positions(season, params, &prepared, batch);
+        add_counts(&mut counts, batch_counts);
+        done += batch.len();
+        let progress = SimulationProgress {
+            iterations_done: done,
+            iterations_total: params.iterations,
+            counts: &counts,
+        };
+        if on_batch(&progress).is_break() {
+            break;
+        }
+    }
+
+    Some((counts, done))
+}
+
+/// Turn position counts (`counts[team_id][position]` over `iterations`
+/// seasons) into probabilities and order teams by average position (best
+/// teams first).
+pub fn probabilities_from_counts(
+    position_counts: &[Vec<usize>],
+    iterations: usize,
+    team_names: &[String],
+) -> SimulationResult {
+    let n_teams = position_counts.len();
+
+    // Convert counts to probabilities
+    let mut probability_matrix = vec![vec![0.0; n_teams]; n_teams];
+
+    for (team_id, counts) in position_counts.iter().enumerate() {
+        for (position, &count) in counts.iter().enumerate() {
+            probability_matrix[team_id][position] = count as f64 / iterations as f64;
+        }
+    }
+
+    ranked_result(&probability_matrix, team_names)
+}
+
+/// Order a probability matrix in team_id order by average position (best
+/// teams first) and name its rows.
+fn ranked_result(probability_matrix: &[Vec<f64>], team_names: &[String]) -> SimulationResult {
+    let n_teams = probability_matrix.len();
+
+    // Reorder probability matrix by ranking
+    let mut sorted_matrix = vec![vec![0.0; n_teams]; n_teams];
+    let mut sorted_names = vec![String::new(); n_teams];
+
+    for (new_idx, team_id) in rank_by_average_position(probability_matrix)
+        .into_iter()
+        .enumerate()
+    {
+        sorted_matrix[new_idx] = probability_matrix[team_id].clone();
+        sorted_names[new_idx] = if team_id < team_names.len() {
+            team_names[team_id].clone()
+        } else {
+            format!("Team {}", team_id + 1)
+        };
+    }
+
+    SimulationResult {
+        probability_matrix: sorted_matrix,
+        team_names: sorted_names,
+    }
+}
+
+/// Team ids sorted by average position (best teams first), given a
+/// probability matrix in team_id order.
+fn rank_by_average_position(probability_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let mut team_rankings: Vec<(usize, f64)> = probability_matrix
+        .iter()
+        .enumerate()
+        .map(|(team_id, row)| {
+            let avg_position: f64 = row
+                .iter()
+                .enumerate()
+                .map(|(pos, &prob)| (pos + 1) as f64 * prob)
+                .sum();
+            (team_id, avg_position)
+        })
+        .collect();
+
+    team_rankings.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    team_rankings
+        .into_iter()
+        .map(|(team_id, _)| team_id)
+        .collect()
+}
+
+fn add_counts(total: &mut [Vec<usize>], other: Vec<Vec<usize>>) {
+    for (row_a, row_b) in total.iter_mut().zip(other) {
+        for (cell_a, cell_b) in row_a.iter_mut().zip(row_b) {
+            *cell_a += cell_b;
+        }
+    }
+}
+
+/// Count final positions over one iteration per seed with the generator
+/// selected in `params`.
+fn count_positions(
+    season: &Season,
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    seeds: &[u64],
+) -> Vec<Vec<usize>> {
+    match params.rng {
+        RngKind::Std => count_positions_with::<StdRng>(season, params, prepared, seeds),
+        RngKind::SmallRng => count_positions_with::<SmallRng>(season, params, prepared, seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            count_positions_with::<Xoshiro256PlusPlus>(season, params, prepared, seeds)
+        }
+    }
+}
+
+/// Simulate one season per seed with generator `R` and count how often each
+/// team finishes in each position: `counts[team_id][position]`.
+fn count_positions_with<R: Rng + RngExt + SeedableRng>(
+    season: &Season,
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    seeds: &[u64],
+) -> Vec<Vec<usize>> {
+    let n_teams = season.number_teams;
+
+    let lanes = LaneBuffers::applies(params, prepared);
+
+    // Per-thread fold state: reusable simulation buffers + local counts.
+    // No locks; rayon reduces the per-thread counts at the end (addition is
+    // commutative, so scheduling order cannot affect the result).
+    seeds
+        .par_chunks(LANES)
+        .fold(
+            || {
+                (
+                    LaneBuffers::new(prepared),
+                    vec![vec![0usize; n_teams]; n_teams],
+                )
+            },
+            |(mut buffers, mut counts), chunk| {
+                match <&[u64; LANES]>::try_from(chunk) {
+                    Ok(chunk) if lanes => {
+                        buffers.simulate::<R>(params, prepared, chunk);
+                        for lane in &buffers.lanes {
+                            for standing in &lane.standings {
+                                counts[standing.team_id][standing.position - 1] += 1;
+                            }
+                        }
+                    }
+                    _ => {
+                        let lane = &mut buffers.lanes[0];
+                        for &seed in chunk {
+                            lane.simulate::<R>(params, prepared, seed);
+                            for standing in &lane.standings {
+                                counts[standing.team_id][standing.position - 1] += 1;
+                            }
+                        }
+                    }
+                }
+                (buffers, counts)
+            },
+        )
+        .map(|(_, counts)| counts)
+        .reduce(
+            || vec![vec![0usize; n_teams]; n_teams],
+            |mut a, b| {
+                add_counts(&mut a, b);
+                a
+            },
+        )
+}
+
+/// [`IterBuffers`] of [`LANES`] iterations simulated side by side.
+///
+/// The lanes walk the tail in step, so the goal means and Poisson
+/// quantiles of one match are computed for all of them at once (see
+/// [`poisson_quantile_lanes`]). Each lane draws from its own generator in
+/// the order [`IterBuffers::simulate`] does, so a lane's table is exactly
+/// the one its seed gives on its own.
+struct LaneBuffers {
+    lanes: [IterBuffers; LANES],
+}
+
+impl LaneBuffers {
+    fn new(prepared: &PreparedSeason) -> Self {
+        Self {
+            lanes: std::array::from_fn(|_| IterBuffers::new(prepared)),
+        }
+    }
+
+    /// Whether the lanes can run `params`: Elo ratings, the linear goal
+    /// model and no matches in progress. Everything else is simulated one
+    /// iteration at a time.
+    fn applies(params: &SimulationParams, prepared: &PreparedSeason) -> bool {
+        prepared.glicko.is_none()
+            && prepared.live.is_empty()
+            && params.poisson_strengths.is_none()
+            && params.team_strengths.is_none()
+            && params.strength_priors.is_none()
+    }
+
+    /// Simulate one season per seed, leaving each lane's sorted final
+    /// table in its `standings`.
+    fn simulate<R: Rng + RngExt + SeedableRng>(
+        &mut self,
+        params: &SimulationParams,
+        prepared: &PreparedSeason,
+        seeds: &[u64; LANES],
+    ) {
+        let mut rngs: [R; LANES] = std::array::from_fn(|lane| R::seed_from_u64(seeds[lane]));
+        for lane in &mut self.lanes {
+            lane.matches.clear();
+            lane.matches.extend_from_slice(&prepared.tail);
+            lane.elos.clear();
+            lane.elos.extend_from_slice(&prepared.elos);
+        }
+
+        let system = Elo {
+            mod_factor: params.mod_factor,
+        };
+        let weights = prepared.tail_weights.as_deref();
+        for (index, m) in prepared.tail.iter().enumerate() {
+            if m.goals_home.is_none() || m.goals_away.is_none() {
+                let (home, away) = (m.team_home, m.team_away);
+                let delta = std::array::from_fn(|lane| {
+                    let elos = &self.lanes[lane].elos;
+                    elos[home] + params.home_advantage - elos[away]
+                });
+                let draws: [(f64, f64); LANES] =
+                    std::array::from_fn(|lane| (rngs[lane].random(), rngs[lane].random()));
+                let (lambda_home, lambda_away) =
+                    expected_goals_lanes(delta, params.tore_slope, params.tore_intercept);
+                let goals_home = poisson_quantile_lanes(draws.map(|d| d.0), lambda_home);
+                let goals_away = poisson_quantile_lanes(draws.map(|d| d.1), lambda_away);
+                for (lane, buffers) in self.lanes.iter_mut().enumerate() {
+                    buffers.matches[index].goals_home = Some(goals_home[lane] as i32);
+                    buffers.matches[index].goals_away = Some(goals_away[lane] as i32);
+                }
+            }
+            for buffers in &mut self.lanes {
+                let m = &buffers.matches[index];
+                update_pair(
+                    &system,
+                    &mut buffers.elos,
+                    m,
+                    m.goals_home.unwrap_or_default(),
+                    m.goals_away.unwrap_or_default(),
+                    params.home_advantage,
+                    match_weight(weights, index),
+                );
+            }
+        }
+
+        let tiebreakers = Tiebreakers::new(params);
+        for (buffers, rng) in self.lanes.iter_mut().zip(&mut rngs) {
+            prepared.finish_table(&buffers.matches, &mut buffers.standings);
+            tiebreakers.sort_with(
+                &mut buffers.standings,
+                &[&prepared.prefix, &buffers.matches],
+                || rng.next_u64(),
+                &mut buffers.tiebreak,
+            );
+        }
+    }
+}
+
+/// Simulation buffers reused across the iterations of one rayon worker.
+pub(crate) struct IterBuffers {
+    pub(crate) matches: Vec<crate::models::Match>,
+    pub(crate) elos: Vec<f64>,
+    pub(crate) standings: Vec<crate::models::TeamStanding>,
+    glicko: Vec<GlickoRating>,
+    tiebreak: TiebreakScratch,
+}
+
+impl IterBuffers {
+    pub(crate) fn new(prepared: &PreparedSeason) -> Self {
+        Self {
+            matches: Vec::with_capacity(prepared.tail.len()),
+            elos: Vec::with_capacity(prepared.elos.len()),
+            standings: Vec::with_capacity(prepared.baseline.len()),
+            glicko: Vec::with_capacity(prepared.glicko.as_ref().map_or(0, Vec::len)),
+            tiebreak: TiebreakScratch::default(),
+        }
+    }
+
+    /// Simulate one season from `seed`, leaving the sorted final table in
+    /// `standings`.
+    pub(crate) fn simulate<R: Rng + RngExt + SeedableRng>(
+        &mut self,
+        params: &SimulationParams,
+        prepared: &PreparedSeason,
+        seed: u64,
+    ) {
+        self.simulate_recording::<R>(params, prepared, seed, |_, _| {});
+    }
+
+    /// [`Self::simulate`] that calls `after_match` with the index of each
+    /// tail match and the ratings after it.
+    fn simulate_recording<R: Rng + RngExt + SeedableRng>(
+        &mut self,
+        params: &SimulationParams,
+        prepared: &PreparedSeason,
+        seed: u64,
+        after_match: impl FnMut(usize, &[f64]),
+    ) {
+        let mut rng = R::seed_from_u64(seed);
+
+        // Only the tail from the first unplayed match onwards is
+        // simulated; the played prefix is folded into `prepared`.
+        self.matches.clear();
+        self.matches.extend_from_slice(&prepared.tail);
+        self.elos.clear();
+        self.elos.extend_from_slice(&prepared.elos);
+        if let Some(goal_model) = &params.poisson_strengths {
+            self.play(params, prepared, goal_model, &mut rng, after_match);
+        } else if let Some(goal_model) = params.strength_goal_model() {
+            self.play(params, prepared, &goal_model, &mut rng, after_match);
+        } else if let Some(goal_model) = params.prior_goal_model() {
+            self.play(params, prepared, &goal_model, &mut rng, after_match);
+        } else {
+            self.play(
+                params,
+                prepared,
+                &params.goal_model(),
+                &mut rng,
+                after_match,
+            );
+        }
+
+        prepared.finish_table(&self.matches, &mut self.standings);
+        Tiebreakers::new(params).sort_with(
+            &mut self.standings,
+            &[&prepared.prefix, &self.matches],
+            || rng.next_u64(),
+            &mut self.tiebreak,
+        );
+    }
+
+    /// Complete the live matches and simulate the rest of the tail in the
+    /// buffers: `goal_model` draws the goals, the rating system of
+    /// `prepared` moves the ratings.
+    fn play<G: GoalModel, R: Rng + RngExt>(
+        &mut self,
+        params: &SimulationParams,
+        prepared: &PreparedSeason,
+        goal_model: &G,
+        rng: &mut R,
+        mut after_match: impl FnMut(usize, &[f64]),
+    ) {
+        for live in &prepared.live {
+            let m = &mut self.matches[live.index];
+            let (home, away) = (m.team_home, m.team_away);
+            let strength_delta = self.elos[home] + params.home_advantage - self.elos[away];
+            let (goals_home, goals_away) =
+                goal_model.finish_live(live, home, away, strength_delta, rng);
+            m.goals_home = Some(goals_home);
+            m.goals_away = Some(goals_away);
+        }
+
+        let weights = prepared.tail_weights.as_deref();
+        match &prepared.glicko {
+            None => simulate_season_rated(
+                &Elo {
+                    mod_factor: params.mod_factor,
+                },
+                goal_model,
+                &mut self.matches,
+                &mut self.elos,
+                weights,
+                params.home_advantage,
+                rng,
+                after_match,
+            ),
+            Some(start) => {
+                // Callers read strengths from `elos`, so mirror them there.
+                self.glicko.clear();
+                self.glicko.extend_from_slice(start);
+                let system = params.glicko.system();
+                let elos = &mut self.elos;
+                simulate_season_rated(
+                    &system,
+                    goal_model,
+                    &mut self.matches,
+                    &mut self.glicko,
+                    weights,
+                    params.home_advantage,
+                    rng,
+                    |index, ratings| {
+                        for (elo, rating) in elos.iter_mut().zip(ratings) {
+                            *elo = system.strength(rating);
+                        }
+                        after_match(index, elos);
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,489 @@
+use super::IterBuffers;
+use crate::elo::regress_to_mean;
+use crate::models::{GlickoSettings, RngKind, Season, SimulationParams};
+use crate::simulation::{
+    double_round_robin, entropy_seed, simulate_two_legged_tie_with, stream_seeds, PreparedSeason,
+    TwoLegRules,
+};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One division of a [`MultiSeason`] run.
+#[derive(Debug, Clone)]
+pub struct Division {
+    pub name: String,
+    /// The current season, team indices local to the division
+    pub season: Season,
+    /// Parameters of the current season. Later seasons use the same model
+    /// parameters without the adjustments.
+    pub params: SimulationParams,
+    pub team_names: Vec<String>,
+    /// Top teams moving up to the division above after each season
+    pub promoted: usize,
+    /// Bottom teams moving down to the division below
+    pub relegated: usize,
+    /// Points added per team when ranking for promotion only, e.g. -50 for
+    /// second teams that cannot go up (the TeamList `Promotion` column).
+    /// Promotion spots pass down to the next team in that ranking.
+    pub promotion_adjustments: Option<Vec<i32>>,
+    /// Whether the team just above the relegation spots plays the best
+    /// team below the promotion spots of the division below for its place,
+    /// in a two-legged tie hosted first by the upper team (see
+    /// [`simulate_two_legged_tie_with`])
+    pub relegation_playoff: bool,
+    /// How a level relegation playoff is settled
+    pub playoff_rules: TwoLegRules,
+    /// ELO points added to the division's ratings to put them on the scale
+    /// shared by all divisions, e.g. -300 for a third tier rated around
+    /// the same mean as the first. Counts in relegation playoffs and moves
+    /// a team's rating when it changes division; 0 where the ratings
+    /// already share one scale.
+    pub elo_offset: f64,
+}
+
+/// Consecutive seasons of a pyramid of divisions, top division first.
+/// Between seasons the promoted and relegated teams swap divisions and
+/// every division's ratings are regressed to its mean by `regression`
+/// (see [`regress_to_mean`]); later seasons are double round robins.
+#[derive(Debug, Clone)]
+pub struct MultiSeason {
+    pub divisions: Vec<Division>,
+    /// Seasons simulated, the current one included
+    pub seasons: usize,
+    pub regression: f64,
+}
+
+impl MultiSeason {
+    /// The first inconsistency of the setup, if any: every relegation spot
+    /// must match a promotion spot of the division below, so division
+    /// sizes stay constant.
+    pub fn check(&self) -> Result<(), String> {
+        if self.divisions.is_empty() {
+            return Err("at least one division is required".to_string());
+        }
+        if self.seasons == 0 {
+            return Err("at least one season is required".to_string());
+        }
+        let (first, last) = (
+            &self.divisions[0],
+            &self.divisions[self.divisions.len() - 1],
+        );
+        if first.promoted != 0 {
+            return Err(format!("top division '{}' cannot promote", first.name));
+        }
+        if last.relegated != 0 || last.relegation_playoff {
+            return Err(format!("bottom division '{}' cannot relegate", last.name));
+        }
+        for pair in self.divisions.windows(2) {
+            if pair[0].relegated != pair[1].promoted {
+                return Err(format!(
+                    "'{}' relegates {} teams but '{}' promotes {}",
+                    pair[0].name, pair[0].relegated, pair[1].name, pair[1].promoted
+                ));
+            }
+        }
+        for (d, division) in self.divisions.iter().enumerate() {
+            let playoffs = usize::from(division.relegation_playoff)
+                + usize::from(d > 0 && self.divisions[d - 1].relegation_playoff);
+            if division.promoted + division.relegated + playoffs > division.season.number_teams {
+                return Err(format!("'{}' moves more teams than it has", division.name));
+            }
+            if let Some(adjustments) = &division.promotion_adjustments {
+                if adjustments.len() != division.season.number_teams {
+                    return Err(format!(
+                        "'{}' has {} promotion adjustments for {} teams",
+                        division.name,
+                        adjustments.len(),
+                        division.season.number_teams
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a multi-season run.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiSeasonResult {
+    /// Division names, top first; the indices of `division_probabilities`
+    pub divisions: Vec<String>,
+    /// Every team of every division, in division order
+    pub teams: Vec<TeamMultiSeason>,
+}
+
+/// Where one team plays over the simulated seasons.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamMultiSeason {
+    pub team_name: String,
+    /// Division of the current season (0 = top)
+    pub division: usize,
+    /// `[season][division]`: probability of playing in the division in the
+    /// season after each simulated one
+    pub division_probabilities: Vec<Vec<f64>>,
+    /// Per simulated season: probability of having been promoted by its end
+    pub promoted_within: Vec<f64>,
+    /// Per simulated season: probability of having been relegated by its end
+    pub relegated_within: Vec<f64>,
+    /// Per simulated season: probability of going down from the relegation
+    /// places at its end
+    pub relegated_directly: Vec<f64>,
+    /// Per simulated season: probability of going down after losing the
+    /// relegation playoff at its end
+    pub relegated_after_playoff: Vec<f64>,
+    /// Per simulated season: probability of going up after winning the
+    /// relegation playoff at its end
+    pub promoted_after_playoff: Vec<f64>,
+}
+
+/// Simulate `iterations` runs of `setup.seasons` consecutive seasons.
+/// Seeded from OS entropy like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+/// `setup` must pass [`MultiSeason::check`].
+pub fn run_multi_season(setup: &MultiSeason, iterations: usize) -> MultiSeasonResult {
+    let seeds = stream_seeds(entropy_seed(), 0, iterations);
+    multi_season_with_seeds(setup, &seeds)
+}
+
+/// Deterministic variant of [`run_multi_season`].
+pub fn run_multi_season_seeded(
+    setup: &MultiSeason,
+    iterations: usize,
+    master_seed: u64,
+) -> MultiSeasonResult {
+    let seeds = stream_seeds(master_seed, 0, iterations);
+    multi_season_with_seeds(setup, &seeds)
+}
+
+/// Per-run tallies: division counts `[season][team][division]`, teams
+/// promoted / relegated so far `[season][team]`, and how teams moved at the
+/// end of each season `[season][team]`.
+struct Tally {
+    divisions: Vec<usize>,
+    promoted: Vec<usize>,
+    relegated: Vec<usize>,
+    relegated_directly: Vec<usize>,
+    relegated_after_playoff: Vec<usize>,
+    promoted_after_playoff: Vec<usize>,
+}
+
+impl Tally {
+    fn new(seasons: usize, teams: usize, divisions: usize) -> Self {
+        Self {
+            divisions: vec![0; seasons * teams * divisions],
+            promoted: vec![0; seasons * teams],
+            relegated: vec![0; seasons * teams],
+            relegated_directly: vec![0; seasons * teams],
+            relegated_after_playoff: vec![0; seasons * teams],
+            promoted_after_playoff: vec![0; seasons * teams],
+        }
+    }
+
+    fn add(mut self, other: Self) -> Self {
+        for (a, b) in [
+            (&mut self.divisions, other.divisions),
+            (&mut self.promoted, other.promoted),
+            (&mut self.relegated, other.relegated),
+            (&mut self.relegated_directly, other.relegated_directly),
+            (
+                &mut self.relegated_after_playoff,
+                other.relegated_after_playoff,
+            ),
+            (
+                &mut self.promoted_after_playoff,
+                other.promoted_after_playoff,
+            ),
+        ] {
+            for (x, y) in a.iter_mut().zip(b) {
+                *x += y;
+            }
+        }
+        self
+    }
+}
+
+/// What every run needs from `setup`, computed once.
+struct Prepared {
+    /// Current season of each division
+    current: Vec<PreparedSeason>,
+    /// Parameters of the later seasons of each division
+    later: Vec<SimulationParams>,
+    /// Double round robin per division size
+    schedules: Vec<Vec<crate::models::Match>>,
+    /// Global id of each division's first team
+    offsets: Vec<usize>,
+    /// Promotion ranking adjustment per global id
+    promotion_adjustments: Vec<i32>,
+    teams: usize,
+}
+
+fn multi_season_with_seeds(setup: &MultiSeason, seeds: &[u64]) -> MultiSeasonResult {
+    let divisions = &setup.divisions;
+    let mut offsets = Vec::with_capacity(divisions.len());
+    let mut teams = 0;
+    for division in divisions {
+        offsets.push(teams);
+        teams += division.season.number_teams;
+    }
+    let prepared = Prepared {
+        current: divisions
+            .iter()
+            .map(|d| PreparedSeason::new(&d.season, &d.params))
+            .collect(),
+        later: divisions
+            .iter()
+            .map(|d| SimulationParams {
+                mod_factor_weights: None,
+                adj_points: None,
+                adj_goals: None,
+                adj_goals_against: None,
+                adj_goal_diff: None,
+                adj_played: None,
+                glicko: GlickoSettings {
+                    deviations: None,
+                    ..d.params.glicko.clone()
+                },
+                team_strengths: None,
+                poisson_strengths: None,
+                strength_priors: None,
+                ..d.params.clone()
+            })
+            .collect(),
+        schedules: divisions
+            .iter()
+            .map(|d| double_round_robin(d.season.number_teams))
+            .collect(),
+        offsets,
+        promotion_adjustments: divisions
+            .iter()
+            .flat_map(|d| match &d.promotion_adjustments {
+                Some(adjustments) => adjustments.clone(),
+                None => vec![0; d.season.number_teams],
+            })
+            .collect(),
+        teams,
+    };
+
+    let n_divisions = divisions.len();
+    let tally = seeds
+        .par_iter()
+        .fold(
+            || {
+                (
+                    Tally::new(setup.seasons, teams, n_divisions),
+                    IterBuffers::new(&prepared.current[0]),
+                )
+            },
+            |(mut tally, mut buffers), &seed| {
+                simulate_run(setup, &prepared, seed, &mut tally, &mut buffers);
+                (tally, buffers)
+            },
+        )
+        .map(|(tally, _)| tally)
+        .reduce(|| Tally::new(setup.seasons, teams, n_divisions), Tally::add);
+
+    let runs = seeds.len().max(1) as f64;
+    let mut results = Vec::with_capacity(teams);
+    for (d, division) in divisions.iter().enumerate() {
+        for local in 0..division.season.number_teams {
+            let team = prepared.offsets[d] + local;
+            let per_season = |counts: &[usize]| -> Vec<f64> {
+                (0..setup.seasons)
+                    .map(|s| counts[s * teams + team] as f64 / runs)
+                    .collect()
+            };
+            results.push(TeamMultiSeason {
+                team_name: division
+                    .team_names
+                    .get(local)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Team {}", team + 1)),
+                division: d,
+                division_probabilities: (0..setup.seasons)
+                    .map(|s| {
+                        let at = (s * teams + team) * n_divisions;
+                        tally.divisions[at..at + n_divisions]
+                            .iter()
+                            .map(|&c| c as f64 / runs)
+                            .collect()
+                    })
+                    .collect(),
+                promoted_within: per_season(&tally.promoted),
+                relegated_within: per_season(&tally.relegated),
+                relegated_directly: per_season(&tally.relegated_directly),
+                relegated_after_playoff: per_season(&tally.relegated_after_playoff),
+                promoted_after_playoff: per_season(&tally.promoted_after_playoff),
+            });
+        }
+    }
+
+    MultiSeasonResult {
+        divisions: divisions.iter().map(|d| d.name.clone()).collect(),
+        teams: results,
+    }
+}
+
+/// One run of every season, added to `tally`. Every division's seasons are
+/// simulated in `buffers`.
+fn simulate_run(
+    setup: &MultiSeason,
+    prepared: &Prepared,
+    seed: u64,
+    tally: &mut Tally,
+    buffers: &mut IterBuffers,
+) {
+    let divisions = &setup.divisions;
+    let n_divisions = divisions.len();
+    let teams = prepared.teams;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Global ids per division and the global ratings.
+    let mut members: Vec<Vec<usize>> = divisions
+        .iter()
+        .zip(&prepared.offsets)
+        .map(|(d, &offset)| (offset..offset + d.season.number_teams).collect())
+        .collect();
+    let mut elos = vec![0.0; teams];
+    let mut division_of = vec![0; teams];
+    for (d, ids) in members.iter().enumerate() {
+        for &team in ids {
+            division_of[team] = d;
+        }
+    }
+    let mut promoted = vec![false; teams];
+    let mut relegated = vec![false; teams];
+
+    for s in 0..setup.seasons {
+        // Finishing order of each division and its promotion ranking,
+        // global ids.
+        let mut orders = Vec::with_capacity(n_divisions);
+        let mut candidates = Vec::with_capacity(n_divisions);
+        for (d, ids) in members.iter().enumerate() {
+            let division_seed = rng.random();
+            let later;
+            let (season_prep, params) = if s == 0 {
+                (&prepared.current[d], &divisions[d].params)
+            } else {
+                let season = Season {
+                    matches: prepared.schedules[d].clone(),
+                    team_elos: ids.iter().map(|&t| elos[t]).collect(),
+                    number_teams: ids.len(),
+                };
+                later = PreparedSeason::new(&season, &prepared.later[d]);
+                (&later, &prepared.later[d])
+            };
+            match params.rng {
+                RngKind::Std => buffers.simulate::<StdRng>(params, season_prep, division_seed),
+                RngKind::SmallRng => {
+                    buffers.simulate::<SmallRng>(params, season_prep, division_seed)
+                }
+                RngKind::Xoshiro256PlusPlus => {
+                    buffers.simulate::<Xoshiro256PlusPlus>(params, season_prep, division_seed)
+                }
+            }
+            for (local, &team) in ids.iter().enumerate() {
+                elos[team] = buffers.elos[local];
+            }
+            let order: Vec<usize> = buffers.standings.iter().map(|r| ids[r.team_id]).collect();
+
+            // Teams outside the relegation and playoff places, re-ranked
+            // with the promotion adjustments; the stable sort keeps the
+            // table order among equal totals.
+            let eligible =
+                order.len() - divisions[d].relegated - usize::from(divisions[d].relegation_playoff);
+            let mut ranking: Vec<(usize, i32)> = buffers.standings[..eligible]
+                .iter()
+                .map(|r| {
+                    let team = ids[r.team_id];
+                    (team, r.points + prepared.promotion_adjustments[team])
+                })
+                .collect();
+            ranking.sort_by_key(|&(_, points)| std::cmp::Reverse(points));
+            candidates.push(
+                ranking
+                    .into_iter()
+                    .map(|(team, _)| team)
+                    .collect::<Vec<_>>(),
+            );
+            orders.push(order);
+        }
+
+        // Teams leaving each division, upwards and downwards.
+        let mut up: Vec<Vec<usize>> = (0..n_divisions)
+            .map(|d| candidates[d][..divisions[d].promoted].to_vec())
+            .collect();
+        let mut down: Vec<Vec<usize>> = (0..n_divisions)
+            .map(|d| orders[d][orders[d].len() - divisions[d].relegated..].to_vec())
+            .collect();
+        for team in down.iter().flatten() {
+            tally.relegated_directly[s * teams + team] += 1;
+        }
+        for d in 0..n_divisions {
+            if !divisions[d].relegation_playoff {
+                continue;
+            }
+            let upper = orders[d][orders[d].len() - divisions[d].relegated - 1];
+            let lower = candidates[d + 1][divisions[d + 1].promoted];
+            let (upper_offset, lower_offset) =
+                (divisions[d].elo_offset, divisions[d + 1].elo_offset);
+            let tie = simulate_two_legged_tie_with(
+                elos[upper] + upper_offset,
+                elos[lower] + lower_offset,
+                &prepared.later[d],
+                &divisions[d].playoff_rules,
+                &mut rng,
+            );
+            elos[upper] = tie.elo_first_host - upper_offset;
+            elos[lower] = tie.elo_second_host - lower_offset;
+            if !tie.first_host_wins {
+                down[d].push(upper);
+                up[d + 1].push(lower);
+                tally.relegated_after_playoff[s * teams + upper] += 1;
+                tally.promoted_after_playoff[s * teams + lower] += 1;
+            }
+        }
+
+        // Swap the promoted and relegated teams between neighbours.
+        for (d, ids) in members.iter_mut().enumerate() {
+            ids.clear();
+            if d > 0 {
+                ids.extend_from_slice(&down[d - 1]);
+            }
+            ids.extend(
+                orders[d]
+                    .iter()
+                    .filter(|team| !up[d].contains(team) && !down[d].contains(team)),
+            );
+            if d + 1 < n_divisions {
+                ids.extend_from_slice(&up[d + 1]);
+            }
+        }
+
+        // A team changing division keeps its strength on the shared scale.
+        for (d, ids) in members.iter().enumerate() {
+            for &team in ids {
+                elos[team] += divisions[division_of[team]].elo_offset - divisions[d].elo_offset;
+            }
+        }
+
+        for (d, ids) in members.iter().enumerate() {
+            let regressed = regress_to_mean(
+                &ids.iter().map(|&t| elos[t]).collect::<Vec<_>>(),
+                setup.regression,
+            );
+            for (&team, elo) in ids.iter().zip(regressed) {
+                elos[team] = elo;
+                promoted[team] |= d < division_of[team];
+                relegated[team] |= d > division_of[team];
+                division_of[team] = d;
+                tally.divisions[(s * teams + team) * n_divisions + d] += 1;
+                tally.promoted[s * teams + team] += usize::from(promoted[team]);
+                tally.relegated[s * teams + team] += usize::from(relegated[team]);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::{count_positions, master_seed, run_span};
+use crate::models::{Season, SimulationParams};
+use crate::simulation::{calculate_table_with, stream_seeds, PreparedSeason};
+use serde::{Deserialize, Serialize};
+
+/// Forecasts of a completed season as they stood after each matchday.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonReplay {
+    pub team_names: Vec<String>,
+    /// Actual final position per team_id, 1-based
+    pub final_positions: Vec<usize>,
+    /// One snapshot per matchday before the last, starting before the
+    /// first
+    pub snapshots: Vec<ReplaySnapshot>,
+}
+
+/// The simulation as of one matchday of a replayed season.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplaySnapshot {
+    /// Last matchday whose results were known; 0 before the season
+    pub matchday: u32,
+    /// Matches played up to and including `matchday`
+    pub matches_played: usize,
+    /// Rows are teams in team_id order, columns are positions
+    pub probability_matrix: Vec<Vec<f64>>,
+    /// Probability each team_id was given of its actual final position
+    pub actual_position_probability: Vec<f64>,
+}
+
+/// Replay a completed season: for every matchday, hide the results after
+/// it, simulate the rest of the season from the ratings and table at that
+/// point, and record the probabilities next to the actual final table.
+///
+/// Matches are replayed by matchday. If any match has no matchday, the
+/// schedule is taken to be in round order and split into rounds of
+/// `number_teams / 2` matches. `mod_factor_weights` follow their rows. None
+/// if any match is unplayed. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn replay_season(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+) -> Option<SeasonReplay> {
+    replay_with_seed(season, params, team_names, master_seed(params))
+}
+
+/// Deterministic variant of [`replay_season`].
+pub fn replay_season_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    master_seed: u64,
+) -> Option<SeasonReplay> {
+    replay_with_seed(season, params, team_names, master_seed)
+}
+
+fn replay_with_seed(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    master: u64,
+) -> Option<SeasonReplay> {
+    if season
+        .matches
+        .iter()
+        .any(|m| m.goals_home.is_none() || m.goals_away.is_none())
+    {
+        return None;
+    }
+
+    let round = (season.number_teams / 2).max(1);
+    let inferred = season.matches.iter().any(|m| m.matchday.is_none());
+    let matchday = |i: usize| match season.matches[i].matchday {
+        Some(day) if !inferred => day,
+        _ => (i / round) as u32 + 1,
+    };
+    let mut order: Vec<usize> = (0..season.matches.len()).collect();
+    order.sort_by_key(|&i| matchday(i));
+
+    let table = calculate_table_with(&season.matches, season.number_teams, params);
+    let mut final_positions = vec![0; season.number_teams];
+    for (rank, row) in table.standings.iter().enumerate() {
+        final_positions[row.team_id] = rank + 1;
+    }
+
+    let last = order.last().map_or(0, |&i| matchday(i));
+    let mut snapshots = Vec::with_capacity(last as usize);
+    for day in 0..last {
+        let matches_played = order.iter().filter(|&&i| matchday(i) <= day).count();
+        let snapshot = Season {
+            matches: order
+                .iter()
+                .enumerate()
+                .map(|(position, &i)| {
+                    let mut m = season.matches[i].clone();
+                    m.matchday = Some(matchday(i));
+                    if position >= matches_played {
+                        m.goals_home = None;
+                        m.goals_away = None;
+                    }
+                    m
+                })
+                .collect(),
+            ..season.clone()
+        };
+        let params = SimulationParams {
+            mod_factor_weights: params.mod_factor_weights.as_ref().map(|weights| {
+                order
+                    .iter()
+                    .map(|&i| weights.get(i).copied().unwrap_or(1.0))
+                    .collect()
+            }),
+            ..params.clone()
+        };
+        // Each snapshot takes its own range of the iteration streams.
+        let seeds = stream_seeds(
+            master,
+            u64::from(day) * params.iterations as u64,
+            params.iterations,
+        );
+        let _span = run_span(&snapshot, &params).entered();
+        let prepared = PreparedSeason::new(&snapshot, &params);
+        let probability_matrix: Vec<Vec<f64>> =
+            count_positions(&snapshot, &params, &prepared, &seeds)
+                .into_iter()
+                .map(|counts| {
+                    counts
+                        .into_iter()
+                        .map(|count| count as f64 / seeds.len().max(1) as f64)
+                        .collect()
+                })
+                .collect();
+        let actual_position_probability = probability_matrix
+            .iter()
+            .zip(&final_positions)
+            .map(|(row, &position)| row[position - 1])
+            .collect();
+        snapshots.push(ReplaySnapshot {
+            matchday: day,
+            matches_played,
+            probability_matrix,
+            actual_position_probability,
+        });
+    }
+
+    Some(SeasonReplay {
+        team_names: team_names.to_vec(),
+        final_positions,
+        snapshots,
+    })
+}
@@ -0,0 +1,80 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Final table of one simulated season, in team_id order.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeasonSample {
+    /// 1-based final position of each team
+    pub positions: Vec<u32>,
+    /// Final points of each team, adjustments included
+    pub points: Vec<i32>,
+}
+
+/// Simulate `params.iterations` seasons and keep every final table, for
+/// analyses the aggregated probability matrix cannot express (joint
+/// outcomes, points distributions). Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_season_samples(season: &Season, params: &SimulationParams) -> Vec<SeasonSample> {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    samples_with_seeds(season, params, &seeds)
+}
+
+/// Deterministic variant of [`run_season_samples`].
+pub fn run_season_samples_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    master_seed: u64,
+) -> Vec<SeasonSample> {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    samples_with_seeds(season, params, &seeds)
+}
+
+fn samples_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    seeds: &[u64],
+) -> Vec<SeasonSample> {
+    let _span = super::run_span(season, params).entered();
+    let prepared = PreparedSeason::new(season, params);
+    let n_teams = season.number_teams;
+    match params.rng {
+        RngKind::Std => sample_with::<StdRng>(params, &prepared, n_teams, seeds),
+        RngKind::SmallRng => sample_with::<SmallRng>(params, &prepared, n_teams, seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            sample_with::<Xoshiro256PlusPlus>(params, &prepared, n_teams, seeds)
+        }
+    }
+}
+
+/// One sample per seed, in seed order.
+fn sample_with<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    n_teams: usize,
+    seeds: &[u64],
+) -> Vec<SeasonSample> {
+    seeds
+        .par_iter()
+        .map_init(
+            || IterBuffers::new(prepared),
+            |buffers, &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                let mut sample = SeasonSample {
+                    positions: vec![0; n_teams],
+                    points: vec![0; n_teams],
+                };
+                for standing in &buffers.standings {
+                    sample.positions[standing.team_id] = standing.position as u32;
+                    sample.points[standing.team_id] = standing.points;
+                }
+                sample
+            },
+        )
+        .collect()
+}
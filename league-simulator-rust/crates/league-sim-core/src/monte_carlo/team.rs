@@ -0,0 +1,117 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One team's final positions and points over the simulated seasons.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamDistribution {
+    pub team_id: usize,
+    /// Probability of each final position, first place first
+    pub positions: Vec<f64>,
+    /// Probability of each final points total that occurred, fewest points
+    /// first
+    pub points: Vec<PointsProbability>,
+}
+
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointsProbability {
+    /// Final points, adjustments included
+    pub points: i32,
+    pub probability: f64,
+}
+
+/// Distribution of `team`'s final position and points over
+/// `params.iterations` simulated seasons. The positions equal the team's
+/// row of the probability matrix of the same run. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_team_distribution(
+    season: &Season,
+    params: &SimulationParams,
+    team: usize,
+) -> TeamDistribution {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    distribution_with_seeds(season, params, team, &seeds)
+}
+
+/// Deterministic variant of [`run_team_distribution`].
+pub fn run_team_distribution_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team: usize,
+    master_seed: u64,
+) -> TeamDistribution {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    distribution_with_seeds(season, params, team, &seeds)
+}
+
+fn distribution_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team: usize,
+    seeds: &[u64],
+) -> TeamDistribution {
+    let _span = super::run_span(season, params).entered();
+    let prepared = PreparedSeason::new(season, params);
+    let (positions, points) = match params.rng {
+        RngKind::Std => count_with::<StdRng>(params, &prepared, team, seeds),
+        RngKind::SmallRng => count_with::<SmallRng>(params, &prepared, team, seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            count_with::<Xoshiro256PlusPlus>(params, &prepared, team, seeds)
+        }
+    };
+
+    let runs = seeds.len().max(1) as f64;
+    TeamDistribution {
+        team_id: team,
+        positions: positions.iter().map(|&n| n as f64 / runs).collect(),
+        points: points
+            .into_iter()
+            .map(|(points, n)| PointsProbability {
+                points,
+                probability: n as f64 / runs,
+            })
+            .collect(),
+    }
+}
+
+/// Runs finishing in each position, 0-based, and per final points total.
+fn count_with<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    team: usize,
+    seeds: &[u64],
+) -> (Vec<usize>, BTreeMap<i32, usize>) {
+    let n_teams = prepared.baseline.len();
+    let empty = || (vec![0; n_teams], BTreeMap::new());
+
+    seeds
+        .par_iter()
+        .fold(
+            || (IterBuffers::new(prepared), empty()),
+            |(mut buffers, (mut positions, mut points)), &seed| {
+                buffers.simulate::<R>(params, prepared, seed);
+                if let Some(row) = buffers.standings.iter().find(|s| s.team_id == team) {
+                    positions[row.position - 1] += 1;
+                    *points.entry(row.points).or_insert(0) += 1;
+                }
+                (buffers, (positions, points))
+            },
+        )
+        .map(|(_, counts)| counts)
+        .reduce(empty, |(mut positions, mut points), (p, q)| {
+            for (x, y) in positions.iter_mut().zip(p) {
+                *x += y;
+            }
+            for (total, n) in q {
+                *points.entry(total).or_insert(0) += n;
+            }
+            (positions, points)
+        })
+}
@@ -0,0 +1,150 @@
+use super::{master_seed, IterBuffers};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{stream_seeds, PreparedSeason};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Distribution of one team's Elo over its remaining matches.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamEloTrajectory {
+    pub team_name: String,
+    /// Rating after the played matches before the first unplayed one
+    pub current_elo: f64,
+    /// One point per remaining match of the team, in schedule order
+    pub points: Vec<EloTrajectoryPoint>,
+}
+
+/// Rating of a team after one of its matches, over all iterations.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EloTrajectoryPoint {
+    /// 1-based position of the match in the schedule
+    pub match_number: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Mean and standard deviation of every team's Elo after each of its
+/// matches from the first unplayed one on, over `params.iterations`
+/// simulated seasons, in team_id order. Seeded like
+/// [`run_monte_carlo_simulation`](super::run_monte_carlo_simulation).
+pub fn run_elo_trajectories(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+) -> Vec<TeamEloTrajectory> {
+    let seeds = stream_seeds(master_seed(params), 0, params.iterations);
+    trajectories_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Deterministic variant of [`run_elo_trajectories`].
+pub fn run_elo_trajectories_seeded(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    master_seed: u64,
+) -> Vec<TeamEloTrajectory> {
+    let seeds = stream_seeds(master_seed, 0, params.iterations);
+    trajectories_with_seeds(season, params, team_names, &seeds)
+}
+
+/// Sum and sum of squares of the home and away rating changes (relative
+/// to `PreparedSeason::elos`, which keeps the sums of squares small) after
+/// each tail match.
+type Moments = Vec<[f64; 4]>;
+
+fn trajectories_with_seeds(
+    season: &Season,
+    params: &SimulationParams,
+    team_names: &[String],
+    seeds: &[u64],
+) -> Vec<TeamEloTrajectory> {
+    let _span = super::run_span(season, params).entered();
+    let prepared = PreparedSeason::new(season, params);
+    let moments = match params.rng {
+        RngKind::Std => elo_moments::<StdRng>(params, &prepared, seeds),
+        RngKind::SmallRng => elo_moments::<SmallRng>(params, &prepared, seeds),
+        RngKind::Xoshiro256PlusPlus => elo_moments::<Xoshiro256PlusPlus>(params, &prepared, seeds),
+    };
+
+    let mut trajectories: Vec<TeamEloTrajectory> = prepared
+        .elos
+        .iter()
+        .enumerate()
+        .map(|(team_id, &elo)| TeamEloTrajectory {
+            team_name: team_names
+                .get(team_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Team {}", team_id + 1)),
+            current_elo: elo,
+            points: Vec::new(),
+        })
+        .collect();
+
+    let iterations = seeds.len().max(1) as f64;
+    let point = |match_number: usize, start: f64, sum: f64, sum_sq: f64| {
+        let change = sum / iterations;
+        EloTrajectoryPoint {
+            match_number,
+            mean: start + change,
+            // Clamped: rounding can leave a tiny negative variance.
+            std_dev: (sum_sq / iterations - change * change).max(0.0).sqrt(),
+        }
+    };
+    let offset = season.matches.len() - prepared.tail.len();
+    for (index, (m, [home, home_sq, away, away_sq])) in
+        prepared.tail.iter().zip(moments).enumerate()
+    {
+        let match_number = offset + index + 1;
+        let (start_home, start_away) = (prepared.elos[m.team_home], prepared.elos[m.team_away]);
+        trajectories[m.team_home]
+            .points
+            .push(point(match_number, start_home, home, home_sq));
+        trajectories[m.team_away]
+            .points
+            .push(point(match_number, start_away, away, away_sq));
+    }
+    trajectories
+}
+
+fn elo_moments<R: Rng + RngExt + SeedableRng>(
+    params: &SimulationParams,
+    prepared: &PreparedSeason,
+    seeds: &[u64],
+) -> Moments {
+    let n_matches = prepared.tail.len();
+
+    seeds
+        .par_iter()
+        .fold(
+            || (IterBuffers::new(prepared), vec![[0.0; 4]; n_matches]),
+            |(mut buffers, mut moments), &seed| {
+                buffers.simulate_recording::<R>(params, prepared, seed, |index, elos| {
+                    let m = &prepared.tail[index];
+                    let home = elos[m.team_home] - prepared.elos[m.team_home];
+                    let away = elos[m.team_away] - prepared.elos[m.team_away];
+                    let slot = &mut moments[index];
+                    slot[0] += home;
+                    slot[1] += home * home;
+                    slot[2] += away;
+                    slot[3] += away * away;
+                });
+                (buffers, moments)
+            },
+        )
+        .map(|(_, moments)| moments)
+        .reduce(
+            || vec![[0.0; 4]; n_matches],
+            |mut a, b| {
+                for (row_a, row_b) in a.iter_mut().zip(b) {
+                    for (x, y) in row_a.iter_mut().zip(row_b) {
+                        *x += y;
+                    }
+                }
+                a
+            },
+        )
+}
@@ -0,0 +1,186 @@
+use crate::elo::{calculate_elo_change, weighted_mod_factor};
+use crate::models::{EloParams, Match, Season, SimulationParams, TeamStanding};
+use crate::simulation::{
+    add_match_results, expected_goals, outcome_probabilities, poisson_pmf, sort_standings,
+};
+
+/// What counts as a distinct result of a remaining fixture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeMode {
+    /// Home win, draw, away win, entered into the table as 1:0, 0:0, 0:1.
+    Results,
+    /// Every scoreline with at most `max_goals` goals per side.
+    Scorelines { max_goals: u32 },
+}
+
+/// One way the remaining fixtures can end.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// `[goals_home, goals_away]` per remaining fixture, in schedule order
+    pub results: Vec<[i32; 2]>,
+    /// Probability under the goal model
+    pub probability: f64,
+    /// Final table in rank order
+    pub table: Vec<TeamStanding>,
+}
+
+/// Every outcome of the remaining fixtures.
+#[derive(Debug, Clone)]
+pub struct OutcomeTree {
+    /// The unplayed fixtures the results refer to
+    pub fixtures: Vec<Match>,
+    pub outcomes: Vec<Outcome>,
+    /// Total probability of the enumerated outcomes: 1 for `Results`, less
+    /// for `Scorelines` by the mass of scorelines above the cap
+    pub coverage: f64,
+}
+
+/// Number of outcomes `enumerate_outcomes` would produce, or None if it
+/// doesn't fit in a u64.
+pub fn count_outcomes(season: &Season, mode: OutcomeMode) -> Option<u64> {
+    let per_fixture = match mode {
+        OutcomeMode::Results => 3u64,
+        OutcomeMode::Scorelines { max_goals } => (max_goals as u64 + 1).checked_pow(2)?,
+    };
+    let remaining = season
+        .matches
+        .iter()
+        .filter(|m| m.goals_home.is_none())
+        .count();
+    per_fixture.checked_pow(remaining.try_into().ok()?)
+}
+
+/// Enumerate every combination of results of the unplayed fixtures and the
+/// final table each one produces.
+///
+/// Outcome probabilities use the ratings current at each fixture after
+/// replaying the played matches in schedule order; unplayed fixtures don't
+/// move the ratings, as in `project_table`. This is exact for a final
+/// matchday and an approximation when several matchdays remain. Check
+/// `count_outcomes` first: the tree grows exponentially with the number of
+/// remaining fixtures.
+pub fn enumerate_outcomes(
+    season: &Season,
+    params: &SimulationParams,
+    mode: OutcomeMode,
+) -> OutcomeTree {
+    // Candidate results with their probabilities, per unplayed fixture.
+    let mut elos = season.team_elos.clone();
+    let mut fixtures = Vec::new();
+    let mut choices: Vec<Vec<([i32; 2], f64)>> = Vec::new();
+    for (i, m) in season.matches.iter().enumerate() {
+        let (home, away) = (m.team_home, m.team_away);
+        match (m.goals_home, m.goals_away) {
+            (Some(goals_home), Some(goals_away)) => {
+                let result = calculate_elo_change(&EloParams {
+                    elo_home: elos[home],
+                    elo_away: elos[away],
+                    goals_home,
+                    goals_away,
+                    mod_factor: weighted_mod_factor(
+                        params.mod_factor,
+                        params.mod_factor_weights.as_deref(),
+                        i,
+                    ) * params.match_weight(m),
+                    home_advantage: params.home_advantage,
+                });
+                elos[home] = result.new_elo_home;
+                elos[away] = result.new_elo_away;
+            }
+            _ => {
+                fixtures.push(m.clone());
+                choices.push(fixture_choices(elos[home], elos[away], params, mode));
+            }
+        }
+    }
+
+    let mut baseline = params.baseline_standings(season.number_teams);
+    add_match_results(&mut baseline, &season.matches);
+
+    // Odometer over the choice indices, last fixture fastest.
+    let mut outcomes = Vec::new();
+    let mut index = vec![0usize; fixtures.len()];
+    let mut played: Vec<Match> = fixtures.clone();
+    loop {
+        let mut probability = 1.0;
+        let mut results = Vec::with_capacity(fixtures.len());
+        for ((m, options), &i) in played.iter_mut().zip(&choices).zip(&index) {
+            let (score, p) = options[i];
+            m.goals_home = Some(score[0]);
+            m.goals_away = Some(score[1]);
+            probability *= p;
+            results.push(score);
+        }
+        let mut table = baseline.clone();
+        add_match_results(&mut table, &played);
+        sort_standings(&mut table);
+        outcomes.push(Outcome {
+            results,
+            probability,
+            table,
+        });
+
+        // Advance the odometer; done once every digit has wrapped.
+        let mut digit = index.len();
+        loop {
+            if digit == 0 {
+                let coverage = outcomes.iter().map(|o| o.probability).sum();
+                return OutcomeTree {
+                    fixtures,
+                    outcomes,
+                    coverage,
+                };
+            }
+            digit -= 1;
+            index[digit] += 1;
+            if index[digit] < choices[digit].len() {
+                break;
+            }
+            index[digit] = 0;
+        }
+    }
+}
+
+/// Possible results of one fixture with their probabilities.
+fn fixture_choices(
+    elo_home: f64,
+    elo_away: f64,
+    params: &SimulationParams,
+    mode: OutcomeMode,
+) -> Vec<([i32; 2], f64)> {
+    match mode {
+        OutcomeMode::Results => {
+            let p = outcome_probabilities(
+                elo_home,
+                elo_away,
+                params.home_advantage,
+                params.tore_slope,
+                params.tore_intercept,
+            );
+            vec![([1, 0], p.home_win), ([0, 0], p.draw), ([0, 1], p.away_win)]
+        }
+        OutcomeMode::Scorelines { max_goals } => {
+            let (lambda_home, lambda_away) = expected_goals(
+                elo_home,
+                elo_away,
+                params.home_advantage,
+                params.tore_slope,
+                params.tore_intercept,
+            );
+            let pmf_home = poisson_pmf(lambda_home);
+            let pmf_away = poisson_pmf(lambda_away);
+            let p = |pmf: &[f64], goals: u32| pmf.get(goals as usize).copied().unwrap_or(0.0);
+
+            let mut options = Vec::new();
+            for h in 0..=max_goals {
+                for a in 0..=max_goals {
+                    options.push(([h as i32, a as i32], p(&pmf_home, h) * p(&pmf_away, a)));
+                }
+            }
+            options
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use super::*;
+
+fn fixture(home: usize, away: usize, goals: Option<(i32, i32)>) -> Match {
+    Match {
+        team_home: home,
+        team_away: away,
+        goals_home: goals.map(|g| g.0),
+        goals_away: goals.map(|g| g.1),
+        matchday: None,
+        competition: None,
+        weight: None,
+    }
+}
+
+/// Four teams, one matchday left: 0 and 1 level on points at the top.
+fn final_matchday() -> Season {
+    Season {
+        matches: vec![
+            fixture(0, 2, Some((2, 0))),
+            fixture(1, 3, Some((1, 0))),
+            fixture(2, 3, Some((1, 1))),
+            fixture(0, 3, None),
+            fixture(1, 2, None),
+        ],
+        team_elos: vec![1600.0, 1550.0, 1450.0, 1400.0],
+        number_teams: 4,
+    }
+}
+
+#[test]
+fn results_mode_enumerates_three_outcomes_per_fixture() {
+    let season = final_matchday();
+    let params = SimulationParams::default();
+
+    assert_eq!(count_outcomes(&season, OutcomeMode::Results), Some(9));
+    let tree = enumerate_outcomes(&season, &params, OutcomeMode::Results);
+
+    assert_eq!(tree.fixtures.len(), 2);
+    assert_eq!(tree.outcomes.len(), 9);
+    assert!((tree.coverage - 1.0).abs() < 1e-9);
+
+    // First outcome: both home teams win 1:0.
+    let first = &tree.outcomes[0];
+    assert_eq!(first.results, vec![[1, 0], [1, 0]]);
+    let leader = &first.table[0];
+    assert_eq!(leader.team_id, 0, "0 wins on goal difference");
+    assert_eq!(leader.points, 6);
+    assert_eq!(leader.position, 1);
+
+    // Team 1 wins, team 0 loses: 1 is champion.
+    let swap = tree
+        .outcomes
+        .iter()
+        .find(|o| o.results == vec![[0, 1], [1, 0]])
+        .unwrap();
+    assert_eq!(swap.table[0].team_id, 1);
+}
+
+#[test]
+fn scoreline_mode_probabilities_match_the_goal_model() {
+    let season = final_matchday();
+    let params = SimulationParams::default();
+    let mode = OutcomeMode::Scorelines { max_goals: 3 };
+
+    assert_eq!(count_outcomes(&season, mode), Some(256));
+    let tree = enumerate_outcomes(&season, &params, mode);
+    assert_eq!(tree.outcomes.len(), 256);
+    assert!(tree.coverage < 1.0 && tree.coverage > 0.7);
+
+    // Summing scorelines by result reproduces the win/draw/loss split,
+    // up to the mass above the cap.
+    let results = enumerate_outcomes(&season, &params, OutcomeMode::Results);
+    let home_wins = |tree: &OutcomeTree| -> f64 {
+        tree.outcomes
+            .iter()
+            .filter(|o| o.results[0][0] > o.results[0][1])
+            .map(|o| o.probability)
+            .sum()
+    };
+    assert!((home_wins(&tree) / tree.coverage - home_wins(&results)).abs() < 0.05);
+}
+
+#[test]
+fn fully_played_season_has_one_outcome() {
+    let mut season = final_matchday();
+    season.matches.truncate(3);
+
+    let tree = enumerate_outcomes(&season, &SimulationParams::default(), OutcomeMode::Results);
+    assert_eq!(tree.outcomes.len(), 1);
+    assert!(tree.outcomes[0].results.is_empty());
+    assert_eq!(tree.outcomes[0].probability, 1.0);
+}
+
+#[test]
+fn count_reports_overflow() {
+    let season = Season {
+        matches: (0..50).map(|_| fixture(0, 1, None)).collect(),
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+    assert_eq!(count_outcomes(&season, OutcomeMode::Results), None);
+}
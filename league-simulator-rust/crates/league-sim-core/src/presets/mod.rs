@@ -0,0 +1,128 @@
+//! Built-in league configurations, so a request can name its league
+//! instead of spelling out the format.
+
+use crate::models::TiebreakerRule;
+use crate::simulation::TwoLegRules;
+use serde::{Deserialize, Serialize};
+
+/// The rules of one league beyond its schedule: format, table order,
+/// movement between divisions and adjustment conventions.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LeaguePreset {
+    pub id: String,
+    pub name: String,
+    pub number_teams: usize,
+    /// Id of the [`LeagueStructure`](crate::structure::LeagueStructure)
+    /// template with the league's zones
+    pub structure: String,
+    /// Order of the final table
+    pub tiebreakers: Vec<TiebreakerRule>,
+    /// Top teams going up to the division above after each season
+    pub promoted: usize,
+    /// Bottom teams going down to the division below
+    pub relegated: usize,
+    /// Whether the team just above the relegation places plays the best
+    /// non-promoted team of the division below for its place
+    pub relegation_playoff: bool,
+    /// How a level relegation playoff is settled
+    pub playoff_rules: TwoLegRules,
+    /// Points added, when ranking for promotion only, to reserve teams
+    /// (named "... II"), which may not go up: the TeamList `Promotion`
+    /// convention. None where the league has no reserve teams.
+    pub reserve_team_promotion: Option<i32>,
+}
+
+impl LeaguePreset {
+    /// Promotion adjustments of `team_names` under the reserve team
+    /// convention; None if the league has none or no team is a reserve
+    /// team.
+    pub fn promotion_adjustments(&self, team_names: &[String]) -> Option<Vec<i32>> {
+        let points = self.reserve_team_promotion?;
+        let adjustments: Vec<i32> = team_names
+            .iter()
+            .map(|name| if name.ends_with(" II") { points } else { 0 })
+            .collect();
+        adjustments.iter().any(|&a| a != 0).then_some(adjustments)
+    }
+}
+
+/// Points, goal difference and goals scored, then the matches among the
+/// tied teams: the DFL and Premier League order.
+const HEAD_TO_HEAD_CHAIN: [TiebreakerRule; 5] = [
+    TiebreakerRule::Points,
+    TiebreakerRule::GoalDifference,
+    TiebreakerRule::GoalsFor,
+    TiebreakerRule::HeadToHeadPoints,
+    TiebreakerRule::HeadToHeadAwayGoals,
+];
+
+/// Built-in league presets, one per built-in structure template.
+pub fn presets() -> Vec<LeaguePreset> {
+    let preset = |id: &str, name: &str, number_teams: usize| LeaguePreset {
+        id: id.to_string(),
+        name: name.to_string(),
+        number_teams,
+        structure: id.to_string(),
+        tiebreakers: HEAD_TO_HEAD_CHAIN.to_vec(),
+        promoted: 0,
+        relegated: 0,
+        relegation_playoff: false,
+        playoff_rules: TwoLegRules::default(),
+        reserve_team_promotion: None,
+    };
+    vec![
+        LeaguePreset {
+            relegated: 2,
+            relegation_playoff: true,
+            ..preset("bundesliga", "Bundesliga", 18)
+        },
+        LeaguePreset {
+            promoted: 2,
+            relegated: 2,
+            relegation_playoff: true,
+            ..preset("2_bundesliga", "2. Bundesliga", 18)
+        },
+        LeaguePreset {
+            promoted: 2,
+            relegated: 4,
+            reserve_team_promotion: Some(-50),
+            ..preset("3_liga", "3. Liga", 20)
+        },
+        LeaguePreset {
+            relegated: 3,
+            ..preset("premier_league", "Premier League", 20)
+        },
+        // Level teams play off; the simulation keeps goals scored as its
+        // last criterion.
+        LeaguePreset {
+            tiebreakers: TiebreakerRule::DEFAULT_CHAIN.to_vec(),
+            relegated: 1,
+            relegation_playoff: true,
+            ..preset("scottish_premiership", "Scottish Premiership", 12)
+        },
+        // Away goals, wins and the later criteria of the league phase are
+        // beyond the schedule's results; lots settle what remains.
+        LeaguePreset {
+            tiebreakers: vec![
+                TiebreakerRule::Points,
+                TiebreakerRule::GoalDifference,
+                TiebreakerRule::GoalsFor,
+                TiebreakerRule::DrawingOfLots,
+            ],
+            ..preset(
+                "champions_league_phase",
+                "UEFA Champions League (league phase)",
+                36,
+            )
+        },
+    ]
+}
+
+/// Preset by id.
+pub fn preset(id: &str) -> Option<LeaguePreset> {
+    presets().into_iter().find(|p| p.id == id)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,41 @@
+use super::*;
+use crate::structure::template;
+
+#[test]
+fn every_preset_names_a_template_of_its_size() {
+    for preset in presets() {
+        let structure = template(&preset.structure)
+            .unwrap_or_else(|| panic!("{}: unknown template", preset.id));
+        assert_eq!(structure.number_teams, preset.number_teams, "{}", preset.id);
+        assert_eq!(
+            preset.tiebreakers[0],
+            TiebreakerRule::Points,
+            "{}",
+            preset.id
+        );
+        assert!(
+            preset.promoted + preset.relegated + usize::from(preset.relegation_playoff)
+                <= preset.number_teams
+        );
+    }
+}
+
+#[test]
+fn reserve_teams_are_held_back_only_where_the_convention_applies() {
+    let names: Vec<String> = ["Dresden", "VfB Stuttgart II", "Essen"]
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+
+    let dritte_liga = preset("3_liga").unwrap();
+    assert_eq!(
+        dritte_liga.promotion_adjustments(&names),
+        Some(vec![0, -50, 0])
+    );
+    assert_eq!(dritte_liga.promotion_adjustments(&names[..1]), None);
+    assert_eq!(
+        preset("bundesliga").unwrap().promotion_adjustments(&names),
+        None
+    );
+    assert!(preset("serie_a").is_none());
+}
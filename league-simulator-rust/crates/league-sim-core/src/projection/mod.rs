@@ -0,0 +1,227 @@
+use crate::elo::{calculate_elo_change, weighted_mod_factor};
+use crate::models::{
+    EloParams, Match, OutcomeProbabilities, ProjectedStanding, Season, SimulationParams,
+    TiebreakerRule,
+};
+use crate::simulation::{add_match_results, outcome_probabilities};
+use crate::structure::Zone;
+use serde::{Deserialize, Serialize};
+
+/// Deterministic projected final table (no sampling).
+///
+/// Played matches count with their actual result and update the ratings in
+/// schedule order, exactly as in `simulate_season`. Every unplayed match
+/// contributes its expected points (3 * P(win) + P(draw)) and expected goals
+/// at the ratings current at that point; ratings are not moved by unplayed
+/// matches, since there is no sampled result to update them with.
+///
+/// Teams are ordered by expected points, then expected goal difference, then
+/// expected goals scored. Adjustments in `params` are applied as in
+/// `calculate_table`.
+pub fn project_table(season: &Season, params: &SimulationParams) -> Vec<ProjectedStanding> {
+    let n_teams = season.number_teams;
+    let adj = |v: &Option<Vec<i32>>, i: usize| v.as_ref().map(|a| a[i]).unwrap_or(0) as f64;
+
+    let mut standings: Vec<ProjectedStanding> = (0..n_teams)
+        .map(|i| ProjectedStanding {
+            team_id: i,
+            expected_points: adj(&params.adj_points, i),
+            expected_goals_for: adj(&params.adj_goals, i),
+            expected_goals_against: adj(&params.adj_goals_against, i),
+            expected_goal_difference: adj(&params.adj_goal_diff, i),
+            position: 0,
+        })
+        .collect();
+    let mut elos = season.team_elos.clone();
+
+    for (i, m) in season.matches.iter().enumerate() {
+        let (home, away) = (m.team_home, m.team_away);
+        let (points_home, points_away, goals_home, goals_away) = match (m.goals_home, m.goals_away)
+        {
+            (Some(goals_home), Some(goals_away)) => {
+                update_elos(&mut elos, m, i, params);
+                let (points_home, points_away) = match goals_home.cmp(&goals_away) {
+                    std::cmp::Ordering::Greater => (3.0, 0.0),
+                    std::cmp::Ordering::Equal => (1.0, 1.0),
+                    std::cmp::Ordering::Less => (0.0, 3.0),
+                };
+                (
+                    points_home,
+                    points_away,
+                    goals_home as f64,
+                    goals_away as f64,
+                )
+            }
+            _ => {
+                let p = outcome_probabilities(
+                    elos[home],
+                    elos[away],
+                    params.home_advantage,
+                    params.tore_slope,
+                    params.tore_intercept,
+                );
+                (
+                    3.0 * p.home_win + p.draw,
+                    3.0 * p.away_win + p.draw,
+                    p.expected_goals_home,
+                    p.expected_goals_away,
+                )
+            }
+        };
+
+        standings[home].expected_points += points_home;
+        standings[away].expected_points += points_away;
+        standings[home].expected_goals_for += goals_home;
+        standings[home].expected_goals_against += goals_away;
+        standings[away].expected_goals_for += goals_away;
+        standings[away].expected_goals_against += goals_home;
+        standings[home].expected_goal_difference += goals_home - goals_away;
+        standings[away].expected_goal_difference += goals_away - goals_home;
+    }
+
+    standings.sort_by(|a, b| {
+        b.expected_points
+            .total_cmp(&a.expected_points)
+            .then_with(|| {
+                b.expected_goal_difference
+                    .total_cmp(&a.expected_goal_difference)
+            })
+            .then_with(|| b.expected_goals_for.total_cmp(&a.expected_goals_for))
+    });
+    for (pos, standing) in standings.iter_mut().enumerate() {
+        standing.position = pos + 1;
+    }
+
+    standings
+}
+
+/// Win/draw/loss probabilities of every unplayed match, as pairs of its
+/// index in `season.matches` and the probabilities, in schedule order. Each
+/// uses the ratings current at that point, exactly as [`project_table`]
+/// does.
+pub fn fixture_probabilities(
+    season: &Season,
+    params: &SimulationParams,
+) -> Vec<(usize, OutcomeProbabilities)> {
+    let mut elos = season.team_elos.clone();
+    let mut fixtures = Vec::new();
+    for (i, m) in season.matches.iter().enumerate() {
+        if m.goals_home.is_some() && m.goals_away.is_some() {
+            update_elos(&mut elos, m, i, params);
+        } else {
+            fixtures.push((
+                i,
+                outcome_probabilities(
+                    elos[m.team_home],
+                    elos[m.team_away],
+                    params.home_advantage,
+                    params.tore_slope,
+                    params.tore_intercept,
+                ),
+            ));
+        }
+    }
+    fixtures
+}
+
+/// Move `elos` by the result of the played match `m`, row `i` of the
+/// schedule.
+fn update_elos(elos: &mut [f64], m: &Match, i: usize, params: &SimulationParams) {
+    let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+        return;
+    };
+    let result = calculate_elo_change(&EloParams {
+        elo_home: elos[m.team_home],
+        elo_away: elos[m.team_away],
+        goals_home,
+        goals_away,
+        mod_factor: weighted_mod_factor(params.mod_factor, params.mod_factor_weights.as_deref(), i)
+            * params.match_weight(m),
+        home_advantage: params.home_advantage,
+    });
+    elos[m.team_home] = result.new_elo_home;
+    elos[m.team_away] = result.new_elo_away;
+}
+
+/// Final positions a team can still reach whatever the remaining results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PositionBounds {
+    pub team_id: usize,
+    /// Best reachable position, 1-based
+    pub best: usize,
+    /// Worst reachable position, 1-based
+    pub worst: usize,
+}
+
+impl PositionBounds {
+    /// `Some(true)` if the team is certain to finish in `zone`,
+    /// `Some(false)` if it cannot, `None` while that is open.
+    pub fn settles(&self, zone: &Zone) -> Option<bool> {
+        if zone.first <= self.best && self.worst <= zone.last {
+            Some(true)
+        } else if self.worst < zone.first || self.best > zone.last {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Mathematical bounds on every team's final position, in team_id order,
+/// from points alone: a team's best case wins all its remaining matches,
+/// its worst case loses them, and a rival level on points may go either
+/// way unless both have finished, when the table's tiebreakers decide.
+/// Only the default chain is evaluated for that; under any other chain,
+/// which must start with points, finished teams level on points stay open.
+///
+/// The bounds never exclude a reachable position, so a zone reported as
+/// clinched or lost by [`PositionBounds::settles`] really is. Each rival is
+/// bounded on its own, though, so a race can be settled a little before it
+/// is reported, e.g. while two contenders still have to meet. Adjustments
+/// in `params` count as in `calculate_table`.
+pub fn position_bounds(season: &Season, params: &SimulationParams) -> Vec<PositionBounds> {
+    let n_teams = season.number_teams;
+    let mut standings = params.baseline_standings(n_teams);
+    add_match_results(&mut standings, &season.matches);
+    let mut remaining = vec![0; n_teams];
+    for m in season.matches.iter().filter(|m| m.goals_home.is_none()) {
+        remaining[m.team_home] += 1;
+        remaining[m.team_away] += 1;
+    }
+    let default_chain = params.tiebreakers == TiebreakerRule::DEFAULT_CHAIN;
+    let least = |t: usize| standings[t].points;
+    let most = |t: usize| standings[t].points + 3 * remaining[t];
+    // Whether `u` finishes above `t` once neither has a match left, as
+    // `sort_standings` orders them (a full tie keeps team_id order).
+    let settled_above = |u: usize, t: usize| {
+        let key = |i: usize| {
+            let s = &standings[i];
+            (s.points, s.goal_difference, s.goals_for)
+        };
+        key(u) > key(t) || (key(u) == key(t) && u < t)
+    };
+
+    (0..n_teams)
+        .map(|t| {
+            let (mut surely_above, mut maybe_above) = (0, 0);
+            for u in (0..n_teams).filter(|&u| u != t) {
+                if remaining[u] == 0 && remaining[t] == 0 && default_chain {
+                    let above = usize::from(settled_above(u, t));
+                    surely_above += above;
+                    maybe_above += above;
+                } else {
+                    surely_above += usize::from(least(u) > most(t));
+                    maybe_above += usize::from(most(u) >= least(t));
+                }
+            }
+            PositionBounds {
+                team_id: t,
+                best: surely_above + 1,
+                worst: maybe_above + 1,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,175 @@
+use super::*;
+use crate::models::Match;
+use crate::simulation::outcome_probabilities;
+use approx::assert_relative_eq;
+
+fn unplayed(team_home: usize, team_away: usize) -> Match {
+    Match {
+        team_home,
+        team_away,
+        goals_home: None,
+        goals_away: None,
+        matchday: None,
+        competition: None,
+        weight: None,
+    }
+}
+
+#[test]
+fn outcome_probabilities_sum_to_one_and_favour_stronger_team() {
+    let p = outcome_probabilities(1700.0, 1400.0, 65.0, 0.0017854953143549, 1.32183908045977);
+
+    assert_relative_eq!(p.home_win + p.draw + p.away_win, 1.0, epsilon = 1e-12);
+    assert!(p.home_win > p.away_win);
+    assert!(p.expected_goals_home > p.expected_goals_away);
+}
+
+#[test]
+fn played_matches_count_with_actual_result() {
+    let season = Season {
+        matches: vec![Match {
+            team_home: 0,
+            team_away: 1,
+            goals_home: Some(3),
+            goals_away: Some(1),
+            matchday: None,
+            competition: None,
+            weight: None,
+        }],
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+
+    let table = project_table(&season, &SimulationParams::default());
+
+    assert_eq!(table[0].team_id, 0);
+    assert_eq!(table[0].expected_points, 3.0);
+    assert_eq!(table[0].expected_goal_difference, 2.0);
+    assert_eq!(table[1].expected_points, 0.0);
+    assert_eq!(table[1].position, 2);
+}
+
+#[test]
+fn remaining_fixtures_add_expected_points() {
+    let season = Season {
+        matches: vec![unplayed(0, 1), unplayed(1, 0)],
+        team_elos: vec![1800.0, 1400.0],
+        number_teams: 2,
+    };
+    let params = SimulationParams::default();
+
+    let table = project_table(&season, &params);
+
+    let first = outcome_probabilities(
+        1800.0,
+        1400.0,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    );
+    let second = outcome_probabilities(
+        1400.0,
+        1800.0,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    );
+    let expected_strong = 3.0 * first.home_win + first.draw + 3.0 * second.away_win + second.draw;
+
+    assert_eq!(
+        table[0].team_id, 0,
+        "stronger team should be projected first"
+    );
+    assert_relative_eq!(table[0].expected_points, expected_strong, epsilon = 1e-12);
+    // Each match hands out between 2 and 3 points in expectation.
+    let total: f64 = table.iter().map(|s| s.expected_points).sum();
+    assert!(total > 4.0 && total < 6.0);
+}
+
+#[test]
+fn adjustments_shift_the_projection() {
+    let season = Season {
+        matches: vec![unplayed(0, 1)],
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+    let params = SimulationParams {
+        adj_points: Some(vec![-10, 0]),
+        ..Default::default()
+    };
+
+    let table = project_table(&season, &params);
+
+    assert_eq!(table[1].team_id, 0, "penalised team should drop to last");
+    assert!(table[1].expected_points < -7.0);
+}
+
+#[test]
+fn position_bounds_settle_zones_only_when_certain() {
+    let season = Season {
+        matches: vec![unplayed(0, 3), unplayed(1, 2)],
+        team_elos: vec![1500.0; 4],
+        number_teams: 4,
+    };
+    let params = SimulationParams {
+        adj_points: Some(vec![12, 6, 3, 0]),
+        ..Default::default()
+    };
+    let bounds = position_bounds(&season, &params);
+    let range = |b: &PositionBounds| (b.best, b.worst);
+    assert_eq!(
+        bounds.iter().map(range).collect::<Vec<_>>(),
+        [(1, 1), (2, 3), (2, 4), (3, 4)]
+    );
+
+    let zone = |first, last| Zone {
+        name: String::new(),
+        first,
+        last,
+    };
+    assert_eq!(bounds[0].settles(&zone(1, 1)), Some(true));
+    assert_eq!(bounds[1].settles(&zone(1, 1)), Some(false));
+    assert_eq!(bounds[1].settles(&zone(1, 2)), None);
+    assert_eq!(bounds[3].settles(&zone(1, 2)), Some(false));
+    assert_eq!(bounds[3].settles(&zone(4, 4)), None);
+
+    // Once all is played, level teams are ordered by the table: D passes C
+    // on goal difference.
+    let mut finished = season;
+    for (m, goals) in finished.matches.iter_mut().zip([(0, 2), (1, 0)]) {
+        m.goals_home = Some(goals.0);
+        m.goals_away = Some(goals.1);
+    }
+    let bounds = position_bounds(&finished, &params);
+    assert_eq!(
+        bounds.iter().map(range).collect::<Vec<_>>(),
+        [(1, 1), (2, 2), (4, 4), (3, 3)]
+    );
+}
+
+#[test]
+fn fixture_probabilities_use_the_ratings_after_played_matches() {
+    let mut played = unplayed(0, 1);
+    played.goals_home = Some(0);
+    played.goals_away = Some(3);
+    let season = Season {
+        matches: vec![played, unplayed(1, 0), unplayed(0, 1)],
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+    let params = SimulationParams::default();
+
+    let fixtures = fixture_probabilities(&season, &params);
+
+    assert_eq!(fixtures.iter().map(|f| f.0).collect::<Vec<_>>(), [1, 2]);
+    // The away win moved the ratings, which both fixtures are priced at.
+    let before = outcome_probabilities(
+        1500.0,
+        1500.0,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    );
+    assert!(fixtures[0].1.home_win > before.home_win);
+    assert!(fixtures[1].1.home_win < before.home_win);
+}
@@ -0,0 +1,158 @@
+use super::RatingSystem;
+use crate::models::GlickoSettings;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Conversion between the Glicko scale (1500 average) and the internal
+/// Glicko-2 scale.
+const SCALE: f64 = 173.7178;
+
+/// Convergence tolerance of the volatility iteration.
+const EPSILON: f64 = 1e-6;
+
+/// Glicko-2 rating of a team, on the Glicko scale: `rating` is read like an
+/// Elo rating, `deviation` is its uncertainty (350 for an unknown team).
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GlickoRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+/// Glicko-2 (Glickman, 2012) with every match as its own rating period, so
+/// ratings move between matchdays as they do under Elo.
+///
+/// Only win, draw or loss counts, not the margin. The home side plays with
+/// its rating raised by the home advantage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2 {
+    /// System constant limiting how fast volatility changes, typically
+    /// 0.3 to 1.2
+    pub tau: f64,
+}
+
+impl GlickoSettings {
+    /// Starting Glicko-2 ratings: `ratings` with the per-team deviations
+    /// (or the common one) and the starting volatility.
+    pub fn starting_ratings(&self, ratings: &[f64]) -> Vec<GlickoRating> {
+        ratings
+            .iter()
+            .enumerate()
+            .map(|(i, &rating)| GlickoRating {
+                rating,
+                deviation: self
+                    .deviations
+                    .as_ref()
+                    .and_then(|d| d.get(i))
+                    .copied()
+                    .unwrap_or(self.deviation),
+                volatility: self.volatility,
+            })
+            .collect()
+    }
+
+    pub fn system(&self) -> Glicko2 {
+        Glicko2 { tau: self.tau }
+    }
+}
+
+impl Glicko2 {
+    /// `own` after scoring `score` (1 win, 0.5 draw, 0 loss) against
+    /// `opponent`, with `edge` rating points added to its own rating for
+    /// the expectation. `weight` counts the match that many times.
+    pub fn rate(
+        &self,
+        own: &GlickoRating,
+        opponent: &GlickoRating,
+        edge: f64,
+        score: f64,
+        weight: f64,
+    ) -> GlickoRating {
+        if weight <= 0.0 {
+            return *own;
+        }
+        let mu = (own.rating + edge - 1500.0) / SCALE;
+        let phi = own.deviation / SCALE;
+        let mu_opponent = (opponent.rating - 1500.0) / SCALE;
+        let g = 1.0 / (1.0 + 3.0 * (opponent.deviation / SCALE).powi(2) / (PI * PI)).sqrt();
+        let expected = 1.0 / (1.0 + (-g * (mu - mu_opponent)).exp());
+
+        let v = 1.0 / (weight * g * g * expected * (1.0 - expected));
+        let surprise = weight * g * (score - expected);
+        let delta = v * surprise;
+        let volatility = self.volatility(phi, own.volatility, v, delta);
+
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let phi_new = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        GlickoRating {
+            rating: own.rating + SCALE * phi_new * phi_new * surprise,
+            deviation: SCALE * phi_new,
+            volatility,
+        }
+    }
+
+    /// New volatility by the Illinois iteration of step 5 of the Glicko-2
+    /// paper.
+    fn volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let tau2 = self.tau * self.tau;
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2))
+                - (x - a) / tau2
+        };
+
+        let mut lo = a;
+        let mut hi = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * self.tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * self.tau
+        };
+        let (mut f_lo, mut f_hi) = (f(lo), f(hi));
+        while (hi - lo).abs() > EPSILON {
+            let mid = lo + (lo - hi) * f_lo / (f_hi - f_lo);
+            let f_mid = f(mid);
+            if f_mid * f_hi <= 0.0 {
+                (lo, f_lo) = (hi, f_hi);
+            } else {
+                f_lo /= 2.0;
+            }
+            (hi, f_hi) = (mid, f_mid);
+        }
+        (lo / 2.0).exp()
+    }
+}
+
+impl RatingSystem for Glicko2 {
+    type Rating = GlickoRating;
+
+    fn strength(&self, rating: &GlickoRating) -> f64 {
+        rating.rating
+    }
+
+    fn update(
+        &self,
+        home: &mut GlickoRating,
+        away: &mut GlickoRating,
+        goals_home: i32,
+        goals_away: i32,
+        home_advantage: f64,
+        weight: f64,
+    ) {
+        let score = match goals_home.cmp(&goals_away) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        };
+        // Both sides are rated from the ratings before the match.
+        let new_home = self.rate(home, away, home_advantage, score, weight);
+        let new_away = self.rate(away, home, -home_advantage, 1.0 - score, weight);
+        *home = new_home;
+        *away = new_away;
+    }
+}
@@ -0,0 +1,127 @@
+use crate::elo::calculate_elo_change;
+use crate::models::{EloParams, Match};
+
+mod glicko;
+pub use glicko::*;
+
+/// How results move team ratings, and what strength a rating stands for.
+///
+/// The goal model only ever sees [`strength`](Self::strength), on the Elo
+/// scale the `tore_slope` regression was fitted on, so any system whose
+/// ratings live on that scale can drive a simulation.
+pub trait RatingSystem {
+    /// Everything the system tracks per team
+    type Rating: Clone + Send + Sync;
+
+    /// Strength of a team in ELO points
+    fn strength(&self, rating: &Self::Rating) -> f64;
+
+    /// Update both ratings with a result. `weight` scales the influence of
+    /// the match (see `SimulationParams::mod_factor_weights`); 1 counts it
+    /// once.
+    fn update(
+        &self,
+        home: &mut Self::Rating,
+        away: &mut Self::Rating,
+        goals_home: i32,
+        goals_away: i32,
+        home_advantage: f64,
+        weight: f64,
+    );
+}
+
+/// The engine's Elo formula ([`calculate_elo_change`]) with `mod_factor`
+/// as K.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elo {
+    pub mod_factor: f64,
+}
+
+impl RatingSystem for Elo {
+    type Rating = f64;
+
+    fn strength(&self, rating: &f64) -> f64 {
+        *rating
+    }
+
+    fn update(
+        &self,
+        home: &mut f64,
+        away: &mut f64,
+        goals_home: i32,
+        goals_away: i32,
+        home_advantage: f64,
+        weight: f64,
+    ) {
+        let result = calculate_elo_change(&EloParams {
+            elo_home: *home,
+            elo_away: *away,
+            goals_home,
+            goals_away,
+            mod_factor: self.mod_factor * weight,
+            home_advantage,
+        });
+        *home = result.new_elo_home;
+        *away = result.new_elo_away;
+    }
+}
+
+/// Ratings after applying the played matches of `matches` in order with
+/// `system`, indexed by team_id. `weights`, one per entry of `matches`,
+/// scale each update; unplayed matches are skipped.
+pub fn apply_rated_results<S: RatingSystem>(
+    system: &S,
+    ratings: &[S::Rating],
+    matches: &[Match],
+    weights: Option<&[f64]>,
+    home_advantage: f64,
+) -> Vec<S::Rating> {
+    let mut ratings = ratings.to_vec();
+    for (i, m) in matches.iter().enumerate() {
+        let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+            continue;
+        };
+        update_pair(
+            system,
+            &mut ratings,
+            m,
+            goals_home,
+            goals_away,
+            home_advantage,
+            match_weight(weights, i),
+        );
+    }
+    ratings
+}
+
+/// Weight of the match at `index` of the schedule; 1 without weights.
+pub(crate) fn match_weight(weights: Option<&[f64]>, index: usize) -> f64 {
+    weights.and_then(|w| w.get(index)).copied().unwrap_or(1.0)
+}
+
+/// Apply the result of `m` to the two ratings it involves.
+pub(crate) fn update_pair<S: RatingSystem>(
+    system: &S,
+    ratings: &mut [S::Rating],
+    m: &Match,
+    goals_home: i32,
+    goals_away: i32,
+    home_advantage: f64,
+    weight: f64,
+) {
+    let mut home = ratings[m.team_home].clone();
+    let mut away = ratings[m.team_away].clone();
+    system.update(
+        &mut home,
+        &mut away,
+        goals_home,
+        goals_away,
+        home_advantage,
+        weight,
+    );
+    ratings[m.team_home] = home;
+    ratings[m.team_away] = away;
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,122 @@
+use super::*;
+use crate::models::{RatingSystemKind, Season, SimulationParams};
+use crate::monte_carlo::run_monte_carlo_simulation_seeded;
+use crate::simulation::double_round_robin;
+use approx::assert_relative_eq;
+
+fn played(team_home: usize, team_away: usize, goals_home: i32, goals_away: i32) -> Match {
+    Match {
+        team_home,
+        team_away,
+        goals_home: Some(goals_home),
+        goals_away: Some(goals_away),
+        matchday: None,
+        competition: None,
+        weight: None,
+    }
+}
+
+#[test]
+fn glicko2_matches_the_reference_implementation() {
+    // Reference values from an independent implementation of Glickman's
+    // algorithm, checked against the worked example of the paper.
+    let system = Glicko2 { tau: 0.5 };
+    let start = |rating, deviation| GlickoRating {
+        rating,
+        deviation,
+        volatility: 0.06,
+    };
+
+    let (mut home, mut away) = (start(1600.0, 80.0), start(1500.0, 120.0));
+    system.update(&mut home, &mut away, 2, 0, 65.0, 1.0);
+    assert_relative_eq!(home.rating, 1609.8276, epsilon = 1e-3);
+    assert_relative_eq!(home.deviation, 79.1510, epsilon = 1e-3);
+    assert_relative_eq!(away.rating, 1478.8853, epsilon = 1e-3);
+    assert_relative_eq!(away.deviation, 115.2660, epsilon = 1e-3);
+    assert_relative_eq!(away.volatility, 0.0599985, epsilon = 1e-6);
+
+    let (mut home, mut away) = (start(1600.0, 80.0), start(1500.0, 120.0));
+    system.update(&mut home, &mut away, 1, 1, 65.0, 1.0);
+    assert_relative_eq!(home.rating, 1592.9765, epsilon = 1e-3);
+    assert_relative_eq!(away.rating, 1515.9502, epsilon = 1e-3);
+}
+
+#[test]
+fn glicko2_moves_uncertain_teams_further() {
+    let system = Glicko2 { tau: 0.5 };
+    let team = |deviation| GlickoRating {
+        rating: 1500.0,
+        deviation,
+        volatility: 0.06,
+    };
+    let gain = |deviation| {
+        let (mut home, mut away) = (team(deviation), team(60.0));
+        system.update(&mut home, &mut away, 1, 0, 0.0, 1.0);
+        home.rating - 1500.0
+    };
+    assert!(gain(200.0) > 2.0 * gain(50.0));
+
+    // A weight of 0 leaves both ratings alone, 2 counts the match twice.
+    let (mut home, mut away) = (team(100.0), team(100.0));
+    system.update(&mut home, &mut away, 3, 0, 0.0, 0.0);
+    assert_eq!((home, away), (team(100.0), team(100.0)));
+    let (mut once, mut other) = (team(100.0), team(100.0));
+    system.update(&mut once, &mut other, 3, 0, 0.0, 1.0);
+    let (mut twice, mut other) = (team(100.0), team(100.0));
+    system.update(&mut twice, &mut other, 3, 0, 0.0, 2.0);
+    assert!(twice.rating > once.rating && twice.deviation < once.deviation);
+}
+
+#[test]
+fn elo_system_replays_like_the_elo_formula() {
+    let matches = [played(0, 1, 3, 1), played(1, 2, 0, 0), played(2, 0, 2, 1)];
+    let weights = [1.0, 2.0, 0.5];
+    let mut expected = vec![1600.0, 1500.0, 1400.0];
+    for (m, weight) in matches.iter().zip(weights) {
+        let result = calculate_elo_change(&EloParams {
+            elo_home: expected[m.team_home],
+            elo_away: expected[m.team_away],
+            goals_home: m.goals_home.unwrap(),
+            goals_away: m.goals_away.unwrap(),
+            mod_factor: 20.0 * weight,
+            home_advantage: 65.0,
+        });
+        expected[m.team_home] = result.new_elo_home;
+        expected[m.team_away] = result.new_elo_away;
+    }
+    assert_eq!(
+        apply_rated_results(
+            &Elo { mod_factor: 20.0 },
+            &[1600.0, 1500.0, 1400.0],
+            &matches,
+            Some(&weights),
+            65.0
+        ),
+        expected
+    );
+}
+
+#[test]
+fn monte_carlo_runs_under_glicko2() {
+    let season = Season {
+        matches: double_round_robin(4),
+        team_elos: vec![1800.0, 1500.0, 1500.0, 1200.0],
+        number_teams: 4,
+    };
+    let names: Vec<String> = (1..=4).map(|i| format!("Team {i}")).collect();
+    let elo = SimulationParams {
+        iterations: 2000,
+        ..Default::default()
+    };
+    let glicko = SimulationParams {
+        rating_system: RatingSystemKind::Glicko2,
+        ..elo.clone()
+    };
+
+    let by_elo = run_monte_carlo_simulation_seeded(&season, &elo, names.clone(), 7);
+    let by_glicko = run_monte_carlo_simulation_seeded(&season, &glicko, names, 7);
+    assert_eq!(by_glicko.team_names[0], "Team 1");
+    assert_eq!(by_glicko.team_names[3], "Team 4");
+    assert!(by_glicko.probability_matrix[0][0] > 0.5);
+    assert_ne!(by_glicko.probability_matrix, by_elo.probability_matrix);
+}
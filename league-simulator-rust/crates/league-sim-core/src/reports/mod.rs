@@ -0,0 +1,197 @@
+//! CSV and Markdown renderings of results, for spreadsheets and GitHub
+//! summaries.
+//!
+//! A [`Report`] is a header row plus typed cells; the cell type decides how
+//! a value is written. Probabilities are plain fractions in CSV (so
+//! spreadsheets can compute with them) and percentages in Markdown. The
+//! [`Locale`] picks the decimal separator, and with it the CSV delimiter.
+
+use crate::models::LeagueTable;
+use serde::{Deserialize, Serialize};
+
+/// Number conventions of a rendering.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    /// Decimal point, comma-separated CSV
+    #[default]
+    En,
+    /// Decimal comma (German spreadsheets), semicolon-separated CSV
+    De,
+}
+
+impl Locale {
+    fn decimal(self, number: String) -> String {
+        match self {
+            Locale::En => number,
+            Locale::De => number.replace('.', ","),
+        }
+    }
+
+    fn csv_delimiter(self) -> u8 {
+        match self {
+            Locale::En => b',',
+            Locale::De => b';',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    /// Probability in [0, 1]
+    Probability(f64),
+    Number(f64),
+}
+
+impl Cell {
+    fn csv(&self, locale: Locale) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Int(n) => n.to_string(),
+            Cell::Probability(p) => locale.decimal(format!("{:.4}", p)),
+            Cell::Number(x) => locale.decimal(format!("{:.2}", x)),
+        }
+    }
+
+    fn markdown(&self, locale: Locale) -> String {
+        match self {
+            Cell::Text(s) => s.replace('|', "\\|"),
+            Cell::Int(n) => n.to_string(),
+            Cell::Probability(p) => locale.decimal(format!("{:.1}%", p * 100.0)),
+            Cell::Number(x) => locale.decimal(format!("{:.1}", x)),
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Cell::Text(_))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Report {
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            headers: headers.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    /// One row per team with its probability of every final position.
+    pub fn probabilities(team_names: &[String], matrix: &[Vec<f64>]) -> Self {
+        let positions = matrix.first().map_or(0, Vec::len);
+        let mut report = Self::new(
+            std::iter::once("team".to_string()).chain((1..=positions).map(|p| p.to_string())),
+        );
+        for (name, row) in team_names.iter().zip(matrix) {
+            let mut cells = vec![Cell::Text(name.clone())];
+            cells.extend(row.iter().map(|&p| Cell::Probability(p)));
+            report.push(cells);
+        }
+        report
+    }
+
+    /// A league table in rank order; `team_names` is indexed by team id.
+    pub fn standings(table: &LeagueTable, team_names: &[String]) -> Self {
+        let mut report = Self::new([
+            "position",
+            "team",
+            "played",
+            "won",
+            "drawn",
+            "lost",
+            "goals_for",
+            "goals_against",
+            "goal_difference",
+            "points",
+        ]);
+        for s in &table.standings {
+            let name = team_names
+                .get(s.team_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Team {}", s.team_id + 1));
+            report.push(vec![
+                Cell::Int(s.position as i64),
+                Cell::Text(name),
+                Cell::Int(s.played.into()),
+                Cell::Int(s.won.into()),
+                Cell::Int(s.drawn.into()),
+                Cell::Int(s.lost.into()),
+                Cell::Int(s.goals_for.into()),
+                Cell::Int(s.goals_against.into()),
+                Cell::Int(s.goal_difference.into()),
+                Cell::Int(s.points.into()),
+            ]);
+        }
+        report
+    }
+
+    /// RFC 4180 CSV with a header line.
+    pub fn to_csv(&self) -> String {
+        self.to_csv_in(Locale::default())
+    }
+
+    /// [`Report::to_csv`] with the decimal separator and delimiter of
+    /// `locale`.
+    pub fn to_csv_in(&self, locale: Locale) -> String {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(locale.csv_delimiter())
+            .from_writer(Vec::new());
+        // Writing to a Vec cannot fail.
+        writer.write_record(&self.headers).expect("in-memory write");
+        for row in &self.rows {
+            writer
+                .write_record(row.iter().map(|cell| cell.csv(locale)))
+                .expect("in-memory write");
+        }
+        let bytes = writer.into_inner().expect("in-memory write");
+        String::from_utf8(bytes).expect("CSV of UTF-8 strings")
+    }
+
+    /// GitHub-flavoured Markdown table; numeric columns are right-aligned.
+    pub fn to_markdown(&self) -> String {
+        self.to_markdown_in(Locale::default())
+    }
+
+    /// [`Report::to_markdown`] with the decimal separator of `locale`.
+    pub fn to_markdown_in(&self, locale: Locale) -> String {
+        let numeric: Vec<bool> = (0..self.headers.len())
+            .map(|i| {
+                !self.rows.is_empty()
+                    && self
+                        .rows
+                        .iter()
+                        .all(|row| row.get(i).is_some_and(Cell::is_numeric))
+            })
+            .collect();
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+        let mut out = line(self.headers.iter().map(|h| h.replace('|', "\\|")).collect());
+        out.push_str(&line(
+            numeric
+                .iter()
+                .map(|&n| if n { "---:" } else { "---" }.to_string())
+                .collect(),
+        ));
+        for row in &self.rows {
+            out.push_str(&line(
+                row.iter().map(|cell| cell.markdown(locale)).collect(),
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+use crate::calculate_table;
+use crate::models::Match;
+
+fn names() -> Vec<String> {
+    vec!["Bayern".to_string(), "Werder, Bremen".to_string()]
+}
+
+#[test]
+fn probabilities_render_as_fractions_in_csv_and_percent_in_markdown() {
+    let report = Report::probabilities(&names(), &[vec![0.75, 0.25], vec![0.25, 0.75]]);
+
+    assert_eq!(
+        report.to_csv(),
+        "team,1,2\nBayern,0.7500,0.2500\n\"Werder, Bremen\",0.2500,0.7500\n"
+    );
+    assert_eq!(
+        report.to_markdown(),
+        "| team | 1 | 2 |\n| --- | ---: | ---: |\n| Bayern | 75.0% | 25.0% |\n| Werder, Bremen | 25.0% | 75.0% |\n"
+    );
+}
+
+#[test]
+fn standings_follow_the_table_order() {
+    let matches = vec![Match {
+        team_home: 0,
+        team_away: 1,
+        goals_home: Some(0),
+        goals_away: Some(2),
+        matchday: None,
+        competition: None,
+        weight: None,
+    }];
+    let table = calculate_table(&matches, 2, None, None, None, None);
+    let csv = Report::standings(&table, &names()).to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "position,team,played,won,drawn,lost,goals_for,goals_against,goal_difference,points"
+    );
+    assert_eq!(lines[1], "1,\"Werder, Bremen\",1,1,0,0,2,0,2,3");
+    assert_eq!(lines[2], "2,Bayern,1,0,0,1,0,2,-2,0");
+}
+
+#[test]
+fn german_locale_uses_decimal_commas_and_semicolons() {
+    let mut report = Report::probabilities(&names(), &[vec![0.75, 0.25], vec![0.25, 0.75]]);
+    report.headers.push("points".to_string());
+    report.rows[0].push(Cell::Number(61.5));
+    report.rows[1].push(Cell::Number(1234.0));
+
+    assert_eq!(
+        report.to_csv_in(Locale::De),
+        "team;1;2;points\nBayern;0,7500;0,2500;61,50\nWerder, Bremen;0,2500;0,7500;1234,00\n"
+    );
+    assert!(report
+        .to_markdown_in(Locale::De)
+        .ends_with("| Werder, Bremen | 25,0% | 75,0% | 1234,0 |\n"));
+}
+
+#[test]
+fn markdown_escapes_pipes() {
+    let mut report = Report::new(["team", "rating"]);
+    report.push(vec![Cell::Text("A|B".to_string()), Cell::Number(1500.0)]);
+    assert_eq!(
+        report.to_markdown(),
+        "| team | rating |\n| --- | ---: |\n| A\\|B | 1500.0 |\n"
+    );
+}
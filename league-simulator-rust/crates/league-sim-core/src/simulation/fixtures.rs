@@ -0,0 +1,233 @@
+use crate::models::{Match, Season};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// Unplayed double round robin for `number_teams` teams by the circle
+/// method: every pair meets once at each ground, the second half of the
+/// season mirroring the first with home and away swapped. Matches are in
+/// round order and carry their matchday; with an odd number of teams one
+/// team rests each round.
+pub fn double_round_robin(number_teams: usize) -> Vec<Match> {
+    if number_teams < 2 {
+        return Vec::new();
+    }
+    // An odd field gets a bye slot; pairings with it are skipped.
+    let slots = number_teams + number_teams % 2;
+    let mut ring: Vec<usize> = (0..slots).collect();
+    let mut first_half = Vec::with_capacity(number_teams * (number_teams - 1) / 2);
+    for round in 0..slots - 1 {
+        for i in 0..slots / 2 {
+            let (a, b) = (ring[i], ring[slots - 1 - i]);
+            if a >= number_teams || b >= number_teams {
+                continue;
+            }
+            // Alternate grounds so no team is at home every week.
+            let (home, away) = if (round + i) % 2 == 0 { (a, b) } else { (b, a) };
+            first_half.push(Match {
+                team_home: home,
+                team_away: away,
+                goals_home: None,
+                goals_away: None,
+                matchday: Some(round as u32 + 1),
+                competition: None,
+                weight: None,
+            });
+        }
+        ring[1..].rotate_right(1);
+    }
+
+    let second_half: Vec<Match> = first_half
+        .iter()
+        .map(|m| Match {
+            team_home: m.team_away,
+            team_away: m.team_home,
+            goals_home: None,
+            goals_away: None,
+            matchday: m.matchday.map(|day| day + (slots - 1) as u32),
+            competition: None,
+            weight: None,
+        })
+        .collect();
+    first_half.extend(second_half);
+    first_half
+}
+
+/// [`double_round_robin`] with the teams placed on the circle in an order
+/// drawn from `rng`, so who meets whom on which matchday is random.
+pub fn random_double_round_robin<R: Rng + ?Sized>(number_teams: usize, rng: &mut R) -> Vec<Match> {
+    let mut teams: Vec<usize> = (0..number_teams).collect();
+    teams.shuffle(rng);
+    let mut matches = double_round_robin(number_teams);
+    for m in &mut matches {
+        m.team_home = teams[m.team_home];
+        m.team_away = teams[m.team_away];
+    }
+    matches
+}
+
+/// Unplayed Swiss-model schedule like the Champions League league phase:
+/// `rounds` rounds in which each of `number_teams` teams (an even number)
+/// meets a different opponent, drawn at random from `rng`. Every team is at
+/// home in half of its matches (one more or less for an odd `rounds`).
+/// Matches are in round order and carry their matchday. `rounds` must be below `number_teams`.
+pub fn swiss_schedule<R: Rng + ?Sized>(
+    number_teams: usize,
+    rounds: usize,
+    rng: &mut R,
+) -> Vec<Match> {
+    // The first rounds of a round robin over shuffled teams give distinct,
+    // random opponents.
+    let mut teams: Vec<usize> = (0..number_teams).collect();
+    teams.shuffle(rng);
+    let first_rounds: Vec<Match> = double_round_robin(number_teams)
+        .into_iter()
+        .take(rounds * (number_teams / 2))
+        .collect();
+    let pairs: Vec<(usize, usize)> = first_rounds
+        .iter()
+        .map(|m| (teams[m.team_home], teams[m.team_away]))
+        .collect();
+
+    // Orient every pairing along closed trails, so each team leaves (plays
+    // at home) as often as it arrives. A dummy vertex joined to the teams
+    // of odd degree makes every degree even.
+    let dummy = number_teams;
+    let mut edges = pairs.clone();
+    let mut degree = vec![0usize; number_teams];
+    for &(a, b) in &pairs {
+        degree[a] += 1;
+        degree[b] += 1;
+    }
+    edges.extend(
+        (0..number_teams)
+            .filter(|&t| degree[t] % 2 == 1)
+            .map(|t| (t, dummy)),
+    );
+    let mut incident = vec![Vec::new(); number_teams + 1];
+    for (e, &(a, b)) in edges.iter().enumerate() {
+        incident[a].push(e);
+        incident[b].push(e);
+    }
+    let mut home_first = vec![true; edges.len()];
+    let mut used = vec![false; edges.len()];
+    let mut next = vec![0usize; number_teams + 1];
+    for start in 0..=number_teams {
+        let mut stack = vec![start];
+        while let Some(&at) = stack.last() {
+            while next[at] < incident[at].len() && used[incident[at][next[at]]] {
+                next[at] += 1;
+            }
+            let Some(&e) = incident[at].get(next[at]) else {
+                stack.pop();
+                continue;
+            };
+            used[e] = true;
+            let (a, b) = edges[e];
+            home_first[e] = a == at;
+            stack.push(if a == at { b } else { a });
+        }
+    }
+
+    pairs
+        .iter()
+        .zip(&home_first)
+        .zip(&first_rounds)
+        .map(|((&(a, b), &first), m)| Match {
+            team_home: if first { a } else { b },
+            team_away: if first { b } else { a },
+            goals_home: None,
+            goals_away: None,
+            matchday: m.matchday,
+            competition: None,
+            weight: None,
+        })
+        .collect()
+}
+
+/// Problems with a schedule that do not stop a simulation but usually
+/// point to malformed input: a team playing more than once on a matchday
+/// and, if the schedule should hold the `complete` season, pairings that
+/// make it something other than a full single or double round robin. Each
+/// kind of problem gives one message with a count and an example; teams are
+/// numbered from 1 as in the API's schedule rows.
+pub fn schedule_warnings(season: &Season, complete: bool) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let n = season.number_teams;
+
+    let mut seen = HashSet::new();
+    let mut clashes = Vec::new();
+    for m in &season.matches {
+        let Some(day) = m.matchday else { continue };
+        for team in [m.team_home, m.team_away] {
+            if !seen.insert((day, team)) {
+                clashes.push((team, day));
+            }
+        }
+    }
+    if let Some(&(team, day)) = clashes.first() {
+        warnings.push(format!(
+            "{} extra appearances of a team on one matchday, for example team {} on matchday {}",
+            clashes.len(),
+            team + 1,
+            day
+        ));
+    }
+    if !complete || n < 2 {
+        return warnings;
+    }
+
+    // Meetings per ground, `[home * n + away]`.
+    let mut meetings = vec![0usize; n * n];
+    for m in &season.matches {
+        if m.team_home != m.team_away && m.team_home < n && m.team_away < n {
+            meetings[m.team_home * n + m.team_away] += 1;
+        }
+    }
+    let pairs: Vec<(usize, usize, usize)> = (0..n)
+        .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+        .map(|(a, b)| (a, b, meetings[a * n + b] + meetings[b * n + a]))
+        .collect();
+    let legs = pairs
+        .iter()
+        .map(|&(_, _, k)| k)
+        .max()
+        .unwrap_or(0)
+        .clamp(1, 2);
+    let kind = if legs == 1 { "single" } else { "double" };
+
+    let short: Vec<_> = pairs.iter().filter(|&&(_, _, k)| k < legs).collect();
+    if let Some(&&(a, b, k)) = short.first() {
+        warnings.push(format!(
+            "not a complete {} round robin: {} pairings meet fewer than {} times, for example teams {} and {} ({})",
+            kind,
+            short.len(),
+            legs,
+            a + 1,
+            b + 1,
+            k
+        ));
+    }
+    let over: Vec<_> = pairs.iter().filter(|&&(_, _, k)| k > 2).collect();
+    if let Some(&&(a, b, k)) = over.first() {
+        warnings.push(format!(
+            "{} pairings meet more than twice, for example teams {} and {} ({} times)",
+            over.len(),
+            a + 1,
+            b + 1,
+            k
+        ));
+    }
+    if legs == 2 {
+        let repeated: Vec<_> = (0..n * n).filter(|&i| meetings[i] > 1).collect();
+        if let Some(&i) = repeated.first() {
+            warnings.push(format!(
+                "{} pairings meet at the same ground more than once, for example team {} hosting team {}",
+                repeated.len(),
+                i / n + 1,
+                i % n + 1
+            ));
+        }
+    }
+    warnings
+}
@@ -0,0 +1,247 @@
+use crate::models::{
+    LiveMatch, OutcomeProbabilities, PoissonStrengths, SimulationParams, TeamStrength,
+};
+use crate::simulation::match_sim::{expected_goals_from_delta, poisson_pmf, poisson_quantile};
+use rand::{Rng, RngExt};
+
+/// How a fixture turns into goals: the Poisson means of both sides.
+///
+/// Implementations see the teams of the fixture and their strength
+/// difference under the active
+/// [`RatingSystem`](crate::rating::RatingSystem), so a model may derive
+/// the goals from the ratings, from per-team coefficients, or from both.
+/// Goals are independent Poisson draws with those means.
+pub trait GoalModel {
+    /// Poisson means of the home and away goals when team `home` hosts
+    /// `away` (team ids). `strength_delta` is the home side's strength
+    /// minus the away side's, home advantage included, in ELO points.
+    fn expected_goals(&self, home: usize, away: usize, strength_delta: f64) -> (f64, f64);
+
+    /// Exact win/draw/loss probabilities of the fixture.
+    fn outcome_probabilities(
+        &self,
+        home: usize,
+        away: usize,
+        strength_delta: f64,
+    ) -> OutcomeProbabilities {
+        let (lambda_home, lambda_away) = self.expected_goals(home, away, strength_delta);
+        poisson_outcome_probabilities(lambda_home, lambda_away)
+    }
+
+    /// Draw a score: one uniform number for the home goals, then one for
+    /// the away goals, each through the Poisson quantile function.
+    fn sample_goals<R: Rng + RngExt>(
+        &self,
+        home: usize,
+        away: usize,
+        strength_delta: f64,
+        rng: &mut R,
+    ) -> (i32, i32) {
+        let (lambda_home, lambda_away) = self.expected_goals(home, away, strength_delta);
+        let random_home = rng.random::<f64>();
+        let random_away = rng.random::<f64>();
+        (
+            poisson_quantile(random_home, lambda_home) as i32,
+            poisson_quantile(random_away, lambda_away) as i32,
+        )
+    }
+
+    /// Final score of a match in progress: the goals still to come are
+    /// drawn with the means scaled to the minutes left.
+    fn finish_live<R: Rng + RngExt>(
+        &self,
+        live: &LiveMatch,
+        home: usize,
+        away: usize,
+        strength_delta: f64,
+        rng: &mut R,
+    ) -> (i32, i32) {
+        let (lambda_home, lambda_away) = self.expected_goals(home, away, strength_delta);
+        let remaining = f64::from(90 - live.minute.min(90)) / 90.0;
+        let more_home = poisson_quantile(rng.random::<f64>(), lambda_home * remaining) as i32;
+        let more_away = poisson_quantile(rng.random::<f64>(), lambda_away * remaining) as i32;
+        (live.goals_home + more_home, live.goals_away + more_away)
+    }
+}
+
+/// The goal model of SpielCPP.R: goals linear in the ELO difference,
+/// `tore_slope * delta + tore_intercept`, floored at 0.001.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearGoalModel {
+    pub tore_slope: f64,
+    pub tore_intercept: f64,
+}
+
+impl GoalModel for LinearGoalModel {
+    fn expected_goals(&self, _home: usize, _away: usize, strength_delta: f64) -> (f64, f64) {
+        expected_goals_from_delta(strength_delta, self.tore_slope, self.tore_intercept)
+    }
+}
+
+/// Goal means from per-team attack and defense strengths, blended with a
+/// [`LinearGoalModel`] on the ratings.
+///
+/// The strength side multiplies the home attack by the away defense
+/// relative to the league average, the usual multiplicative model for
+/// expected goals, and shifts the result by the home advantage as the
+/// linear model would at equal ratings. Strengths stay fixed over the
+/// season; the ratings still move with every result and keep feeding the
+/// Elo share of the blend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrengthGoalModel<'a> {
+    /// Attack and defense per team_id
+    pub strengths: &'a [TeamStrength],
+    /// Average attack and defense of `strengths`: the goals of an average
+    /// side against an average side
+    pub average: f64,
+    /// Share of the strengths in the means, 0 to 1
+    pub weight: f64,
+    pub home_advantage: f64,
+    pub linear: LinearGoalModel,
+}
+
+impl<'a> StrengthGoalModel<'a> {
+    pub fn new(
+        strengths: &'a [TeamStrength],
+        weight: f64,
+        home_advantage: f64,
+        linear: LinearGoalModel,
+    ) -> Self {
+        let total: f64 = strengths.iter().map(|s| s.attack + s.defense).sum();
+        Self {
+            strengths,
+            average: total / (2 * strengths.len()).max(1) as f64,
+            weight,
+            home_advantage,
+            linear,
+        }
+    }
+}
+
+impl GoalModel for StrengthGoalModel<'_> {
+    fn expected_goals(&self, home: usize, away: usize, strength_delta: f64) -> (f64, f64) {
+        let (elo_home, elo_away) = self.linear.expected_goals(home, away, strength_delta);
+        if self.weight == 0.0 {
+            return (elo_home, elo_away);
+        }
+        let (h, a) = (&self.strengths[home], &self.strengths[away]);
+        let shift = self.home_advantage * self.linear.tore_slope;
+        let xg_home = (h.attack * a.defense / self.average + shift).max(0.001);
+        let xg_away = (a.attack * h.defense / self.average - shift).max(0.001);
+        (
+            self.weight * xg_home + (1.0 - self.weight) * elo_home,
+            self.weight * xg_away + (1.0 - self.weight) * elo_away,
+        )
+    }
+}
+
+/// A [`LinearGoalModel`] on a strength difference blended from the
+/// ratings and fixed priors: `(1 - weight) * delta + weight * prior_delta`,
+/// where `prior_delta` is the home side's prior minus the away side's plus
+/// the home advantage.
+///
+/// Early in a season the ratings know little about this season's squads;
+/// priors such as market values (see [`priors_from_market_values`]) carry
+/// that information until the results do. Like the strengths of a
+/// [`StrengthGoalModel`], the priors stay fixed while the ratings move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriorGoalModel<'a> {
+    /// Prior per team_id, in ELO points
+    pub priors: &'a [f64],
+    /// Share of the priors in the strength difference, 0 to 1
+    pub weight: f64,
+    pub home_advantage: f64,
+    pub linear: LinearGoalModel,
+}
+
+impl GoalModel for PriorGoalModel<'_> {
+    fn expected_goals(&self, home: usize, away: usize, strength_delta: f64) -> (f64, f64) {
+        let prior_delta = self.priors[home] + self.home_advantage - self.priors[away];
+        let delta = (1.0 - self.weight) * strength_delta + self.weight * prior_delta;
+        self.linear.expected_goals(home, away, delta)
+    }
+}
+
+/// Strength priors from squad market values, in ELO points around 0:
+/// `elo_per_doubling * log2(value / geometric mean)`, so a squad worth
+/// twice as much as another is rated `elo_per_doubling` higher. Values
+/// must be positive; only their ratios matter.
+pub fn priors_from_market_values(values: &[f64], elo_per_doubling: f64) -> Vec<f64> {
+    let logs: Vec<f64> = values.iter().map(|v| v.log2()).collect();
+    let mean = logs.iter().sum::<f64>() / logs.len().max(1) as f64;
+    logs.iter().map(|l| elo_per_doubling * (l - mean)).collect()
+}
+
+impl GoalModel for PoissonStrengths {
+    fn expected_goals(&self, home: usize, away: usize, _strength_delta: f64) -> (f64, f64) {
+        (
+            (self.intercept + self.home_advantage + self.attack[home] + self.defense[away]).exp(),
+            (self.intercept + self.attack[away] + self.defense[home]).exp(),
+        )
+    }
+}
+
+impl SimulationParams {
+    /// Goal model of these parameters on the ratings alone.
+    pub fn goal_model(&self) -> LinearGoalModel {
+        LinearGoalModel {
+            tore_slope: self.tore_slope,
+            tore_intercept: self.tore_intercept,
+        }
+    }
+
+    /// Goal model blending `team_strengths` in, if set.
+    pub fn strength_goal_model(&self) -> Option<StrengthGoalModel<'_>> {
+        self.team_strengths.as_deref().map(|strengths| {
+            StrengthGoalModel::new(
+                strengths,
+                self.strength_weight,
+                self.home_advantage,
+                self.goal_model(),
+            )
+        })
+    }
+
+    /// Goal model blending `strength_priors` in, if set.
+    pub fn prior_goal_model(&self) -> Option<PriorGoalModel<'_>> {
+        self.strength_priors
+            .as_deref()
+            .map(|priors| PriorGoalModel {
+                priors,
+                weight: self.prior_weight,
+                home_advantage: self.home_advantage,
+                linear: self.goal_model(),
+            })
+    }
+}
+
+/// Win/draw/loss probabilities of independent Poisson goals with means
+/// `lambda_home` and `lambda_away`.
+pub fn poisson_outcome_probabilities(lambda_home: f64, lambda_away: f64) -> OutcomeProbabilities {
+    let pmf_home = poisson_pmf(lambda_home);
+    let pmf_away = poisson_pmf(lambda_away);
+
+    let mut home_win = 0.0;
+    let mut draw = 0.0;
+    let mut away_win = 0.0;
+    for (h, &p_h) in pmf_home.iter().enumerate() {
+        for (a, &p_a) in pmf_away.iter().enumerate() {
+            let p = p_h * p_a;
+            match h.cmp(&a) {
+                std::cmp::Ordering::Greater => home_win += p,
+                std::cmp::Ordering::Equal => draw += p,
+                std::cmp::Ordering::Less => away_win += p,
+            }
+        }
+    }
+
+    // Renormalise the truncated tails away.
+    let total = home_win + draw + away_win;
+    OutcomeProbabilities {
+        home_win: home_win / total,
+        draw: draw / total,
+        away_win: away_win / total,
+        expected_goals_home: lambda_home,
+        expected_goals_away: lambda_away,
+    }
+}
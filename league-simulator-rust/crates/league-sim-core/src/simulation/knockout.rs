@@ -0,0 +1,256 @@
+use super::match_sim::{expected_goals, poisson_quantile, simulate_match_random};
+use crate::models::SimulationParams;
+use rand::{Rng, RngExt};
+use serde::{Deserialize, Serialize};
+
+/// How a knockout match or tie was decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieDecision {
+    /// More goals in normal time, over both legs of a tie
+    RegularTime,
+    /// Level on aggregate, more goals away from home
+    AwayGoals,
+    /// Level after the second leg, more goals in extra time
+    ExtraTime,
+    /// Still level after extra time
+    Penalties,
+}
+
+/// How a two-legged tie level on aggregate is settled.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TwoLegRules {
+    /// More away goals win a tie level on aggregate, checked again after
+    /// extra time, whose goals count as away goals for the visitors
+    pub away_goals: bool,
+    /// Extra time before penalties; without it a level tie goes straight
+    /// to penalties
+    pub extra_time: bool,
+}
+
+impl Default for TwoLegRules {
+    /// Aggregate, then extra time and penalties, as in today's UEFA
+    /// competitions and the Bundesliga relegation playoff.
+    fn default() -> Self {
+        Self {
+            away_goals: false,
+            extra_time: true,
+        }
+    }
+}
+
+/// Winner of a two-legged tie from its scores, goals given as (first-leg
+/// host, second-leg host) for every part. `Some((first_host_wins, how))`,
+/// or None if the tie is still level, so extra time (when `extra_time` is
+/// None) or penalties have to follow.
+pub fn aggregate_two_legs(
+    first_leg: (i32, i32),
+    second_leg: (i32, i32),
+    extra_time: Option<(i32, i32)>,
+    away_goals: bool,
+) -> Option<(bool, TieDecision)> {
+    let extra = extra_time.unwrap_or((0, 0));
+    let aggregate = (
+        first_leg.0 + second_leg.0 + extra.0,
+        first_leg.1 + second_leg.1 + extra.1,
+    );
+    let decided_by = if extra_time.is_some() {
+        TieDecision::ExtraTime
+    } else {
+        TieDecision::RegularTime
+    };
+    if aggregate.0 != aggregate.1 {
+        return Some((aggregate.0 > aggregate.1, decided_by));
+    }
+    // The first host plays away in the second leg and its extra time.
+    let away = (second_leg.0 + extra.0, first_leg.1);
+    (away_goals && away.0 != away.1).then_some((away.0 > away.1, TieDecision::AwayGoals))
+}
+
+/// Outcome of [`simulate_two_legged_tie`]. Goals are given as
+/// (first-leg host, second-leg host) for both legs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TieResult {
+    pub first_leg: (i32, i32),
+    /// Second leg after 90 minutes
+    pub second_leg: (i32, i32),
+    /// Extra-time goals, if it was needed
+    pub extra_time: Option<(i32, i32)>,
+    pub first_host_wins: bool,
+    pub decided_by: TieDecision,
+    /// Ratings after the two legs; extra time and penalties do not count
+    pub elo_first_host: f64,
+    pub elo_second_host: f64,
+}
+
+/// Play a two-legged tie like the Bundesliga relegation playoff: one leg at
+/// each ground, more goals on aggregate wins (no away-goals rule). Level
+/// ties go to 30 minutes of extra time at the second host's ground, with
+/// the goal model scaled to a third of a match, then to penalties, a coin
+/// toss. Both legs update the ratings with `params`.
+pub fn simulate_two_legged_tie<R: Rng + RngExt>(
+    elo_first_host: f64,
+    elo_second_host: f64,
+    params: &SimulationParams,
+    rng: &mut R,
+) -> TieResult {
+    simulate_two_legged_tie_with(
+        elo_first_host,
+        elo_second_host,
+        params,
+        &TwoLegRules::default(),
+        rng,
+    )
+}
+
+/// [`simulate_two_legged_tie`] settled by `rules`: with the away-goals
+/// rule, or straight to penalties without extra time.
+pub fn simulate_two_legged_tie_with<R: Rng + RngExt>(
+    elo_first_host: f64,
+    elo_second_host: f64,
+    params: &SimulationParams,
+    rules: &TwoLegRules,
+    rng: &mut R,
+) -> TieResult {
+    let first = simulate_match_random(
+        elo_first_host,
+        elo_second_host,
+        params.mod_factor,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+        rng,
+    );
+    let second = simulate_match_random(
+        first.new_elo_away,
+        first.new_elo_home,
+        params.mod_factor,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+        rng,
+    );
+    let first_leg = (first.goals_home, first.goals_away);
+    let second_leg = (second.goals_away, second.goals_home);
+
+    let mut result = TieResult {
+        first_leg,
+        second_leg,
+        extra_time: None,
+        first_host_wins: false,
+        decided_by: TieDecision::Penalties,
+        elo_first_host: second.new_elo_away,
+        elo_second_host: second.new_elo_home,
+    };
+    let mut decided = aggregate_two_legs(first_leg, second_leg, None, rules.away_goals);
+    if decided.is_none() && rules.extra_time {
+        let (second_host, first_host) = extra_time_goals(
+            result.elo_second_host,
+            result.elo_first_host,
+            params.home_advantage,
+            params,
+            rng,
+        );
+        result.extra_time = Some((first_host, second_host));
+        decided = aggregate_two_legs(first_leg, second_leg, result.extra_time, rules.away_goals);
+    }
+    (result.first_host_wins, result.decided_by) =
+        decided.unwrap_or_else(|| (rng.random::<bool>(), TieDecision::Penalties));
+    result
+}
+
+/// Outcome of [`simulate_knockout_match`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnockoutResult {
+    /// Goals after 90 minutes
+    pub goals: (i32, i32),
+    /// Extra-time goals, if it was needed
+    pub extra_time: Option<(i32, i32)>,
+    pub home_wins: bool,
+    pub decided_by: TieDecision,
+    /// Ratings after the match; extra time and penalties do not count
+    pub elo_home: f64,
+    pub elo_away: f64,
+}
+
+/// Play a single knockout match like a DFB-Pokal round: level after 90
+/// minutes, extra time and penalties follow as in
+/// [`simulate_two_legged_tie`]. On `neutral` ground nobody gets the home
+/// advantage.
+pub fn simulate_knockout_match<R: Rng + RngExt>(
+    elo_home: f64,
+    elo_away: f64,
+    params: &SimulationParams,
+    neutral: bool,
+    rng: &mut R,
+) -> KnockoutResult {
+    let home_advantage = if neutral { 0.0 } else { params.home_advantage };
+    let played = simulate_match_random(
+        elo_home,
+        elo_away,
+        params.mod_factor,
+        home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+        rng,
+    );
+    let mut result = KnockoutResult {
+        goals: (played.goals_home, played.goals_away),
+        extra_time: None,
+        home_wins: played.goals_home > played.goals_away,
+        decided_by: TieDecision::RegularTime,
+        elo_home: played.new_elo_home,
+        elo_away: played.new_elo_away,
+    };
+    if played.goals_home != played.goals_away {
+        return result;
+    }
+
+    let (extra_time, home_wins, decided_by) =
+        extra_time_and_penalties(elo_home, elo_away, home_advantage, params, rng);
+    result.extra_time = Some(extra_time);
+    result.home_wins = home_wins;
+    result.decided_by = decided_by;
+    result
+}
+
+/// 30 minutes of extra time, the goal model scaled to a third of a match.
+fn extra_time_goals<R: Rng + RngExt>(
+    elo_home: f64,
+    elo_away: f64,
+    home_advantage: f64,
+    params: &SimulationParams,
+    rng: &mut R,
+) -> (i32, i32) {
+    let (lambda_home, lambda_away) = expected_goals(
+        elo_home,
+        elo_away,
+        home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    );
+    let mut goals = |lambda: f64| poisson_quantile(rng.random::<f64>(), lambda / 3.0) as i32;
+    (goals(lambda_home), goals(lambda_away))
+}
+
+/// Extra time, then penalties, a coin toss. Returns the extra-time goals,
+/// whether the home side won and how.
+fn extra_time_and_penalties<R: Rng + RngExt>(
+    elo_home: f64,
+    elo_away: f64,
+    home_advantage: f64,
+    params: &SimulationParams,
+    rng: &mut R,
+) -> ((i32, i32), bool, TieDecision) {
+    let extra_time = extra_time_goals(elo_home, elo_away, home_advantage, params, rng);
+    if extra_time.0 != extra_time.1 {
+        (
+            extra_time,
+            extra_time.0 > extra_time.1,
+            TieDecision::ExtraTime,
+        )
+    } else {
+        (extra_time, rng.random::<bool>(), TieDecision::Penalties)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::elo::calculate_elo_change;
-use crate::models::{EloParams, EloResult};
+use crate::models::{EloParams, EloResult, LiveMatch, OutcomeProbabilities};
+use crate::simulation::goal_model::{poisson_outcome_probabilities, GoalModel, LinearGoalModel};
 
 /// Simulates a match between two teams based on their ELO ratings
 /// Matches the logic in SpielCPP.R
@@ -13,12 +14,13 @@ pub fn simulate_match(
     random_home: f64,
     random_away: f64,
 ) -> EloResult {
-    // Calculate ELO delta
-    let elo_delta = elo_home + home_advantage - elo_away;
-
-    // Calculate average goals for each team
-    let tore_heim_durchschnitt = (elo_delta * tore_slope + tore_intercept).max(0.001);
-    let tore_gast_durchschnitt = ((-elo_delta) * tore_slope + tore_intercept).max(0.001);
+    let (tore_heim_durchschnitt, tore_gast_durchschnitt) = expected_goals(
+        elo_home,
+        elo_away,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+    );
 
     // Generate goals using Poisson distribution with quantile function
     let goals_home = poisson_quantile(random_home, tore_heim_durchschnitt) as i32;
@@ -37,6 +39,80 @@ pub fn simulate_match(
     calculate_elo_change(&params)
 }
 
+/// Average goals (Poisson lambdas) for home and away team.
+/// Matches the goal model in SpielCPP.R
+pub fn expected_goals(
+    elo_home: f64,
+    elo_away: f64,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+) -> (f64, f64) {
+    expected_goals_from_delta(
+        elo_home + home_advantage - elo_away,
+        tore_slope,
+        tore_intercept,
+    )
+}
+
+/// [`expected_goals`] from the strength difference alone, home advantage
+/// included, in ELO points; the means of [`LinearGoalModel`].
+pub fn expected_goals_from_delta(
+    elo_delta: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+) -> (f64, f64) {
+    // Calculate average goals for each team
+    let tore_heim_durchschnitt = (elo_delta * tore_slope + tore_intercept).max(0.001);
+    let tore_gast_durchschnitt = ((-elo_delta) * tore_slope + tore_intercept).max(0.001);
+
+    (tore_heim_durchschnitt, tore_gast_durchschnitt)
+}
+
+/// Exact win/draw/loss probabilities of a match under the same goal model
+/// that `simulate_match` samples from (independent Poisson goals).
+pub fn outcome_probabilities(
+    elo_home: f64,
+    elo_away: f64,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+) -> OutcomeProbabilities {
+    let (lambda_home, lambda_away) = expected_goals(
+        elo_home,
+        elo_away,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+    );
+    poisson_outcome_probabilities(lambda_home, lambda_away)
+}
+
+/// Poisson probabilities P(X = 0), P(X = 1), ... truncated once the
+/// remaining tail mass is below 1e-12.
+pub fn poisson_pmf(lambda: f64) -> Vec<f64> {
+    let mut pmf = Vec::with_capacity(16);
+    poisson_pmf_into(lambda, &mut pmf);
+    pmf
+}
+
+/// [`poisson_pmf`] written into `pmf`, reusing its allocation.
+pub fn poisson_pmf_into(lambda: f64, pmf: &mut Vec<f64>) {
+    pmf.clear();
+    let mut prob = (-lambda).exp();
+    let mut cumulative = 0.0;
+    let mut k = 0u32;
+    loop {
+        pmf.push(prob);
+        cumulative += prob;
+        if 1.0 - cumulative < 1e-12 || k >= 1000 {
+            break;
+        }
+        k += 1;
+        prob *= lambda / k as f64;
+    }
+}
+
 /// Simulates a match with actual random number generation
 pub fn simulate_match_random<R: rand::Rng + rand::RngExt>(
     elo_home: f64,
@@ -62,12 +138,41 @@ pub fn simulate_match_random<R: rand::Rng + rand::RngExt>(
     )
 }
 
+/// Final score of a match in progress: the goals still to come are drawn
+/// from the goal model scaled to the minutes left.
+pub fn finish_live_match<R: rand::Rng + rand::RngExt>(
+    live: &LiveMatch,
+    elo_home: f64,
+    elo_away: f64,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    rng: &mut R,
+) -> (i32, i32) {
+    // The linear model only looks at the strength difference, not the ids.
+    LinearGoalModel {
+        tore_slope,
+        tore_intercept,
+    }
+    .finish_live(live, 0, 1, elo_home + home_advantage - elo_away, rng)
+}
+
 /// Calculate the quantile of a Poisson distribution.
 /// Matches R's qpois: smallest integer k with P(X <= k) >= p.
-fn poisson_quantile(p: f64, lambda: f64) -> f64 {
+pub(crate) fn poisson_quantile(p: f64, lambda: f64) -> f64 {
     // Production lambdas are ~0.6-2.5 (ELO-derived goal averages), so the
     // O(k) direct summation terminates after a handful of multiplications
     // instead of ~5 regularized-gamma CDF evaluations per draw.
+    //
+    // Per-match CDF tables built once per request would not be exact: lambda
+    // depends on the Elo ratings at kick-off, and those move with every
+    // simulated result earlier in the same iteration. Building a table per
+    // draw costs the same as the summation below, so there is nothing to cache.
+    if !lambda.is_finite() {
+        // No usable mean, e.g. from overflowing strengths: score nothing
+        // rather than abort the whole simulation.
+        return 0.0;
+    }
     if lambda < 10.0 {
         poisson_quantile_direct(p, lambda)
     } else {
@@ -105,7 +210,9 @@ pub fn poisson_quantile_statrs(p: f64, lambda: f64) -> f64 {
         return f64::INFINITY;
     }
 
-    let poisson = StatrsPoisson::new(lambda).unwrap();
+    let Ok(poisson) = StatrsPoisson::new(lambda) else {
+        return 0.0;
+    };
 
     // Binary search for the quantile
     let mut low = 0;
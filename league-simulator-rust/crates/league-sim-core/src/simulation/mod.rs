@@ -0,0 +1,20 @@
+pub mod fixtures;
+pub mod goal_model;
+pub mod knockout;
+pub mod match_sim;
+pub mod season;
+pub mod simd;
+pub mod streams;
+pub mod tiebreak;
+
+pub use fixtures::*;
+pub use goal_model::*;
+pub use knockout::*;
+pub use match_sim::*;
+pub use season::*;
+pub use simd::*;
+pub use streams::*;
+pub use tiebreak::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,432 @@
+use crate::elo::apply_weighted_results;
+use crate::models::{
+    LeagueTable, LiveMatch, Match, RatingSystemKind, Season, SimulationParams, TeamStanding,
+};
+use crate::rating::{
+    apply_rated_results, match_weight, update_pair, Elo, GlickoRating, RatingSystem,
+};
+use crate::simulation::goal_model::{GoalModel, LinearGoalModel};
+use crate::simulation::tiebreak::Tiebreakers;
+use rand::{Rng, RngExt};
+
+/// In-place variant: operates on caller-owned buffers so Monte Carlo
+/// iterations can reuse allocations instead of cloning per iteration.
+/// Matches the logic in SaisonSimulierenCPP.R. `mod_factor_weights`, one
+/// per entry of `matches`, scale `mod_factor` match by match (see
+/// [`weighted_mod_factor`](crate::elo::weighted_mod_factor)).
+pub fn simulate_season_in_place<R: Rng + RngExt>(
+    matches: &mut [Match],
+    elos: &mut [f64],
+    mod_factor: f64,
+    mod_factor_weights: Option<&[f64]>,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    rng: &mut R,
+) {
+    simulate_season_recording(
+        matches,
+        elos,
+        mod_factor,
+        mod_factor_weights,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+        rng,
+        |_, _| {},
+    );
+}
+
+/// [`simulate_season_in_place`] that calls `after_match` with the index of
+/// each match in `matches` and the ratings after it, for Elo trajectories.
+pub fn simulate_season_recording<R: Rng + RngExt>(
+    matches: &mut [Match],
+    elos: &mut [f64],
+    mod_factor: f64,
+    mod_factor_weights: Option<&[f64]>,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    rng: &mut R,
+    after_match: impl FnMut(usize, &[f64]),
+) {
+    simulate_season_rated(
+        &Elo { mod_factor },
+        &LinearGoalModel {
+            tore_slope,
+            tore_intercept,
+        },
+        matches,
+        elos,
+        mod_factor_weights,
+        home_advantage,
+        rng,
+        after_match,
+    );
+}
+
+/// [`simulate_season_recording`] under any [`RatingSystem`] and
+/// [`GoalModel`]: unplayed matches are drawn from `goal_model` at the
+/// strength difference of the current ratings, and every result, played or
+/// simulated, updates the ratings in schedule order. `weights`, one per
+/// entry of `matches`, scale each update.
+pub fn simulate_season_rated<S: RatingSystem, G: GoalModel, R: Rng + RngExt>(
+    system: &S,
+    goal_model: &G,
+    matches: &mut [Match],
+    ratings: &mut [S::Rating],
+    weights: Option<&[f64]>,
+    home_advantage: f64,
+    rng: &mut R,
+    mut after_match: impl FnMut(usize, &[S::Rating]),
+) {
+    for (index, match_data) in matches.iter_mut().enumerate() {
+        let (home, away) = (match_data.team_home, match_data.team_away);
+        let (goals_home, goals_away) = match (match_data.goals_home, match_data.goals_away) {
+            (Some(goals_home), Some(goals_away)) => (goals_home, goals_away),
+            _ => {
+                let strength_delta = system.strength(&ratings[home]) + home_advantage
+                    - system.strength(&ratings[away]);
+                let (goals_home, goals_away) =
+                    goal_model.sample_goals(home, away, strength_delta, rng);
+                match_data.goals_home = Some(goals_home);
+                match_data.goals_away = Some(goals_away);
+                (goals_home, goals_away)
+            }
+        };
+        update_pair(
+            system,
+            ratings,
+            match_data,
+            goals_home,
+            goals_away,
+            home_advantage,
+            match_weight(weights, index),
+        );
+        after_match(index, ratings);
+    }
+}
+
+/// Simulates a complete season, updating ELO values as matches are played
+/// Matches the logic in SaisonSimulierenCPP.R
+pub fn simulate_season<R: Rng + RngExt>(
+    season: &Season,
+    mod_factor: f64,
+    mod_factor_weights: Option<&[f64]>,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    rng: &mut R,
+) -> (Vec<Match>, Vec<f64>) {
+    let mut matches = season.matches.clone();
+    let mut elos = season.team_elos.clone();
+
+    simulate_season_in_place(
+        &mut matches,
+        &mut elos,
+        mod_factor,
+        mod_factor_weights,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+        rng,
+    );
+
+    (matches, elos)
+}
+
+/// [`simulate_season`] that also returns the ratings after every match,
+/// one row per entry of `season.matches`.
+pub fn simulate_season_with_trajectory<R: Rng + RngExt>(
+    season: &Season,
+    mod_factor: f64,
+    mod_factor_weights: Option<&[f64]>,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    rng: &mut R,
+) -> (Vec<Match>, Vec<f64>, Vec<Vec<f64>>) {
+    let mut matches = season.matches.clone();
+    let mut elos = season.team_elos.clone();
+    let mut trajectory = Vec::with_capacity(matches.len());
+
+    simulate_season_recording(
+        &mut matches,
+        &mut elos,
+        mod_factor,
+        mod_factor_weights,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+        rng,
+        |_, elos| trajectory.push(elos.to_vec()),
+    );
+
+    (matches, elos, trajectory)
+}
+
+/// Calculate league table from match results
+/// Matches the logic in Tabelle.R
+pub fn calculate_table(
+    matches: &[Match],
+    number_teams: usize,
+    adj_points: Option<&[i32]>,
+    adj_goals: Option<&[i32]>,
+    adj_goals_against: Option<&[i32]>,
+    adj_goal_diff: Option<&[i32]>,
+) -> LeagueTable {
+    let mut standings = initial_standings(
+        number_teams,
+        adj_points,
+        adj_goals,
+        adj_goals_against,
+        adj_goal_diff,
+    );
+    add_match_results(&mut standings, matches);
+    sort_standings(&mut standings);
+    LeagueTable { standings }
+}
+
+/// [`calculate_table`] with the adjustments and tiebreaker chain of
+/// `params`. The table is deterministic, so teams a drawing of lots would
+/// separate keep team_id order.
+pub fn calculate_table_with(
+    matches: &[Match],
+    number_teams: usize,
+    params: &SimulationParams,
+) -> LeagueTable {
+    let mut standings = params.baseline_standings(number_teams);
+    add_match_results(&mut standings, matches);
+    Tiebreakers::new(params).sort(&mut standings, &[matches], || 0);
+    LeagueTable { standings }
+}
+
+/// Empty table rows in team_id order, seeded with the adjustments.
+pub fn initial_standings(
+    number_teams: usize,
+    adj_points: Option<&[i32]>,
+    adj_goals: Option<&[i32]>,
+    adj_goals_against: Option<&[i32]>,
+    adj_goal_diff: Option<&[i32]>,
+) -> Vec<TeamStanding> {
+    (0..number_teams)
+        .map(|i| TeamStanding {
+            team_id: i,
+            played: 0,
+            won: 0,
+            drawn: 0,
+            lost: 0,
+            goals_for: adj_goals.map(|a| a[i]).unwrap_or(0),
+            goals_against: adj_goals_against.map(|a| a[i]).unwrap_or(0),
+            goal_difference: adj_goal_diff.map(|a| a[i]).unwrap_or(0),
+            points: adj_points.map(|a| a[i]).unwrap_or(0),
+            position: 0,
+        })
+        .collect()
+}
+
+/// Add the results of all played matches to `standings`, which must be
+/// indexed by team_id (as returned by `initial_standings`).
+pub fn add_match_results(standings: &mut [TeamStanding], matches: &[Match]) {
+    for match_data in matches {
+        if let (Some(goals_home), Some(goals_away)) = (match_data.goals_home, match_data.goals_away)
+        {
+            let home_idx = match_data.team_home;
+            let away_idx = match_data.team_away;
+
+            // Update games played
+            standings[home_idx].played += 1;
+            standings[away_idx].played += 1;
+
+            // Update goals
+            standings[home_idx].goals_for += goals_home;
+            standings[home_idx].goals_against += goals_away;
+            standings[away_idx].goals_for += goals_away;
+            standings[away_idx].goals_against += goals_home;
+
+            // Update goal difference
+            standings[home_idx].goal_difference += goals_home - goals_away;
+            standings[away_idx].goal_difference += goals_away - goals_home;
+
+            // Update points and W/D/L
+            if goals_home > goals_away {
+                standings[home_idx].won += 1;
+                standings[home_idx].points += 3;
+                standings[away_idx].lost += 1;
+            } else if goals_home < goals_away {
+                standings[away_idx].won += 1;
+                standings[away_idx].points += 3;
+                standings[home_idx].lost += 1;
+            } else {
+                standings[home_idx].drawn += 1;
+                standings[home_idx].points += 1;
+                standings[away_idx].drawn += 1;
+                standings[away_idx].points += 1;
+            }
+        }
+    }
+}
+
+/// Sort rows into final order by points, goal difference and goals scored,
+/// and assign positions.
+pub fn sort_standings(standings: &mut [TeamStanding]) {
+    Tiebreakers::default().sort(standings, &[], || 0);
+}
+
+/// Work shared by every Monte Carlo iteration, done once per request.
+///
+/// Played matches before the first unplayed one always produce the same Elo
+/// updates, so they are replayed once here. The table contribution of every
+/// played match (and the adjustments) is order-independent, so it is summed
+/// once into `baseline`. Per iteration only `tail` has to be simulated:
+/// played matches inside the tail still update Elo in schedule order, but
+/// their results are already in the baseline.
+#[derive(Debug, Clone)]
+pub struct PreparedSeason {
+    /// Ratings after the played prefix of the schedule
+    pub elos: Vec<f64>,
+    /// Schedule from the first unplayed match onwards
+    pub tail: Vec<Match>,
+    /// Table rows in team_id order: adjustments plus all played results
+    pub baseline: Vec<TeamStanding>,
+    /// Matches in progress, `index` relative to `tail`
+    pub live: Vec<LiveMatch>,
+    /// Schedule before `tail`, kept only when the tiebreakers compare
+    /// head-to-head results
+    pub prefix: Vec<Match>,
+    /// `SimulationParams::schedule_weights` of the `tail` matches
+    pub tail_weights: Option<Vec<f64>>,
+    /// Glicko-2 ratings after the played prefix, with
+    /// `RatingSystemKind::Glicko2`; `elos` then holds their strengths
+    pub glicko: Option<Vec<GlickoRating>>,
+}
+
+impl PreparedSeason {
+    pub fn new(season: &Season, params: &SimulationParams) -> Self {
+        let split = season
+            .matches
+            .iter()
+            .position(|m| m.goals_home.is_none())
+            .unwrap_or(season.matches.len());
+        let (prefix, tail) = season.matches.split_at(split);
+
+        let weights = params.schedule_weights(&season.matches);
+        let weights = weights.as_deref();
+        let (elos, glicko) = match params.rating_system {
+            RatingSystemKind::Elo => (
+                apply_weighted_results(
+                    &season.team_elos,
+                    prefix,
+                    params.mod_factor,
+                    weights,
+                    params.home_advantage,
+                ),
+                None,
+            ),
+            RatingSystemKind::Glicko2 => {
+                let system = params.glicko.system();
+                let ratings = apply_rated_results(
+                    &system,
+                    &params.glicko.starting_ratings(&season.team_elos),
+                    prefix,
+                    weights,
+                    params.home_advantage,
+                );
+                let elos = ratings.iter().map(|r| system.strength(r)).collect();
+                (elos, Some(ratings))
+            }
+        };
+
+        let mut baseline = params.baseline_standings(season.number_teams);
+        add_match_results(&mut baseline, &season.matches);
+
+        let prefix = if Tiebreakers::new(params).needs_matches() {
+            prefix.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            elos,
+            tail: tail.to_vec(),
+            baseline,
+            live: Vec::new(),
+            prefix,
+            tail_weights: weights.map(|w| w.get(split..).unwrap_or_default().to_vec()),
+            glicko,
+        }
+    }
+
+    /// Mark unplayed matches as in progress. Each iteration completes them
+    /// from their current score (see `finish_live_match`) before simulating
+    /// the rest of the tail. Entries for played matches are ignored.
+    ///
+    /// The goals still to come are drawn with the ratings after the played
+    /// prefix rather than the simulated ratings at kick-off; live matches
+    /// are on the current matchday, so the two rarely differ.
+    pub fn with_live(mut self, season: &Season, live: &[LiveMatch]) -> Self {
+        let offset = season.matches.len() - self.tail.len();
+        self.live = live
+            .iter()
+            .filter_map(|m| {
+                let index = m.index.checked_sub(offset)?;
+                self.tail
+                    .get(index)
+                    .filter(|t| t.goals_home.is_none())
+                    .map(|_| LiveMatch { index, ..*m })
+            })
+            .collect();
+        self
+    }
+
+    /// Add the simulated results of `simulated_tail` (the tail after
+    /// `simulate_season_in_place`) to a copy of the baseline. Tail matches
+    /// that were already played are skipped, they are in the baseline.
+    pub fn finish_table(&self, simulated_tail: &[Match], standings: &mut Vec<TeamStanding>) {
+        standings.clear();
+        standings.extend_from_slice(&self.baseline);
+        for (original, simulated) in self.tail.iter().zip(simulated_tail) {
+            if original.goals_home.is_none() {
+                add_match_results(standings, std::slice::from_ref(simulated));
+            }
+        }
+    }
+}
+
+/// Process a season with played and unplayed matches
+/// Returns the final table after simulating remaining matches
+pub fn process_season<R: Rng + RngExt>(
+    season: &Season,
+    mod_factor: f64,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    adj_points: Option<&[i32]>,
+    adj_goals: Option<&[i32]>,
+    adj_goals_against: Option<&[i32]>,
+    adj_goal_diff: Option<&[i32]>,
+    rng: &mut R,
+) -> (LeagueTable, Vec<f64>) {
+    // Simulate the season
+    let (completed_matches, final_elos) = simulate_season(
+        season,
+        mod_factor,
+        None,
+        home_advantage,
+        tore_slope,
+        tore_intercept,
+        rng,
+    );
+
+    // Calculate the table
+    let table = calculate_table(
+        &completed_matches,
+        season.number_teams,
+        adj_points,
+        adj_goals,
+        adj_goals_against,
+        adj_goal_diff,
+    );
+
+    (table, final_elos)
+}
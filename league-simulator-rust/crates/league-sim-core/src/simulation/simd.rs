@@ -0,0 +1,131 @@
+use crate::simulation::match_sim::poisson_quantile;
+use std::sync::OnceLock;
+
+/// Iterations simulated side by side by the lane functions: one AVX2
+/// register of f64.
+pub const LANES: usize = 4;
+
+/// [`expected_goals_from_delta`](super::expected_goals_from_delta) of
+/// [`LANES`] strength differences at once: the home and away means.
+pub fn expected_goals_lanes(
+    elo_delta: [f64; LANES],
+    tore_slope: f64,
+    tore_intercept: f64,
+) -> ([f64; LANES], [f64; LANES]) {
+    let home = elo_delta.map(|delta| (delta * tore_slope + tore_intercept).max(0.001));
+    let away = elo_delta.map(|delta| ((-delta) * tore_slope + tore_intercept).max(0.001));
+    (home, away)
+}
+
+/// [`poisson_quantile`] of [`LANES`] draws at once, bit for bit.
+///
+/// Lanes in the direct-summation range (`lambda < 10`, `0 < p < 1`) run the
+/// CDF summation together, with AVX2 when the CPU has it (detected at run
+/// time) and a plain loop over the lanes otherwise. Every other lane falls
+/// back to the scalar function. `exp` has no vector form in std, so the
+/// starting terms are computed per lane; the summation only multiplies,
+/// divides and adds, which give the same results in either path.
+pub fn poisson_quantile_lanes(p: [f64; LANES], lambda: [f64; LANES]) -> [f64; LANES] {
+    let direct: [bool; LANES] =
+        std::array::from_fn(|lane| lambda[lane] < 10.0 && p[lane] > 0.0 && p[lane] < 1.0);
+    // Lanes left to the scalar path stop the summation at once: their
+    // target is 0, which the first cumulative term already reaches.
+    let target: [f64; LANES] = std::array::from_fn(|lane| if direct[lane] { p[lane] } else { 0.0 });
+    let start = lambda.map(|lambda| (-lambda).exp());
+
+    let k = sum_lanes(target, lambda, start);
+    std::array::from_fn(|lane| {
+        if direct[lane] {
+            k[lane]
+        } else {
+            poisson_quantile(p[lane], lambda[lane])
+        }
+    })
+}
+
+/// A `sum_lanes` implementation.
+type SumLanes = fn([f64; LANES], [f64; LANES], [f64; LANES]) -> [f64; LANES];
+
+/// Smallest k per lane whose cumulative probability reaches `target`,
+/// capped at 1000 like `poisson_quantile_direct`. The implementation is
+/// chosen on the first call, not per match.
+pub(super) fn sum_lanes(
+    target: [f64; LANES],
+    lambda: [f64; LANES],
+    start: [f64; LANES],
+) -> [f64; LANES] {
+    static IMPLEMENTATION: OnceLock<SumLanes> = OnceLock::new();
+    IMPLEMENTATION.get_or_init(detect_sum_lanes)(target, lambda, start)
+}
+
+/// AVX2 summation if the CPU has it, else the portable loop.
+fn detect_sum_lanes() -> SumLanes {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2, checked just above.
+        return |target, lambda, start| unsafe { avx2::sum_lanes(target, lambda, start) };
+    }
+    sum_lanes_portable
+}
+
+pub(super) fn sum_lanes_portable(
+    target: [f64; LANES],
+    lambda: [f64; LANES],
+    start: [f64; LANES],
+) -> [f64; LANES] {
+    let mut k = [0.0; LANES];
+    let mut prob = start;
+    let mut cumulative = start;
+    loop {
+        let active: [bool; LANES] =
+            std::array::from_fn(|lane| cumulative[lane] < target[lane] && k[lane] < 1000.0);
+        if !active.contains(&true) {
+            return k;
+        }
+        for lane in 0..LANES {
+            if active[lane] {
+                k[lane] += 1.0;
+                prob[lane] *= lambda[lane] / k[lane];
+                cumulative[lane] += prob[lane];
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::LANES;
+    use std::arch::x86_64::*;
+
+    /// `sum_lanes_portable` on one 256-bit register per quantity.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn sum_lanes(
+        target: [f64; LANES],
+        lambda: [f64; LANES],
+        start: [f64; LANES],
+    ) -> [f64; LANES] {
+        let target = _mm256_loadu_pd(target.as_ptr());
+        let lambda = _mm256_loadu_pd(lambda.as_ptr());
+        let one = _mm256_set1_pd(1.0);
+        let cap = _mm256_set1_pd(1000.0);
+        let mut k = _mm256_setzero_pd();
+        let mut prob = _mm256_loadu_pd(start.as_ptr());
+        let mut cumulative = prob;
+        loop {
+            let active = _mm256_and_pd(
+                _mm256_cmp_pd::<_CMP_LT_OQ>(cumulative, target),
+                _mm256_cmp_pd::<_CMP_LT_OQ>(k, cap),
+            );
+            if _mm256_movemask_pd(active) == 0 {
+                break;
+            }
+            k = _mm256_add_pd(k, _mm256_and_pd(active, one));
+            let next = _mm256_mul_pd(prob, _mm256_div_pd(lambda, k));
+            prob = _mm256_blendv_pd(prob, next, active);
+            cumulative = _mm256_blendv_pd(cumulative, _mm256_add_pd(cumulative, next), active);
+        }
+        let mut out = [0.0; LANES];
+        _mm256_storeu_pd(out.as_mut_ptr(), k);
+        out
+    }
+}
@@ -0,0 +1,38 @@
+use rand::RngExt;
+
+/// Weyl increment of SplitMix64: 2^64 divided by the golden ratio, odd.
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Output function of SplitMix64 (Steele, Lea and Flood, 2014): a
+/// bijection on u64 in which every input bit affects every output bit.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Seed of the random stream of iteration `iteration` of a run keyed on
+/// `master_seed`.
+///
+/// This is output `iteration` of a SplitMix64 generator started from a
+/// hash of the master seed, computed directly from the counter: an
+/// iteration's stream does not depend on which iterations ran before it,
+/// on which thread, or in which batch, and neighbouring iterations or
+/// master seeds give unrelated seeds. Each seed then seeds the iteration's
+/// generator of `SimulationParams::rng`.
+pub fn stream_seed(master_seed: u64, iteration: u64) -> u64 {
+    let counter = iteration.wrapping_add(1).wrapping_mul(GAMMA);
+    mix(mix(master_seed).wrapping_add(counter))
+}
+
+/// [`stream_seed`] of iterations `first..first + count`.
+pub fn stream_seeds(master_seed: u64, first: u64, count: usize) -> Vec<u64> {
+    (first..first + count as u64)
+        .map(|iteration| stream_seed(master_seed, iteration))
+        .collect()
+}
+
+/// Master seed drawn from OS entropy, for runs that need not repeat.
+pub fn entropy_seed() -> u64 {
+    rand::rng().random()
+}
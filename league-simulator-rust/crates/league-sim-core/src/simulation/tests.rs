@@ -0,0 +1,1216 @@
+use super::*;
+use crate::models::{Match, Season, SimulationParams, TiebreakerRule};
+use approx::assert_relative_eq;
+use serde_json;
+use std::fs;
+
+#[derive(serde::Deserialize)]
+struct MatchSimTestCase {
+    name: String,
+    input: MatchSimInput,
+    expected: Vec<f64>,
+}
+
+#[derive(serde::Deserialize)]
+struct MatchSimInput {
+    elo_home: f64,
+    elo_away: f64,
+    mod_factor: f64,
+    home_advantage: f64,
+    tore_slope: f64,
+    tore_intercept: f64,
+    random_home: f64,
+    random_away: f64,
+}
+
+#[derive(serde::Deserialize)]
+struct MatchSimTestData {
+    test_cases: Vec<MatchSimTestCase>,
+}
+
+#[test]
+fn test_match_simulation_matches_r() {
+    let data = fs::read_to_string("test_data/match_simulation_cases.json")
+        .expect("Failed to read match simulation test data");
+    let test_data: MatchSimTestData =
+        serde_json::from_str(&data).expect("Failed to parse match simulation test data");
+
+    for test_case in test_data.test_cases {
+        println!("Testing match simulation: {}", test_case.name);
+
+        let result = simulate_match(
+            test_case.input.elo_home,
+            test_case.input.elo_away,
+            test_case.input.mod_factor,
+            test_case.input.home_advantage,
+            test_case.input.tore_slope,
+            test_case.input.tore_intercept,
+            test_case.input.random_home,
+            test_case.input.random_away,
+        );
+
+        // Check ELO changes
+        assert_relative_eq!(
+            result.new_elo_home,
+            test_case.expected[0],
+            epsilon = 0.001,
+            max_relative = 0.001
+        );
+
+        assert_relative_eq!(
+            result.new_elo_away,
+            test_case.expected[1],
+            epsilon = 0.001,
+            max_relative = 0.001
+        );
+
+        // Check goals
+        assert_eq!(
+            result.goals_home, test_case.expected[2] as i32,
+            "{}: Goals home mismatch",
+            test_case.name
+        );
+
+        assert_eq!(
+            result.goals_away, test_case.expected[3] as i32,
+            "{}: Goals away mismatch",
+            test_case.name
+        );
+    }
+}
+
+#[test]
+fn test_season_simulation() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Create a simple season with 3 teams
+    let season = Season {
+        matches: vec![
+            Match {
+                team_home: 0,
+                team_away: 1,
+                goals_home: Some(2),
+                goals_away: Some(1),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 1,
+                team_away: 2,
+                goals_home: Some(1),
+                goals_away: Some(1),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 2,
+                team_away: 0,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            }, // To simulate
+            Match {
+                team_home: 0,
+                team_away: 2,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            }, // To simulate
+            Match {
+                team_home: 1,
+                team_away: 0,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            }, // To simulate
+            Match {
+                team_home: 2,
+                team_away: 1,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            }, // To simulate
+        ],
+        team_elos: vec![1500.0, 1600.0, 1400.0],
+        number_teams: 3,
+    };
+
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let (completed_matches, final_elos) = simulate_season(
+        &season,
+        20.0,               // mod_factor
+        None,               // mod_factor_weights
+        65.0,               // home_advantage
+        0.0017854953143549, // tore_slope
+        1.3218390804597700, // tore_intercept
+        &mut rng,
+    );
+
+    // Check that all matches have results
+    for match_data in &completed_matches {
+        assert!(
+            match_data.goals_home.is_some(),
+            "Match should have home goals"
+        );
+        assert!(
+            match_data.goals_away.is_some(),
+            "Match should have away goals"
+        );
+    }
+
+    // Check that ELO values have been updated
+    assert_eq!(final_elos.len(), 3, "Should have 3 team ELOs");
+
+    // ELOs should have changed from initial values
+    assert_ne!(final_elos[0], 1500.0, "Team 0 ELO should have changed");
+    assert_ne!(final_elos[1], 1600.0, "Team 1 ELO should have changed");
+    assert_ne!(final_elos[2], 1400.0, "Team 2 ELO should have changed");
+}
+
+#[test]
+fn test_table_calculation() {
+    let matches = vec![
+        Match {
+            team_home: 0,
+            team_away: 1,
+            goals_home: Some(2),
+            goals_away: Some(1),
+            matchday: None,
+            competition: None,
+            weight: None,
+        },
+        Match {
+            team_home: 1,
+            team_away: 2,
+            goals_home: Some(3),
+            goals_away: Some(1),
+            matchday: None,
+            competition: None,
+            weight: None,
+        },
+        Match {
+            team_home: 2,
+            team_away: 0,
+            goals_home: Some(0),
+            goals_away: Some(0),
+            matchday: None,
+            competition: None,
+            weight: None,
+        },
+    ];
+
+    let table = calculate_table(&matches, 3, None, None, None, None);
+
+    // Check standings
+    assert_eq!(table.standings.len(), 3, "Should have 3 teams");
+
+    // Team 0: W1 D1 L0, 4 points
+    let team0 = table.standings.iter().find(|s| s.team_id == 0).unwrap();
+    assert_eq!(team0.won, 1, "Team 0 wins");
+    assert_eq!(team0.drawn, 1, "Team 0 draws");
+    assert_eq!(team0.lost, 0, "Team 0 losses");
+    assert_eq!(team0.points, 4, "Team 0 points");
+    assert_eq!(team0.goals_for, 2, "Team 0 goals for");
+    assert_eq!(team0.goals_against, 1, "Team 0 goals against");
+
+    // Team 1: W1 D0 L1, 3 points
+    let team1 = table.standings.iter().find(|s| s.team_id == 1).unwrap();
+    assert_eq!(team1.won, 1, "Team 1 wins");
+    assert_eq!(team1.drawn, 0, "Team 1 draws");
+    assert_eq!(team1.lost, 1, "Team 1 losses");
+    assert_eq!(team1.points, 3, "Team 1 points");
+
+    // Team 2: W0 D1 L1, 1 point
+    let team2 = table.standings.iter().find(|s| s.team_id == 2).unwrap();
+    assert_eq!(team2.won, 0, "Team 2 wins");
+    assert_eq!(team2.drawn, 1, "Team 2 draws");
+    assert_eq!(team2.lost, 1, "Team 2 losses");
+    assert_eq!(team2.points, 1, "Team 2 points");
+
+    // Check positions (Team 0 should be first)
+    assert_eq!(table.standings[0].team_id, 0, "Team 0 should be first");
+    assert_eq!(table.standings[0].position, 1, "First position should be 1");
+}
+
+#[test]
+fn test_table_with_adjustments() {
+    let matches = vec![Match {
+        team_home: 0,
+        team_away: 1,
+        goals_home: Some(1),
+        goals_away: Some(1),
+        matchday: None,
+        competition: None,
+        weight: None,
+    }];
+
+    let adj_points = vec![-50, 0, 0]; // Penalize team 0
+    let table = calculate_table(&matches, 3, Some(&adj_points), None, None, None);
+
+    // Team 0 should have 1 - 50 = -49 points
+    let team0 = table.standings.iter().find(|s| s.team_id == 0).unwrap();
+    assert_eq!(team0.points, -49, "Team 0 should be penalized");
+
+    // Team 0 should be last despite drawing
+    assert_eq!(table.standings[2].team_id, 0, "Team 0 should be last");
+}
+
+#[test]
+fn tiebreaker_chains_reorder_teams_level_on_points() {
+    use TiebreakerRule::*;
+    let game = |home, away, goals_home, goals_away| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: Some(goals_home),
+        goals_away: Some(goals_away),
+        matchday: None,
+        competition: None,
+        weight: None,
+    };
+    // Teams 0 and 1 finish on 4 points; 0 has the better goal difference,
+    // 1 won the match between them away from home.
+    let matches = vec![
+        game(0, 1, 0, 1),
+        game(0, 2, 5, 0),
+        game(1, 2, 0, 0),
+        game(2, 0, 0, 0),
+    ];
+    let order = |params: &SimulationParams| -> Vec<usize> {
+        calculate_table_with(&matches, 3, params)
+            .standings
+            .iter()
+            .map(|s| s.team_id)
+            .collect()
+    };
+    let with = |rules: &[TiebreakerRule]| SimulationParams {
+        tiebreakers: rules.to_vec(),
+        fair_play: Some(vec![5, 2, 0]),
+        ..Default::default()
+    };
+
+    assert_eq!(order(&SimulationParams::default()), [0, 1, 2]);
+    assert_eq!(
+        order(&with(&[Points, HeadToHeadPoints, GoalDifference])),
+        [1, 0, 2]
+    );
+    assert_eq!(order(&with(&[Points, HeadToHeadAwayGoals])), [1, 0, 2]);
+    assert_eq!(order(&with(&[Points, FairPlay])), [1, 0, 2]);
+    // A deterministic table leaves the lots undrawn.
+    assert_eq!(order(&with(&[Points, DrawingOfLots])), [0, 1, 2]);
+
+    let params = with(&[Points, DrawingOfLots]);
+    let mut standings = initial_standings(3, None, None, None, None);
+    add_match_results(&mut standings, &matches);
+    let mut lot = 0;
+    Tiebreakers::new(&params).sort(&mut standings, &[&matches], || {
+        lot += 1;
+        lot
+    });
+    let drawn: Vec<usize> = standings.iter().map(|s| s.team_id).collect();
+    assert_eq!(drawn, [1, 0, 2], "the later, higher lot ranks first");
+    assert_eq!(lot, 2, "lots are drawn only for the tied teams");
+}
+
+#[test]
+fn table_baseline_carries_games_played() {
+    use crate::models::TableEntry;
+    let mut params = SimulationParams::default();
+    params.apply_table_baseline(&[
+        TableEntry {
+            played: 10,
+            points: 20,
+            goals_for: 15,
+            goals_against: 8,
+        },
+        TableEntry {
+            played: 9,
+            points: 12,
+            goals_for: 10,
+            goals_against: 11,
+        },
+    ]);
+    let matches = [Match {
+        team_home: 0,
+        team_away: 1,
+        goals_home: Some(1),
+        goals_away: Some(1),
+        matchday: None,
+        competition: None,
+        weight: None,
+    }];
+
+    let table = calculate_table_with(&matches, 2, &params);
+
+    let played: Vec<i32> = table.standings.iter().map(|s| s.played).collect();
+    assert_eq!(played, [11, 10]);
+    assert_eq!(table.standings[0].points, 21);
+}
+
+#[test]
+fn test_poisson_quantile() {
+    // Test some known values
+    // For lambda=1.5, p=0.5 should give approximately 1
+    let q = poisson_quantile_statrs(0.5, 1.5);
+    assert!(
+        q >= 1.0 && q <= 2.0,
+        "Median of Poisson(1.5) should be around 1-2"
+    );
+
+    // Edge cases
+    assert_eq!(poisson_quantile_statrs(0.0, 1.5), 0.0, "p=0 should give 0");
+    assert!(
+        poisson_quantile_statrs(0.99999, 1.5) < 20.0,
+        "p~1 should give finite value"
+    );
+
+    // Test with different lambdas
+    let q_small = poisson_quantile_statrs(0.5, 0.5);
+    let q_large = poisson_quantile_statrs(0.5, 5.0);
+    assert!(
+        q_large > q_small,
+        "Larger lambda should give larger quantile"
+    );
+}
+
+#[test]
+fn test_deterministic_simulation() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let season = Season {
+        matches: vec![Match {
+            team_home: 0,
+            team_away: 1,
+            goals_home: None,
+            goals_away: None,
+            matchday: None,
+            competition: None,
+            weight: None,
+        }],
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+
+    // Run simulation twice with same seed
+    let mut rng1 = StdRng::seed_from_u64(12345);
+    let (matches1, _) = simulate_season(
+        &season,
+        20.0,
+        None,
+        65.0,
+        0.0017854953143549,
+        1.3218390804597700,
+        &mut rng1,
+    );
+
+    let mut rng2 = StdRng::seed_from_u64(12345);
+    let (matches2, _) = simulate_season(
+        &season,
+        20.0,
+        None,
+        65.0,
+        0.0017854953143549,
+        1.3218390804597700,
+        &mut rng2,
+    );
+
+    // Results should be identical
+    assert_eq!(
+        matches1[0].goals_home, matches2[0].goals_home,
+        "Same seed should give same home goals"
+    );
+    assert_eq!(
+        matches1[0].goals_away, matches2[0].goals_away,
+        "Same seed should give same away goals"
+    );
+}
+
+#[test]
+fn trajectory_records_ratings_after_every_match() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let season = Season {
+        matches: vec![
+            Match {
+                team_home: 0,
+                team_away: 1,
+                goals_home: Some(1),
+                goals_away: Some(0),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 1,
+                team_away: 2,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 2,
+                team_away: 0,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+        ],
+        team_elos: vec![1600.0, 1500.0, 1400.0],
+        number_teams: 3,
+    };
+
+    let mut rng = StdRng::seed_from_u64(9);
+    let (matches, elos) =
+        simulate_season(&season, 20.0, None, 65.0, 0.0017854953, 1.3218391, &mut rng);
+    let mut rng = StdRng::seed_from_u64(9);
+    let (recorded_matches, recorded_elos, trajectory) = simulate_season_with_trajectory(
+        &season,
+        20.0,
+        None,
+        65.0,
+        0.0017854953,
+        1.3218391,
+        &mut rng,
+    );
+
+    // Recording does not change the simulation.
+    for (a, b) in recorded_matches.iter().zip(&matches) {
+        assert_eq!((a.goals_home, a.goals_away), (b.goals_home, b.goals_away));
+    }
+    assert_eq!(recorded_elos, elos);
+    assert_eq!(trajectory.len(), 3);
+    assert_eq!(trajectory[2], elos);
+    // Team 2 is idle in the opener.
+    assert_eq!(trajectory[0][2], 1400.0);
+    assert!(trajectory[0][0] > 1600.0);
+}
+
+#[test]
+fn mod_factor_weights_scale_each_elo_update() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let played = |home, away, goals_home, goals_away| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: Some(goals_home),
+        goals_away: Some(goals_away),
+        matchday: None,
+        competition: None,
+        weight: None,
+    };
+    let season = Season {
+        matches: vec![
+            played(0, 1, 2, 0),
+            played(1, 2, 1, 1),
+            Match {
+                team_home: 2,
+                team_away: 0,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+        ],
+        team_elos: vec![1600.0, 1500.0, 1400.0],
+        number_teams: 3,
+    };
+    let run = |weights: Option<&[f64]>| {
+        simulate_season_with_trajectory(
+            &season,
+            20.0,
+            weights,
+            65.0,
+            0.0017854953,
+            1.3218391,
+            &mut StdRng::seed_from_u64(3),
+        )
+        .2
+    };
+    let plain = run(None);
+    let weighted = run(Some(&[2.0, 0.0, 1.0]));
+
+    // Double K doubles the opener's change, zero K freezes the second match.
+    assert_relative_eq!(
+        weighted[0][0] - 1600.0,
+        2.0 * (plain[0][0] - 1600.0),
+        epsilon = 1e-9
+    );
+    assert_eq!(weighted[1], weighted[0]);
+
+    // The prepared prefix and the simulated tail see the same weights.
+    let params = SimulationParams {
+        mod_factor_weights: Some(vec![2.0, 0.0, 1.0]),
+        ..Default::default()
+    };
+    let prepared = PreparedSeason::new(&season, &params);
+    assert_eq!(prepared.elos, weighted[1]);
+    assert_eq!(prepared.tail_weights.as_deref(), Some(&[1.0][..]));
+}
+
+#[test]
+fn competition_tags_weight_elo_updates_like_schedule_weights() {
+    let tagged = |home, away, goals: Option<(i32, i32)>, competition: &str| Match {
+        team_home: home,
+        team_away: away,
+        goals_home: goals.map(|g| g.0),
+        goals_away: goals.map(|g| g.1),
+        matchday: None,
+        competition: Some(competition.to_string()),
+        weight: None,
+    };
+    let mut season = Season {
+        matches: vec![
+            tagged(0, 1, Some((2, 0)), "cup"),
+            tagged(1, 2, Some((1, 1)), "league"),
+            tagged(2, 0, Some((0, 3)), "friendly"),
+            tagged(2, 0, None, "league"),
+        ],
+        team_elos: vec![1600.0, 1500.0, 1400.0],
+        number_teams: 3,
+    };
+    season.matches[1].weight = Some(0.5);
+    let params = SimulationParams {
+        competition_weights: Some(
+            [("cup".to_string(), 2.0), ("friendly".to_string(), 0.0)]
+                .into_iter()
+                .collect(),
+        ),
+        mod_factor_weights: Some(vec![1.0, 3.0, 1.0, 1.0]),
+        ..Default::default()
+    };
+    // A match's own weight overrides its competition's; unknown
+    // competitions count once; schedule weights multiply on top.
+    assert_eq!(
+        params.schedule_weights(&season.matches),
+        Some(vec![2.0, 1.5, 0.0, 1.0])
+    );
+
+    let untagged = Season {
+        matches: season
+            .matches
+            .iter()
+            .map(|m| Match {
+                competition: None,
+                weight: None,
+                ..m.clone()
+            })
+            .collect(),
+        ..season.clone()
+    };
+    let by_row = SimulationParams {
+        mod_factor_weights: Some(vec![2.0, 1.5, 0.0, 1.0]),
+        ..Default::default()
+    };
+    assert_eq!(
+        PreparedSeason::new(&season, &params).elos,
+        PreparedSeason::new(&untagged, &by_row).elos
+    );
+    assert_eq!(
+        crate::projection::fixture_probabilities(&season, &params),
+        crate::projection::fixture_probabilities(&untagged, &by_row)
+    );
+    assert_eq!(
+        SimulationParams::default().schedule_weights(&season.matches),
+        Some(vec![1.0, 0.5, 1.0, 1.0])
+    );
+}
+
+#[test]
+fn goal_models_plug_into_the_season_logic() {
+    use crate::rating::Elo;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Goals per team whatever the opponent or the ratings.
+    struct Fixed(Vec<f64>);
+    impl GoalModel for Fixed {
+        fn expected_goals(&self, home: usize, away: usize, _: f64) -> (f64, f64) {
+            (self.0[home], self.0[away])
+        }
+    }
+
+    let season = Season {
+        matches: double_round_robin(3),
+        team_elos: vec![1300.0, 1500.0, 1700.0],
+        number_teams: 3,
+    };
+    let mut matches = season.matches.clone();
+    let mut elos = season.team_elos.clone();
+    simulate_season_rated(
+        &Elo { mod_factor: 20.0 },
+        &Fixed(vec![6.0, 0.001, 0.001]),
+        &mut matches,
+        &mut elos,
+        None,
+        65.0,
+        &mut StdRng::seed_from_u64(1),
+        |_, _| {},
+    );
+    let table = calculate_table(&matches, 3, None, None, None, None);
+    assert_eq!(table.standings[0].team_id, 0);
+    assert_eq!(table.standings[0].points, 12);
+    assert!(elos[0] > 1300.0);
+
+    // The linear model reproduces the built-in season simulation.
+    let params = SimulationParams::default();
+    let mut rated = season.matches.clone();
+    let mut rated_elos = season.team_elos.clone();
+    simulate_season_rated(
+        &Elo {
+            mod_factor: params.mod_factor,
+        },
+        &params.goal_model(),
+        &mut rated,
+        &mut rated_elos,
+        None,
+        params.home_advantage,
+        &mut StdRng::seed_from_u64(2),
+        |_, _| {},
+    );
+    let (builtin, builtin_elos) = simulate_season(
+        &season,
+        params.mod_factor,
+        None,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+        &mut StdRng::seed_from_u64(2),
+    );
+    assert_eq!(rated_elos, builtin_elos);
+    for (a, b) in rated.iter().zip(&builtin) {
+        assert_eq!((a.goals_home, a.goals_away), (b.goals_home, b.goals_away));
+    }
+}
+
+#[test]
+fn huge_team_strengths_do_not_poison_the_goal_means() {
+    use crate::models::TeamStrength;
+    use rand::SeedableRng;
+
+    let strengths = [
+        TeamStrength {
+            attack: 1e200,
+            defense: 1e200,
+        },
+        TeamStrength {
+            attack: 1e200,
+            defense: 1e200,
+        },
+    ];
+    let linear = SimulationParams::default().goal_model();
+    // At weight 0 the overflowing strengths must not enter as 0 * inf.
+    let elo_only = StrengthGoalModel::new(&strengths, 0.0, 0.0, linear);
+    assert_eq!(
+        elo_only.expected_goals(0, 1, 100.0),
+        linear.expected_goals(0, 1, 100.0)
+    );
+
+    // Infinite or NaN means score nothing instead of panicking.
+    let full = StrengthGoalModel::new(&strengths, 1.0, 0.0, linear);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let _ = full.sample_goals(0, 1, 0.0, &mut rng);
+    assert_eq!(poisson_quantile(0.5, f64::NAN), 0.0);
+    assert_eq!(poisson_quantile(0.5, f64::INFINITY), 0.0);
+    assert_eq!(poisson_quantile_statrs(0.5, f64::NAN), 0.0);
+}
+
+#[test]
+fn team_strengths_set_the_goal_means() {
+    use crate::models::TeamStrength;
+    use crate::monte_carlo::run_monte_carlo_simulation_seeded;
+
+    let strengths = [
+        TeamStrength {
+            attack: 2.0,
+            defense: 1.0,
+        },
+        TeamStrength {
+            attack: 1.0,
+            defense: 2.0,
+        },
+    ];
+    let linear = SimulationParams::default().goal_model();
+    let model = StrengthGoalModel::new(&strengths, 1.0, 0.0, linear);
+    // Average 1.5: attack 2 against defense 2 gives 2 * 2 / 1.5.
+    let (home, away) = model.expected_goals(0, 1, 500.0);
+    assert_relative_eq!(home, 4.0 / 1.5, epsilon = 1e-12);
+    assert_relative_eq!(away, 1.0 / 1.5, epsilon = 1e-12);
+
+    let half = StrengthGoalModel::new(&strengths, 0.5, 0.0, linear);
+    let (home, _) = half.expected_goals(0, 1, 0.0);
+    assert_relative_eq!(
+        home,
+        (4.0 / 1.5 + linear.tore_intercept) / 2.0,
+        epsilon = 1e-12
+    );
+
+    // Level ratings, but the strengths make team 2 the favourite.
+    let season = Season {
+        matches: double_round_robin(2),
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+    let params = SimulationParams {
+        iterations: 1000,
+        team_strengths: Some(strengths.iter().rev().copied().collect()),
+        ..Default::default()
+    };
+    let names = vec!["A".to_string(), "B".to_string()];
+    let result = run_monte_carlo_simulation_seeded(&season, &params, names, 5);
+    assert_eq!(result.team_names[0], "B");
+    assert!(result.probability_matrix[0][0] > 0.75);
+}
+
+#[test]
+fn strength_priors_blend_into_the_elo_difference() {
+    use crate::monte_carlo::run_monte_carlo_simulation_seeded;
+
+    // Worth four times as much: two doublings above.
+    let priors = priors_from_market_values(&[400.0, 100.0], 100.0);
+    assert_relative_eq!(priors[0] - priors[1], 200.0, epsilon = 1e-9);
+    assert_relative_eq!(priors[0] + priors[1], 0.0, epsilon = 1e-9);
+
+    let linear = SimulationParams::default().goal_model();
+    let model = PriorGoalModel {
+        priors: &priors,
+        weight: 0.25,
+        home_advantage: 65.0,
+        linear,
+    };
+    // Ratings say 465 including home advantage, priors 265: 415 blended.
+    assert_eq!(
+        model.expected_goals(0, 1, 465.0),
+        linear.expected_goals(0, 1, 415.0)
+    );
+
+    // Level ratings, but the priors make team 2 the favourite.
+    let season = Season {
+        matches: double_round_robin(2),
+        team_elos: vec![1500.0, 1500.0],
+        number_teams: 2,
+    };
+    let params = SimulationParams {
+        iterations: 1000,
+        strength_priors: Some(vec![-300.0, 300.0]),
+        prior_weight: 1.0,
+        ..Default::default()
+    };
+    let names = vec!["A".to_string(), "B".to_string()];
+    let result = run_monte_carlo_simulation_seeded(&season, &params, names, 5);
+    assert_eq!(result.team_names[0], "B");
+    assert!(result.probability_matrix[0][0] > 0.75);
+}
+
+#[test]
+fn prepared_season_replays_played_prefix_once() {
+    let season = Season {
+        matches: vec![
+            Match {
+                team_home: 0,
+                team_away: 1,
+                goals_home: Some(2),
+                goals_away: Some(0),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 1,
+                team_away: 2,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+            Match {
+                team_home: 2,
+                team_away: 0,
+                goals_home: Some(1),
+                goals_away: Some(1),
+                matchday: None,
+                competition: None,
+                weight: None,
+            },
+        ],
+        team_elos: vec![1500.0, 1500.0, 1500.0],
+        number_teams: 3,
+    };
+    let params = crate::models::SimulationParams::default();
+
+    let prepared = PreparedSeason::new(&season, &params);
+
+    // Elo moved only for the prefix match (0 vs 1).
+    assert!(prepared.elos[0] > 1500.0);
+    assert!(prepared.elos[1] < 1500.0);
+    assert_eq!(prepared.elos[2], 1500.0);
+
+    // The tail starts at the first unplayed match and keeps the played
+    // match scheduled after it.
+    assert_eq!(prepared.tail.len(), 2);
+
+    // The baseline counts every played result, including the one in the tail.
+    assert_eq!(prepared.baseline[0].points, 4);
+    assert_eq!(prepared.baseline[0].played, 2);
+    assert_eq!(prepared.baseline[2].points, 1);
+
+    // Finishing the table with a simulated tail adds only the simulated match.
+    let mut simulated = prepared.tail.clone();
+    simulated[0].goals_home = Some(0);
+    simulated[0].goals_away = Some(3);
+    let mut standings = Vec::new();
+    prepared.finish_table(&simulated, &mut standings);
+    assert_eq!(standings[2].points, 4);
+    assert_eq!(standings[2].played, 2);
+    assert_eq!(standings[0].played, 2);
+}
+
+#[test]
+fn double_round_robin_pairs_every_team_twice() {
+    for n in [2, 5, 18] {
+        let matches = double_round_robin(n);
+        assert_eq!(matches.len(), n * (n - 1));
+        let mut meetings = vec![vec![0; n]; n];
+        let mut home_games = vec![0; n];
+        for m in &matches {
+            assert!(m.goals_home.is_none());
+            meetings[m.team_home][m.team_away] += 1;
+            home_games[m.team_home] += 1;
+        }
+        for (a, row) in meetings.iter().enumerate() {
+            for (b, &count) in row.iter().enumerate() {
+                assert_eq!(count, usize::from(a != b), "{a} v {b} for {n} teams");
+            }
+        }
+        assert!(home_games.iter().all(|&h| h == n - 1));
+
+        // No team plays twice in a round of the first half.
+        let per_round = n / 2;
+        for round in matches[..matches.len() / 2].chunks(per_round) {
+            let mut seen = vec![false; n];
+            for m in round {
+                assert!(!seen[m.team_home] && !seen[m.team_away]);
+                seen[m.team_home] = true;
+                seen[m.team_away] = true;
+            }
+        }
+    }
+    assert!(double_round_robin(1).is_empty());
+}
+
+#[test]
+fn random_double_round_robin_reorders_a_complete_schedule() {
+    use rand::SeedableRng;
+    let pairs = |matches: &[Match]| -> Vec<(usize, usize)> {
+        matches.iter().map(|m| (m.team_home, m.team_away)).collect()
+    };
+    let draw = |seed| random_double_round_robin(18, &mut rand::rngs::StdRng::seed_from_u64(seed));
+    let matches = draw(7);
+    assert_eq!(pairs(&matches), pairs(&draw(7)));
+    assert_ne!(pairs(&matches), pairs(&double_round_robin(18)));
+    let season = Season {
+        matches,
+        team_elos: vec![1500.0; 18],
+        number_teams: 18,
+    };
+    assert!(schedule_warnings(&season, true).is_empty());
+    assert_eq!(season.matches.last().unwrap().matchday, Some(34));
+}
+
+#[test]
+fn schedule_warnings_flag_matchday_clashes_and_incomplete_round_robins() {
+    let season = |matches: Vec<Match>| Season {
+        matches,
+        team_elos: vec![1500.0; 6],
+        number_teams: 6,
+    };
+    let full = double_round_robin(6);
+    assert_eq!(full[0].matchday, Some(1));
+    assert_eq!(full.last().unwrap().matchday, Some(10));
+    assert!(schedule_warnings(&season(full.clone()), true).is_empty());
+    let single: Vec<Match> = full[..15].to_vec();
+    assert!(schedule_warnings(&season(single.clone()), true).is_empty());
+
+    // A match moved to the matchday before clashes with two others.
+    let mut moved = full.clone();
+    moved[3].matchday = Some(1);
+    let warnings = schedule_warnings(&season(moved), false);
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].starts_with("2 extra appearances"),
+        "{warnings:?}"
+    );
+
+    // Dropping a return match leaves the double round robin incomplete;
+    // replacing it with a repeat puts a pairing at one ground twice.
+    let mut short = full.clone();
+    let dropped = short.pop().unwrap();
+    let warnings = schedule_warnings(&season(short.clone()), true);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("not a complete double round robin: 1 pairings"));
+    let mut repeated = short;
+    repeated.push(Match {
+        team_home: dropped.team_away,
+        team_away: dropped.team_home,
+        matchday: None,
+        ..dropped
+    });
+    let warnings = schedule_warnings(&season(repeated), true);
+    assert!(
+        warnings.iter().any(|w| w.contains("same ground")),
+        "{warnings:?}"
+    );
+    assert!(schedule_warnings(&season(single[..14].to_vec()), false).is_empty());
+}
+
+#[test]
+fn two_legged_tie_goes_to_extra_time_only_when_level() {
+    use rand::SeedableRng;
+    let params = crate::models::SimulationParams::default();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    let mut favourite_wins = 0;
+    let mut decisions = [0; 3];
+    for _ in 0..2000 {
+        let tie = simulate_two_legged_tie(1800.0, 1500.0, &params, &mut rng);
+        let aggregate = (
+            tie.first_leg.0 + tie.second_leg.0,
+            tie.first_leg.1 + tie.second_leg.1,
+        );
+        match tie.decided_by {
+            TieDecision::RegularTime => {
+                assert!(tie.extra_time.is_none());
+                assert_eq!(tie.first_host_wins, aggregate.0 > aggregate.1);
+                decisions[0] += 1;
+            }
+            TieDecision::ExtraTime => {
+                assert_eq!(aggregate.0, aggregate.1);
+                let (first, second) = tie.extra_time.unwrap();
+                assert_eq!(tie.first_host_wins, first > second);
+                decisions[1] += 1;
+            }
+            TieDecision::Penalties => {
+                let (first, second) = tie.extra_time.unwrap();
+                assert_eq!(first, second);
+                decisions[2] += 1;
+            }
+            TieDecision::AwayGoals => panic!("no away-goals rule by default"),
+        }
+        favourite_wins += usize::from(tie.first_host_wins);
+    }
+    assert!(decisions.iter().all(|&n| n > 0));
+    assert!(favourite_wins > 1400);
+}
+
+#[test]
+fn two_leg_aggregate_applies_away_goals_after_extra_time_too() {
+    // 2-1 and 1-0 for the second host: level, but it scored away.
+    assert_eq!(aggregate_two_legs((2, 1), (0, 1), None, false), None);
+    assert_eq!(
+        aggregate_two_legs((2, 1), (0, 1), None, true),
+        Some((false, TieDecision::AwayGoals))
+    );
+    assert_eq!(
+        aggregate_two_legs((3, 0), (0, 1), None, true),
+        Some((true, TieDecision::RegularTime))
+    );
+    // 1-1 after extra time: the visitors' goal counts away, so the away
+    // goals are level too.
+    assert_eq!(aggregate_two_legs((2, 1), (0, 1), Some((1, 1)), true), None);
+    assert_eq!(
+        aggregate_two_legs((2, 1), (0, 1), Some((1, 0)), true),
+        Some((true, TieDecision::ExtraTime))
+    );
+
+    use rand::SeedableRng;
+    let params = crate::models::SimulationParams::default();
+    let rules = TwoLegRules {
+        away_goals: false,
+        extra_time: false,
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+    let mut shootouts = 0;
+    for _ in 0..500 {
+        let tie = simulate_two_legged_tie_with(1600.0, 1600.0, &params, &rules, &mut rng);
+        assert!(tie.extra_time.is_none());
+        assert_ne!(tie.decided_by, TieDecision::ExtraTime);
+        shootouts += usize::from(tie.decided_by == TieDecision::Penalties);
+    }
+    assert!(shootouts > 0);
+}
+
+#[test]
+fn swiss_schedule_gives_distinct_opponents_and_balanced_grounds() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+    for rounds in [8, 5] {
+        let matches = swiss_schedule(36, rounds, &mut rng);
+        assert_eq!(matches.len(), 18 * rounds);
+        for team in 0..36 {
+            let mut opponents: Vec<usize> = matches
+                .iter()
+                .filter_map(|m| match (m.team_home == team, m.team_away == team) {
+                    (true, _) => Some(m.team_away),
+                    (_, true) => Some(m.team_home),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(opponents.len(), rounds);
+            opponents.sort_unstable();
+            opponents.dedup();
+            assert_eq!(opponents.len(), rounds);
+            let home = matches.iter().filter(|m| m.team_home == team).count();
+            assert!(
+                home.abs_diff(rounds - home) <= 1,
+                "team {team}: {home} home"
+            );
+        }
+        // Each round is a full matchday.
+        for round in matches.chunks(18) {
+            let mut playing: Vec<usize> = round
+                .iter()
+                .flat_map(|m| [m.team_home, m.team_away])
+                .collect();
+            playing.sort_unstable();
+            playing.dedup();
+            assert_eq!(playing.len(), 36);
+        }
+    }
+}
+
+#[test]
+fn stream_seeds_are_counter_based_and_distinct() {
+    assert_eq!(stream_seed(7, 42), stream_seed(7, 42));
+    assert_eq!(stream_seeds(7, 40, 5)[2], stream_seed(7, 42));
+
+    let mut seeds: Vec<u64> = (0..16)
+        .flat_map(|master| stream_seeds(master, 0, 1000))
+        .collect();
+    seeds.sort_unstable();
+    seeds.dedup();
+    assert_eq!(seeds.len(), 16 * 1000);
+}
+
+#[test]
+fn stream_draws_are_uniform_and_uncorrelated() {
+    use rand::rngs::StdRng;
+    use rand::{RngExt, SeedableRng};
+
+    // Master seed 0 and consecutive iterations are the low-entropy inputs
+    // that seeding with the raw counter would have correlated.
+    let n = 20_000;
+    let draws: Vec<f64> = stream_seeds(0, 0, n)
+        .into_iter()
+        .map(|seed| StdRng::seed_from_u64(seed).random::<f64>())
+        .collect();
+
+    // Chi-square over 20 equal bins; 43.82 is the 0.1% critical value at
+    // 19 degrees of freedom.
+    let bins = 20;
+    let mut counts = vec![0usize; bins];
+    for &x in &draws {
+        counts[((x * bins as f64) as usize).min(bins - 1)] += 1;
+    }
+    let expected = n as f64 / bins as f64;
+    let chi_square: f64 = counts
+        .iter()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum();
+    assert!(chi_square < 43.82, "chi-square {}", chi_square);
+
+    // Lag-one correlation between neighbouring iterations stays within four
+    // standard errors of zero.
+    let mean = draws.iter().sum::<f64>() / n as f64;
+    let variance: f64 = draws.iter().map(|x| (x - mean).powi(2)).sum();
+    let covariance: f64 = draws
+        .windows(2)
+        .map(|w| (w[0] - mean) * (w[1] - mean))
+        .sum();
+    let correlation = covariance / variance;
+    assert!(
+        correlation.abs() < 4.0 / (n as f64).sqrt(),
+        "correlation {}",
+        correlation
+    );
+
+    // Each seed bit is set in about half of the streams.
+    let seeds = stream_seeds(0, 0, n);
+    for bit in 0..64 {
+        let ones = seeds.iter().filter(|&&s| s >> bit & 1 == 1).count() as f64;
+        assert!((ones / n as f64 - 0.5).abs() < 0.02, "bit {}", bit);
+    }
+}
+
+#[test]
+fn lanes_match_the_scalar_quantile() {
+    let lambdas = [0.001, 0.3, 1.3218390805, 2.5, 9.99, 10.0, 14.0, 40.0];
+    let mut p: f64 = 0.0005;
+    while p < 1.0 {
+        for window in lambdas.windows(LANES) {
+            let lambda: [f64; LANES] = window.try_into().unwrap();
+            let ps = [p, 1.0 - p, p * 0.5, (p * 7.0).fract()];
+            let lanes = poisson_quantile_lanes(ps, lambda);
+            for lane in 0..LANES {
+                assert_eq!(
+                    lanes[lane],
+                    poisson_quantile(ps[lane], lambda[lane]),
+                    "p={}, lambda={}",
+                    ps[lane],
+                    lambda[lane]
+                );
+            }
+        }
+        p += 0.0005;
+    }
+}
+
+#[test]
+fn lanes_pass_edge_draws_to_the_scalar_path() {
+    let lanes = poisson_quantile_lanes([0.0, -0.1, 1.0, 0.5], [1.5, 1.5, 1.5, 1.5]);
+    assert_eq!(lanes, [0.0, 0.0, f64::INFINITY, 1.0]);
+}
+
+#[test]
+fn dispatched_summation_matches_the_portable_loop() {
+    let target = [0.2, 0.999_999, 0.0, 0.73];
+    let lambda = [0.8, 9.5, 2.0, 0.001];
+    let start = lambda.map(|lambda: f64| (-lambda).exp());
+    assert_eq!(
+        simd::sum_lanes(target, lambda, start),
+        simd::sum_lanes_portable(target, lambda, start)
+    );
+}
+
+#[test]
+fn lane_means_match_the_scalar_means() {
+    let delta = [-400.0, 0.0, 65.0, 10_000.0];
+    let (home, away) = expected_goals_lanes(delta, 0.0017854953143549, 1.3218390804597700);
+    for lane in 0..LANES {
+        assert_eq!(
+            (home[lane], away[lane]),
+            crate::simulation::expected_goals_from_delta(
+                delta[lane],
+                0.0017854953143549,
+                1.3218390804597700
+            )
+        );
+    }
+}
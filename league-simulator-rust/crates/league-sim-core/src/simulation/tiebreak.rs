@@ -0,0 +1,208 @@
+use crate::models::{Match, SimulationParams, TeamStanding, TiebreakerRule};
+use std::cmp::{Ordering, Reverse};
+use std::ops::Range;
+
+/// A league's tiebreaker chain and the data its criteria consult beyond
+/// the table rows.
+#[derive(Debug, Clone, Copy)]
+pub struct Tiebreakers<'a> {
+    pub rules: &'a [TiebreakerRule],
+    /// Fair play points per team, fewer is better
+    pub fair_play: Option<&'a [i32]>,
+}
+
+/// Working memory of [`Tiebreakers::sort_with`], kept between sorts so
+/// chains with head-to-head criteria or lots do not allocate per table.
+#[derive(Debug, Default)]
+pub struct TiebreakScratch {
+    groups: Vec<Range<usize>>,
+    split: Vec<Range<usize>>,
+    keys: Vec<i64>,
+    keyed: Vec<(i64, TeamStanding)>,
+}
+
+impl Default for Tiebreakers<'_> {
+    fn default() -> Self {
+        Self {
+            rules: &TiebreakerRule::DEFAULT_CHAIN,
+            fair_play: None,
+        }
+    }
+}
+
+impl<'a> Tiebreakers<'a> {
+    pub fn new(params: &'a SimulationParams) -> Self {
+        Self {
+            rules: &params.tiebreakers,
+            fair_play: params.fair_play.as_deref(),
+        }
+    }
+
+    /// Whether the chain orders teams by their own rows alone, so a plain
+    /// comparison sort suffices.
+    fn is_row_only(&self) -> bool {
+        self.rules.iter().all(|rule| {
+            matches!(
+                rule,
+                TiebreakerRule::Points
+                    | TiebreakerRule::GoalDifference
+                    | TiebreakerRule::GoalsFor
+                    | TiebreakerRule::FairPlay
+            )
+        })
+    }
+
+    /// Whether the chain needs the individual results, not just the table.
+    pub fn needs_matches(&self) -> bool {
+        self.rules.iter().any(|rule| {
+            matches!(
+                rule,
+                TiebreakerRule::HeadToHeadPoints | TiebreakerRule::HeadToHeadAwayGoals
+            )
+        })
+    }
+
+    /// Sort rows into final order and assign positions.
+    ///
+    /// `matches` holds every played match, possibly split over several
+    /// slices; only the head-to-head criteria look at it. `draw_lot` is
+    /// called once per team in a tie that reaches
+    /// [`TiebreakerRule::DrawingOfLots`], the higher lot ranking first.
+    /// Teams level on the whole chain keep team_id order.
+    ///
+    /// Each criterion splits the groups still level after the previous
+    /// ones; head-to-head criteria count the matches among the teams of
+    /// the group being split, and are not reapplied to smaller groups.
+    pub fn sort(
+        &self,
+        standings: &mut [TeamStanding],
+        matches: &[&[Match]],
+        draw_lot: impl FnMut() -> u64,
+    ) {
+        self.sort_with(
+            standings,
+            matches,
+            draw_lot,
+            &mut TiebreakScratch::default(),
+        );
+    }
+
+    /// [`Self::sort`] with its working memory in `scratch`, for callers
+    /// that sort many tables.
+    pub fn sort_with(
+        &self,
+        standings: &mut [TeamStanding],
+        matches: &[&[Match]],
+        mut draw_lot: impl FnMut() -> u64,
+        scratch: &mut TiebreakScratch,
+    ) {
+        standings.sort_by_key(|row| row.team_id);
+        if self.is_row_only() {
+            standings.sort_by(|a, b| {
+                self.rules
+                    .iter()
+                    .map(|&rule| self.row_key(rule, b).cmp(&self.row_key(rule, a)))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+        } else {
+            let TiebreakScratch {
+                groups,
+                split,
+                keys,
+                keyed,
+            } = scratch;
+            groups.clear();
+            groups.push(0..standings.len());
+            for &rule in self.rules {
+                split.clear();
+                for range in groups.drain(..) {
+                    if range.len() < 2 {
+                        split.push(range);
+                        continue;
+                    }
+                    let group = &mut standings[range.clone()];
+                    self.group_keys(rule, group, matches, &mut draw_lot, keys);
+                    keyed.clear();
+                    keyed.extend(keys.iter().copied().zip(group.iter().cloned()));
+                    keyed.sort_by_key(|(key, _)| Reverse(*key));
+                    let mut start = range.start;
+                    for (i, (key, row)) in keyed.iter().enumerate() {
+                        group[i] = row.clone();
+                        if keyed.get(i + 1).is_none_or(|next| next.0 != *key) {
+                            split.push(start..range.start + i + 1);
+                            start = range.start + i + 1;
+                        }
+                    }
+                }
+                std::mem::swap(groups, split);
+            }
+        }
+
+        for (pos, standing) in standings.iter_mut().enumerate() {
+            standing.position = pos + 1;
+        }
+    }
+
+    /// Sort key of a criterion that looks at a single row, higher first.
+    fn row_key(&self, rule: TiebreakerRule, row: &TeamStanding) -> i64 {
+        match rule {
+            TiebreakerRule::Points => row.points.into(),
+            TiebreakerRule::GoalDifference => row.goal_difference.into(),
+            TiebreakerRule::GoalsFor => row.goals_for.into(),
+            TiebreakerRule::FairPlay => -i64::from(
+                self.fair_play
+                    .and_then(|points| points.get(row.team_id))
+                    .copied()
+                    .unwrap_or(0),
+            ),
+            TiebreakerRule::HeadToHeadPoints
+            | TiebreakerRule::HeadToHeadAwayGoals
+            | TiebreakerRule::DrawingOfLots => 0,
+        }
+    }
+
+    /// Sort keys of the rows of one tied group, higher first, into `keys`.
+    fn group_keys(
+        &self,
+        rule: TiebreakerRule,
+        group: &[TeamStanding],
+        matches: &[&[Match]],
+        draw_lot: &mut impl FnMut() -> u64,
+        keys: &mut Vec<i64>,
+    ) {
+        let member = |team: usize| group.iter().position(|row| row.team_id == team);
+        keys.clear();
+        match rule {
+            TiebreakerRule::HeadToHeadPoints | TiebreakerRule::HeadToHeadAwayGoals => {
+                keys.resize(group.len(), 0);
+                for m in matches.iter().copied().flatten() {
+                    let (Some(goals_home), Some(goals_away)) = (m.goals_home, m.goals_away) else {
+                        continue;
+                    };
+                    let (Some(home), Some(away)) = (member(m.team_home), member(m.team_away))
+                    else {
+                        continue;
+                    };
+                    if rule == TiebreakerRule::HeadToHeadAwayGoals {
+                        keys[away] += i64::from(goals_away);
+                    } else {
+                        match goals_home.cmp(&goals_away) {
+                            Ordering::Greater => keys[home] += 3,
+                            Ordering::Less => keys[away] += 3,
+                            Ordering::Equal => {
+                                keys[home] += 1;
+                                keys[away] += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            // Shifted into i64 range; only the order matters.
+            TiebreakerRule::DrawingOfLots => {
+                keys.extend(group.iter().map(|_| (draw_lot() >> 1) as i64));
+            }
+            _ => keys.extend(group.iter().map(|row| self.row_key(rule, row))),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::models::Season;
+use serde::{Deserialize, Serialize};
+
+/// Range of final positions sharing an outcome (e.g. relegation).
+/// Positions are 1-based and inclusive.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub first: usize,
+    pub last: usize,
+}
+
+/// Shape and zone layout of a league format.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LeagueStructure {
+    pub id: String,
+    pub name: String,
+    pub number_teams: usize,
+    /// Matchdays in the regular season (each team plays once per matchday)
+    pub matchdays: usize,
+    /// Whether not every pair of teams meets equally often: the table splits
+    /// into groups for the final matchdays, or (as in a Swiss-model league
+    /// phase) each team meets only some of the others
+    pub split: bool,
+    pub zones: Vec<Zone>,
+}
+
+impl LeagueStructure {
+    /// Probability of finishing in each zone, in `zones` order, given one
+    /// team's row of the probability matrix (index = position - 1).
+    pub fn zone_probabilities(&self, position_probabilities: &[f64]) -> Vec<f64> {
+        self.zones
+            .iter()
+            .map(|zone| {
+                position_probabilities
+                    .iter()
+                    .skip(zone.first - 1)
+                    .take(zone.last + 1 - zone.first)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+fn zone(name: &str, first: usize, last: usize) -> Zone {
+    Zone {
+        name: name.to_string(),
+        first,
+        last,
+    }
+}
+
+/// Built-in league templates.
+pub fn templates() -> Vec<LeagueStructure> {
+    vec![
+        LeagueStructure {
+            id: "bundesliga".to_string(),
+            name: "Bundesliga".to_string(),
+            number_teams: 18,
+            matchdays: 34,
+            split: false,
+            zones: vec![
+                zone("champions_league", 1, 4),
+                zone("europa_league", 5, 5),
+                zone("conference_league", 6, 6),
+                zone("relegation_playoff", 16, 16),
+                zone("relegation", 17, 18),
+            ],
+        },
+        LeagueStructure {
+            id: "2_bundesliga".to_string(),
+            name: "2. Bundesliga".to_string(),
+            number_teams: 18,
+            matchdays: 34,
+            split: false,
+            zones: vec![
+                zone("promotion", 1, 2),
+                zone("promotion_playoff", 3, 3),
+                zone("relegation_playoff", 16, 16),
+                zone("relegation", 17, 18),
+            ],
+        },
+        LeagueStructure {
+            id: "3_liga".to_string(),
+            name: "3. Liga".to_string(),
+            number_teams: 20,
+            matchdays: 38,
+            split: false,
+            zones: vec![
+                zone("promotion", 1, 2),
+                zone("promotion_playoff", 3, 3),
+                zone("relegation", 17, 20),
+            ],
+        },
+        LeagueStructure {
+            id: "premier_league".to_string(),
+            name: "Premier League".to_string(),
+            number_teams: 20,
+            matchdays: 38,
+            split: false,
+            zones: vec![
+                zone("champions_league", 1, 4),
+                zone("europa_league", 5, 5),
+                zone("relegation", 18, 20),
+            ],
+        },
+        LeagueStructure {
+            id: "scottish_premiership".to_string(),
+            name: "Scottish Premiership".to_string(),
+            number_teams: 12,
+            matchdays: 38,
+            split: true,
+            zones: vec![
+                zone("champions_league", 1, 2),
+                zone("relegation_playoff", 11, 11),
+                zone("relegation", 12, 12),
+            ],
+        },
+        LeagueStructure {
+            id: "champions_league_phase".to_string(),
+            name: "UEFA Champions League (league phase)".to_string(),
+            number_teams: 36,
+            matchdays: 8,
+            split: true,
+            zones: vec![
+                zone("round_of_16", 1, 8),
+                zone("knockout_playoff", 9, 24),
+                zone("eliminated", 25, 36),
+            ],
+        },
+    ]
+}
+
+/// Template by id.
+pub fn template(id: &str) -> Option<LeagueStructure> {
+    templates().into_iter().find(|t| t.id == id)
+}
+
+/// Format properties read off a schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleShape {
+    pub number_teams: usize,
+    pub matches: usize,
+    /// Matchdays implied by the match count (2 * matches / teams)
+    pub matchdays: usize,
+    /// Uneven meetings between pairs of teams, as after a table split
+    pub split: bool,
+}
+
+impl ScheduleShape {
+    pub fn of(season: &Season) -> Self {
+        let n = season.number_teams;
+        let mut meetings = vec![0usize; n * n];
+        for m in &season.matches {
+            let (a, b) = (m.team_home.min(m.team_away), m.team_home.max(m.team_away));
+            meetings[a * n + b] += 1;
+        }
+        let pair_counts = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b)));
+        let (min, max) = pair_counts.fold((usize::MAX, 0), |(min, max), (a, b)| {
+            let c = meetings[a * n + b];
+            (min.min(c), max.max(c))
+        });
+
+        Self {
+            number_teams: n,
+            matches: season.matches.len(),
+            matchdays: (2 * season.matches.len()).checked_div(n).unwrap_or(0),
+            split: n > 1 && min != max,
+        }
+    }
+}
+
+/// Closest template to a schedule, with the differences spelled out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureSuggestion {
+    pub structure: LeagueStructure,
+    /// True when team count, matchdays and split all match the template
+    pub exact: bool,
+    /// Ids of every template as close as `structure`, itself first. More
+    /// than one means the shape cannot tell them apart.
+    pub candidates: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Pick the template closest to the schedule's shape. Team count weighs
+/// most, since zones are defined by position; matchdays and the split
+/// marker break ties between leagues of equal size. Leagues of the same
+/// shape (the Bundesliga and 2. Bundesliga) stay tied: the first is applied
+/// and a warning names all of them.
+pub fn detect_structure(season: &Season) -> StructureSuggestion {
+    detect_structure_among(season, &templates())
+}
+
+/// [`detect_structure`] over a caller-supplied template list, e.g. the
+/// built-ins plus leagues from a configuration file. `candidates` must not
+/// be empty.
+pub fn detect_structure_among(
+    season: &Season,
+    candidates: &[LeagueStructure],
+) -> StructureSuggestion {
+    let shape = ScheduleShape::of(season);
+
+    let distance = |t: &LeagueStructure| {
+        t.number_teams.abs_diff(shape.number_teams) * 100
+            + t.matchdays.abs_diff(shape.matchdays)
+            + if t.split != shape.split { 10 } else { 0 }
+    };
+    let best = candidates
+        .iter()
+        .map(distance)
+        .min()
+        .expect("template list is not empty");
+    let tied: Vec<&LeagueStructure> = candidates.iter().filter(|t| distance(t) == best).collect();
+    let structure = tied[0].clone();
+
+    let mut warnings = Vec::new();
+    if structure.number_teams != shape.number_teams {
+        warnings.push(format!(
+            "schedule has {} teams, closest template '{}' has {}",
+            shape.number_teams, structure.id, structure.number_teams
+        ));
+    }
+    if structure.matchdays != shape.matchdays {
+        warnings.push(format!(
+            "schedule implies {} matchdays, template '{}' has {}",
+            shape.matchdays, structure.id, structure.matchdays
+        ));
+    }
+    if structure.split != shape.split {
+        warnings.push(format!(
+            "schedule {} a table split, template '{}' {}",
+            if shape.split { "has" } else { "has no" },
+            structure.id,
+            if structure.split {
+                "expects one"
+            } else {
+                "has none"
+            }
+        ));
+    }
+
+    let exact = warnings.is_empty();
+    let candidates: Vec<String> = tied.iter().map(|t| t.id.clone()).collect();
+    if candidates.len() > 1 {
+        warnings.push(format!(
+            "schedule fits {} equally well; applied '{}', set the structure explicitly to choose",
+            candidates
+                .iter()
+                .map(|id| format!("'{}'", id))
+                .collect::<Vec<_>>()
+                .join(", "),
+            structure.id
+        ));
+    }
+
+    StructureSuggestion {
+        exact,
+        structure,
+        candidates,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests;
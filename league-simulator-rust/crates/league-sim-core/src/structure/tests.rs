@@ -0,0 +1,142 @@
+use super::*;
+use crate::models::Match;
+
+fn double_round_robin(n: usize) -> Season {
+    let mut matches = Vec::new();
+    for home in 0..n {
+        for away in 0..n {
+            if home != away {
+                matches.push(Match {
+                    team_home: home,
+                    team_away: away,
+                    goals_home: None,
+                    goals_away: None,
+                    matchday: None,
+                    competition: None,
+                    weight: None,
+                });
+            }
+        }
+    }
+    Season {
+        matches,
+        team_elos: vec![1500.0; n],
+        number_teams: n,
+    }
+}
+
+#[test]
+fn detects_eighteen_team_double_round_robin_exactly() {
+    let suggestion = detect_structure(&double_round_robin(18));
+
+    assert!(suggestion.exact, "warnings: {:?}", suggestion.warnings);
+    assert_eq!(suggestion.structure.number_teams, 18);
+    assert_eq!(suggestion.structure.matchdays, 34);
+}
+
+#[test]
+fn warns_about_every_template_of_the_same_shape() {
+    let suggestion = detect_structure(&double_round_robin(18));
+
+    assert_eq!(suggestion.candidates, ["bundesliga", "2_bundesliga"]);
+    assert_eq!(suggestion.structure.id, "bundesliga");
+    let warning = suggestion.warnings.last().unwrap();
+    assert!(
+        warning.contains("'bundesliga', '2_bundesliga'"),
+        "{}",
+        warning
+    );
+
+    let suggestion = detect_structure(&double_round_robin(20));
+    assert_eq!(suggestion.candidates, ["3_liga", "premier_league"]);
+}
+
+#[test]
+fn detects_twenty_team_league() {
+    let suggestion = detect_structure(&double_round_robin(20));
+
+    assert!(suggestion.exact);
+    assert_eq!(suggestion.structure.number_teams, 20);
+}
+
+#[test]
+fn detects_split_league_from_uneven_meetings() {
+    // 12 teams, three full meetings (33 matchdays) plus five extra rounds
+    // inside the top and bottom halves.
+    let mut season = double_round_robin(12);
+    for home in 0..12 {
+        for away in home + 1..12 {
+            season.matches.push(Match {
+                team_home: home,
+                team_away: away,
+                goals_home: None,
+                goals_away: None,
+                matchday: None,
+                competition: None,
+                weight: None,
+            });
+        }
+    }
+    for half in [0..6, 6..12] {
+        for home in half.clone() {
+            for away in half.clone() {
+                if home < away {
+                    season.matches.push(Match {
+                        team_home: away,
+                        team_away: home,
+                        goals_home: None,
+                        goals_away: None,
+                        matchday: None,
+                        competition: None,
+                        weight: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let shape = ScheduleShape::of(&season);
+    assert!(shape.split);
+    assert_eq!(shape.matchdays, 38);
+
+    let suggestion = detect_structure(&season);
+    assert_eq!(suggestion.structure.id, "scottish_premiership");
+    assert!(suggestion.exact);
+}
+
+#[test]
+fn detects_swiss_league_phase() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+    let season = Season {
+        matches: crate::simulation::swiss_schedule(36, 8, &mut rng),
+        team_elos: vec![1500.0; 36],
+        number_teams: 36,
+    };
+    let suggestion = detect_structure(&season);
+
+    assert!(suggestion.exact, "warnings: {:?}", suggestion.warnings);
+    assert_eq!(suggestion.structure.id, "champions_league_phase");
+}
+
+#[test]
+fn warns_when_no_template_matches() {
+    let suggestion = detect_structure(&double_round_robin(16));
+
+    assert!(!suggestion.exact);
+    assert!(suggestion.warnings[0].contains("16 teams"));
+}
+
+#[test]
+fn zone_probabilities_sum_position_ranges() {
+    let structure = template("bundesliga").unwrap();
+    let mut row = vec![0.0; 18];
+    row[0] = 0.5;
+    row[3] = 0.25;
+    row[16] = 0.25;
+
+    let zones = structure.zone_probabilities(&row);
+
+    assert_eq!(zones[0], 0.75, "champions_league covers positions 1-4");
+    assert_eq!(zones[4], 0.25, "relegation covers positions 17-18");
+}
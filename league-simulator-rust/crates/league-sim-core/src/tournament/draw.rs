@@ -0,0 +1,341 @@
+use super::groups::{play_tournament, Group, GroupTournament, Slot};
+use super::knockout::{round_names, TeamTournament, TournamentResult};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::simulation::{
+    double_round_robin, entropy_seed, stream_seeds, PreparedSeason, TwoLegRules,
+};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Placements one draw may try before it is given up as too constrained.
+/// Feasible real-world draws need a few hundred.
+const DRAW_NODE_BUDGET: usize = 200_000;
+
+/// Why a [`Draw`] produced no groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawError {
+    /// No assignment honours the pot and association restrictions.
+    Impossible,
+    /// The search for one ran out of its placement budget.
+    TooConstrained,
+}
+
+/// A team entering a [`Draw`].
+#[derive(Debug, Clone)]
+pub struct DrawTeam {
+    pub name: String,
+    pub elo: f64,
+    /// Seeding pot, 0 for the first; a group takes one team per pot
+    pub pot: usize,
+    /// National association; teams sharing one are drawn into different
+    /// groups
+    pub association: String,
+}
+
+/// A group-stage draw from seeding pots with country protection.
+///
+/// Pots are drawn in order, the teams of each pot in random order. Every
+/// drawn team goes into the first group (A, B, ...) that has no team from
+/// its pot or association yet and still leaves a valid draw for the teams
+/// to come, as in UEFA's computer-assisted draws, so a dead end is never
+/// reached. The look-ahead is a backtracking search, capped at
+/// [`DRAW_NODE_BUDGET`] placements per draw.
+#[derive(Debug, Clone)]
+pub struct Draw {
+    pub teams: Vec<DrawTeam>,
+    pub groups: usize,
+}
+
+impl Draw {
+    /// The first problem with the draw, if any.
+    pub fn check(&self) -> Result<(), String> {
+        if self.groups == 0 {
+            return Err("at least one group is required".to_string());
+        }
+        let mut pots: HashMap<usize, usize> = HashMap::new();
+        let mut associations: HashMap<&str, usize> = HashMap::new();
+        for team in &self.teams {
+            *pots.entry(team.pot).or_default() += 1;
+            *associations.entry(team.association.as_str()).or_default() += 1;
+        }
+        let mut pots: Vec<_> = pots.into_iter().collect();
+        pots.sort_unstable();
+        if let Some((pot, teams)) = pots.into_iter().find(|&(_, n)| n > self.groups) {
+            return Err(format!(
+                "pot {} has {} teams for {} groups",
+                pot + 1,
+                teams,
+                self.groups
+            ));
+        }
+        let mut associations: Vec<_> = associations.into_iter().collect();
+        associations.sort_unstable();
+        if let Some((association, teams)) = associations.into_iter().find(|&(_, n)| n > self.groups)
+        {
+            return Err(format!(
+                "{} teams from '{}' cannot be kept apart in {} groups",
+                teams, association, self.groups
+            ));
+        }
+        match self.draw(&mut StdRng::seed_from_u64(0)) {
+            Ok(_) => Ok(()),
+            Err(DrawError::Impossible) => {
+                Err("no draw keeps the teams of each association apart".to_string())
+            }
+            Err(DrawError::TooConstrained) => Err(format!(
+                "draw too constrained: no valid draw found within {} placements",
+                DRAW_NODE_BUDGET
+            )),
+        }
+    }
+
+    /// Draw the groups: indices into `teams` per group, in pot order, or why
+    /// there are none.
+    pub fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<Vec<usize>>, DrawError> {
+        self.draw_with_budget(rng, DRAW_NODE_BUDGET)
+    }
+
+    /// [`Draw::draw`] trying at most `budget` placements.
+    pub(crate) fn draw_with_budget<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        mut budget: usize,
+    ) -> Result<Vec<Vec<usize>>, DrawError> {
+        let mut ids = HashMap::new();
+        let associations: Vec<usize> = self
+            .teams
+            .iter()
+            .map(|t| {
+                let next = ids.len();
+                *ids.entry(t.association.as_str()).or_insert(next)
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..self.teams.len()).collect();
+        order.sort_by_key(|&t| self.teams[t].pot);
+        for pot in order.chunk_by_mut(|&a, &b| self.teams[a].pot == self.teams[b].pot) {
+            pot.shuffle(rng);
+        }
+
+        let mut groups = vec![Vec::new(); self.groups];
+        match self.place(&order, &associations, &mut groups, &mut budget)? {
+            true => Ok(groups),
+            false => Err(DrawError::Impossible),
+        }
+    }
+
+    /// Place `order[0]` and, recursively, the rest into the first group
+    /// that leaves a complete draw, spending one unit of `budget` per
+    /// placement tried.
+    fn place(
+        &self,
+        order: &[usize],
+        associations: &[usize],
+        groups: &mut [Vec<usize>],
+        budget: &mut usize,
+    ) -> Result<bool, DrawError> {
+        let Some((&team, rest)) = order.split_first() else {
+            return Ok(true);
+        };
+        for g in 0..groups.len() {
+            let clash = groups[g].iter().any(|&other| {
+                self.teams[other].pot == self.teams[team].pot
+                    || associations[other] == associations[team]
+            });
+            if clash {
+                continue;
+            }
+            *budget = budget.checked_sub(1).ok_or(DrawError::TooConstrained)?;
+            let empty = groups[g].is_empty();
+            groups[g].push(team);
+            if self.place(rest, associations, groups, budget)? {
+                return Ok(true);
+            }
+            groups[g].pop();
+            // Empty groups are interchangeable: if one fails, all do.
+            if empty {
+                break;
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// A [`GroupTournament`] whose groups come out of a [`Draw`], made again
+/// for every run, answering "how far does each team get" before the draw.
+#[derive(Debug, Clone)]
+pub struct DrawnTournament {
+    pub draw: Draw,
+    /// Whether each group plays home and away rather than once
+    pub home_and_away: bool,
+    /// Match model of the group stage; `iterations` and the adjustments are
+    /// not used
+    pub group_params: SimulationParams,
+    pub best_thirds: usize,
+    /// First knockout round in bracket order; [`Slot::Group`] indices are
+    /// groups of the draw
+    pub bracket: Vec<Slot>,
+    /// Match model of the knockout rounds
+    pub params: SimulationParams,
+    /// Whether the knockout rounds are on neutral ground
+    pub neutral: bool,
+    /// Rules of two-legged rounds, if every knockout round before the
+    /// final has two legs
+    pub two_legs: Option<TwoLegRules>,
+}
+
+impl DrawnTournament {
+    /// The first problem with the setup, if any.
+    pub fn check(&self) -> Result<(), String> {
+        self.draw.check()?;
+        let groups = self
+            .draw
+            .draw(&mut StdRng::seed_from_u64(0))
+            .expect("checked draw");
+        self.tournament(&groups).check()
+    }
+
+    /// The group tournament of one draw.
+    fn tournament(&self, groups: &[Vec<usize>]) -> GroupTournament {
+        let groups = groups
+            .iter()
+            .enumerate()
+            .map(|(g, members)| {
+                let number_teams = members.len();
+                let mut matches = double_round_robin(number_teams);
+                if !self.home_and_away {
+                    matches.truncate(matches.len() / 2);
+                }
+                Group {
+                    name: group_name(g),
+                    season: Season {
+                        matches,
+                        team_elos: members.iter().map(|&t| self.draw.teams[t].elo).collect(),
+                        number_teams,
+                    },
+                    params: self.group_params.clone(),
+                    team_names: Vec::new(),
+                }
+            })
+            .collect();
+        GroupTournament {
+            groups,
+            best_thirds: self.best_thirds,
+            bracket: self.bracket.clone(),
+            params: self.params.clone(),
+            neutral: self.neutral,
+            two_legs: self.two_legs,
+        }
+    }
+}
+
+/// "A", "B", ..., the name of group `g` of a draw.
+pub fn group_name(g: usize) -> String {
+    match u8::try_from(g) {
+        Ok(g) if g < 26 => char::from(b'A' + g).to_string(),
+        _ => format!("Group {}", g + 1),
+    }
+}
+
+/// Simulate `iterations` draws, each followed by its tournament. Stages are
+/// those of [`run_group_tournament`](super::run_group_tournament); teams
+/// are listed in the order of `draw.teams`. Seeded from OS entropy like
+/// [`run_knockout`](super::run_knockout). `tournament` must pass
+/// [`DrawnTournament::check`].
+pub fn run_drawn_tournament(tournament: &DrawnTournament, iterations: usize) -> TournamentResult {
+    let seeds = stream_seeds(entropy_seed(), 0, iterations);
+    drawn_tournament_with_seeds(tournament, &seeds)
+}
+
+/// Deterministic variant of [`run_drawn_tournament`].
+pub fn run_drawn_tournament_seeded(
+    tournament: &DrawnTournament,
+    iterations: usize,
+    master_seed: u64,
+) -> TournamentResult {
+    let seeds = stream_seeds(master_seed, 0, iterations);
+    drawn_tournament_with_seeds(tournament, &seeds)
+}
+
+fn drawn_tournament_with_seeds(tournament: &DrawnTournament, seeds: &[u64]) -> TournamentResult {
+    let mut rounds = vec!["Group stage".to_string()];
+    rounds.extend(round_names(tournament.bracket.len()));
+    let counts = match tournament.params.rng {
+        RngKind::Std => stage_counts::<StdRng>(tournament, rounds.len(), seeds),
+        RngKind::SmallRng => stage_counts::<SmallRng>(tournament, rounds.len(), seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            stage_counts::<Xoshiro256PlusPlus>(tournament, rounds.len(), seeds)
+        }
+    };
+
+    let runs = seeds.len().max(1) as f64;
+    let stages = rounds.len();
+    let teams = tournament
+        .draw
+        .teams
+        .iter()
+        .enumerate()
+        .map(|(team, t)| TeamTournament {
+            team_name: t.name.clone(),
+            round_probabilities: counts[team * stages..(team + 1) * stages]
+                .iter()
+                .map(|&c| c as f64 / runs)
+                .collect(),
+        })
+        .collect();
+    TournamentResult { rounds, teams }
+}
+
+/// Times each team reached each stage, `[team][stage]`.
+fn stage_counts<R: Rng + RngExt + SeedableRng>(
+    tournament: &DrawnTournament,
+    stages: usize,
+    seeds: &[u64],
+) -> Vec<usize> {
+    let teams = tournament.draw.teams.len();
+    // The draw `check` found; a run whose own shuffle runs out of budget
+    // plays this one rather than failing the whole simulation.
+    let fallback = tournament
+        .draw
+        .draw(&mut StdRng::seed_from_u64(0))
+        .expect("checked draw");
+    seeds
+        .par_iter()
+        .fold(
+            || (vec![0usize; teams * stages], vec![0usize; teams * stages]),
+            |(mut counts, mut run), &seed| {
+                let mut rng = R::seed_from_u64(seed);
+                let groups = tournament
+                    .draw
+                    .draw(&mut rng)
+                    .unwrap_or_else(|_| fallback.clone());
+                let drawn = tournament.tournament(&groups);
+                let prepared: Vec<PreparedSeason> = drawn
+                    .groups
+                    .iter()
+                    .map(|g| PreparedSeason::new(&g.season, &g.params))
+                    .collect();
+                run.fill(0);
+                play_tournament(&drawn, &prepared, &mut rng, &mut run, stages);
+                // The run numbers teams group by group; map them back.
+                for (slot, &team) in groups.iter().flatten().enumerate() {
+                    for stage in 0..stages {
+                        counts[team * stages + stage] += run[slot * stages + stage];
+                    }
+                }
+                (counts, run)
+            },
+        )
+        .map(|(counts, _)| counts)
+        .reduce(
+            || vec![0; teams * stages],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        )
+}
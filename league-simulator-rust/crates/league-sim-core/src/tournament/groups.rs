@@ -0,0 +1,321 @@
+use super::knockout::{play_bracket, round_names, TeamTournament, TournamentResult};
+use crate::models::{RngKind, Season, SimulationParams};
+use crate::monte_carlo::IterBuffers;
+use crate::simulation::{entropy_seed, stream_seeds, PreparedSeason, TwoLegRules};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+
+/// One group of a [`GroupTournament`].
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub name: String,
+    /// The group's matches, team indices local to the group; played ones
+    /// count as in a league
+    pub season: Season,
+    pub params: SimulationParams,
+    pub team_names: Vec<String>,
+}
+
+/// A place in the knockout bracket of a [`GroupTournament`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// The team finishing `position` (1-based) in group `group` (index)
+    Group { group: usize, position: usize },
+    /// The `n`-th (1-based) qualifying third-placed team
+    BestThird(usize),
+}
+
+/// A World Cup or Euro style tournament: group tables, then a knockout
+/// bracket filled from them.
+///
+/// The best `best_thirds` third-placed teams across groups (ranked by
+/// points, goal difference, then goals scored) also go through and fill the
+/// [`Slot::BestThird`] places in that order, swapped between those places
+/// where this avoids a first-round rematch of two teams from one group.
+#[derive(Debug, Clone)]
+pub struct GroupTournament {
+    pub groups: Vec<Group>,
+    pub best_thirds: usize,
+    /// First knockout round in bracket order, as for [`Knockout`](super::Knockout)
+    pub bracket: Vec<Slot>,
+    /// Match model of the knockout rounds; `iterations` and the
+    /// adjustments are not used
+    pub params: SimulationParams,
+    /// Whether the knockout rounds are on neutral ground
+    pub neutral: bool,
+    /// Rules of two-legged rounds, if every knockout round before the
+    /// final has two legs
+    pub two_legs: Option<TwoLegRules>,
+}
+
+impl GroupTournament {
+    /// Bracket for group winners and runners-up of an even number of
+    /// groups, as at the 2018 World Cup: the winner of each group meets the
+    /// runner-up of its neighbour (A1-B2, C1-D2, ..., then B1-A2, D1-C2,
+    /// ...), so teams from one group can meet again only in the final.
+    pub fn winners_and_runners_up(groups: usize) -> Vec<Slot> {
+        let slot = |group, position| Slot::Group { group, position };
+        let pairs = (0..groups / 2).map(|p| (2 * p, 2 * p + 1));
+        let top = pairs.clone().flat_map(|(a, b)| [slot(a, 1), slot(b, 2)]);
+        let bottom = pairs.flat_map(|(a, b)| [slot(b, 1), slot(a, 2)]);
+        top.chain(bottom).collect()
+    }
+
+    /// The first problem with the setup, if any.
+    pub fn check(&self) -> Result<(), String> {
+        if self.groups.is_empty() {
+            return Err("at least one group is required".to_string());
+        }
+        let teams = self.bracket.len();
+        if teams < 2 || !teams.is_power_of_two() {
+            return Err(format!(
+                "the bracket needs a power of two places (2, 4, 8, ...), got {}",
+                teams
+            ));
+        }
+        for (i, slot) in self.bracket.iter().enumerate() {
+            if self.bracket[..i].contains(slot) {
+                return Err(format!("bracket place {} repeats {:?}", i + 1, slot));
+            }
+            match *slot {
+                Slot::Group { group, position } => {
+                    let Some(g) = self.groups.get(group) else {
+                        return Err(format!("bracket place {}: no group {}", i + 1, group));
+                    };
+                    if position == 0 || position > g.season.number_teams {
+                        return Err(format!(
+                            "bracket place {}: group '{}' has no position {}",
+                            i + 1,
+                            g.name,
+                            position
+                        ));
+                    }
+                }
+                Slot::BestThird(n) if n == 0 || n > self.best_thirds => {
+                    return Err(format!(
+                        "bracket place {}: best third {} of {}",
+                        i + 1,
+                        n,
+                        self.best_thirds
+                    ));
+                }
+                Slot::BestThird(_) => {}
+            }
+        }
+        let third_places = self
+            .bracket
+            .iter()
+            .filter(|s| matches!(s, Slot::BestThird(_)))
+            .count();
+        if third_places != self.best_thirds {
+            return Err(format!(
+                "{} best thirds go through but the bracket has {} places for them",
+                self.best_thirds, third_places
+            ));
+        }
+        let groups_with_thirds = self
+            .groups
+            .iter()
+            .filter(|g| g.season.number_teams >= 3)
+            .count();
+        if self.best_thirds > groups_with_thirds {
+            return Err(format!(
+                "{} best thirds from {} groups of three or more",
+                self.best_thirds, groups_with_thirds
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Simulate `iterations` runs of the group stage and the knockout rounds.
+/// The stages are "Group stage" followed by those of
+/// [`round_names`]; teams are listed group by group. Seeded from OS
+/// entropy like [`run_knockout`](super::run_knockout). `tournament` must
+/// pass [`GroupTournament::check`].
+pub fn run_group_tournament(tournament: &GroupTournament, iterations: usize) -> TournamentResult {
+    let seeds = stream_seeds(entropy_seed(), 0, iterations);
+    group_tournament_with_seeds(tournament, &seeds)
+}
+
+/// Deterministic variant of [`run_group_tournament`].
+pub fn run_group_tournament_seeded(
+    tournament: &GroupTournament,
+    iterations: usize,
+    master_seed: u64,
+) -> TournamentResult {
+    let seeds = stream_seeds(master_seed, 0, iterations);
+    group_tournament_with_seeds(tournament, &seeds)
+}
+
+fn group_tournament_with_seeds(tournament: &GroupTournament, seeds: &[u64]) -> TournamentResult {
+    let mut rounds = vec!["Group stage".to_string()];
+    rounds.extend(round_names(tournament.bracket.len()));
+    let prepared: Vec<PreparedSeason> = tournament
+        .groups
+        .iter()
+        .map(|g| PreparedSeason::new(&g.season, &g.params))
+        .collect();
+    let counts = match tournament.params.rng {
+        RngKind::Std => stage_counts::<StdRng>(tournament, &prepared, rounds.len(), seeds),
+        RngKind::SmallRng => stage_counts::<SmallRng>(tournament, &prepared, rounds.len(), seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            stage_counts::<Xoshiro256PlusPlus>(tournament, &prepared, rounds.len(), seeds)
+        }
+    };
+
+    let runs = seeds.len().max(1) as f64;
+    let stages = rounds.len();
+    let teams = tournament
+        .groups
+        .iter()
+        .flat_map(|g| {
+            (0..g.season.number_teams).map(move |local| {
+                g.team_names
+                    .get(local)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{} {}", g.name, local + 1))
+            })
+        })
+        .enumerate()
+        .map(|(team, team_name)| TeamTournament {
+            team_name,
+            round_probabilities: counts[team * stages..(team + 1) * stages]
+                .iter()
+                .map(|&c| c as f64 / runs)
+                .collect(),
+        })
+        .collect();
+    TournamentResult { rounds, teams }
+}
+
+/// Times each team reached each stage, `[team][stage]`.
+fn stage_counts<R: Rng + RngExt + SeedableRng>(
+    tournament: &GroupTournament,
+    prepared: &[PreparedSeason],
+    stages: usize,
+    seeds: &[u64],
+) -> Vec<usize> {
+    let teams: usize = tournament
+        .groups
+        .iter()
+        .map(|g| g.season.number_teams)
+        .sum();
+    seeds
+        .par_iter()
+        .fold(
+            || vec![0usize; teams * stages],
+            |mut counts, &seed| {
+                let mut rng = R::seed_from_u64(seed);
+                play_tournament(tournament, prepared, &mut rng, &mut counts, stages);
+                counts
+            },
+        )
+        .reduce(
+            || vec![0; teams * stages],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        )
+}
+
+/// Play one run of the group stage and the knockout rounds, adding to
+/// `counts` as [`play_bracket`] does; teams are numbered group by group.
+/// `prepared` holds each group's [`PreparedSeason`].
+pub(super) fn play_tournament<R: Rng + RngExt + SeedableRng>(
+    tournament: &GroupTournament,
+    prepared: &[PreparedSeason],
+    rng: &mut R,
+    counts: &mut [usize],
+    stages: usize,
+) {
+    let groups = &tournament.groups;
+    let mut offsets = Vec::with_capacity(groups.len());
+    let mut teams = 0;
+    for group in groups {
+        offsets.push(teams);
+        teams += group.season.number_teams;
+    }
+
+    let mut elos = vec![0.0; teams];
+    // Finishing order per group, global ids, and the third-placed teams
+    // with their ranking keys.
+    let mut orders = Vec::with_capacity(groups.len());
+    let mut thirds = Vec::new();
+    let mut buffers = IterBuffers::new(&prepared[0]);
+    for (g, group) in groups.iter().enumerate() {
+        buffers.simulate::<R>(&group.params, &prepared[g], rng.random());
+        for (local, &elo) in buffers.elos.iter().enumerate() {
+            elos[offsets[g] + local] = elo;
+        }
+        if let Some(third) = buffers.standings.get(2) {
+            thirds.push((
+                offsets[g] + third.team_id,
+                g,
+                (third.points, third.goal_difference, third.goals_for),
+            ));
+        }
+        let order: Vec<usize> = buffers
+            .standings
+            .iter()
+            .map(|r| offsets[g] + r.team_id)
+            .collect();
+        orders.push(order);
+    }
+    thirds.sort_by_key(|&(_, _, key)| std::cmp::Reverse(key));
+    thirds.truncate(tournament.best_thirds);
+
+    let group_of = |team: usize| offsets.partition_point(|&o| o <= team) - 1;
+    let mut alive: Vec<usize> = tournament
+        .bracket
+        .iter()
+        .map(|slot| match *slot {
+            Slot::Group { group, position } => orders[group][position - 1],
+            Slot::BestThird(n) => thirds[n - 1].0,
+        })
+        .collect();
+    avoid_group_rematches(&tournament.bracket, &mut alive, group_of);
+
+    for team in 0..teams {
+        counts[team * stages] += 1;
+    }
+    play_bracket(
+        &mut alive,
+        &mut elos,
+        &tournament.params,
+        |_| tournament.neutral,
+        tournament.two_legs.as_ref(),
+        rng,
+        counts,
+        stages,
+        1,
+    );
+}
+
+/// Swap third-placed teams between their bracket places where one would
+/// otherwise meet a team from its own group in the first round.
+fn avoid_group_rematches(bracket: &[Slot], alive: &mut [usize], group_of: impl Fn(usize) -> usize) {
+    let places: Vec<usize> = (0..bracket.len())
+        .filter(|&i| matches!(bracket[i], Slot::BestThird(_)))
+        .collect();
+    let clash = |alive: &[usize], i: usize| group_of(alive[i]) == group_of(alive[i ^ 1]);
+    for &i in &places {
+        if !clash(alive, i) {
+            continue;
+        }
+        for &j in &places {
+            if i == j {
+                continue;
+            }
+            alive.swap(i, j);
+            if !clash(alive, i) && !clash(alive, j) {
+                break;
+            }
+            alive.swap(i, j);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::models::{RngKind, SimulationParams};
+use crate::simulation::{
+    entropy_seed, simulate_knockout_match, simulate_two_legged_tie_with, stream_seeds, TwoLegRules,
+};
+use rand::rngs::{SmallRng, StdRng, Xoshiro256PlusPlus};
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A single-elimination bracket like the DFB-Pokal. Teams are in bracket
+/// order: the first round pairs teams 0 and 1, 2 and 3, and so on, with the
+/// first of each pair at home, and winners meet in the same order in the
+/// next round.
+#[derive(Debug, Clone)]
+pub struct Knockout {
+    pub team_names: Vec<String>,
+    pub elos: Vec<f64>,
+    /// Match model; `iterations` and the adjustments are not used
+    pub params: SimulationParams,
+    /// Whether the final is played on neutral ground
+    pub neutral_final: bool,
+    /// Rules of two-legged rounds, if every round before the final has
+    /// two legs
+    pub two_legs: Option<TwoLegRules>,
+}
+
+impl Knockout {
+    /// The first problem with the bracket, if any.
+    pub fn check(&self) -> Result<(), String> {
+        let teams = self.elos.len();
+        if teams < 2 || !teams.is_power_of_two() {
+            return Err(format!(
+                "a bracket needs a power of two teams (2, 4, 8, ...), got {}",
+                teams
+            ));
+        }
+        if !self.team_names.is_empty() && self.team_names.len() != teams {
+            return Err(format!(
+                "{} team names for {} teams",
+                self.team_names.len(),
+                teams
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Round-by-round outcome of a knockout run.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TournamentResult {
+    /// Stages in order, from the first round to "Winner"; the indices of
+    /// `round_probabilities`
+    pub rounds: Vec<String>,
+    /// Every team, in bracket order (group by group for a
+    /// [`GroupTournament`](super::GroupTournament))
+    pub teams: Vec<TeamTournament>,
+}
+
+/// How far one team gets.
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamTournament {
+    pub team_name: String,
+    /// Probability of reaching each stage of `rounds`
+    pub round_probabilities: Vec<f64>,
+}
+
+/// Names of the stages of a bracket of `teams` entrants: "Round of 64",
+/// ..., "Quarter-finals", "Semi-finals", "Final", "Winner".
+pub fn round_names(teams: usize) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut remaining = teams;
+    while remaining >= 2 {
+        names.push(match remaining {
+            2 => "Final".to_string(),
+            4 => "Semi-finals".to_string(),
+            8 => "Quarter-finals".to_string(),
+            n => format!("Round of {}", n),
+        });
+        remaining /= 2;
+    }
+    names.push("Winner".to_string());
+    names
+}
+
+/// Simulate `iterations` runs of the bracket. Seeded from OS entropy like
+/// [`run_monte_carlo_simulation`](crate::monte_carlo::run_monte_carlo_simulation).
+/// `knockout` must pass [`Knockout::check`].
+pub fn run_knockout(knockout: &Knockout, iterations: usize) -> TournamentResult {
+    let seeds = stream_seeds(entropy_seed(), 0, iterations);
+    knockout_with_seeds(knockout, &seeds)
+}
+
+/// Deterministic variant of [`run_knockout`].
+pub fn run_knockout_seeded(
+    knockout: &Knockout,
+    iterations: usize,
+    master_seed: u64,
+) -> TournamentResult {
+    let seeds = stream_seeds(master_seed, 0, iterations);
+    knockout_with_seeds(knockout, &seeds)
+}
+
+fn knockout_with_seeds(knockout: &Knockout, seeds: &[u64]) -> TournamentResult {
+    let rounds = round_names(knockout.elos.len());
+    let counts = match knockout.params.rng {
+        RngKind::Std => stage_counts::<StdRng>(knockout, rounds.len(), seeds),
+        RngKind::SmallRng => stage_counts::<SmallRng>(knockout, rounds.len(), seeds),
+        RngKind::Xoshiro256PlusPlus => {
+            stage_counts::<Xoshiro256PlusPlus>(knockout, rounds.len(), seeds)
+        }
+    };
+
+    let runs = seeds.len().max(1) as f64;
+    let teams = (0..knockout.elos.len())
+        .map(|team| TeamTournament {
+            team_name: knockout
+                .team_names
+                .get(team)
+                .cloned()
+                .unwrap_or_else(|| format!("Team {}", team + 1)),
+            round_probabilities: counts[team * rounds.len()..(team + 1) * rounds.len()]
+                .iter()
+                .map(|&c| c as f64 / runs)
+                .collect(),
+        })
+        .collect();
+    TournamentResult { rounds, teams }
+}
+
+/// Times each team reached each stage, `[team][stage]`.
+fn stage_counts<R: Rng + RngExt + SeedableRng>(
+    knockout: &Knockout,
+    stages: usize,
+    seeds: &[u64],
+) -> Vec<usize> {
+    let teams = knockout.elos.len();
+    seeds
+        .par_iter()
+        .fold(
+            || (vec![0usize; teams * stages], Vec::with_capacity(teams)),
+            |(mut counts, mut alive), &seed| {
+                let mut rng = R::seed_from_u64(seed);
+                let mut elos = knockout.elos.clone();
+                alive.clear();
+                alive.extend(0..teams);
+                play_bracket(
+                    &mut alive,
+                    &mut elos,
+                    &knockout.params,
+                    |field| knockout.neutral_final && field == 2,
+                    knockout.two_legs.as_ref(),
+                    &mut rng,
+                    &mut counts[..],
+                    stages,
+                    0,
+                );
+                (counts, alive)
+            },
+        )
+        .map(|(counts, _)| counts)
+        .reduce(
+            || vec![0; teams * stages],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        )
+}
+
+/// Play the teams of `alive` (bracket order) down to a winner, adding one to
+/// `counts[team * stages + stage]` for every stage a team reaches, from
+/// `first_stage` on. `neutral` tells from the number of teams left whether a
+/// round is on neutral ground. With `two_legs`, rounds before the final are
+/// two-legged ties, the second of each pair hosting the first leg so that
+/// the first hosts the decisive one. Ratings in `elos` move with every
+/// match.
+pub(super) fn play_bracket<R: Rng + RngExt>(
+    alive: &mut Vec<usize>,
+    elos: &mut [f64],
+    params: &SimulationParams,
+    neutral: impl Fn(usize) -> bool,
+    two_legs: Option<&TwoLegRules>,
+    rng: &mut R,
+    counts: &mut [usize],
+    stages: usize,
+    first_stage: usize,
+) {
+    for stage in first_stage.. {
+        for &team in alive.iter() {
+            counts[team * stages + stage] += 1;
+        }
+        if alive.len() <= 1 {
+            break;
+        }
+        let neutral = neutral(alive.len());
+        let two_legs = two_legs.filter(|_| alive.len() > 2);
+        for pair in 0..alive.len() / 2 {
+            let (home, away) = (alive[2 * pair], alive[2 * pair + 1]);
+            if let Some(rules) = two_legs {
+                let tie = simulate_two_legged_tie_with(elos[away], elos[home], params, rules, rng);
+                elos[away] = tie.elo_first_host;
+                elos[home] = tie.elo_second_host;
+                alive[pair] = if tie.first_host_wins { away } else { home };
+                continue;
+            }
+            let result = simulate_knockout_match(elos[home], elos[away], params, neutral, rng);
+            elos[home] = result.elo_home;
+            elos[away] = result.elo_away;
+            alive[pair] = if result.home_wins { home } else { away };
+        }
+        alive.truncate(alive.len() / 2);
+    }
+}
@@ -0,0 +1,13 @@
+//! Cup competitions simulated from Elo ratings, as opposed to the league
+//! seasons of [`crate::monte_carlo`].
+
+mod draw;
+mod groups;
+mod knockout;
+
+pub use draw::*;
+pub use groups::*;
+pub use knockout::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,304 @@
+use super::*;
+use crate::models::{Season, SimulationParams};
+use crate::simulation::TwoLegRules;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn bracket(elos: Vec<f64>) -> Knockout {
+    Knockout {
+        team_names: (0..elos.len()).map(|i| format!("T{}", i + 1)).collect(),
+        elos,
+        params: SimulationParams::default(),
+        neutral_final: true,
+        two_legs: None,
+    }
+}
+
+#[test]
+fn round_names_count_down_to_the_winner() {
+    assert_eq!(
+        round_names(16),
+        [
+            "Round of 16",
+            "Quarter-finals",
+            "Semi-finals",
+            "Final",
+            "Winner"
+        ]
+    );
+    assert_eq!(round_names(2), ["Final", "Winner"]);
+}
+
+#[test]
+fn knockout_halves_the_field_every_round() {
+    let knockout = bracket(vec![
+        1900.0, 1500.0, 1500.0, 1500.0, 1500.0, 1500.0, 1500.0, 1300.0,
+    ]);
+    assert!(knockout.check().is_ok());
+
+    let result = run_knockout_seeded(&knockout, 2000, 11);
+    assert_eq!(result.rounds.len(), 4);
+    for (stage, expected) in [8.0, 4.0, 2.0, 1.0].into_iter().enumerate() {
+        let total: f64 = result
+            .teams
+            .iter()
+            .map(|t| t.round_probabilities[stage])
+            .sum();
+        assert!((total - expected).abs() < 1e-9);
+    }
+    for team in &result.teams {
+        assert_eq!(team.round_probabilities[0], 1.0);
+        assert!(team.round_probabilities.windows(2).all(|w| w[0] >= w[1]));
+    }
+    let (favourite, outsider) = (&result.teams[0], &result.teams[7]);
+    assert!(favourite.round_probabilities[3] > 0.3);
+    assert!(favourite.round_probabilities[3] > 3.0 * outsider.round_probabilities[3]);
+    assert_eq!(run_knockout_seeded(&knockout, 2000, 11), result);
+}
+
+#[test]
+fn two_legged_rounds_favour_the_stronger_team() {
+    let mut knockout = bracket(vec![1800.0, 1500.0, 1500.0, 1500.0]);
+    // No home advantage, which a single match would hand the favourite.
+    knockout.params.home_advantage = 0.0;
+    let single = run_knockout_seeded(&knockout, 4000, 5);
+    knockout.two_legs = Some(TwoLegRules {
+        away_goals: true,
+        extra_time: true,
+    });
+    let two_legged = run_knockout_seeded(&knockout, 4000, 5);
+
+    for stage in 0..3 {
+        let total: f64 = two_legged
+            .teams
+            .iter()
+            .map(|t| t.round_probabilities[stage])
+            .sum();
+        assert!((total - [4.0, 2.0, 1.0][stage]).abs() < 1e-9);
+    }
+    // Twice the football leaves less room for an upset.
+    assert!(
+        two_legged.teams[0].round_probabilities[1] > single.teams[0].round_probabilities[1] + 0.02
+    );
+}
+
+#[test]
+fn knockout_check_requires_a_power_of_two() {
+    assert!(bracket(vec![1500.0; 6])
+        .check()
+        .unwrap_err()
+        .contains("got 6"));
+    assert!(bracket(vec![1500.0]).check().is_err());
+}
+
+fn group(name: &str, elos: Vec<f64>) -> Group {
+    let number_teams = elos.len();
+    Group {
+        name: name.to_string(),
+        season: Season {
+            matches: crate::simulation::double_round_robin(number_teams)
+                .into_iter()
+                .take(number_teams * (number_teams - 1) / 2)
+                .collect(),
+            team_elos: elos,
+            number_teams,
+        },
+        params: SimulationParams::default(),
+        team_names: (0..number_teams)
+            .map(|i| format!("{}{}", name, i + 1))
+            .collect(),
+    }
+}
+
+#[test]
+fn winners_meet_runners_up_of_the_neighbouring_group() {
+    let slot = |group, position| Slot::Group { group, position };
+    assert_eq!(
+        GroupTournament::winners_and_runners_up(4),
+        [
+            slot(0, 1),
+            slot(1, 2),
+            slot(2, 1),
+            slot(3, 2),
+            slot(1, 1),
+            slot(0, 2),
+            slot(3, 1),
+            slot(2, 2)
+        ]
+    );
+}
+
+#[test]
+fn group_tournament_sends_best_thirds_through() {
+    // Euro 2016 shape in miniature: 3 groups of 4 fill 8 places with the
+    // two best thirds.
+    let elos = |top: f64| vec![top, 1600.0, 1500.0, 1300.0];
+    let slot = |group, position| Slot::Group { group, position };
+    let tournament = GroupTournament {
+        groups: vec![
+            group("A", elos(2000.0)),
+            group("B", elos(1700.0)),
+            group("C", elos(1700.0)),
+        ],
+        best_thirds: 2,
+        bracket: vec![
+            slot(0, 1),
+            Slot::BestThird(1),
+            slot(1, 1),
+            slot(2, 2),
+            slot(2, 1),
+            Slot::BestThird(2),
+            slot(0, 2),
+            slot(1, 2),
+        ],
+        params: SimulationParams::default(),
+        neutral: true,
+        two_legs: None,
+    };
+    assert!(tournament.check().is_ok());
+
+    let result = run_group_tournament_seeded(&tournament, 1000, 21);
+    assert_eq!(
+        result.rounds,
+        [
+            "Group stage",
+            "Quarter-finals",
+            "Semi-finals",
+            "Final",
+            "Winner"
+        ]
+    );
+    assert_eq!(result.teams.len(), 12);
+    assert_eq!(result.teams[4].team_name, "B1");
+    for (stage, expected) in [12.0, 8.0, 4.0, 2.0, 1.0].into_iter().enumerate() {
+        let total: f64 = result
+            .teams
+            .iter()
+            .map(|t| t.round_probabilities[stage])
+            .sum();
+        assert!((total - expected).abs() < 1e-9);
+    }
+    // The strongest side nearly always goes through and wins most often.
+    let favourite = &result.teams[0];
+    assert!(favourite.round_probabilities[1] > 0.95);
+    assert!(result
+        .teams
+        .iter()
+        .all(|t| t.round_probabilities[4] <= favourite.round_probabilities[4]));
+}
+
+#[test]
+fn group_tournament_check_matches_thirds_to_bracket_places() {
+    let tournament = GroupTournament {
+        groups: vec![group("A", vec![1500.0; 4]), group("B", vec![1500.0; 4])],
+        best_thirds: 1,
+        bracket: GroupTournament::winners_and_runners_up(2),
+        params: SimulationParams::default(),
+        neutral: true,
+        two_legs: None,
+    };
+    assert!(tournament.check().unwrap_err().contains("0 places"));
+}
+
+fn draw_team(name: &str, pot: usize, association: &str) -> DrawTeam {
+    DrawTeam {
+        name: name.to_string(),
+        elo: 1500.0 + 100.0 * (3 - pot) as f64,
+        pot,
+        association: association.to_string(),
+    }
+}
+
+/// Four pots of four, each with one club from each of four associations,
+/// so every group ends up with one club per association.
+fn champions_league_draw() -> Draw {
+    let associations = ["GER", "ENG", "ESP", "ITA"];
+    let teams = (0..16)
+        .map(|i| draw_team(&format!("T{}", i + 1), i / 4, associations[i % 4]))
+        .collect();
+    Draw { teams, groups: 4 }
+}
+
+#[test]
+fn draw_keeps_pots_and_associations_apart() {
+    let draw = champions_league_draw();
+    assert!(draw.check().is_ok());
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..200 {
+        let groups = draw.draw(&mut rng).unwrap();
+        assert_eq!(groups.len(), 4);
+        for group in &groups {
+            let pots: Vec<usize> = group.iter().map(|&t| draw.teams[t].pot).collect();
+            assert_eq!(pots, [0, 1, 2, 3]);
+            let mut associations: Vec<&str> = group
+                .iter()
+                .map(|&t| draw.teams[t].association.as_str())
+                .collect();
+            associations.sort_unstable();
+            assert_eq!(associations, ["ENG", "ESP", "GER", "ITA"]);
+        }
+    }
+}
+
+#[test]
+fn draw_check_reports_impossible_restrictions() {
+    let mut draw = champions_league_draw();
+    draw.teams[4].pot = 0;
+    assert!(draw.check().unwrap_err().contains("pot 1 has 5 teams"));
+
+    // Three associations among two groups of pot pairs: the third club of
+    // "ESP" has nowhere to go.
+    let draw = Draw {
+        teams: vec![
+            draw_team("A", 0, "ESP"),
+            draw_team("B", 0, "ITA"),
+            draw_team("C", 1, "ESP"),
+            draw_team("D", 1, "FRA"),
+            draw_team("E", 2, "ESP"),
+            draw_team("F", 2, "ITA"),
+        ],
+        groups: 2,
+    };
+    assert!(draw.check().unwrap_err().contains("3 teams from 'ESP'"));
+}
+
+#[test]
+fn draw_gives_up_when_its_search_budget_runs_out() {
+    let draw = champions_league_draw();
+    let mut rng = StdRng::seed_from_u64(5);
+    assert_eq!(
+        draw.draw_with_budget(&mut rng, 3),
+        Err(DrawError::TooConstrained)
+    );
+    assert!(draw.draw_with_budget(&mut rng, 1_000).is_ok());
+}
+
+#[test]
+fn drawn_tournament_redraws_every_run() {
+    let tournament = DrawnTournament {
+        draw: champions_league_draw(),
+        home_and_away: true,
+        group_params: SimulationParams::default(),
+        best_thirds: 0,
+        bracket: GroupTournament::winners_and_runners_up(4),
+        params: SimulationParams::default(),
+        neutral: false,
+        two_legs: None,
+    };
+    assert!(tournament.check().is_ok());
+
+    let result = run_drawn_tournament_seeded(&tournament, 500, 8);
+    assert_eq!(result.teams.len(), 16);
+    assert_eq!(result.teams[0].team_name, "T1");
+    for (stage, expected) in [16.0, 8.0, 4.0, 2.0, 1.0].into_iter().enumerate() {
+        let total: f64 = result
+            .teams
+            .iter()
+            .map(|t| t.round_probabilities[stage])
+            .sum();
+        assert!((total - expected).abs() < 1e-9);
+    }
+    // Pot 1 sides go through far more often than pot 4 sides.
+    assert!(result.teams[0].round_probabilities[1] > result.teams[15].round_probabilities[1]);
+    assert_eq!(run_drawn_tournament_seeded(&tournament, 500, 8), result);
+}
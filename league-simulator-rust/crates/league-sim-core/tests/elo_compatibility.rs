@@ -1,4 +1,4 @@
-use league_simulator_rust::*;
+use league_sim_core::*;
 use serde_json;
 use std::fs;
 
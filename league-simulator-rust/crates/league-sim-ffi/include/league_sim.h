@@ -0,0 +1,103 @@
+#ifndef LEAGUE_SIM_H
+#define LEAGUE_SIM_H
+
+/* Generated by cbindgen from crates/league-sim-ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// ABI version implemented by this library; matches the symbol prefix.
+#define LSIM_ABI_VERSION 1
+
+// Result code of every fallible call.
+typedef enum LsimStatus {
+  LSIM_STATUS_OK = 0,
+  // A required pointer argument was null
+  LSIM_STATUS_NULL_POINTER = 1,
+  // An argument was out of range (team index, empty league, zero
+  // iterations, a non-finite number, only one side of a score set)
+  LSIM_STATUS_INVALID_ARGUMENT = 2,
+  // The engine panicked; the output buffers are unspecified. Only
+  // builds that unwind, such as the `release-ffi` profile, report it;
+  // under `panic = "abort"` the host process aborts instead
+  LSIM_STATUS_INTERNAL = 3,
+} LsimStatus;
+
+// Simulation parameters; start from `lsim_v1_default_params()`.
+typedef struct LsimParams {
+  uint64_t iterations;
+  double mod_factor;
+  double home_advantage;
+  double tore_slope;
+  double tore_intercept;
+} LsimParams;
+
+// Elo update for one played match.
+typedef struct LsimEloResult {
+  double new_elo_home;
+  double new_elo_away;
+  double win_probability_home;
+} LsimEloResult;
+
+// One fixture. Teams are 0-based indices into the Elo array; negative
+// goals on both sides mark an unplayed match.
+typedef struct LsimMatch {
+  uint32_t team_home;
+  uint32_t team_away;
+  int32_t goals_home;
+  int32_t goals_away;
+} LsimMatch;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// ABI version of the loaded library, to check against `LSIM_ABI_VERSION`
+// from the header at load time.
+uint32_t lsim_v1_abi_version(void);
+
+// Engine defaults (10,000 iterations, mod factor 20, home advantage 65).
+struct LsimParams lsim_v1_default_params(void);
+
+// Static, NUL-terminated description of a status code.
+const char *lsim_v1_status_message(enum LsimStatus status);
+
+// Elo update for one played match (same formula as the R engine's
+// SpielNichtSimulieren).
+//
+// # Safety
+// `out` must be null or point to writable memory for one `LsimEloResult`.
+enum LsimStatus lsim_v1_elo_update(double elo_home,
+                                   double elo_away,
+                                   int32_t goals_home,
+                                   int32_t goals_away,
+                                   double mod_factor,
+                                   double home_advantage,
+                                   struct LsimEloResult *out);
+
+// Monte Carlo position probabilities, deterministic for a given `seed`.
+//
+// Writes `n_teams * n_teams` values to `out_probabilities`, row-major with
+// one row per team in input order: `out[team * n_teams + position]` is the
+// probability that `team` finishes in `position` (0 = first).
+//
+// # Safety
+// `matches` must point to `n_matches` readable `LsimMatch` values, `elos`
+// to `n_teams` readable doubles, `params` to one `LsimParams`, and
+// `out_probabilities` to `n_teams * n_teams` writable doubles.
+enum LsimStatus lsim_v1_simulate(const struct LsimMatch *matches,
+                                 size_t n_matches,
+                                 const double *elos,
+                                 size_t n_teams,
+                                 const struct LsimParams *params,
+                                 uint64_t seed,
+                                 double *out_probabilities);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LEAGUE_SIM_H */
@@ -0,0 +1,252 @@
+// C ABI over league-sim-core.
+// Every exported symbol carries the `lsim_v1_` prefix and keeps its
+// signature for the lifetime of ABI version 1; incompatible changes get new
+// `lsim_v2_` symbols next to the old ones. Buffers are always allocated by
+// the caller, so no memory crosses the boundary in either direction and
+// bindings (Julia `ccall`, Erlang/Elixir NIFs, Python ctypes) need no free
+// functions. The header in include/league_sim.h is generated by cbindgen.
+
+use league_sim_core::{
+    calculate_elo_change, run_monte_carlo_simulation_seeded, EloParams, Match, Season,
+    SimulationParams,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// ABI version implemented by this library; matches the symbol prefix.
+pub const LSIM_ABI_VERSION: u32 = 1;
+
+/// Result code of every fallible call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LsimStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullPointer = 1,
+    /// An argument was out of range (team index, empty league, zero
+    /// iterations, a non-finite number, only one side of a score set)
+    InvalidArgument = 2,
+    /// The engine panicked; the output buffers are unspecified. Only
+    /// builds that unwind, such as the `release-ffi` profile, report it;
+    /// under `panic = "abort"` the host process aborts instead
+    Internal = 3,
+}
+
+/// One fixture. Teams are 0-based indices into the Elo array; negative
+/// goals on both sides mark an unplayed match.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LsimMatch {
+    pub team_home: u32,
+    pub team_away: u32,
+    pub goals_home: i32,
+    pub goals_away: i32,
+}
+
+/// Simulation parameters; start from `lsim_v1_default_params()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LsimParams {
+    pub iterations: u64,
+    pub mod_factor: f64,
+    pub home_advantage: f64,
+    pub tore_slope: f64,
+    pub tore_intercept: f64,
+}
+
+/// Elo update for one played match.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LsimEloResult {
+    pub new_elo_home: f64,
+    pub new_elo_away: f64,
+    pub win_probability_home: f64,
+}
+
+/// ABI version of the loaded library, to check against `LSIM_ABI_VERSION`
+/// from the header at load time.
+#[no_mangle]
+pub extern "C" fn lsim_v1_abi_version() -> u32 {
+    LSIM_ABI_VERSION
+}
+
+/// Engine defaults (10,000 iterations, mod factor 20, home advantage 65).
+#[no_mangle]
+pub extern "C" fn lsim_v1_default_params() -> LsimParams {
+    let defaults = SimulationParams::default();
+    LsimParams {
+        iterations: defaults.iterations as u64,
+        mod_factor: defaults.mod_factor,
+        home_advantage: defaults.home_advantage,
+        tore_slope: defaults.tore_slope,
+        tore_intercept: defaults.tore_intercept,
+    }
+}
+
+/// Static, NUL-terminated description of a status code.
+#[no_mangle]
+pub extern "C" fn lsim_v1_status_message(status: LsimStatus) -> *const std::ffi::c_char {
+    let message: &'static [u8] = match status {
+        LsimStatus::Ok => b"ok\0",
+        LsimStatus::NullPointer => b"null pointer argument\0",
+        LsimStatus::InvalidArgument => b"invalid argument\0",
+        LsimStatus::Internal => b"internal engine error\0",
+    };
+    message.as_ptr().cast()
+}
+
+/// Elo update for one played match (same formula as the R engine's
+/// SpielNichtSimulieren).
+///
+/// # Safety
+/// `out` must be null or point to writable memory for one `LsimEloResult`.
+#[no_mangle]
+pub unsafe extern "C" fn lsim_v1_elo_update(
+    elo_home: f64,
+    elo_away: f64,
+    goals_home: i32,
+    goals_away: i32,
+    mod_factor: f64,
+    home_advantage: f64,
+    out: *mut LsimEloResult,
+) -> LsimStatus {
+    if out.is_null() {
+        return LsimStatus::NullPointer;
+    }
+    if ![elo_home, elo_away, mod_factor, home_advantage]
+        .iter()
+        .all(|v| v.is_finite())
+    {
+        return LsimStatus::InvalidArgument;
+    }
+    let result = calculate_elo_change(&EloParams {
+        elo_home,
+        elo_away,
+        goals_home,
+        goals_away,
+        mod_factor,
+        home_advantage,
+    });
+    out.write(LsimEloResult {
+        new_elo_home: result.new_elo_home,
+        new_elo_away: result.new_elo_away,
+        win_probability_home: result.win_probability_home,
+    });
+    LsimStatus::Ok
+}
+
+/// Monte Carlo position probabilities, deterministic for a given `seed`.
+///
+/// Writes `n_teams * n_teams` values to `out_probabilities`, row-major with
+/// one row per team in input order: `out[team * n_teams + position]` is the
+/// probability that `team` finishes in `position` (0 = first).
+///
+/// # Safety
+/// `matches` must point to `n_matches` readable `LsimMatch` values, `elos`
+/// to `n_teams` readable doubles, `params` to one `LsimParams`, and
+/// `out_probabilities` to `n_teams * n_teams` writable doubles.
+#[no_mangle]
+pub unsafe extern "C" fn lsim_v1_simulate(
+    matches: *const LsimMatch,
+    n_matches: usize,
+    elos: *const f64,
+    n_teams: usize,
+    params: *const LsimParams,
+    seed: u64,
+    out_probabilities: *mut f64,
+) -> LsimStatus {
+    if (matches.is_null() && n_matches > 0)
+        || elos.is_null()
+        || params.is_null()
+        || out_probabilities.is_null()
+    {
+        return LsimStatus::NullPointer;
+    }
+    let fixtures = if n_matches == 0 {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(matches, n_matches)
+    };
+    // A square that wraps, or does not fit in memory, cannot describe the
+    // caller's buffer: refuse it before any slice is built.
+    let out_len = match n_teams
+        .checked_mul(n_teams)
+        .filter(|&len| len <= isize::MAX as usize / std::mem::size_of::<f64>())
+    {
+        Some(len) => len,
+        None => return LsimStatus::InvalidArgument,
+    };
+    let elos = std::slice::from_raw_parts(elos, n_teams);
+    let params = *params;
+    let out = std::slice::from_raw_parts_mut(out_probabilities, out_len);
+
+    let finite = [
+        params.mod_factor,
+        params.home_advantage,
+        params.tore_slope,
+        params.tore_intercept,
+    ]
+    .iter()
+    .chain(elos)
+    .all(|v| v.is_finite());
+    if n_teams == 0 || params.iterations == 0 || !finite {
+        return LsimStatus::InvalidArgument;
+    }
+    let season = match season_from_fixtures(fixtures, elos) {
+        Some(season) => season,
+        None => return LsimStatus::InvalidArgument,
+    };
+
+    let run = catch_unwind(AssertUnwindSafe(|| {
+        let sim_params = SimulationParams {
+            iterations: params.iterations as usize,
+            mod_factor: params.mod_factor,
+            home_advantage: params.home_advantage,
+            tore_slope: params.tore_slope,
+            tore_intercept: params.tore_intercept,
+            ..Default::default()
+        };
+        // Team names carry the input index so rows can be put back into
+        // input order after the engine sorts them by rank.
+        let names = (0..n_teams).map(|i| i.to_string()).collect();
+        run_monte_carlo_simulation_seeded(&season, &sim_params, names, seed)
+    }));
+    let result = match run {
+        Ok(result) => result,
+        Err(_) => return LsimStatus::Internal,
+    };
+
+    for (row, name) in result.probability_matrix.iter().zip(&result.team_names) {
+        let team: usize = name.parse().expect("index names round-trip");
+        out[team * n_teams..(team + 1) * n_teams].copy_from_slice(row);
+    }
+    LsimStatus::Ok
+}
+
+/// Convert C fixtures into a core `Season`; None if a team index is out of
+/// range or a row has a score on one side only.
+fn season_from_fixtures(fixtures: &[LsimMatch], elos: &[f64]) -> Option<Season> {
+    let n_teams = elos.len();
+    let matches = fixtures
+        .iter()
+        .map(|m| {
+            let (home, away) = (m.team_home as usize, m.team_away as usize);
+            let played = m.goals_home >= 0;
+            let valid = home < n_teams && away < n_teams && played == (m.goals_away >= 0);
+            valid.then_some(Match {
+                team_home: home,
+                team_away: away,
+                goals_home: played.then_some(m.goals_home),
+                goals_away: played.then_some(m.goals_away),
+                matchday: None,
+                competition: None,
+                weight: None,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Season {
+        matches,
+        team_elos: elos.to_vec(),
+        number_teams: n_teams,
+    })
+}
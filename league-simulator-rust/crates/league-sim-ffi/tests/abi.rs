@@ -0,0 +1,168 @@
+use league_sim_ffi::*;
+
+#[test]
+fn elo_update_matches_core_reference() {
+    let mut out = LsimEloResult {
+        new_elo_home: 0.0,
+        new_elo_away: 0.0,
+        win_probability_home: 0.0,
+    };
+    let status = unsafe { lsim_v1_elo_update(1500.0, 1500.0, 2, 1, 20.0, 65.0, &mut out) };
+
+    assert_eq!(status, LsimStatus::Ok);
+    // test_data/elo_test_cases.json: with_home_advantage
+    assert!((out.new_elo_home - 1508.1506753883).abs() < 1e-9);
+    assert!((out.new_elo_away - 1491.8493246117).abs() < 1e-9);
+    assert!((out.win_probability_home - 0.59246623058).abs() < 1e-9);
+
+    let status =
+        unsafe { lsim_v1_elo_update(1500.0, 1500.0, 2, 1, 20.0, 65.0, std::ptr::null_mut()) };
+    assert_eq!(status, LsimStatus::NullPointer);
+}
+
+#[test]
+fn simulate_writes_rows_in_input_order_and_is_seeded() {
+    let fixtures = [
+        LsimMatch {
+            team_home: 0,
+            team_away: 1,
+            goals_home: 3,
+            goals_away: 0,
+        },
+        LsimMatch {
+            team_home: 1,
+            team_away: 2,
+            goals_home: -1,
+            goals_away: -1,
+        },
+        LsimMatch {
+            team_home: 2,
+            team_away: 0,
+            goals_home: -1,
+            goals_away: -1,
+        },
+    ];
+    let elos = [1500.0, 1500.0, 1500.0];
+    let params = LsimParams {
+        iterations: 500,
+        ..lsim_v1_default_params()
+    };
+
+    let run = |seed: u64| {
+        let mut out = [0.0; 9];
+        let status = unsafe {
+            lsim_v1_simulate(
+                fixtures.as_ptr(),
+                fixtures.len(),
+                elos.as_ptr(),
+                elos.len(),
+                &params,
+                seed,
+                out.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, LsimStatus::Ok);
+        out
+    };
+
+    let out = run(7);
+    for row in out.chunks(3) {
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+    assert_eq!(out, run(7));
+    // Team 1 lost its played match 0:3, so it cannot top the table more
+    // often than the winner.
+    assert!(out[0] > out[3]);
+}
+
+#[test]
+fn simulate_rejects_bad_arguments() {
+    let fixtures = [LsimMatch {
+        team_home: 0,
+        team_away: 5,
+        goals_home: -1,
+        goals_away: -1,
+    }];
+    let elos = [1500.0, 1500.0];
+    let params = lsim_v1_default_params();
+    let mut out = [0.0; 4];
+
+    let status = unsafe {
+        lsim_v1_simulate(
+            fixtures.as_ptr(),
+            1,
+            elos.as_ptr(),
+            2,
+            &params,
+            1,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, LsimStatus::InvalidArgument);
+
+    let status = unsafe {
+        lsim_v1_simulate(
+            fixtures.as_ptr(),
+            1,
+            std::ptr::null(),
+            2,
+            &params,
+            1,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, LsimStatus::NullPointer);
+
+    // A score on one side only, or a non-finite rating, is rejected rather
+    // than simulated.
+    let half_played = [LsimMatch {
+        team_home: 0,
+        team_away: 1,
+        goals_home: 2,
+        goals_away: -1,
+    }];
+    let simulate = |fixtures: &[LsimMatch], elos: &[f64], out: &mut [f64]| unsafe {
+        lsim_v1_simulate(
+            fixtures.as_ptr(),
+            fixtures.len(),
+            elos.as_ptr(),
+            elos.len(),
+            &params,
+            1,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(
+        simulate(&half_played, &elos, &mut out),
+        LsimStatus::InvalidArgument
+    );
+    let unplayed = [LsimMatch {
+        goals_home: -1,
+        ..half_played[0]
+    }];
+    assert_eq!(
+        simulate(&unplayed, &[1500.0, f64::NAN], &mut out),
+        LsimStatus::InvalidArgument
+    );
+
+    // n_teams * n_teams overflows: refused before the buffers are touched.
+    let status = unsafe {
+        lsim_v1_simulate(
+            fixtures.as_ptr(),
+            0,
+            elos.as_ptr(),
+            1 << (usize::BITS / 2),
+            &params,
+            1,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, LsimStatus::InvalidArgument);
+}
+
+#[test]
+fn version_and_messages_are_stable() {
+    assert_eq!(lsim_v1_abi_version(), LSIM_ABI_VERSION);
+    let message = unsafe { std::ffi::CStr::from_ptr(lsim_v1_status_message(LsimStatus::Ok)) };
+    assert_eq!(message.to_str().unwrap(), "ok");
+}
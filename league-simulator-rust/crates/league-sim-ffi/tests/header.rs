@@ -0,0 +1,27 @@
+// include/league_sim.h is committed so FFI users don't need cbindgen.
+// This test regenerates it and fails if the checked-in copy is stale; run
+// with UPDATE_HEADER=1 to rewrite it after changing the ABI.
+
+use std::path::Path;
+
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::generate_with_config(crate_dir, config)
+        .expect("cbindgen parses the crate")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let path = crate_dir.join("include/league_sim.h");
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+        return;
+    }
+    let committed = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "include/league_sim.h is stale; rerun with UPDATE_HEADER=1"
+    );
+}
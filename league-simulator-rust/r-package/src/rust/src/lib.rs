@@ -0,0 +1,193 @@
+// R bindings to league-sim-core via extendr.
+// Function and argument names follow the retired Rcpp engine
+// (SpielNichtSimulieren.cpp, SaisonSimulierenCPP.R) so existing R code can
+// switch to the native package without renaming call sites.
+#![allow(non_snake_case)]
+
+use extendr_api::prelude::*;
+use league_sim_core::{
+    calculate_elo_change, run_monte_carlo_simulation_seeded, simulate_season, EloParams, Match,
+    Season, SimulationParams,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Elo update for one played match.
+///
+/// Same signature and result layout as the retired C++ primitive:
+/// `c(ELOHome, ELOAway, GoalsHome, GoalsAway, ELOProb)`.
+/// @export
+#[extendr]
+fn SpielNichtSimulieren(
+    ELOHome: f64,
+    ELOAway: f64,
+    GoalsHome: i32,
+    GoalsAway: i32,
+    modFactor: f64,
+    homeAdvantage: f64,
+) -> Vec<f64> {
+    let result = calculate_elo_change(&EloParams {
+        elo_home: ELOHome,
+        elo_away: ELOAway,
+        goals_home: GoalsHome,
+        goals_away: GoalsAway,
+        mod_factor: modFactor,
+        home_advantage: homeAdvantage,
+    });
+    vec![
+        result.new_elo_home,
+        result.new_elo_away,
+        result.goals_home as f64,
+        result.goals_away as f64,
+        result.win_probability_home,
+    ]
+}
+
+/// Simulate the unplayed matches of one season.
+///
+/// `schedule` is a numeric matrix with columns team_home, team_away,
+/// goals_home, goals_away (1-indexed teams, NA goals for unplayed matches).
+/// Returns `list(schedule, elo)` with all goals filled in and the final Elos.
+/// @export
+#[extendr]
+fn SaisonSimulieren(
+    schedule: RMatrix<f64>,
+    ELOValues: Vec<f64>,
+    modFactor: f64,
+    homeAdvantage: f64,
+    seed: f64,
+) -> Result<List> {
+    let season = season_from_matrix(&schedule, ELOValues)?;
+    let defaults = SimulationParams::default();
+    let mut rng = StdRng::seed_from_u64(seed_from(seed)?);
+
+    let (matches, elos) = simulate_season(
+        &season,
+        modFactor,
+        None,
+        homeAdvantage,
+        defaults.tore_slope,
+        defaults.tore_intercept,
+        &mut rng,
+    );
+
+    let played = RMatrix::new_matrix(matches.len(), 4, |r, c| {
+        let m = &matches[r];
+        match c {
+            0 => (m.team_home + 1) as f64,
+            1 => (m.team_away + 1) as f64,
+            2 => m.goals_home.map_or(f64::NAN, f64::from),
+            _ => m.goals_away.map_or(f64::NAN, f64::from),
+        }
+    });
+
+    Ok(list!(schedule = played, elo = elos))
+}
+
+/// Monte Carlo position probabilities.
+///
+/// Returns `list(probability_matrix, team_names)`, rows in final rank order.
+/// @export
+#[extendr]
+fn leagueSimulator(
+    schedule: RMatrix<f64>,
+    ELOValues: Vec<f64>,
+    teamNames: Vec<String>,
+    iterations: i32,
+    modFactor: f64,
+    homeAdvantage: f64,
+    seed: f64,
+) -> Result<List> {
+    if iterations < 1 {
+        return Err(Error::Other("iterations must be at least 1".to_string()));
+    }
+    let seed = seed_from(seed)?;
+    let season = season_from_matrix(&schedule, ELOValues)?;
+    let params = SimulationParams {
+        mod_factor: modFactor,
+        home_advantage: homeAdvantage,
+        iterations: iterations as usize,
+        ..Default::default()
+    };
+
+    let result = run_monte_carlo_simulation_seeded(&season, &params, teamNames, seed);
+    let n = result.probability_matrix.len();
+    let matrix = RMatrix::new_matrix(n, n, |r, c| result.probability_matrix[r][c]);
+
+    Ok(list!(
+        probability_matrix = matrix,
+        team_names = result.team_names
+    ))
+}
+
+/// An R seed as the engine's: a whole number from 0 to 2^53, the range a
+/// double holds exactly. Anything else would be truncated or saturated by
+/// a cast, silently running another stream.
+fn seed_from(seed: f64) -> Result<u64> {
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+    if seed.is_finite() && seed >= 0.0 && seed.fract() == 0.0 && seed <= MAX_EXACT {
+        Ok(seed as u64)
+    } else {
+        Err(Error::Other(format!(
+            "seed must be a whole number between 0 and 2^53, got {}",
+            seed
+        )))
+    }
+}
+
+/// Convert an R schedule matrix (1-indexed teams, NA for unplayed goals)
+/// into a core `Season`.
+fn season_from_matrix(schedule: &RMatrix<f64>, elos: Vec<f64>) -> Result<Season> {
+    if schedule.ncols() != 4 {
+        return Err(Error::Other(format!(
+            "schedule must have 4 columns, got {}",
+            schedule.ncols()
+        )));
+    }
+    let number_teams = elos.len();
+
+    let mut matches = Vec::with_capacity(schedule.nrows());
+    for r in 0..schedule.nrows() {
+        let team = |c: usize| {
+            let value = schedule[[r, c]];
+            if value.is_nan() || value < 1.0 || value as usize > number_teams {
+                Err(Error::Other(format!(
+                    "schedule row {}: team index must be between 1 and {}",
+                    r + 1,
+                    number_teams
+                )))
+            } else {
+                Ok(value as usize - 1)
+            }
+        };
+        let goals = |c: usize| {
+            let value = schedule[[r, c]];
+            (!value.is_nan()).then_some(value as i32)
+        };
+        matches.push(Match {
+            team_home: team(0)?,
+            team_away: team(1)?,
+            goals_home: goals(2),
+            goals_away: goals(3),
+            matchday: None,
+            competition: None,
+            weight: None,
+        });
+    }
+
+    Ok(Season {
+        matches,
+        team_elos: elos,
+        number_teams,
+    })
+}
+
+// Macro to generate exports.
+// This ensures exported functions are registered with R.
+// See corresponding C code in `entrypoint.c`.
+extendr_module! {
+    mod leaguesim;
+    fn SpielNichtSimulieren;
+    fn SaisonSimulieren;
+    fn leagueSimulator;
+}
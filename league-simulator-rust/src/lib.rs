@@ -1,10 +0,0 @@
-pub mod api;
-pub mod elo;
-pub mod models;
-pub mod monte_carlo;
-pub mod simulation;
-
-pub use elo::*;
-pub use models::*;
-pub use monte_carlo::*;
-pub use simulation::*;
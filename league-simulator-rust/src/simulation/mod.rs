@@ -1,8 +0,0 @@
-pub mod match_sim;
-pub mod season;
-
-pub use match_sim::*;
-pub use season::*;
-
-#[cfg(test)]
-mod tests;